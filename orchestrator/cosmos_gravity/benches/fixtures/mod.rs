@@ -0,0 +1,126 @@
+//! Synthetic Gravity contract logs for the oracle benchmarks. These are built the way a full node
+//! would return them from eth_getLogs, so decoding is measured from the same JSON the oracle sees.
+
+use clarity::utils::bytes_to_hex_str;
+use deep_space::private_key::{CosmosPrivateKey, PrivateKey};
+use deep_space::Address as CosmosAddress;
+use gravity_utils::types::event_signatures::{
+    SENT_TO_COSMOS_EVENT_SIG, TRANSACTION_BATCH_EXECUTED_EVENT_SIG, VALSET_UPDATED_EVENT_SIG,
+};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use web30::types::Log;
+
+const GRAVITY_ADDRESS: &str = "0x7580bFE88Dd3d07947908FAE12d95872a260F2D8";
+/// How many events share an Ethereum block in the fixtures
+const EVENTS_PER_BLOCK: u64 = 4;
+const START_BLOCK: u64 = 10_000;
+
+/// The address the benchmarked claims are signed by
+pub fn orchestrator_address() -> CosmosAddress {
+    CosmosPrivateKey::from_secret(&[1u8; 32])
+        .to_address("gravity")
+        .unwrap()
+}
+
+/// A deposit log for every nonce in `nonces`, all to a valid destination
+pub fn send_to_cosmos_logs(nonces: impl Iterator<Item = u64>) -> Vec<Log> {
+    let destination = orchestrator_address().to_string();
+    nonces
+        .map(|nonce| {
+            let mut data = word(0x60);
+            data.extend(word(nonce * 1_000_000));
+            data.extend(word(nonce));
+            data.extend(string_bytes(&destination));
+            log(
+                SENT_TO_COSMOS_EVENT_SIG,
+                vec![address_word(1), address_word(nonce)],
+                data,
+                nonce,
+            )
+        })
+        .collect()
+}
+
+/// A batch executed log for every nonce in `nonces`
+pub fn batch_executed_logs(nonces: impl Iterator<Item = u64>) -> Vec<Log> {
+    nonces
+        .map(|nonce| {
+            log(
+                TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+                vec![word(nonce), address_word(1)],
+                word(nonce),
+                nonce,
+            )
+        })
+        .collect()
+}
+
+/// A valset updated log with `members` validators for every nonce in `nonces`
+pub fn valset_updated_logs(nonces: impl Iterator<Item = u64>, members: u64) -> Vec<Log> {
+    nonces
+        .map(|nonce| {
+            let mut data = word(nonce);
+            data.extend(word(0));
+            data.extend(word(0));
+            data.extend(word(0xa0));
+            data.extend(word(0xc0 + members * 32));
+            data.extend(word(members));
+            for i in 0..members {
+                data.extend(address_word(i + 1));
+            }
+            data.extend(word(members));
+            for i in 0..members {
+                data.extend(word(u32::MAX as u64 / members - i));
+            }
+            log(VALSET_UPDATED_EVENT_SIG, vec![word(nonce)], data, nonce)
+        })
+        .collect()
+}
+
+fn log(signature: &str, mut topics: Vec<Vec<u8>>, data: Vec<u8>, nonce: u64) -> Log {
+    topics.insert(0, Keccak256::digest(signature.as_bytes()).to_vec());
+    let topics: Vec<Value> = topics.iter().map(|t| json!(hex(t))).collect();
+    let block = START_BLOCK + nonce / EVENTS_PER_BLOCK;
+    serde_json::from_value(json!({
+        "address": GRAVITY_ADDRESS,
+        "topics": topics,
+        "data": hex(&data),
+        "blockNumber": format!("{:#x}", block),
+        "blockHash": hex(&word(block)),
+        "transactionHash": hex(&word(nonce)),
+        "transactionIndex": "0x0",
+        "logIndex": format!("{:#x}", nonce % EVENTS_PER_BLOCK),
+        "removed": false
+    }))
+    .expect("Invalid log fixture")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes_to_hex_str(bytes))
+}
+
+/// A big endian abi word holding `value`
+fn word(value: u64) -> Vec<u8> {
+    let mut out = vec![0u8; 24];
+    out.extend_from_slice(&value.to_be_bytes());
+    out
+}
+
+/// An abi word holding a made up but valid Ethereum address derived from `seed`
+fn address_word(seed: u64) -> Vec<u8> {
+    let mut out = vec![0u8; 12];
+    out.extend_from_slice(&[0x11u8; 12]);
+    out.extend_from_slice(&seed.to_be_bytes());
+    out
+}
+
+/// The length word and right padded contents of an abi encoded string
+fn string_bytes(value: &str) -> Vec<u8> {
+    let mut out = word(value.len() as u64);
+    out.extend_from_slice(value.as_bytes());
+    while out.len() % 32 != 0 {
+        out.push(0);
+    }
+    out
+}
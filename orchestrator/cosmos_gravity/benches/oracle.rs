@@ -0,0 +1,117 @@
+//! Benchmarks for the hot path of the Ethereum oracle, decoding Gravity contract logs, turning the
+//! events into claims and ordering those claims by event nonce before they are submitted.
+//! Run with `cargo bench -p cosmos_gravity`, criterion will report regressions against the last run
+
+use cosmos_gravity::send::order_claim_msgs;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use gravity_utils::types::{
+    EthereumEvent, SendToCosmosEvent, TransactionBatchExecutedEvent, ValsetUpdatedEvent,
+};
+
+mod fixtures;
+
+use fixtures::{
+    batch_executed_logs, orchestrator_address, send_to_cosmos_logs, valset_updated_logs,
+};
+
+/// Roughly what the oracle sees when catching up on a busy bridge
+const EVENTS: u64 = 1000;
+/// The size of the Gravity Bridge validator set at the time of writing
+const VALSET_MEMBERS: u64 = 150;
+
+fn bench_event_decoding(c: &mut Criterion) {
+    let deposits = send_to_cosmos_logs(0..EVENTS);
+    let batches = batch_executed_logs(0..EVENTS);
+    let valsets = valset_updated_logs(0..EVENTS / 10, VALSET_MEMBERS);
+
+    let mut group = c.benchmark_group("event_decoding");
+    group.bench_function("send_to_cosmos", |b| {
+        b.iter(|| SendToCosmosEvent::from_logs(black_box(&deposits)).unwrap())
+    });
+    group.bench_function("transaction_batch_executed", |b| {
+        b.iter(|| TransactionBatchExecutedEvent::from_logs(black_box(&batches)).unwrap())
+    });
+    group.bench_function("valset_updated", |b| {
+        b.iter(|| ValsetUpdatedEvent::from_logs(black_box(&valsets)).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_claim_construction(c: &mut Criterion) {
+    let orchestrator = orchestrator_address();
+    let deposits = SendToCosmosEvent::from_logs(&send_to_cosmos_logs(0..EVENTS)).unwrap();
+    let valsets =
+        ValsetUpdatedEvent::from_logs(&valset_updated_logs(0..EVENTS / 10, VALSET_MEMBERS))
+            .unwrap();
+
+    let mut group = c.benchmark_group("claim_construction");
+    group.bench_function("send_to_cosmos", |b| {
+        b.iter_batched(
+            || deposits.clone(),
+            |events| {
+                events
+                    .into_iter()
+                    .map(|e| e.to_claim_msg(orchestrator))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("valset_updated", |b| {
+        b.iter_batched(
+            || valsets.clone(),
+            |events| {
+                events
+                    .into_iter()
+                    .map(|e| e.to_claim_msg(orchestrator))
+                    .collect::<Vec<_>>()
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_claim_ordering(c: &mut Criterion) {
+    let orchestrator = orchestrator_address();
+    // interleave the event types across the nonce range, the way they come out of the contract,
+    // and make sure there are more than fit into a single oracle Tx so truncation is measured too
+    let total = EVENTS * 2;
+    let deposits =
+        SendToCosmosEvent::from_logs(&send_to_cosmos_logs((0..total).filter(|n| n % 3 == 0)))
+            .unwrap();
+    let batches = TransactionBatchExecutedEvent::from_logs(&batch_executed_logs(
+        (0..total).filter(|n| n % 3 == 1),
+    ))
+    .unwrap();
+    let valsets = ValsetUpdatedEvent::from_logs(&valset_updated_logs(
+        (0..total).filter(|n| n % 3 == 2),
+        VALSET_MEMBERS,
+    ))
+    .unwrap();
+
+    c.bench_function("order_claim_msgs", |b| {
+        b.iter_batched(
+            || (deposits.clone(), batches.clone(), valsets.clone()),
+            |(deposits, batches, valsets)| {
+                order_claim_msgs(
+                    orchestrator,
+                    deposits,
+                    batches,
+                    Vec::new(),
+                    Vec::new(),
+                    valsets,
+                )
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_event_decoding,
+    bench_claim_construction,
+    bench_claim_ordering
+);
+criterion_main!(benches);
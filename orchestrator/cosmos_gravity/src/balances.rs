@@ -0,0 +1,272 @@
+//! Bulk balance queries for wallets and portfolio frontends built on the bridge. Given a set of
+//! Cosmos and Ethereum addresses and the tokens of interest this returns Cosmos bank balances,
+//! Ethereum ETH and ERC20 balances and amounts still on their way to Ethereum in one call. gRPC
+//! queries are made concurrently and Ethereum balances are batched through Multicall3
+
+use crate::query::get_pending_send_to_eth;
+use crate::query_cache::get_erc20_to_denom_cached;
+use clarity::abi::encode_call;
+use clarity::constants::zero_address;
+use clarity::{Address as EthAddress, Uint256};
+use deep_space::{Address as CosmosAddress, Coin, Contact};
+use ethereum_gravity::multicall::{
+    encode_get_eth_balance, multicall3_address, multicall_aggregate,
+};
+use futures::future::{join, join_all};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::OutgoingTransferTx;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{BatchTransaction, Erc20Token};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// The most calls sent to Multicall3 in a single eth_call, larger requests are split up to stay
+/// under node gas limits for eth_call
+pub const MULTICALL_BATCH_SIZE: usize = 200;
+
+/// The addresses and tokens to query, if neither `erc20s` nor `denoms` is given every Cosmos
+/// balance and pending transfer is returned but only ETH balances on Ethereum
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BulkBalanceRequest {
+    #[serde(default)]
+    pub cosmos_addresses: Vec<CosmosAddress>,
+    #[serde(default)]
+    pub ethereum_addresses: Vec<EthAddress>,
+    /// ERC20 contracts to report, on Cosmos these are their bridged denoms
+    #[serde(default)]
+    pub erc20s: Vec<EthAddress>,
+    /// Additional Cosmos denoms to report that have no ERC20 representation
+    #[serde(default)]
+    pub denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BulkBalances {
+    pub cosmos: Vec<CosmosBalances>,
+    pub ethereum: Vec<EthereumBalances>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CosmosBalances {
+    pub address: CosmosAddress,
+    pub balances: Vec<Coin>,
+    /// The amount plus fee of transfers to Ethereum waiting to be batched, per token
+    pub pending_unbatched: Vec<Erc20Token>,
+    /// The amount plus fee of transfers to Ethereum in batches not yet relayed, per token
+    pub pending_in_batches: Vec<Erc20Token>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EthereumBalances {
+    pub address: EthAddress,
+    pub eth: Uint256,
+    pub erc20s: Vec<Erc20Token>,
+}
+
+/// Queries every balance described by `request`, Cosmos and Ethereum concurrently
+pub async fn get_bulk_balances(
+    contact: &Contact,
+    grpc_client: &GravityQueryClient<Channel>,
+    web3: &Web3,
+    request: &BulkBalanceRequest,
+) -> Result<BulkBalances, GravityError> {
+    let (cosmos, ethereum) = join(
+        get_cosmos_balances(contact, grpc_client, request),
+        get_ethereum_balances(web3, &request.ethereum_addresses, &request.erc20s),
+    )
+    .await;
+    Ok(BulkBalances {
+        cosmos: cosmos?,
+        ethereum: ethereum?,
+    })
+}
+
+/// Queries bank balances and pending transfers to Ethereum of every Cosmos address in `request`
+pub async fn get_cosmos_balances(
+    contact: &Contact,
+    grpc_client: &GravityQueryClient<Channel>,
+    request: &BulkBalanceRequest,
+) -> Result<Vec<CosmosBalances>, GravityError> {
+    let denoms = get_denoms(grpc_client, request).await?;
+    let erc20s: HashSet<EthAddress> = request.erc20s.iter().cloned().collect();
+
+    let queries = request.cosmos_addresses.iter().map(|address| {
+        let mut grpc_client = grpc_client.clone();
+        let denoms = &denoms;
+        let erc20s = &erc20s;
+        async move {
+            let (balances, pending) = join(
+                contact.get_balances(*address),
+                get_pending_send_to_eth(&mut grpc_client, *address),
+            )
+            .await;
+            let balances = balances?
+                .into_iter()
+                .filter(|c| {
+                    denoms
+                        .as_ref()
+                        .map(|d| d.contains(&c.denom))
+                        .unwrap_or(true)
+                })
+                .collect();
+            let pending = pending?;
+            Ok(CosmosBalances {
+                address: *address,
+                balances,
+                pending_unbatched: sum_transfers(pending.unbatched_transfers, erc20s)?,
+                pending_in_batches: sum_transfers(pending.transfers_in_batches, erc20s)?,
+            })
+        }
+    });
+    join_all(queries).await.into_iter().collect()
+}
+
+/// The Cosmos denoms to report, None if every denom should be
+async fn get_denoms(
+    grpc_client: &GravityQueryClient<Channel>,
+    request: &BulkBalanceRequest,
+) -> Result<Option<HashSet<String>>, GravityError> {
+    if request.erc20s.is_empty() && request.denoms.is_empty() {
+        return Ok(None);
+    }
+    let queries = request.erc20s.iter().map(|erc20| {
+        let mut grpc_client = grpc_client.clone();
+        async move { get_erc20_to_denom_cached(&mut grpc_client, *erc20).await }
+    });
+    let mut denoms: HashSet<String> = request.denoms.iter().cloned().collect();
+    for res in join_all(queries).await {
+        denoms.insert(res?.denom);
+    }
+    Ok(Some(denoms))
+}
+
+/// Totals the amount and fee of pending transfers by token, limited to `erc20s` unless it is empty
+fn sum_transfers(
+    transfers: Vec<OutgoingTransferTx>,
+    erc20s: &HashSet<EthAddress>,
+) -> Result<Vec<Erc20Token>, GravityError> {
+    let mut totals: BTreeMap<EthAddress, Uint256> = BTreeMap::new();
+    for transfer in transfers {
+        let transfer = BatchTransaction::try_from(transfer)?;
+        let token = transfer.erc20_token.token_contract_address;
+        if !erc20s.is_empty() && !erc20s.contains(&token) {
+            continue;
+        }
+        *totals.entry(token).or_insert_with(|| 0u8.into()) +=
+            transfer.erc20_token.amount + transfer.erc20_fee.amount;
+    }
+    Ok(totals
+        .into_iter()
+        .map(|(token_contract_address, amount)| Erc20Token {
+            amount,
+            token_contract_address,
+        })
+        .collect())
+}
+
+/// A single balance to query, the ETH balance of `holder` if `erc20` is None
+#[derive(Debug, Clone, Copy)]
+struct BalanceCall {
+    holder: EthAddress,
+    erc20: Option<EthAddress>,
+}
+
+impl BalanceCall {
+    fn to_call(self) -> Result<(EthAddress, Vec<u8>), GravityError> {
+        Ok(match self.erc20 {
+            Some(erc20) => (
+                erc20,
+                encode_call("balanceOf(address)", &[self.holder.into()])?,
+            ),
+            None => (multicall3_address(), encode_get_eth_balance(self.holder)),
+        })
+    }
+}
+
+/// Queries the ETH balance and the balance of each of `erc20s` for every holder
+pub async fn get_ethereum_balances(
+    web3: &Web3,
+    holders: &[EthAddress],
+    erc20s: &[EthAddress],
+) -> Result<Vec<EthereumBalances>, GravityError> {
+    let mut calls = Vec::new();
+    for holder in holders {
+        calls.push(BalanceCall {
+            holder: *holder,
+            erc20: None,
+        });
+        for erc20 in erc20s {
+            calls.push(BalanceCall {
+                holder: *holder,
+                erc20: Some(*erc20),
+            });
+        }
+    }
+
+    let mut results = Vec::new();
+    for chunk in calls.chunks(MULTICALL_BATCH_SIZE) {
+        results.extend(query_balances(web3, chunk).await?);
+    }
+
+    // results are in the same order the calls were built in
+    let mut results = results.into_iter();
+    let mut out = Vec::new();
+    for holder in holders {
+        let eth = results.next().unwrap();
+        let mut balances = Vec::new();
+        for erc20 in erc20s {
+            balances.push(Erc20Token {
+                amount: results.next().unwrap(),
+                token_contract_address: *erc20,
+            });
+        }
+        out.push(EthereumBalances {
+            address: *holder,
+            eth,
+            erc20s: balances,
+        });
+    }
+    Ok(out)
+}
+
+/// Queries a chunk of balances through Multicall3, falling back to individual calls if the
+/// aggregate fails, for example because Multicall3 isn't deployed on this chain
+async fn query_balances(web3: &Web3, calls: &[BalanceCall]) -> Result<Vec<Uint256>, GravityError> {
+    let mut encoded = Vec::new();
+    for call in calls {
+        encoded.push(call.to_call()?);
+    }
+    match multicall_aggregate(&encoded, multicall3_address(), zero_address(), web3).await {
+        Ok(results) => {
+            let mut out = Vec::new();
+            for (res, (target, _)) in results.iter().zip(encoded.iter()) {
+                if res.len() < 32 {
+                    return Err(GravityError::EthereumContractError(format!(
+                        "Invalid balance response from {}",
+                        target
+                    )));
+                }
+                out.push(Uint256::from_be_bytes(&res[0..32]));
+            }
+            Ok(out)
+        }
+        Err(e) => {
+            warn!(
+                "Multicall failed, querying {} balances individually {:?}",
+                calls.len(),
+                e
+            );
+            let queries = calls.iter().map(|call| async move {
+                Ok::<Uint256, GravityError>(match call.erc20 {
+                    Some(erc20) => web3.get_erc20_balance(erc20, call.holder).await?,
+                    None => web3.eth_get_balance(call.holder).await?,
+                })
+            });
+            join_all(queries).await.into_iter().collect()
+        }
+    }
+}
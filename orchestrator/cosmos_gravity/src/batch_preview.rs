@@ -0,0 +1,214 @@
+//! Previews the batch the chain would build if a batch were requested right now for a token. The
+//! Gravity module takes the unbatched transactions for the token in descending fee order (ties go
+//! to the newest transaction), skips destinations on the Ethereum blacklist and stops at
+//! OutgoingTxBatchSize. It refuses to build the batch if the bridge is paused, there is nothing to
+//! batch or the new batch would not pay more in fees than the newest unexecuted batch for the same
+//! token. This lets relayers judge a batch's profitability before paying to request it
+
+use crate::query::{get_gravity_params, get_latest_transaction_batches};
+use clarity::{Address as EthAddress, Uint256};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::QueryPendingSendToEth;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::BatchTransaction;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fmt;
+use tonic::transport::Channel;
+
+/// The most transactions the Gravity module places in a requested batch, OutgoingTxBatchSize
+pub const OUTGOING_TX_BATCH_SIZE: usize = 100;
+
+/// Why the chain would refuse to build the batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchPreviewRejection {
+    BridgePaused,
+    NoTransactions,
+    /// The newest unexecuted batch for this token pays at least as much in fees
+    NotMoreProfitable {
+        last_batch_fees: Uint256,
+    },
+}
+
+impl fmt::Display for BatchPreviewRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchPreviewRejection::BridgePaused => write!(f, "the bridge is paused"),
+            BatchPreviewRejection::NoTransactions => {
+                write!(f, "there are no transactions of this token to batch")
+            }
+            BatchPreviewRejection::NotMoreProfitable { last_batch_fees } => write!(
+                f,
+                "the new batch would not pay more than the {} in fees of the last batch",
+                last_batch_fees
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchPreview {
+    pub token_contract: EthAddress,
+    /// The transactions the batch would contain, in the order the chain selects them
+    pub transactions: Vec<BatchTransaction>,
+    pub total_fees: Uint256,
+    /// Unbatched transactions to blacklisted destinations, these are never batched
+    pub blacklisted: Vec<BatchTransaction>,
+    /// Unbatched transactions left for a later batch because this one is full
+    pub left_over: usize,
+    /// The fees of the newest unexecuted batch for this token, if there is one
+    pub last_batch_fees: Option<Uint256>,
+    /// Set if requesting a batch right now would fail
+    pub rejection: Option<BatchPreviewRejection>,
+}
+
+/// Previews the batch a MsgRequestBatch for `token_contract` would create right now
+pub async fn get_batch_preview(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    token_contract: EthAddress,
+) -> Result<BatchPreview, GravityError> {
+    let params = get_gravity_params(grpc_client).await?;
+    let mut blacklist = HashSet::new();
+    for address in params.ethereum_blacklist.iter() {
+        if let Ok(a) = address.parse() {
+            blacklist.insert(a);
+        }
+    }
+
+    // an empty sender returns every pending transfer
+    let pending = grpc_client
+        .get_pending_send_to_eth(QueryPendingSendToEth {
+            sender_address: String::new(),
+        })
+        .await?
+        .into_inner();
+    let mut unbatched = Vec::new();
+    for tx in pending.unbatched_transfers {
+        unbatched.push(BatchTransaction::try_from(tx)?);
+    }
+
+    let last_batch_fees = get_latest_transaction_batches(grpc_client)
+        .await?
+        .into_iter()
+        .filter(|b| b.token_contract == token_contract)
+        .max_by_key(|b| b.nonce)
+        .map(|b| b.total_fee.amount);
+
+    let mut preview = preview_batch(
+        unbatched,
+        token_contract,
+        &blacklist,
+        OUTGOING_TX_BATCH_SIZE,
+    );
+    preview.last_batch_fees = last_batch_fees;
+    preview.rejection = if !params.bridge_active {
+        Some(BatchPreviewRejection::BridgePaused)
+    } else {
+        match last_batch_fees {
+            Some(last_batch_fees) if last_batch_fees >= preview.total_fees => {
+                Some(BatchPreviewRejection::NotMoreProfitable { last_batch_fees })
+            }
+            _ if preview.transactions.is_empty() => Some(BatchPreviewRejection::NoTransactions),
+            _ => None,
+        }
+    };
+    Ok(preview)
+}
+
+/// Selects the transactions for a batch of `token_contract` from `unbatched` the same way the
+/// Gravity module does, the chain state dependent checks are left to the caller
+pub fn preview_batch(
+    unbatched: Vec<BatchTransaction>,
+    token_contract: EthAddress,
+    blacklist: &HashSet<EthAddress>,
+    max_elements: usize,
+) -> BatchPreview {
+    let mut candidates: Vec<BatchTransaction> = unbatched
+        .into_iter()
+        .filter(|tx| tx.erc20_fee.token_contract_address == token_contract)
+        .collect();
+    // the pool is keyed by fee amount then id and iterated in reverse
+    candidates.sort_by(|a, b| {
+        b.erc20_fee
+            .amount
+            .cmp(&a.erc20_fee.amount)
+            .then(b.id.cmp(&a.id))
+    });
+
+    let mut transactions = Vec::new();
+    let mut blacklisted = Vec::new();
+    let mut left_over = 0;
+    let mut total_fees: Uint256 = 0u8.into();
+    for tx in candidates {
+        if blacklist.contains(&tx.destination) {
+            blacklisted.push(tx);
+        } else if transactions.len() < max_elements {
+            total_fees += tx.erc20_fee.amount;
+            transactions.push(tx);
+        } else {
+            left_over += 1;
+        }
+    }
+    BatchPreview {
+        token_contract,
+        transactions,
+        total_fees,
+        blacklisted,
+        left_over,
+        last_batch_fees: None,
+        rejection: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity_utils::types::Erc20Token;
+
+    fn address(i: u8) -> EthAddress {
+        EthAddress::from_slice(&[i; 20]).unwrap()
+    }
+
+    fn tx(id: u64, token: EthAddress, fee: u32, destination: EthAddress) -> BatchTransaction {
+        BatchTransaction {
+            id,
+            sender: "gravity1g0etv93428tvxqftnmj25jn06mz6dtda5zxt8k"
+                .parse()
+                .unwrap(),
+            destination,
+            erc20_token: Erc20Token {
+                amount: 1000u32.into(),
+                token_contract_address: token,
+            },
+            erc20_fee: Erc20Token {
+                amount: fee.into(),
+                token_contract_address: token,
+            },
+        }
+    }
+
+    #[test]
+    fn test_preview_batch() {
+        let token = address(1);
+        let other = address(2);
+        let blocked = address(3);
+        let dest = address(4);
+        let unbatched = vec![
+            tx(1, token, 10, dest),
+            tx(2, token, 30, dest),
+            tx(3, other, 100, dest),
+            tx(4, token, 30, dest),
+            tx(5, token, 50, blocked),
+            tx(6, token, 20, dest),
+        ];
+        let blacklist: HashSet<EthAddress> = vec![blocked].into_iter().collect();
+        let preview = preview_batch(unbatched, token, &blacklist, 3);
+        let ids: Vec<u64> = preview.transactions.iter().map(|t| t.id).collect();
+        // equal fees are taken newest first
+        assert_eq!(ids, vec![4, 2, 6]);
+        assert_eq!(preview.total_fees, 80u32.into());
+        assert_eq!(preview.blacklisted.len(), 1);
+        assert_eq!(preview.blacklisted[0].id, 5);
+        assert_eq!(preview.left_over, 1);
+    }
+}
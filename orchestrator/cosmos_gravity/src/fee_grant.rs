@@ -0,0 +1,199 @@
+//! Fee grants allow the orchestrator to pay transaction fees out of an allowance granted by another
+//! account, usually the validator operator, so that the orchestrator key never needs to hold funds.
+//! Once a granter is set with `set_fee_granter` every transaction sent by the orchestrator names it
+//! as the fee granter
+
+use crate::query::shared_channel;
+use deep_space::address::Address as CosmosAddress;
+use deep_space::coin::Coin;
+use deep_space::error::CosmosGrpcError;
+use deep_space::Contact;
+use gravity_proto::cosmos_sdk_proto::cosmos::feegrant::v1beta1::query_client::QueryClient as FeegrantQueryClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::feegrant::v1beta1::{
+    AllowedMsgAllowance, BasicAllowance, Grant, PeriodicAllowance, QueryAllowanceRequest,
+    QueryAllowancesRequest,
+};
+use prost::Message;
+use prost_types::{Any, Timestamp};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const BASIC_ALLOWANCE_TYPE_URL: &str = "/cosmos.feegrant.v1beta1.BasicAllowance";
+pub const PERIODIC_ALLOWANCE_TYPE_URL: &str = "/cosmos.feegrant.v1beta1.PeriodicAllowance";
+pub const ALLOWED_MSG_ALLOWANCE_TYPE_URL: &str = "/cosmos.feegrant.v1beta1.AllowedMsgAllowance";
+
+static FEE_GRANTER: RwLock<Option<CosmosAddress>> = RwLock::new(None);
+
+/// Sets the account whose fee grant pays for orchestrator transactions, None pays fees directly
+pub fn set_fee_granter(granter: Option<CosmosAddress>) {
+    *FEE_GRANTER.write().unwrap() = granter;
+}
+
+/// The account paying for orchestrator transactions, if fees are paid out of a fee grant
+pub fn get_fee_granter() -> Option<CosmosAddress> {
+    *FEE_GRANTER.read().unwrap()
+}
+
+/// What remains of a fee allowance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemainingAllowance {
+    /// The coins that can still be spent, None if the allowance has no spend limit. For periodic
+    /// allowances this is what is left in the current period
+    pub spend_limit: Option<Vec<Coin>>,
+    /// When the allowance expires, if ever
+    pub expiration: Option<SystemTime>,
+    /// When a periodic allowance next resets its spend limit
+    pub period_reset: Option<SystemTime>,
+    /// The message types the allowance may pay for, empty if it may pay for any message
+    pub allowed_messages: Vec<String>,
+}
+
+impl RemainingAllowance {
+    /// The amount of `denom` that can still be spent, None if there is no limit
+    pub fn remaining(&self, denom: &str) -> Option<Coin> {
+        self.spend_limit.as_ref().map(|limit| {
+            limit
+                .iter()
+                .find(|c| c.denom == denom)
+                .cloned()
+                .unwrap_or(Coin {
+                    amount: 0u8.into(),
+                    denom: denom.to_string(),
+                })
+        })
+    }
+
+    /// Whether the allowance may pay for a message of this type
+    pub fn allows(&self, type_url: &str) -> bool {
+        self.allowed_messages.is_empty() || self.allowed_messages.iter().any(|m| m == type_url)
+    }
+}
+
+/// Gets the fee allowance granted to `grantee` by `granter`, None if there is no such grant
+pub async fn get_fee_allowance(
+    contact: &Contact,
+    granter: CosmosAddress,
+    grantee: CosmosAddress,
+) -> Result<Option<Grant>, CosmosGrpcError> {
+    let mut feegrant_qc = FeegrantQueryClient::new(shared_channel(&contact.get_url())?);
+    let res = feegrant_qc
+        .allowance(QueryAllowanceRequest {
+            granter: granter.to_string(),
+            grantee: grantee.to_string(),
+        })
+        .await;
+    match res {
+        Ok(v) => Ok(v.into_inner().allowance),
+        Err(status)
+            if status.code() == tonic::Code::NotFound
+                || status.message().contains("fee-grant not found") =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Gets every fee allowance granted to `grantee`
+pub async fn get_fee_allowances(
+    contact: &Contact,
+    grantee: CosmosAddress,
+) -> Result<Vec<Grant>, CosmosGrpcError> {
+    let mut feegrant_qc = FeegrantQueryClient::new(shared_channel(&contact.get_url())?);
+    let res = feegrant_qc
+        .allowances(QueryAllowancesRequest {
+            grantee: grantee.to_string(),
+            pagination: None,
+        })
+        .await?
+        .into_inner();
+    Ok(res.allowances)
+}
+
+/// Decodes the allowance of a grant into what remains of it
+pub fn decode_allowance(grant: &Grant) -> Result<RemainingAllowance, CosmosGrpcError> {
+    match &grant.allowance {
+        Some(any) => decode_allowance_any(any),
+        None => Err(CosmosGrpcError::BadResponse(
+            "Fee grant has no allowance".to_string(),
+        )),
+    }
+}
+
+fn decode_allowance_any(any: &Any) -> Result<RemainingAllowance, CosmosGrpcError> {
+    let bad = |e: prost::DecodeError| CosmosGrpcError::BadResponse(e.to_string());
+    match any.type_url.as_str() {
+        BASIC_ALLOWANCE_TYPE_URL => {
+            let basic = BasicAllowance::decode(any.value.as_slice()).map_err(bad)?;
+            decode_basic(basic)
+        }
+        PERIODIC_ALLOWANCE_TYPE_URL => {
+            let periodic = PeriodicAllowance::decode(any.value.as_slice()).map_err(bad)?;
+            let mut remaining = decode_basic(periodic.basic.unwrap_or_default())?;
+            // the period limit only applies alongside the overall limit, whichever is lower binds
+            let period = to_coins(&periodic.period_can_spend)?;
+            remaining.spend_limit = Some(match remaining.spend_limit {
+                Some(overall) => period
+                    .into_iter()
+                    .map(|p| match overall.iter().find(|o| o.denom == p.denom) {
+                        Some(o) if o.amount < p.amount => o.clone(),
+                        _ => p,
+                    })
+                    .collect(),
+                None => period,
+            });
+            remaining.period_reset = periodic.period_reset.as_ref().map(to_system_time);
+            Ok(remaining)
+        }
+        ALLOWED_MSG_ALLOWANCE_TYPE_URL => {
+            let allowed = AllowedMsgAllowance::decode(any.value.as_slice()).map_err(bad)?;
+            let mut remaining = match &allowed.allowance {
+                Some(inner) => decode_allowance_any(inner)?,
+                None => {
+                    return Err(CosmosGrpcError::BadResponse(
+                        "AllowedMsgAllowance has no allowance".to_string(),
+                    ))
+                }
+            };
+            remaining.allowed_messages = allowed.allowed_messages;
+            Ok(remaining)
+        }
+        other => Err(CosmosGrpcError::BadResponse(format!(
+            "Unknown fee allowance type {}",
+            other
+        ))),
+    }
+}
+
+fn decode_basic(basic: BasicAllowance) -> Result<RemainingAllowance, CosmosGrpcError> {
+    Ok(RemainingAllowance {
+        // an empty spend limit means no limit at all
+        spend_limit: if basic.spend_limit.is_empty() {
+            None
+        } else {
+            Some(to_coins(&basic.spend_limit)?)
+        },
+        expiration: basic.expiration.as_ref().map(to_system_time),
+        period_reset: None,
+        allowed_messages: Vec::new(),
+    })
+}
+
+fn to_coins(
+    coins: &[gravity_proto::cosmos_sdk_proto::cosmos::base::v1beta1::Coin],
+) -> Result<Vec<Coin>, CosmosGrpcError> {
+    let mut out = Vec::new();
+    for c in coins {
+        out.push(Coin {
+            amount: c.amount.parse().map_err(|_| {
+                CosmosGrpcError::BadResponse(format!("Invalid coin amount {}", c.amount))
+            })?,
+            denom: c.denom.clone(),
+        });
+    }
+    Ok(out)
+}
+
+fn to_system_time(t: &Timestamp) -> SystemTime {
+    UNIX_EPOCH + Duration::new(t.seconds.max(0) as u64, t.nanos.max(0) as u32)
+}
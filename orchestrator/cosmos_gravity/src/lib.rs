@@ -6,7 +6,16 @@
 #[macro_use]
 extern crate log;
 
+pub mod balances;
+pub mod batch_preview;
+pub mod fee_grant;
+pub mod module_version;
+pub mod pending_work;
 pub mod proposals;
 pub mod query;
+pub mod query_cache;
+pub mod redundancy;
+pub mod round_trip;
+pub mod safe_mode;
 pub mod send;
 pub mod utils;
@@ -0,0 +1,33 @@
+//! The Gravity module consensus versions this orchestrator can talk to. The consensus version is
+//! bumped by every chain upgrade that migrates the module's state, which is when message formats
+//! and validation change. Running against a version outside this range risks submitting messages
+//! the chain rejects, so the orchestrator checks it at startup
+
+use crate::query::get_module_version;
+use deep_space::Contact;
+use std::ops::RangeInclusive;
+
+/// The name of the Gravity module in the chain's module version map
+pub const GRAVITY_MODULE_NAME: &str = "gravity";
+/// The Gravity module consensus versions this orchestrator's messages are built for
+pub const SUPPORTED_GRAVITY_MODULE_VERSIONS: RangeInclusive<u64> = 5..=5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleVersionCheck {
+    Supported(u64),
+    Unsupported(u64),
+    /// The node could not report the version, such as a node too old to serve module versions
+    Unknown(String),
+}
+
+/// Checks the chain's Gravity module version against SUPPORTED_GRAVITY_MODULE_VERSIONS
+pub async fn check_gravity_module_version(contact: &Contact) -> ModuleVersionCheck {
+    match get_module_version(contact, GRAVITY_MODULE_NAME).await {
+        Ok(Some(v)) if SUPPORTED_GRAVITY_MODULE_VERSIONS.contains(&v) => {
+            ModuleVersionCheck::Supported(v)
+        }
+        Ok(Some(v)) => ModuleVersionCheck::Unsupported(v),
+        Ok(None) => ModuleVersionCheck::Unknown("the chain has no gravity module".to_string()),
+        Err(e) => ModuleVersionCheck::Unknown(format!("{:?}", e)),
+    }
+}
@@ -0,0 +1,114 @@
+//! Finds everything on the chain awaiting a given orchestrator's signature. The LastPending*ByAddr
+//! queries only return the oldest unsigned batch and logic call, which is all the signer needs to
+//! make progress, so batches and logic calls are instead checked against their confirms directly.
+//! None of these queries are paginated by the chain, pages are taken from the full results here
+
+use crate::query::{
+    get_latest_logic_calls, get_latest_transaction_batches, get_logic_call_signatures,
+    get_oldest_unsigned_valsets, get_transaction_batch_signatures,
+};
+use deep_space::Address as CosmosAddress;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{LogicCall, TransactionBatch, Valset};
+use tonic::transport::Channel;
+
+/// Selects part of each list of pending work, every kind of work is paged separately
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pagination {
+    /// How many items of each kind to skip
+    pub offset: usize,
+    /// The most items of each kind to return, None for all of them
+    pub limit: Option<usize>,
+}
+
+impl Pagination {
+    pub fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        let items = items.into_iter().skip(self.offset);
+        match self.limit {
+            Some(limit) => items.take(limit).collect(),
+            None => items.collect(),
+        }
+    }
+}
+
+/// The work awaiting an orchestrator's signature, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct PendingWork {
+    pub valsets: Vec<Valset>,
+    pub batches: Vec<TransactionBatch>,
+    pub logic_calls: Vec<LogicCall>,
+}
+
+impl PendingWork {
+    pub fn is_empty(&self) -> bool {
+        self.valsets.is_empty() && self.batches.is_empty() && self.logic_calls.is_empty()
+    }
+}
+
+/// Gets the validator sets, batches and logic calls `orchestrator` has not yet signed
+pub async fn get_pending_work(
+    client: &mut GravityQueryClient<Channel>,
+    orchestrator: CosmosAddress,
+    prefix: String,
+    page: Pagination,
+) -> Result<PendingWork, GravityError> {
+    let mut valsets = get_oldest_unsigned_valsets(client, orchestrator, prefix).await?;
+    valsets.sort_by_key(|v| v.nonce);
+
+    let mut batches = Vec::new();
+    for batch in get_latest_transaction_batches(client).await? {
+        let confirms =
+            get_transaction_batch_signatures(client, batch.nonce, batch.token_contract).await?;
+        if !confirms.iter().any(|c| c.orchestrator == orchestrator) {
+            batches.push(batch);
+        }
+    }
+    batches.sort_by_key(|b| b.nonce);
+
+    let mut logic_calls = Vec::new();
+    for call in get_latest_logic_calls(client).await? {
+        let confirms = get_logic_call_signatures(
+            client,
+            call.invalidation_id.clone(),
+            call.invalidation_nonce,
+        )
+        .await?;
+        if !confirms.iter().any(|c| c.orchestrator == orchestrator) {
+            logic_calls.push(call);
+        }
+    }
+    logic_calls.sort_by_key(|c| c.invalidation_nonce);
+
+    Ok(PendingWork {
+        valsets: page.apply(valsets),
+        batches: page.apply(batches),
+        logic_calls: page.apply(logic_calls),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagination() {
+        let items: Vec<u32> = (0..10).collect();
+        assert_eq!(Pagination::default().apply(items.clone()), items);
+        let page = Pagination {
+            offset: 3,
+            limit: Some(4),
+        };
+        assert_eq!(page.apply(items.clone()), vec![3, 4, 5, 6]);
+        let page = Pagination {
+            offset: 8,
+            limit: Some(4),
+        };
+        assert_eq!(page.apply(items.clone()), vec![8, 9]);
+        let page = Pagination {
+            offset: 20,
+            limit: None,
+        };
+        assert!(page.apply(items).is_empty());
+    }
+}
@@ -6,16 +6,20 @@ use deep_space::utils::encode_any;
 use deep_space::Address;
 use deep_space::Coin;
 use deep_space::Contact;
+use deep_space::Msg;
 use deep_space::PrivateKey;
 use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::DenomUnit;
 use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::Metadata;
 use gravity_proto::cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use gravity_proto::cosmos_sdk_proto::cosmos::gov::v1beta1::MsgSubmitProposal;
 use gravity_proto::cosmos_sdk_proto::cosmos::params::v1beta1::ParamChange;
 use gravity_proto::cosmos_sdk_proto::cosmos::params::v1beta1::ParameterChangeProposal;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateResponse;
 use gravity_proto::cosmos_sdk_proto::cosmos::upgrade::v1beta1::SoftwareUpgradeProposal;
 use gravity_proto::gravity::AirdropProposal as AirdropProposalMsg;
 use gravity_proto::gravity::IbcMetadataProposal;
 use gravity_proto::gravity::UnhaltBridgeProposal;
+use prost_types::Any;
 use serde::Deserialize;
 use serde::Serialize;
 use std::convert::TryFrom;
@@ -32,6 +36,8 @@ pub const PARAMETER_CHANGE_PROPOSAL_TYPE_URL: &str =
 pub const SOFTWARE_UPGRADE_PROPOSAL_TYPE_URL: &str =
     "/cosmos.upgrade.v1beta1.SoftwareUpgradeProposal";
 
+pub const MSG_SUBMIT_PROPOSAL_TYPE_URL: &str = "/cosmos.gov.v1beta1.MsgSubmitProposal";
+
 // bech32ibc proposals
 pub const UPDATE_HRP_IBC_CHANNEL_PROPOSAL: &str =
     "/bech32ibc.bech32ibc.v1beta1.UpdateHrpIbcChannelProposal";
@@ -83,6 +89,14 @@ pub async fn submit_airdrop_proposal(
     key: impl PrivateKey,
     wait_timeout: Option<Duration>,
 ) -> Result<TxResponse, CosmosGrpcError> {
+    let any = airdrop_proposal_content(proposal);
+    contact
+        .create_gov_proposal(any, deposit, fee, key, wait_timeout)
+        .await
+}
+
+/// Encodes an airdrop proposal as generic proposal content
+pub fn airdrop_proposal_content(proposal: AirdropProposalJson) -> Any {
     let mut byte_recipients = Vec::new();
     for r in proposal.recipients {
         byte_recipients.extend_from_slice(r.get_bytes())
@@ -97,11 +111,7 @@ pub async fn submit_airdrop_proposal(
     };
 
     // encode as a generic proposal
-    let any = encode_any(proposal_content, AIRDROP_PROPOSAL_TYPE_URL.to_string());
-
-    contact
-        .create_gov_proposal(any, deposit, fee, key, wait_timeout)
-        .await
+    encode_any(proposal_content, AIRDROP_PROPOSAL_TYPE_URL.to_string())
 }
 
 /// The proposal.json representation for pausing/unpausing the bridge easily
@@ -131,13 +141,17 @@ pub async fn submit_unhalt_bridge_proposal(
     key: impl PrivateKey,
     wait_timeout: Option<Duration>,
 ) -> Result<TxResponse, CosmosGrpcError> {
-    // encode as a generic proposal
-    let any = encode_any(proposal, UNHALT_BRIDGE_PROPOSAL_TYPE_URL.to_string());
+    let any = unhalt_bridge_proposal_content(proposal);
     contact
         .create_gov_proposal(any, deposit, fee, key, wait_timeout)
         .await
 }
 
+/// Encodes an unhalt bridge proposal as generic proposal content
+pub fn unhalt_bridge_proposal_content(proposal: UnhaltBridgeProposal) -> Any {
+    encode_any(proposal, UNHALT_BRIDGE_PROPOSAL_TYPE_URL.to_string())
+}
+
 /// The proposal.json representation for pausing/unpausing the bridge easily
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PauseBridgeProposalJson {
@@ -155,6 +169,14 @@ pub async fn submit_pause_bridge_proposal(
     key: impl PrivateKey,
     wait_timeout: Option<Duration>,
 ) -> Result<TxResponse, CosmosGrpcError> {
+    let any = pause_bridge_proposal_content(proposal);
+    contact
+        .create_gov_proposal(any, deposit, fee, key, wait_timeout)
+        .await
+}
+
+/// Encodes a pause bridge proposal as the generic parameter change proposal it is submitted as
+pub fn pause_bridge_proposal_content(proposal: PauseBridgeProposalJson) -> Any {
     let mut params_to_change = Vec::new();
     let halt = ParamChange {
         subspace: "gravity".to_string(),
@@ -167,7 +189,7 @@ pub async fn submit_pause_bridge_proposal(
         description: proposal.description,
         changes: params_to_change,
     };
-    submit_parameter_change_proposal(proposal, deposit, fee, contact, key, wait_timeout).await
+    encode_any(proposal, PARAMETER_CHANGE_PROPOSAL_TYPE_URL.to_string())
 }
 
 /// Encodes and submits a proposal change bridge parameters, should maybe be in deep_space
@@ -266,13 +288,17 @@ pub async fn submit_ibc_metadata_proposal(
     key: impl PrivateKey,
     wait_timeout: Option<Duration>,
 ) -> Result<TxResponse, CosmosGrpcError> {
-    // encode as a generic proposal
-    let any = encode_any(proposal, IBC_METADATA_PROPOSAL_TYPE_URL.to_string());
+    let any = ibc_metadata_proposal_content(proposal);
     contact
         .create_gov_proposal(any, deposit, fee, key, wait_timeout)
         .await
 }
 
+/// Encodes an IBC metadata proposal as generic proposal content
+pub fn ibc_metadata_proposal_content(proposal: IbcMetadataProposal) -> Any {
+    encode_any(proposal, IBC_METADATA_PROPOSAL_TYPE_URL.to_string())
+}
+
 /// The proposal.json representation for setting the MinChainFeeBasisPoints parameter
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SendToEthFeesProposalJson {
@@ -377,3 +403,27 @@ pub async fn submit_auction_params_proposal(
     info!("Submitting auction params proposal:\n{:?}", proposal);
     submit_parameter_change_proposal(proposal, deposit, fee, contact, key, wait_timeout).await
 }
+
+/// Simulates submitting a proposal with the given content without broadcasting it, the chain runs
+/// the same checks `create_gov_proposal` would be subject to, including the deposit
+pub async fn simulate_gov_proposal(
+    content: Any,
+    deposit: Coin,
+    fee: Coin,
+    contact: &Contact,
+    key: impl PrivateKey,
+) -> Result<SimulateResponse, CosmosGrpcError> {
+    let proposer = key.to_address(&contact.get_prefix()).unwrap();
+    let msg = gov_proposal_msg(content, deposit, proposer);
+    contact.simulate_tx(&[msg], Some(&[fee]), key).await
+}
+
+/// Builds the MsgSubmitProposal `create_gov_proposal` sends for the given content
+pub fn gov_proposal_msg(content: Any, deposit: Coin, proposer: Address) -> Msg {
+    let msg_submit_proposal = MsgSubmitProposal {
+        content: Some(content),
+        initial_deposit: vec![deposit.into()],
+        proposer: proposer.to_string(),
+    };
+    Msg::new(MSG_SUBMIT_PROPOSAL_TYPE_URL, msg_submit_proposal)
+}
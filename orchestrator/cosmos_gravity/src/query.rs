@@ -7,18 +7,26 @@ use deep_space::Contact;
 use gravity_proto::auction::query_client::QueryClient as AuctionQueryClient;
 use gravity_proto::auction::Params as AuctionParams;
 use gravity_proto::auction::QueryParamsRequest as QueryAuctionParamsRequest;
+use gravity_proto::cosmos_sdk_proto::cosmos::upgrade::v1beta1::query_client::QueryClient as UpgradeQueryClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::upgrade::v1beta1::QueryModuleVersionsRequest;
+use gravity_proto::cosmos_sdk_proto::ibc::applications::transfer::v1::query_client::QueryClient as IbcTransferQueryClient;
+use gravity_proto::cosmos_sdk_proto::ibc::applications::transfer::v1::{
+    DenomTrace, QueryDenomTraceRequest,
+};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_proto::gravity::Params;
 use gravity_proto::gravity::QueryAttestationsRequest;
 use gravity_proto::gravity::QueryBatchConfirmsRequest;
 use gravity_proto::gravity::QueryBatchFeeRequest;
 use gravity_proto::gravity::QueryBatchFeeResponse;
+use gravity_proto::gravity::QueryBatchRequestByNonceRequest;
 use gravity_proto::gravity::QueryCurrentValsetRequest;
 use gravity_proto::gravity::QueryDenomToErc20Request;
 use gravity_proto::gravity::QueryDenomToErc20Response;
 use gravity_proto::gravity::QueryErc20ToDenomRequest;
 use gravity_proto::gravity::QueryErc20ToDenomResponse;
 use gravity_proto::gravity::QueryLastEventNonceByAddrRequest;
+use gravity_proto::gravity::QueryLastObservedEthBlockRequest;
 use gravity_proto::gravity::QueryLastPendingBatchRequestByAddrRequest;
 use gravity_proto::gravity::QueryLastPendingLogicCallByAddrRequest;
 use gravity_proto::gravity::QueryLastPendingValsetRequestByAddrRequest;
@@ -31,10 +39,33 @@ use gravity_proto::gravity::QueryPendingSendToEth;
 use gravity_proto::gravity::QueryPendingSendToEthResponse;
 use gravity_proto::gravity::QueryValsetConfirmsByNonceRequest;
 use gravity_proto::gravity::QueryValsetRequestRequest;
-use gravity_proto::gravity::{Attestation, PendingIbcAutoForward, QueryPendingIbcAutoForwards};
+use gravity_proto::gravity::{
+    Attestation, MsgSendToCosmosClaim, PendingIbcAutoForward, QueryPendingIbcAutoForwards,
+};
 use gravity_utils::error::GravityError;
 use gravity_utils::types::*;
-use tonic::transport::Channel;
+use lazy_static::lazy_static;
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tonic::transport::{Channel, Endpoint};
+
+lazy_static! {
+    static ref CHANNELS: Mutex<HashMap<String, Channel>> = Mutex::new(HashMap::new());
+}
+
+/// A channel to the gRPC server at `url` shared by every query client built with it. Channels are
+/// HTTP/2 connections that carry concurrent requests, so reusing one avoids a new connection per
+/// query and lets cloned clients run requests in parallel. The connection is made on first use
+pub fn shared_channel(url: &str) -> Result<Channel, CosmosGrpcError> {
+    let mut channels = CHANNELS.lock().unwrap();
+    if let Some(channel) = channels.get(url) {
+        return Ok(channel.clone());
+    }
+    let channel = Endpoint::from_shared(url.to_string())?.connect_lazy();
+    channels.insert(url.to_string(), channel.clone());
+    Ok(channel)
+}
 
 /// Gets the Gravity module parameters from the Gravity module
 pub async fn get_gravity_params(
@@ -84,6 +115,16 @@ pub async fn get_oldest_unsigned_valsets(
     address: Address,
     prefix: String,
 ) -> Result<Vec<Valset>, GravityError> {
+    let valsets = get_oldest_unsigned_valsets_with_height(client, address, prefix).await?;
+    Ok(valsets.into_iter().map(|(v, _)| v).collect())
+}
+
+/// As `get_oldest_unsigned_valsets`, with the Cosmos block each valset was created in
+pub async fn get_oldest_unsigned_valsets_with_height(
+    client: &mut GravityQueryClient<Channel>,
+    address: Address,
+    prefix: String,
+) -> Result<Vec<(Valset, u64)>, GravityError> {
     let request = client
         .last_pending_valset_request_by_addr(QueryLastPendingValsetRequestByAddrRequest {
             address: address.to_bech32(prefix).unwrap(),
@@ -91,7 +132,7 @@ pub async fn get_oldest_unsigned_valsets(
         .await?;
     let valsets = request.into_inner().valsets;
     // convert from proto valset type to rust valset type
-    let valsets = valsets.iter().map(|v| v.into()).collect();
+    let valsets = valsets.iter().map(|v| (v.into(), v.height)).collect();
     Ok(valsets)
 }
 
@@ -129,6 +170,17 @@ pub async fn get_oldest_unsigned_transaction_batches(
     address: Address,
     prefix: String,
 ) -> Result<Vec<TransactionBatch>, GravityError> {
+    let batches =
+        get_oldest_unsigned_transaction_batches_with_height(client, address, prefix).await?;
+    Ok(batches.into_iter().map(|(b, _)| b).collect())
+}
+
+/// As `get_oldest_unsigned_transaction_batches`, with the Cosmos block each batch was created in
+pub async fn get_oldest_unsigned_transaction_batches_with_height(
+    client: &mut GravityQueryClient<Channel>,
+    address: Address,
+    prefix: String,
+) -> Result<Vec<(TransactionBatch, u64)>, GravityError> {
     let request = client
         .last_pending_batch_request_by_addr(QueryLastPendingBatchRequestByAddrRequest {
             address: address.to_bech32(prefix).unwrap(),
@@ -139,7 +191,8 @@ pub async fn get_oldest_unsigned_transaction_batches(
     let mut ret_batches = Vec::new();
 
     for batch in batches {
-        ret_batches.push(TransactionBatch::try_from(batch)?);
+        let created = batch.cosmos_block_created;
+        ret_batches.push((TransactionBatch::try_from(batch)?, created));
     }
     Ok(ret_batches)
 }
@@ -160,6 +213,29 @@ pub async fn get_latest_transaction_batches(
     Ok(out)
 }
 
+/// Gets a single transaction batch by nonce and token contract, returns None if the chain does not
+/// have it. Batches are removed once executed on Ethereum, cancelled or timed out
+pub async fn get_transaction_batch(
+    client: &mut GravityQueryClient<Channel>,
+    nonce: u64,
+    contract_address: EthAddress,
+) -> Result<Option<TransactionBatch>, GravityError> {
+    let request = client
+        .batch_request_by_nonce(QueryBatchRequestByNonceRequest {
+            nonce,
+            contract_address: contract_address.to_string(),
+        })
+        .await;
+    match request {
+        Ok(response) => match response.into_inner().batch {
+            Some(batch) => Ok(Some(TransactionBatch::try_from(batch)?)),
+            None => Ok(None),
+        },
+        Err(e) if e.message().contains("cannot find tx batch") => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 /// get all batch confirmations for a given nonce and denom
 pub async fn get_transaction_batch_signatures(
     client: &mut GravityQueryClient<Channel>,
@@ -234,6 +310,16 @@ pub async fn get_oldest_unsigned_logic_calls(
     address: Address,
     prefix: String,
 ) -> Result<Vec<LogicCall>, GravityError> {
+    let calls = get_oldest_unsigned_logic_calls_with_height(client, address, prefix).await?;
+    Ok(calls.into_iter().map(|(c, _)| c).collect())
+}
+
+/// As `get_oldest_unsigned_logic_calls`, with the Cosmos block each call was created in
+pub async fn get_oldest_unsigned_logic_calls_with_height(
+    client: &mut GravityQueryClient<Channel>,
+    address: Address,
+    prefix: String,
+) -> Result<Vec<(LogicCall, u64)>, GravityError> {
     let request = client
         .last_pending_logic_call_by_addr(QueryLastPendingLogicCallByAddrRequest {
             address: address.to_bech32(prefix).unwrap(),
@@ -244,11 +330,23 @@ pub async fn get_oldest_unsigned_logic_calls(
     let mut ret_calls = Vec::new();
 
     for call in calls {
-        ret_calls.push(LogicCall::try_from(call)?);
+        let created = call.cosmos_block_created;
+        ret_calls.push((LogicCall::try_from(call)?, created));
     }
     Ok(ret_calls)
 }
 
+/// The latest Ethereum block the chain has observed an event in, 0 if the bridge is inactive. This
+/// lags the actual Ethereum height, by a lot if there have been no bridge events recently
+pub async fn get_last_observed_eth_block(
+    client: &mut GravityQueryClient<Channel>,
+) -> Result<u64, GravityError> {
+    let request = client
+        .get_last_observed_eth_block(QueryLastObservedEthBlockRequest { use_v1_key: false })
+        .await?;
+    Ok(request.into_inner().block)
+}
+
 pub async fn get_attestations(
     client: &mut GravityQueryClient<Channel>,
     limit: Option<u64>,
@@ -267,6 +365,53 @@ pub async fn get_attestations(
     Ok(attestations)
 }
 
+/// Whether the chain has attested to a deposit
+#[derive(Debug, Clone, PartialEq)]
+pub enum DepositAttestation {
+    /// no attestation for the deposit's event nonce has been observed yet
+    Pending,
+    /// the observed attestation matches the deposit log
+    Attested,
+    /// the observed attestation for the deposit's event nonce is for a different deposit, the
+    /// deposit log was most likely reorged out
+    Mismatched(MsgSendToCosmosClaim),
+}
+
+/// Checks whether `deposit` has been attested by looking up the observed attestation for its event
+/// nonce and comparing the claim against the deposit log
+pub async fn get_deposit_attestation(
+    client: &mut GravityQueryClient<Channel>,
+    deposit: &SendToCosmosEvent,
+) -> Result<DepositAttestation, GravityError> {
+    let attestations = client
+        .get_attestations(QueryAttestationsRequest {
+            limit: 0,
+            order_by: String::new(),
+            claim_type: String::new(),
+            nonce: deposit.event_nonce,
+            height: 0,
+            use_v1_key: false,
+        })
+        .await?
+        .into_inner()
+        .attestations;
+    for attestation in attestations.into_iter().filter(|a| a.observed) {
+        let claim = match attestation.claim {
+            Some(c) if c.type_url == MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL => c,
+            _ => continue,
+        };
+        let claim = MsgSendToCosmosClaim::decode(claim.value.as_slice()).map_err(|e| {
+            GravityError::InvalidBridgeStateError(format!("Invalid attested claim {:?}", e))
+        })?;
+        return Ok(if deposit.matches_claim(&claim) {
+            DepositAttestation::Attested
+        } else {
+            DepositAttestation::Mismatched(claim)
+        });
+    }
+    Ok(DepositAttestation::Pending)
+}
+
 /// Get a list of transactions going to the EVM blockchain that are pending for a given user.
 pub async fn get_pending_send_to_eth(
     client: &mut GravityQueryClient<Channel>,
@@ -366,7 +511,7 @@ pub async fn get_min_chain_fee_basis_points(contact: &Contact) -> Result<u64, Co
 pub async fn get_auction_module_params(
     contact: &Contact,
 ) -> Result<AuctionParams, CosmosGrpcError> {
-    let mut auction_qc = AuctionQueryClient::connect(contact.get_url()).await?;
+    let mut auction_qc = AuctionQueryClient::new(shared_channel(&contact.get_url())?);
 
     let params = auction_qc
         .params(QueryAuctionParamsRequest {})
@@ -379,3 +524,42 @@ pub async fn get_auction_module_params(
 
     Ok(params)
 }
+
+// Gets the IBC denom trace for an ibc/HASH denom, returns None if the chain does not know the hash
+pub async fn get_denom_trace(
+    contact: &Contact,
+    denom: &str,
+) -> Result<Option<DenomTrace>, CosmosGrpcError> {
+    let mut transfer_qc = IbcTransferQueryClient::new(shared_channel(&contact.get_url())?);
+
+    let res = transfer_qc
+        .denom_trace(QueryDenomTraceRequest {
+            hash: denom.trim_start_matches("ibc/").to_string(),
+        })
+        .await;
+    match res {
+        Ok(v) => Ok(v.into_inner().denom_trace),
+        Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Gets the consensus version of a module from the upgrade module, None if the chain has no such
+/// module
+pub async fn get_module_version(
+    contact: &Contact,
+    module: &str,
+) -> Result<Option<u64>, CosmosGrpcError> {
+    let mut upgrade_qc = UpgradeQueryClient::new(shared_channel(&contact.get_url())?);
+    let versions = upgrade_qc
+        .module_versions(QueryModuleVersionsRequest {
+            module_name: module.to_string(),
+        })
+        .await?
+        .into_inner()
+        .module_versions;
+    Ok(versions
+        .into_iter()
+        .find(|v| v.name == module)
+        .map(|v| v.version))
+}
@@ -0,0 +1,117 @@
+//! Cached versions of the queries repeated on every loop iteration or request: the gravity params
+//! read by the orchestrator and relayer loops and the ERC20 to denom mapping looked up for every
+//! token in a balance request. The values behind them only change through governance or rarely at
+//! all, but re-querying them dominates the gRPC traffic of a busy orchestrator. Each item is kept
+//! for its own TTL, after which the next call queries the chain again
+
+use crate::query::{get_erc20_to_denom, get_gravity_params};
+use clarity::Address as EthAddress;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::{Params, QueryErc20ToDenomResponse};
+use gravity_utils::error::GravityError;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tonic::transport::Channel;
+
+/// Params change only through governance, which takes days, so a minute is plenty fresh
+pub const PARAMS_TTL: Duration = Duration::from_secs(60);
+/// A token's denom never changes once it has been bridged
+pub const DENOM_MAPPING_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// A map whose entries expire `ttl` after they were inserted
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        TtlCache {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The value for `key` if it was inserted less than the TTL before `now`
+    pub fn get(&mut self, key: &K, now: Instant) -> Option<V> {
+        match self.entries.get(key) {
+            Some((inserted, value)) if now.saturating_duration_since(*inserted) < self.ttl => {
+                Some(value.clone())
+            }
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V, now: Instant) {
+        self.entries.insert(key, (now, value));
+    }
+}
+
+lazy_static! {
+    static ref PARAMS: Mutex<TtlCache<(), Params>> = Mutex::new(TtlCache::new(PARAMS_TTL));
+    static ref ERC20_TO_DENOM: Mutex<TtlCache<EthAddress, QueryErc20ToDenomResponse>> =
+        Mutex::new(TtlCache::new(DENOM_MAPPING_TTL));
+}
+
+/// `get_gravity_params`, cached for PARAMS_TTL
+pub async fn get_gravity_params_cached(
+    client: &mut GravityQueryClient<Channel>,
+) -> Result<Params, GravityError> {
+    if let Some(params) = PARAMS.lock().unwrap().get(&(), Instant::now()) {
+        return Ok(params);
+    }
+    let params = get_gravity_params(client).await?;
+    PARAMS
+        .lock()
+        .unwrap()
+        .insert((), params.clone(), Instant::now());
+    Ok(params)
+}
+
+/// Replaces the cached params with ones just queried, so a parameter change is used immediately
+/// rather than once the cached copy expires
+pub fn update_gravity_params_cache(params: &Params) {
+    PARAMS
+        .lock()
+        .unwrap()
+        .insert((), params.clone(), Instant::now());
+}
+
+/// `get_erc20_to_denom`, cached for DENOM_MAPPING_TTL
+pub async fn get_erc20_to_denom_cached(
+    client: &mut GravityQueryClient<Channel>,
+    erc20: EthAddress,
+) -> Result<QueryErc20ToDenomResponse, GravityError> {
+    if let Some(res) = ERC20_TO_DENOM.lock().unwrap().get(&erc20, Instant::now()) {
+        return Ok(res);
+    }
+    let res = get_erc20_to_denom(client, erc20).await?;
+    ERC20_TO_DENOM
+        .lock()
+        .unwrap()
+        .insert(erc20, res.clone(), Instant::now());
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_cache() {
+        let start = Instant::now();
+        let mut cache = TtlCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get(&"a", start), None);
+        cache.insert("a", 1, start);
+        assert_eq!(cache.get(&"a", start + Duration::from_secs(59)), Some(1));
+        assert_eq!(cache.get(&"a", start + Duration::from_secs(60)), None);
+        assert!(cache.entries.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+//! Coordination between redundant orchestrators sharing one Cosmos key. While a failover pair
+//! overlaps both instances submit the same claims and confirms, only the first transaction to land
+//! is accepted and the other pays its fee for nothing. Once enabled with `set_redundancy` the key's
+//! most recent transactions and, if a Tendermint RPC is configured, the mempool are searched before
+//! every submission so that anything the other instance already sent can be left out
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use deep_space::address::Address as CosmosAddress;
+use deep_space::error::CosmosGrpcError;
+use deep_space::Contact;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    GetTxsEventRequest, OrderBy, TxBody, TxRaw,
+};
+use gravity_proto::gravity::{
+    MsgBatchSendToEthClaim, MsgConfirmBatch, MsgConfirmLogicCall, MsgErc20DeployedClaim,
+    MsgLogicCallExecutedClaim, MsgSendToCosmosClaim, MsgValsetConfirm, MsgValsetUpdatedClaim,
+};
+use gravity_utils::types::{
+    RedundancyConfig, MSG_BATCH_SEND_TO_ETH_TYPE_URL, MSG_ERC20_DEPLOYED_CLAIM_TYPE_URL,
+    MSG_LOGIC_CALL_EXECUTED_CLAIM_TYPE_URL, MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL,
+    MSG_VALSET_UPDATED_CLAIM_TYPE_URL,
+};
+use prost::Message;
+use prost_types::Any;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::RwLock;
+use std::time::Duration;
+use web30::jsonrpc::client::HttpClient;
+
+use crate::query::shared_channel;
+use crate::send::{
+    MSG_CONFIRM_BATCH_TYPE_URL, MSG_CONFIRM_LOGIC_CALL_TYPE_URL, MSG_VALSET_CONFIRM_TYPE_URL,
+};
+
+/// How many mempool transactions are requested from the Tendermint RPC
+const MEMPOOL_TX_LIMIT: u64 = 100;
+const MEMPOOL_TIMEOUT: Duration = Duration::from_secs(10);
+
+static REDUNDANCY: RwLock<Option<RedundancyConfig>> = RwLock::new(None);
+
+/// Enables the search for submissions by a redundant orchestrator, a disabled config turns it off
+pub fn set_redundancy(config: &RedundancyConfig) {
+    *REDUNDANCY.write().unwrap() = if config.enabled {
+        Some(config.clone())
+    } else {
+        None
+    };
+}
+
+/// A claim or confirm identified independently of the transaction carrying it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Submission {
+    Claim {
+        event_nonce: u64,
+    },
+    ValsetConfirm {
+        nonce: u64,
+    },
+    BatchConfirm {
+        token_contract: String,
+        nonce: u64,
+    },
+    LogicCallConfirm {
+        invalidation_id: String,
+        invalidation_nonce: u64,
+    },
+}
+
+/// The claims and confirms `orchestrator` has in recent successful transactions or in the
+/// mempool. Empty unless enabled with `set_redundancy`, if the search fails a warning is logged
+/// and nothing is left out, paying for a duplicate is better than missing a submission
+pub async fn find_submitted(contact: &Contact, orchestrator: CosmosAddress) -> HashSet<Submission> {
+    let config = match REDUNDANCY.read().unwrap().clone() {
+        Some(c) => c,
+        None => return HashSet::new(),
+    };
+    let orchestrator = orchestrator.to_string();
+    let mut found = HashSet::new();
+    match recent_messages(contact, &orchestrator, config.recent_txs).await {
+        Ok(msgs) => found.extend(submissions(&msgs, &orchestrator)),
+        Err(e) => warn!("Could not search recent orchestrator transactions {:?}", e),
+    }
+    if let Some(url) = config.tendermint_rpc {
+        match mempool_messages(&url).await {
+            Ok(msgs) => found.extend(submissions(&msgs, &orchestrator)),
+            Err(e) => warn!("Could not search the mempool at {} {:?}", url, e),
+        }
+    }
+    found
+}
+
+/// The lowest of the sorted claim `nonces` not already in `submitted`, every claim from it on has to
+/// be sent. None if all of them were submitted
+pub fn first_unsubmitted_claim(nonces: &[u64], submitted: &HashSet<Submission>) -> Option<u64> {
+    nonces
+        .iter()
+        .find(|nonce| {
+            !submitted.contains(&Submission::Claim {
+                event_nonce: **nonce,
+            })
+        })
+        .copied()
+}
+
+/// Logs what is being left out because a redundant orchestrator already submitted it
+pub fn log_skipped(kind: &str, skipped: usize) {
+    if skipped > 0 {
+        info!(
+            "Skipping {} {} already submitted by a redundant orchestrator",
+            skipped, kind
+        );
+    }
+}
+
+/// The messages of `orchestrator`'s most recent successful transactions
+async fn recent_messages(
+    contact: &Contact,
+    orchestrator: &str,
+    limit: u64,
+) -> Result<Vec<Any>, CosmosGrpcError> {
+    let mut tx_client = TxServiceClient::new(shared_channel(&contact.get_url())?);
+    let res = tx_client
+        .get_txs_event(GetTxsEventRequest {
+            events: vec![format!("message.sender='{}'", orchestrator)],
+            order_by: OrderBy::Desc as i32,
+            pagination: Some(PageRequest {
+                limit,
+                ..Default::default()
+            }),
+        })
+        .await?
+        .into_inner();
+    Ok(res
+        .txs
+        .into_iter()
+        .zip(res.tx_responses)
+        // failed transactions did not submit anything
+        .filter(|(_, response)| response.code == 0)
+        .filter_map(|(tx, _)| tx.body)
+        .flat_map(|body| body.messages)
+        .collect())
+}
+
+#[derive(Deserialize, Debug)]
+struct UnconfirmedTxs {
+    txs: Option<Vec<String>>,
+}
+
+/// The messages of every transaction in the mempool of the node at `tendermint_rpc`
+async fn mempool_messages(tendermint_rpc: &str) -> Result<Vec<Any>, CosmosGrpcError> {
+    let client = HttpClient::new(tendermint_rpc);
+    let res: UnconfirmedTxs = client
+        .request_method(
+            "unconfirmed_txs",
+            json!({ "limit": MEMPOOL_TX_LIMIT.to_string() }),
+            MEMPOOL_TIMEOUT,
+        )
+        .await
+        .map_err(|e| CosmosGrpcError::BadResponse(format!("{:?}", e)))?;
+    let mut msgs = Vec::new();
+    for tx in res.txs.unwrap_or_default() {
+        // other transactions in the mempool are none of our business, skip what can't be read
+        let body = BASE64
+            .decode(tx)
+            .ok()
+            .and_then(|bytes| TxRaw::decode(bytes.as_slice()).ok())
+            .and_then(|raw| TxBody::decode(raw.body_bytes.as_slice()).ok());
+        if let Some(body) = body {
+            msgs.extend(body.messages);
+        }
+    }
+    Ok(msgs)
+}
+
+/// The claims and confirms among `msgs` made by `orchestrator`
+fn submissions(msgs: &[Any], orchestrator: &str) -> Vec<Submission> {
+    msgs.iter()
+        .filter_map(decode_submission)
+        .filter(|(signer, _)| signer == orchestrator)
+        .map(|(_, submission)| submission)
+        .collect()
+}
+
+/// Decodes a claim or confirm, returning its orchestrator alongside it
+fn decode_submission(msg: &Any) -> Option<(String, Submission)> {
+    let value = msg.value.as_slice();
+    let claim = |orchestrator: String, event_nonce: u64| {
+        Some((orchestrator, Submission::Claim { event_nonce }))
+    };
+    match msg.type_url.as_str() {
+        MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL => {
+            let m = MsgSendToCosmosClaim::decode(value).ok()?;
+            claim(m.orchestrator, m.event_nonce)
+        }
+        MSG_BATCH_SEND_TO_ETH_TYPE_URL => {
+            let m = MsgBatchSendToEthClaim::decode(value).ok()?;
+            claim(m.orchestrator, m.event_nonce)
+        }
+        MSG_ERC20_DEPLOYED_CLAIM_TYPE_URL => {
+            let m = MsgErc20DeployedClaim::decode(value).ok()?;
+            claim(m.orchestrator, m.event_nonce)
+        }
+        MSG_LOGIC_CALL_EXECUTED_CLAIM_TYPE_URL => {
+            let m = MsgLogicCallExecutedClaim::decode(value).ok()?;
+            claim(m.orchestrator, m.event_nonce)
+        }
+        MSG_VALSET_UPDATED_CLAIM_TYPE_URL => {
+            let m = MsgValsetUpdatedClaim::decode(value).ok()?;
+            claim(m.orchestrator, m.event_nonce)
+        }
+        MSG_VALSET_CONFIRM_TYPE_URL => {
+            let m = MsgValsetConfirm::decode(value).ok()?;
+            Some((m.orchestrator, Submission::ValsetConfirm { nonce: m.nonce }))
+        }
+        MSG_CONFIRM_BATCH_TYPE_URL => {
+            let m = MsgConfirmBatch::decode(value).ok()?;
+            Some((
+                m.orchestrator,
+                Submission::BatchConfirm {
+                    token_contract: m.token_contract.to_lowercase(),
+                    nonce: m.nonce,
+                },
+            ))
+        }
+        MSG_CONFIRM_LOGIC_CALL_TYPE_URL => {
+            let m = MsgConfirmLogicCall::decode(value).ok()?;
+            Some((
+                m.orchestrator,
+                Submission::LogicCallConfirm {
+                    invalidation_id: m.invalidation_id.to_lowercase(),
+                    invalidation_nonce: m.invalidation_nonce,
+                },
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_unsubmitted_claim() {
+        let submitted: HashSet<Submission> = [3, 4, 6]
+            .iter()
+            .map(|n| Submission::Claim { event_nonce: *n })
+            .collect();
+        // 3 and 4 can be left out, 6 has to be sent again since 5 comes before it
+        assert_eq!(first_unsubmitted_claim(&[3, 4, 5, 6], &submitted), Some(5));
+        assert_eq!(first_unsubmitted_claim(&[2, 3, 4], &submitted), Some(2));
+        assert_eq!(first_unsubmitted_claim(&[3, 4], &submitted), None);
+        assert_eq!(first_unsubmitted_claim(&[], &submitted), None);
+    }
+
+    #[test]
+    fn test_submissions() {
+        let ours = "gravity1ours".to_string();
+        let confirm = MsgValsetConfirm {
+            nonce: 7,
+            orchestrator: ours.clone(),
+            ..Default::default()
+        };
+        let claim = MsgSendToCosmosClaim {
+            event_nonce: 12,
+            orchestrator: ours.clone(),
+            ..Default::default()
+        };
+        let theirs = MsgConfirmBatch {
+            nonce: 3,
+            token_contract: "0xabc".to_string(),
+            orchestrator: "gravity1theirs".to_string(),
+            ..Default::default()
+        };
+        let msgs = vec![
+            Any {
+                type_url: MSG_VALSET_CONFIRM_TYPE_URL.to_string(),
+                value: confirm.encode_to_vec(),
+            },
+            Any {
+                type_url: MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL.to_string(),
+                value: claim.encode_to_vec(),
+            },
+            Any {
+                type_url: MSG_CONFIRM_BATCH_TYPE_URL.to_string(),
+                value: theirs.encode_to_vec(),
+            },
+            Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            submissions(&msgs, &ours),
+            vec![
+                Submission::ValsetConfirm { nonce: 7 },
+                Submission::Claim { event_nonce: 12 }
+            ]
+        );
+    }
+}
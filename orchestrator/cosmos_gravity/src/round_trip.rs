@@ -0,0 +1,181 @@
+//! Sends a Cosmos originated asset to Ethereum and back again, checking the balances on both sides
+//! at every step. This exercises the whole bridge, the oracle, signers and relayers, so it is used
+//! both as an operator smoke test and as a test_runner scenario
+
+use crate::send::{send_request_batch, send_to_eth};
+use crate::utils::get_reasonable_send_to_eth_fee;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Uint256};
+use deep_space::{Address as CosmosAddress, Coin, Contact, CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use gravity_utils::error::GravityError;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+
+/// How often balances are checked while waiting for a transfer to arrive
+const BALANCE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct RoundTripReport {
+    /// Time from sending the MsgSendToEth until the ERC20 arrived on Ethereum
+    pub to_eth: Duration,
+    /// Time from sending the sendToCosmos deposit until the tokens arrived on Cosmos
+    pub to_cosmos: Duration,
+    /// The amount of the token spent on the bridge, chain and Cosmos fees
+    pub fees_paid: Uint256,
+}
+
+/// Sends `amount` of a Cosmos originated token from the Cosmos key to the Ethereum key, waits for
+/// it to be relayed, then sends it back and waits for it to arrive. Errors if either leg does not
+/// arrive within `timeout` or if any balance does not change by exactly the expected amount.
+/// If `request_batch` is set a batch is requested after the MsgSendToEth, otherwise the relayers
+/// are relied on to request one
+#[allow(clippy::too_many_arguments)]
+pub async fn round_trip(
+    contact: &Contact,
+    web3: &Web3,
+    cosmos_key: CosmosPrivateKey,
+    ethereum_key: EthPrivateKey,
+    gravity_contract: EthAddress,
+    erc20: EthAddress,
+    amount: Coin,
+    bridge_fee: Coin,
+    chain_fee: Option<Coin>,
+    fee: Coin,
+    request_batch: bool,
+    timeout: Duration,
+) -> Result<RoundTripReport, GravityError> {
+    let cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    let eth_address = ethereum_key.to_address();
+    let denom = amount.denom.clone();
+    // the chain fee is computed up front so the amount spent can be checked exactly
+    let chain_fee = match chain_fee {
+        Some(c) => c,
+        None => Coin {
+            denom: denom.clone(),
+            amount: get_reasonable_send_to_eth_fee(contact, amount.amount).await?,
+        },
+    };
+    let fee_in_denom = fee.denom == denom;
+
+    let start_cosmos = get_cosmos_balance(contact, cosmos_address, &denom).await?;
+    let start_erc20 = web3.get_erc20_balance(erc20, eth_address).await?;
+    info!(
+        "Starting round trip of {} from {} to {} and back, balances {}{} and {} of ERC20 {}",
+        amount, cosmos_address, eth_address, start_cosmos, denom, start_erc20, erc20
+    );
+
+    let mut fees_paid: Uint256 = bridge_fee.amount + chain_fee.amount;
+    let sent = Instant::now();
+    send_to_eth(
+        cosmos_key,
+        eth_address,
+        amount.clone(),
+        bridge_fee.clone(),
+        Some(chain_fee.clone()),
+        fee.clone(),
+        contact,
+        None,
+    )
+    .await?;
+    if fee_in_denom {
+        fees_paid += fee.amount;
+    }
+    info!("Sent {} to Ethereum, waiting for it to be relayed", amount);
+    if request_batch {
+        match send_request_batch(cosmos_key, denom.clone(), Some(fee.clone()), contact).await {
+            Ok(_) => {
+                if fee_in_denom {
+                    fees_paid += fee.amount;
+                }
+                info!("Requested a batch of {}", denom);
+            }
+            Err(e) => warn!("Failed to request a batch, waiting for a relayer {:?}", e),
+        }
+    }
+
+    let expected_erc20 = start_erc20 + amount.amount;
+    while web3.get_erc20_balance(erc20, eth_address).await? < expected_erc20 {
+        if Instant::now() - sent > timeout {
+            error!("{} did not arrive on Ethereum", amount);
+            return Err(GravityError::TimeoutError);
+        }
+        delay_for(BALANCE_CHECK_INTERVAL).await;
+    }
+    let to_eth = Instant::now() - sent;
+    let erc20_balance = web3.get_erc20_balance(erc20, eth_address).await?;
+    if erc20_balance != expected_erc20 {
+        return Err(GravityError::InvalidBridgeStateError(format!(
+            "Expected an ERC20 balance of {} after the transfer to Ethereum, found {}",
+            expected_erc20, erc20_balance
+        )));
+    }
+    let mid_cosmos = get_cosmos_balance(contact, cosmos_address, &denom).await?;
+    info!(
+        "{} arrived on Ethereum after {} seconds, sending it back",
+        amount,
+        to_eth.as_secs()
+    );
+
+    let sent = Instant::now();
+    send_to_cosmos(
+        erc20,
+        gravity_contract,
+        amount.amount,
+        cosmos_address,
+        ethereum_key,
+        Some(timeout),
+        web3,
+        vec![],
+    )
+    .await?;
+
+    let expected_cosmos = mid_cosmos + amount.amount;
+    while get_cosmos_balance(contact, cosmos_address, &denom).await? < expected_cosmos {
+        if Instant::now() - sent > timeout {
+            error!("{} did not arrive back on Cosmos", amount);
+            return Err(GravityError::TimeoutError);
+        }
+        delay_for(BALANCE_CHECK_INTERVAL).await;
+    }
+    let to_cosmos = Instant::now() - sent;
+
+    let end_cosmos = get_cosmos_balance(contact, cosmos_address, &denom).await?;
+    let end_erc20 = web3.get_erc20_balance(erc20, eth_address).await?;
+    if end_erc20 != start_erc20 {
+        return Err(GravityError::InvalidBridgeStateError(format!(
+            "Expected the ERC20 balance to return to {}, found {}",
+            start_erc20, end_erc20
+        )));
+    }
+    if end_cosmos + fees_paid != start_cosmos {
+        return Err(GravityError::InvalidBridgeStateError(format!(
+            "Expected to pay {}{} in fees, the balance went from {} to {}",
+            fees_paid, denom, start_cosmos, end_cosmos
+        )));
+    }
+    info!(
+        "Round trip complete, {} seconds to Ethereum and {} seconds back, {}{} paid in fees",
+        to_eth.as_secs(),
+        to_cosmos.as_secs(),
+        fees_paid,
+        denom
+    );
+    Ok(RoundTripReport {
+        to_eth,
+        to_cosmos,
+        fees_paid,
+    })
+}
+
+async fn get_cosmos_balance(
+    contact: &Contact,
+    address: CosmosAddress,
+    denom: &str,
+) -> Result<Uint256, GravityError> {
+    Ok(contact
+        .get_balance(address, denom.to_string())
+        .await?
+        .map(|c| c.amount)
+        .unwrap_or_else(|| 0u8.into()))
+}
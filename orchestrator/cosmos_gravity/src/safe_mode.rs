@@ -0,0 +1,62 @@
+//! Safe mode while the bridge is halted. Governance halts the bridge by setting the bridge_active
+//! param to false, after which the chain creates no batches or logic calls and rejects batch
+//! requests. Relaying batches and logic calls and requesting batches would only spend fees until
+//! the halt is lifted, so in safe mode the relayer skips them. Valsets are still created and relayed
+//! so the contract keeps following the validator set and the bridge can be resumed safely. Claims
+//! and confirms are still submitted, the chain keeps processing them and validators are still
+//! slashed for missing confirms. The Gravity contract has no pause of its own, so the param is
+//! the only halt there is to detect. Safe mode is left as soon as the param is set back to true
+
+use crate::query_cache::get_gravity_params_cached;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use std::sync::RwLock;
+use tonic::transport::Channel;
+
+static BRIDGE_HALTED: RwLock<bool> = RwLock::new(false);
+
+/// True while the bridge is known to be halted by governance
+pub fn bridge_halted() -> bool {
+    *BRIDGE_HALTED.read().unwrap()
+}
+
+/// Records whether the bridge is active, logging when safe mode is entered or left. Returns true if
+/// the bridge is halted
+pub fn update_bridge_halt(bridge_active: bool) -> bool {
+    let mut halted = BRIDGE_HALTED.write().unwrap();
+    if !bridge_active && !*halted {
+        warn!("The bridge has been halted by governance, entering safe mode. Batch and logic call relaying and batch requests are paused until it is resumed");
+    } else if bridge_active && *halted {
+        info!("The bridge has been resumed, leaving safe mode");
+    }
+    *halted = !bridge_active;
+    !bridge_active
+}
+
+/// Checks the bridge_active param, using the cached params. If they can not be queried the last
+/// known state is kept
+pub async fn check_bridge_halt(grpc_client: &mut GravityQueryClient<Channel>) -> bool {
+    match get_gravity_params_cached(grpc_client).await {
+        Ok(params) => update_bridge_halt(params.bridge_active),
+        Err(e) => {
+            warn!("Could not check if the bridge is halted {:?}", e);
+            bridge_halted()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_bridge_halt() {
+        assert!(!update_bridge_halt(true));
+        assert!(!bridge_halted());
+        assert!(update_bridge_halt(false));
+        assert!(bridge_halted());
+        // stays halted without logging again until resumed
+        assert!(update_bridge_halt(false));
+        assert!(!update_bridge_halt(true));
+        assert!(!bridge_halted());
+    }
+}
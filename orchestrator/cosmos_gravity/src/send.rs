@@ -1,14 +1,17 @@
 use clarity::Address as EthAddress;
-use clarity::{PrivateKey as EthPrivateKey, Signature};
+use clarity::Signature;
 use deep_space::address::Address as CosmosAddress;
 use deep_space::error::CosmosGrpcError;
 use deep_space::private_key::PrivateKey;
 use deep_space::Contact;
+use deep_space::Fee;
 use deep_space::Msg;
 use deep_space::{coin::Coin, utils::bytes_to_hex_str};
 use ethereum_gravity::message_signatures::{
-    encode_logic_call_confirm, encode_tx_batch_confirm, encode_valset_confirm,
+    encode_logic_call_confirm, encode_logic_call_confirm_hashed, encode_tx_batch_confirm,
+    encode_tx_batch_confirm_hashed, encode_valset_confirm, encode_valset_confirm_hashed,
 };
+use ethereum_gravity::signer::EthereumSigner;
 use gravity_proto::cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
 
 use gravity_proto::gravity::{
@@ -17,14 +20,29 @@ use gravity_proto::gravity::{
     MsgValsetConfirm,
 };
 
+use gravity_utils::audit_log::record_signature;
 use gravity_utils::types::*;
+use gravity_utils::watch_only::{get_watched_address, record_watched};
+use metrics_exporter::{metrics_claims_submitted, metrics_cosmos_tx_failure, metrics_signed_nonce};
 
+use serde_json::json;
+use std::sync::RwLock;
 use std::{collections::HashMap, time::Duration};
+use tracing::instrument;
 
+use crate::fee_grant::get_fee_granter;
+use crate::redundancy::{find_submitted, first_unsubmitted_claim, log_skipped, Submission};
 use crate::utils::{get_reasonable_send_to_eth_fee, BadSignatureEvidence};
 
 pub const MEMO: &str = "Sent using Althea Gravity Bridge Orchestrator";
+/// The longest memo the Cosmos SDK accepts with its default auth params
+pub const MAX_MEMO_LENGTH: usize = 256;
 pub const TIMEOUT: Duration = Duration::from_secs(60);
+/// The most claims a single oracle Tx will carry, prevents the message buffer from getting too
+/// big if a lot of events are left in a validators queue
+pub const MAX_ORACLE_MESSAGES: usize = 1000;
+/// Gas limit margin over the simulated gas used, for transactions paid for by a fee grant
+pub const FEE_GRANT_GAS_ADJUSTMENT: f64 = 1.5;
 
 // gravity msg type urls
 pub const MSG_SET_ORCHESTRATOR_ADDRESS_TYPE_URL: &str = "/gravity.v1.MsgSetOrchestratorAddress";
@@ -41,6 +59,55 @@ pub const MSG_EXECUTE_IBC_AUTO_FORWARDS_TYPE_URL: &str = "/gravity.v1.MsgExecute
 // auction msg type url
 pub const MSG_BID_TYPE_URL: &str = "/auction.v1.MsgBid";
 
+static CONFIGURED_MEMO: RwLock<Option<String>> = RwLock::new(None);
+static MEMO_VERSION_TAG: RwLock<Option<String>> = RwLock::new(None);
+
+/// Sets the memo attached to every transaction in place of MEMO, so that a deployment can identify
+/// itself on chain. None restores the default
+pub fn set_memo(memo: Option<String>) -> Result<(), CosmosGrpcError> {
+    if let Some(memo) = &memo {
+        check_memo(memo)?;
+    }
+    *CONFIGURED_MEMO.write().unwrap() = memo;
+    Ok(())
+}
+
+/// Sets a version tag such as "gbt/1.11.1+abc1234" appended to the memo, so that version
+/// adoption can be measured on chain. None removes the tag
+pub fn set_memo_version_tag(tag: Option<String>) {
+    *MEMO_VERSION_TAG.write().unwrap() = tag;
+}
+
+/// The memo attached to transactions that don't provide their own
+pub fn get_memo() -> String {
+    let memo = CONFIGURED_MEMO
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| MEMO.to_string());
+    tag_memo(memo, MEMO_VERSION_TAG.read().unwrap().as_deref())
+}
+
+/// Appends the version tag to `memo` if the result still fits in MAX_MEMO_LENGTH
+fn tag_memo(memo: String, tag: Option<&str>) -> String {
+    match tag {
+        Some(tag) if memo.is_empty() => tag.to_string(),
+        Some(tag) if memo.len() + tag.len() < MAX_MEMO_LENGTH => format!("{} {}", memo, tag),
+        _ => memo,
+    }
+}
+
+fn check_memo(memo: &str) -> Result<(), CosmosGrpcError> {
+    if memo.len() > MAX_MEMO_LENGTH {
+        return Err(CosmosGrpcError::BadInput(format!(
+            "Memo is {} characters, at most {} are allowed",
+            memo.len(),
+            MAX_MEMO_LENGTH
+        )));
+    }
+    Ok(())
+}
+
 /// Send a transaction updating the eth address for the sending
 /// Cosmos address. The sending Cosmos address should be a validator
 /// this can only be called once! Key rotation code is possible but
@@ -53,9 +120,27 @@ pub async fn set_gravity_delegate_addresses(
     fee: Coin,
 ) -> Result<TxResponse, CosmosGrpcError> {
     trace!("Updating Gravity Delegate addresses");
-    let our_valoper_address = private_key
-        .to_address(&contact.get_prefix())
-        .unwrap()
+    let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msg = set_gravity_delegate_addresses_msg(
+        contact,
+        our_address,
+        delegate_eth_address,
+        delegate_cosmos_address,
+    );
+    contact
+        .send_message(&[msg], Some(get_memo()), &[fee], Some(TIMEOUT), private_key)
+        .await
+}
+
+/// Builds the MsgSetOrchestratorAddress sent by `set_gravity_delegate_addresses` for the validator
+/// operated by `validator_address`
+pub fn set_gravity_delegate_addresses_msg(
+    contact: &Contact,
+    validator_address: CosmosAddress,
+    delegate_eth_address: EthAddress,
+    delegate_cosmos_address: CosmosAddress,
+) -> Msg {
+    let our_valoper_address = validator_address
         // This works so long as the format set by the cosmos hub is maintained
         // having a main prefix followed by a series of titles for specific keys
         // this will not work if that convention is broken. This will be resolved when
@@ -69,38 +154,72 @@ pub async fn set_gravity_delegate_addresses(
         eth_address: delegate_eth_address.to_string(),
     };
 
-    let msg = Msg::new(MSG_SET_ORCHESTRATOR_ADDRESS_TYPE_URL, msg_set_orch_address);
-    contact
-        .send_message(
-            &[msg],
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    Msg::new(MSG_SET_ORCHESTRATOR_ADDRESS_TYPE_URL, msg_set_orch_address)
 }
 
 /// Send in a confirmation for an array of validator sets, it's far more efficient to send these
 /// as a single message
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(nonces = ?valsets.iter().map(|v| v.nonce).collect::<Vec<_>>()))]
 pub async fn send_valset_confirms(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &dyn EthereumSigner,
     fee: Coin,
     valsets: Vec<Valset>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+
+    // in watch only mode checkpoints are derived and exported, but never signed
+    if get_watched_address().is_some() {
+        let nonces = valsets.iter().map(|v| v.nonce).collect();
+        let checkpoints = valsets
+            .iter()
+            .map(|v| bytes_to_hex_str(&encode_valset_confirm_hashed(gravity_id.clone(), v.clone())))
+            .collect();
+        record_watched("valset_confirm", nonces, checkpoints, &valsets);
+        return Ok(TxResponse::default());
+    }
+    let submitted = find_submitted(contact, our_address).await;
+    let count = valsets.len();
+    let valsets: Vec<Valset> = valsets
+        .into_iter()
+        .filter(|v| !submitted.contains(&Submission::ValsetConfirm { nonce: v.nonce }))
+        .collect();
+    log_skipped("valset confirms", count - valsets.len());
+    if valsets.is_empty() {
+        return Ok(TxResponse::default());
+    }
+    let our_eth_address = eth_signer.address();
 
     let mut messages = Vec::new();
+    let mut nonces = Vec::new();
+    let mut checkpoints = Vec::new();
 
     for valset in valsets {
         trace!("Submitting signature for valset {:?}", valset);
+        let checkpoint = bytes_to_hex_str(&encode_valset_confirm_hashed(
+            gravity_id.clone(),
+            valset.clone(),
+        ));
+        info!(
+            "Signing checkpoint {} for {}",
+            checkpoint,
+            valset.checkpoint_summary()
+        );
+        nonces.push(valset.nonce);
+        checkpoints.push(checkpoint);
         let message = encode_valset_confirm(gravity_id.clone(), valset.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = match eth_signer.sign_ethereum_msg(&message).await {
+            Ok(s) => s,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "Failed to sign valset {} {}",
+                    valset.nonce, e
+                )))
+            }
+        };
         trace!(
             "Sending valset update with address {} and sig {}",
             our_eth_address,
@@ -115,37 +234,84 @@ pub async fn send_valset_confirms(
         let msg = Msg::new(MSG_VALSET_CONFIRM_TYPE_URL, confirm);
         messages.push(msg);
     }
-    let res = contact
-        .send_message(
-            &messages,
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await;
+    let res =
+        send_orchestrator_messages(contact, &messages, Some(get_memo()), &[fee], private_key).await;
     info!("Valset confirm res is {:?}", res);
+    audit_tx("valset_confirm", our_address, nonces, checkpoints, &res);
     res
 }
 
 /// Send in a confirmation for a specific transaction batch
+#[instrument(skip_all, fields(nonces = ?transaction_batches.iter().map(|b| b.nonce).collect::<Vec<_>>()))]
 pub async fn send_batch_confirm(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &dyn EthereumSigner,
     fee: Coin,
     transaction_batches: Vec<TransactionBatch>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+
+    // in watch only mode checkpoints are derived and exported, but never signed
+    if get_watched_address().is_some() {
+        let nonces = transaction_batches.iter().map(|b| b.nonce).collect();
+        let checkpoints = transaction_batches
+            .iter()
+            .map(|b| {
+                bytes_to_hex_str(&encode_tx_batch_confirm_hashed(
+                    gravity_id.clone(),
+                    b.clone(),
+                ))
+            })
+            .collect();
+        record_watched("batch_confirm", nonces, checkpoints, &transaction_batches);
+        return Ok(TxResponse::default());
+    }
+    let submitted = find_submitted(contact, our_address).await;
+    let count = transaction_batches.len();
+    let transaction_batches: Vec<TransactionBatch> = transaction_batches
+        .into_iter()
+        .filter(|b| {
+            !submitted.contains(&Submission::BatchConfirm {
+                token_contract: b.token_contract.to_string().to_lowercase(),
+                nonce: b.nonce,
+            })
+        })
+        .collect();
+    log_skipped("batch confirms", count - transaction_batches.len());
+    if transaction_batches.is_empty() {
+        return Ok(TxResponse::default());
+    }
+    let our_eth_address = eth_signer.address();
 
     let mut messages = Vec::new();
+    let mut nonces = Vec::new();
+    let mut checkpoints = Vec::new();
 
     for batch in transaction_batches {
         trace!("Submitting signature for batch {:?}", batch);
+        let checkpoint = bytes_to_hex_str(&encode_tx_batch_confirm_hashed(
+            gravity_id.clone(),
+            batch.clone(),
+        ));
+        info!(
+            "Signing checkpoint {} for {}",
+            checkpoint,
+            batch.checkpoint_summary()
+        );
+        nonces.push(batch.nonce);
+        checkpoints.push(checkpoint);
         let message = encode_tx_batch_confirm(gravity_id.clone(), batch.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = match eth_signer.sign_ethereum_msg(&message).await {
+            Ok(s) => s,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "Failed to sign batch {} {}",
+                    batch.nonce, e
+                )))
+            }
+        };
         trace!(
             "Sending batch update with address {} and sig {}",
             our_eth_address,
@@ -161,35 +327,83 @@ pub async fn send_batch_confirm(
         let msg = Msg::new(MSG_CONFIRM_BATCH_TYPE_URL, confirm);
         messages.push(msg);
     }
-    contact
-        .send_message(
-            &messages,
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    let res =
+        send_orchestrator_messages(contact, &messages, Some(get_memo()), &[fee], private_key).await;
+    audit_tx("batch_confirm", our_address, nonces, checkpoints, &res);
+    res
 }
 
 /// Send in a confirmation for a specific logic call
+#[instrument(skip_all, fields(nonces = ?logic_calls.iter().map(|c| c.invalidation_nonce).collect::<Vec<_>>()))]
 pub async fn send_logic_call_confirm(
     contact: &Contact,
-    eth_private_key: EthPrivateKey,
+    eth_signer: &dyn EthereumSigner,
     fee: Coin,
     logic_calls: Vec<LogicCall>,
     private_key: impl PrivateKey,
     gravity_id: String,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-    let our_eth_address = eth_private_key.to_address();
+
+    // in watch only mode checkpoints are derived and exported, but never signed
+    if get_watched_address().is_some() {
+        let nonces = logic_calls.iter().map(|c| c.invalidation_nonce).collect();
+        let checkpoints = logic_calls
+            .iter()
+            .map(|c| {
+                bytes_to_hex_str(&encode_logic_call_confirm_hashed(
+                    gravity_id.clone(),
+                    c.clone(),
+                ))
+            })
+            .collect();
+        record_watched("logic_call_confirm", nonces, checkpoints, &logic_calls);
+        return Ok(TxResponse::default());
+    }
+    let submitted = find_submitted(contact, our_address).await;
+    let count = logic_calls.len();
+    let logic_calls: Vec<LogicCall> = logic_calls
+        .into_iter()
+        .filter(|c| {
+            !submitted.contains(&Submission::LogicCallConfirm {
+                invalidation_id: bytes_to_hex_str(&c.invalidation_id).to_lowercase(),
+                invalidation_nonce: c.invalidation_nonce,
+            })
+        })
+        .collect();
+    log_skipped("logic call confirms", count - logic_calls.len());
+    if logic_calls.is_empty() {
+        return Ok(TxResponse::default());
+    }
+    let our_eth_address = eth_signer.address();
 
     let mut messages = Vec::new();
+    let mut nonces = Vec::new();
+    let mut checkpoints = Vec::new();
 
     for call in logic_calls {
         trace!("Submitting signature for LogicCall {:?}", call);
+        let checkpoint = bytes_to_hex_str(&encode_logic_call_confirm_hashed(
+            gravity_id.clone(),
+            call.clone(),
+        ));
+        info!(
+            "Signing checkpoint {} for {}",
+            checkpoint,
+            call.checkpoint_summary()
+        );
+        nonces.push(call.invalidation_nonce);
+        checkpoints.push(checkpoint);
         let message = encode_logic_call_confirm(gravity_id.clone(), call.clone());
-        let eth_signature = eth_private_key.sign_ethereum_msg(&message);
+        let eth_signature = match eth_signer.sign_ethereum_msg(&message).await {
+            Ok(s) => s,
+            Err(e) => {
+                return Err(CosmosGrpcError::BadInput(format!(
+                    "Failed to sign logic call {} {}",
+                    call.invalidation_nonce, e
+                )))
+            }
+        };
         trace!(
             "Sending LogicCall update with address {} and sig {}",
             our_eth_address,
@@ -205,19 +419,19 @@ pub async fn send_logic_call_confirm(
         let msg = Msg::new(MSG_CONFIRM_LOGIC_CALL_TYPE_URL, confirm);
         messages.push(msg);
     }
-    contact
-        .send_message(
-            &messages,
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    let res =
+        send_orchestrator_messages(contact, &messages, Some(get_memo()), &[fee], private_key).await;
+    audit_tx("logic_call_confirm", our_address, nonces, checkpoints, &res);
+    res
 }
 
-/// Creates and submits Ethereum event claims from the input EthereumEvent collections
+/// Creates and submits Ethereum event claims from the input EthereumEvent collections. Unless `memo`
+/// is provided claims carry the configured memo and version tag like every other transaction, they
+/// are the orchestrator's most frequent transaction. Returns None if no transaction was broadcast,
+/// because every claim was already submitted by a redundant orchestrator or because claims are
+/// only being watched
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(deposit_nonces = ?deposits.iter().map(|d| d.event_nonce).collect::<Vec<_>>(), batch_nonces = ?withdraws.iter().map(|w| w.batch_nonce).collect::<Vec<_>>()))]
 pub async fn send_ethereum_claims(
     contact: &Contact,
     our_cosmos_key: impl PrivateKey,
@@ -227,9 +441,111 @@ pub async fn send_ethereum_claims(
     logic_calls: Vec<LogicCallExecutedEvent>,
     valsets: Vec<ValsetUpdatedEvent>,
     fee: Coin,
-) -> Result<TxResponse, CosmosGrpcError> {
+    memo: Option<String>,
+) -> Result<Option<TxResponse>, CosmosGrpcError> {
     let our_cosmos_address = our_cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    // in watch only mode claims are derived and exported, but never sent
+    if get_watched_address().is_some() {
+        let items = json!({
+            "deposits": deposits,
+            "withdraws": withdraws,
+            "erc20_deploys": erc20_deploys,
+            "logic_calls": logic_calls,
+            "valsets": valsets,
+        });
+        let (keys, _) = order_claim_msgs(
+            our_cosmos_address,
+            deposits,
+            withdraws,
+            erc20_deploys,
+            logic_calls,
+            valsets,
+        );
+        record_watched("ethereum_claims", keys, Vec::new(), &items);
+        return Ok(None);
+    }
+    // claims a redundant orchestrator already submitted are left out, the oracle moves on once
+    // this returns so the other instance's transaction is relied on to deliver them. The chain only
+    // accepts a claim whose nonce follows the last one it accepted from us, so only a contiguous
+    // run of the lowest nonces is left out, anything after a gap is sent along with the gap
+    let submitted = find_submitted(contact, our_cosmos_address).await;
+    let mut nonces: Vec<u64> = deposits
+        .iter()
+        .map(|e| e.get_event_nonce())
+        .chain(withdraws.iter().map(|e| e.get_event_nonce()))
+        .chain(erc20_deploys.iter().map(|e| e.get_event_nonce()))
+        .chain(logic_calls.iter().map(|e| e.get_event_nonce()))
+        .chain(valsets.iter().map(|e| e.get_event_nonce()))
+        .collect();
+    nonces.sort_unstable();
+    let first_new = first_unsubmitted_claim(&nonces, &submitted);
+    let is_new = |nonce: u64| first_new.map(|first| nonce >= first).unwrap_or(false);
+    let count =
+        deposits.len() + withdraws.len() + erc20_deploys.len() + logic_calls.len() + valsets.len();
+    let deposits: Vec<_> = deposits
+        .into_iter()
+        .filter(|e| is_new(e.get_event_nonce()))
+        .collect();
+    let withdraws: Vec<_> = withdraws
+        .into_iter()
+        .filter(|e| is_new(e.get_event_nonce()))
+        .collect();
+    let erc20_deploys: Vec<_> = erc20_deploys
+        .into_iter()
+        .filter(|e| is_new(e.get_event_nonce()))
+        .collect();
+    let logic_calls: Vec<_> = logic_calls
+        .into_iter()
+        .filter(|e| is_new(e.get_event_nonce()))
+        .collect();
+    let valsets: Vec<_> = valsets
+        .into_iter()
+        .filter(|e| is_new(e.get_event_nonce()))
+        .collect();
+    let remaining =
+        deposits.len() + withdraws.len() + erc20_deploys.len() + logic_calls.len() + valsets.len();
+    log_skipped("claims", count - remaining);
+    if remaining == 0 && count != 0 {
+        return Ok(None);
+    }
+    let (keys, msgs) = order_claim_msgs(
+        our_cosmos_address,
+        deposits,
+        withdraws,
+        erc20_deploys,
+        logic_calls,
+        valsets,
+    );
 
+    let memo = match memo {
+        Some(memo) => {
+            check_memo(&memo)?;
+            Some(memo)
+        }
+        None => Some(get_memo()),
+    };
+    let res = send_orchestrator_messages(contact, &msgs, memo, &[fee], our_cosmos_key).await;
+    audit_tx(
+        "ethereum_claims",
+        our_cosmos_address,
+        keys,
+        Vec::new(),
+        &res,
+    );
+    res.map(Some)
+}
+
+/// Converts Ethereum events into claim Msgs ordered by event nonce, at most MAX_ORACLE_MESSAGES
+/// of them. Returns the event nonces of the claims alongside the Msgs. Separated out of
+/// send_ethereum_claims so that it can be benchmarked
+pub fn order_claim_msgs(
+    orchestrator: CosmosAddress,
+    deposits: Vec<SendToCosmosEvent>,
+    withdraws: Vec<TransactionBatchExecutedEvent>,
+    erc20_deploys: Vec<Erc20DeployedEvent>,
+    logic_calls: Vec<LogicCallExecutedEvent>,
+    valsets: Vec<ValsetUpdatedEvent>,
+) -> (Vec<u64>, Vec<Msg>) {
     // This sorts oracle messages by event nonce before submitting them. It's not a pretty implementation because
     // we're missing an intermediary layer of abstraction. We could implement 'EventTrait' and then implement sort
     // for it, but then when we go to transform 'EventTrait' objects into GravityMsg enum values we'll have all sorts
@@ -242,11 +558,11 @@ pub async fn send_ethereum_claims(
 
     // Create claim Msgs, keeping their event_nonces for insertion into unordered_msgs
 
-    let deposit_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(deposits, our_cosmos_address);
-    let withdraw_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(withdraws, our_cosmos_address);
-    let deploy_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(erc20_deploys, our_cosmos_address);
-    let logic_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(logic_calls, our_cosmos_address);
-    let valset_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(valsets, our_cosmos_address);
+    let deposit_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(deposits, orchestrator);
+    let withdraw_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(withdraws, orchestrator);
+    let deploy_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(erc20_deploys, orchestrator);
+    let logic_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(logic_calls, orchestrator);
+    let valset_nonces_msgs: Vec<(u64, Msg)> = create_claim_msgs(valsets, orchestrator);
 
     // Collect all of the claims into an iterator, then add them to unordered_msgs
     deposit_nonces_msgs
@@ -265,11 +581,10 @@ pub async fn send_ethereum_claims(
     // sorts ascending by default
     keys.sort_unstable();
 
-    const MAX_ORACLE_MESSAGES: usize = 1000;
     let mut msgs = Vec::new();
-    for i in keys {
+    for i in keys.iter() {
         // pushes messages with a later nonce onto the end
-        msgs.push(unordered_msgs.remove_entry(&i).unwrap().1);
+        msgs.push(unordered_msgs.remove_entry(i).unwrap().1);
     }
     // prevents the message buffer from getting too big if a lot of events
     // are left in a validators queue
@@ -277,12 +592,75 @@ pub async fn send_ethereum_claims(
         // pops messages off of the end
         msgs.pop();
     }
+    keys.truncate(msgs.len());
 
+    (keys, msgs)
+}
+
+/// Sends messages signed by the orchestrator key, if a fee granter has been set with
+/// `set_fee_granter` the fee is paid out of its grant rather than by the orchestrator
+async fn send_orchestrator_messages(
+    contact: &Contact,
+    messages: &[Msg],
+    memo: Option<String>,
+    fee: &[Coin],
+    private_key: impl PrivateKey,
+) -> Result<TxResponse, CosmosGrpcError> {
+    let granter = match get_fee_granter() {
+        Some(g) => g,
+        None => {
+            return contact
+                .send_message(messages, memo, fee, Some(TIMEOUT), private_key)
+                .await
+        }
+    };
+    let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let gas = contact
+        .simulate_tx(messages, Some(fee), private_key.clone())
+        .await?
+        .gas_info
+        .map(|g| g.gas_used)
+        .unwrap_or_default();
+    let fee = Fee {
+        amount: fee.to_vec(),
+        gas_limit: (gas as f64 * FEE_GRANT_GAS_ADJUSTMENT) as u64,
+        granter: Some(granter.to_string()),
+        payer: None,
+    };
+    let args = contact.get_message_args(our_address, fee).await?;
     contact
-        .send_message(&msgs, None, &[fee], Some(TIMEOUT), our_cosmos_key)
+        .send_message_with_args(messages, memo, args, Some(TIMEOUT), private_key)
         .await
 }
 
+/// Records a signed transaction in the signature audit log and the metrics
+fn audit_tx(
+    kind: &str,
+    signer: CosmosAddress,
+    nonces: Vec<u64>,
+    checkpoints: Vec<String>,
+    res: &Result<TxResponse, CosmosGrpcError>,
+) {
+    let last_nonce = nonces.iter().max().copied().unwrap_or_default();
+    match (res, kind) {
+        (Ok(_), "ethereum_claims") => metrics_claims_submitted(nonces.len() as u64, last_nonce),
+        (Ok(_), _) => metrics_signed_nonce(kind, last_nonce),
+        (Err(_), _) => metrics_cosmos_tx_failure(kind),
+    }
+    let (tx_hash, error) = match res {
+        Ok(tx) => (Some(tx.txhash.clone()), None),
+        Err(e) => (None, Some(format!("{:?}", e))),
+    };
+    record_signature(
+        kind,
+        signer.to_string(),
+        nonces,
+        checkpoints,
+        tx_hash,
+        error,
+    );
+}
+
 /// Creates the `Msg`s needed for `orchestrator` to attest to `events`
 /// Returns a Vec of (event_nonce: u64, Msg), which will contain one (nonce, msg) per event
 fn create_claim_msgs(
@@ -304,6 +682,9 @@ fn create_claim_msgs(
 ///     must also meet the governance-defined minimum percentage of the amount
 /// cosmos_fee: the Cosmos anti-spam fee set by each Validator which is required for any Tx
 ///     to be considered for the mempool.
+/// memo: attached to the transaction in place of the configured memo, for attribution by
+///     exchanges and analytics
+#[allow(clippy::too_many_arguments)]
 pub async fn send_to_eth(
     private_key: impl PrivateKey,
     destination: EthAddress,
@@ -312,8 +693,42 @@ pub async fn send_to_eth(
     chain_fee: Option<Coin>,
     fee: Coin,
     contact: &Contact,
+    memo: Option<String>,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let memo = match memo {
+        Some(memo) => {
+            check_memo(&memo)?;
+            memo
+        }
+        None => get_memo(),
+    };
+    let msg = send_to_eth_msg(
+        our_address,
+        destination,
+        amount,
+        bridge_fee,
+        chain_fee,
+        &fee,
+        contact,
+    )
+    .await?;
+    contact
+        .send_message(&[msg], Some(memo), &[fee], Some(TIMEOUT), private_key)
+        .await
+}
+
+/// Builds the MsgSendToEth sent by `send_to_eth`, computing the chain fee if none is provided and
+/// checking that the sender can pay for the amount, the fees and the Cosmos fee
+pub async fn send_to_eth_msg(
+    our_address: CosmosAddress,
+    destination: EthAddress,
+    amount: Coin,
+    bridge_fee: Coin,
+    chain_fee: Option<Coin>,
+    fee: &Coin,
+    contact: &Contact,
+) -> Result<Msg, CosmosGrpcError> {
     if amount.denom != bridge_fee.denom {
         return Err(CosmosGrpcError::BadInput(format!(
             "{} {} is an invalid denom set for SendToEth you must pay ethereum fees in the same token your sending",
@@ -356,8 +771,26 @@ pub async fn send_to_eth(
         )));
     }
 
+    Ok(build_send_to_eth_msg(
+        our_address,
+        destination,
+        amount,
+        bridge_fee,
+        chain_fee,
+    ))
+}
+
+/// Builds a MsgSendToEth without any of the checks `send_to_eth_msg` makes against the chain, for
+/// transactions constructed offline
+pub fn build_send_to_eth_msg(
+    sender: CosmosAddress,
+    destination: EthAddress,
+    amount: Coin,
+    bridge_fee: Coin,
+    chain_fee: Coin,
+) -> Msg {
     let msg_send_to_eth = MsgSendToEth {
-        sender: our_address.to_string(),
+        sender: sender.to_string(),
         eth_dest: destination.to_string(),
         amount: Some(amount.into()),
         bridge_fee: Some(bridge_fee.into()),
@@ -368,16 +801,7 @@ pub async fn send_to_eth(
         msg_send_to_eth
     );
 
-    let msg = Msg::new(MSG_SEND_TO_ETH_TYPE_URL, msg_send_to_eth);
-    contact
-        .send_message(
-            &[msg],
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    Msg::new(MSG_SEND_TO_ETH_TYPE_URL, msg_send_to_eth)
 }
 
 pub async fn send_request_batch(
@@ -387,26 +811,38 @@ pub async fn send_request_batch(
     contact: &Contact,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msg = request_batch_msg(our_address, denom);
 
+    let fee: Vec<Coin> = match fee {
+        Some(fee) => vec![fee],
+        None => vec![],
+    };
+    send_orchestrator_messages(contact, &[msg], Some(get_memo()), &fee, private_key).await
+}
+
+/// Builds the MsgRequestBatch sent by `send_request_batch`
+pub fn request_batch_msg(sender: CosmosAddress, denom: String) -> Msg {
     let msg_request_batch = MsgRequestBatch {
-        sender: our_address.to_string(),
+        sender: sender.to_string(),
         denom,
     };
-    let msg = Msg::new(MSG_REQUEST_BATCH_TYPE_URL, msg_request_batch);
+    Msg::new(MSG_REQUEST_BATCH_TYPE_URL, msg_request_batch)
+}
 
-    let fee: Vec<Coin> = match fee {
-        Some(fee) => vec![fee],
-        None => vec![],
+fn bad_signature_evidence_msg(
+    sender: CosmosAddress,
+    signed_object: &BadSignatureEvidence,
+    signature: &Signature,
+) -> Msg {
+    let msg_submit_bad_signature_evidence = MsgSubmitBadSignatureEvidence {
+        subject: Some(signed_object.to_any()),
+        signature: bytes_to_hex_str(&signature.to_bytes()),
+        sender: sender.to_string(),
     };
-    contact
-        .send_message(
-            &[msg],
-            Some(MEMO.to_string()),
-            &fee,
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    Msg::new(
+        MSG_SUBMIT_BAD_SIGNATURE_EVIDENCE_TYPE_URL,
+        msg_submit_bad_signature_evidence,
+    )
 }
 
 /// Sends evidence of a bad signature to the chain to slash the malicious validator
@@ -419,30 +855,27 @@ pub async fn submit_bad_signature_evidence(
     signature: Signature,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
-
-    let any = signed_object.to_any();
-
-    let msg_submit_bad_signature_evidence = MsgSubmitBadSignatureEvidence {
-        subject: Some(any),
-        signature: bytes_to_hex_str(&signature.to_bytes()),
-        sender: our_address.to_string(),
-    };
-
-    let msg = Msg::new(
-        MSG_SUBMIT_BAD_SIGNATURE_EVIDENCE_TYPE_URL,
-        msg_submit_bad_signature_evidence,
-    );
+    let msg = bad_signature_evidence_msg(our_address, &signed_object, &signature);
     contact
-        .send_message(
-            &[msg],
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
+        .send_message(&[msg], Some(get_memo()), &[fee], Some(TIMEOUT), private_key)
         .await
 }
 
+/// Simulates submitting evidence of a bad signature without broadcasting it, this succeeds only if
+/// the chain would accept the evidence, meaning the signed checkpoint never existed on Cosmos
+pub async fn simulate_bad_signature_evidence(
+    private_key: impl PrivateKey,
+    contact: &Contact,
+    signed_object: &BadSignatureEvidence,
+    signature: &Signature,
+) -> Result<(), CosmosGrpcError> {
+    let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msg = bad_signature_evidence_msg(our_address, signed_object, signature);
+    // simulations are not subject to the minimum fee, no fee is needed to check the evidence
+    contact.simulate_tx(&[msg], None, private_key).await?;
+    Ok(())
+}
+
 /// Cancels a user provided SendToEth transaction, provided it's not already in a batch
 /// you should check with `QueryPendingSendToEth`
 pub async fn cancel_send_to_eth(
@@ -452,22 +885,19 @@ pub async fn cancel_send_to_eth(
     transaction_id: u64,
 ) -> Result<TxResponse, CosmosGrpcError> {
     let our_address = private_key.to_address(&contact.get_prefix()).unwrap();
+    let msg = cancel_send_to_eth_msg(our_address, transaction_id);
+    contact
+        .send_message(&[msg], Some(get_memo()), &[fee], Some(TIMEOUT), private_key)
+        .await
+}
 
+/// Builds the MsgCancelSendToEth sent by `cancel_send_to_eth`
+pub fn cancel_send_to_eth_msg(sender: CosmosAddress, transaction_id: u64) -> Msg {
     let msg_cancel_send_to_eth = MsgCancelSendToEth {
         transaction_id,
-        sender: our_address.to_string(),
+        sender: sender.to_string(),
     };
-
-    let msg = Msg::new(MSG_CANCEL_SEND_TO_ETH_TYPE_URL, msg_cancel_send_to_eth);
-    contact
-        .send_message(
-            &[msg],
-            Some(MEMO.to_string()),
-            &[fee],
-            Some(TIMEOUT),
-            private_key,
-        )
-        .await
+    Msg::new(MSG_CANCEL_SEND_TO_ETH_TYPE_URL, msg_cancel_send_to_eth)
 }
 
 /// Executes a MsgExecuteIbcAutoForwards on the gravity chain, which will process forwards_to_clear number of pending ibc auto forwards
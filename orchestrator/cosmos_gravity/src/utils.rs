@@ -41,6 +41,7 @@ pub async fn get_last_event_nonce_with_retry(
     res.unwrap()
 }
 
+#[derive(Debug, Clone)]
 pub enum BadSignatureEvidence {
     Valset(Valset),
     Batch(TransactionBatch),
@@ -0,0 +1,327 @@
+//! Decodes the calldata of updateValset, submitBatch and submitLogicCall transactions sent to the
+//! Gravity contract, recovering the item that was signed and the validator signatures that
+//! approved it. This is the inverse of the payload encoding in valset_update, submit_batch and
+//! logic_call and is used to inspect what was actually executed on Ethereum.
+
+use crate::logic_call::SUBMIT_LOGIC_CALL_SIGNATURE;
+use crate::submit_batch::SUBMIT_BATCH_SIGNATURE;
+use crate::valset_update::UPDATE_VALSET_SIGNATURE;
+use clarity::constants::zero_address;
+use clarity::Signature as EthSignature;
+use clarity::{Address as EthAddress, Uint256};
+use deep_space::Address as CosmosAddress;
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::downcast_uint256;
+use gravity_utils::types::{
+    BatchTransaction, Erc20Token, LogicCall, SigWithAddress, TransactionBatch, Valset, ValsetMember,
+};
+use sha3::{Digest, Keccak256};
+
+/// A Gravity contract call decoded from its calldata
+#[derive(Debug, Clone)]
+pub enum GravityCall {
+    ValsetUpdate {
+        new_valset: Valset,
+        current_valset: Valset,
+        signatures: Vec<SigWithAddress>,
+    },
+    SubmitBatch {
+        batch: TransactionBatch,
+        current_valset: Valset,
+        signatures: Vec<SigWithAddress>,
+    },
+    SubmitLogicCall {
+        call: LogicCall,
+        current_valset: Valset,
+        signatures: Vec<SigWithAddress>,
+    },
+}
+
+fn method_id(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn malformed(reason: &str) -> GravityError {
+    GravityError::EthereumContractError(format!("Malformed Gravity calldata: {}", reason))
+}
+
+/// A view over ABI encoded arguments, all positions are byte offsets into `data`
+struct AbiReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AbiReader<'a> {
+    fn word(&self, pos: usize) -> Result<&'a [u8], GravityError> {
+        match pos.checked_add(32) {
+            Some(end) if end <= self.data.len() => Ok(&self.data[pos..end]),
+            _ => Err(malformed("read past the end of the calldata")),
+        }
+    }
+
+    fn uint(&self, pos: usize) -> Result<Uint256, GravityError> {
+        Ok(Uint256::from_be_bytes(self.word(pos)?))
+    }
+
+    fn u64(&self, pos: usize) -> Result<u64, GravityError> {
+        downcast_uint256(self.uint(pos)?).ok_or_else(|| malformed("value does not fit in a u64"))
+    }
+
+    fn usize(&self, pos: usize) -> Result<usize, GravityError> {
+        let value = self.u64(pos)?;
+        if value > self.data.len() as u64 {
+            return Err(malformed("offset or length larger than the calldata"));
+        }
+        Ok(value as usize)
+    }
+
+    fn address(&self, pos: usize) -> Result<EthAddress, GravityError> {
+        let word = self.word(pos)?;
+        EthAddress::from_slice(&word[12..]).map_err(|_| malformed("invalid address"))
+    }
+
+    /// Follows the offset stored at `pos`, offsets are relative to the start of the enclosing
+    /// tuple `base`
+    fn offset(&self, base: usize, pos: usize) -> Result<usize, GravityError> {
+        base.checked_add(self.usize(pos)?)
+            .ok_or_else(|| malformed("offset overflow"))
+    }
+
+    fn uint_array(&self, pos: usize) -> Result<Vec<Uint256>, GravityError> {
+        let len = self.usize(pos)?;
+        (0..len).map(|i| self.uint(pos + 32 + i * 32)).collect()
+    }
+
+    fn address_array(&self, pos: usize) -> Result<Vec<EthAddress>, GravityError> {
+        let len = self.usize(pos)?;
+        (0..len).map(|i| self.address(pos + 32 + i * 32)).collect()
+    }
+
+    fn bytes(&self, pos: usize) -> Result<Vec<u8>, GravityError> {
+        let len = self.usize(pos)?;
+        let start = pos + 32;
+        if start + len > self.data.len() {
+            return Err(malformed("bytes run past the end of the calldata"));
+        }
+        Ok(self.data[start..start + len].to_vec())
+    }
+
+    /// Decodes a ValsetArgs struct starting at `pos`
+    fn valset(&self, pos: usize) -> Result<Valset, GravityError> {
+        let validators = self.address_array(self.offset(pos, pos)?)?;
+        let powers = self.uint_array(self.offset(pos, pos + 32)?)?;
+        if validators.len() != powers.len() {
+            return Err(malformed("valset validators and powers differ in length"));
+        }
+        let mut members = Vec::new();
+        for (eth_address, power) in validators.into_iter().zip(powers) {
+            members.push(ValsetMember {
+                eth_address,
+                power: downcast_uint256(power).ok_or_else(|| malformed("invalid power"))?,
+            });
+        }
+        let reward_token = self.address(pos + 128)?;
+        Ok(Valset {
+            members,
+            nonce: self.u64(pos + 64)?,
+            reward_amount: self.uint(pos + 96)?,
+            reward_token: if reward_token == zero_address() {
+                None
+            } else {
+                Some(reward_token)
+            },
+        })
+    }
+
+    /// Decodes a Signature[] array starting at `pos`, pairing each signature with the member of
+    /// `valset` in the same position. Members who did not sign are submitted with zeroed
+    /// signatures and are skipped.
+    fn signatures(&self, pos: usize, valset: &Valset) -> Result<Vec<SigWithAddress>, GravityError> {
+        let len = self.usize(pos)?;
+        if len != valset.members.len() {
+            return Err(malformed(
+                "signature count does not match the current valset",
+            ));
+        }
+        let mut out = Vec::new();
+        for (i, member) in valset.members.iter().enumerate() {
+            let sig_pos = pos + 32 + i * 96;
+            let v = self.uint(sig_pos)?;
+            let parity = if v == 27u8.into() {
+                false
+            } else if v == 28u8.into() {
+                true
+            } else {
+                continue;
+            };
+            out.push(SigWithAddress {
+                eth_address: member.eth_address,
+                eth_signature: EthSignature::ModernSignature {
+                    v: parity,
+                    r: self.uint(sig_pos + 32)?,
+                    s: self.uint(sig_pos + 64)?,
+                },
+            });
+        }
+        Ok(out)
+    }
+}
+
+fn tokens(
+    amounts: Vec<Uint256>,
+    contracts: Vec<EthAddress>,
+) -> Result<Vec<Erc20Token>, GravityError> {
+    if amounts.len() != contracts.len() {
+        return Err(malformed("token amounts and contracts differ in length"));
+    }
+    Ok(amounts
+        .into_iter()
+        .zip(contracts)
+        .map(|(amount, token_contract_address)| Erc20Token {
+            amount,
+            token_contract_address,
+        })
+        .collect())
+}
+
+/// Decodes the calldata of a transaction sent to the Gravity contract, returns None if it is not
+/// a call to updateValset, submitBatch or submitLogicCall. Batch transactions do not carry their
+/// Cosmos sender, id or total fee on Ethereum, none of these are part of the checkpoint so ids are
+/// numbered from zero, the total fee is zero and `placeholder_sender` is used for every transaction.
+pub fn decode_gravity_call(
+    input: &[u8],
+    placeholder_sender: CosmosAddress,
+) -> Result<Option<GravityCall>, GravityError> {
+    if input.len() < 4 {
+        return Ok(None);
+    }
+    let (selector, args) = input.split_at(4);
+    let reader = AbiReader { data: args };
+
+    if selector == method_id(UPDATE_VALSET_SIGNATURE) {
+        let new_valset = reader.valset(reader.offset(0, 0)?)?;
+        let current_valset = reader.valset(reader.offset(0, 32)?)?;
+        let signatures = reader.signatures(reader.offset(0, 64)?, &current_valset)?;
+        Ok(Some(GravityCall::ValsetUpdate {
+            new_valset,
+            current_valset,
+            signatures,
+        }))
+    } else if selector == method_id(SUBMIT_BATCH_SIGNATURE) {
+        let current_valset = reader.valset(reader.offset(0, 0)?)?;
+        let signatures = reader.signatures(reader.offset(0, 32)?, &current_valset)?;
+        let amounts = reader.uint_array(reader.offset(0, 64)?)?;
+        let destinations = reader.address_array(reader.offset(0, 96)?)?;
+        let fees = reader.uint_array(reader.offset(0, 128)?)?;
+        let token_contract = reader.address(192)?;
+        if amounts.len() != destinations.len() || amounts.len() != fees.len() {
+            return Err(malformed(
+                "batch amounts, destinations and fees differ in length",
+            ));
+        }
+        let mut transactions = Vec::new();
+        for (i, ((amount, destination), fee)) in
+            amounts.into_iter().zip(destinations).zip(fees).enumerate()
+        {
+            transactions.push(BatchTransaction {
+                id: i as u64,
+                sender: placeholder_sender,
+                destination,
+                erc20_token: Erc20Token {
+                    amount,
+                    token_contract_address: token_contract,
+                },
+                erc20_fee: Erc20Token {
+                    amount: fee,
+                    token_contract_address: token_contract,
+                },
+            });
+        }
+        Ok(Some(GravityCall::SubmitBatch {
+            batch: TransactionBatch {
+                nonce: reader.u64(160)?,
+                batch_timeout: reader.u64(224)?,
+                transactions,
+                // the total fee is not part of the checkpoint
+                total_fee: Erc20Token {
+                    amount: 0u8.into(),
+                    token_contract_address: token_contract,
+                },
+                token_contract,
+            },
+            current_valset,
+            signatures,
+        }))
+    } else if selector == method_id(SUBMIT_LOGIC_CALL_SIGNATURE) {
+        let current_valset = reader.valset(reader.offset(0, 0)?)?;
+        let signatures = reader.signatures(reader.offset(0, 32)?, &current_valset)?;
+        let base = reader.offset(0, 64)?;
+        let transfers = tokens(
+            reader.uint_array(reader.offset(base, base)?)?,
+            reader.address_array(reader.offset(base, base + 32)?)?,
+        )?;
+        let fees = tokens(
+            reader.uint_array(reader.offset(base, base + 64)?)?,
+            reader.address_array(reader.offset(base, base + 96)?)?,
+        )?;
+        Ok(Some(GravityCall::SubmitLogicCall {
+            call: LogicCall {
+                transfers,
+                fees,
+                logic_contract_address: reader.address(base + 128)?,
+                payload: reader.bytes(reader.offset(base, base + 160)?)?,
+                timeout: reader.u64(base + 192)?,
+                invalidation_id: reader.word(base + 224)?.to_vec(),
+                invalidation_nonce: reader.u64(base + 256)?,
+            },
+            current_valset,
+            signatures,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message_signatures::encode_valset_confirm_hashed;
+    use clarity::utils::hex_str_to_bytes;
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_decode_valset_update() {
+        let sender = "gravity1g0etv93428tvxqftnmj25jn06mz6dtda5zxt8k"
+            .parse()
+            .unwrap();
+        let calldata = read_to_string("test_files/valset_update_rlp").unwrap();
+        let calldata = hex_str_to_bytes(&calldata).unwrap();
+
+        let (new_valset, current_valset, signatures) =
+            match decode_gravity_call(&calldata, sender).unwrap() {
+                Some(GravityCall::ValsetUpdate {
+                    new_valset,
+                    current_valset,
+                    signatures,
+                }) => (new_valset, current_valset, signatures),
+                v => panic!("Decoded the wrong call {:?}", v),
+            };
+        assert_eq!(new_valset.nonce, 1);
+        assert_eq!(current_valset.nonce, 0);
+        assert_eq!(new_valset.members.len(), current_valset.members.len());
+        assert_eq!(signatures.len(), current_valset.members.len());
+
+        let checkpoint = encode_valset_confirm_hashed("foo".to_string(), new_valset);
+        for sig in signatures {
+            assert_eq!(
+                sig.eth_signature.recover(&checkpoint).unwrap(),
+                sig.eth_address
+            );
+        }
+
+        assert!(decode_gravity_call(&calldata[..100], sender).is_err());
+        assert!(decode_gravity_call(&[0, 1, 2, 3, 4], sender)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -2,6 +2,7 @@
 //! the event for this deployment is then ferried over to Cosmos where the validators will accept the ERC20 contract address
 //! as the representation of this asset on Ethereum
 
+use crate::utils::GasCost;
 use clarity::{
     abi::{encode_call, AbiToken as Token},
     Uint256,
@@ -9,7 +10,10 @@ use clarity::{
 use clarity::{Address, PrivateKey};
 use gravity_utils::error::GravityError;
 use std::time::Duration;
-use web30::{client::Web3, types::SendTxOption};
+use web30::{
+    client::Web3,
+    types::{SendTxOption, TransactionRequest},
+};
 
 /// Calls the Gravity ethereum contract to deploy the ERC20 representation of the given Cosmos asset
 /// denom. If an existing contract is already deployed representing this asset this call will cost
@@ -30,15 +34,7 @@ pub async fn deploy_erc20(
         .send_prepared_transaction(
             web3.prepare_transaction(
                 gravity_contract,
-                encode_call(
-                    "deployERC20(string,string,string,uint8)",
-                    &[
-                        Token::String(cosmos_denom),
-                        Token::String(erc20_name),
-                        Token::String(erc20_symbol),
-                        decimals.into(),
-                    ],
-                )?,
+                encode_deploy_erc20_payload(cosmos_denom, erc20_name, erc20_symbol, decimals)?,
                 0u32.into(),
                 sender_secret,
                 options,
@@ -53,3 +49,43 @@ pub async fn deploy_erc20(
 
     Ok(tx_hash)
 }
+
+/// Estimates the gas `deploy_erc20` would use and the max fee per gas it should be sent with, the
+/// current gas price padded by 50% to allow for the base fee rising before the transaction lands.
+/// Errors if the deployment would revert
+pub async fn estimate_deploy_erc20_cost(
+    payload: Vec<u8>,
+    gravity_contract: Address,
+    sender: Address,
+    web3: &Web3,
+) -> Result<GasCost, GravityError> {
+    let gas = web3
+        .eth_estimate_gas(TransactionRequest::quick_tx(
+            sender,
+            gravity_contract,
+            payload,
+        ))
+        .await?;
+    let gas_price = web3.eth_gas_price().await?;
+    // increase the value by 50% without using floating point multiplication
+    let gas_price = gas_price + (gas_price / 2u8.into());
+    Ok(GasCost { gas, gas_price })
+}
+
+/// Encodes the deployERC20 call made by `deploy_erc20`
+pub fn encode_deploy_erc20_payload(
+    cosmos_denom: String,
+    erc20_name: String,
+    erc20_symbol: String,
+    decimals: u32,
+) -> Result<Vec<u8>, GravityError> {
+    Ok(encode_call(
+        "deployERC20(string,string,string,uint8)",
+        &[
+            Token::String(cosmos_denom),
+            Token::String(erc20_name),
+            Token::String(erc20_symbol),
+            decimals.into(),
+        ],
+    )?)
+}
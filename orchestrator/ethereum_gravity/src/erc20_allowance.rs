@@ -0,0 +1,169 @@
+//! Helper functions for managing the ERC20 allowance the Gravity contract needs to pull tokens
+//! for sendToCosmos
+
+use clarity::abi::encode_call;
+use clarity::PrivateKey as EthPrivateKey;
+use clarity::{Address, Uint256};
+use gravity_utils::error::GravityError;
+use std::time::Duration;
+use web30::client::Web3;
+use web30::jsonrpc::error::Web3Error;
+use web30::types::{SendTxOption, TransactionRequest};
+
+pub const APPROVE_GAS_LIMIT: u128 = 100_000;
+
+/// Gets the amount of `erc20` that `owner` has allowed `spender` to transfer
+pub async fn get_erc20_allowance(
+    erc20: Address,
+    owner: Address,
+    spender: Address,
+    web3: &Web3,
+) -> Result<Uint256, Web3Error> {
+    let payload = encode_call(
+        "allowance(address,address)",
+        &[owner.into(), spender.into()],
+    )
+    .unwrap();
+    let request = TransactionRequest::quick_tx(owner, erc20, payload);
+    let val = web3.simulate_transaction(request, None).await?;
+    if val.len() < 32 {
+        return Err(Web3Error::BadResponse(format!(
+            "Invalid allowance response from {}",
+            erc20
+        )));
+    }
+    Ok(Uint256::from_be_bytes(&val[0..32]))
+}
+
+/// The largest possible allowance, approving it is the usual way to grant an unlimited allowance
+pub fn unlimited_allowance() -> Uint256 {
+    Uint256::from_be_bytes(&[0xff; 32])
+}
+
+/// Whether an allowance is so large it will never run out, tokens such as WETH never decrease an
+/// allowance of the maximum value and others decrease it so slowly it makes no difference
+pub fn is_unlimited_allowance(allowance: Uint256) -> bool {
+    let mut half = [0u8; 32];
+    half[0] = 0x80;
+    allowance >= Uint256::from_be_bytes(&half)
+}
+
+/// Encodes an ERC20 approve call allowing `spender` to transfer `amount`
+pub fn encode_approve_payload(spender: Address, amount: Uint256) -> Result<Vec<u8>, GravityError> {
+    Ok(encode_call(
+        "approve(address,uint256)",
+        &[spender.into(), amount.into()],
+    )?)
+}
+
+/// Sets the allowance of `erc20` toward the Gravity contract to exactly `amount`, zero revokes it.
+/// Some tokens, most notably USDT, refuse to change one non zero allowance to another, so an existing
+/// allowance is revoked first in that case. Returns the hash of the final approval
+pub async fn set_erc20_allowance(
+    erc20: Address,
+    gravity_contract: Address,
+    amount: Uint256,
+    sender_secret: EthPrivateKey,
+    wait_timeout: Option<Duration>,
+    web3: &Web3,
+    options: Vec<SendTxOption>,
+) -> Result<Uint256, GravityError> {
+    let sender_address = sender_secret.to_address();
+    for option in options.iter() {
+        if let SendTxOption::Nonce(_) = option {
+            return Err(GravityError::InvalidOptionsError(
+                "This call may send more than one tx! Can't specify".to_string(),
+            ));
+        }
+    }
+    let mut options = options;
+    if !options
+        .iter()
+        .any(|o| matches!(o, SendTxOption::GasLimit(_)))
+    {
+        options.push(SendTxOption::GasLimit(APPROVE_GAS_LIMIT.into()));
+    }
+
+    let current = get_erc20_allowance(erc20, sender_address, gravity_contract, web3).await?;
+    let mut nonce = web3.eth_get_transaction_count(sender_address).await?;
+    if current != 0u8.into() && amount != 0u8.into() && current != amount {
+        let mut reset_options = options.clone();
+        reset_options.push(SendTxOption::Nonce(nonce));
+        let txid = send_approve(
+            erc20,
+            gravity_contract,
+            0u8.into(),
+            sender_secret,
+            web3,
+            reset_options,
+        )
+        .await?;
+        info!(
+            "Revoking the existing allowance of {} before setting a new one, txid: {:#066x}",
+            current, txid
+        );
+        // the new approval goes in immediately behind the revocation
+        nonce += 1u8.into();
+    }
+
+    options.push(SendTxOption::Nonce(nonce));
+    let txid = send_approve(
+        erc20,
+        gravity_contract,
+        amount,
+        sender_secret,
+        web3,
+        options,
+    )
+    .await?;
+    if let Some(timeout) = wait_timeout {
+        web3.wait_for_transaction(txid, timeout, None).await?;
+    }
+    Ok(txid)
+}
+
+async fn send_approve(
+    erc20: Address,
+    spender: Address,
+    amount: Uint256,
+    sender_secret: EthPrivateKey,
+    web3: &Web3,
+    options: Vec<SendTxOption>,
+) -> Result<Uint256, GravityError> {
+    Ok(web3
+        .send_prepared_transaction(
+            web3.prepare_transaction(
+                erc20,
+                encode_approve_payload(spender, amount)?,
+                0u32.into(),
+                sender_secret,
+                options,
+            )
+            .await?,
+        )
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::constants::zero_address;
+
+    #[test]
+    fn test_unlimited_allowance() {
+        assert!(is_unlimited_allowance(unlimited_allowance()));
+        // a token that decreases even the maximum allowance is still effectively unlimited
+        assert!(is_unlimited_allowance(
+            unlimited_allowance() - 1_000_000_000_000_000_000u128.into()
+        ));
+        assert!(!is_unlimited_allowance(
+            1_000_000_000_000_000_000u128.into()
+        ));
+        assert!(!is_unlimited_allowance(0u8.into()));
+
+        let payload = encode_approve_payload(zero_address(), 0u8.into()).unwrap();
+        // approve(address,uint256)
+        assert_eq!(payload[0..4], [0x09, 0x5e, 0xa7, 0xb3]);
+        assert_eq!(payload.len(), 68);
+    }
+}
@@ -0,0 +1,438 @@
+//! Deterministic fixtures shared between the Rust and Solidity test suites. Every valset, batch and
+//! logic call is listed with its checkpoint and signatures from the well known ethers test keys in
+//! test_files/ethers_test_privkeys, every Gravity contract event with the log a full node would
+//! return for it and the struct the oracle decodes from that log. `gbt dev gen-fixtures` writes the
+//! output of `generate_fixtures` to spec/test-fixtures.json, which is committed. Regenerate it
+//! whenever an encoding changes, both suites fail while the committed file is missing or stale.
+
+use crate::message_signatures::{
+    batch_checkpoint, encode_logic_call_confirm_hashed, encode_tx_batch_confirm_hashed,
+    encode_valset_confirm_hashed, logic_call_checkpoint, valset_checkpoint,
+};
+use clarity::abi::{encode_tokens, AbiToken as Token};
+use clarity::constants::zero_address;
+use clarity::utils::bytes_to_hex_str;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Uint256};
+use gravity_utils::types::event_signatures::{
+    SENT_TO_COSMOS_EVENT_SIG, TRANSACTION_BATCH_EXECUTED_EVENT_SIG, VALSET_UPDATED_EVENT_SIG,
+};
+use gravity_utils::types::{
+    BatchTransaction, Erc20Token, LogicCall, SendToCosmosEvent, TransactionBatch,
+    TransactionBatchExecutedEvent, Valset, ValsetMember, ValsetUpdatedEvent,
+};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+
+const TEST_KEYS: &str = include_str!("../test_files/ethers_test_privkeys");
+/// How many of the test keys sign the fixtures
+const SIGNERS: usize = 3;
+const GRAVITY_ID: &str = "foo";
+const GRAVITY_ADDRESS: &str = "0x7580bFE88Dd3d07947908FAE12d95872a260F2D8";
+const TOKEN_CONTRACT: &str = "0x835973768750b3ED2D5c3EF5AdcD5eDb44d12aD4";
+const LOGIC_CONTRACT: &str = "0x17c1736CcF692F653c433d7aa2aB45148C016F68";
+const COSMOS_ADDRESS: &str = "gravity1g0etv93428tvxqftnmj25jn06mz6dtda5zxt8k";
+const BLOCK_HEIGHT: u64 = 10_000;
+
+/// The test keys that sign the fixtures
+pub fn fixture_keys() -> Vec<EthPrivateKey> {
+    TEST_KEYS
+        .lines()
+        .take(SIGNERS)
+        .map(|line| line.trim().parse().expect("Invalid test key"))
+        .collect()
+}
+
+/// Every fixture as JSON, the output is identical on every call
+pub fn generate_fixtures() -> Value {
+    let keys = fixture_keys();
+    let sign = |hash: Vec<u8>| {
+        keys.iter()
+            .map(|k| {
+                json!({
+                    "signer": k.to_address().to_string(),
+                    "signature": hex(&k.sign_hash(&hash).to_bytes()),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let valsets: Vec<Value> = fixture_valsets()
+        .into_iter()
+        .map(|(description, valset)| {
+            json!({
+                "description": description,
+                "gravity_id": GRAVITY_ID,
+                "nonce": valset.nonce.to_string(),
+                "members": valset.members.iter().map(|m| json!({
+                    "eth_address": m.eth_address.to_string(),
+                    "power": m.power.to_string(),
+                })).collect::<Vec<_>>(),
+                "reward_amount": valset.reward_amount.to_string(),
+                "reward_token": valset.reward_token.unwrap_or_else(zero_address).to_string(),
+                "checkpoint": hex(&valset_checkpoint(GRAVITY_ID.to_string(), valset.clone())),
+                "signatures": sign(encode_valset_confirm_hashed(GRAVITY_ID.to_string(), valset)),
+            })
+        })
+        .collect();
+
+    let batches: Vec<Value> = fixture_batches()
+        .into_iter()
+        .map(|(description, batch)| {
+            json!({
+                "description": description,
+                "gravity_id": GRAVITY_ID,
+                "nonce": batch.nonce.to_string(),
+                "batch_timeout": batch.batch_timeout.to_string(),
+                "token_contract": batch.token_contract.to_string(),
+                "transactions": batch.transactions.iter().map(|tx| json!({
+                    "destination": tx.destination.to_string(),
+                    "amount": tx.erc20_token.amount.to_string(),
+                    "fee": tx.erc20_fee.amount.to_string(),
+                })).collect::<Vec<_>>(),
+                "checkpoint": hex(&batch_checkpoint(GRAVITY_ID.to_string(), batch.clone())),
+                "signatures": sign(encode_tx_batch_confirm_hashed(GRAVITY_ID.to_string(), batch)),
+            })
+        })
+        .collect();
+
+    let tokens = |list: &[Erc20Token]| {
+        list.iter()
+            .map(|t| {
+                json!({
+                    "amount": t.amount.to_string(),
+                    "token_contract_address": t.token_contract_address.to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let logic_calls: Vec<Value> = fixture_logic_calls()
+        .into_iter()
+        .map(|(description, call)| {
+            json!({
+                "description": description,
+                "gravity_id": GRAVITY_ID,
+                "transfers": tokens(&call.transfers),
+                "fees": tokens(&call.fees),
+                "logic_contract_address": call.logic_contract_address.to_string(),
+                "payload": hex(&call.payload),
+                "timeout": call.timeout.to_string(),
+                "invalidation_id": hex(&call.invalidation_id),
+                "invalidation_nonce": call.invalidation_nonce.to_string(),
+                "checkpoint": hex(&logic_call_checkpoint(GRAVITY_ID.to_string(), call.clone())),
+                "signatures": sign(encode_logic_call_confirm_hashed(GRAVITY_ID.to_string(), call)),
+            })
+        })
+        .collect();
+
+    let events = FixtureEvents::new();
+    json!({
+        "gravity_id": GRAVITY_ID,
+        "signers": keys.iter().map(|k| k.to_address().to_string()).collect::<Vec<_>>(),
+        "valsets": valsets,
+        "batches": batches,
+        "logic_calls": logic_calls,
+        "events": {
+            "send_to_cosmos": events.send_to_cosmos.iter().map(|(e, log)| json!({
+                "event": e,
+                "log": log,
+            })).collect::<Vec<_>>(),
+            "transaction_batch_executed": events.batch_executed.iter().map(|(e, log)| json!({
+                "event": e,
+                "log": log,
+            })).collect::<Vec<_>>(),
+            "valset_updated": events.valset_updated.iter().map(|(e, log)| json!({
+                "event": e,
+                "log": log,
+            })).collect::<Vec<_>>(),
+        },
+    })
+}
+
+fn fixture_valsets() -> Vec<(&'static str, Valset)> {
+    let members: Vec<ValsetMember> = fixture_keys()
+        .iter()
+        .zip([2147483648u64, 1073741824, 1073741823])
+        .map(|(k, power)| ValsetMember {
+            eth_address: k.to_address(),
+            power,
+        })
+        .collect();
+    vec![
+        (
+            "test keys, no reward",
+            Valset {
+                nonce: 1,
+                members: members.clone(),
+                reward_amount: 0u8.into(),
+                reward_token: None,
+            },
+        ),
+        (
+            "test keys with an ERC20 relayer reward",
+            Valset {
+                nonce: 2,
+                members,
+                reward_amount: 1_000_000_000_000_000_000u64.into(),
+                reward_token: Some(TOKEN_CONTRACT.parse().unwrap()),
+            },
+        ),
+    ]
+}
+
+fn fixture_batches() -> Vec<(&'static str, TransactionBatch)> {
+    let token_contract: EthAddress = TOKEN_CONTRACT.parse().unwrap();
+    let token = |amount: u64| Erc20Token {
+        amount: amount.into(),
+        token_contract_address: token_contract,
+    };
+    let transactions: Vec<BatchTransaction> = fixture_keys()
+        .iter()
+        .enumerate()
+        .map(|(i, k)| BatchTransaction {
+            id: i as u64 + 1,
+            sender: COSMOS_ADDRESS.parse().unwrap(),
+            destination: k.to_address(),
+            erc20_token: token(1000 * (i as u64 + 1)),
+            erc20_fee: token(10 * (i as u64 + 1)),
+        })
+        .collect();
+    let total_fee = transactions
+        .iter()
+        .fold(0u8.into(), |acc: Uint256, tx| acc + tx.erc20_fee.amount);
+    vec![(
+        "one transaction per test key",
+        TransactionBatch {
+            nonce: 1,
+            batch_timeout: 4_766_922_941,
+            transactions,
+            total_fee: Erc20Token {
+                amount: total_fee,
+                token_contract_address: token_contract,
+            },
+            token_contract,
+        },
+    )]
+}
+
+fn fixture_logic_calls() -> Vec<(&'static str, LogicCall)> {
+    let token = Erc20Token {
+        amount: 1000u32.into(),
+        token_contract_address: TOKEN_CONTRACT.parse().unwrap(),
+    };
+    vec![(
+        "one transfer and fee",
+        LogicCall {
+            transfers: vec![token.clone()],
+            fees: vec![Erc20Token {
+                amount: 10u8.into(),
+                ..token
+            }],
+            logic_contract_address: LOGIC_CONTRACT.parse().unwrap(),
+            payload: b"fixture payload".to_vec(),
+            timeout: 4_766_922_941,
+            invalidation_id: Keccak256::digest(b"fixtures").to_vec(),
+            invalidation_nonce: 1,
+        },
+    )]
+}
+
+/// Each Gravity contract event alongside the log it is decoded from
+struct FixtureEvents {
+    send_to_cosmos: Vec<(SendToCosmosEvent, Value)>,
+    batch_executed: Vec<(TransactionBatchExecutedEvent, Value)>,
+    valset_updated: Vec<(ValsetUpdatedEvent, Value)>,
+}
+
+impl FixtureEvents {
+    fn new() -> Self {
+        let keys = fixture_keys();
+        let erc20: EthAddress = TOKEN_CONTRACT.parse().unwrap();
+
+        let deposit = |event_nonce: u64, destination: &str| {
+            let event = SendToCosmosEvent {
+                erc20,
+                sender: keys[0].to_address(),
+                destination: destination.to_string(),
+                validated_destination: destination.parse().ok(),
+                amount: (event_nonce * 1000).into(),
+                event_nonce,
+                block_height: BLOCK_HEIGHT.into(),
+            };
+            let data = encode_tokens(&[
+                Token::String(destination.to_string()),
+                event.amount.into(),
+                event_nonce.into(),
+            ]);
+            let topics = vec![word(erc20.into()), word(event.sender.into())];
+            let log = log(SENT_TO_COSMOS_EVENT_SIG, topics, data, event_nonce);
+            (event, log)
+        };
+        let send_to_cosmos = vec![
+            deposit(1, COSMOS_ADDRESS),
+            // not bech32, the deposit is still claimed but the funds go to the community pool
+            deposit(2, "not a cosmos address"),
+        ];
+
+        let batch_event = TransactionBatchExecutedEvent {
+            batch_nonce: 1,
+            block_height: BLOCK_HEIGHT.into(),
+            erc20,
+            event_nonce: 3,
+        };
+        let batch_log = log(
+            TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+            vec![word(batch_event.batch_nonce.into()), word(erc20.into())],
+            word(batch_event.event_nonce.into()),
+            batch_event.event_nonce,
+        );
+
+        let valset_updated = fixture_valsets()
+            .into_iter()
+            .zip(4u64..)
+            .map(|((_, valset), event_nonce)| {
+                let (addresses, powers) = valset.to_arrays();
+                let event = ValsetUpdatedEvent {
+                    valset_nonce: valset.nonce,
+                    event_nonce,
+                    block_height: BLOCK_HEIGHT.into(),
+                    reward_amount: valset.reward_amount,
+                    reward_token: valset.reward_token,
+                    members: valset.members,
+                };
+                let data = encode_tokens(&[
+                    event_nonce.into(),
+                    event.reward_amount.into(),
+                    event.reward_token.unwrap_or_else(zero_address).into(),
+                    addresses.into(),
+                    powers.into(),
+                ]);
+                let log = log(
+                    VALSET_UPDATED_EVENT_SIG,
+                    vec![word(event.valset_nonce.into())],
+                    data,
+                    event_nonce,
+                );
+                (event, log)
+            })
+            .collect();
+
+        FixtureEvents {
+            send_to_cosmos,
+            batch_executed: vec![(batch_event, batch_log)],
+            valset_updated,
+        }
+    }
+}
+
+/// A log as returned by eth_getLogs, every fixture event is in the same block
+fn log(signature: &str, mut topics: Vec<Vec<u8>>, data: Vec<u8>, event_nonce: u64) -> Value {
+    topics.insert(0, Keccak256::digest(signature.as_bytes()).to_vec());
+    json!({
+        "address": GRAVITY_ADDRESS,
+        "topics": topics.iter().map(|t| hex(t)).collect::<Vec<_>>(),
+        "data": hex(&data),
+        "blockNumber": format!("{:#x}", BLOCK_HEIGHT),
+        "blockHash": hex(&word(BLOCK_HEIGHT.into())),
+        "transactionHash": hex(&word(event_nonce.into())),
+        "transactionIndex": "0x0",
+        "logIndex": format!("{:#x}", event_nonce),
+        "removed": false
+    })
+}
+
+/// A single abi encoded word
+fn word(token: Token) -> Vec<u8> {
+    encode_tokens(&[token])
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes_to_hex_str(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::Signature as EthSignature;
+    use gravity_utils::types::EthereumEvent;
+    use web30::types::Log;
+
+    const COMMITTED_FIXTURES: &str = include_str!("../../../spec/test-fixtures.json");
+
+    fn decode<T: EthereumEvent + PartialEq + std::fmt::Debug>(expected: &[(T, Value)]) {
+        for (event, log) in expected {
+            let log: Log = serde_json::from_value(log.clone()).unwrap();
+            assert_eq!(&T::from_log(&log).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_deterministic() {
+        assert_eq!(generate_fixtures(), generate_fixtures());
+    }
+
+    #[test]
+    fn test_committed_fixtures() {
+        let committed: Value = serde_json::from_str(COMMITTED_FIXTURES).unwrap();
+        assert!(
+            committed == generate_fixtures(),
+            "spec/test-fixtures.json is stale, regenerate it with `gbt dev gen-fixtures --output ../spec/test-fixtures.json`"
+        );
+    }
+
+    #[test]
+    fn test_fixture_signatures() {
+        let fixtures = generate_fixtures();
+        let signers = fixtures["signers"].as_array().unwrap();
+        assert_eq!(signers.len(), SIGNERS);
+        for (kind, hash) in [
+            (
+                "valsets",
+                fixture_valsets()
+                    .into_iter()
+                    .map(|(_, v)| encode_valset_confirm_hashed(GRAVITY_ID.to_string(), v))
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "batches",
+                fixture_batches()
+                    .into_iter()
+                    .map(|(_, b)| encode_tx_batch_confirm_hashed(GRAVITY_ID.to_string(), b))
+                    .collect(),
+            ),
+            (
+                "logic_calls",
+                fixture_logic_calls()
+                    .into_iter()
+                    .map(|(_, c)| encode_logic_call_confirm_hashed(GRAVITY_ID.to_string(), c))
+                    .collect(),
+            ),
+        ] {
+            for (fixture, hash) in fixtures[kind].as_array().unwrap().iter().zip(hash) {
+                for (sig, signer) in fixture["signatures"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .zip(signers)
+                {
+                    let sig: EthSignature = sig["signature"]
+                        .as_str()
+                        .unwrap()
+                        .trim_start_matches("0x")
+                        .parse()
+                        .unwrap();
+                    let recovered = sig.recover(&hash).unwrap();
+                    assert_eq!(recovered.to_string(), signer.as_str().unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixture_events() {
+        let events = FixtureEvents::new();
+        decode(&events.send_to_cosmos);
+        decode(&events.batch_executed);
+        decode(&events.valset_updated);
+        assert!(events.send_to_cosmos[1].0.has_invalid_destination());
+    }
+}
@@ -3,11 +3,21 @@
 #[macro_use]
 extern crate log;
 
+pub mod calldata;
 pub mod deploy_erc20;
+pub mod erc20_allowance;
+pub mod fixtures;
 pub mod logic_call;
+pub mod logic_call_builder;
+pub mod mempool;
 pub mod message_signatures;
+pub mod multicall;
+pub mod private_relay;
+pub mod relay_signatures;
+pub mod revert;
 pub mod send_erc721_to_cosmos;
 pub mod send_to_cosmos;
+pub mod signer;
 pub mod submit_batch;
 mod test_cases;
 pub mod utils;
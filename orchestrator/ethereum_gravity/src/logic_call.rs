@@ -1,16 +1,29 @@
-use crate::message_signatures::encode_logic_call_confirm_hashed;
-use crate::utils::{encode_valset_struct, get_logic_call_nonce, GasCost};
+use crate::message_signatures::{bytes_token, encode_logic_call_confirm_hashed};
+use crate::private_relay::broadcast_relaying_transaction;
+use crate::relay_signatures::select_relay_signatures;
+use crate::revert::log_if_reverted;
+use crate::utils::{
+    encode_valset_struct, gas_price_options, get_logic_call_nonce, record_relaying_gas, GasCost,
+    RelayingGasPrice,
+};
 use clarity::{abi::AbiToken as Token, utils::bytes_to_hex_str, PrivateKey as EthPrivateKey};
 use clarity::{Address as EthAddress, Uint256};
 use gravity_utils::error::GravityError;
+use gravity_utils::log_context::with_tx_hash;
 use gravity_utils::types::*;
 use std::{cmp::min, time::Duration};
+use tracing::instrument;
 use web30::types::SendTxOption;
 use web30::{client::Web3, types::TransactionRequest};
 
+/// The ABI signature of the Gravity contract's submitLogicCall function
+pub const SUBMIT_LOGIC_CALL_SIGNATURE: &str =
+    "submitLogicCall((address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[],(uint256[],address[],uint256[],address[],address,bytes,uint256,bytes32,uint256))";
+
 /// this function generates an appropriate Ethereum transaction
 /// to submit the provided logic call
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(nonce = call.invalidation_nonce))]
 pub async fn send_eth_logic_call(
     current_valset: Valset,
     call: LogicCall,
@@ -20,6 +33,9 @@ pub async fn send_eth_logic_call(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
+    private_relay: &PrivateRelayConfig,
 ) -> Result<(), GravityError> {
     let new_call_nonce = call.invalidation_nonce;
     let eth_address = our_eth_key.to_address();
@@ -52,26 +68,36 @@ pub async fn send_eth_logic_call(
         return Ok(());
     }
 
-    let payload = encode_logic_call_payload(current_valset, &call, confirms, gravity_id)?;
+    let payload =
+        encode_logic_call_payload(current_valset, &call, confirms, gravity_id, trim_signatures)?;
 
-    let tx = web3
-        .send_prepared_transaction(
-            web3.prepare_transaction(
-                gravity_contract_address,
-                payload,
-                0u32.into(),
-                our_eth_key,
+    let prepared = web3
+        .prepare_transaction(
+            gravity_contract_address,
+            payload,
+            0u32.into(),
+            our_eth_key,
+            match gas_price {
+                Some(gas_price) => gas_price_options(Some(gas_price)),
                 // we maintain a 20% gas price increase to compensate for the 12.5% maximum
                 // base fee increase allowed per block in eip1559, if we overpay we'll
                 // be refunded.
-                vec![SendTxOption::GasPriceMultiplier(1.20f32)],
-            )
-            .await?,
+                None => vec![SendTxOption::GasPriceMultiplier(1.20f32)],
+            },
         )
         .await?;
-    info!("Sent batch update with txid {:#066x}", tx);
+    let tx = broadcast_relaying_transaction(web3, prepared, private_relay).await?;
+    let tx_hash = format!("{:#066x}", tx);
+    with_tx_hash(tx_hash.clone(), || {
+        info!("Sent batch update with txid {}", tx_hash)
+    });
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    log_if_reverted(web3, tx).await;
+    if res.is_ok() {
+        record_relaying_gas(web3, tx, "logic_call").await;
+    }
+    res?;
 
     let last_nonce = get_logic_call_nonce(
         gravity_contract_address,
@@ -99,6 +125,7 @@ pub async fn send_eth_logic_call(
 /// as 12.5% per block. In order to prevent race conditions we pad our estimate by 20%
 /// if the gas price has in fact gone down we'll be refunded. But we must bake
 /// this uncertainty into our cost estimates
+#[allow(clippy::too_many_arguments)]
 pub async fn estimate_logic_call_cost(
     current_valset: Valset,
     call: LogicCall,
@@ -107,6 +134,8 @@ pub async fn estimate_logic_call_cost(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_address: EthAddress,
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
 ) -> Result<GasCost, GravityError> {
     let our_balance = web3.eth_get_balance(our_eth_address).await?;
     let our_nonce = web3.eth_get_transaction_count(our_eth_address).await?;
@@ -115,9 +144,14 @@ pub async fn estimate_logic_call_cost(
         .await?
         .expect("Eth node does not return chain id?");
     let gas_limit = min((u64::MAX - 1).into(), our_balance);
-    let gas_price = web3.eth_gas_price().await?;
-    // increase the value by 20% without using floating point multiplication
-    let gas_price = gas_price + (gas_price / 5u8.into());
+    let gas_price = match gas_price {
+        Some(gas_price) => gas_price.max_fee,
+        None => {
+            let gas_price = web3.eth_gas_price().await?;
+            // increase the value by 20% without using floating point multiplication
+            gas_price + (gas_price / 5u8.into())
+        }
+    };
     let zero: Uint256 = 0u8.into();
     let val = web3
         .eth_estimate_gas(TransactionRequest::Eip1559 {
@@ -131,7 +165,14 @@ pub async fn estimate_logic_call_cost(
             gas: Some(gas_limit.into()),
             value: Some(zero.into()),
             data: Some(
-                encode_logic_call_payload(current_valset, &call, confirms, gravity_id)?.into(),
+                encode_logic_call_payload(
+                    current_valset,
+                    &call,
+                    confirms,
+                    gravity_id,
+                    trim_signatures,
+                )?
+                .into(),
             ),
         })
         .await?;
@@ -148,10 +189,11 @@ fn encode_logic_call_payload(
     call: &LogicCall,
     confirms: &[LogicCallConfirmResponse],
     gravity_id: String,
+    trim_signatures: bool,
 ) -> Result<Vec<u8>, GravityError> {
     let current_valset_token = encode_valset_struct(&current_valset);
     let hash = encode_logic_call_confirm_hashed(gravity_id, call.clone());
-    let sig_data = current_valset.order_sigs(&hash, confirms)?;
+    let sig_data = select_relay_signatures(&current_valset, &hash, confirms, trim_signatures)?;
     let sig_arrays = to_arrays(sig_data);
 
     let mut transfer_amounts = Vec::new();
@@ -201,7 +243,7 @@ fn encode_logic_call_payload(
         Token::Dynamic(fee_amounts),
         fee_token_contracts.into(),
         call.logic_contract_address.into(),
-        Token::UnboundedBytes(call.payload.clone()),
+        bytes_token(call.payload.clone()),
         call.timeout.into(),
         Token::Bytes(call.invalidation_id.clone()),
         call.invalidation_nonce.into(),
@@ -211,11 +253,7 @@ fn encode_logic_call_payload(
         sig_arrays.sigs,
         Token::Struct(struct_tokens.to_vec()),
     ];
-    let payload = clarity::abi::encode_call(
-        "submitLogicCall((address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[],(uint256[],address[],uint256[],address[],address,bytes,uint256,bytes32,uint256))",
-        tokens,
-    )
-    .unwrap();
+    let payload = clarity::abi::encode_call(SUBMIT_LOGIC_CALL_SIGNATURE, tokens).unwrap();
     trace!("Tokens {:?}", tokens);
 
     Ok(payload)
@@ -302,7 +340,8 @@ mod tests {
         };
 
         let our_encoding =
-            encode_logic_call_payload(valset, &logic_call, &[confirm], "foo".to_string()).unwrap();
+            encode_logic_call_payload(valset, &logic_call, &[confirm], "foo".to_string(), false)
+                .unwrap();
         assert_eq!(bytes_to_hex_str(&encoded), bytes_to_hex_str(&our_encoding));
     }
 }
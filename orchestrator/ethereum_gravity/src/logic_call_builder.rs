@@ -0,0 +1,172 @@
+//! Helpers for constructing logic calls by hand. On a live chain logic calls are only created by
+//! other Cosmos modules, but on testnets they can be added to genesis, which lets dApp developers
+//! exercise the logic call path end to end. These helpers ABI encode the call to the logic
+//! contract, derive invalidation ids from a human readable scope and find the next usable nonce
+
+use crate::message_signatures::bytes_token;
+use crate::utils::get_logic_call_nonce;
+use clarity::abi::{encode_call, AbiToken as Token};
+use clarity::utils::hex_str_to_bytes;
+use clarity::{Address as EthAddress, Uint256};
+use gravity_utils::error::GravityError;
+use sha3::{Digest, Keccak256};
+use web30::client::Web3;
+
+/// ABI encodes a call to `signature`, for example "transfer(address,uint256)", with one string
+/// argument per parameter. Supported types are address, bool, string, bytes, bytes1 to bytes32
+/// (hex, with or without 0x) and uint8 to uint256 (decimal)
+pub fn encode_function_call(signature: &str, args: &[String]) -> Result<Vec<u8>, GravityError> {
+    let types = parse_signature(signature)?;
+    if types.len() != args.len() {
+        return Err(GravityError::InvalidOptionsError(format!(
+            "{} takes {} arguments, {} were given",
+            signature,
+            types.len(),
+            args.len()
+        )));
+    }
+    let tokens = types
+        .iter()
+        .zip(args)
+        .map(|(kind, value)| parse_arg(kind, value))
+        .collect::<Result<Vec<Token>, GravityError>>()?;
+    Ok(encode_call(signature, &tokens)?)
+}
+
+/// The parameter types of a function signature such as "transfer(address,uint256)"
+fn parse_signature(signature: &str) -> Result<Vec<String>, GravityError> {
+    let invalid = || {
+        GravityError::InvalidOptionsError(format!(
+            "{} is not a function signature like transfer(address,uint256)",
+            signature
+        ))
+    };
+    let open = signature.find('(').ok_or_else(invalid)?;
+    if open == 0 || !signature.ends_with(')') || signature.contains(' ') {
+        return Err(invalid());
+    }
+    let params = &signature[open + 1..signature.len() - 1];
+    if params.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(params.split(',').map(|p| p.to_string()).collect())
+}
+
+fn parse_arg(kind: &str, value: &str) -> Result<Token, GravityError> {
+    let invalid = |reason: String| {
+        GravityError::InvalidOptionsError(format!(
+            "Invalid {} argument {}: {}",
+            kind, value, reason
+        ))
+    };
+    let hex = |value: &str| {
+        hex_str_to_bytes(value.trim_start_matches("0x")).map_err(|e| invalid(format!("{:?}", e)))
+    };
+    match kind {
+        "address" => Ok(Token::Address(
+            value.parse().map_err(|e| invalid(format!("{:?}", e)))?,
+        )),
+        "bool" => Ok(Token::Bool(
+            value.parse().map_err(|e| invalid(format!("{:?}", e)))?,
+        )),
+        "string" => Ok(Token::String(value.to_string())),
+        "bytes" => Ok(bytes_token(hex(value)?)),
+        k if k.starts_with("bytes") => {
+            let size: usize = k[5..]
+                .parse()
+                .map_err(|_| invalid("unsupported type".to_string()))?;
+            let bytes = hex(value)?;
+            if size == 0 || size > 32 || bytes.len() != size {
+                return Err(invalid(format!("expected {} bytes", size)));
+            }
+            Ok(Token::Bytes(bytes))
+        }
+        k if k.starts_with("uint") => {
+            let bits: usize = if k == "uint" {
+                256
+            } else {
+                k[4..]
+                    .parse()
+                    .map_err(|_| invalid("unsupported type".to_string()))?
+            };
+            if bits == 0 || bits > 256 || bits % 8 != 0 {
+                return Err(invalid("unsupported type".to_string()));
+            }
+            let amount: Uint256 = value.parse().map_err(|e| invalid(format!("{:?}", e)))?;
+            // the contract would revert on an oversized small integer, wider types are rare enough
+            // to leave to the caller
+            if bits < 128 {
+                let small: u128 = value.parse().map_err(|e| invalid(format!("{:?}", e)))?;
+                if small >= 1u128 << bits {
+                    return Err(invalid(format!("does not fit in {} bits", bits)));
+                }
+            }
+            Ok(Token::Uint(amount))
+        }
+        _ => Err(invalid(
+            "unsupported type, arrays, tuples and signed integers must be encoded by hand"
+                .to_string(),
+        )),
+    }
+}
+
+/// The invalidation id for a scope name. Every logic call sharing an invalidation id is ordered by
+/// nonce and executing one invalidates all earlier ones, so a dApp picks one scope per independent
+/// stream of calls
+pub fn invalidation_id_for_scope(scope: &str) -> Vec<u8> {
+    Keccak256::digest(scope.as_bytes()).to_vec()
+}
+
+/// The lowest invalidation nonce the Gravity contract will still accept for `invalidation_id`
+pub async fn next_invalidation_nonce(
+    gravity_contract_address: EthAddress,
+    invalidation_id: Vec<u8>,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<u64, GravityError> {
+    let last = get_logic_call_nonce(
+        gravity_contract_address,
+        invalidation_id,
+        caller_address,
+        web3,
+    )
+    .await?;
+    Ok(last + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::utils::bytes_to_hex_str;
+
+    #[test]
+    fn test_encode_function_call() {
+        let call = encode_function_call(
+            "transfer(address,uint256)",
+            &[
+                "0x6Bd41fCdF129297c3524395d669c0865b3CA85B2".to_string(),
+                "1000".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            bytes_to_hex_str(&call),
+            "a9059cbb\
+             0000000000000000000000006bd41fcdf129297c3524395d669c0865b3ca85b2\
+             00000000000000000000000000000000000000000000000000000000000003e8"
+        );
+        assert!(encode_function_call("transfer(address,uint256)", &["1".to_string()]).is_err());
+        assert!(encode_function_call("set(uint8)", &["256".to_string()]).is_err());
+        assert!(encode_function_call("set(int256)", &["1".to_string()]).is_err());
+        assert!(encode_function_call("transfer", &[]).is_err());
+        assert_eq!(encode_function_call("poke()", &[]).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_invalidation_id_for_scope() {
+        let id = invalidation_id_for_scope("my-dapp");
+        assert_eq!(id.len(), 32);
+        assert_eq!(id, invalidation_id_for_scope("my-dapp"));
+        assert_ne!(id, invalidation_id_for_scope("other-dapp"));
+    }
+}
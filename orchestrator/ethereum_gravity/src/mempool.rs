@@ -0,0 +1,138 @@
+//! Reads Gravity contract calls that have been broadcast to Ethereum but not yet mined, so relayers
+//! can see an identical submission already in flight and avoid racing it. The full txpool is read
+//! with txpool_content where the node supports it, otherwise the node's pending block is used.
+
+use crate::calldata::{decode_gravity_call, GravityCall};
+use clarity::utils::hex_str_to_bytes;
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use deep_space::Address as CosmosAddress;
+use gravity_utils::error::GravityError;
+use serde_json::Value;
+use std::time::Duration;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+use web30::types::TransactionRequest;
+
+const MEMPOOL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A Gravity contract call waiting to be mined
+#[derive(Debug, Clone)]
+pub struct PendingGravityCall {
+    pub tx_hash: String,
+    pub from: EthAddress,
+    /// The most the transaction pays per gas, its max fee for eip1559 transactions
+    pub gas_price: Uint256,
+    pub input: Vec<u8>,
+    pub call: GravityCall,
+}
+
+/// Returns every updateValset, submitBatch and submitLogicCall to `gravity_contract_address` the
+/// node knows to be pending, an error means the node exposes neither txpool_content nor a pending
+/// block and the mempool can not be inspected
+pub async fn get_pending_gravity_calls(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+) -> Result<Vec<PendingGravityCall>, GravityError> {
+    let client = HttpClient::new(&web3.get_url());
+    let txs = match get_txpool_content(&client).await {
+        Ok(txs) => txs,
+        Err(e) => {
+            trace!(
+                "txpool_content unavailable {:?}, using the pending block",
+                e
+            );
+            get_pending_block_transactions(&client).await?
+        }
+    };
+
+    // batch senders are not part of the calldata, they are irrelevant here
+    let placeholder_sender = CosmosAddress::from_slice(&[0u8; 20], "gravity").unwrap();
+    let mut out = Vec::new();
+    for tx in txs {
+        let to: Option<EthAddress> = tx["to"].as_str().and_then(|to| to.parse().ok());
+        if to != Some(gravity_contract_address) {
+            continue;
+        }
+        let (from, input) = match (
+            tx["from"].as_str().and_then(|from| from.parse().ok()),
+            tx["input"].as_str().and_then(|i| hex_str_to_bytes(i).ok()),
+        ) {
+            (Some(from), Some(input)) => (from, input),
+            _ => continue,
+        };
+        let gas_price = parse_hex_quantity(&tx["maxFeePerGas"])
+            .or_else(|| parse_hex_quantity(&tx["gasPrice"]))
+            .unwrap_or_default();
+        if let Ok(Some(call)) = decode_gravity_call(&input, placeholder_sender) {
+            out.push(PendingGravityCall {
+                tx_hash: tx["hash"].as_str().unwrap_or_default().to_string(),
+                from,
+                gas_price,
+                input,
+                call,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Runs `pending` with eth_call against the latest block, false if it would revert or could not
+/// be checked. A pending call that reverts, for example because its signatures are invalid, will
+/// not relay anything once mined
+pub async fn pending_call_succeeds(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    pending: &PendingGravityCall,
+) -> bool {
+    let request = TransactionRequest::quick_tx(
+        pending.from,
+        gravity_contract_address,
+        pending.input.clone(),
+    );
+    match web3.eth_call(request).await {
+        Ok(_) => true,
+        Err(e) => {
+            debug!("Pending tx {} fails a dry run {:?}", pending.tx_hash, e);
+            false
+        }
+    }
+}
+
+fn parse_hex_quantity(v: &Value) -> Option<Uint256> {
+    let s = v.as_str()?.trim_start_matches("0x");
+    u128::from_str_radix(s, 16).ok().map(|v| v.into())
+}
+
+/// Reads every pending transaction from txpool_content, supported by Geth, Erigon and Nethermind.
+/// Queued transactions are skipped, they can not be mined until the nonce gap before them is filled
+async fn get_txpool_content(client: &HttpClient) -> Result<Vec<Value>, GravityError> {
+    let content: Value = client
+        .request_method("txpool_content", Vec::<String>::new(), MEMPOOL_TIMEOUT)
+        .await?;
+    let mut out = Vec::new();
+    if let Some(senders) = content["pending"].as_object() {
+        for txs in senders.values() {
+            if let Some(txs) = txs.as_object() {
+                out.extend(txs.values().cloned());
+            }
+        }
+    }
+    Ok(out)
+}
+
+async fn get_pending_block_transactions(client: &HttpClient) -> Result<Vec<Value>, GravityError> {
+    let block: Value = client
+        .request_method(
+            "eth_getBlockByNumber",
+            (String::from("pending"), true),
+            MEMPOOL_TIMEOUT,
+        )
+        .await?;
+    match block["transactions"].as_array() {
+        Some(txs) => Ok(txs.clone()),
+        None => Err(GravityError::EthereumContractError(
+            "Node did not return a pending block".to_string(),
+        )),
+    }
+}
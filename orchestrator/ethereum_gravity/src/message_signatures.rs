@@ -1,7 +1,22 @@
+//! Computes the checkpoints the Gravity contract verifies signatures against. Each of valsets,
+//! batches and logic calls has three functions:
+//!
+//! * `encode_*_confirm` the ABI encoded message, identical to the `abi.encode` performed by the
+//!   contract
+//! * `*_checkpoint` the keccak256 hash of that message, this is the checkpoint the contract stores
+//!   and the value the Cosmos module computes for the same item
+//! * `encode_*_confirm_hashed` the checkpoint with the Ethereum signed message prefix applied, this
+//!   is what orchestrators actually sign
+//!
+//! These functions are the reference for anyone recomputing checkpoints independently, the golden
+//! test vectors in spec/checkpoint-test-vectors.json are checked against both this module and the
+//! Solidity tests so the implementations can not silently drift apart.
+
 use clarity::abi::{encode_tokens, AbiToken as Token};
 use clarity::constants::zero_address;
 use clarity::utils::get_ethereum_msg_hash;
 use gravity_utils::types::{LogicCall, TransactionBatch, Valset};
+use sha3::{Digest, Keccak256};
 
 /// takes the required input data and produces the required signature to confirm a validator
 /// set update on the Gravity Ethereum contract. This value will then be signed before being
@@ -26,6 +41,13 @@ pub fn encode_valset_confirm(gravity_id: String, valset: Valset) -> Vec<u8> {
     ])
 }
 
+/// the 32 byte checkpoint of a validator set as stored by the Gravity contract
+pub fn valset_checkpoint(gravity_id: String, valset: Valset) -> Vec<u8> {
+    Keccak256::digest(encode_valset_confirm(gravity_id, valset)).to_vec()
+}
+
+/// the digest orchestrators sign to confirm a validator set, the checkpoint with the Ethereum
+/// signed message prefix applied
 pub fn encode_valset_confirm_hashed(gravity_id: String, valset: Valset) -> Vec<u8> {
     let digest = encode_valset_confirm(gravity_id, valset);
     get_ethereum_msg_hash(&digest)
@@ -50,6 +72,13 @@ pub fn encode_tx_batch_confirm(gravity_id: String, batch: TransactionBatch) -> V
     ])
 }
 
+/// the 32 byte checkpoint of a transaction batch as stored by the Gravity contract
+pub fn batch_checkpoint(gravity_id: String, batch: TransactionBatch) -> Vec<u8> {
+    Keccak256::digest(encode_tx_batch_confirm(gravity_id, batch)).to_vec()
+}
+
+/// the digest orchestrators sign to confirm a transaction batch, the checkpoint with the Ethereum
+/// signed message prefix applied
 pub fn encode_tx_batch_confirm_hashed(gravity_id: String, batch: TransactionBatch) -> Vec<u8> {
     let digest = encode_tx_batch_confirm(gravity_id, batch);
     get_ethereum_msg_hash(&digest)
@@ -82,13 +111,30 @@ pub fn encode_logic_call_confirm(gravity_id: String, call: LogicCall) -> Vec<u8>
         Token::Dynamic(fee_amounts),                 // Array of Fees
         fee_token_contracts.into(),                  // ERC-20 contract for fee payments
         call.logic_contract_address.into(),          // Address of a logic contract
-        Token::UnboundedBytes(call.payload),         // Encoded arguments to logic contract
+        bytes_token(call.payload),                   // Encoded arguments to logic contract
         call.timeout.into(),                         // Timeout on batch
         Token::Bytes(call.invalidation_id),          // ID of logic batch
         call.invalidation_nonce.into(),              // Nonce of logic batch. See 2-d nonce scheme.
     ])
 }
 
+/// ABI token for a dynamic `bytes` value. Clarity panics encoding an empty UnboundedBytes, an
+/// empty dynamic array encodes to the same single zero length word that `abi.encode` produces
+pub fn bytes_token(bytes: Vec<u8>) -> Token {
+    if bytes.is_empty() {
+        Token::Dynamic(Vec::new())
+    } else {
+        Token::UnboundedBytes(bytes)
+    }
+}
+
+/// the 32 byte checkpoint of a logic call as stored by the Gravity contract
+pub fn logic_call_checkpoint(gravity_id: String, call: LogicCall) -> Vec<u8> {
+    Keccak256::digest(encode_logic_call_confirm(gravity_id, call)).to_vec()
+}
+
+/// the digest orchestrators sign to confirm a logic call, the checkpoint with the Ethereum
+/// signed message prefix applied
 pub fn encode_logic_call_confirm_hashed(gravity_id: String, call: LogicCall) -> Vec<u8> {
     let digest = encode_logic_call_confirm(gravity_id, call);
     get_ethereum_msg_hash(&digest)
@@ -105,8 +151,9 @@ mod test {
     use gravity_utils::types::LogicCall;
     use gravity_utils::types::TransactionBatch;
     use gravity_utils::types::ValsetMember;
+    use num256::Uint256;
     use rand::Rng;
-    use sha3::{Digest, Keccak256};
+    use serde_json::Value;
 
     #[test]
     fn test_valset_signature() {
@@ -321,4 +368,119 @@ mod test {
         assert_eq!(correct_hash.len(), checkpoint_hash.len());
         assert_eq!(correct_hash, checkpoint_hash.as_slice())
     }
+
+    /// golden vectors shared with the Solidity tests, see solidity/test/checkpointVectors.ts
+    const CHECKPOINT_VECTORS: &str = include_str!("../../../spec/checkpoint-test-vectors.json");
+
+    fn parse<T: std::str::FromStr>(v: &Value) -> T
+    where
+        T::Err: std::fmt::Debug,
+    {
+        v.as_str().unwrap().parse().unwrap()
+    }
+
+    fn parse_bytes(v: &Value) -> Vec<u8> {
+        hex_str_to_bytes(v.as_str().unwrap()).unwrap()
+    }
+
+    fn parse_tokens(v: &Value) -> Vec<Erc20Token> {
+        v.as_array()
+            .unwrap()
+            .iter()
+            .map(|t| Erc20Token {
+                amount: parse(&t["amount"]),
+                token_contract_address: parse(&t["token_contract_address"]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpoint_vectors() {
+        let vectors: Value = serde_json::from_str(CHECKPOINT_VECTORS).unwrap();
+
+        for v in vectors["valsets"].as_array().unwrap() {
+            let valset = Valset {
+                nonce: parse(&v["nonce"]),
+                members: v["members"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|m| ValsetMember {
+                        power: parse(&m["power"]),
+                        eth_address: parse(&m["eth_address"]),
+                    })
+                    .collect(),
+                reward_amount: parse::<Uint256>(&v["reward_amount"]),
+                reward_token: Some(parse(&v["reward_token"])),
+            };
+            let gravity_id = v["gravity_id"].as_str().unwrap().to_string();
+            assert_eq!(
+                valset_checkpoint(gravity_id, valset),
+                parse_bytes(&v["checkpoint"]),
+                "valset vector {}",
+                v["description"]
+            );
+        }
+
+        for v in vectors["batches"].as_array().unwrap() {
+            let token_contract = parse(&v["token_contract"]);
+            let token = |amount: &Value| Erc20Token {
+                amount: parse(amount),
+                token_contract_address: token_contract,
+            };
+            let transactions: Vec<BatchTransaction> = v["transactions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, tx)| BatchTransaction {
+                    id: i as u64,
+                    // the sender is not part of the checkpoint
+                    sender: "gravity1g0etv93428tvxqftnmj25jn06mz6dtda5zxt8k"
+                        .parse()
+                        .unwrap(),
+                    destination: parse(&tx["destination"]),
+                    erc20_token: token(&tx["amount"]),
+                    erc20_fee: token(&tx["fee"]),
+                })
+                .collect();
+            let batch = TransactionBatch {
+                nonce: parse(&v["nonce"]),
+                batch_timeout: parse(&v["batch_timeout"]),
+                transactions,
+                // the total fee is not part of the checkpoint
+                total_fee: Erc20Token {
+                    amount: 0u8.into(),
+                    token_contract_address: token_contract,
+                },
+                token_contract,
+            };
+            let gravity_id = v["gravity_id"].as_str().unwrap().to_string();
+            assert_eq!(
+                batch_checkpoint(gravity_id, batch),
+                parse_bytes(&v["checkpoint"]),
+                "batch vector {}",
+                v["description"]
+            );
+        }
+
+        for v in vectors["logic_calls"].as_array().unwrap() {
+            let call = LogicCall {
+                transfers: parse_tokens(&v["transfers"]),
+                fees: parse_tokens(&v["fees"]),
+                logic_contract_address: parse(&v["logic_contract_address"]),
+                payload: parse_bytes(&v["payload"]),
+                timeout: parse(&v["timeout"]),
+                invalidation_id: parse_bytes(&v["invalidation_id"]),
+                invalidation_nonce: parse(&v["invalidation_nonce"]),
+            };
+            let gravity_id = v["gravity_id"].as_str().unwrap().to_string();
+            assert_eq!(
+                logic_call_checkpoint(gravity_id, call),
+                parse_bytes(&v["checkpoint"]),
+                "logic call vector {}",
+                v["description"]
+            );
+        }
+    }
 }
@@ -0,0 +1,166 @@
+//! Batching of read only contract calls through the Multicall3 contract, which is deployed at the
+//! same address on Ethereum and nearly every EVM chain. Querying many balances this way takes a
+//! single eth_call instead of one per token and holder
+
+use clarity::abi::{encode_call, encode_tokens};
+use clarity::{Address, Uint256};
+use gravity_utils::error::GravityError;
+use sha3::{Digest, Keccak256};
+use web30::client::Web3;
+use web30::types::TransactionRequest;
+
+/// The address of Multicall3 on Ethereum mainnet and most other EVM chains
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+const AGGREGATE_SIGNATURE: &str = "aggregate((address,bytes)[])";
+
+pub fn multicall3_address() -> Address {
+    MULTICALL3_ADDRESS.parse().unwrap()
+}
+
+/// Encodes a call to the Multicall3 getEthBalance function, aggregate it with the Multicall3
+/// contract itself as the target to fetch ETH balances alongside token balances
+pub fn encode_get_eth_balance(holder: Address) -> Vec<u8> {
+    encode_call("getEthBalance(address)", &[holder.into()]).unwrap()
+}
+
+/// Runs `calls` of (target, calldata) in a single eth_call against Multicall3 at `multicall`,
+/// returning the raw return data of each call in order. Multicall3 reverts the whole aggregate if
+/// any one call reverts, callers wanting partial results should fall back to individual calls
+pub async fn multicall_aggregate(
+    calls: &[(Address, Vec<u8>)],
+    multicall: Address,
+    caller: Address,
+    web3: &Web3,
+) -> Result<Vec<Vec<u8>>, GravityError> {
+    if calls.is_empty() {
+        return Ok(Vec::new());
+    }
+    let request = TransactionRequest::quick_tx(caller, multicall, encode_aggregate(calls));
+    let val = web3.simulate_transaction(request, None).await?;
+    let results = decode_aggregate_result(&val)?;
+    if results.len() != calls.len() {
+        return Err(GravityError::EthereumContractError(format!(
+            "Multicall returned {} results for {} calls",
+            results.len(),
+            calls.len()
+        )));
+    }
+    Ok(results)
+}
+
+/// Encodes aggregate((address,bytes)[]), clarity can't encode arrays of dynamic structs so the
+/// offsets are laid out by hand
+pub fn encode_aggregate(calls: &[(Address, Vec<u8>)]) -> Vec<u8> {
+    let mut elements = Vec::new();
+    for (target, data) in calls {
+        let mut element = encode_tokens(&[(*target).into()]);
+        element.extend_from_slice(&word(64));
+        element.extend_from_slice(&word(data.len()));
+        element.extend_from_slice(data);
+        element.resize(element.len() + padding(data.len()), 0);
+        elements.push(element);
+    }
+
+    let mut out = Keccak256::digest(AGGREGATE_SIGNATURE.as_bytes())[0..4].to_vec();
+    out.extend_from_slice(&word(32));
+    out.extend_from_slice(&word(calls.len()));
+    // element offsets are relative to the first word after the array length
+    let mut offset = 32 * calls.len();
+    for element in elements.iter() {
+        out.extend_from_slice(&word(offset));
+        offset += element.len();
+    }
+    for element in elements {
+        out.extend_from_slice(&element);
+    }
+    out
+}
+
+/// Decodes the (uint256 blockNumber, bytes[] returnData) returned by aggregate
+pub fn decode_aggregate_result(val: &[u8]) -> Result<Vec<Vec<u8>>, GravityError> {
+    let array = read_word(val, 32)?;
+    let len = read_word(val, array)?;
+    let start = array + 32;
+    let mut out = Vec::new();
+    for i in 0..len {
+        let item = start + read_word(val, start + 32 * i)?;
+        let item_len = read_word(val, item)?;
+        match val.get(item + 32..item + 32 + item_len) {
+            Some(data) => out.push(data.to_vec()),
+            None => return Err(malformed()),
+        }
+    }
+    Ok(out)
+}
+
+fn word(v: usize) -> Vec<u8> {
+    encode_tokens(&[Uint256::from(v as u64).into()])
+}
+
+fn padding(len: usize) -> usize {
+    (32 - len % 32) % 32
+}
+
+/// Reads the word at `pos` as an offset or length, anything that doesn't fit in a u32 can't be a
+/// valid position in the response
+fn read_word(val: &[u8], pos: usize) -> Result<usize, GravityError> {
+    let word = val.get(pos..pos + 32).ok_or_else(malformed)?;
+    if word[0..28].iter().any(|b| *b != 0) {
+        return Err(malformed());
+    }
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&word[28..32]);
+    Ok(u32::from_be_bytes(bytes) as usize)
+}
+
+fn malformed() -> GravityError {
+    GravityError::EthereumContractError("Malformed multicall response".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::constants::zero_address;
+
+    #[test]
+    fn test_encode_aggregate() {
+        let token: Address = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let calls = vec![
+            (
+                token,
+                encode_call("balanceOf(address)", &[token.into()]).unwrap(),
+            ),
+            (multicall3_address(), encode_get_eth_balance(zero_address())),
+        ];
+        let payload = encode_aggregate(&calls);
+        // aggregate((address,bytes)[])
+        assert_eq!(payload[0..4], [0x25, 0x2d, 0xba, 0x42]);
+        // selector, array offset and length, two element offsets, two elements of
+        // target, bytes offset, bytes length and 64 bytes of padded calldata
+        assert_eq!(payload.len(), 4 + 32 * 4 + 2 * 160);
+        assert_eq!(payload[4 + 32 * 3 - 1], 64);
+        assert_eq!(payload[4 + 32 * 4 - 1], 64 + 160);
+    }
+
+    #[test]
+    fn test_decode_aggregate_result() {
+        let mut val = Vec::new();
+        val.extend_from_slice(&word(1234));
+        val.extend_from_slice(&word(64));
+        val.extend_from_slice(&word(2));
+        val.extend_from_slice(&word(64));
+        val.extend_from_slice(&word(128));
+        val.extend_from_slice(&word(32));
+        val.extend_from_slice(&word(5));
+        val.extend_from_slice(&word(32));
+        val.extend_from_slice(&word(6));
+        let results = decode_aggregate_result(&val).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(Uint256::from_be_bytes(&results[0]), 5u8.into());
+        assert_eq!(Uint256::from_be_bytes(&results[1]), 6u8.into());
+
+        assert!(decode_aggregate_result(&val[0..val.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Broadcasting of relaying transactions through a private relay such as Flashbots Protect. A batch
+//! or logic call in the public mempool can be copied and frontrun by anyone, and the relayer who
+//! loses the race still pays gas for a reverted transaction. Private relays keep transactions out
+//! of the mempool and only include those that succeed. They accept signed transactions with
+//! eth_sendRawTransaction like any node, inclusion is then awaited on the regular Ethereum node
+
+use clarity::{Transaction, Uint256};
+use gravity_utils::error::GravityError;
+use gravity_utils::log_context::with_tx_hash;
+use gravity_utils::types::PrivateRelayConfig;
+use std::time::Duration;
+use web30::client::Web3;
+
+const PRIVATE_RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Broadcasts `prepared` through the configured private relay, or to the public mempool through
+/// `web3` if there is none. Returns the transaction hash
+pub async fn broadcast_relaying_transaction(
+    web3: &Web3,
+    prepared: Transaction,
+    private_relay: &PrivateRelayConfig,
+) -> Result<Uint256, GravityError> {
+    let url = match &private_relay.url {
+        Some(url) => url,
+        None => return Ok(web3.send_prepared_transaction(prepared).await?),
+    };
+    let relay = Web3::new(url, PRIVATE_RELAY_TIMEOUT);
+    match relay.send_prepared_transaction(prepared.clone()).await {
+        Ok(tx) => {
+            let tx_hash = format!("{:#066x}", tx);
+            with_tx_hash(tx_hash.clone(), || {
+                info!("Sent {} through private relay {}", tx_hash, url)
+            });
+            Ok(tx)
+        }
+        Err(e) if private_relay.public_fallback => {
+            warn!(
+                "Private relay {} failed {:?}, broadcasting to the public mempool",
+                url, e
+            );
+            Ok(web3.send_prepared_transaction(prepared).await?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,171 @@
+//! Selection of the signatures submitted when relaying a validator set update, batch or logic call.
+//! The contract takes one signature slot per member of the current validator set, in the order the
+//! members were submitted, and skips any slot with a v of zero. It verifies signatures in that order
+//! and stops once they pass the power threshold, so every signature beyond the fewest needed to pass
+//! only costs calldata and, if it comes earlier in the array, an ecrecover
+
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{
+    trim_signatures_to_threshold, Confirm, GravitySignature, Valset, GRAVITY_POWER_TO_PASS,
+};
+use std::fmt::Debug;
+
+/// Orders `confirms` over `signed_hash` into the slots of `current_valset`. With `trim` only the
+/// fewest, highest powered, signatures that pass the power threshold are kept and every other slot
+/// is zeroed. Errors if the confirms don't carry enough power to pass
+pub fn select_relay_signatures<T: Confirm + Clone + Debug>(
+    current_valset: &Valset,
+    signed_hash: &[u8],
+    confirms: &[T],
+    trim: bool,
+) -> Result<Vec<GravitySignature>, GravityError> {
+    let ordered = current_valset.order_sigs(signed_hash, confirms)?;
+    if !trim {
+        return Ok(ordered);
+    }
+    let trimmed = trim_signatures_to_threshold(ordered, GRAVITY_POWER_TO_PASS);
+    debug!(
+        "Submitting {} of {} signatures",
+        count_signatures(&trimmed),
+        trimmed.len()
+    );
+    Ok(trimmed)
+}
+
+/// The number of filled signature slots
+pub fn count_signatures(sigs: &[GravitySignature]) -> usize {
+    let zero: clarity::Uint256 = 0u8.into();
+    sigs.iter().filter(|s| s.v != zero).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::utils::get_ethereum_msg_hash;
+    use clarity::PrivateKey as EthPrivateKey;
+    use gravity_utils::types::{ValsetConfirmResponse, ValsetMember, TOTAL_GRAVITY_POWER};
+
+    const MESSAGE: &[u8] = b"relay signature selection";
+
+    fn key(i: u8) -> EthPrivateKey {
+        EthPrivateKey::from_bytes([i + 1; 32]).unwrap()
+    }
+
+    /// A valset with a member of each power in order, and confirms from the members at `signers`
+    fn setup(powers: &[u64], signers: &[usize]) -> (Valset, Vec<ValsetConfirmResponse>) {
+        let members = powers
+            .iter()
+            .enumerate()
+            .map(|(i, power)| ValsetMember {
+                power: *power,
+                eth_address: key(i as u8).to_address(),
+            })
+            .collect();
+        let valset = Valset {
+            nonce: 1,
+            members,
+            reward_amount: 0u8.into(),
+            reward_token: None,
+        };
+        let confirms = signers
+            .iter()
+            .map(|i| ValsetConfirmResponse {
+                orchestrator: "althea1hv0dcr9l2l090jxtuxu8nsa2jm6h307xcmf4sw"
+                    .parse()
+                    .unwrap(),
+                eth_address: key(*i as u8).to_address(),
+                nonce: 1,
+                eth_signature: key(*i as u8).sign_ethereum_msg(MESSAGE),
+            })
+            .collect();
+        (valset, confirms)
+    }
+
+    fn select(powers: &[u64], signers: &[usize]) -> Result<Vec<GravitySignature>, GravityError> {
+        let (valset, confirms) = setup(powers, signers);
+        let hash = get_ethereum_msg_hash(MESSAGE);
+        let trimmed = select_relay_signatures(&valset, &hash, &confirms, true)?;
+        let full = select_relay_signatures(&valset, &hash, &confirms, false)?;
+        assert_eq!(full.len(), powers.len());
+        assert_eq!(count_signatures(&full), signers.len());
+        Ok(trimmed)
+    }
+
+    /// The indexes of the filled slots
+    fn kept(sigs: &[GravitySignature]) -> Vec<usize> {
+        let zero: clarity::Uint256 = 0u8.into();
+        sigs.iter()
+            .enumerate()
+            .filter(|(_, s)| s.v != zero)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_one_dominant_validator() {
+        let big = GRAVITY_POWER_TO_PASS + 1;
+        let rest = (TOTAL_GRAVITY_POWER - big) / 3;
+        let sigs = select(&[big, rest, rest, rest], &[0, 1, 2, 3]).unwrap();
+        assert_eq!(sigs.len(), 4);
+        assert_eq!(kept(&sigs), vec![0]);
+    }
+
+    #[test]
+    fn test_equal_powers() {
+        // 7 of 10 equal validators are needed to pass 2/3, which 7 doesn't matter
+        let powers = vec![TOTAL_GRAVITY_POWER / 10; 10];
+        let sigs = select(&powers, &(0..10).collect::<Vec<_>>()).unwrap();
+        assert_eq!(sigs.len(), 10);
+        assert_eq!(count_signatures(&sigs), 7);
+    }
+
+    #[test]
+    fn test_unsorted_valset() {
+        // the valset in the contract need not be sorted, slots must still match its order and the
+        // highest powers are chosen wherever they are
+        let unit = TOTAL_GRAVITY_POWER / 100;
+        let powers = [
+            5 * unit,
+            35 * unit,
+            10 * unit,
+            25 * unit,
+            15 * unit,
+            10 * unit,
+        ];
+        let sigs = select(&powers, &[0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(kept(&sigs), vec![1, 3, 4]);
+        for (sig, power) in sigs.iter().zip(powers.iter()) {
+            assert_eq!(sig.power, *power);
+        }
+    }
+
+    #[test]
+    fn test_missing_top_signer() {
+        let unit = TOTAL_GRAVITY_POWER / 100;
+        let powers = [30 * unit, 25 * unit, 20 * unit, 15 * unit, 10 * unit];
+        let sigs = select(&powers, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(kept(&sigs), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_threshold_boundary() {
+        // exactly the threshold does not pass, the contract requires strictly more
+        let rest = TOTAL_GRAVITY_POWER - GRAVITY_POWER_TO_PASS;
+        assert!(select(&[GRAVITY_POWER_TO_PASS, rest], &[0]).is_err());
+        let sigs = select(&[GRAVITY_POWER_TO_PASS, rest], &[0, 1]).unwrap();
+        assert_eq!(kept(&sigs), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_many_small_validators() {
+        // a few large validators and a long tail, the tail is only used once the large ones run out
+        let unit = TOTAL_GRAVITY_POWER / 1000;
+        let mut powers = vec![200 * unit, 200 * unit, 150 * unit];
+        powers.extend(vec![5 * unit; 90]);
+        let signers: Vec<usize> = (0..powers.len()).collect();
+        let sigs = select(&powers, &signers).unwrap();
+        // 550 from the large validators, then 24 of the tail to pass 666.7
+        assert_eq!(count_signatures(&sigs), 3 + 24);
+        assert_eq!(kept(&sigs)[0..3], [0, 1, 2]);
+    }
+}
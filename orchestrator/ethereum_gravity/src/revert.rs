@@ -0,0 +1,388 @@
+//! Explains why a relay transaction reverted. The revert data is read from a debug_traceTransaction
+//! call trace where the node supports it, otherwise the transaction is replayed with eth_call on top
+//! of its parent block. Gravity.sol custom errors are decoded into a readable diagnosis with the
+//! most likely cause, so a failed relay logs more than a transaction hash.
+
+use crate::calldata::{decode_gravity_call, GravityCall};
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::Uint256;
+use deep_space::Address as CosmosAddress;
+use gravity_utils::error::GravityError;
+use gravity_utils::log_context::with_tx_hash;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::time::Duration;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+const REVERT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Every error Gravity.sol can revert with, its parameter names and the most likely cause
+const GRAVITY_ERRORS: &[(&str, &[&str], &str)] = &[
+    (
+        "InvalidSignature",
+        &[],
+        "a signature does not match its validator, the signatures were probably ordered against a different valset",
+    ),
+    (
+        "InvalidValsetNonce",
+        &["newNonce", "currentNonce"],
+        "another relayer submitted this valset or a newer one first",
+    ),
+    (
+        "InvalidBatchNonce",
+        &["newNonce", "currentNonce"],
+        "another relayer submitted this batch or a newer one first",
+    ),
+    (
+        "InvalidLogicCallNonce",
+        &["newNonce", "currentNonce"],
+        "another relayer submitted this logic call or a newer one first",
+    ),
+    ("InvalidLogicCallTransfers", &[], "the transfer token and amount arrays differ in length"),
+    ("InvalidLogicCallFees", &[], "the fee token and amount arrays differ in length"),
+    ("InvalidSendToCosmos", &[], "the deposit did not transfer the expected amount"),
+    (
+        "IncorrectCheckpoint",
+        &[],
+        "the current valset passed in is not the one stored in the contract, a valset update landed first",
+    ),
+    ("MalformedNewValidatorSet", &[], "the new valset's address and power arrays differ in length"),
+    (
+        "MalformedCurrentValidatorSet",
+        &[],
+        "the current valset's arrays and the signatures differ in length",
+    ),
+    ("MalformedBatch", &[], "the batch's amount, destination and fee arrays differ in length"),
+    (
+        "InsufficientPower",
+        &["cumulativePower", "powerThreshold"],
+        "the signatures included do not reach the power threshold",
+    ),
+    ("BatchTimedOut", &[], "the batch timeout block passed before it was mined"),
+    ("LogicCallTimedOut", &[], "the logic call timeout block passed before it was mined"),
+];
+
+/// Gravity.sol errors caused by the contract state moving on while the transaction was pending,
+/// retrying with fresh state can succeed
+const TRANSIENT_ERRORS: &[&str] = &[
+    "InvalidSignature",
+    "InvalidValsetNonce",
+    "InvalidBatchNonce",
+    "InvalidLogicCallNonce",
+    "IncorrectCheckpoint",
+    "InsufficientPower",
+];
+
+/// Why a transaction reverted, as far as it could be worked out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertDiagnosis {
+    pub tx_hash: String,
+    pub block: Option<u64>,
+    /// the Gravity call the transaction made, if its calldata decodes
+    pub call: Option<String>,
+    /// the decoded revert reason, if any revert data was recovered
+    pub reason: Option<String>,
+    /// the most likely cause of the revert
+    pub cause: Option<String>,
+    /// how the revert data was recovered
+    pub source: &'static str,
+}
+
+impl fmt::Display for RevertDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transaction {} reverted", self.tx_hash)?;
+        if let Some(block) = self.block {
+            write!(f, " in block {}", block)?;
+        }
+        if let Some(call) = &self.call {
+            write!(f, ", {}", call)?;
+        }
+        match &self.reason {
+            Some(reason) => write!(f, ", reason {}", reason)?,
+            None => write!(f, ", reason unknown")?,
+        }
+        if let Some(cause) = &self.cause {
+            write!(f, ", {}", cause)?;
+        }
+        write!(f, " (from {})", self.source)
+    }
+}
+
+impl RevertDiagnosis {
+    /// True unless the revert reason shows the transaction would revert again if resubmitted.
+    /// Races with other relayers and valset updates are transient, as is a revert whose reason
+    /// could not be recovered or is empty, such as running out of gas
+    pub fn is_transient(&self) -> bool {
+        match &self.reason {
+            None => true,
+            Some(reason) => {
+                reason == "0x" || TRANSIENT_ERRORS.iter().any(|e| reason.starts_with(e))
+            }
+        }
+    }
+}
+
+/// Logs a diagnosis if `tx` reverted, called after waiting for a relay transaction, and returns
+/// it. Failures to diagnose are only logged, the relay itself has already finished
+pub async fn log_if_reverted(web3: &Web3, tx: Uint256) -> Option<RevertDiagnosis> {
+    let tx_hash = format!("{:#066x}", tx);
+    let res = diagnose_revert(web3, &tx_hash).await;
+    with_tx_hash(tx_hash.clone(), || match res {
+        Ok(Some(diagnosis)) => {
+            error!("{}", diagnosis);
+            Some(diagnosis)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Could not check whether {} reverted {:?}", tx_hash, e);
+            None
+        }
+    })
+}
+
+/// A diagnosis of `tx_hash` if it was mined and reverted, None if it succeeded
+pub async fn diagnose_revert(
+    web3: &Web3,
+    tx_hash: &str,
+) -> Result<Option<RevertDiagnosis>, GravityError> {
+    let client = HttpClient::new(&web3.get_url());
+    let receipt: Value = client
+        .request_method("eth_getTransactionReceipt", vec![tx_hash], REVERT_TIMEOUT)
+        .await?;
+    if receipt["status"].as_str() != Some("0x0") {
+        return Ok(None);
+    }
+    let tx: Value = client
+        .request_method("eth_getTransactionByHash", vec![tx_hash], REVERT_TIMEOUT)
+        .await?;
+    let block = receipt["blockNumber"].as_str().and_then(parse_quantity);
+    let input = tx["input"].as_str().and_then(|i| hex_str_to_bytes(i).ok());
+    // batch senders are not part of the calldata, they are irrelevant here
+    let placeholder_sender = CosmosAddress::from_slice(&[0u8; 20], "gravity").unwrap();
+    let call = input
+        .and_then(|i| decode_gravity_call(&i, placeholder_sender).ok().flatten())
+        .map(|c| describe_call(&c));
+
+    let (data, source) = match trace_revert_data(&client, tx_hash).await {
+        Ok(data) => (data, "debug_traceTransaction"),
+        Err(e) => {
+            trace!(
+                "debug_traceTransaction unavailable {:?}, replaying the call",
+                e
+            );
+            match block {
+                Some(block) => (
+                    replay_revert_data(&client, &tx, block).await,
+                    "eth_call at the parent block",
+                ),
+                None => (None, "the receipt"),
+            }
+        }
+    };
+    let (reason, cause) = match data {
+        Some(ReplayResult::Reverted(data)) => match decode_revert(&data) {
+            Some((reason, cause)) => (Some(reason), cause.map(|c| c.to_string())),
+            None => (Some(format!("0x{}", bytes_to_hex_str(&data))), None),
+        },
+        Some(ReplayResult::Succeeded) => (
+            None,
+            Some(
+                "the call succeeds on top of the parent block, a transaction earlier in the same \
+                 block changed the contract state first"
+                    .to_string(),
+            ),
+        ),
+        None => (None, None),
+    };
+    Ok(Some(RevertDiagnosis {
+        tx_hash: tx_hash.to_string(),
+        block,
+        call,
+        reason,
+        cause,
+        source,
+    }))
+}
+
+/// The outcome of recovering a transaction's revert data
+enum ReplayResult {
+    Reverted(Vec<u8>),
+    /// only possible when replaying, the state the transaction actually ran on differed
+    Succeeded,
+}
+
+async fn trace_revert_data(
+    client: &HttpClient,
+    tx_hash: &str,
+) -> Result<Option<ReplayResult>, GravityError> {
+    let trace: Value = client
+        .request_method(
+            "debug_traceTransaction",
+            (tx_hash.to_string(), json!({ "tracer": "callTracer" })),
+            REVERT_TIMEOUT,
+        )
+        .await?;
+    Ok(Some(ReplayResult::Reverted(
+        trace["output"]
+            .as_str()
+            .and_then(|o| hex_str_to_bytes(o).ok())
+            .unwrap_or_default(),
+    )))
+}
+
+/// Replays the transaction with eth_call on top of its parent block, nodes return the revert data
+/// inside the JSON-RPC error so it is searched for in the error text
+async fn replay_revert_data(client: &HttpClient, tx: &Value, block: u64) -> Option<ReplayResult> {
+    let call = json!({
+        "from": tx["from"],
+        "to": tx["to"],
+        "gas": tx["gas"],
+        "value": tx["value"],
+        "data": tx["input"],
+    });
+    let res: Result<Value, _> = client
+        .request_method(
+            "eth_call",
+            (call, format!("{:#x}", block.saturating_sub(1))),
+            REVERT_TIMEOUT,
+        )
+        .await;
+    match res {
+        Ok(_) => Some(ReplayResult::Succeeded),
+        Err(e) => Some(ReplayResult::Reverted(
+            find_revert_data(&format!("{:?}", e)).unwrap_or_default(),
+        )),
+    }
+}
+
+/// The longest 0x prefixed hex string in `text` that is at least a 4 byte selector long
+fn find_revert_data(text: &str) -> Option<Vec<u8>> {
+    text.match_indices("0x")
+        .map(|(start, _)| {
+            text[start + 2..]
+                .chars()
+                .take_while(|c| c.is_ascii_hexdigit())
+                .collect::<String>()
+        })
+        .filter(|hex| hex.len() >= 8 && hex.len() % 2 == 0)
+        .max_by_key(|hex| hex.len())
+        .and_then(|hex| hex_str_to_bytes(&hex).ok())
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Decodes revert data into a readable reason and, for Gravity.sol errors, the most likely cause
+pub fn decode_revert(data: &[u8]) -> Option<(String, Option<&'static str>)> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (sel, args) = data.split_at(4);
+    let word = |i: usize| args.get(i * 32..(i + 1) * 32).map(Uint256::from_be_bytes);
+    for (name, params, cause) in GRAVITY_ERRORS {
+        let signature = format!("{}({})", name, vec!["uint256"; params.len()].join(","));
+        if sel == selector(&signature) {
+            let args: Vec<String> = params
+                .iter()
+                .enumerate()
+                .map(|(i, p)| match word(i) {
+                    Some(v) => format!("{} {}", p, v),
+                    None => format!("{} ?", p),
+                })
+                .collect();
+            return Some((format!("{}({})", name, args.join(", ")), Some(*cause)));
+        }
+    }
+    if sel == selector("Error(string)") {
+        let len: usize = word(1)?.to_string().parse().ok()?;
+        let message = args.get(64..64 + len)?;
+        return Some((
+            format!("Error(\"{}\")", String::from_utf8_lossy(message)),
+            None,
+        ));
+    }
+    if sel == selector("Panic(uint256)") {
+        return Some((format!("Panic({})", word(0)?), None));
+    }
+    None
+}
+
+fn describe_call(call: &GravityCall) -> String {
+    match call {
+        GravityCall::ValsetUpdate { new_valset, .. } => {
+            format!("updateValset to nonce {}", new_valset.nonce)
+        }
+        GravityCall::SubmitBatch { batch, .. } => format!(
+            "submitBatch nonce {} for {}",
+            batch.nonce, batch.token_contract
+        ),
+        GravityCall::SubmitLogicCall { call, .. } => format!(
+            "submitLogicCall 0x{} nonce {}",
+            bytes_to_hex_str(&call.invalidation_id),
+            call.invalidation_nonce
+        ),
+    }
+}
+
+fn parse_quantity(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clarity::abi::{encode_tokens, AbiToken as Token};
+
+    #[test]
+    fn test_decode_revert() {
+        let mut data = selector("InvalidBatchNonce(uint256,uint256)").to_vec();
+        data.extend(encode_tokens(&[
+            Token::Uint(5u8.into()),
+            Token::Uint(6u8.into()),
+        ]));
+        let (reason, cause) = decode_revert(&data).unwrap();
+        assert_eq!(reason, "InvalidBatchNonce(newNonce 5, currentNonce 6)");
+        assert!(cause.unwrap().contains("another relayer"));
+
+        let data = selector("IncorrectCheckpoint()").to_vec();
+        assert_eq!(decode_revert(&data).unwrap().0, "IncorrectCheckpoint()");
+
+        let mut data = selector("Error(string)").to_vec();
+        data.extend(encode_tokens(&[Token::String("out of gas".to_string())]));
+        assert_eq!(
+            decode_revert(&data).unwrap(),
+            ("Error(\"out of gas\")".to_string(), None)
+        );
+
+        assert_eq!(decode_revert(&[0xde, 0xad, 0xbe, 0xef]), None);
+        assert_eq!(decode_revert(&[]), None);
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let diagnosis = |reason: Option<&str>| RevertDiagnosis {
+            tx_hash: "0x01".to_string(),
+            block: None,
+            call: None,
+            reason: reason.map(|r| r.to_string()),
+            cause: None,
+            source: "the receipt",
+        };
+        assert!(diagnosis(None).is_transient());
+        assert!(diagnosis(Some("0x")).is_transient());
+        assert!(diagnosis(Some("InvalidBatchNonce(newNonce 5, currentNonce 6)")).is_transient());
+        assert!(!diagnosis(Some("MalformedBatch()")).is_transient());
+        assert!(!diagnosis(Some("Error(\"transfer amount exceeds balance\")")).is_transient());
+    }
+
+    #[test]
+    fn test_find_revert_data() {
+        let text = "JsonRpcError { code: 3, message: \"execution reverted\", data: Some(String(\"0x8db7f0bb\")) }";
+        assert_eq!(find_revert_data(text), Some(vec![0x8d, 0xb7, 0xf0, 0xbb]));
+        assert_eq!(find_revert_data("reverted at 0x12"), None);
+    }
+}
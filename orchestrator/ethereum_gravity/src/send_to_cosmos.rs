@@ -85,16 +85,12 @@ pub async fn send_to_cosmos(
     }
 
     info!("sending to on cosmos {}", cosmos_destination);
-    let encoded_destination_address = Token::String(cosmos_destination.to_string());
 
     let tx_hash = web3
         .send_prepared_transaction(
             web3.prepare_transaction(
                 gravity_contract,
-                encode_call(
-                    "sendToCosmos(address,string,uint256)",
-                    &[erc20.into(), encoded_destination_address, amount.into()],
-                )?,
+                encode_send_to_cosmos_payload(erc20, cosmos_destination, amount)?,
                 0u32.into(),
                 sender_secret,
                 options,
@@ -109,3 +105,16 @@ pub async fn send_to_cosmos(
 
     Ok(tx_hash)
 }
+
+/// Encodes the sendToCosmos call made by `send_to_cosmos`
+pub fn encode_send_to_cosmos_payload(
+    erc20: Address,
+    cosmos_destination: CosmosAddress,
+    amount: Uint256,
+) -> Result<Vec<u8>, GravityError> {
+    let encoded_destination_address = Token::String(cosmos_destination.to_string());
+    Ok(encode_call(
+        "sendToCosmos(address,string,uint256)",
+        &[erc20.into(), encoded_destination_address, amount.into()],
+    )?)
+}
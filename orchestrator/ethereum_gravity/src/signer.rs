@@ -0,0 +1,390 @@
+//! Signing of valset, batch and logic call confirms with an Ethereum key that may live outside of
+//! the orchestrator. Validators who keep their keys in a KMS or HSM can point the orchestrator at
+//! it instead of handing gbt the private key. Remote backends sign the 32 byte hash of the message
+//! with the Ethereum signed message prefix applied, exactly what `PrivateKey::sign_ethereum_msg`
+//! signs, and return DER signatures without a recovery id. These are normalized to low-s and the
+//! recovery id is found by recovering against the key's address. The AWS KMS and YubiHSM backends
+//! pull in large SDKs and are only built with the aws-kms and yubihsm-signer features
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clarity::utils::get_ethereum_msg_hash;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Signature, Uint256};
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{EthereumSignerBackend, EthereumSignerConfig};
+use secp256k1::ecdsa::Signature as DerSignature;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+const GCP_KMS_URL: &str = "https://cloudkms.googleapis.com/v1";
+const GCP_METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// Used instead of the metadata server when set, as with `gcloud auth print-access-token`
+const GCP_TOKEN_ENV: &str = "GOOGLE_OAUTH_ACCESS_TOKEN";
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A key that signs Ethereum messages for the orchestrator
+#[async_trait]
+pub trait EthereumSigner: Send + Sync {
+    /// The Ethereum address of the key, this is the delegate Ethereum address on chain
+    fn address(&self) -> EthAddress;
+    /// Signs `msg` with the Ethereum signed message prefix, as `PrivateKey::sign_ethereum_msg`
+    async fn sign_ethereum_msg(&self, msg: &[u8]) -> Result<Signature, GravityError>;
+}
+
+#[async_trait]
+impl EthereumSigner for EthPrivateKey {
+    fn address(&self) -> EthAddress {
+        self.to_address()
+    }
+
+    async fn sign_ethereum_msg(&self, msg: &[u8]) -> Result<Signature, GravityError> {
+        Ok(EthPrivateKey::sign_ethereum_msg(self, msg))
+    }
+}
+
+/// Creates the signer selected by `config`, `local_key` is used for the Local backend
+pub async fn build_ethereum_signer(
+    config: &EthereumSignerConfig,
+    local_key: EthPrivateKey,
+) -> Result<Arc<dyn EthereumSigner>, GravityError> {
+    let key_id = || {
+        config.key_id.clone().ok_or_else(|| {
+            GravityError::InvalidOptionsError(format!(
+                "The {:?} Ethereum signer requires a key_id",
+                config.backend
+            ))
+        })
+    };
+    Ok(match config.backend {
+        EthereumSignerBackend::Local => Arc::new(local_key),
+        #[cfg(feature = "aws-kms")]
+        EthereumSignerBackend::AwsKms => Arc::new(AwsKmsSigner::new(key_id()?).await?),
+        EthereumSignerBackend::GcpKms => Arc::new(GcpKmsSigner::new(key_id()?).await?),
+        #[cfg(feature = "yubihsm-signer")]
+        EthereumSignerBackend::YubiHsm => {
+            let password = env::var(&config.yubihsm_password_env).map_err(|_| {
+                GravityError::InvalidOptionsError(format!(
+                    "Set the YubiHSM password in {}",
+                    config.yubihsm_password_env
+                ))
+            })?;
+            Arc::new(YubiHsmSigner::new(
+                &config.yubihsm_connector,
+                config.yubihsm_auth_key_id,
+                &password,
+                &key_id()?,
+            )?)
+        }
+        #[allow(unreachable_patterns)]
+        backend => {
+            return Err(GravityError::InvalidOptionsError(format!(
+                "This build does not support the {:?} Ethereum signer, build with --features {}",
+                backend,
+                match backend {
+                    EthereumSignerBackend::AwsKms => "aws-kms",
+                    _ => "yubihsm-signer",
+                }
+            )))
+        }
+    })
+}
+
+/// A secp256k1 key in AWS KMS. Credentials and region are read from the environment, the shared
+/// config files or the instance role, as with the AWS CLI
+#[cfg(feature = "aws-kms")]
+pub struct AwsKmsSigner {
+    client: aws_sdk_kms::Client,
+    key_id: String,
+    address: EthAddress,
+}
+
+#[cfg(feature = "aws-kms")]
+impl AwsKmsSigner {
+    pub async fn new(key_id: String) -> Result<AwsKmsSigner, GravityError> {
+        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        let client = aws_sdk_kms::Client::new(&config);
+        let public_key = client
+            .get_public_key()
+            .key_id(&key_id)
+            .send()
+            .await
+            .map_err(|e| remote_error("AWS KMS", e))?;
+        let public_key = public_key
+            .public_key()
+            .ok_or_else(|| remote_error("AWS KMS", "no public key returned"))?;
+        Ok(AwsKmsSigner {
+            client,
+            key_id,
+            address: address_from_public_key(public_key.as_ref())?,
+        })
+    }
+}
+
+#[cfg(feature = "aws-kms")]
+#[async_trait]
+impl EthereumSigner for AwsKmsSigner {
+    fn address(&self) -> EthAddress {
+        self.address
+    }
+
+    async fn sign_ethereum_msg(&self, msg: &[u8]) -> Result<Signature, GravityError> {
+        let hash = get_ethereum_msg_hash(msg);
+        let res = self
+            .client
+            .sign()
+            .key_id(&self.key_id)
+            .message(aws_sdk_kms::primitives::Blob::new(hash.clone()))
+            .message_type(aws_sdk_kms::types::MessageType::Digest)
+            .signing_algorithm(aws_sdk_kms::types::SigningAlgorithmSpec::EcdsaSha256)
+            .send()
+            .await
+            .map_err(|e| remote_error("AWS KMS", e))?;
+        let der = res
+            .signature()
+            .ok_or_else(|| remote_error("AWS KMS", "no signature returned"))?;
+        signature_from_der(der.as_ref(), &hash, self.address)
+    }
+}
+
+/// A secp256k1 key version in Google Cloud KMS, `key_name` is the full resource name
+/// projects/../locations/../keyRings/../cryptoKeys/../cryptoKeyVersions/... Access tokens are
+/// read from GOOGLE_OAUTH_ACCESS_TOKEN or the metadata server of the instance
+pub struct GcpKmsSigner {
+    client: reqwest::Client,
+    key_name: String,
+    address: EthAddress,
+}
+
+impl GcpKmsSigner {
+    pub async fn new(key_name: String) -> Result<GcpKmsSigner, GravityError> {
+        let client = reqwest::Client::builder()
+            .timeout(REMOTE_SIGNER_TIMEOUT)
+            .build()
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        let token = gcp_access_token(&client).await?;
+        let response = client
+            .get(format!("{}/{}/publicKey", GCP_KMS_URL, key_name))
+            .bearer_auth(token)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        let res: Value = response
+            .json()
+            .await
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        let pem = res["pem"]
+            .as_str()
+            .ok_or_else(|| remote_error("Google Cloud KMS", "no public key returned"))?;
+        let der: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        let der = BASE64
+            .decode(der)
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        Ok(GcpKmsSigner {
+            address: address_from_public_key(&der)?,
+            client,
+            key_name,
+        })
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for GcpKmsSigner {
+    fn address(&self) -> EthAddress {
+        self.address
+    }
+
+    async fn sign_ethereum_msg(&self, msg: &[u8]) -> Result<Signature, GravityError> {
+        let hash = get_ethereum_msg_hash(msg);
+        // the key only accepts sha256 digests, any 32 byte hash is signed the same way
+        let token = gcp_access_token(&self.client).await?;
+        let response = self
+            .client
+            .post(format!("{}/{}:asymmetricSign", GCP_KMS_URL, self.key_name))
+            .bearer_auth(token)
+            .json(&json!({ "digest": { "sha256": BASE64.encode(&hash) } }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        let res: Value = response
+            .json()
+            .await
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        let der = res["signature"]
+            .as_str()
+            .ok_or_else(|| remote_error("Google Cloud KMS", "no signature returned"))?;
+        let der = BASE64
+            .decode(der)
+            .map_err(|e| remote_error("Google Cloud KMS", e))?;
+        signature_from_der(&der, &hash, self.address)
+    }
+}
+
+async fn gcp_access_token(client: &reqwest::Client) -> Result<String, GravityError> {
+    if let Ok(token) = env::var(GCP_TOKEN_ENV) {
+        return Ok(token);
+    }
+    let response = client
+        .get(GCP_METADATA_TOKEN_URL)
+        .header("Metadata-Flavor", "Google")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| remote_error("Google Cloud metadata server", e))?;
+    let res: Value = response
+        .json()
+        .await
+        .map_err(|e| remote_error("Google Cloud metadata server", e))?;
+    res["access_token"]
+        .as_str()
+        .map(|t| t.to_string())
+        .ok_or_else(|| remote_error("Google Cloud metadata server", "no access token returned"))
+}
+
+/// A secp256k1 asymmetric key on a YubiHSM 2, reached through yubihsm-connector
+#[cfg(feature = "yubihsm-signer")]
+pub struct YubiHsmSigner {
+    client: yubihsm::Client,
+    key_id: u16,
+    address: EthAddress,
+}
+
+#[cfg(feature = "yubihsm-signer")]
+impl YubiHsmSigner {
+    pub fn new(
+        connector: &str,
+        auth_key_id: u16,
+        password: &str,
+        key_id: &str,
+    ) -> Result<YubiHsmSigner, GravityError> {
+        let (addr, port) = connector
+            .rsplit_once(':')
+            .and_then(|(addr, port)| Some((addr.to_string(), port.parse().ok()?)))
+            .ok_or_else(|| {
+                GravityError::InvalidOptionsError(format!(
+                    "Invalid yubihsm_connector {}, expected host:port",
+                    connector
+                ))
+            })?;
+        let key_id = key_id.parse().map_err(|_| {
+            GravityError::InvalidOptionsError(format!("Invalid YubiHSM object id {}", key_id))
+        })?;
+        let connector = yubihsm::Connector::http(&yubihsm::HttpConfig {
+            addr,
+            port,
+            timeout_ms: REMOTE_SIGNER_TIMEOUT.as_millis() as u64,
+        });
+        let credentials = yubihsm::Credentials::from_password(auth_key_id, password.as_bytes());
+        let client = yubihsm::Client::open(connector, credentials, true)
+            .map_err(|e| remote_error("YubiHSM", e))?;
+        let public_key = client
+            .get_public_key(key_id)
+            .map_err(|e| remote_error("YubiHSM", e))?;
+        Ok(YubiHsmSigner {
+            address: address_from_public_key(public_key.as_ref())?,
+            client,
+            key_id,
+        })
+    }
+}
+
+#[cfg(feature = "yubihsm-signer")]
+#[async_trait]
+impl EthereumSigner for YubiHsmSigner {
+    fn address(&self) -> EthAddress {
+        self.address
+    }
+
+    async fn sign_ethereum_msg(&self, msg: &[u8]) -> Result<Signature, GravityError> {
+        let hash = get_ethereum_msg_hash(msg);
+        // the yubihsm client is blocking and holds its session lock for the whole round trip
+        let client = self.client.clone();
+        let key_id = self.key_id;
+        let digest = hash.clone();
+        let der =
+            tokio::task::spawn_blocking(move || client.sign_ecdsa_prehash_raw(key_id, digest))
+                .await
+                .map_err(|e| remote_error("YubiHSM", e))?
+                .map_err(|e| remote_error("YubiHSM", e))?;
+        signature_from_der(&der, &hash, self.address)
+    }
+}
+
+fn remote_error(backend: &str, e: impl std::fmt::Display) -> GravityError {
+    GravityError::InvalidOptionsError(format!("{} signer failed: {}", backend, e))
+}
+
+/// The Ethereum address of an uncompressed secp256k1 public key. KMS services return it wrapped
+/// in a DER SubjectPublicKeyInfo and HSMs as the bare coordinates, in both the last 64 bytes are
+/// the x and y coordinates
+fn address_from_public_key(public_key: &[u8]) -> Result<EthAddress, GravityError> {
+    if public_key.len() < 64 {
+        return Err(GravityError::InvalidOptionsError(format!(
+            "Invalid secp256k1 public key of {} bytes, is the key a secp256k1 key?",
+            public_key.len()
+        )));
+    }
+    let hash = Keccak256::digest(&public_key[public_key.len() - 64..]);
+    Ok(EthAddress::from_slice(&hash[12..])?)
+}
+
+/// Converts a DER signature of `hash` by `address` to an Ethereum signature. The recovery id is
+/// not part of DER signatures, both are tried and the one recovering `address` kept
+fn signature_from_der(
+    der: &[u8],
+    hash: &[u8],
+    address: EthAddress,
+) -> Result<Signature, GravityError> {
+    let mut signature = DerSignature::from_der(der)
+        .map_err(|e| GravityError::InvalidOptionsError(format!("Invalid DER signature {}", e)))?;
+    // Ethereum rejects high-s signatures, KMS and HSM signatures may be either
+    signature.normalize_s();
+    let compact = signature.serialize_compact();
+    let r = Uint256::from_be_bytes(&compact[..32]);
+    let s = Uint256::from_be_bytes(&compact[32..]);
+    for v in [false, true] {
+        let candidate = Signature::ModernSignature { v, r, s };
+        if candidate.recover(hash).ok() == Some(address) {
+            return Ok(candidate);
+        }
+    }
+    Err(GravityError::InvalidOptionsError(format!(
+        "Signature does not recover to the signer's address {}",
+        address
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+    #[test]
+    fn test_remote_signature_conversion() {
+        let secp = Secp256k1::new();
+        let secret = [7u8; 32];
+        let key = SecretKey::from_slice(&secret).unwrap();
+        let local = EthPrivateKey::from_bytes(secret).unwrap();
+        // the uncompressed key as a 0x04 prefixed point, as the tail of an SPKI
+        let public_key = PublicKey::from_secret_key(&secp, &key).serialize_uncompressed();
+        assert_eq!(
+            address_from_public_key(&public_key).unwrap(),
+            local.to_address()
+        );
+        assert!(address_from_public_key(&public_key[..33]).is_err());
+
+        let hash = get_ethereum_msg_hash(b"gravity checkpoint");
+        let signature = secp.sign_ecdsa(&Message::from_slice(&hash).unwrap(), &key);
+        let converted =
+            signature_from_der(&signature.serialize_der(), &hash, local.to_address()).unwrap();
+        assert_eq!(converted.recover(&hash).unwrap(), local.to_address());
+        let other = EthAddress::from_slice(&[1u8; 20]).unwrap();
+        assert!(signature_from_der(&signature.serialize_der(), &hash, other).is_err());
+    }
+}
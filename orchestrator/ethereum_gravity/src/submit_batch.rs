@@ -1,15 +1,28 @@
 use crate::message_signatures::encode_tx_batch_confirm_hashed;
-use crate::utils::{encode_valset_struct, get_tx_batch_nonce, GasCost};
+use crate::private_relay::broadcast_relaying_transaction;
+use crate::relay_signatures::select_relay_signatures;
+use crate::revert::log_if_reverted;
+use crate::utils::{
+    encode_valset_struct, gas_price_options, get_tx_batch_nonce, get_tx_batch_nonce_pending,
+    record_relaying_gas, GasCost, RelayOutcome, RelayingGasPrice,
+};
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::{Address as EthAddress, Uint256};
 use gravity_utils::error::GravityError;
+use gravity_utils::log_context::with_tx_hash;
 use gravity_utils::types::*;
 use std::{cmp::min, time::Duration};
+use tracing::instrument;
 use web30::{client::Web3, types::TransactionRequest};
 
+/// The ABI signature of the Gravity contract's submitBatch function
+pub const SUBMIT_BATCH_SIGNATURE: &str =
+    "submitBatch((address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[],uint256[],address[],uint256[],uint256,address,uint256)";
+
 /// this function generates an appropriate Ethereum transaction
 /// to submit the provided transaction batch
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(token = %batch.token_contract, nonce = batch.nonce))]
 pub async fn send_eth_transaction_batch(
     current_valset: Valset,
     batch: TransactionBatch,
@@ -19,7 +32,10 @@ pub async fn send_eth_transaction_batch(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
-) -> Result<(), GravityError> {
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
+    private_relay: &PrivateRelayConfig,
+) -> Result<RelayOutcome, GravityError> {
     let new_batch_nonce = batch.nonce;
     let eth_address = our_eth_key.to_address();
     info!(
@@ -41,31 +57,71 @@ pub async fn send_eth_transaction_batch(
             "Someone else updated the batch to {}, exiting early",
             before_nonce
         );
-        return Ok(());
+        return Ok(RelayOutcome::Skipped);
     } else if current_block_height > batch.batch_timeout.into() {
         info!(
             "This batch is timed out. timeout block: {} current block: {}, exiting early",
             current_block_height, batch.batch_timeout
         );
-        return Ok(());
+        return Ok(RelayOutcome::Skipped);
     }
 
-    let payload = encode_batch_payload(current_valset, &batch, confirms, gravity_id)?;
-    let tx = web3
-        .send_prepared_transaction(
-            web3.prepare_transaction(
-                gravity_contract_address,
-                payload,
-                0u32.into(),
-                our_eth_key,
-                vec![],
-            )
-            .await?,
+    let payload = encode_batch_payload(
+        current_valset,
+        &batch,
+        confirms,
+        gravity_id,
+        trim_signatures,
+    )?;
+    let prepared = web3
+        .prepare_transaction(
+            gravity_contract_address,
+            payload,
+            0u32.into(),
+            our_eth_key,
+            gas_price_options(gas_price),
         )
         .await?;
-    info!("Sent batch update with txid {:#066x}", tx);
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    // preparing the transaction takes several round trips, check once more right before
+    // broadcasting that nobody else got there first, otherwise we would pay for a revert
+    match get_tx_batch_nonce_pending(
+        gravity_contract_address,
+        batch.token_contract,
+        eth_address,
+        web3,
+    )
+    .await
+    {
+        Ok(pending_nonce) if pending_nonce >= new_batch_nonce => {
+            info!(
+                "Someone else updated the batch to {} in the pending block, not broadcasting",
+                pending_nonce
+            );
+            return Ok(RelayOutcome::Skipped);
+        }
+        Ok(_) => {}
+        Err(e) => warn!(
+            "Failed to check the batch nonce at the pending block {:?}",
+            e
+        ),
+    }
+
+    let tx = broadcast_relaying_transaction(web3, prepared, private_relay).await?;
+    let tx_hash = format!("{:#066x}", tx);
+    with_tx_hash(tx_hash.clone(), || {
+        info!("Sent batch update with txid {}", tx_hash)
+    });
+
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    let revert = log_if_reverted(web3, tx).await;
+    if res.is_ok() {
+        record_relaying_gas(web3, tx, "batch").await;
+    }
+    res?;
+    if let Some(revert) = revert.filter(|r| !r.is_transient()) {
+        return Err(GravityError::RelayReverted(revert.to_string()));
+    }
 
     let last_nonce = get_tx_batch_nonce(
         gravity_contract_address,
@@ -82,7 +138,7 @@ pub async fn send_eth_transaction_batch(
     } else {
         info!("Successfully updated Batch with new Nonce {:?}", last_nonce);
     }
-    Ok(())
+    Ok(RelayOutcome::Relayed)
 }
 
 /// Returns the cost in Eth of sending this batch, because of the way eip1559 fee
@@ -90,6 +146,7 @@ pub async fn send_eth_transaction_batch(
 /// as 12.5% per block. In order to prevent race conditions we pad our estimate by 20%
 /// if the gas price has in fact gone down we'll be refunded. But we must bake
 /// this uncertainty into our cost estimates
+#[allow(clippy::too_many_arguments)]
 pub async fn estimate_tx_batch_cost(
     current_valset: Valset,
     batch: TransactionBatch,
@@ -98,6 +155,8 @@ pub async fn estimate_tx_batch_cost(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_address: EthAddress,
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
 ) -> Result<GasCost, GravityError> {
     let our_balance = web3.eth_get_balance(our_eth_address).await?;
     let our_nonce = web3.eth_get_transaction_count(our_eth_address).await?;
@@ -106,9 +165,14 @@ pub async fn estimate_tx_batch_cost(
         .await?
         .expect("Failed to get chain id from ETH node?");
     let gas_limit = min((u64::MAX - 1).into(), our_balance);
-    let gas_price = web3.eth_gas_price().await?;
-    // increase the value by 20% without using floating point multiplication
-    let gas_price = gas_price + (gas_price / 5u8.into());
+    let gas_price = match gas_price {
+        Some(gas_price) => gas_price.max_fee,
+        None => {
+            let gas_price = web3.eth_gas_price().await?;
+            // increase the value by 20% without using floating point multiplication
+            gas_price + (gas_price / 5u8.into())
+        }
+    };
     let zero: Uint256 = 0u8.into();
     let val = web3
         .eth_estimate_gas(TransactionRequest::Eip1559 {
@@ -121,7 +185,16 @@ pub async fn estimate_tx_batch_cost(
             max_priority_fee_per_gas: None,
             gas: Some(gas_limit.into()),
             value: Some(zero.into()),
-            data: Some(encode_batch_payload(current_valset, &batch, confirms, gravity_id)?.into()),
+            data: Some(
+                encode_batch_payload(
+                    current_valset,
+                    &batch,
+                    confirms,
+                    gravity_id,
+                    trim_signatures,
+                )?
+                .into(),
+            ),
         })
         .await?;
 
@@ -137,11 +210,12 @@ fn encode_batch_payload(
     batch: &TransactionBatch,
     confirms: &[BatchConfirmResponse],
     gravity_id: String,
+    trim_signatures: bool,
 ) -> Result<Vec<u8>, GravityError> {
     let current_valset_token = encode_valset_struct(&current_valset);
     let new_batch_nonce = batch.nonce;
     let hash = encode_tx_batch_confirm_hashed(gravity_id, batch.clone());
-    let sig_data = current_valset.order_sigs(&hash, confirms)?;
+    let sig_data = select_relay_signatures(&current_valset, &hash, confirms, trim_signatures)?;
     let sig_arrays = to_arrays(sig_data);
     let (amounts, destinations, fees) = batch.get_checkpoint_values();
 
@@ -175,8 +249,7 @@ fn encode_batch_payload(
         batch.token_contract.into(),
         batch.batch_timeout.into(),
     ];
-    let payload = clarity::abi::encode_call("submitBatch((address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[],uint256[],address[],uint256[],uint256,address,uint256)",
-    tokens).unwrap();
+    let payload = clarity::abi::encode_call(SUBMIT_BATCH_SIGNATURE, tokens).unwrap();
     trace!("Tokens {:?}", tokens);
 
     Ok(payload)
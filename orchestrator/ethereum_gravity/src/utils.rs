@@ -1,12 +1,19 @@
 use clarity::abi::encode_call;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
 use clarity::Address as EthAddress;
 use clarity::Uint256;
 use clarity::{abi::AbiToken as Token, constants::zero_address};
 use gravity_utils::num_conversion::downcast_uint256;
 use gravity_utils::types::*;
-use web30::types::TransactionRequest;
+use metrics_exporter::metrics_relayer_gas;
+use serde_json::{json, Value};
+use std::time::Duration;
+use web30::jsonrpc::client::HttpClient;
+use web30::types::{SendTxOption, TransactionRequest};
 use web30::{client::Web3, jsonrpc::error::Web3Error};
 
+const PENDING_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Gets the latest validator set nonce
 pub async fn get_valset_nonce(
     contract_address: EthAddress,
@@ -25,6 +32,54 @@ pub async fn get_valset_nonce(
     Ok(downcast_uint256(real_num).expect("Valset nonce overflow! Bridge Halt!"))
 }
 
+/// Gets the validator set nonce as of a past block, this requires an archive node for any block
+/// older than the node keeps state for
+pub async fn get_valset_nonce_at_block(
+    contract_address: EthAddress,
+    caller_address: EthAddress,
+    block: Uint256,
+    web3: &Web3,
+) -> Result<u64, Web3Error> {
+    let payload = encode_call("state_lastValsetNonce()", &[]).unwrap();
+    let request = TransactionRequest::quick_tx(caller_address, contract_address, payload);
+    let val = web3.simulate_transaction(request, Some(block)).await?;
+    let real_num = Uint256::from_be_bytes(&val);
+    Ok(downcast_uint256(real_num).expect("Valset nonce overflow! Bridge Halt!"))
+}
+
+/// Gets the validator set nonce as of the node's pending block, this includes the effects of
+/// transactions that are waiting to be mined
+pub async fn get_valset_nonce_pending(
+    contract_address: EthAddress,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<u64, Web3Error> {
+    let payload = encode_call("state_lastValsetNonce()", &[]).unwrap();
+    let val = call_at_pending_block(caller_address, contract_address, payload, web3).await?;
+    let real_num = Uint256::from_be_bytes(&val);
+    Ok(downcast_uint256(real_num).expect("Valset nonce overflow! Bridge Halt!"))
+}
+
+/// Performs an eth_call against the pending block rather than the latest one, Web3::simulate_transaction
+/// only accepts block numbers so the request is made directly
+async fn call_at_pending_block(
+    caller_address: EthAddress,
+    contract_address: EthAddress,
+    payload: Vec<u8>,
+    web3: &Web3,
+) -> Result<Vec<u8>, Web3Error> {
+    let client = HttpClient::new(&web3.get_url());
+    let request = json!({
+        "from": caller_address.to_string(),
+        "to": contract_address.to_string(),
+        "data": format!("0x{}", bytes_to_hex_str(&payload)),
+    });
+    let res: String = client
+        .request_method("eth_call", (request, "pending"), PENDING_CALL_TIMEOUT)
+        .await?;
+    hex_str_to_bytes(&res).map_err(|e| Web3Error::BadResponse(format!("{:?}", e)))
+}
+
 /// Gets the latest transaction batch nonce
 pub async fn get_tx_batch_nonce(
     gravity_contract_address: EthAddress,
@@ -44,6 +99,21 @@ pub async fn get_tx_batch_nonce(
     Ok(downcast_uint256(real_num).expect("TxBatch nonce overflow! Bridge Halt!"))
 }
 
+/// Gets the transaction batch nonce as of the node's pending block, this includes the effects of
+/// transactions that are waiting to be mined
+pub async fn get_tx_batch_nonce_pending(
+    gravity_contract_address: EthAddress,
+    erc20_contract_address: EthAddress,
+    caller_address: EthAddress,
+    web3: &Web3,
+) -> Result<u64, Web3Error> {
+    let payload = encode_call("lastBatchNonce(address)", &[erc20_contract_address.into()]).unwrap();
+    let val =
+        call_at_pending_block(caller_address, gravity_contract_address, payload, web3).await?;
+    let real_num = Uint256::from_be_bytes(&val);
+    Ok(downcast_uint256(real_num).expect("TxBatch nonce overflow! Bridge Halt!"))
+}
+
 /// Gets the latest transaction batch nonce
 pub async fn get_logic_call_nonce(
     gravity_contract_address: EthAddress,
@@ -123,6 +193,77 @@ pub async fn get_gravity_sol_address(
     }
 }
 
+/// The fees a relaying transaction pays per gas, as chosen by the relayer's gas oracle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayingGasPrice {
+    /// The most paid per gas including the priority fee, relaying costs are estimated with it
+    pub max_fee: Uint256,
+    /// The tip per gas for the block producer, None leaves it to web30
+    pub priority_fee: Option<Uint256>,
+}
+
+impl From<Uint256> for RelayingGasPrice {
+    fn from(max_fee: Uint256) -> Self {
+        RelayingGasPrice {
+            max_fee,
+            priority_fee: None,
+        }
+    }
+}
+
+/// Transaction options that pay at most `gas_price` per gas, or no options at all to let web30
+/// price the transaction from the node's estimate
+pub fn gas_price_options(gas_price: Option<RelayingGasPrice>) -> Vec<SendTxOption> {
+    let mut options = Vec::new();
+    if let Some(gas_price) = gas_price {
+        options.push(SendTxOption::GasMaxFee(gas_price.max_fee));
+        if let Some(priority_fee) = gas_price.priority_fee {
+            options.push(SendTxOption::GasPriorityFee(priority_fee));
+        }
+    }
+    options
+}
+
+/// Records the gas used by relayed transaction `tx` of `kind` and what it cost in the metrics, from
+/// its receipt. Reverted transactions are counted too, their gas is spent all the same
+pub async fn record_relaying_gas(web3: &Web3, tx: Uint256, kind: &str) {
+    let client = HttpClient::new(&web3.get_url());
+    let receipt: Result<Value, Web3Error> = client
+        .request_method(
+            "eth_getTransactionReceipt",
+            vec![format!("{:#066x}", tx)],
+            PENDING_CALL_TIMEOUT,
+        )
+        .await;
+    let receipt = match receipt {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("Could not get the receipt of {:#066x} {:?}", tx, e);
+            return;
+        }
+    };
+    let quantity = |field: &str| {
+        receipt[field]
+            .as_str()
+            .and_then(|v| u128::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+    };
+    if let (Some(gas_used), Some(gas_price)) = (quantity("gasUsed"), quantity("effectiveGasPrice"))
+    {
+        metrics_relayer_gas(kind, gas_used as u64, gas_used.saturating_mul(gas_price));
+    }
+}
+
+/// The result of an attempt to relay a valset or batch, a relay that was skipped because
+/// someone else got there first or because it timed out did not spend anything and must
+/// not be treated as a successful relay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayOutcome {
+    /// The transaction was broadcast and confirmed
+    Relayed,
+    /// Nothing was broadcast
+    Skipped,
+}
+
 /// Just a helper struct to represent the cost of actions on Ethereum
 #[derive(Debug, Default, Clone)]
 pub struct GasCost {
@@ -1,15 +1,27 @@
 use crate::message_signatures::encode_valset_confirm_hashed;
-use crate::utils::{encode_valset_struct, get_valset_nonce, GasCost};
+use crate::relay_signatures::select_relay_signatures;
+use crate::revert::log_if_reverted;
+use crate::utils::{
+    encode_valset_struct, gas_price_options, get_valset_nonce, get_valset_nonce_pending,
+    record_relaying_gas, GasCost, RelayOutcome, RelayingGasPrice,
+};
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::{Address as EthAddress, Uint256};
 use gravity_utils::error::GravityError;
+use gravity_utils::log_context::with_tx_hash;
 use gravity_utils::types::*;
 use std::{cmp::min, time::Duration};
+use tracing::instrument;
 use web30::{client::Web3, types::TransactionRequest};
 
+/// The ABI signature of the Gravity contract's updateValset function
+pub const UPDATE_VALSET_SIGNATURE: &str =
+    "updateValset((address[],uint256[],uint256,uint256,address),(address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[])";
+
 /// this function generates an appropriate Ethereum transaction
 /// to submit the provided validator set and signatures.
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(old_nonce = old_valset.nonce, new_nonce = new_valset.nonce))]
 pub async fn send_eth_valset_update(
     new_valset: Valset,
     old_valset: Valset,
@@ -19,7 +31,9 @@ pub async fn send_eth_valset_update(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_key: EthPrivateKey,
-) -> Result<(), GravityError> {
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
+) -> Result<RelayOutcome, GravityError> {
     let old_nonce = old_valset.nonce;
     let new_nonce = new_valset.nonce;
     assert!(new_nonce > old_nonce);
@@ -34,26 +48,55 @@ pub async fn send_eth_valset_update(
             "Someone else updated the valset to {}, exiting early",
             before_nonce
         );
-        return Ok(());
+        return Ok(RelayOutcome::Skipped);
     }
 
-    let payload = encode_valset_update_payload(new_valset, old_valset, confirms, gravity_id)?;
-
-    let tx = web3
-        .send_prepared_transaction(
-            web3.prepare_transaction(
-                gravity_contract_address,
-                payload,
-                0u32.into(),
-                our_eth_key,
-                vec![],
-            )
-            .await?,
+    let payload = encode_valset_update_payload(
+        new_valset,
+        old_valset,
+        confirms,
+        gravity_id,
+        trim_signatures,
+    )?;
+    let prepared = web3
+        .prepare_transaction(
+            gravity_contract_address,
+            payload,
+            0u32.into(),
+            our_eth_key,
+            gas_price_options(gas_price),
         )
         .await?;
-    info!("Sent valset update with txid {:#066x}", tx);
 
-    web3.wait_for_transaction(tx, timeout, None).await?;
+    // preparing the transaction takes several round trips, check once more right before
+    // broadcasting that nobody else got there first, otherwise we would pay for a revert
+    match get_valset_nonce_pending(gravity_contract_address, eth_address, web3).await {
+        Ok(pending_nonce) if pending_nonce != old_nonce => {
+            info!(
+                "Someone else updated the valset to {} in the pending block, not broadcasting",
+                pending_nonce
+            );
+            return Ok(RelayOutcome::Skipped);
+        }
+        Ok(_) => {}
+        Err(e) => warn!(
+            "Failed to check the valset nonce at the pending block {:?}",
+            e
+        ),
+    }
+
+    let tx = web3.send_prepared_transaction(prepared).await?;
+    let tx_hash = format!("{:#066x}", tx);
+    with_tx_hash(tx_hash.clone(), || {
+        info!("Sent valset update with txid {}", tx_hash)
+    });
+
+    let res = web3.wait_for_transaction(tx, timeout, None).await;
+    log_if_reverted(web3, tx).await;
+    if res.is_ok() {
+        record_relaying_gas(web3, tx, "valset").await;
+    }
+    res?;
 
     let last_nonce = get_valset_nonce(gravity_contract_address, eth_address, web3).await?;
     if last_nonce != new_nonce {
@@ -67,7 +110,7 @@ pub async fn send_eth_valset_update(
             last_nonce
         );
     }
-    Ok(())
+    Ok(RelayOutcome::Relayed)
 }
 
 /// Returns the cost in Eth of sending this valset update, because of the way eip1559 fee
@@ -75,6 +118,7 @@ pub async fn send_eth_valset_update(
 /// as 12.5% per block. In order to prevent race conditions we pad our estimate by 20%
 /// if the gas price has in fact gone down we'll be refunded. But we must bake
 /// this uncertainty into our cost estimates
+#[allow(clippy::too_many_arguments)]
 pub async fn estimate_valset_cost(
     new_valset: &Valset,
     old_valset: &Valset,
@@ -83,6 +127,8 @@ pub async fn estimate_valset_cost(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     our_eth_address: EthAddress,
+    gas_price: Option<RelayingGasPrice>,
+    trim_signatures: bool,
 ) -> Result<GasCost, GravityError> {
     let our_balance = web3.eth_get_balance(our_eth_address).await?;
     let our_nonce = web3.eth_get_transaction_count(our_eth_address).await?;
@@ -91,9 +137,14 @@ pub async fn estimate_valset_cost(
         .await?
         .expect("No chain id response from eth node?");
     let gas_limit = min((u64::MAX - 1).into(), our_balance);
-    let gas_price = web3.eth_gas_price().await?;
-    // increase the value by 20% without using floating point multiplication
-    let gas_price = gas_price + (gas_price / 5u8.into());
+    let gas_price = match gas_price {
+        Some(gas_price) => gas_price.max_fee,
+        None => {
+            let gas_price = web3.eth_gas_price().await?;
+            // increase the value by 20% without using floating point multiplication
+            gas_price + (gas_price / 5u8.into())
+        }
+    };
     let zero: Uint256 = 0u8.into();
     let val = web3
         .eth_estimate_gas(TransactionRequest::Eip1559 {
@@ -112,6 +163,7 @@ pub async fn estimate_valset_cost(
                     old_valset.clone(),
                     confirms,
                     gravity_id,
+                    trim_signatures,
                 )?
                 .into(),
             ),
@@ -125,12 +177,14 @@ pub async fn estimate_valset_cost(
 }
 
 /// Encodes the payload bytes for the validator set update call, useful for
-/// estimating the cost of submitting a validator set
+/// estimating the cost of submitting a validator set. With `trim_signatures` only the
+/// highest powered signatures needed to pass are included, see `select_relay_signatures`
 pub fn encode_valset_update_payload(
     new_valset: Valset,
     old_valset: Valset,
     confirms: &[ValsetConfirmResponse],
     gravity_id: String,
+    trim_signatures: bool,
 ) -> Result<Vec<u8>, GravityError> {
     let new_valset_token = encode_valset_struct(&new_valset);
     let old_valset_token = encode_valset_struct(&old_valset);
@@ -140,7 +194,7 @@ pub fn encode_valset_update_payload(
     let hash = encode_valset_confirm_hashed(gravity_id, new_valset);
     // we need to use the old valset here because our signatures need to match the current
     // members of the validator set in the contract.
-    let sig_data = old_valset.order_sigs(&hash, confirms)?;
+    let sig_data = select_relay_signatures(&old_valset, &hash, confirms, trim_signatures)?;
     let sig_arrays = to_arrays(sig_data);
 
     // Solidity function signature
@@ -163,8 +217,7 @@ pub fn encode_valset_update_payload(
     // // These are arrays of the parts of the current validator's signatures
     // Signature[] _sigs,
     let tokens = &[new_valset_token, old_valset_token, sig_arrays.sigs];
-    let payload = clarity::abi::encode_call("updateValset((address[],uint256[],uint256,uint256,address),(address[],uint256[],uint256,uint256,address),(uint8,bytes32,bytes32)[])",
-    tokens).unwrap();
+    let payload = clarity::abi::encode_call(UPDATE_VALSET_SIGNATURE, tokens).unwrap();
 
     Ok(payload)
 }
@@ -0,0 +1,279 @@
+//! Named recipients stored in the [address_book] section of the config, so that a destination
+//! can be given as `--to treasury` instead of pasting an address every time. The config is edited
+//! in place with toml_edit so that the comments and layout of the rest of the file are kept
+
+use crate::args::{AddressBookAddOpts, AddressBookRemoveOpts};
+use crate::config::{CONFIG_FOLDER, CONFIG_NAME};
+use clarity::Address as EthAddress;
+use deep_space::Address as CosmosAddress;
+use gravity_utils::eth_address::parse_checksummed_address;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use toml_edit::{value, Document, Item, Table};
+
+/// The config section holding the address book
+const ADDRESS_BOOK_SECTION: &str = "address_book";
+
+/// Checks that an address book entry is a well formed Ethereum or Cosmos address. Cosmos addresses
+/// of any chain are accepted since deposits may be forwarded over IBC
+fn validate_address(address: &str, allow_unchecksummed: bool) -> Result<(), String> {
+    if address.starts_with("0x") {
+        parse_checksummed_address(address, allow_unchecksummed).map(|_| ())
+    } else if address.parse::<CosmosAddress>().is_ok() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a valid Ethereum or Cosmos address",
+            address
+        ))
+    }
+}
+
+/// Returns the Ethereum address `input` names, either directly or through the address book,
+/// enforcing the EIP-55 checksum either way
+pub fn resolve_eth_address(
+    input: &str,
+    address_book: &BTreeMap<String, String>,
+    allow_unchecksummed: bool,
+) -> Result<EthAddress, String> {
+    let (address, name) = match address_book.get(input) {
+        Some(a) => (a.as_str(), Some(input)),
+        None => (input, None),
+    };
+    match (
+        parse_checksummed_address(address, allow_unchecksummed),
+        name,
+    ) {
+        (Ok(a), _) => Ok(a),
+        (Err(e), Some(name)) => Err(format!("Address book entry {}: {}", name, e)),
+        (Err(e), None) if input.starts_with("0x") => Err(e),
+        (Err(_), None) => Err(format!(
+            "{} is not an Ethereum address or an address book entry",
+            input
+        )),
+    }
+}
+
+/// Returns the Cosmos address `input` names, either directly or through the address book
+pub fn resolve_cosmos_address(
+    input: &str,
+    address_book: &BTreeMap<String, String>,
+) -> Result<CosmosAddress, String> {
+    if let Ok(a) = input.parse() {
+        return Ok(a);
+    }
+    match address_book.get(input) {
+        Some(a) => a.parse().map_err(|_| {
+            format!(
+                "Address book entry {} is {}, which is not a Cosmos address",
+                input, a
+            )
+        }),
+        None => Err(format!(
+            "{} is not a Cosmos address or an address book entry",
+            input
+        )),
+    }
+}
+
+/// Like `resolve_eth_address` but exits on failure, for use by commands
+pub fn eth_address_or_exit(
+    input: &str,
+    address_book: &BTreeMap<String, String>,
+    allow_unchecksummed: bool,
+) -> EthAddress {
+    match resolve_eth_address(input, address_book, allow_unchecksummed) {
+        Ok(a) => a,
+        Err(e) => {
+            error!("{}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Like `resolve_cosmos_address` but exits on failure, for use by commands
+pub fn cosmos_address_or_exit(
+    input: &str,
+    address_book: &BTreeMap<String, String>,
+) -> CosmosAddress {
+    match resolve_cosmos_address(input, address_book) {
+        Ok(a) => a,
+        Err(e) => {
+            error!("{}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Reads the raw config file so that the address book can be edited, the config must exist
+fn load_raw_config(config_file: &Path) -> Document {
+    if !config_file.exists() {
+        error!(
+            "Config file at {} not detected, use `gbt init` to generate a config.",
+            config_file.display()
+        );
+        exit(1);
+    }
+    let contents = fs::read_to_string(config_file).expect("Unable to read config file");
+    match contents.parse() {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Invalid config! {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+fn save_raw_config(config_file: &Path, config: &Document) {
+    fs::write(config_file, config.to_string()).expect("Unable to write config file");
+}
+
+pub fn add_address(home_dir: &Path, opts: AddressBookAddOpts) {
+    if opts.name.parse::<EthAddress>().is_ok() || opts.name.parse::<CosmosAddress>().is_ok() {
+        error!("Address book names can not themselves be addresses");
+        exit(1);
+    }
+    if let Err(e) = validate_address(&opts.address, opts.allow_unchecksummed) {
+        error!("{}", e);
+        exit(1);
+    }
+    let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
+    let mut config = load_raw_config(&config_file);
+    let book = match config
+        .entry(ADDRESS_BOOK_SECTION)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+    {
+        Some(b) => b,
+        None => {
+            error!(
+                "Invalid config! [{}] is not a section",
+                ADDRESS_BOOK_SECTION
+            );
+            exit(1);
+        }
+    };
+    if let Some(existing) = book.get(&opts.name).and_then(|e| e.as_str()) {
+        if !opts.overwrite {
+            error!(
+                "{} is already in the address book as {}, pass --overwrite to replace it",
+                opts.name, existing
+            );
+            exit(1);
+        }
+    }
+    book.insert(&opts.name, value(opts.address.clone()));
+    save_raw_config(&config_file, &config);
+    info!("Added {} as {}", opts.name, opts.address);
+}
+
+pub fn list_addresses(address_book: &BTreeMap<String, String>) {
+    if address_book.is_empty() {
+        info!("The address book is empty, add entries with `gbt address-book add`");
+        return;
+    }
+    for (name, address) in address_book.iter() {
+        info!("{} {}", name, address);
+    }
+}
+
+pub fn remove_address(home_dir: &Path, opts: AddressBookRemoveOpts) {
+    let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
+    let mut config = load_raw_config(&config_file);
+    let removed = config
+        .get_mut(ADDRESS_BOOK_SECTION)
+        .and_then(|b| b.as_table_mut())
+        .and_then(|b| b.remove(&opts.name));
+    match removed {
+        Some(address) => {
+            save_raw_config(&config_file, &config);
+            info!(
+                "Removed {} ({})",
+                opts.name,
+                address.as_str().unwrap_or_default()
+            );
+        }
+        None => {
+            error!("{} is not in the address book", opts.name);
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_address() {
+        let eth = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let cosmos = "gravity1ahx7f8wyertuus9r20284ej0asrs085ceqtfnm";
+        let mut book = BTreeMap::new();
+        book.insert("treasury".to_string(), eth.to_string());
+        book.insert("validator".to_string(), cosmos.to_string());
+
+        let eth_address: EthAddress = eth.parse().unwrap();
+        assert_eq!(resolve_eth_address(eth, &book, false), Ok(eth_address));
+        assert_eq!(
+            resolve_eth_address("treasury", &book, false),
+            Ok(eth_address)
+        );
+        assert!(resolve_eth_address(&eth.to_lowercase(), &book, false).is_err());
+        assert_eq!(
+            resolve_eth_address(&eth.to_lowercase(), &book, true),
+            Ok(eth_address)
+        );
+        assert!(resolve_eth_address("validator", &book, true).is_err());
+        assert!(resolve_eth_address("unknown", &book, true).is_err());
+
+        let cosmos_address: CosmosAddress = cosmos.parse().unwrap();
+        assert_eq!(
+            resolve_cosmos_address("validator", &book),
+            Ok(cosmos_address)
+        );
+        assert_eq!(resolve_cosmos_address(cosmos, &book), Ok(cosmos_address));
+        assert!(resolve_cosmos_address("treasury", &book).is_err());
+
+        assert!(validate_address(eth, false).is_ok());
+        assert!(validate_address(&eth.to_lowercase(), false).is_err());
+        assert!(validate_address(cosmos, false).is_ok());
+        assert!(validate_address("treasury", true).is_err());
+    }
+
+    #[test]
+    fn test_edit_keeps_comments() {
+        let home = std::env::temp_dir().join("gbt_test_address_book");
+        fs::create_dir_all(&home).unwrap();
+        let config_file = home.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
+        let original =
+            "# relayer settings\n[relayer]\n# keep this comment\nrelayer_loop_speed = 600\n";
+        fs::write(&config_file, original).unwrap();
+
+        let eth = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        add_address(
+            &home,
+            AddressBookAddOpts {
+                name: "treasury".to_string(),
+                address: eth.to_string(),
+                overwrite: false,
+                allow_unchecksummed: false,
+            },
+        );
+        let edited = fs::read_to_string(&config_file).unwrap();
+        assert!(edited.starts_with(original));
+        assert!(edited.contains(&format!("treasury = \"{}\"", eth)));
+
+        remove_address(
+            &home,
+            AddressBookRemoveOpts {
+                name: "treasury".to_string(),
+            },
+        );
+        let edited = fs::read_to_string(&config_file).unwrap();
+        assert!(edited.starts_with(original));
+        assert!(!edited.contains("treasury ="));
+        fs::remove_dir_all(&home).unwrap();
+    }
+}
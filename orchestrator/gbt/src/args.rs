@@ -1,6 +1,7 @@
 //! Command line argument definitions for Gravity bridge tools
 //! See the clap documentation for how exactly this works, note that doc comments are displayed to the user
 
+use crate::log_format::LogFormat;
 use clap::Parser;
 use clarity::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
@@ -9,6 +10,10 @@ use deep_space::{CosmosPrivateKey, EthermintPrivateKey};
 use std::path::PathBuf;
 
 /// Gravity Bridge tools (gbt) provides tools for interacting with the Althea Gravity bridge for Cosmos based blockchains.
+///
+/// Settings are taken from, in order of precedence: command line flags, GBT_ environment variables,
+/// the config file and finally the defaults. Config file settings can be overridden with --set or a
+/// GBT_CONFIG__ environment variable, for example GBT_CONFIG__RELAYER__RELAYER_LOOP_SPEED=30
 #[derive(Parser)]
 #[clap(version = env!("CARGO_PKG_VERSION"), author = "Justin Kilpatrick <justin@althea.net>")]
 pub struct Opts {
@@ -18,13 +23,32 @@ pub struct Opts {
     /// Decrease the logging verbosity
     #[clap(short, long)]
     pub quiet: bool,
+    /// The format of log output, text or json. json writes one object per line with the timestamp,
+    /// level, loop and any event nonce or transaction hash as fields, for log aggregation
+    #[clap(
+        long,
+        default_value = "text",
+        parse(try_from_str),
+        env = "GBT_LOG_FORMAT"
+    )]
+    pub log_format: LogFormat,
     /// The home directory for Gravity Bridge Tools, by default
     /// $HOME/.althea_gbt/
-    #[clap(short, long, parse(from_str))]
+    #[clap(short, long, parse(from_str), env = "GBT_HOME")]
     pub home: Option<PathBuf>,
-    /// Set the address prefix for the Cosmos chain
-    #[clap(short, long, default_value = "gravity")]
+    /// Use a named profile such as mainnet, testnet or localnet. Each profile has its own config,
+    /// keys and endpoints in the profiles folder of the home directory, create one with
+    /// `gbt --profile <name> init`
+    #[clap(long, env = "GBT_PROFILE")]
+    pub profile: Option<String>,
+    /// Set the address prefix for the Cosmos chain, the orchestrator and relayer detect the chain's
+    /// prefix unless this is set
+    #[clap(short, long, default_value = "gravity", env = "GBT_ADDRESS_PREFIX")]
     pub address_prefix: String,
+    /// Override a config file setting, for example --set relayer.relayer_loop_speed=30
+    /// may be specified multiple times
+    #[clap(long = "set", multiple_occurrences(true))]
+    pub config_overrides: Vec<String>,
     #[clap(subcommand)]
     pub subcmd: SubCommand,
 }
@@ -37,7 +61,14 @@ pub enum SubCommand {
     Client(ClientOpts),
     Gov(GovOpts),
     Keys(KeyOpts),
+    AddressBook(AddressBookOpts),
     Init(InitOpts),
+    DumpState(DumpStateOpts),
+    LogLevel(LogLevelOpts),
+    Watch(WatchOpts),
+    DepositWatch(DepositWatchOpts),
+    Test(TestOpts),
+    Dev(DevOpts),
 }
 
 const DEFAULT_GRPC_ADDRESS: &str = "http://gravitychain.io:9090";
@@ -46,54 +77,288 @@ const DEFAULT_ETH_RPC_ADDRESS: &str = "https://eth.althea.net";
 /// The Gravity Bridge orchestrator is required for all validators of the Cosmos chain running
 /// the Gravity Bridge module. It contains an Ethereum Signer, Oracle, and optional relayer
 #[derive(Parser)]
+#[clap(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 pub struct OrchestratorOpts {
     /// Cosmos mnemonic phrase containing the tokens you would like to send
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(
+        short,
+        long,
+        parse(try_from_str),
+        env = "GBT_COSMOS_PHRASE",
+        hide_env_values = true
+    )]
     pub cosmos_phrase: Option<CosmosPrivateKey>,
     /// An Ethereum private key containing ETH to pay for fees, this will also hold the relayers earnings
     /// in the near future it will be possible to disable the Orchestrators integrated relayer
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(
+        short,
+        long,
+        parse(try_from_str),
+        env = "GBT_ETHEREUM_KEY",
+        hide_env_values = true
+    )]
     pub ethereum_key: Option<EthPrivateKey>,
     /// (Optional) The Cosmos gRPC server that will be used
-    #[clap(long, default_value = "http://localhost:9090")]
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// (Optional) The Ethereum RPC server that will be used
-    #[clap(long, default_value = "http://localhost:8545")]
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
     pub ethereum_rpc: String,
     /// The Cosmos Denom and amount to pay Cosmos chain fees
-    #[clap(short, long, parse(try_from_str))]
-    pub fees: Coin,
+    #[clap(short, long, parse(try_from_str), env = "GBT_FEES", required = true)]
+    pub fees: Option<Coin>,
     /// The address fo the Gravity contract on Ethereum
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
     pub gravity_contract_address: Option<EthAddress>,
+    /// Audit mode, follow the given orchestrator address and derive every confirm and claim it
+    /// should submit without signing or broadcasting anything. No keys are needed, what would be
+    /// sent is logged and exported to watch-only.jsonl in the gbt home directory
+    #[clap(long, parse(try_from_str))]
+    pub watch_only: Option<CosmosAddress>,
+    /// (Optional) Serve Prometheus metrics on this address, such as 0.0.0.0:6631. This enables the
+    /// metrics server and overrides metrics_bind in the config
+    #[clap(long, env = "GBT_METRICS_LISTEN")]
+    pub metrics_listen: Option<String>,
+    #[clap(subcommand)]
+    pub subcmd: Option<OrchestratorSubcommand>,
+}
+
+#[derive(Parser)]
+pub enum OrchestratorSubcommand {
+    HuntEvidence(HuntEvidenceOpts),
+    PendingWork(PendingWorkOpts),
+    SignLogicCall(SignLogicCallOpts),
+}
+
+/// Searches Ethereum for validator sets, batches and logic calls executed on the Gravity contract
+/// whose checkpoints never existed on the Cosmos chain. The validator signatures that approved them
+/// are slashable, they are packaged as MsgSubmitBadSignatureEvidence and optionally submitted
+#[derive(Parser)]
+pub struct HuntEvidenceOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to check and submit evidence
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to search for evidence
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) Cosmos mnemonic phrase used to check evidence against the chain and to submit it,
+    /// defaults to the stored orchestrator key
+    #[clap(
+        long,
+        parse(try_from_str),
+        env = "GBT_COSMOS_PHRASE",
+        hide_env_values = true
+    )]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// The Ethereum block to start searching from
+    #[clap(short, long)]
+    pub start_block: u64,
+    /// (Optional) The Ethereum block to stop searching at, defaults to the latest block
+    #[clap(short, long)]
+    pub end_block: Option<u64>,
+    /// (Optional) Write the evidence found to this file as JSON
+    #[clap(short, long, parse(from_str))]
+    pub output: Option<PathBuf>,
+    /// Submit the evidence found to the chain, slashing the validators who signed it
+    #[clap(long, requires = "fees")]
+    pub submit: bool,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees when submitting evidence
+    #[clap(short, long, parse(try_from_str), env = "GBT_FEES")]
+    pub fees: Option<Coin>,
+}
+
+/// Lists the validator sets, batches and logic calls on the chain that an orchestrator has not yet
+/// signed, oldest first. Anything listed here for long counts towards the orchestrator's validator
+/// being slashed
+#[derive(Parser)]
+pub struct PendingWorkOpts {
+    /// (Optional) The Cosmos gRPC server that will be queried
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The orchestrator address to check, defaults to the stored orchestrator key
+    #[clap(long, parse(try_from_str))]
+    pub orchestrator_address: Option<CosmosAddress>,
+    /// (Optional) How many items of each kind to skip
+    #[clap(long, default_value = "0")]
+    pub offset: usize,
+    /// (Optional) The most items of each kind to list
+    #[clap(long)]
+    pub limit: Option<usize>,
+}
+
+/// Signs a logic call the orchestrator refused because it violates the logic_call_policy, once you
+/// have reviewed it. The call is looked up among those awaiting the orchestrator's signature and
+/// printed before it is signed
+#[derive(Parser)]
+pub struct SignLogicCallOpts {
+    /// The invalidation id of the logic call, in hex
+    #[clap(long)]
+    pub invalidation_id: String,
+    /// The invalidation nonce of the logic call
+    #[clap(long)]
+    pub invalidation_nonce: u64,
+    /// (Optional) The orchestrator's Cosmos mnemonic phrase, defaults to the stored orchestrator key
+    #[clap(
+        short,
+        long,
+        parse(try_from_str),
+        env = "GBT_COSMOS_PHRASE",
+        hide_env_values = true
+    )]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// (Optional) The orchestrator's Ethereum key, defaults to the stored Ethereum key. The
+    /// ethereum_signer in the config is used if it is not the local key
+    #[clap(
+        short,
+        long,
+        parse(try_from_str),
+        env = "GBT_ETHEREUM_KEY",
+        hide_env_values = true
+    )]
+    pub ethereum_key: Option<EthPrivateKey>,
+    /// (Optional) The Cosmos gRPC server that will be used
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees
+    #[clap(short, long, parse(try_from_str), env = "GBT_FEES")]
+    pub fees: Coin,
 }
 
 /// The Gravity Bridge Relayer is an unpermissioned role that takes data from the Cosmos blockchain
 /// packages it into Ethereum transactions and is paid to submit these transactions to the Ethereum blockchain
 /// The relayer will attempt to only relay profitable transactions, but there is no guarantee that it will succeed
 #[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
 pub struct RelayerOpts {
     /// An Ethereum private key containing ETH to pay for fees, this will also hold the relayers earnings
     /// This overrides the key set in the config, which will be used if no key is provided here
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(
+        short,
+        long,
+        parse(try_from_str),
+        env = "GBT_ETHEREUM_KEY",
+        hide_env_values = true
+    )]
     pub ethereum_key: Option<EthPrivateKey>,
     /// Cosmos mnemonic phrase containing tokens used to pay fees on Cosmos for requesting batches
     /// This overrides the key set in the config, which will be used if no key is provided here.
     /// If no key is provided and no key is set in the config, this relayer will not request batches
-    #[clap(long, parse(try_from_str))]
+    #[clap(
+        long,
+        parse(try_from_str),
+        env = "GBT_COSMOS_PHRASE",
+        hide_env_values = true
+    )]
     pub cosmos_phrase: Option<CosmosPrivateKey>,
     /// (Optional) The Cosmos Denom and amount to pay Cosmos chain fees. If not set this relayer will not automatically
     /// request batches
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(short, long, parse(try_from_str), env = "GBT_FEES")]
     pub fees: Option<Coin>,
     /// The address fo the Gravity contract on Ethereum
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
     pub gravity_contract_address: Option<EthAddress>,
     /// (Optional) The Ethereum RPC server that will be used
-    #[clap(long, default_value = "http://localhost:8545")]
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
     pub ethereum_rpc: String,
     /// (Optional) The Cosmos gRPC server that will be used to
-    #[clap(short, long, default_value = "http://localhost:9090")]
+    #[clap(
+        short,
+        long,
+        default_value = "http://localhost:9090",
+        env = "GBT_COSMOS_GRPC"
+    )]
+    pub cosmos_grpc: String,
+    /// (Optional) Serve Prometheus metrics on this address, such as 0.0.0.0:6632. The relayer only
+    /// serves metrics when this is set, so that it does not collide with an orchestrator on the
+    /// same host
+    #[clap(long, env = "GBT_RELAYER_METRICS_LISTEN")]
+    pub metrics_listen: Option<String>,
+    /// (Optional) Find and price everything the relayer would relay with the current config and log
+    /// it with the expected gas cost and reward, without sending anything to Ethereum. Batches are
+    /// not requested and IBC auto forwards are not executed either
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(subcommand)]
+    pub subcmd: Option<RelayerSubcommand>,
+}
+
+#[derive(Parser)]
+pub enum RelayerSubcommand {
+    Runway(RelayerRunwayOpts),
+    RetryBatch(RelayerRetryBatchOpts),
+}
+
+/// Makes a running relayer or orchestrator retry batches it skips because their last relay reverted,
+/// such as after the token contract was fixed. Without options every skipped batch is retried
+#[derive(Parser)]
+pub struct RelayerRetryBatchOpts {
+    /// (Optional) Only retry batches of this token
+    #[clap(long, parse(try_from_str))]
+    pub token: Option<EthAddress>,
+    /// (Optional) Only retry the batch with this nonce, requires --token
+    #[clap(long, requires = "token")]
+    pub nonce: Option<u64>,
+    /// How long to wait for the running process to respond, in seconds
+    #[clap(long, default_value = "30")]
+    pub timeout: u64,
+}
+
+/// Estimates how many days the relayer's ETH will last from its relays over the lookback period
+/// priced at the current gas price. Given a funding key, tops the relayer up to the target runway
+/// once it falls below the minimum, run it periodically from cron to keep a relayer funded
+#[derive(Parser)]
+pub struct RelayerRunwayOpts {
+    /// (Optional) The relayer address to check, defaults to the stored Ethereum key
+    #[clap(long, parse(try_from_str))]
+    pub relayer_address: Option<EthAddress>,
+    /// (Optional) The number of days of relaying to average over
+    #[clap(long, default_value = "7")]
+    pub lookback_days: u64,
+    /// (Optional) Top up once the balance lasts fewer than this many days
+    #[clap(long, default_value = "7")]
+    pub min_runway_days: u64,
+    /// (Optional) Top up to enough ETH to last this many days
+    #[clap(long, default_value = "30")]
+    pub target_runway_days: u64,
+    /// (Optional) The key of the account funding the relayer, without it the runway is only reported
+    #[clap(
+        long,
+        parse(try_from_str),
+        env = "GBT_FUNDING_KEY",
+        hide_env_values = true
+    )]
+    pub funding_key: Option<EthPrivateKey>,
+    /// (Optional) The most ETH sent in a single top up
+    #[clap(long, default_value = "1")]
+    pub max_top_up: f64,
+    /// (Optional) The address of the Gravity contract on Ethereum, by default from the chain
+    #[clap(long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) The Ethereum RPC server that will be used
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
+    pub ethereum_rpc: String,
+    /// (Optional) The Cosmos gRPC server used to find the Gravity contract
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
 }
 
@@ -122,6 +387,14 @@ pub struct JsonrpcServerOpts {
     /// A path to the SSL key file, only used if use_ssl is true; default /etc/letsencrypt/live/<domain name>/privkey.pem
     #[clap(long, parse(try_from_str))]
     pub cert_key_path: Option<String>,
+
+    /// A Cosmos gRPC endpoint, if given together with ethereum_rpc bulk balance queries are served at /balances
+    #[clap(long, requires = "ethereum-rpc")]
+    pub cosmos_grpc: Option<String>,
+
+    /// An Ethereum RPC endpoint, if given together with cosmos_grpc bulk balance queries are served at /balances
+    #[clap(long, requires = "cosmos-grpc")]
+    pub ethereum_rpc: Option<String>,
 }
 
 /// The Gravity Bridge client contains helpful command line tools for interacting with the Gravity bridge
@@ -135,18 +408,40 @@ pub struct ClientOpts {
 pub enum ClientSubcommand {
     CosmosToEth(CosmosToEthOpts),
     EthToCosmos(EthToCosmosOpts),
+    ApproveErc20(ApproveErc20Opts),
+    RevokeErc20(RevokeErc20Opts),
     DeployErc20Representation(DeployErc20RepresentationOpts),
     SpotRelay(SpotRelayOpts),
+    RoundTrip(RoundTripOpts),
+    IbcToEth(IbcToEthOpts),
+    Sign(SignOpts),
+    Broadcast(BroadcastOpts),
+    #[clap(subcommand)]
+    /// Create and sign transactions for legacy amino threshold multisig accounts
+    Multisig(MultisigSubcommand),
+    #[clap(subcommand)]
+    /// Query information about the bridge
+    Query(ClientQuerySubcommand),
+    #[clap(subcommand)]
+    /// Construct and relay logic calls for testing a logic contract against the bridge
+    LogicCall(LogicCallSubcommand),
 }
 
 /// Send Cosmos tokens to Ethereum
 #[derive(Parser)]
 pub struct CosmosToEthOpts {
     /// Cosmos mnemonic phrase containing the tokens you would like to send
-    #[clap(short, long, parse(try_from_str))]
-    pub cosmos_phrase: CosmosPrivateKey,
+    #[clap(short, long, parse(try_from_str), required_unless_present = "ledger")]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// Sign with the Cosmos app on a connected Ledger instead of a phrase, requires gbt built with
+    /// the ledger feature
+    #[clap(long, conflicts_with_all = &["cosmos-phrase", "simulate"])]
+    pub ledger: bool,
+    /// (Optional) The account of the Ledger key to sign with, the key at 44'/118'/account'/0/0
+    #[clap(long, default_value = "0")]
+    pub ledger_account: u32,
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The Denom and amount you wish to send eg: 100ugraviton
     #[clap(short, long, parse(try_from_str))]
@@ -154,18 +449,36 @@ pub struct CosmosToEthOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees eg: 1ugraviton
     #[clap(short, long, parse(try_from_str))]
     pub fee: Coin,
-    /// The amount you want to pay in bridge fees, this is used to pay relayers
-    /// on Ethereum and must be of the same denomination as `amount` dependent on governance.
-    /// Please ask in the official discord for more information.
+    /// (Optional) The amount you want to pay in bridge fees, this is used to pay relayers
+    /// on Ethereum and must be of the same denomination as `amount`. If not provided a fee
+    /// is suggested based on current Ethereum gas prices and the price of the token
     #[clap(short, long, parse(try_from_str))]
-    pub bridge_fee: Coin,
+    pub bridge_fee: Option<Coin>,
     /// The amount you want to pay as a chain fee, this is used to pay Gravity Bridge
     /// stakers and must be at least a certain percentage of `amount`
     #[clap(short, long, parse(try_from_str))]
     pub chain_fee: Coin,
-    /// The destination address on the Ethereum chain
-    #[clap(short, long, parse(try_from_str))]
-    pub eth_destination: EthAddress,
+    /// The destination address on the Ethereum chain, or the name of an address book entry
+    #[clap(short, long, alias = "to")]
+    pub eth_destination: String,
+    /// (Optional) The Ethereum RPC server used to check the destination and to suggest a bridge
+    /// fee when one is not provided
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// Acknowledge that the destination is a contract. Tokens sent to a contract that can not
+    /// handle them, such as a Gnosis Safe without the token added or a contract with no way to
+    /// withdraw, are lost. Sends to contracts are refused without this flag
+    #[clap(long)]
+    pub dest_is_contract: bool,
+    /// Accept an all lowercase destination, which has no EIP-55 checksum to catch typos
+    #[clap(long)]
+    pub allow_unchecksummed: bool,
+    /// (Optional) A memo for the transaction in place of the configured one, for attribution
+    #[clap(long)]
+    pub memo: Option<String>,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// Send an Ethereum ERC20 token to Cosmos
@@ -175,10 +488,10 @@ pub struct EthToCosmosOpts {
     #[clap(long, parse(try_from_str))]
     pub ethereum_key: EthPrivateKey,
     /// (Optional) The Ethereum RPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
     pub ethereum_rpc: String,
     /// The address fo the Gravity contract on Ethereum
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
     pub gravity_contract_address: EthAddress,
     /// The ERC20 contract address of the ERC20 you are sending
     #[clap(short, long, parse(try_from_str))]
@@ -186,9 +499,59 @@ pub struct EthToCosmosOpts {
     /// The amount of tokens you are sending eg. 1.2
     #[clap(short, long, parse(try_from_str))]
     pub amount: f64,
-    /// The destination address on the Cosmos blockchain
+    /// The destination address on the Cosmos blockchain, or the name of an address book entry
+    #[clap(short, long, alias = "to")]
+    pub destination: String,
+    /// Estimate the gas of the transaction and check that it would not revert, without sending it
+    #[clap(long)]
+    pub simulate: bool,
+}
+
+/// Set the allowance the Gravity contract has to transfer an ERC20 token on your behalf, either an
+/// exact amount or unlimited. The current allowance is shown first
+#[derive(Parser)]
+pub struct ApproveErc20Opts {
+    /// The Ethereum private key that owns the tokens
+    #[clap(long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The Ethereum RPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// The address of the Gravity contract on Ethereum
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: EthAddress,
+    /// The ERC20 contract address of the token to approve
+    #[clap(short, long, parse(try_from_str))]
+    pub token_contract_address: EthAddress,
+    /// The amount of tokens the Gravity contract may transfer eg. 1.2
+    #[clap(short, long, parse(try_from_str))]
+    pub amount: Option<f64>,
+    /// Allow the Gravity contract to transfer any amount, instead of --amount
+    #[clap(long)]
+    pub unlimited: bool,
+    /// Estimate the gas of the transaction and check that it would not revert, without sending it
+    #[clap(long)]
+    pub simulate: bool,
+}
+
+/// Revoke the allowance the Gravity contract has to transfer an ERC20 token on your behalf
+#[derive(Parser)]
+pub struct RevokeErc20Opts {
+    /// The Ethereum private key that owns the tokens
+    #[clap(long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The Ethereum RPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// The address of the Gravity contract on Ethereum
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: EthAddress,
+    /// The ERC20 contract address of the token to revoke the allowance of
     #[clap(short, long, parse(try_from_str))]
-    pub destination: CosmosAddress,
+    pub token_contract_address: EthAddress,
+    /// Estimate the gas of the transaction and check that it would not revert, without sending it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// Deploy an ERC20 representation of a Cosmos asset on the Ethereum chain
@@ -196,10 +559,10 @@ pub struct EthToCosmosOpts {
 #[derive(Parser)]
 pub struct DeployErc20RepresentationOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// (Optional) The Ethereum RPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
     pub ethereum_rpc: String,
     /// The Cosmos Denom you wish to create an ERC20 representation for
     #[clap(short, long)]
@@ -208,8 +571,57 @@ pub struct DeployErc20RepresentationOpts {
     #[clap(short, long, parse(try_from_str))]
     pub ethereum_key: EthPrivateKey,
     /// The address fo the Gravity contract on Ethereum
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// Estimate the gas of the transaction and check that it would not revert, without sending it
+    #[clap(long)]
+    pub simulate: bool,
+    /// (Optional) Refuse to deploy if the estimated cost exceeds this many US dollars, 0 disables
+    /// the limit. Deployment is expensive, wait for lower gas prices rather than raising this
+    #[clap(long, default_value = "100")]
+    pub max_cost_usd: f64,
+}
+
+/// Sends a Cosmos originated token to Ethereum and back again, checking that the ERC20 arrives and
+/// that the final Cosmos balance only differs by the fees paid. Use this as a smoke test of the
+/// whole bridge, it will take as long as it takes for a batch to be relayed
+#[derive(Parser)]
+pub struct RoundTripOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to submit the transactions
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to submit the transactions
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// Cosmos mnemonic phrase containing the tokens to send and the Cosmos fees
+    #[clap(short, long, parse(try_from_str))]
+    pub cosmos_phrase: CosmosPrivateKey,
+    /// Ethereum private key that receives the ERC20 and pays the ETH to send it back
     #[clap(short, long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
     pub gravity_contract_address: Option<EthAddress>,
+    /// The Cosmos originated Denom and amount to send eg: 100ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub amount: Coin,
+    /// The amount you want to pay in bridge fees, in the same denomination as `amount`
+    #[clap(short, long, parse(try_from_str))]
+    pub bridge_fee: Coin,
+    /// (Optional) The Gravity chain fee, in the same denomination as `amount`. The minimum is
+    /// used if not provided
+    #[clap(long, parse(try_from_str))]
+    pub chain_fee: Option<Coin>,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees eg: 1ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub fee: Coin,
+    /// Request a batch after sending instead of waiting for a relayer to request one
+    #[clap(long)]
+    pub request_batch: bool,
+    /// (Optional) How many minutes to wait for each leg of the trip
+    #[clap(long, default_value = "30")]
+    pub timeout: u64,
 }
 
 /// Requests and relays a batch of a specific token type.
@@ -220,10 +632,10 @@ pub struct DeployErc20RepresentationOpts {
 #[derive(Parser)]
 pub struct SpotRelayOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// (Optional) The Ethereum RPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
     pub ethereum_rpc: String,
     /// The token or denom you wish to relay, can be a ERC20 address or ibc token address if Cosmos originated
     /// Not all tokens are built into the human readable lookup list
@@ -235,11 +647,604 @@ pub struct SpotRelayOpts {
     pub ethereum_key: EthPrivateKey,
     /// (Optional) The address fo the Gravity contract on Ethereum, this should be auto filled
     /// from chain parameters
-    #[clap(short, long, parse(try_from_str))]
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
     pub gravity_contract_address: Option<EthAddress>,
     /// (Optional) Cosmos mnemonic phrase used for requesting batches if they are not already pending
     #[clap(short, long, parse(try_from_str))]
     pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// Estimate the gas of relaying the batch and check that it would not revert, without requesting
+    /// or relaying anything
+    #[clap(long)]
+    pub simulate: bool,
+}
+
+#[derive(Parser)]
+pub enum LogicCallSubcommand {
+    Build(LogicCallBuildOpts),
+    Relay(LogicCallRelayOpts),
+}
+
+/// Build a logic call for a testnet genesis file. Logic calls can't be created by a transaction,
+/// only by other modules on the chain or in genesis, so this prints an entry for the
+/// `gravity.logic_calls` list of the genesis along with the checkpoint validators will sign
+#[derive(Parser)]
+pub struct LogicCallBuildOpts {
+    /// The contract the Gravity contract will call
+    #[clap(long, parse(try_from_str))]
+    pub logic_contract: EthAddress,
+    /// The signature of the function to call, eg: transfer(address,uint256)
+    #[clap(long)]
+    pub function: String,
+    /// The arguments of the function in order, repeat once per argument. Numbers are decimal,
+    /// bytes are hex
+    #[clap(long = "arg")]
+    pub args: Vec<String>,
+    /// Tokens sent to the logic contract before the call as erc20_address:amount, may be repeated
+    #[clap(long = "transfer")]
+    pub transfers: Vec<String>,
+    /// Tokens paid to the relayer as erc20_address:amount, may be repeated
+    #[clap(long = "fee")]
+    pub fees: Vec<String>,
+    /// The Ethereum block height after which the call can no longer be relayed
+    #[clap(long)]
+    pub timeout: u64,
+    /// A name for the stream of calls this one belongs to, its keccak256 hash is used as the
+    /// invalidation id. Executing a call invalidates every call of the same scope with a lower nonce
+    #[clap(long, conflicts_with = "invalidation-id")]
+    pub invalidation_scope: Option<String>,
+    /// A hex invalidation id, used instead of --invalidation-scope
+    #[clap(long)]
+    pub invalidation_id: Option<String>,
+    /// The invalidation nonce, by default the next nonce the Gravity contract will accept
+    #[clap(long)]
+    pub invalidation_nonce: Option<u64>,
+    /// The gravity_id of the testnet, if set the checkpoint validators will sign is printed
+    #[clap(long)]
+    pub gravity_id: Option<String>,
+    /// (Optional) The Ethereum RPC server used to look up the next invalidation nonce
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// The address of the Gravity contract on Ethereum, needed to look up the next invalidation nonce
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Relay a signed logic call to Ethereum
+#[derive(Parser)]
+pub struct LogicCallRelayOpts {
+    /// (Optional) The Cosmos gRPC server used to find the logic call and its signatures
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// The hex invalidation id of the call
+    #[clap(long, conflicts_with = "invalidation-scope")]
+    pub invalidation_id: Option<String>,
+    /// The invalidation scope the call was built with, used instead of --invalidation-id
+    #[clap(long)]
+    pub invalidation_scope: Option<String>,
+    /// The invalidation nonce of the call
+    #[clap(long)]
+    pub invalidation_nonce: u64,
+    /// An Ethereum private key, containing enough ETH to pay for the transaction
+    #[clap(short, long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The address fo the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Send tokens from an IBC connected chain to Ethereum in one step. The tokens are sent over IBC
+/// to the Gravity address of the same key, once they arrive they are sent to Ethereum and tracked
+/// until they are relayed. The bridge fee and chain fee are taken from the transferred tokens.
+#[derive(Parser)]
+pub struct IbcToEthOpts {
+    /// Cosmos mnemonic phrase containing the tokens on the counterparty chain, the same key
+    /// is used on Gravity Bridge
+    #[clap(long, parse(try_from_str), required_unless_present = "ledger")]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// Sign with the Cosmos app on a connected Ledger instead of a phrase, requires gbt built with
+    /// the ledger feature
+    #[clap(long, conflicts_with_all = &["cosmos-phrase", "simulate"])]
+    pub ledger: bool,
+    /// (Optional) The account of the Ledger key to sign with, the key at 44'/118'/account'/0/0
+    #[clap(long, default_value = "0")]
+    pub ledger_account: u32,
+    /// The gRPC server of the chain the tokens are currently on
+    #[clap(long)]
+    pub counterparty_grpc: String,
+    /// The address prefix of the chain the tokens are currently on eg: cosmos
+    #[clap(long)]
+    pub counterparty_prefix: String,
+    /// The IBC channel on the counterparty chain which connects to Gravity Bridge eg: channel-0
+    #[clap(long)]
+    pub channel: String,
+    /// The Denom and amount to pay fees on the counterparty chain eg: 1000uatom
+    #[clap(long, parse(try_from_str))]
+    pub counterparty_fee: Coin,
+    /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// The Denom and amount you wish to send, using the denom on the counterparty chain eg: 1000000uatom
+    #[clap(short, long, parse(try_from_str))]
+    pub amount: Coin,
+    /// The Cosmos Denom and amount to pay Gravity Bridge chain fees eg: 1ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub fee: Coin,
+    /// The amount you want to pay in bridge fees, this is used to pay relayers
+    /// on Ethereum and must be of the same denomination as `amount`
+    #[clap(short, long, parse(try_from_str))]
+    pub bridge_fee: Coin,
+    /// (Optional) The amount you want to pay as a chain fee, must be of the same denomination as `amount`.
+    /// If not provided the minimum chain fee is computed for you
+    #[clap(long, parse(try_from_str))]
+    pub chain_fee: Option<Coin>,
+    /// The destination address on the Ethereum chain, or the name of an address book entry
+    #[clap(short, long, alias = "to")]
+    pub eth_destination: String,
+    /// Accept an all lowercase destination, which has no EIP-55 checksum to catch typos
+    #[clap(long)]
+    pub allow_unchecksummed: bool,
+    /// (Optional) The number of seconds before the IBC packet times out
+    #[clap(long, default_value = "3600")]
+    pub packet_timeout: u64,
+    /// (Optional) The number of minutes to follow the transfer before exiting
+    #[clap(long, default_value = "120")]
+    pub tracking_timeout: u64,
+    /// (Optional) The Ethereum RPC server used to check the destination
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// Acknowledge that the destination is a contract. Tokens sent to a contract that can not
+    /// handle them are lost, sends to contracts are refused without this flag
+    #[clap(long)]
+    pub dest_is_contract: bool,
+    /// (Optional) A memo for the SendToEth in place of the configured one, for attribution
+    #[clap(long)]
+    pub memo: Option<String>,
+    /// Simulate the IBC transfer on the counterparty chain and print its expected gas, fees and events
+    /// without broadcasting it. The SendToEth can not be simulated until the tokens are on Gravity
+    #[clap(long)]
+    pub simulate: bool,
+}
+
+/// Construct and sign a transaction without any network access, so that the key can stay on an
+/// air-gapped machine. Look up the chain id, account number and sequence of the signer on an online
+/// machine with `gbt client query signing-info`, then broadcast the output file from an online
+/// machine with `gbt client broadcast`
+#[derive(Parser)]
+pub struct SignOpts {
+    /// Cosmos mnemonic phrase of the signer
+    #[clap(short, long, parse(try_from_str))]
+    pub cosmos_phrase: CosmosPrivateKey,
+    /// The chain id of the chain the transaction will be broadcast to eg: gravity-bridge-3
+    #[clap(long)]
+    pub chain_id: String,
+    /// The account number of the signer
+    #[clap(long)]
+    pub account_number: u64,
+    /// The sequence of the signer, every transaction signed by the account increments it so each
+    /// sequence can only be used once
+    #[clap(long)]
+    pub sequence: u64,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees eg: 1ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub fee: Coin,
+    /// (Optional) The gas limit of the transaction
+    #[clap(long, default_value = "300000")]
+    pub gas_limit: u64,
+    /// (Optional) The block height after which the transaction can no longer be included, 0 for no limit
+    #[clap(long, default_value = "0")]
+    pub timeout_height: u64,
+    /// The file to write the signed transaction to
+    #[clap(short, long, parse(from_str))]
+    pub output: PathBuf,
+    #[clap(subcommand)]
+    pub tx: SignTxSubcommand,
+}
+
+#[derive(Parser)]
+pub enum SignTxSubcommand {
+    SendToEth(SignSendToEthOpts),
+    CancelSendToEth(SignCancelSendToEthOpts),
+    IbcMetadataProposal(SignProposalOpts),
+    AirdropProposal(SignProposalOpts),
+    EmergencyBridgeHaltProposal(SignProposalOpts),
+    OracleUnhaltProposal(SignProposalOpts),
+}
+
+/// Sign a MsgSendToEth. Nothing is checked against the chain, make sure the account holds the
+/// amount and all fees
+#[derive(Parser)]
+pub struct SignSendToEthOpts {
+    /// The Denom and amount you wish to send eg: 100ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub amount: Coin,
+    /// The amount you want to pay in bridge fees, this is used to pay relayers
+    /// on Ethereum and must be of the same denomination as `amount`
+    #[clap(short, long, parse(try_from_str))]
+    pub bridge_fee: Coin,
+    /// The amount you want to pay as a chain fee, this is used to pay Gravity Bridge
+    /// stakers and must be at least a certain percentage of `amount`
+    #[clap(long, parse(try_from_str))]
+    pub chain_fee: Coin,
+    /// The destination address on the Ethereum chain, or the name of an address book entry
+    #[clap(short, long, alias = "to")]
+    pub eth_destination: String,
+    /// Accept an all lowercase destination, which has no EIP-55 checksum to catch typos
+    #[clap(long)]
+    pub allow_unchecksummed: bool,
+}
+
+/// Sign a MsgCancelSendToEth, returning a SendToEth that has not yet been batched
+#[derive(Parser)]
+pub struct SignCancelSendToEthOpts {
+    /// The id of the SendToEth to cancel
+    #[clap(long)]
+    pub transaction_id: u64,
+}
+
+/// Sign a governance proposal from the same proposal.json `gbt gov submit` takes
+#[derive(Parser)]
+pub struct SignProposalOpts {
+    /// Path to the proposal.json
+    #[clap(short, long, parse(from_str))]
+    pub json: PathBuf,
+    /// The Cosmos Denom and amount to pay the governance proposal deposit
+    #[clap(short, long, parse(try_from_str))]
+    pub deposit: Coin,
+}
+
+/// Broadcast a transaction signed with `gbt client sign` and wait for it to be included
+#[derive(Parser)]
+pub struct BroadcastOpts {
+    /// The signed transaction file
+    #[clap(short, long, parse(from_str))]
+    pub input: PathBuf,
+    /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+}
+
+#[derive(Parser)]
+#[allow(clippy::large_enum_variant)]
+pub enum MultisigSubcommand {
+    Pubkey(MultisigPubkeyOpts),
+    Create(MultisigCreateOpts),
+    Sign(MultisigSignOpts),
+    Combine(MultisigCombineOpts),
+}
+
+/// Print the public key of a multisig member, to be shared with the other members
+#[derive(Parser)]
+pub struct MultisigPubkeyOpts {
+    /// The hex private key of the member, export it with `gravity keys export <name> --unarmored-hex --unsafe`
+    #[clap(long)]
+    pub key: String,
+}
+
+/// Create the description of a multisig account from the public keys of its members, the file is
+/// needed by every member to sign and to combine signatures
+#[derive(Parser)]
+pub struct MultisigCreateOpts {
+    /// The number of signatures needed to sign for the account
+    #[clap(long)]
+    pub threshold: u32,
+    /// The hex public key of a member, from `gbt client multisig pubkey`, provided once per member
+    /// in the order the account was created with
+    #[clap(long)]
+    pub pubkey: Vec<String>,
+    /// The file to write the multisig description to
+    #[clap(short, long, parse(from_str))]
+    pub output: PathBuf,
+}
+
+/// Produce one member's signature for a multisig transaction. Every member must sign with exactly
+/// the same transaction details, including the sequence
+#[derive(Parser)]
+pub struct MultisigSignOpts {
+    /// The hex private key of the member, export it with `gravity keys export <name> --unarmored-hex --unsafe`
+    #[clap(long)]
+    pub key: String,
+    /// The multisig description from `gbt client multisig create`
+    #[clap(short, long, parse(from_str))]
+    pub multisig: PathBuf,
+    /// The chain id of the chain the transaction will be broadcast to eg: gravity-bridge-3
+    #[clap(long)]
+    pub chain_id: String,
+    /// The account number of the multisig account
+    #[clap(long)]
+    pub account_number: u64,
+    /// The sequence of the multisig account
+    #[clap(long)]
+    pub sequence: u64,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees eg: 1ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub fee: Coin,
+    /// (Optional) The gas limit of the transaction
+    #[clap(long, default_value = "300000")]
+    pub gas_limit: u64,
+    /// The file to write this member's signature to
+    #[clap(short, long, parse(from_str))]
+    pub output: PathBuf,
+    #[clap(subcommand)]
+    pub tx: MultisigTxSubcommand,
+}
+
+#[derive(Parser)]
+pub enum MultisigTxSubcommand {
+    SendToEth(SignSendToEthOpts),
+    CancelSendToEth(SignCancelSendToEthOpts),
+}
+
+/// Combine the signatures of enough members into a signed transaction, broadcast it with
+/// `gbt client broadcast`
+#[derive(Parser)]
+pub struct MultisigCombineOpts {
+    /// The multisig description from `gbt client multisig create`
+    #[clap(short, long, parse(from_str))]
+    pub multisig: PathBuf,
+    /// A signature file from `gbt client multisig sign`, provided once per signature
+    #[clap(short, long, parse(from_str))]
+    pub signature: Vec<PathBuf>,
+    /// The file to write the signed transaction to
+    #[clap(short, long, parse(from_str))]
+    pub output: PathBuf,
+}
+
+#[derive(Parser)]
+pub enum ClientQuerySubcommand {
+    Attestation(AttestationQueryOpts),
+    Batch(BatchQueryOpts),
+    BatchFees(BatchFeesQueryOpts),
+    BatchPreview(BatchPreviewQueryOpts),
+    DelegateKeys(DelegateKeysQueryOpts),
+    RelayerPayouts(RelayerPayoutsQueryOpts),
+    TokenMappings(TokenMappingsQueryOpts),
+    ValsetHistory(ValsetHistoryQueryOpts),
+    EthereumValsetHistory(EthereumValsetHistoryQueryOpts),
+    AccidentalTransfers(AccidentalTransfersQueryOpts),
+    SigningInfo(SigningInfoQueryOpts),
+    History(HistoryQueryOpts),
+}
+
+/// Shows every attestation for an Ethereum event nonce: the claim contents, which validators voted
+/// for it and with how much power, which have not voted, whether it is observed and how long it has
+/// been pending. Use this when the bridge is stuck on an attestation
+#[derive(Parser)]
+pub struct AttestationQueryOpts {
+    /// The Ethereum event nonce of the attestation
+    pub nonce: u64,
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+}
+
+/// Shows the full lifecycle of a single transaction batch: its transactions and fees, the
+/// confirmations gathered so far, whether it has been relayed to Ethereum and when it times out
+#[derive(Parser)]
+pub struct BatchQueryOpts {
+    /// The token of the batch, can be a ERC20 address, a Gravity denom or a common token name
+    /// Examples: USDC, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48
+    pub token: String,
+    /// The nonce of the batch
+    pub nonce: u64,
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to check if the batch was relayed
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) How many Ethereum blocks back to search for the relaying transaction if the chain
+    /// no longer has the attestation recording where the batch was executed
+    #[clap(long, default_value = "50000")]
+    pub search_blocks: u64,
+}
+
+/// Shows the fees waiting to be batched for each token, the estimated cost of relaying a batch
+/// and the bridge_fee a new send would need to make a batch profitable to relay
+#[derive(Parser)]
+pub struct BatchFeesQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to query gas prices
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The profit margin relayers are assumed to require, 1.1 is 10% over cost
+    #[clap(short, long, default_value = "1.1")]
+    pub margin: f32,
+}
+
+/// Shows exactly which pending sends would be included if a batch were requested for a token right
+/// now, the fees they pay and whether the chain would accept the request, so relayers can judge
+/// a batch's profitability before requesting it
+#[derive(Parser)]
+pub struct BatchPreviewQueryOpts {
+    /// The token to preview a batch for, can be a ERC20 address, a Gravity denom or a common token name
+    /// Examples: USDC, 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, gravity0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48
+    pub token: String,
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to price the fees
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The profit margin relayers are assumed to require, 1.1 is 10% over cost
+    #[clap(short, long, default_value = "1.1")]
+    pub margin: f32,
+}
+
+/// Audits the delegate keys of every active validator against recent activity, flagging validators
+/// with missing keys, keys shared with another validator, Ethereum keys that have not signed any
+/// recent valset or batch or hold no ETH, and orchestrator keys that are not submitting claims
+#[derive(Parser)]
+pub struct DelegateKeysQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to check balances
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) Also list validators with no problems
+    #[clap(short, long)]
+    pub all: bool,
+}
+
+/// Reconciles relayer payouts for a range of batch nonces of a token, matching the fees each
+/// executed batch promised against the ERC20 transfers the relayer actually received on Ethereum.
+/// Discrepancies point to fee on transfer tokens or bugs in the token or Gravity contract
+#[derive(Parser)]
+pub struct RelayerPayoutsQueryOpts {
+    /// The token of the batches, can be a ERC20 address, a Gravity denom or a common token name
+    pub token: String,
+    /// The first batch nonce to reconcile
+    #[clap(long)]
+    pub start_nonce: u64,
+    /// The last batch nonce to reconcile
+    #[clap(long)]
+    pub end_nonce: u64,
+    /// The Ethereum block to start searching for batch executions from
+    #[clap(short, long)]
+    pub start_block: u64,
+    /// (Optional) The Ethereum block to stop searching at, defaults to the latest block
+    #[clap(short, long)]
+    pub end_block: Option<u64>,
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to search for batch executions
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+}
+
+/// Exports every Cosmos denom to ERC20 mapping known to the chain, including Cosmos originated
+/// assets, along with the ERC20 name, symbol and decimals from Ethereum as JSON
+#[derive(Parser)]
+pub struct TokenMappingsQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to query ERC20 metadata
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) Write the mappings to this file, by default they are printed
+    #[clap(short, long, parse(from_str))]
+    pub output: Option<PathBuf>,
+}
+
+/// Lists the most recent validator sets and the power and membership changes between each one and
+/// the set before it, explaining why each update was created and roughly what it costs to relay
+#[derive(Parser)]
+pub struct ValsetHistoryQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to query gas prices
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) How many of the latest validator sets to show
+    #[clap(short, long, default_value = "5")]
+    pub last: u64,
+}
+
+/// Rebuilds the full validator set history from the Gravity contract's ValsetUpdatedEvent logs and
+/// compares every update to the Cosmos chain's record of the same nonce. An independent consistency
+/// check of the bridge, and a way to recover the history without a Cosmos archive node
+#[derive(Parser)]
+pub struct EthereumValsetHistoryQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to search for events
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// The Ethereum block to start searching from, the block the contract was deployed in
+    /// to rebuild the full history
+    #[clap(short, long)]
+    pub start_block: u64,
+    /// (Optional) The Ethereum block to stop searching at, defaults to the latest block
+    #[clap(short, long)]
+    pub end_block: Option<u64>,
+    /// (Optional) Only rebuild the history from Ethereum, without comparing it to Cosmos
+    #[clap(long)]
+    pub skip_cosmos: bool,
+    /// (Optional) Write the rebuilt history as JSON to this file
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Searches the Gravity contract history for funds which entered the contract without being credited
+/// on Cosmos. This includes plain ERC20 transfers to the contract and deposits with an invalid destination.
+/// These funds can only be recovered through governance
+#[derive(Parser)]
+pub struct AccidentalTransfersQueryOpts {
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to search for events
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// The Ethereum block to start searching from
+    #[clap(short, long)]
+    pub start_block: u64,
+    /// (Optional) The Ethereum block to stop searching at, defaults to the latest block
+    #[clap(short, long)]
+    pub end_block: Option<u64>,
+}
+
+/// Prints the chain id, account number and next sequence of an account, the inputs needed to sign
+/// a transaction for it offline with `gbt client sign`
+#[derive(Parser)]
+pub struct SigningInfoQueryOpts {
+    /// The address of the account, or the name of an address book entry
+    pub address: String,
+    /// (Optional) The Cosmos gRPC server that will be used to query the account
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+}
+
+/// Shows the bridge transfers of an address from both chains in chronological order, with the
+/// status of each. For a Cosmos address these are its sends to Ethereum and the deposits it received,
+/// for an Ethereum address its deposits to Cosmos and the batch payouts it received
+#[derive(Parser)]
+pub struct HistoryQueryOpts {
+    /// A Cosmos or Ethereum address, or the name of an address book entry
+    pub address: String,
+    /// (Optional) The Cosmos gRPC server that will be used to search transactions, it must index
+    /// transactions
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to search for events
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// The Ethereum block to start searching for deposits and payouts from
+    #[clap(short, long)]
+    pub start_block: u64,
+    /// (Optional) The Ethereum block to stop searching at, defaults to the latest block
+    #[clap(short, long)]
+    pub end_block: Option<u64>,
 }
 
 /// Manage keys
@@ -255,18 +1260,80 @@ pub enum KeysSubcommand {
     RegisterOrchestratorAddress(RegisterOrchestratorAddressOpts),
     SetEthereumKey(SetEthereumKeyOpts),
     SetOrchestratorKey(SetOrchestratorKeyOpts),
-    Show,
+    Show(KeysShowOpts),
     RecoverFunds(RecoverFundsOpts),
+    Backup(KeysBackupOpts),
+    Restore(KeysRestoreOpts),
+}
+
+/// Shows the locally stored keys. With --on-chain also cross-references them with both chains:
+/// whether they are registered as delegate keys, the validator they belong to and whether it is
+/// bonded, their balances and how much work is waiting for their signatures
+#[derive(Parser)]
+pub struct KeysShowOpts {
+    /// (Optional) Also query both chains for the key registrations, balances and pending work,
+    /// without this only the local addresses are shown
+    #[clap(long)]
+    pub on_chain: bool,
+    /// (Optional) The Cosmos gRPC server that will be used to query the chain
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be used to query balances
+    #[clap(long, default_value = DEFAULT_ETH_RPC_ADDRESS, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+}
+
+/// Manage named recipients, stored in the [address_book] section of the config. Any command taking
+/// a destination address also accepts an address book name, eg `gbt client cosmos-to-eth --to treasury`
+#[derive(Parser)]
+pub struct AddressBookOpts {
+    #[clap(subcommand)]
+    pub subcmd: AddressBookSubcommand,
+}
+
+#[derive(Parser)]
+pub enum AddressBookSubcommand {
+    Add(AddressBookAddOpts),
+    List,
+    Remove(AddressBookRemoveOpts),
+}
+
+/// Add a named Ethereum or Cosmos address to the address book
+#[derive(Parser)]
+pub struct AddressBookAddOpts {
+    /// The name to refer to the address by
+    pub name: String,
+    /// The Ethereum or Cosmos address
+    pub address: String,
+    /// Replace the address if the name is already in use
+    #[clap(long)]
+    pub overwrite: bool,
+    /// Accept an all lowercase Ethereum address, which has no EIP-55 checksum to catch typos
+    #[clap(long)]
+    pub allow_unchecksummed: bool,
+}
+
+/// Remove a named address from the address book
+#[derive(Parser)]
+pub struct AddressBookRemoveOpts {
+    /// The name of the address to remove
+    pub name: String,
 }
 
 /// Register delegate keys for the Gravity Orchestrator.
 /// this is a mandatory part of setting up a Gravity Orchestrator
-/// If you would like sign using a ledger see `cosmos tx gravity set-orchestrator-address` instead
 #[derive(Parser)]
 pub struct RegisterOrchestratorAddressOpts {
     /// The Cosmos private key of the validator
-    #[clap(short, long, parse(try_from_str))]
-    pub validator_phrase: CosmosPrivateKey,
+    #[clap(short, long, parse(try_from_str), required_unless_present = "ledger")]
+    pub validator_phrase: Option<CosmosPrivateKey>,
+    /// Sign with the validator key held by the Cosmos app on a connected Ledger instead of a phrase,
+    /// requires gbt built with the ledger feature
+    #[clap(long, conflicts_with_all = &["validator-phrase", "simulate"])]
+    pub ledger: bool,
+    /// (Optional) The account of the Ledger key to sign with, the key at 44'/118'/account'/0/0
+    #[clap(long, default_value = "0")]
+    pub ledger_account: u32,
     /// (Optional) The Ethereum private key to register, will be generated if not provided
     #[clap(short, long, parse(try_from_str))]
     pub ethereum_key: Option<EthPrivateKey>,
@@ -274,7 +1341,7 @@ pub struct RegisterOrchestratorAddressOpts {
     #[clap(short, long, parse(try_from_str))]
     pub cosmos_phrase: Option<String>,
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = "http://localhost:9090")]
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[clap(short, long, parse(try_from_str))]
@@ -282,6 +1349,9 @@ pub struct RegisterOrchestratorAddressOpts {
     /// Do not save keys to disk for later use with `orchestrator start`
     #[clap(long)]
     pub no_save: bool,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// Add an Ethereum private key for use with either the Relayer or the Orchestrator
@@ -299,6 +1369,32 @@ pub struct SetOrchestratorKeyOpts {
     pub phrase: String,
 }
 
+/// Write all keys stored by gbt, their addresses and the config file to a single passphrase
+/// encrypted backup file, restore it on another machine with `gbt keys restore`
+#[derive(Parser)]
+pub struct KeysBackupOpts {
+    /// The file to write the backup to
+    #[clap(short, long, parse(from_str))]
+    pub output: PathBuf,
+    /// The passphrase to encrypt the backup with, you will be prompted for one if not provided
+    #[clap(long, env = "GBT_BACKUP_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+}
+
+/// Restore keys and config from a backup created with `gbt keys backup`
+#[derive(Parser)]
+pub struct KeysRestoreOpts {
+    /// The backup file to restore
+    #[clap(short, long, parse(from_str))]
+    pub input: PathBuf,
+    /// The passphrase the backup was encrypted with, you will be prompted for it if not provided
+    #[clap(long, env = "GBT_BACKUP_PASSPHRASE", hide_env_values = true)]
+    pub passphrase: Option<String>,
+    /// Replace keys and config that already exist in the home directory
+    #[clap(long)]
+    pub overwrite: bool,
+}
+
 /// Recover inaccessible funds from a failed IBC Auto Forward to an Ethermint chain (e.g. Evmos, Canto)
 /// by either sending them back to Ethereum, or to another Gravity address you control.
 /// To send to Ethereum, provide the --send-to-eth flag.
@@ -315,7 +1411,7 @@ pub struct RecoverFundsOpts {
     #[clap(short, long, parse(try_from_str))]
     pub ethereum_key: EthermintPrivateKey,
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The Denom and amount you wish to send eg: 100ugraviton
     #[clap(short, long, parse(try_from_str))]
@@ -342,12 +1438,105 @@ pub struct RecoverFundsOpts {
     /// **Only use this with the send-on-cosmos flag**
     #[clap(long, parse(try_from_str))]
     pub cosmos_destination: Option<CosmosAddress>,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// Initialize configuration
 #[derive(Parser)]
 pub struct InitOpts {}
 
+/// Asks an orchestrator or relayer running with the same home directory to dump its in memory
+/// state (oracle progress, unsigned items, relayer queue, current valset) as JSON. Attach the
+/// output to bug reports
+#[derive(Parser)]
+pub struct DumpStateOpts {
+    /// The file to write the state to, by default state-dump-<timestamp>.json in the home directory
+    #[clap(short, long, parse(from_str))]
+    pub output: Option<PathBuf>,
+    /// How long to wait for the running process to respond, in seconds
+    #[clap(long, default_value = "30")]
+    pub timeout: u64,
+}
+
+/// Changes the log levels of individual modules on an orchestrator or relayer running with the
+/// same home directory, without restarting it. For example `gbt log-level relayer=debug web30=warn`.
+/// "oracle" sets the orchestrator's Ethereum oracle, other names are crates or module paths
+#[derive(Parser)]
+pub struct LogLevelOpts {
+    /// module=level pairs, level is one of off, error, warn, info, debug or trace
+    pub levels: Vec<String>,
+    /// Restore the levels from the [log_levels] section of the config instead
+    #[clap(long, conflicts_with = "levels")]
+    pub reset: bool,
+    /// How long to wait for the running process to respond, in seconds
+    #[clap(long, default_value = "30")]
+    pub timeout: u64,
+}
+
+/// Continuously prints a compact status of an orchestrator, one line per component: chain heights,
+/// event nonces and oracle lag, unsigned work, balances and the last error seen. Sized for a tmux
+/// pane, nothing needs to be running alongside it
+#[derive(Parser)]
+pub struct WatchOpts {
+    /// (Optional) The Cosmos gRPC server that will be queried
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be queried
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) The orchestrator address to watch, defaults to the stored orchestrator key
+    #[clap(long, parse(try_from_str))]
+    pub orchestrator_address: Option<CosmosAddress>,
+    /// (Optional) The Ethereum address to watch, defaults to the stored Ethereum key
+    #[clap(long, parse(try_from_str))]
+    pub ethereum_address: Option<EthAddress>,
+    /// Seconds between refreshes
+    #[clap(long, default_value = "5")]
+    pub interval: u64,
+    /// Print each refresh below the last instead of redrawing the screen
+    #[clap(long)]
+    pub no_clear: bool,
+}
+
+/// Reports sendToCosmos deposits to the destinations in the [deposit_watch] config section as they
+/// move through three states, printing one JSON line per transition: "seen" once the deposit is in
+/// an Ethereum block, "confirmed" once it has the confirmations configured for its token and
+/// "attested" once the Cosmos chain has credited it. Built for exchanges crediting user deposits
+#[derive(Parser)]
+pub struct DepositWatchOpts {
+    /// (Optional) The Cosmos gRPC server that will be queried
+    #[clap(long, default_value = "http://localhost:9090", env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// (Optional) The Ethereum RPC server that will be queried
+    #[clap(
+        long,
+        default_value = "http://localhost:8545",
+        env = "GBT_ETHEREUM_RPC"
+    )]
+    pub ethereum_rpc: String,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// (Optional) The Ethereum block to start searching from, defaults to the latest block.
+    /// Deposits made before it are not reported
+    #[clap(long)]
+    pub start_block: Option<u64>,
+    /// Seconds between checks
+    #[clap(long, default_value = "15")]
+    pub interval: u64,
+}
+
 /// The Gravity Bridge Governance subcommand contains tools for interacting with governance and submitting
 /// proposal types custom to Gravity Bridge
 #[derive(Parser)]
@@ -383,7 +1572,7 @@ pub enum GovQuerySubcommand {
 /// Queries active airdrop proposals and pretty-prints the interpreted data
 pub struct AirdropQueryOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the proposal
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// (Optional) query airdrops not actively being voted on
     #[clap(short, long)]
@@ -400,7 +1589,7 @@ pub struct AirdropQueryOpts {
 #[derive(Parser)]
 pub struct IbcMetadataProposalOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The phrase for an address containing enough funds to submit the proposal.
     #[clap(short, long, parse(try_from_str))]
@@ -414,6 +1603,9 @@ pub struct IbcMetadataProposalOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[clap(short, long, parse(try_from_str))]
     pub fees: Coin,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// An Airdrop Proposal allows the community to create, vote on, and execute
@@ -421,7 +1613,7 @@ pub struct IbcMetadataProposalOpts {
 #[derive(Parser)]
 pub struct AirdropProposalOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The phrase for an address containing enough funds to submit the proposal.
     #[clap(short, long, parse(try_from_str))]
@@ -435,6 +1627,9 @@ pub struct AirdropProposalOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[clap(short, long, parse(try_from_str))]
     pub fees: Coin,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// In case of a critical bug or other event involving the bridge the Gravity Bridge community may
@@ -444,11 +1639,18 @@ pub struct AirdropProposalOpts {
 #[derive(Parser)]
 pub struct EmergencyBridgeHaltProposalOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The phrase for an address containing enough funds to submit the proposal.
-    #[clap(short, long, parse(try_from_str))]
-    pub cosmos_phrase: CosmosPrivateKey,
+    #[clap(short, long, parse(try_from_str), required_unless_present = "ledger")]
+    pub cosmos_phrase: Option<CosmosPrivateKey>,
+    /// Sign with the Cosmos app on a connected Ledger instead of a phrase, requires gbt built with
+    /// the ledger feature
+    #[clap(long, conflicts_with_all = &["cosmos-phrase", "simulate"])]
+    pub ledger: bool,
+    /// (Optional) The account of the Ledger key to sign with, the key at 44'/118'/account'/0/0
+    #[clap(long, default_value = "0")]
+    pub ledger_account: u32,
     /// Path to the proposal.json
     #[clap(short, long, parse(try_from_str))]
     pub json: PathBuf,
@@ -458,6 +1660,9 @@ pub struct EmergencyBridgeHaltProposalOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[clap(short, long, parse(try_from_str))]
     pub fees: Coin,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
 }
 
 /// If there is a fork on the Ethereum mainnet it may cause disagreement in the bridge Oracle
@@ -467,7 +1672,7 @@ pub struct EmergencyBridgeHaltProposalOpts {
 #[derive(Parser)]
 pub struct OracleUnhaltProposalOpts {
     /// (Optional) The Cosmos gRPC server that will be used to submit the transaction
-    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS)]
+    #[clap(long, default_value = DEFAULT_GRPC_ADDRESS, env = "GBT_COSMOS_GRPC")]
     pub cosmos_grpc: String,
     /// The phrase for an address containing enough funds to submit the proposal.
     #[clap(short, long, parse(try_from_str))]
@@ -481,4 +1686,89 @@ pub struct OracleUnhaltProposalOpts {
     /// The Cosmos Denom and amount to pay Cosmos chain fees
     #[clap(short, long, parse(try_from_str))]
     pub fees: Coin,
+    /// Simulate the transaction and print its expected gas, fees and events without broadcasting it
+    #[clap(long)]
+    pub simulate: bool,
+}
+
+/// Tools for exercising a test network, never point these at a production chain
+#[derive(Parser)]
+pub struct TestOpts {
+    #[clap(subcommand)]
+    pub subcmd: TestSubcommand,
+}
+
+#[derive(Parser)]
+pub enum TestSubcommand {
+    Load(LoadTestOpts),
+}
+
+/// Generates a steady stream of sendToCosmos deposits and SendToEth transfers against a test network
+/// and measures how long each takes to arrive on the other side, as well as how many transfers the
+/// relayers execute per minute. Use this for capacity planning before changing bridge parameters
+#[derive(Parser)]
+pub struct LoadTestOpts {
+    /// The Cosmos gRPC server of the test network
+    #[clap(long, env = "GBT_COSMOS_GRPC")]
+    pub cosmos_grpc: String,
+    /// The Ethereum RPC server of the test network
+    #[clap(long, env = "GBT_ETHEREUM_RPC")]
+    pub ethereum_rpc: String,
+    /// Cosmos mnemonic phrase holding the bridged representation of the token and the Cosmos fees
+    /// for every SendToEth
+    #[clap(short, long, parse(try_from_str))]
+    pub cosmos_phrase: CosmosPrivateKey,
+    /// Ethereum private key holding the ERC20 and the ETH to pay for every deposit
+    #[clap(short, long, parse(try_from_str))]
+    pub ethereum_key: EthPrivateKey,
+    /// (Optional) The address of the Gravity contract on Ethereum, this should be auto filled
+    /// from chain parameters
+    #[clap(short, long, parse(try_from_str), env = "GBT_GRAVITY_CONTRACT_ADDRESS")]
+    pub gravity_contract_address: Option<EthAddress>,
+    /// The ERC20 contract address of the token to bridge back and forth
+    #[clap(short, long, parse(try_from_str))]
+    pub token_contract_address: EthAddress,
+    /// The amount of every transfer, in the smallest unit of the token
+    #[clap(long, default_value = "1000")]
+    pub amount: u128,
+    /// The bridge fee paid on every SendToEth, in the smallest unit of the token
+    #[clap(long, default_value = "1")]
+    pub bridge_fee: u128,
+    /// The Cosmos Denom and amount to pay Cosmos chain fees on every SendToEth eg: 1ugraviton
+    #[clap(short, long, parse(try_from_str))]
+    pub fee: Coin,
+    /// How many sendToCosmos deposits to submit per minute, 0 disables deposits
+    #[clap(long, default_value = "6")]
+    pub deposits_per_minute: f64,
+    /// How many SendToEth transfers to submit per minute, 0 disables SendToEth
+    #[clap(long, default_value = "6")]
+    pub sends_per_minute: f64,
+    /// How many minutes to generate load for
+    #[clap(long, default_value = "10")]
+    pub duration: u64,
+    /// How many minutes to wait for in flight transfers to complete once load generation stops
+    #[clap(long, default_value = "30")]
+    pub settle_timeout: u64,
+}
+
+/// Tools for developing the bridge itself
+#[derive(Parser)]
+pub struct DevOpts {
+    #[clap(subcommand)]
+    pub subcmd: DevSubcommand,
+}
+
+#[derive(Parser)]
+pub enum DevSubcommand {
+    GenFixtures(GenFixturesOpts),
+}
+
+/// Generate the deterministic valsets, batches, logic calls and events, with their checkpoints and
+/// signatures from the ethers test keys, that both the Rust and Solidity tests check against
+#[derive(Parser)]
+pub struct GenFixturesOpts {
+    /// (Optional) Write the fixtures to this file instead of printing them,
+    /// the Solidity tests read spec/test-fixtures.json
+    #[clap(short, long, parse(from_str))]
+    pub output: Option<PathBuf>,
 }
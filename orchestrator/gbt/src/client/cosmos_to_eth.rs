@@ -1,49 +1,199 @@
+use crate::address_book::eth_address_or_exit;
 use crate::args::CosmosToEthOpts;
-use crate::utils::TIMEOUT;
+use crate::client::multisig::amino_coin;
+use crate::ledger::{send_with_ledger, Ledger};
+use crate::simulate::simulate_cosmos_tx;
+use crate::utils::{display_denom, TIMEOUT};
 use clarity::Address as EthAddress;
 use cosmos_gravity::query::get_denom_to_erc20;
-use cosmos_gravity::send::send_to_eth;
+use cosmos_gravity::send::{get_memo, send_to_eth, send_to_eth_msg, MSG_SEND_TO_ETH_TYPE_URL};
 use deep_space::{Address as CosmosAddress, Coin, Contact, PrivateKey};
 use gravity_proto::gravity::query_client::QueryClient;
-use gravity_proto::gravity::QueryDenomToErc20Request;
+use gravity_proto::gravity::{MsgSendToEth, QueryDenomToErc20Request};
 use gravity_utils::{
     connection_prep::{check_for_fee, create_rpc_connections},
-    num_conversion::{print_atom, print_eth},
+    token_display::format_coin,
 };
+use prost::Message;
+use prost_types::Any;
+use relayer::request_batches::get_suggested_bridge_fee;
+use serde_json::json;
+use std::collections::BTreeMap;
 use std::process::exit;
 use tonic::transport::Channel;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
 
-pub async fn cosmos_to_eth_cmd(args: CosmosToEthOpts, address_prefix: String) {
+pub async fn cosmos_to_eth_cmd(
+    args: CosmosToEthOpts,
+    address_prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
     let cosmos_key = args.cosmos_phrase;
     let gravity_coin = args.amount;
     let fee = args.fee;
     let cosmos_grpc = args.cosmos_grpc;
-    let eth_dest = args.eth_destination;
+    let eth_dest = eth_address_or_exit(
+        &args.eth_destination,
+        address_book,
+        args.allow_unchecksummed,
+    );
     let bridge_fee = args.bridge_fee;
     let chain_fee = args.chain_fee;
 
-    let cosmos_address = cosmos_key.to_address(&address_prefix).unwrap();
+    let ledger = if args.ledger {
+        match Ledger::connect(args.ledger_account, &address_prefix) {
+            Ok(l) => Some(l),
+            Err(e) => {
+                error!("Failed to connect to the Ledger {}", e);
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let cosmos_address = match (&ledger, &cosmos_key) {
+        (Some(l), _) => l.address(),
+        (None, Some(k)) => k.to_address(&address_prefix).unwrap(),
+        (None, None) => {
+            error!("Provide either --cosmos-phrase or --ledger");
+            exit(1);
+        }
+    };
 
     info!("Sending from Cosmos address {}", cosmos_address);
-    let connections =
-        create_rpc_connections(address_prefix, Some(cosmos_grpc), None, TIMEOUT).await;
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
     let contact = connections.contact.unwrap();
-    let grpc = connections.grpc.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    check_eth_destination(&web3, eth_dest, args.dest_is_contract).await;
+
+    let bridge_fee = match bridge_fee {
+        Some(f) => f,
+        None => suggest_bridge_fee(&mut grpc, &web3, &gravity_coin).await,
+    };
+    info!(
+        "Sending {} to {} with a bridge fee of {}",
+        format_coin(&contact, Some(&web3), &gravity_coin).await,
+        eth_dest,
+        format_coin(&contact, Some(&web3), &bridge_fee).await
+    );
 
+    if let Some(ledger) = ledger {
+        cosmos_to_eth_with_ledger(
+            &contact,
+            grpc,
+            &ledger,
+            gravity_coin,
+            fee,
+            chain_fee,
+            bridge_fee,
+            eth_dest,
+            args.memo,
+        )
+        .await;
+        return;
+    }
     cosmos_to_eth(
         &contact,
         grpc,
-        cosmos_key,
+        cosmos_key.unwrap(),
         cosmos_address,
         gravity_coin,
         fee,
         bridge_fee,
         chain_fee,
         eth_dest,
+        args.memo,
+        args.simulate,
     )
     .await;
 }
 
+/// Refuses to send to a contract unless the user has acknowledged it, many contracts can not
+/// handle arbitrary ERC20s and tokens delivered to them are lost
+pub(crate) async fn check_eth_destination(
+    web3: &Web3,
+    eth_dest: EthAddress,
+    dest_is_contract: bool,
+) {
+    let client = HttpClient::new(&web3.get_url());
+    let code: Result<String, _> = client
+        .request_method(
+            "eth_getCode",
+            vec![eth_dest.to_string(), "latest".to_string()],
+            TIMEOUT,
+        )
+        .await;
+    let is_contract = match code {
+        Ok(code) => code.trim_start_matches("0x").chars().any(|c| c != '0'),
+        Err(e) => {
+            error!(
+                "Could not check if {} is a contract, is your Ethereum node working? {:?}",
+                eth_dest, e
+            );
+            exit(1);
+        }
+    };
+    if !is_contract {
+        return;
+    }
+    if dest_is_contract {
+        warn!(
+            "Destination {} is a contract, make sure it can handle the token you are sending",
+            eth_dest
+        );
+    } else {
+        error!(
+            "Destination {} is a contract! Tokens sent to a contract that can not handle them are lost",
+            eth_dest
+        );
+        error!("If you are sure this contract can receive the token, pass --dest-is-contract");
+        exit(1);
+    }
+}
+
+/// Computes a bridge fee for users who have not provided one, exits if no fee can be suggested
+async fn suggest_bridge_fee(
+    grpc: &mut QueryClient<Channel>,
+    web3: &Web3,
+    to_bridge: &Coin,
+) -> Coin {
+    let erc20: EthAddress = match get_denom_to_erc20(grpc, to_bridge.denom.clone()).await {
+        Ok(v) => v.erc20.parse().unwrap(),
+        Err(e) => {
+            error!("Could not lookup denom is it valid? {:?}", e);
+            exit(1);
+        }
+    };
+    match get_suggested_bridge_fee(web3, erc20, to_bridge.amount).await {
+        Ok(amount) => {
+            info!(
+                "No bridge fee provided, using a suggested bridge fee of {}{} based on current Ethereum gas prices",
+                amount, to_bridge.denom
+            );
+            Coin {
+                amount,
+                denom: to_bridge.denom.clone(),
+            }
+        }
+        Err(e) => {
+            error!(
+                "Unable to suggest a bridge fee, please provide one with --bridge-fee {:?}",
+                e
+            );
+            exit(1);
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn cosmos_to_eth(
     contact: &Contact,
@@ -55,16 +205,86 @@ pub async fn cosmos_to_eth(
     chain_fee: Coin,
     bridge_fee: Coin,
     receiver_address: EthAddress,
+    memo: Option<String>,
+    simulate: bool,
 ) {
+    let display = check_send_to_eth(
+        contact,
+        grpc,
+        sender_address,
+        &to_bridge,
+        &cosmos_fee,
+        &chain_fee,
+        &bridge_fee,
+    )
+    .await;
+    let amount = to_bridge.clone();
+
+    if simulate {
+        let msg = match send_to_eth_msg(
+            sender_address,
+            receiver_address,
+            amount,
+            bridge_fee,
+            Some(chain_fee),
+            &cosmos_fee,
+            contact,
+        )
+        .await
+        {
+            Ok(m) => m,
+            Err(e) => {
+                error!("Failed to build the SendToEth {:?}", e);
+                exit(1);
+            }
+        };
+        simulate_cosmos_tx(contact, &[msg], &[cosmos_fee], sender_key).await;
+        return;
+    }
+
+    info!(
+        "Locking {} of {} into the batch pool",
+        format_coin(contact, None, &amount).await,
+        display
+    );
+    let res = send_to_eth(
+        sender_key,
+        receiver_address,
+        amount.clone(),
+        bridge_fee.clone(),
+        Some(chain_fee),
+        cosmos_fee.clone(),
+        contact,
+        memo,
+    )
+    .await;
+    match res {
+        Ok(tx_id) => info!("Send to Eth txid {}", tx_id.txhash),
+        Err(e) => info!("Failed to send tokens! {:?}", e),
+    }
+    info!("Your funds are now waiting to be sent to Ethereum in a transaction batch!");
+    info!("Depending on how much you and others attached in fees, this might take a while!");
+    info!("You can retrieve your funds using a CancelSendToEth message, up until they are sent to Ethereum");
+}
+
+/// Checks that the token can be bridged and that the sender can pay for it, exits if not. Returns
+/// the display name of the token
+async fn check_send_to_eth(
+    contact: &Contact,
+    grpc: QueryClient<Channel>,
+    sender_address: CosmosAddress,
+    to_bridge: &Coin,
+    cosmos_fee: &Coin,
+    chain_fee: &Coin,
+    bridge_fee: &Coin,
+) -> String {
     let mut grpc = grpc;
     let res = get_denom_to_erc20(&mut grpc, to_bridge.denom.clone()).await;
-    let is_cosmos_originated = match res {
-        Ok(v) => v.cosmos_originated,
-        Err(e) => {
-            error!("Could not lookup denom is it valid? {:?}", e);
-            exit(1);
-        }
-    };
+    if let Err(e) = res {
+        error!("Could not lookup denom is it valid? {:?}", e);
+        exit(1);
+    }
+    let display = display_denom(contact, &to_bridge.denom).await;
 
     let res = grpc
         .denom_to_erc20(QueryDenomToErc20Request {
@@ -74,25 +294,24 @@ pub async fn cosmos_to_eth(
     match res {
         Ok(val) => info!(
             "Asset {} has ERC20 representation {}",
-            to_bridge.denom,
+            display,
             val.into_inner().erc20
         ),
         Err(_e) => {
             info!(
                 "Asset {} has no ERC20 representation, you may need to deploy an ERC20 for it!",
-                to_bridge.denom
+                display
             );
             exit(1);
         }
     }
 
-    let amount = to_bridge.clone();
     let full_amount = Coin {
         amount: to_bridge.amount + chain_fee.amount,
         denom: to_bridge.denom.clone(),
     };
     check_for_fee(&full_amount, sender_address, contact).await;
-    check_for_fee(&cosmos_fee, sender_address, contact).await;
+    check_for_fee(cosmos_fee, sender_address, contact).await;
 
     let balance = contact
         .get_balance(sender_address, to_bridge.denom.clone())
@@ -101,40 +320,95 @@ pub async fn cosmos_to_eth(
 
     match balance {
         Some(balance) => {
-            if balance.amount < amount.amount + bridge_fee.amount {
-                if is_cosmos_originated {
-                    error!("Your transfer of {} {} tokens with chain fee {} is greater than your balance of {} tokens. Remember you need some to pay for fees!", print_atom(amount.amount), to_bridge.denom, print_atom(chain_fee.amount), print_atom(balance.amount));
-                } else {
-                    error!("Your transfer of {} {} tokens with chain fee {} is greater than your balance of {} tokens. Remember you need some to pay for fees!", print_eth(amount.amount), to_bridge.denom, print_eth(chain_fee.amount), print_eth(balance.amount));
-                }
+            if balance.amount < to_bridge.amount + bridge_fee.amount {
+                error!("Your transfer of {} of {} with chain fee {} is greater than your balance of {}. Remember you need some to pay for fees!", format_coin(contact, None, to_bridge).await, display, format_coin(contact, None, chain_fee).await, format_coin(contact, None, &balance).await);
                 exit(1);
             }
         }
         None => {
-            error!("You don't have any {} tokens!", to_bridge.denom);
+            error!("You don't have any {} tokens!", display);
             exit(1);
         }
     }
+    display
+}
 
-    info!(
-        "Locking {} / {} into the batch pool",
-        amount.denom, to_bridge.denom
-    );
-    let res = send_to_eth(
-        sender_key,
-        receiver_address,
-        amount.clone(),
-        bridge_fee.clone(),
-        Some(chain_fee),
-        cosmos_fee.clone(),
+/// As `cosmos_to_eth` but signed on a Ledger, which can't be used to simulate the transaction
+#[allow(clippy::too_many_arguments)]
+async fn cosmos_to_eth_with_ledger(
+    contact: &Contact,
+    grpc: QueryClient<Channel>,
+    ledger: &Ledger,
+    to_bridge: Coin,
+    cosmos_fee: Coin,
+    chain_fee: Coin,
+    bridge_fee: Coin,
+    receiver_address: EthAddress,
+    memo: Option<String>,
+) {
+    let sender_address = ledger.address();
+    let display = check_send_to_eth(
         contact,
+        grpc,
+        sender_address,
+        &to_bridge,
+        &cosmos_fee,
+        &chain_fee,
+        &bridge_fee,
     )
     .await;
-    match res {
-        Ok(tx_id) => info!("Send to Eth txid {}", tx_id.txhash),
-        Err(e) => info!("Failed to send tokens! {:?}", e),
+
+    let (msg, amino) = ledger_send_to_eth_msg(
+        sender_address,
+        receiver_address,
+        &to_bridge,
+        &bridge_fee,
+        &chain_fee,
+    );
+    info!(
+        "Locking {} {} into the batch pool",
+        to_bridge.amount, display
+    );
+    let memo = memo.unwrap_or_else(get_memo);
+    match send_with_ledger(contact, ledger, msg, amino, cosmos_fee, memo).await {
+        Ok(res) => info!("Send to Eth txid {}", res.txhash),
+        Err(e) => {
+            error!("Failed to send tokens! {:?}", e);
+            exit(1);
+        }
     }
     info!("Your funds are now waiting to be sent to Ethereum in a transaction batch!");
-    info!("Depending on how much you and others attached in fees, this might take a while!");
-    info!("You can retrieve your funds using a CancelSendToEth message, up until they are sent to Ethereum");
+}
+
+/// A MsgSendToEth and its amino JSON form, which is what the Ledger shows and signs over
+pub(crate) fn ledger_send_to_eth_msg(
+    sender: CosmosAddress,
+    eth_dest: EthAddress,
+    amount: &Coin,
+    bridge_fee: &Coin,
+    chain_fee: &Coin,
+) -> (Any, serde_json::Value) {
+    // keys are in sorted order, matching the sorted JSON the Ledger signs over
+    let amino = json!({
+        "type": "gravity/MsgSendToEth",
+        "value": {
+            "amount": amino_coin(amount),
+            "bridge_fee": amino_coin(bridge_fee),
+            "chain_fee": amino_coin(chain_fee),
+            "eth_dest": eth_dest.to_string(),
+            "sender": sender.to_string(),
+        }
+    });
+    let msg = MsgSendToEth {
+        sender: sender.to_string(),
+        eth_dest: eth_dest.to_string(),
+        amount: Some(amount.clone().into()),
+        bridge_fee: Some(bridge_fee.clone().into()),
+        chain_fee: Some(chain_fee.clone().into()),
+    };
+    let msg = Any {
+        type_url: MSG_SEND_TO_ETH_TYPE_URL.to_string(),
+        value: msg.encode_to_vec(),
+    };
+    (msg, amino)
 }
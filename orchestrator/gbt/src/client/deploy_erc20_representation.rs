@@ -1,9 +1,17 @@
+use crate::simulate::simulate_eth_tx;
 use crate::{args::DeployErc20RepresentationOpts, utils::TIMEOUT};
 
+use clarity::{Address as EthAddress, Uint256};
 use cosmos_gravity::query::get_gravity_params;
-use ethereum_gravity::deploy_erc20::deploy_erc20;
+use ethereum_gravity::deploy_erc20::{
+    deploy_erc20, encode_deploy_erc20_payload, estimate_deploy_erc20_cost,
+};
 use gravity_proto::gravity::{QueryAttestationsRequest, QueryDenomToErc20Request};
 use gravity_utils::connection_prep::{check_for_eth, create_rpc_connections};
+use gravity_utils::num_conversion::{print_eth, print_gwei};
+use gravity_utils::prices::get_dai_price;
+use web30::amm::WETH_CONTRACT_ADDRESS;
+use web30::client::Web3;
 
 use std::{
     process::exit,
@@ -68,6 +76,61 @@ pub async fn deploy_erc20_representation(
                 }
             }
             let decimals = decimals.unwrap();
+            let payload = encode_deploy_erc20_payload(
+                metadata.base.clone(),
+                metadata.name.clone(),
+                metadata.symbol.clone(),
+                decimals,
+            )
+            .unwrap();
+            if args.simulate {
+                simulate_eth_tx(
+                    &web3,
+                    ethereum_public_key,
+                    contract_address,
+                    payload,
+                    "Deploying the ERC20",
+                )
+                .await;
+                return;
+            }
+
+            let cost = match estimate_deploy_erc20_cost(
+                payload,
+                contract_address,
+                ethereum_public_key,
+                &web3,
+            )
+            .await
+            {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("Failed to estimate the cost of deploying the ERC20, it would likely revert {:?}", e);
+                    exit(1);
+                }
+            };
+            let usd = get_usd_value(ethereum_public_key, cost.get_total(), &web3).await;
+            info!(
+                "Deploying the ERC20 will use {} gas at up to {} gwei, costing at most {} ETH{}",
+                cost.gas,
+                print_gwei(cost.gas_price),
+                print_eth(cost.get_total()),
+                usd.map(|v| format!(" / ${:.2}", v)).unwrap_or_default()
+            );
+            if args.max_cost_usd > 0f64 {
+                match usd {
+                    Some(v) if v > args.max_cost_usd => {
+                        error!("This exceeds your maximum deployment cost of ${:.2}, wait for lower gas prices or raise --max-cost-usd", args.max_cost_usd);
+                        exit(1);
+                    }
+                    Some(_) => {}
+                    None => {
+                        error!("Unable to get the price of ETH in USD to check the maximum deployment cost, try again or set --max-cost-usd 0 to deploy anyway");
+                        exit(1);
+                    }
+                }
+            }
+
             let _contract_to_be_adopted = deploy_erc20(
                 metadata.base,
                 metadata.name,
@@ -77,7 +140,7 @@ pub async fn deploy_erc20_representation(
                 &web3,
                 Some(TIMEOUT),
                 ethereum_key,
-                vec![SendTxOption::GasPriceMultiplier(1.5)],
+                vec![SendTxOption::GasMaxFee(cost.gas_price)],
             )
             .await
             .unwrap();
@@ -153,6 +216,18 @@ pub async fn deploy_erc20_representation(
     }
 }
 
+/// Values `amount` of ETH in US dollars using the Uniswap DAI price
+async fn get_usd_value(caller: EthAddress, amount: Uint256, web3: &Web3) -> Option<f64> {
+    match get_dai_price(caller, *WETH_CONTRACT_ADDRESS, amount, web3).await {
+        // DAI has the same 18 decimals as ETH
+        Ok(dai) => print_eth(dai).parse().ok(),
+        Err(e) => {
+            warn!("Failed to get the price of ETH in DAI {:?}", e);
+            None
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,178 @@
+//! Commands to set or revoke the allowance the Gravity contract has over a user's ERC20 tokens.
+//! eth-to-cosmos approves an unlimited amount automatically, these let users choose exactly how much
+//! the contract may pull instead of approving with a third party tool
+
+use crate::args::{ApproveErc20Opts, RevokeErc20Opts};
+use crate::simulate::simulate_eth_tx;
+use crate::utils::TIMEOUT;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Uint256};
+use ethereum_gravity::erc20_allowance::{
+    encode_approve_payload, get_erc20_allowance, is_unlimited_allowance, set_erc20_allowance,
+    unlimited_allowance,
+};
+use gravity_utils::connection_prep::{check_for_eth, create_rpc_connections};
+use gravity_utils::num_conversion::fraction_to_exponent;
+use gravity_utils::token_display::format_erc20;
+use std::process::exit;
+use web30::client::Web3;
+
+pub async fn approve_erc20(args: ApproveErc20Opts, prefix: String) {
+    let connections = create_rpc_connections(prefix, None, Some(args.ethereum_rpc), TIMEOUT).await;
+    let web3 = connections.web3.unwrap();
+    let owner = args.ethereum_key.to_address();
+
+    let amount = match (args.amount, args.unlimited) {
+        (Some(_), true) | (None, false) => {
+            error!("Provide either --amount or --unlimited");
+            exit(1);
+        }
+        (Some(amount), false) => {
+            let decimals = get_decimals(&web3, args.token_contract_address, owner).await;
+            fraction_to_exponent(amount, decimals)
+        }
+        (None, true) => unlimited_allowance(),
+    };
+    let current = print_allowance(
+        &web3,
+        args.token_contract_address,
+        owner,
+        args.gravity_contract_address,
+    )
+    .await;
+    if current == amount {
+        info!("The allowance is already {}, nothing to do", amount);
+        return;
+    }
+    let description = if args.unlimited {
+        "Approving an unlimited allowance".to_string()
+    } else {
+        format!("Approving an allowance of {}", amount)
+    };
+    set_allowance(
+        &web3,
+        args.ethereum_key,
+        args.token_contract_address,
+        args.gravity_contract_address,
+        amount,
+        args.simulate,
+        &description,
+    )
+    .await;
+}
+
+pub async fn revoke_erc20(args: RevokeErc20Opts, prefix: String) {
+    let connections = create_rpc_connections(prefix, None, Some(args.ethereum_rpc), TIMEOUT).await;
+    let web3 = connections.web3.unwrap();
+    let owner = args.ethereum_key.to_address();
+
+    let current = print_allowance(
+        &web3,
+        args.token_contract_address,
+        owner,
+        args.gravity_contract_address,
+    )
+    .await;
+    if current == 0u8.into() {
+        info!("There is no allowance to revoke");
+        return;
+    }
+    set_allowance(
+        &web3,
+        args.ethereum_key,
+        args.token_contract_address,
+        args.gravity_contract_address,
+        0u8.into(),
+        args.simulate,
+        "Revoking the allowance",
+    )
+    .await;
+}
+
+async fn get_decimals(web3: &Web3, erc20: EthAddress, caller: EthAddress) -> u8 {
+    match web3.get_erc20_decimals(erc20, caller).await {
+        Ok(d) => d.to_string().parse().unwrap(),
+        Err(e) => {
+            error!(
+                "Failed to query the ERC20 contract {}, check the address {:?}",
+                erc20, e
+            );
+            exit(1);
+        }
+    }
+}
+
+/// Prints and returns the current allowance of the Gravity contract
+async fn print_allowance(
+    web3: &Web3,
+    erc20: EthAddress,
+    owner: EthAddress,
+    gravity_contract: EthAddress,
+) -> Uint256 {
+    let allowance = match get_erc20_allowance(erc20, owner, gravity_contract, web3).await {
+        Ok(a) => a,
+        Err(e) => {
+            error!(
+                "Failed to query the allowance, check the ERC20 address {} {:?}",
+                erc20, e
+            );
+            exit(1);
+        }
+    };
+    if is_unlimited_allowance(allowance) {
+        info!(
+            "{} currently allows the Gravity contract {} to transfer an unlimited amount of {}",
+            owner, gravity_contract, erc20
+        );
+    } else {
+        info!(
+            "{} currently allows the Gravity contract {} to transfer {} ({})",
+            owner,
+            gravity_contract,
+            format_erc20(web3, erc20, allowance).await,
+            erc20
+        );
+    }
+    allowance
+}
+
+/// Sends the approval and waits for it to be confirmed, or only simulates it
+async fn set_allowance(
+    web3: &Web3,
+    key: EthPrivateKey,
+    erc20: EthAddress,
+    gravity_contract: EthAddress,
+    amount: Uint256,
+    simulate: bool,
+    description: &str,
+) {
+    let owner = key.to_address();
+    if simulate {
+        let payload = encode_approve_payload(gravity_contract, amount).unwrap();
+        simulate_eth_tx(web3, owner, erc20, payload, description).await;
+        return;
+    }
+
+    check_for_eth(owner, web3).await;
+    info!(
+        "{}, waiting for the transaction to be confirmed",
+        description
+    );
+    match set_erc20_allowance(
+        erc20,
+        gravity_contract,
+        amount,
+        key,
+        Some(TIMEOUT),
+        web3,
+        vec![],
+    )
+    .await
+    {
+        Ok(txid) => info!("Allowance updated, txid: {:#066x}", txid),
+        Err(e) => {
+            error!("Failed to update the allowance {:?}", e);
+            exit(1);
+        }
+    }
+    print_allowance(web3, erc20, owner, gravity_contract).await;
+}
@@ -1,18 +1,29 @@
+use std::collections::BTreeMap;
 use std::process::exit;
 
+use crate::address_book::cosmos_address_or_exit;
 use crate::args::EthToCosmosOpts;
+use crate::simulate::simulate_eth_tx;
 use crate::utils::TIMEOUT;
-use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use clarity::abi::encode_call;
+use ethereum_gravity::send_to_cosmos::{
+    encode_send_to_cosmos_payload, send_to_cosmos, SEND_TO_COSMOS_GAS_LIMIT,
+};
 use ethereum_gravity::utils::get_valset_nonce;
 use gravity_utils::{
     connection_prep::{check_for_eth, create_rpc_connections},
     num_conversion::fraction_to_exponent,
+    token_display::format_erc20,
 };
 
-pub async fn eth_to_cosmos(args: EthToCosmosOpts, prefix: String) {
+pub async fn eth_to_cosmos(
+    args: EthToCosmosOpts,
+    prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
     let gravity_address = args.gravity_contract_address;
     let erc20_address = args.token_contract_address;
-    let cosmos_dest = args.destination;
+    let cosmos_dest = cosmos_address_or_exit(&args.destination, address_book);
     let ethereum_key = args.ethereum_key;
     let ethereum_public_key = ethereum_key.to_address();
     let ethereum_rpc = args.ethereum_rpc;
@@ -47,14 +58,60 @@ pub async fn eth_to_cosmos(args: EthToCosmosOpts, prefix: String) {
         );
         exit(1);
     } else if amount > erc20_balance {
-        error!("Insufficient balance {} > {}", amount, erc20_balance);
+        error!(
+            "Insufficient balance {} > {}",
+            format_erc20(&web3, erc20_address, amount).await,
+            format_erc20(&web3, erc20_address, erc20_balance).await
+        );
         exit(1);
     }
 
     info!(
-        "Sending {} / {} to Cosmos from {} to {}",
-        amount, erc20_address, ethereum_public_key, cosmos_dest
+        "Sending {} ({}) to Cosmos from {} to {}",
+        format_erc20(&web3, erc20_address, amount).await,
+        erc20_address,
+        ethereum_public_key,
+        cosmos_dest
     );
+    if args.simulate {
+        let approved = web3
+            .check_erc20_approved(erc20_address, ethereum_public_key, gravity_address)
+            .await
+            .expect("Failed to check the ERC20 approval");
+        if approved {
+            let payload =
+                encode_send_to_cosmos_payload(erc20_address, cosmos_dest, amount).unwrap();
+            simulate_eth_tx(
+                &web3,
+                ethereum_public_key,
+                gravity_address,
+                payload,
+                "Sending to Cosmos",
+            )
+            .await;
+        } else {
+            // sendToCosmos reverts until the approval is mined, so only the approval can be checked
+            let payload = encode_call(
+                "approve(address,uint256)",
+                &[gravity_address.into(), amount.into()],
+            )
+            .unwrap();
+            simulate_eth_tx(
+                &web3,
+                ethereum_public_key,
+                erc20_address,
+                payload,
+                "Approving the Gravity contract",
+            )
+            .await;
+            info!(
+                "Sending to Cosmos can only be simulated once the approval is mined, it is sent with a gas limit of {}",
+                SEND_TO_COSMOS_GAS_LIMIT
+            );
+        }
+        return;
+    }
+
     // we send some erc20 tokens to the gravity contract to register a deposit
     let res = send_to_cosmos(
         erc20_address,
@@ -0,0 +1,458 @@
+use crate::address_book::eth_address_or_exit;
+use crate::args::IbcToEthOpts;
+use crate::client::cosmos_to_eth::{check_eth_destination, ledger_send_to_eth_msg};
+use crate::client::multisig::amino_coin;
+use crate::ledger::{send_with_ledger, Ledger};
+use crate::simulate::simulate_cosmos_tx;
+use crate::utils::{display_denom, TIMEOUT};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_denom_trace, get_pending_send_to_eth};
+use cosmos_gravity::send::{get_memo, send_to_eth};
+use cosmos_gravity::utils::get_reasonable_send_to_eth_fee;
+use deep_space::client::type_urls::MSG_TRANSFER_TYPE_URL;
+use deep_space::utils::encode_any;
+use deep_space::{Address as CosmosAddress, Coin, Contact, Msg, PrivateKey};
+use gravity_proto::cosmos_sdk_proto::ibc::applications::transfer::v1::MsgTransfer;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::connection_prep::{check_for_fee, create_rpc_connections};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Add;
+use std::process::exit;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+
+/// How often we check on the progress of the transfer while waiting
+const PROGRESS_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Moves tokens from an IBC counterparty chain all the way to Ethereum. The tokens are first sent
+/// over IBC to the Gravity address controlled by the same key, once they arrive a MsgSendToEth is
+/// submitted and the resulting transfer is tracked until it has been relayed to Ethereum
+pub async fn ibc_to_eth(
+    args: IbcToEthOpts,
+    address_prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
+    let amount = args.amount;
+    let bridge_fee = args.bridge_fee;
+    let eth_dest = eth_address_or_exit(
+        &args.eth_destination,
+        address_book,
+        args.allow_unchecksummed,
+    );
+    if amount.denom != bridge_fee.denom {
+        error!(
+            "The bridge fee must be in the same token as the amount, found {} and {}",
+            bridge_fee.denom, amount.denom
+        );
+        exit(1);
+    }
+    if let Some(chain_fee) = &args.chain_fee {
+        if amount.denom != chain_fee.denom {
+            error!(
+                "The chain fee must be in the same token as the amount, found {} and {}",
+                chain_fee.denom, amount.denom
+            );
+            exit(1);
+        }
+    }
+
+    let counterparty_contact =
+        match Contact::new(&args.counterparty_grpc, TIMEOUT, &args.counterparty_prefix) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Could not connect to the counterparty chain {:?}", e);
+                exit(1);
+            }
+        };
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    check_eth_destination(&web3, eth_dest, args.dest_is_contract).await;
+
+    let ledger = if args.ledger {
+        match Ledger::connect(args.ledger_account, &args.counterparty_prefix) {
+            Ok(l) => Some(l),
+            Err(e) => {
+                error!("Failed to connect to the Ledger {}", e);
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let counterparty_address = match (&ledger, &args.cosmos_phrase) {
+        (Some(l), _) => l.address(),
+        (None, Some(k)) => k.to_address(&args.counterparty_prefix).unwrap(),
+        (None, None) => {
+            error!("Provide either --cosmos-phrase or --ledger");
+            exit(1);
+        }
+    };
+    // the same key controls both addresses, only the prefix differs
+    let gravity_address =
+        CosmosAddress::from_slice(counterparty_address.get_bytes(), &address_prefix).unwrap();
+
+    // the chain fee is a percentage of the amount, we need to know it before sending over IBC
+    // so that enough tokens arrive on Gravity to pay for everything
+    let chain_fee_amount = match args.chain_fee {
+        Some(f) => f.amount,
+        None => match get_reasonable_send_to_eth_fee(&contact, amount.amount).await {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Failed to compute the SendToEth chain fee {:?}", e);
+                exit(1);
+            }
+        },
+    };
+    let to_transfer = Coin {
+        amount: amount.amount + bridge_fee.amount + chain_fee_amount,
+        denom: amount.denom.clone(),
+    };
+    check_for_fee(&to_transfer, counterparty_address, &counterparty_contact).await;
+    check_for_fee(
+        &args.counterparty_fee,
+        counterparty_address,
+        &counterparty_contact,
+    )
+    .await;
+    check_for_fee(&args.fee, gravity_address, &contact).await;
+
+    let starting_balances = get_ibc_balances(&contact, gravity_address).await;
+
+    info!(
+        "Sending {} from {} to {} over channel {}",
+        to_transfer, counterparty_address, gravity_address, args.channel
+    );
+    let timeout_timestamp = SystemTime::now()
+        .add(Duration::from_secs(args.packet_timeout))
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let msg_transfer = MsgTransfer {
+        source_port: "transfer".to_string(),
+        source_channel: args.channel,
+        token: Some(to_transfer.clone().into()),
+        sender: counterparty_address.to_string(),
+        receiver: gravity_address.to_string(),
+        timeout_height: None,
+        timeout_timestamp,
+        ..Default::default()
+    };
+    let res = match (&ledger, args.cosmos_phrase) {
+        (Some(ledger), _) => {
+            // keys are in sorted order, matching the sorted JSON the Ledger signs over, a
+            // timeout height of zero is an empty object in amino JSON and the empty packet memo
+            // is omitted
+            let amino = json!({
+                "type": "cosmos-sdk/MsgTransfer",
+                "value": {
+                    "receiver": msg_transfer.receiver,
+                    "sender": msg_transfer.sender,
+                    "source_channel": msg_transfer.source_channel,
+                    "source_port": msg_transfer.source_port,
+                    "timeout_height": {},
+                    "timeout_timestamp": msg_transfer.timeout_timestamp.to_string(),
+                    "token": amino_coin(&to_transfer),
+                }
+            });
+            let msg = encode_any(msg_transfer, MSG_TRANSFER_TYPE_URL.to_string());
+            send_with_ledger(
+                &counterparty_contact,
+                ledger,
+                msg,
+                amino,
+                args.counterparty_fee,
+                String::new(),
+            )
+            .await
+        }
+        (None, Some(key)) => {
+            let msg_transfer = Msg::new(MSG_TRANSFER_TYPE_URL, msg_transfer);
+            if args.simulate {
+                simulate_cosmos_tx(
+                    &counterparty_contact,
+                    &[msg_transfer],
+                    &[args.counterparty_fee],
+                    key,
+                )
+                .await;
+                info!(
+                    "The SendToEth on Gravity can not be simulated until the tokens have arrived"
+                );
+                return;
+            }
+            counterparty_contact
+                .send_message(
+                    &[msg_transfer],
+                    None,
+                    &[args.counterparty_fee],
+                    Some(TIMEOUT),
+                    key,
+                )
+                .await
+        }
+        (None, None) => unreachable!(),
+    };
+    match res {
+        Ok(res) => info!("IBC transfer txid {}", res.txhash),
+        Err(e) => {
+            error!("Failed to send IBC transfer {:?}", e);
+            exit(1);
+        }
+    }
+
+    let tracking_timeout = Duration::from_secs(args.tracking_timeout * 60);
+    let start = Instant::now();
+    let gravity_denom = match wait_for_ibc_arrival(
+        &contact,
+        gravity_address,
+        &amount.denom,
+        to_transfer.amount,
+        &starting_balances,
+        tracking_timeout,
+    )
+    .await
+    {
+        Some(denom) => denom,
+        None => {
+            error!("Tokens did not arrive on Gravity within the timeout, the IBC relayer may be delayed or the packet may have timed out");
+            error!(
+                "If the tokens arrive later you can send them to Ethereum with `gbt client cosmos-to-eth` from {}",
+                gravity_address
+            );
+            exit(1);
+        }
+    };
+    info!(
+        "{} arrived on Gravity as {}",
+        amount.denom,
+        display_denom(&contact, &gravity_denom).await
+    );
+
+    let existing_sends = get_pending_send_ids(&mut grpc, gravity_address).await;
+    let to_bridge = Coin {
+        amount: amount.amount,
+        denom: gravity_denom.clone(),
+    };
+    let bridge_fee = Coin {
+        amount: bridge_fee.amount,
+        denom: gravity_denom.clone(),
+    };
+    let chain_fee = Coin {
+        amount: chain_fee_amount,
+        denom: gravity_denom,
+    };
+    let res = match (ledger, args.cosmos_phrase) {
+        (Some(ledger), _) => {
+            let ledger = ledger.for_prefix(&address_prefix);
+            let (msg, amino) = ledger_send_to_eth_msg(
+                gravity_address,
+                eth_dest,
+                &to_bridge,
+                &bridge_fee,
+                &chain_fee,
+            );
+            let memo = args.memo.unwrap_or_else(get_memo);
+            send_with_ledger(&contact, &ledger, msg, amino, args.fee, memo).await
+        }
+        (None, Some(key)) => {
+            send_to_eth(
+                key,
+                eth_dest,
+                to_bridge,
+                bridge_fee,
+                Some(chain_fee),
+                args.fee,
+                &contact,
+                args.memo,
+            )
+            .await
+        }
+        (None, None) => unreachable!(),
+    };
+    match res {
+        Ok(tx) => info!("Send to Eth txid {}", tx.txhash),
+        Err(e) => {
+            error!("Failed to send tokens to Ethereum! {:?}", e);
+            error!(
+                "Your tokens are on Gravity at {}, you can retry with `gbt client cosmos-to-eth`",
+                gravity_address
+            );
+            exit(1);
+        }
+    }
+
+    let remaining = tracking_timeout
+        .checked_sub(Instant::now() - start)
+        .unwrap_or_default();
+    track_send_to_eth(
+        &mut grpc,
+        gravity_address,
+        eth_dest,
+        existing_sends,
+        remaining,
+    )
+    .await;
+}
+
+/// Returns all the ibc/ balances held by the given address
+async fn get_ibc_balances(contact: &Contact, address: CosmosAddress) -> Vec<Coin> {
+    match contact.get_balances(address).await {
+        Ok(balances) => balances
+            .into_iter()
+            .filter(|b| b.denom.starts_with("ibc/"))
+            .collect(),
+        Err(e) => {
+            error!("Failed to get balances on Gravity {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Waits until a balance of the given base denom has increased by at least `amount` on Gravity,
+/// returning the ibc/ denom it arrived as
+async fn wait_for_ibc_arrival(
+    contact: &Contact,
+    address: CosmosAddress,
+    base_denom: &str,
+    amount: Uint256,
+    starting_balances: &[Coin],
+    timeout: Duration,
+) -> Option<String> {
+    let start = Instant::now();
+    while Instant::now() - start < timeout {
+        for balance in get_ibc_balances(contact, address).await {
+            let previous = starting_balances
+                .iter()
+                .find(|b| b.denom == balance.denom)
+                .map(|b| b.amount)
+                .unwrap_or_default();
+            if balance.amount < previous || balance.amount - previous < amount {
+                continue;
+            }
+            if let Ok(Some(trace)) = get_denom_trace(contact, &balance.denom).await {
+                if trace.base_denom == base_denom {
+                    return Some(balance.denom);
+                }
+            }
+        }
+        info!("Waiting for the IBC transfer to arrive on Gravity");
+        delay_for(PROGRESS_CHECK_INTERVAL).await;
+    }
+    None
+}
+
+/// Whether `tx` is a SendToEth to `eth_dest` that did not exist before the transfer was sent
+fn is_new_send(
+    tx: &gravity_proto::gravity::OutgoingTransferTx,
+    existing_sends: &HashSet<u64>,
+    eth_dest: EthAddress,
+) -> bool {
+    !existing_sends.contains(&tx.id)
+        && tx
+            .dest_address
+            .parse::<EthAddress>()
+            .map(|a| a == eth_dest)
+            .unwrap_or(false)
+}
+
+/// Returns the ids of all pending SendToEth transfers for this sender, batched or not
+async fn get_pending_send_ids(
+    grpc: &mut GravityQueryClient<Channel>,
+    sender: CosmosAddress,
+) -> HashSet<u64> {
+    match get_pending_send_to_eth(grpc, sender).await {
+        Ok(pending) => pending
+            .transfers_in_batches
+            .iter()
+            .chain(pending.unbatched_transfers.iter())
+            .map(|tx| tx.id)
+            .collect(),
+        Err(e) => {
+            warn!("Failed to get pending SendToEth transfers {:?}", e);
+            HashSet::new()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SendToEthStatus {
+    Unbatched,
+    InBatch,
+    Complete,
+}
+
+/// Follows a newly created SendToEth through batching and relaying, once it is no longer pending
+/// on Gravity it has been executed on Ethereum
+async fn track_send_to_eth(
+    grpc: &mut GravityQueryClient<Channel>,
+    sender: CosmosAddress,
+    eth_dest: EthAddress,
+    existing_sends: HashSet<u64>,
+    timeout: Duration,
+) {
+    let start = Instant::now();
+    let mut our_id = None;
+    let mut last_status = None;
+    while Instant::now() - start < timeout {
+        let pending = match get_pending_send_to_eth(grpc, sender).await {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to get pending SendToEth transfers {:?}", e);
+                delay_for(PROGRESS_CHECK_INTERVAL).await;
+                continue;
+            }
+        };
+        let is_ours = |tx: &gravity_proto::gravity::OutgoingTransferTx| match our_id {
+            Some(id) => tx.id == id,
+            None => is_new_send(tx, &existing_sends, eth_dest),
+        };
+        let unbatched = pending.unbatched_transfers.iter().find(|tx| is_ours(tx));
+        let in_batch = pending.transfers_in_batches.iter().find(|tx| is_ours(tx));
+        let (id, status) = match (unbatched, in_batch, our_id) {
+            (Some(tx), _, _) => (tx.id, SendToEthStatus::Unbatched),
+            (None, Some(tx), _) => (tx.id, SendToEthStatus::InBatch),
+            (None, None, Some(id)) => (id, SendToEthStatus::Complete),
+            (None, None, None) => {
+                // the send has not shown up yet
+                delay_for(PROGRESS_CHECK_INTERVAL).await;
+                continue;
+            }
+        };
+        our_id = Some(id);
+
+        if last_status != Some(status) {
+            match status {
+                SendToEthStatus::Unbatched => {
+                    info!("SendToEth {} is waiting to be included in a batch", id)
+                }
+                SendToEthStatus::InBatch => info!(
+                    "SendToEth {} is in a batch, waiting for it to be relayed to Ethereum",
+                    id
+                ),
+                SendToEthStatus::Complete => {
+                    info!(
+                        "SendToEth {} has been executed on Ethereum, your tokens are at {}",
+                        id, eth_dest
+                    );
+                    return;
+                }
+            }
+            last_status = Some(status);
+        }
+        delay_for(PROGRESS_CHECK_INTERVAL).await;
+    }
+    info!("Stopped tracking your transfer, it is still on its way to Ethereum");
+    info!("Depending on how much you and others attached in fees, this might take a while!");
+    info!("You can retrieve your funds using a CancelSendToEth message, up until they are sent to Ethereum");
+}
@@ -0,0 +1,263 @@
+use crate::args::{LogicCallBuildOpts, LogicCallRelayOpts};
+use crate::utils::TIMEOUT;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clarity::constants::zero_address;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::{
+    get_gravity_params, get_latest_logic_calls, get_logic_call_signatures,
+};
+use ethereum_gravity::logic_call::send_eth_logic_call;
+use ethereum_gravity::logic_call_builder::{
+    encode_function_call, invalidation_id_for_scope, next_invalidation_nonce,
+};
+use ethereum_gravity::message_signatures::encode_logic_call_confirm_hashed;
+use gravity_utils::connection_prep::{check_for_eth, create_rpc_connections};
+use gravity_utils::types::{Erc20Token, LogicCall, PrivateRelayConfig};
+use relayer::find_latest_valset::find_latest_valset;
+use serde_json::json;
+use std::process::exit;
+
+pub async fn build_logic_call(args: LogicCallBuildOpts) {
+    let payload = match encode_function_call(&args.function, &args.args) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Could not encode the call {}", e);
+            exit(1);
+        }
+    };
+    let invalidation_id = invalidation_id(args.invalidation_id, args.invalidation_scope);
+    let transfers = parse_tokens(&args.transfers);
+    let fees = parse_tokens(&args.fees);
+
+    let invalidation_nonce = match args.invalidation_nonce {
+        Some(n) => n,
+        None => {
+            let gravity_contract_address = match args.gravity_contract_address {
+                Some(c) => c,
+                None => {
+                    error!("Either --invalidation-nonce or --gravity-contract-address is required");
+                    exit(1);
+                }
+            };
+            let connections =
+                create_rpc_connections(String::new(), None, Some(args.ethereum_rpc), TIMEOUT).await;
+            let web3 = connections.web3.unwrap();
+            let nonce = next_invalidation_nonce(
+                gravity_contract_address,
+                invalidation_id.clone(),
+                zero_address(),
+                &web3,
+            )
+            .await;
+            match nonce {
+                Ok(n) => n,
+                Err(e) => {
+                    error!("Could not get the next invalidation nonce {:?}", e);
+                    exit(1);
+                }
+            }
+        }
+    };
+
+    let call = LogicCall {
+        transfers,
+        fees,
+        logic_contract_address: args.logic_contract,
+        payload,
+        timeout: args.timeout,
+        invalidation_id,
+        invalidation_nonce,
+    };
+    let tokens = |list: &[Erc20Token]| {
+        list.iter()
+            .map(|t| {
+                json!({
+                    "contract": t.token_contract_address.to_string(),
+                    "amount": t.amount.to_string(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    // the genesis encodes bytes as base64 and 64 bit integers as strings
+    let entry = json!({
+        "transfers": tokens(&call.transfers),
+        "fees": tokens(&call.fees),
+        "logic_contract_address": call.logic_contract_address.to_string(),
+        "payload": BASE64.encode(&call.payload),
+        "timeout": call.timeout.to_string(),
+        "invalidation_id": BASE64.encode(&call.invalidation_id),
+        "invalidation_nonce": call.invalidation_nonce.to_string(),
+        "cosmos_block_created": "0",
+    });
+    println!("{}", serde_json::to_string_pretty(&entry).unwrap());
+    println!(
+        "Invalidation id 0x{} nonce {}",
+        bytes_to_hex_str(&call.invalidation_id),
+        call.invalidation_nonce
+    );
+    println!("Payload 0x{}", bytes_to_hex_str(&call.payload));
+    if let Some(gravity_id) = args.gravity_id {
+        println!(
+            "Checkpoint 0x{}",
+            bytes_to_hex_str(&encode_logic_call_confirm_hashed(gravity_id, call))
+        );
+    }
+}
+
+pub async fn relay_logic_call(args: LogicCallRelayOpts, address_prefix: String) {
+    let invalidation_id = invalidation_id(args.invalidation_id, args.invalidation_scope);
+    let invalidation_nonce = args.invalidation_nonce;
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    check_for_eth(args.ethereum_key.to_address(), &web3).await;
+
+    let params = get_gravity_params(&mut grpc).await.unwrap();
+    let gravity_id = params.gravity_id;
+    let gravity_contract_address = if let Some(c) = args.gravity_contract_address {
+        c
+    } else {
+        let c = params.bridge_ethereum_address.parse();
+        if c.is_err() {
+            error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+            exit(1);
+        }
+        c.unwrap()
+    };
+
+    let calls = get_latest_logic_calls(&mut grpc)
+        .await
+        .expect("Failed to get logic calls");
+    let call = match calls.into_iter().find(|c| {
+        c.invalidation_id == invalidation_id && c.invalidation_nonce == invalidation_nonce
+    }) {
+        Some(c) => c,
+        None => {
+            error!(
+                "No pending logic call with invalidation id 0x{} and nonce {}, it may have been relayed, timed out or never been created",
+                bytes_to_hex_str(&invalidation_id),
+                invalidation_nonce
+            );
+            exit(1);
+        }
+    };
+    let latest_eth_height = web3.eth_block_number().await.unwrap();
+    if latest_eth_height > call.timeout.into() {
+        error!(
+            "The logic call timed out at Ethereum block {}",
+            call.timeout
+        );
+        exit(1);
+    }
+
+    let sigs = get_logic_call_signatures(
+        &mut grpc,
+        call.invalidation_id.clone(),
+        call.invalidation_nonce,
+    )
+    .await
+    .expect("Failed to get sigs for logic call!");
+    let current_valset = match find_latest_valset(&mut grpc, gravity_contract_address, &web3).await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Could not get current valset! {:?}", e);
+            exit(1);
+        }
+    };
+    // this checks that the signatures for the call are actually possible to submit to the chain
+    let hash = encode_logic_call_confirm_hashed(gravity_id.clone(), call.clone());
+    if let Err(e) = current_valset.order_sigs(&hash, &sigs) {
+        error!("Not enough validators have signed this logic call to relay it yet");
+        error!("{:?}", e);
+        exit(1);
+    }
+
+    info!(
+        "Relaying logic call {} to {}",
+        call.invalidation_nonce, call.logic_contract_address
+    );
+    let res = send_eth_logic_call(
+        current_valset,
+        call,
+        &sigs,
+        &web3,
+        TIMEOUT,
+        gravity_contract_address,
+        gravity_id,
+        args.ethereum_key,
+        None,
+        true,
+        &PrivateRelayConfig::default(),
+    )
+    .await;
+    match res {
+        Ok(_) => info!("Logic call submission was successful! Check Etherscan"),
+        Err(e) => {
+            error!("Logic call submission has failed {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+/// The invalidation id given directly as hex or derived from a scope name
+fn invalidation_id(id: Option<String>, scope: Option<String>) -> Vec<u8> {
+    match (id, scope) {
+        (Some(id), _) => match hex_str_to_bytes(id.trim_start_matches("0x")) {
+            Ok(id) => id,
+            Err(e) => {
+                error!("Invalid invalidation id {} {:?}", id, e);
+                exit(1);
+            }
+        },
+        (None, Some(scope)) => invalidation_id_for_scope(&scope),
+        (None, None) => {
+            error!("One of --invalidation-id or --invalidation-scope is required");
+            exit(1);
+        }
+    }
+}
+
+/// Parses erc20_address:amount pairs, exiting on the first invalid one
+fn parse_tokens(input: &[String]) -> Vec<Erc20Token> {
+    input
+        .iter()
+        .map(|t| match parse_token(t) {
+            Some(t) => t,
+            None => {
+                error!("{} is not a token in the form erc20_address:amount", t);
+                exit(1);
+            }
+        })
+        .collect()
+}
+
+fn parse_token(input: &str) -> Option<Erc20Token> {
+    let (contract, amount) = input.split_once(':')?;
+    let token_contract_address: EthAddress = contract.parse().ok()?;
+    Some(Erc20Token {
+        amount: amount.parse().ok()?,
+        token_contract_address,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_token() {
+        let token = parse_token("0x6Bd41fCdF129297c3524395d669c0865b3CA85B2:1000").unwrap();
+        assert_eq!(token.amount, 1000u32.into());
+        assert!(parse_token("0x6Bd41fCdF129297c3524395d669c0865b3CA85B2").is_none());
+        assert!(parse_token("usdc:1000").is_none());
+    }
+}
@@ -1,4 +1,11 @@
 pub mod cosmos_to_eth;
 pub mod deploy_erc20_representation;
+pub mod erc20_allowance;
 pub mod eth_to_cosmos;
+pub mod ibc_to_eth;
+pub mod logic_call;
+pub mod multisig;
+pub mod offline;
+pub mod query;
+pub mod round_trip;
 pub mod spot_relay;
@@ -0,0 +1,573 @@
+//! Legacy amino threshold multisig accounts, the kind created with `gravity keys add --multisig`.
+//! Each member signs the amino JSON sign doc of the transaction with their own key, once enough
+//! signatures are collected they are combined into a transaction that can be broadcast with
+//! `gbt client broadcast`. Amino JSON is required since with direct signing the sign doc includes
+//! the set of members that signed, which is not known until the signatures are combined
+
+use crate::address_book::eth_address_or_exit;
+use crate::args::{
+    MultisigCombineOpts, MultisigCreateOpts, MultisigPubkeyOpts, MultisigSignOpts,
+    MultisigTxSubcommand,
+};
+use crate::client::offline::SignedTx;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use cosmos_gravity::send::{MEMO, MSG_CANCEL_SEND_TO_ETH_TYPE_URL, MSG_SEND_TO_ETH_TYPE_URL};
+use deep_space::{Address as CosmosAddress, Coin};
+use gravity_proto::cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use gravity_proto::cosmos_sdk_proto::cosmos::crypto::multisig::v1beta1::{
+    CompactBitArray, MultiSignature,
+};
+use gravity_proto::cosmos_sdk_proto::cosmos::crypto::multisig::LegacyAminoPubKey;
+use gravity_proto::cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as Secp256k1PubKey;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::mode_info::{Multi, Single, Sum};
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    AuthInfo, Fee as ProtoFee, ModeInfo, SignerInfo, TxBody, TxRaw,
+};
+use gravity_proto::gravity::{MsgCancelSendToEth, MsgSendToEth};
+use prost::Message;
+use prost_types::Any;
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message as Secp256k1Message, PublicKey, Secp256k1, SecretKey};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+pub(crate) const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+const LEGACY_AMINO_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.multisig.LegacyAminoPubKey";
+/// Amino prefixes of the registered multisig and secp256k1 public key types
+const AMINO_MULTISIG_PREFIX: [u8; 4] = [0x22, 0xC1, 0xF7, 0xE2];
+const AMINO_SECP256K1_PREFIX: [u8; 4] = [0xEB, 0x5A, 0xE9, 0x87];
+
+/// The members and threshold of a multisig account, written by `gbt client multisig create`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MultisigAccount {
+    pub address: String,
+    pub threshold: u32,
+    /// hex compressed secp256k1 public keys, in account order
+    pub public_keys: Vec<String>,
+}
+
+/// Everything about a transaction that the members sign, every signature being combined must be
+/// for an identical transaction
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedMultisigTx {
+    pub chain_id: String,
+    pub account_number: u64,
+    pub sequence: u64,
+    pub description: String,
+    pub fee_amount: String,
+    pub fee_denom: String,
+    pub gas_limit: u64,
+    /// hex encoded TxBody
+    pub body_bytes: String,
+    /// the exact amino JSON that was signed
+    pub sign_doc: String,
+}
+
+/// A single member's signature, written by `gbt client multisig sign`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PartialSignature {
+    pub tx: UnsignedMultisigTx,
+    pub public_key: String,
+    pub signature: String,
+}
+
+pub fn multisig_pubkey(args: MultisigPubkeyOpts) {
+    let key = parse_key(&args.key);
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &key);
+    info!("{}", bytes_to_hex_str(&public_key.serialize()));
+}
+
+pub fn multisig_create(args: MultisigCreateOpts, address_prefix: String) {
+    if args.pubkey.is_empty() || args.threshold == 0 || args.threshold as usize > args.pubkey.len()
+    {
+        error!(
+            "The threshold must be between 1 and the number of members, {} public keys were provided",
+            args.pubkey.len()
+        );
+        exit(1);
+    }
+    let mut keys = Vec::new();
+    for k in args.pubkey.iter() {
+        keys.push(parse_pubkey(k));
+    }
+    let address = multisig_address(args.threshold, &keys, &address_prefix);
+    let account = MultisigAccount {
+        address: address.to_string(),
+        threshold: args.threshold,
+        public_keys: keys.iter().map(|k| bytes_to_hex_str(k)).collect(),
+    };
+    write_json(&args.output, &account);
+    info!(
+        "{} of {} multisig with address {}",
+        args.threshold,
+        keys.len(),
+        address
+    );
+    info!(
+        "Wrote the multisig description to {}, every member needs it to sign",
+        args.output.display()
+    );
+}
+
+pub fn multisig_sign(
+    args: MultisigSignOpts,
+    address_prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
+    let account: MultisigAccount = read_json(&args.multisig);
+    let key = parse_key(&args.key);
+    let secp = Secp256k1::new();
+    let public_key = PublicKey::from_secret_key(&secp, &key).serialize();
+    if !account
+        .public_keys
+        .iter()
+        .any(|k| parse_pubkey(k) == public_key)
+    {
+        error!(
+            "This key is not a member of the multisig {}",
+            account.address
+        );
+        exit(1);
+    }
+    let multisig: CosmosAddress = match account.address.parse() {
+        Ok(a) => a,
+        Err(e) => {
+            error!("Invalid multisig address {} {:?}", account.address, e);
+            exit(1);
+        }
+    };
+    if multisig.get_prefix() != address_prefix {
+        warn!(
+            "The multisig address {} does not use the prefix {}",
+            multisig, address_prefix
+        );
+    }
+
+    let (any, amino_msg, description) = build_msg(args.tx, multisig, address_book);
+    let body = TxBody {
+        messages: vec![any],
+        // every signer must sign the same memo, so the configured memo is deliberately not used
+        memo: MEMO.to_string(),
+        ..Default::default()
+    };
+    let sign_doc = amino_sign_doc(
+        &args.chain_id,
+        args.account_number,
+        args.sequence,
+        &args.fee,
+        args.gas_limit,
+        MEMO,
+        amino_msg,
+    );
+    let signature = sign(&secp, &key, sign_doc.as_bytes());
+
+    let partial = PartialSignature {
+        tx: UnsignedMultisigTx {
+            chain_id: args.chain_id,
+            account_number: args.account_number,
+            sequence: args.sequence,
+            description: format!("{} paying {}", description, args.fee),
+            fee_amount: args.fee.amount.to_string(),
+            fee_denom: args.fee.denom,
+            gas_limit: args.gas_limit,
+            body_bytes: bytes_to_hex_str(&body.encode_to_vec()),
+            sign_doc,
+        },
+        public_key: bytes_to_hex_str(&public_key),
+        signature: bytes_to_hex_str(&signature),
+    };
+    write_json(&args.output, &partial);
+    info!("Signed: {}", partial.tx.description);
+    info!(
+        "Wrote the signature to {}, combine at least {} signatures with `gbt client multisig combine`",
+        args.output.display(),
+        account.threshold
+    );
+}
+
+pub fn multisig_combine(args: MultisigCombineOpts) {
+    let account: MultisigAccount = read_json(&args.multisig);
+    let keys: Vec<[u8; 33]> = account
+        .public_keys
+        .iter()
+        .map(|k| parse_pubkey(k))
+        .collect();
+    let secp = Secp256k1::new();
+
+    let mut tx: Option<UnsignedMultisigTx> = None;
+    // signatures by member index, sorted as the multisig expects them
+    let mut signatures: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+    for path in args.signature.iter() {
+        let partial: PartialSignature = read_json(path);
+        match &tx {
+            Some(t) if *t != partial.tx => {
+                error!(
+                    "{} is a signature for a different transaction, all members must sign the same transaction details",
+                    path.display()
+                );
+                exit(1);
+            }
+            Some(_) => {}
+            None => tx = Some(partial.tx.clone()),
+        }
+        let public_key = parse_pubkey(&partial.public_key);
+        let index = match keys.iter().position(|k| *k == public_key) {
+            Some(i) => i,
+            None => {
+                error!(
+                    "{} is signed by {} which is not a member of the multisig",
+                    path.display(),
+                    partial.public_key
+                );
+                exit(1);
+            }
+        };
+        let signature = hex_str_to_bytes(&partial.signature).unwrap_or_default();
+        if !verify(
+            &secp,
+            &public_key,
+            partial.tx.sign_doc.as_bytes(),
+            &signature,
+        ) {
+            error!("{} has an invalid signature", path.display());
+            exit(1);
+        }
+        if signatures.insert(index, signature).is_some() {
+            warn!("Ignoring duplicate signature {}", path.display());
+        }
+    }
+    let tx = match tx {
+        Some(t) => t,
+        None => {
+            error!("No signatures provided");
+            exit(1);
+        }
+    };
+    if signatures.len() < account.threshold as usize {
+        error!(
+            "{} signatures were provided but the multisig needs {}",
+            signatures.len(),
+            account.threshold
+        );
+        exit(1);
+    }
+
+    let signed_by: Vec<usize> = signatures.keys().cloned().collect();
+    let auth_info = AuthInfo {
+        signer_infos: vec![SignerInfo {
+            public_key: Some(legacy_amino_pubkey(account.threshold, &keys)),
+            mode_info: Some(multi_mode_info(keys.len(), &signed_by)),
+            sequence: tx.sequence,
+        }],
+        fee: Some(ProtoFee {
+            amount: vec![ProtoCoin {
+                denom: tx.fee_denom.clone(),
+                amount: tx.fee_amount.clone(),
+            }],
+            gas_limit: tx.gas_limit,
+            payer: String::new(),
+            granter: String::new(),
+        }),
+    };
+    let multi_signature = MultiSignature {
+        signatures: signatures.into_values().collect(),
+    };
+    let tx_raw = TxRaw {
+        body_bytes: hex_str_to_bytes(&tx.body_bytes).expect("Corrupted transaction body"),
+        auth_info_bytes: auth_info.encode_to_vec(),
+        signatures: vec![multi_signature.encode_to_vec()],
+    };
+
+    let signed = SignedTx {
+        chain_id: tx.chain_id,
+        signer: account.address,
+        account_number: tx.account_number,
+        sequence: tx.sequence,
+        description: tx.description,
+        tx_bytes: bytes_to_hex_str(&tx_raw.encode_to_vec()),
+    };
+    write_json(&args.output, &signed);
+    info!(
+        "Combined {} signatures for: {}",
+        signed_by.len(),
+        signed.description
+    );
+    info!(
+        "Wrote the signed transaction to {}, broadcast it with `gbt client broadcast`",
+        args.output.display()
+    );
+}
+
+/// Builds the proto message, its amino JSON form and a summary of it
+fn build_msg(
+    tx: MultisigTxSubcommand,
+    sender: CosmosAddress,
+    address_book: &BTreeMap<String, String>,
+) -> (Any, serde_json::Value, String) {
+    match tx {
+        MultisigTxSubcommand::SendToEth(opts) => {
+            let eth_dest = eth_address_or_exit(
+                &opts.eth_destination,
+                address_book,
+                opts.allow_unchecksummed,
+            );
+            if opts.amount.denom != opts.bridge_fee.denom
+                || opts.amount.denom != opts.chain_fee.denom
+            {
+                error!("The bridge fee and chain fee must be in the same token as the amount");
+                exit(1);
+            }
+            let description = format!(
+                "SendToEth of {} from {} to {} with bridge fee {} and chain fee {}",
+                opts.amount, sender, eth_dest, opts.bridge_fee, opts.chain_fee
+            );
+            // keys are in sorted order, matching the sorted JSON the chain signs over
+            let amino = json!({
+                "type": "gravity/MsgSendToEth",
+                "value": {
+                    "amount": amino_coin(&opts.amount),
+                    "bridge_fee": amino_coin(&opts.bridge_fee),
+                    "chain_fee": amino_coin(&opts.chain_fee),
+                    "eth_dest": eth_dest.to_string(),
+                    "sender": sender.to_string(),
+                }
+            });
+            let msg = MsgSendToEth {
+                sender: sender.to_string(),
+                eth_dest: eth_dest.to_string(),
+                amount: Some(opts.amount.into()),
+                bridge_fee: Some(opts.bridge_fee.into()),
+                chain_fee: Some(opts.chain_fee.into()),
+            };
+            let any = Any {
+                type_url: MSG_SEND_TO_ETH_TYPE_URL.to_string(),
+                value: msg.encode_to_vec(),
+            };
+            (any, amino, description)
+        }
+        MultisigTxSubcommand::CancelSendToEth(opts) => {
+            let description = format!("CancelSendToEth of {} by {}", opts.transaction_id, sender);
+            let amino = json!({
+                "type": "gravity/MsgCancelSendToEth",
+                "value": {
+                    "sender": sender.to_string(),
+                    "transaction_id": opts.transaction_id.to_string(),
+                }
+            });
+            let msg = MsgCancelSendToEth {
+                transaction_id: opts.transaction_id,
+                sender: sender.to_string(),
+            };
+            let any = Any {
+                type_url: MSG_CANCEL_SEND_TO_ETH_TYPE_URL.to_string(),
+                value: msg.encode_to_vec(),
+            };
+            (any, amino, description)
+        }
+    }
+}
+
+pub(crate) fn amino_coin(coin: &Coin) -> serde_json::Value {
+    json!({"amount": coin.amount.to_string(), "denom": coin.denom})
+}
+
+/// The legacy StdSignDoc, all keys sorted and no whitespace. Zero timeout heights are omitted
+pub(crate) fn amino_sign_doc(
+    chain_id: &str,
+    account_number: u64,
+    sequence: u64,
+    fee: &Coin,
+    gas_limit: u64,
+    memo: &str,
+    msg: serde_json::Value,
+) -> String {
+    json!({
+        "account_number": account_number.to_string(),
+        "chain_id": chain_id,
+        "fee": {
+            "amount": [amino_coin(fee)],
+            "gas": gas_limit.to_string(),
+        },
+        "memo": memo,
+        "msgs": [msg],
+        "sequence": sequence.to_string(),
+    })
+    .to_string()
+}
+
+fn sign(secp: &Secp256k1<secp256k1::All>, key: &SecretKey, data: &[u8]) -> Vec<u8> {
+    let hash = Sha256::digest(data);
+    let message = Secp256k1Message::from_slice(&hash).unwrap();
+    // signatures are always low-s normalized, as the chain requires
+    secp.sign_ecdsa(&message, key).serialize_compact().to_vec()
+}
+
+fn verify(
+    secp: &Secp256k1<secp256k1::All>,
+    public_key: &[u8; 33],
+    data: &[u8],
+    signature: &[u8],
+) -> bool {
+    let hash = Sha256::digest(data);
+    let message = Secp256k1Message::from_slice(&hash).unwrap();
+    match (
+        PublicKey::from_slice(public_key),
+        Signature::from_compact(signature),
+    ) {
+        (Ok(k), Ok(s)) => secp.verify_ecdsa(&message, &s, &k).is_ok(),
+        _ => false,
+    }
+}
+
+/// The address of a multisig is the truncated hash of the amino encoding of its public key
+fn multisig_address(threshold: u32, keys: &[[u8; 33]], prefix: &str) -> CosmosAddress {
+    let mut encoded = AMINO_MULTISIG_PREFIX.to_vec();
+    encoded.push(0x08);
+    let mut t = threshold;
+    while t >= 0x80 {
+        encoded.push((t as u8) | 0x80);
+        t >>= 7;
+    }
+    encoded.push(t as u8);
+    for k in keys {
+        encoded.push(0x12);
+        encoded.push(38);
+        encoded.extend_from_slice(&AMINO_SECP256K1_PREFIX);
+        encoded.push(33);
+        encoded.extend_from_slice(k);
+    }
+    let hash = Sha256::digest(&encoded);
+    CosmosAddress::from_slice(&hash[..20], prefix).unwrap()
+}
+
+fn legacy_amino_pubkey(threshold: u32, keys: &[[u8; 33]]) -> Any {
+    let public_keys = keys
+        .iter()
+        .map(|k| Any {
+            type_url: SECP256K1_PUBKEY_TYPE_URL.to_string(),
+            value: Secp256k1PubKey { key: k.to_vec() }.encode_to_vec(),
+        })
+        .collect();
+    Any {
+        type_url: LEGACY_AMINO_PUBKEY_TYPE_URL.to_string(),
+        value: LegacyAminoPubKey {
+            threshold,
+            public_keys,
+        }
+        .encode_to_vec(),
+    }
+}
+
+/// The mode info of a multisig signature, a bit array of the members that signed
+fn multi_mode_info(members: usize, signed_by: &[usize]) -> ModeInfo {
+    let mut elems = vec![0u8; members.div_ceil(8)];
+    for i in signed_by {
+        elems[i / 8] |= 1 << (7 - (i % 8));
+    }
+    let single = ModeInfo {
+        sum: Some(Sum::Single(Single {
+            mode: SignMode::LegacyAminoJson as i32,
+        })),
+    };
+    ModeInfo {
+        sum: Some(Sum::Multi(Multi {
+            bitarray: Some(CompactBitArray {
+                extra_bits_stored: (members % 8) as u32,
+                elems,
+            }),
+            mode_infos: vec![single; signed_by.len()],
+        })),
+    }
+}
+
+fn parse_key(input: &str) -> SecretKey {
+    match hex_str_to_bytes(input)
+        .ok()
+        .and_then(|b| SecretKey::from_slice(&b).ok())
+    {
+        Some(k) => k,
+        None => {
+            error!("Invalid private key, provide it as 32 bytes of hex");
+            exit(1);
+        }
+    }
+}
+
+fn parse_pubkey(input: &str) -> [u8; 33] {
+    match hex_str_to_bytes(input)
+        .ok()
+        .and_then(|b| PublicKey::from_slice(&b).ok())
+    {
+        Some(k) => k.serialize(),
+        None => {
+            error!("Invalid public key {}", input);
+            exit(1);
+        }
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> T {
+    match fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+    {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to read {} {}", path.display(), e);
+            exit(1);
+        }
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) {
+    if let Err(e) = fs::write(path, serde_json::to_string_pretty(value).unwrap()) {
+        error!("Failed to write to {} {:?}", path.display(), e);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multisig_signatures() {
+        let secp = Secp256k1::new();
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secp, &key).serialize();
+        let doc = amino_sign_doc(
+            "gravity-bridge-3",
+            5,
+            2,
+            &Coin {
+                amount: 100u32.into(),
+                denom: "ugraviton".to_string(),
+            },
+            300_000,
+            MEMO,
+            json!({"type": "gravity/MsgCancelSendToEth", "value": {"sender": "x", "transaction_id": "1"}}),
+        );
+        assert!(doc.starts_with(r#"{"account_number":"5","chain_id":"gravity-bridge-3","fee":{"amount":[{"amount":"100","denom":"ugraviton"}],"gas":"300000"}"#));
+        let signature = sign(&secp, &key, doc.as_bytes());
+        assert_eq!(signature.len(), 64);
+        assert!(verify(&secp, &public_key, doc.as_bytes(), &signature));
+        assert!(!verify(&secp, &public_key, b"something else", &signature));
+
+        match multi_mode_info(10, &[0, 9]).sum {
+            Some(Sum::Multi(m)) => {
+                let bits = m.bitarray.unwrap();
+                assert_eq!(bits.extra_bits_stored, 2);
+                assert_eq!(bits.elems, vec![0b1000_0000, 0b0100_0000]);
+                assert_eq!(m.mode_infos.len(), 2);
+            }
+            _ => panic!("Expected a multi mode info"),
+        }
+    }
+}
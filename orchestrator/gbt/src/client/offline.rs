@@ -0,0 +1,365 @@
+//! Offline signing, a transaction is built and signed on a machine with no network access and
+//! carried to an online machine as a file to be broadcast
+
+use crate::address_book::eth_address_or_exit;
+use crate::args::{BroadcastOpts, SignOpts, SignTxSubcommand};
+use crate::utils::TIMEOUT;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use cosmos_gravity::proposals::{
+    airdrop_proposal_content, gov_proposal_msg, ibc_metadata_proposal_content,
+    pause_bridge_proposal_content, unhalt_bridge_proposal_content, AirdropProposalJsonUnparsed,
+    IbcMetadataProposalJson, PauseBridgeProposalJson, UnhaltBridgeProposalJson,
+};
+use cosmos_gravity::send::{
+    build_send_to_eth_msg, cancel_send_to_eth_msg, get_memo, MSG_CANCEL_SEND_TO_ETH_TYPE_URL,
+    MSG_SEND_TO_ETH_TYPE_URL,
+};
+use deep_space::client::type_urls::MSG_SUBMIT_PROPOSAL_TYPE_URL;
+use deep_space::{Address as CosmosAddress, Coin, Fee, MessageArgs, Msg, PrivateKey};
+use gravity_proto::cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use gravity_proto::cosmos_sdk_proto::cosmos::gov::v1beta1::MsgSubmitProposal;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    AuthInfo, BroadcastMode, TxBody, TxRaw,
+};
+use gravity_proto::gravity::{MsgCancelSendToEth, MsgSendToEth};
+use gravity_utils::connection_prep::create_rpc_connections;
+use prost::Message;
+use prost_types::Any;
+use serde::de::DeserializeOwned;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+/// The portable signed transaction file written by `gbt client sign`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedTx {
+    pub chain_id: String,
+    pub signer: String,
+    pub account_number: u64,
+    pub sequence: u64,
+    /// a human readable summary of the transaction, so it can be checked before broadcasting
+    pub description: String,
+    /// the hex encoded TxRaw
+    pub tx_bytes: String,
+}
+
+pub fn sign_tx(args: SignOpts, address_prefix: String, address_book: &BTreeMap<String, String>) {
+    let key = args.cosmos_phrase;
+    let signer = key.to_address(&address_prefix).unwrap();
+    let (msg, description) = build_msg(args.tx, signer, address_book);
+
+    let message_args = MessageArgs {
+        sequence: args.sequence,
+        account_number: args.account_number,
+        chain_id: args.chain_id.clone(),
+        fee: Fee {
+            amount: vec![args.fee.clone()],
+            gas_limit: args.gas_limit,
+            granter: None,
+            payer: None,
+        },
+        timeout_height: args.timeout_height,
+    };
+    let tx_bytes = match key.sign_std_msg(&[msg], message_args, &get_memo()) {
+        Ok(b) => b,
+        Err(e) => {
+            error!("Failed to sign the transaction {:?}", e);
+            exit(1);
+        }
+    };
+    let signed = SignedTx {
+        chain_id: args.chain_id,
+        signer: signer.to_string(),
+        account_number: args.account_number,
+        sequence: args.sequence,
+        description: format!("{} paying {}", description, args.fee),
+        tx_bytes: bytes_to_hex_str(&tx_bytes),
+    };
+    let json = serde_json::to_string_pretty(&signed).unwrap();
+    if let Err(e) = fs::write(&args.output, json) {
+        error!("Failed to write to {} {:?}", args.output.display(), e);
+        exit(1);
+    }
+    info!("Signed: {}", signed.description);
+    info!(
+        "Wrote the signed transaction to {}, broadcast it with `gbt client broadcast`",
+        args.output.display()
+    );
+}
+
+/// Builds the message to sign and a summary of it, no checks against the chain can be made offline
+fn build_msg(
+    tx: SignTxSubcommand,
+    signer: CosmosAddress,
+    address_book: &BTreeMap<String, String>,
+) -> (Msg, String) {
+    match tx {
+        SignTxSubcommand::SendToEth(opts) => {
+            let eth_dest = eth_address_or_exit(
+                &opts.eth_destination,
+                address_book,
+                opts.allow_unchecksummed,
+            );
+            if opts.amount.denom != opts.bridge_fee.denom
+                || opts.amount.denom != opts.chain_fee.denom
+            {
+                error!("The bridge fee and chain fee must be in the same token as the amount");
+                exit(1);
+            }
+            let description = format!(
+                "SendToEth of {} from {} to {} with bridge fee {} and chain fee {}",
+                opts.amount, signer, eth_dest, opts.bridge_fee, opts.chain_fee
+            );
+            let msg = build_send_to_eth_msg(
+                signer,
+                eth_dest,
+                opts.amount,
+                opts.bridge_fee,
+                opts.chain_fee,
+            );
+            (msg, description)
+        }
+        SignTxSubcommand::CancelSendToEth(opts) => (
+            cancel_send_to_eth_msg(signer, opts.transaction_id),
+            format!("CancelSendToEth of {} by {}", opts.transaction_id, signer),
+        ),
+        SignTxSubcommand::IbcMetadataProposal(opts) => {
+            let proposal: IbcMetadataProposalJson = read_proposal(&opts.json);
+            let content = ibc_metadata_proposal_content(proposal.into());
+            let description = proposal_description("IBC metadata", signer, &opts.deposit);
+            (gov_proposal_msg(content, opts.deposit, signer), description)
+        }
+        SignTxSubcommand::AirdropProposal(opts) => {
+            let proposal: AirdropProposalJsonUnparsed = read_proposal(&opts.json);
+            let proposal = match proposal.try_into() {
+                Ok(p) => p,
+                Err(e) => {
+                    error!("Invalid address in proposal.json {:?}", e);
+                    exit(1);
+                }
+            };
+            let content = airdrop_proposal_content(proposal);
+            let description = proposal_description("Airdrop", signer, &opts.deposit);
+            (gov_proposal_msg(content, opts.deposit, signer), description)
+        }
+        SignTxSubcommand::EmergencyBridgeHaltProposal(opts) => {
+            let proposal: PauseBridgeProposalJson = read_proposal(&opts.json);
+            let content = pause_bridge_proposal_content(proposal);
+            let description = proposal_description("Emergency bridge halt", signer, &opts.deposit);
+            (gov_proposal_msg(content, opts.deposit, signer), description)
+        }
+        SignTxSubcommand::OracleUnhaltProposal(opts) => {
+            let proposal: UnhaltBridgeProposalJson = read_proposal(&opts.json);
+            let content = unhalt_bridge_proposal_content(proposal.into());
+            let description = proposal_description("Oracle unhalt", signer, &opts.deposit);
+            (gov_proposal_msg(content, opts.deposit, signer), description)
+        }
+    }
+}
+
+fn proposal_description(kind: &str, signer: CosmosAddress, deposit: &Coin) -> String {
+    format!("{} proposal by {} with deposit {}", kind, signer, deposit)
+}
+
+fn read_proposal<T: DeserializeOwned>(path: &Path) -> T {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            error!(
+                "Failed to read your proposal.json check the file path! {:?}",
+                e
+            );
+            exit(1);
+        }
+    };
+    match serde_json::from_str(&contents) {
+        Ok(p) => p,
+        Err(e) => {
+            error!(
+                "Failed to deserialize your proposal.json, check the contents! {:?}",
+                e
+            );
+            exit(1);
+        }
+    }
+}
+
+pub async fn broadcast_tx(args: BroadcastOpts, address_prefix: String) {
+    let signed: SignedTx = match fs::read_to_string(&args.input)
+        .map_err(|e| e.to_string())
+        .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+    {
+        Ok(s) => s,
+        Err(e) => {
+            error!(
+                "Failed to read a signed transaction from {} {}",
+                args.input.display(),
+                e
+            );
+            exit(1);
+        }
+    };
+    let tx_bytes = match hex_str_to_bytes(&signed.tx_bytes) {
+        Ok(b) => b,
+        Err(e) => {
+            error!("The signed transaction is corrupted {:?}", e);
+            exit(1);
+        }
+    };
+    let signer: CosmosAddress = match signed.signer.parse() {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Invalid signer {} {:?}", signed.signer, e);
+            exit(1);
+        }
+    };
+    // the description is not covered by the signature, what is broadcast is what was signed
+    let contents = match describe_signed_tx(&tx_bytes) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("The signed transaction is corrupted {:?}", e);
+            exit(1);
+        }
+    };
+
+    let connections =
+        create_rpc_connections(address_prefix, Some(args.cosmos_grpc), None, TIMEOUT).await;
+    let contact = connections.contact.unwrap();
+
+    // a transaction signed with the wrong chain id or a stale sequence is rejected, checking first
+    // gives a useful error instead of a signature verification failure
+    let current = match contact
+        .get_message_args(
+            signer,
+            Fee {
+                amount: vec![],
+                gas_limit: 0,
+                granter: None,
+                payer: None,
+            },
+        )
+        .await
+    {
+        Ok(a) => a,
+        Err(e) => {
+            error!("Failed to get the account of {} {:?}", signer, e);
+            exit(1);
+        }
+    };
+    if current.chain_id != signed.chain_id {
+        error!(
+            "The transaction was signed for chain {} but this node is on {}",
+            signed.chain_id, current.chain_id
+        );
+        exit(1);
+    }
+    if current.account_number != signed.account_number {
+        error!(
+            "The transaction was signed with account number {} but {} has account number {}",
+            signed.account_number, signer, current.account_number
+        );
+        exit(1);
+    }
+    if current.sequence > signed.sequence {
+        error!(
+            "The transaction was signed with sequence {} but {} is already at sequence {}, it has been broadcast already or another transaction used the sequence",
+            signed.sequence, signer, current.sequence
+        );
+        exit(1);
+    } else if current.sequence < signed.sequence {
+        error!(
+            "The transaction was signed with sequence {} but {} is at sequence {}, broadcast the transactions signed before it first",
+            signed.sequence, signer, current.sequence
+        );
+        exit(1);
+    }
+
+    info!("Broadcasting a transaction signed by {} containing", signer);
+    for line in contents {
+        info!("  {}", line);
+    }
+    let res = match contact
+        .send_transaction(tx_bytes, BroadcastMode::Sync)
+        .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Failed to broadcast the transaction {:?}", e);
+            exit(1);
+        }
+    };
+    match contact.wait_for_tx(res, TIMEOUT).await {
+        Ok(r) => info!("Transaction included with txid {}", r.txhash),
+        Err(e) => {
+            error!("The transaction was not included {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Decodes the messages and fee of a signed TxRaw into readable lines
+fn describe_signed_tx(tx_bytes: &[u8]) -> Result<Vec<String>, prost::DecodeError> {
+    let tx = TxRaw::decode(tx_bytes)?;
+    let body = TxBody::decode(tx.body_bytes.as_slice())?;
+    let auth_info = AuthInfo::decode(tx.auth_info_bytes.as_slice())?;
+    let mut lines = Vec::new();
+    for msg in body.messages {
+        lines.push(describe_msg(&msg)?);
+    }
+    if !body.memo.is_empty() {
+        lines.push(format!("Memo {}", body.memo));
+    }
+    if let Some(fee) = auth_info.fee {
+        let amount: Vec<String> = fee.amount.iter().map(proto_coin).collect();
+        lines.push(format!(
+            "Fee {} with gas limit {}",
+            amount.join(", "),
+            fee.gas_limit
+        ));
+    }
+    Ok(lines)
+}
+
+fn describe_msg(msg: &Any) -> Result<String, prost::DecodeError> {
+    Ok(match msg.type_url.as_str() {
+        MSG_SEND_TO_ETH_TYPE_URL => {
+            let msg = MsgSendToEth::decode(msg.value.as_slice())?;
+            format!(
+                "SendToEth of {} from {} to {} with bridge fee {} and chain fee {}",
+                optional_proto_coin(&msg.amount),
+                msg.sender,
+                msg.eth_dest,
+                optional_proto_coin(&msg.bridge_fee),
+                optional_proto_coin(&msg.chain_fee)
+            )
+        }
+        MSG_CANCEL_SEND_TO_ETH_TYPE_URL => {
+            let msg = MsgCancelSendToEth::decode(msg.value.as_slice())?;
+            format!(
+                "CancelSendToEth of {} by {}",
+                msg.transaction_id, msg.sender
+            )
+        }
+        MSG_SUBMIT_PROPOSAL_TYPE_URL => {
+            let msg = MsgSubmitProposal::decode(msg.value.as_slice())?;
+            let deposit: Vec<String> = msg.initial_deposit.iter().map(proto_coin).collect();
+            format!(
+                "Proposal {} by {} with deposit {}",
+                msg.content.map(|c| c.type_url).unwrap_or_default(),
+                msg.proposer,
+                deposit.join(", ")
+            )
+        }
+        other => format!("{} message", other),
+    })
+}
+
+fn proto_coin(coin: &ProtoCoin) -> String {
+    format!("{}{}", coin.amount, coin.denom)
+}
+
+fn optional_proto_coin(coin: &Option<ProtoCoin>) -> String {
+    coin.as_ref().map(proto_coin).unwrap_or_default()
+}
@@ -0,0 +1,285 @@
+use crate::args::AccidentalTransfersQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::abi::derive_signature;
+use clarity::constants::zero_address;
+use clarity::utils::bytes_to_hex_str;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::get_gravity_params;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::{ERC20_TRANSFER_EVENT_SIG, SENT_TO_COSMOS_EVENT_SIG};
+use gravity_utils::types::{EthereumEvent, SendToCosmosEvent};
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use std::collections::HashSet;
+use std::process::exit;
+use web30::client::Web3;
+use web30::types::Log;
+
+/// An ERC20 transfer directly into the Gravity contract which did not go through sendToCosmos
+/// and is therefore not credited to anyone on Cosmos
+#[derive(Debug, Clone)]
+struct AccidentalTransfer {
+    erc20: EthAddress,
+    from: EthAddress,
+    amount: Uint256,
+    block: Uint256,
+    tx_hash: String,
+}
+
+/// Scans the Gravity contract history for funds that entered the contract without being credited
+/// to a Cosmos address. These are plain ERC20 transfers into the contract and deposits with a
+/// destination that is not valid bech32, both are only recoverable through governance
+pub async fn query_accidental_transfers(
+    args: AccidentalTransfersQueryOpts,
+    address_prefix: String,
+) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let end_block = match args.end_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    let start_block: Uint256 = args.start_block.into();
+
+    let mut accidental = Vec::new();
+    let mut malformed = Vec::new();
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block > BLOCKS_TO_SEARCH.into() {
+            current_block + BLOCKS_TO_SEARCH.into()
+        } else {
+            end_block
+        };
+        info!("Searching blocks {} to {}", current_block, end_search);
+        match scan_range(&web3, gravity_contract_address, current_block, end_search).await {
+            Ok((a, m)) => {
+                accidental.extend(a);
+                malformed.extend(m);
+            }
+            Err(e) => {
+                error!(
+                    "Failed to search blocks {} to {}, is your Ethereum node working? {:?}",
+                    current_block, end_search, e
+                );
+                exit(1);
+            }
+        }
+        current_block = end_search + 1u8.into();
+    }
+
+    if accidental.is_empty() && malformed.is_empty() {
+        info!(
+            "No accidental transfers or malformed deposits found between blocks {} and {}",
+            start_block, end_block
+        );
+        return;
+    }
+    for t in accidental.iter() {
+        warn!(
+            "Accidental transfer of {} {} from {} in block {} tx {}",
+            t.amount, t.erc20, t.from, t.block, t.tx_hash
+        );
+    }
+    for d in malformed.iter() {
+        let destination = if d.destination.len() < 1000 {
+            d.destination.clone()
+        } else {
+            "<too long to display>".to_string()
+        };
+        warn!(
+            "Deposit with event nonce {} of {} {} from {} in block {} has invalid destination {:?} and was sent to the community pool",
+            d.event_nonce, d.amount, d.erc20, d.sender, d.block_height, destination
+        );
+    }
+    warn!(
+        "Found {} accidental transfers and {} deposits with malformed destinations, these can only be recovered by governance",
+        accidental.len(),
+        malformed.len()
+    );
+}
+
+/// Searches a single block range for transfers of any ERC20 into the contract, less those made
+/// by deposits
+async fn scan_range(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    start: Uint256,
+    end: Uint256,
+) -> Result<(Vec<AccidentalTransfer>, Vec<SendToCosmosEvent>), GravityError> {
+    let deposit_logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![SENT_TO_COSMOS_EVENT_SIG],
+        )
+        .await?;
+    let deposits = SendToCosmosEvent::from_logs(&deposit_logs)?;
+    let malformed: Vec<SendToCosmosEvent> = deposits
+        .iter()
+        .filter(|d| d.validated_destination.is_none())
+        .cloned()
+        .collect();
+    let deposit_transfers: HashSet<(String, EthAddress)> =
+        deposit_logs.iter().filter_map(deposit_transfer).collect();
+
+    // no contract address, every token's transfers to the Gravity contract are returned
+    let transfer_logs = web3
+        .check_for_arbitrary_events(
+            start,
+            Some(end),
+            Vec::new(),
+            transfer_topics(None, Some(gravity_contract_address)),
+        )
+        .await?;
+    let transfers = transfer_logs
+        .iter()
+        .filter_map(|log| parse_transfer_to(log, gravity_contract_address))
+        .collect();
+    Ok((without_deposits(transfers, &deposit_transfers), malformed))
+}
+
+/// The transaction hash and token of a SendToCosmos log, sendToCosmos pulls the deposited token
+/// into the contract in the same transaction that emits the event
+fn deposit_transfer(log: &Log) -> Option<(String, EthAddress)> {
+    let token_data = log.topics.get(1)?;
+    if token_data.len() < 32 {
+        return None;
+    }
+    let tx_hash = log.transaction_hash.as_ref()?;
+    Some((
+        bytes_to_hex_str(tx_hash),
+        EthAddress::from_slice(&token_data[12..32]).ok()?,
+    ))
+}
+
+/// Drops the transfers made by deposits, those moving a deposit's token in the deposit's
+/// transaction, and mints to the contract, which happen when deploying Cosmos originated ERC20s
+fn without_deposits(
+    transfers: Vec<AccidentalTransfer>,
+    deposit_transfers: &HashSet<(String, EthAddress)>,
+) -> Vec<AccidentalTransfer> {
+    transfers
+        .into_iter()
+        .filter(|t| {
+            !deposit_transfers.contains(&(t.tx_hash.clone(), t.erc20)) && t.from != zero_address()
+        })
+        .collect()
+}
+
+/// Log filter topics for ERC20 transfers from `from` to `to`, either may be any address. Filtering
+/// on the node keeps it from returning every transfer of popular tokens over the scanned range
+pub(crate) fn transfer_topics(
+    from: Option<EthAddress>,
+    to: Option<EthAddress>,
+) -> Vec<Vec<[u8; 32]>> {
+    let address_topic = |address: Option<EthAddress>| match address {
+        Some(a) => {
+            let mut topic = [0u8; 32];
+            topic[12..].copy_from_slice(a.as_bytes());
+            vec![topic]
+        }
+        // an empty list matches any value
+        None => Vec::new(),
+    };
+    vec![
+        vec![derive_signature(ERC20_TRANSFER_EVENT_SIG).unwrap()],
+        address_topic(from),
+        address_topic(to),
+    ]
+}
+
+/// Parses an ERC20 Transfer log, returning it only if it is a transfer to `to`
+fn parse_transfer_to(log: &Log, to: EthAddress) -> Option<AccidentalTransfer> {
+    let (from_data, to_data) = (log.topics.get(1)?, log.topics.get(2)?);
+    if from_data.len() < 32 || to_data.len() < 32 || log.data.len() < 32 {
+        return None;
+    }
+    let destination = EthAddress::from_slice(&to_data[12..32]).ok()?;
+    if destination != to {
+        return None;
+    }
+    Some(AccidentalTransfer {
+        erc20: log.address,
+        from: EthAddress::from_slice(&from_data[12..32]).ok()?,
+        amount: Uint256::from_be_bytes(&log.data[0..32]),
+        block: log.block_number.unwrap_or_default(),
+        tx_hash: log
+            .transaction_hash
+            .as_ref()
+            .map(|h| bytes_to_hex_str(h))
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(erc20: EthAddress, from: EthAddress, tx_hash: &str) -> AccidentalTransfer {
+        AccidentalTransfer {
+            erc20,
+            from,
+            amount: 100u8.into(),
+            block: 1u8.into(),
+            tx_hash: tx_hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_without_deposits() {
+        let token_a: EthAddress = "0xd7600ae27c99988a6cd360234062b540f88eca43"
+            .parse()
+            .unwrap();
+        let token_b: EthAddress = "0x7f2b3f8e2a2a1f4b5c5ce9a6d3c1b83de1eb2fa1"
+            .parse()
+            .unwrap();
+        let sender: EthAddress = "0x310d72afdb8ea0b2b21f2b7a4ed6d0bd0cb8f36e"
+            .parse()
+            .unwrap();
+        let deposits: HashSet<(String, EthAddress)> =
+            vec![("aa".to_string(), token_a)].into_iter().collect();
+
+        let transfers = vec![
+            // the deposit's own transfer
+            transfer(token_a, sender, "aa"),
+            // a different token moved in the deposit's transaction
+            transfer(token_b, sender, "aa"),
+            // the deposited token sent directly in another transaction
+            transfer(token_a, sender, "bb"),
+            // a token never deposited
+            transfer(token_b, sender, "cc"),
+            // minted when a Cosmos originated ERC20 is deployed
+            transfer(token_b, zero_address(), "dd"),
+        ];
+        let accidental = without_deposits(transfers, &deposits);
+        let found: Vec<(&str, EthAddress)> = accidental
+            .iter()
+            .map(|t| (t.tx_hash.as_str(), t.erc20))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("aa", token_b), ("bb", token_a), ("cc", token_b)]
+        );
+    }
+}
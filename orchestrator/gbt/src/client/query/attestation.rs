@@ -0,0 +1,203 @@
+use crate::args::AttestationQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::Uint256;
+use deep_space::client::types::LatestBlock;
+use deep_space::Contact;
+use gravity_proto::gravity::{
+    Attestation, MsgBatchSendToEthClaim, MsgErc20DeployedClaim, MsgLogicCallExecutedClaim,
+    MsgSendToCosmosClaim, MsgValsetUpdatedClaim, QueryAttestationsRequest,
+};
+use gravity_utils::connection_prep::create_rpc_connections;
+use prost::Message;
+use std::collections::HashMap;
+use std::process::exit;
+
+/// A bonded validator as far as voting on attestations is concerned
+struct Voter {
+    moniker: String,
+    tokens: Uint256,
+}
+
+/// Prints every attestation for the given event nonce, if validators disagree about the event
+/// there will be more than one and none of them will be observed
+pub async fn query_attestation(args: AttestationQueryOpts, address_prefix: String) {
+    let connections =
+        create_rpc_connections(address_prefix, Some(args.cosmos_grpc), None, TIMEOUT).await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let attestations = match grpc
+        .get_attestations(QueryAttestationsRequest {
+            limit: 0,
+            order_by: String::new(),
+            claim_type: String::new(),
+            nonce: args.nonce,
+            height: 0,
+            use_v1_key: false,
+        })
+        .await
+    {
+        Ok(a) => a.into_inner().attestations,
+        Err(e) => {
+            error!("Failed to query attestations {:?}", e);
+            exit(1);
+        }
+    };
+    if attestations.is_empty() {
+        info!(
+            "No attestation for event nonce {}, either no validator has claimed it yet or it has been pruned",
+            args.nonce
+        );
+        return;
+    }
+    if attestations.len() > 1 {
+        warn!(
+            "Validators disagree about event nonce {}, there are {} conflicting attestations",
+            args.nonce,
+            attestations.len()
+        );
+    }
+
+    let validators = match contact.get_active_validators().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the active validator set {:?}", e);
+            exit(1);
+        }
+    };
+    let mut voters = HashMap::new();
+    let mut total_tokens: Uint256 = 0u8.into();
+    for v in validators {
+        let tokens: Uint256 = v.tokens.parse().unwrap_or_default();
+        total_tokens += tokens;
+        let moniker = v.description.map(|d| d.moniker).unwrap_or_default();
+        voters.insert(v.operator_address, Voter { moniker, tokens });
+    }
+    let to_percent = |tokens: Uint256| {
+        if total_tokens == 0u8.into() {
+            return 0.0;
+        }
+        // basis points keep the division in integers, precise enough for display
+        let bp: Uint256 = tokens * 10_000u32.into() / total_tokens;
+        bp.to_string().parse::<f64>().unwrap_or(0.0) / 100.0
+    };
+
+    let latest = get_latest_block(&contact).await;
+    for (i, attestation) in attestations.iter().enumerate() {
+        info!("Attestation {} of {}", i + 1, attestations.len());
+        print_claim(attestation);
+        if attestation.observed {
+            info!("    Observed, this event has been applied on Gravity");
+        } else {
+            info!("    Not yet observed");
+        }
+        print_age(&contact, attestation.height, latest).await;
+
+        let mut voted_tokens: Uint256 = 0u8.into();
+        info!("    {} votes:", attestation.votes.len());
+        for vote in attestation.votes.iter() {
+            match voters.get(vote) {
+                Some(v) => {
+                    voted_tokens += v.tokens;
+                    info!(
+                        "        {} {} {:.2}%",
+                        v.moniker,
+                        vote,
+                        to_percent(v.tokens)
+                    );
+                }
+                None => info!("        {} (no longer bonded)", vote),
+            }
+        }
+        info!(
+            "    {:.2}% of bonded power has voted for this claim",
+            to_percent(voted_tokens)
+        );
+    }
+
+    // a validator voting for any version of the event has done its job, the ones missing from
+    // every attestation are the ones to chase when the oracle is stuck
+    let missing: Vec<(&String, &Voter)> = voters
+        .iter()
+        .filter(|(address, _)| !attestations.iter().any(|a| a.votes.contains(address)))
+        .collect();
+    if !missing.is_empty() {
+        let mut missing_tokens: Uint256 = 0u8.into();
+        info!("{} bonded validators have not voted:", missing.len());
+        for (address, v) in missing.iter() {
+            missing_tokens += v.tokens;
+            info!("    {} {} {:.2}%", v.moniker, address, to_percent(v.tokens));
+        }
+        info!(
+            "{:.2}% of bonded power has not voted",
+            to_percent(missing_tokens)
+        );
+    }
+}
+
+/// Decodes and prints the claim inside the attestation
+fn print_claim(attestation: &Attestation) {
+    let claim = match &attestation.claim {
+        Some(c) => c,
+        None => {
+            warn!("    Attestation has no claim");
+            return;
+        }
+    };
+    let value = claim.value.as_slice();
+    let decoded = if claim.type_url.ends_with("MsgSendToCosmosClaim") {
+        MsgSendToCosmosClaim::decode(value).map(|c| format!("{:?}", c))
+    } else if claim.type_url.ends_with("MsgBatchSendToEthClaim") {
+        MsgBatchSendToEthClaim::decode(value).map(|c| format!("{:?}", c))
+    } else if claim.type_url.ends_with("MsgERC20DeployedClaim") {
+        MsgErc20DeployedClaim::decode(value).map(|c| format!("{:?}", c))
+    } else if claim.type_url.ends_with("MsgLogicCallExecutedClaim") {
+        MsgLogicCallExecutedClaim::decode(value).map(|c| format!("{:?}", c))
+    } else if claim.type_url.ends_with("MsgValsetUpdatedClaim") {
+        MsgValsetUpdatedClaim::decode(value).map(|c| format!("{:?}", c))
+    } else {
+        info!("    Unknown claim type {}", claim.type_url);
+        return;
+    };
+    match decoded {
+        // the orchestrator field is whoever submitted first, it is not meaningful here
+        Ok(d) => info!("    {} {}", claim.type_url, d),
+        Err(e) => warn!("    Failed to decode {} {:?}", claim.type_url, e),
+    }
+}
+
+async fn get_latest_block(contact: &Contact) -> Option<(u64, i64)> {
+    match contact.get_latest_block().await {
+        Ok(LatestBlock::Latest { block }) => {
+            let header = block.header?;
+            Some((header.height as u64, header.time?.seconds))
+        }
+        _ => None,
+    }
+}
+
+/// Prints how long ago the attestation was created, in blocks and, if the node still has the
+/// block, in time
+async fn print_age(contact: &Contact, height: u64, latest: Option<(u64, i64)>) {
+    let (latest_height, latest_time) = match latest {
+        Some(l) => l,
+        None => {
+            info!("    Created at block {}", height);
+            return;
+        }
+    };
+    let blocks = latest_height.saturating_sub(height);
+    let created = match contact.get_block(height).await {
+        Ok(Some(block)) => block.header.and_then(|h| h.time).map(|t| t.seconds),
+        _ => None,
+    };
+    match created {
+        Some(created) => info!(
+            "    Created at block {}, {} blocks and {} minutes ago",
+            height,
+            blocks,
+            (latest_time - created) / 60
+        ),
+        None => info!("    Created at block {}, {} blocks ago", height, blocks),
+    }
+}
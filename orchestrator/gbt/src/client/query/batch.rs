@@ -0,0 +1,325 @@
+use crate::args::BatchQueryOpts;
+use crate::client::spot_relay::user_token_name_to_gravity_token;
+use crate::utils::{display_denom, TIMEOUT};
+use clarity::constants::zero_address;
+use clarity::utils::bytes_to_hex_str;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{
+    get_attestations, get_current_valset, get_denom_to_erc20, get_gravity_params,
+    get_transaction_batch, get_transaction_batch_signatures,
+};
+use ethereum_gravity::utils::get_tx_batch_nonce;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::MsgBatchSendToEthClaim;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::TRANSACTION_BATCH_EXECUTED_EVENT_SIG;
+use gravity_utils::types::{
+    EthereumEvent, TransactionBatch, TransactionBatchExecutedEvent, GRAVITY_POWER_TO_PASS,
+    TOTAL_GRAVITY_POWER,
+};
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use prost::Message;
+use std::process::exit;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// Shows everything known about a single batch, from creation on Gravity through signing and
+/// relaying to its execution or timeout on Ethereum
+pub async fn query_batch(args: BatchQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let denom = match user_token_name_to_gravity_token(args.token.clone(), &mut grpc).await {
+        Some(d) => d,
+        None => {
+            error!("Failed to decode your intended token name {}", args.token);
+            exit(1);
+        }
+    };
+    let erc20: EthAddress = match get_denom_to_erc20(&mut grpc, denom.clone()).await {
+        Ok(v) => v.erc20.parse().unwrap(),
+        Err(e) => {
+            error!(
+                "Could not find the ERC20 for {}, is it bridged? {:?}",
+                denom, e
+            );
+            exit(1);
+        }
+    };
+    let token_name = display_denom(&contact, &denom).await;
+    let nonce = args.nonce;
+
+    let latest_block = web3.eth_block_number().await.unwrap();
+    let last_executed_nonce =
+        get_tx_batch_nonce(gravity_contract_address, erc20, zero_address(), &web3)
+            .await
+            .expect("Failed to get the last executed batch nonce from Ethereum");
+
+    let batch = match get_transaction_batch(&mut grpc, nonce, erc20).await {
+        Ok(b) => b,
+        Err(e) => {
+            error!("Failed to query batch {} of {} {:?}", nonce, token_name, e);
+            exit(1);
+        }
+    };
+    let batch = match batch {
+        Some(b) => b,
+        None => {
+            if last_executed_nonce >= nonce {
+                info!(
+                    "Batch {} of {} is no longer on Gravity, it has been executed on Ethereum",
+                    nonce, token_name
+                );
+                print_relay(
+                    &mut grpc,
+                    &web3,
+                    gravity_contract_address,
+                    erc20,
+                    nonce,
+                    latest_block,
+                    args.search_blocks,
+                )
+                .await;
+            } else {
+                info!(
+                    "Batch {} of {} was not found on Gravity and has not been executed on Ethereum, it has either timed out, been cancelled or never existed",
+                    nonce, token_name
+                );
+                info!(
+                    "The last batch of {} executed on Ethereum is {}",
+                    token_name, last_executed_nonce
+                );
+            }
+            return;
+        }
+    };
+
+    print_contents(&batch, &token_name);
+    print_confirmations(&mut grpc, &batch).await;
+
+    if last_executed_nonce == nonce {
+        info!("Batch has been executed on Ethereum, waiting for the oracle to observe it");
+        print_relay(
+            &mut grpc,
+            &web3,
+            gravity_contract_address,
+            erc20,
+            nonce,
+            latest_block,
+            args.search_blocks,
+        )
+        .await;
+    } else if last_executed_nonce > nonce {
+        info!(
+            "Batch {} of {} has been executed on Ethereum, this batch can no longer be executed and will be cancelled",
+            last_executed_nonce, token_name
+        );
+    } else {
+        info!("Batch has not been relayed to Ethereum");
+    }
+
+    let timeout: Uint256 = batch.batch_timeout.into();
+    if latest_block >= timeout {
+        info!(
+            "Batch timed out at Ethereum block {}, the current block is {}",
+            timeout, latest_block
+        );
+    } else {
+        info!(
+            "Batch times out at Ethereum block {}, {} blocks from now",
+            timeout,
+            timeout - latest_block
+        );
+    }
+}
+
+fn print_contents(batch: &TransactionBatch, token_name: &str) {
+    info!(
+        "Batch {} of {} with {} transactions, total fees {}",
+        batch.nonce,
+        token_name,
+        batch.transactions.len(),
+        batch.total_fee.amount
+    );
+    for tx in batch.transactions.iter() {
+        info!(
+            "    Transfer {}: {} from {} to {} with a fee of {}",
+            tx.id, tx.erc20_token.amount, tx.sender, tx.destination, tx.erc20_fee.amount
+        );
+    }
+}
+
+/// Prints the confirmations gathered so far, weighted by the power of the current validator set
+async fn print_confirmations(grpc: &mut GravityQueryClient<Channel>, batch: &TransactionBatch) {
+    let confirms =
+        match get_transaction_batch_signatures(grpc, batch.nonce, batch.token_contract).await {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("Failed to get batch confirmations {:?}", e);
+                return;
+            }
+        };
+    let valset = match get_current_valset(grpc).await {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Failed to get the current validator set {:?}", e);
+            return;
+        }
+    };
+    let signed_power: u64 = valset
+        .members
+        .iter()
+        .filter(|m| confirms.iter().any(|c| c.ethereum_signer == m.eth_address))
+        .map(|m| m.power)
+        .sum();
+    let to_percent = |power: u64| power as f64 / TOTAL_GRAVITY_POWER as f64 * 100.0;
+    info!(
+        "{} of {} validators have confirmed, {:.2}% of power out of the {:.2}% required",
+        confirms.len(),
+        valset.members.len(),
+        to_percent(signed_power),
+        to_percent(GRAVITY_POWER_TO_PASS)
+    );
+    if signed_power > GRAVITY_POWER_TO_PASS {
+        info!("Batch has enough confirmations to be relayed");
+    }
+}
+
+/// Finds and prints the Ethereum transaction that executed the batch, using the block recorded in
+/// the oracle attestation if the chain still has it and searching recent blocks otherwise
+async fn print_relay(
+    grpc: &mut GravityQueryClient<Channel>,
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    erc20: EthAddress,
+    nonce: u64,
+    latest_block: Uint256,
+    search_blocks: u64,
+) {
+    let (start, end) = match get_claimed_block(grpc, erc20, nonce).await {
+        Some(block) => (block, block),
+        None => {
+            let search: Uint256 = search_blocks.into();
+            let start = if latest_block > search {
+                latest_block - search
+            } else {
+                0u8.into()
+            };
+            (start, latest_block)
+        }
+    };
+    // search backwards, the batch was most likely executed recently
+    let mut end_search = end;
+    loop {
+        let start_search = if end_search - start > BLOCKS_TO_SEARCH.into() {
+            end_search - BLOCKS_TO_SEARCH.into()
+        } else {
+            start
+        };
+        match find_execution(
+            web3,
+            gravity_contract_address,
+            erc20,
+            nonce,
+            start_search,
+            end_search,
+        )
+        .await
+        {
+            Ok(Some((block, tx_hash))) => {
+                info!("Relayed in Ethereum block {} by tx {}", block, tx_hash);
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!(
+                    "Failed to search blocks {} to {} {:?}",
+                    start_search, end_search, e
+                );
+                return;
+            }
+        }
+        if start_search == start {
+            break;
+        }
+        end_search = start_search - 1u8.into();
+    }
+    info!(
+        "The relaying transaction was not found between blocks {} and {}, try a larger --search-blocks",
+        start, end
+    );
+}
+
+/// The Ethereum block the oracle recorded the execution of this batch at, if the attestation is
+/// still stored on chain
+async fn get_claimed_block(
+    grpc: &mut GravityQueryClient<Channel>,
+    erc20: EthAddress,
+    nonce: u64,
+) -> Option<Uint256> {
+    let attestations = get_attestations(grpc, None).await.ok()?;
+    for att in attestations {
+        let claim = match att.claim {
+            Some(c) if c.type_url.contains("MsgBatchSendToEthClaim") => c,
+            _ => continue,
+        };
+        if let Ok(claim) = MsgBatchSendToEthClaim::decode(claim.value.as_slice()) {
+            let token = claim.token_contract.parse::<EthAddress>().ok();
+            if claim.batch_nonce == nonce && token == Some(erc20) {
+                return Some(claim.eth_block_height.into());
+            }
+        }
+    }
+    None
+}
+
+async fn find_execution(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    erc20: EthAddress,
+    nonce: u64,
+    start: Uint256,
+    end: Uint256,
+) -> Result<Option<(Uint256, String)>, GravityError> {
+    let logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![TRANSACTION_BATCH_EXECUTED_EVENT_SIG],
+        )
+        .await?;
+    for log in logs {
+        let event = TransactionBatchExecutedEvent::from_log(&log)?;
+        if event.batch_nonce == nonce && event.erc20 == erc20 {
+            let tx_hash = log
+                .transaction_hash
+                .as_ref()
+                .map(|h| format!("0x{}", bytes_to_hex_str(h)))
+                .unwrap_or_default();
+            return Ok(Some((event.block_height, tx_hash)));
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,134 @@
+use crate::args::BatchFeesQueryOpts;
+use crate::utils::{display_denom, TIMEOUT};
+use clarity::constants::zero_address;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_erc20_to_denom, get_pending_batch_fees};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::num_conversion::{print_eth, print_gwei};
+use gravity_utils::prices::get_weth_price_with_retries;
+use relayer::batch_relaying::get_cost_with_margin;
+use relayer::request_batches::BATCH_GAS;
+use std::process::exit;
+
+/// Displays the fees waiting to be batched for each token, along with how they compare to the
+/// current cost of relaying a batch on Ethereum
+pub async fn query_batch_fees(args: BatchFeesQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let batch_fees = match get_pending_batch_fees(&mut grpc).await {
+        Ok(v) => v.batch_fees,
+        Err(e) => {
+            error!("Failed to get pending batch fees {:?}", e);
+            exit(1);
+        }
+    };
+    if batch_fees.is_empty() {
+        info!("There are no transactions waiting to be batched");
+        return;
+    }
+
+    let gas_price = match web3.eth_gas_price().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the Ethereum gas price {:?}", e);
+            exit(1);
+        }
+    };
+    let batch_cost: Uint256 = gas_price * BATCH_GAS.into();
+    let cost_with_margin = get_cost_with_margin(batch_cost, args.margin);
+    info!(
+        "A batch is estimated to cost {} Gas @ {} gwei / {} ETH, with a {} margin relayers want {} ETH",
+        BATCH_GAS,
+        print_gwei(gas_price),
+        print_eth(batch_cost),
+        args.margin,
+        print_eth(cost_with_margin)
+    );
+
+    // the address used to simulate Uniswap swaps, this does not need to hold any funds
+    let caller = zero_address();
+    for fee in batch_fees {
+        let token: EthAddress = match fee.token.parse() {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Chain returned invalid token {} {:?}", fee.token, e);
+                continue;
+            }
+        };
+        let total_fees: Uint256 = match fee.total_fees.parse() {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("Chain returned invalid fee {} {:?}", fee.total_fees, e);
+                continue;
+            }
+        };
+        let name = match get_erc20_to_denom(&mut grpc, token).await {
+            Ok(d) => display_denom(&contact, &d.denom).await,
+            Err(_) => token.to_string(),
+        };
+
+        info!(
+            "{}: {} pending sends with {} in fees",
+            name, fee.tx_count, total_fees
+        );
+        match get_weth_price_with_retries(caller, token, total_fees, &web3).await {
+            Ok(value) => {
+                info!("    Fees are worth {} ETH", print_eth(value));
+                if value > cost_with_margin {
+                    info!("    A batch is currently profitable to relay");
+                } else {
+                    match get_marginal_fee(total_fees, value, cost_with_margin) {
+                        Some(needed) => info!(
+                            "    A new send would need a bridge_fee of about {} to make a batch profitable",
+                            needed
+                        ),
+                        None => info!(
+                            "    Fees have no value on Uniswap, a marginal bridge_fee can not be computed"
+                        ),
+                    }
+                }
+            }
+            Err(e) => info!(
+                "    Unable to price this token on Uniswap, relayers may not relay it {:?}",
+                e
+            ),
+        }
+    }
+}
+
+/// Computes how many additional tokens would need to be added to the existing fees for them to be
+/// worth `target`, assuming a linear price. Returns None if the fees have no value at all since
+/// there is no price to extrapolate from
+fn get_marginal_fee(total_fees: Uint256, value: Uint256, target: Uint256) -> Option<Uint256> {
+    if value == 0u8.into() || total_fees == 0u8.into() {
+        return None;
+    }
+    if value >= target {
+        return Some(0u8.into());
+    }
+    let missing = target - value;
+    // round up, it's better to overpay by one base unit than miss the target
+    let needed = (missing * total_fees + value - 1u8.into()) / value;
+    Some(needed)
+}
+
+#[test]
+fn test_get_marginal_fee() {
+    let fee = |total: u32, value: u32, target: u32| {
+        get_marginal_fee(total.into(), value.into(), target.into())
+    };
+    assert_eq!(fee(100, 10, 30), Some(200u32.into()));
+    assert_eq!(fee(100, 30, 10), Some(0u8.into()));
+    assert_eq!(fee(3, 2, 3), Some(2u8.into()));
+    assert_eq!(fee(100, 0, 30), None);
+    assert_eq!(fee(0, 0, 30), None);
+}
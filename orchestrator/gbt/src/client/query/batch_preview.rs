@@ -0,0 +1,126 @@
+use crate::args::BatchPreviewQueryOpts;
+use crate::client::spot_relay::user_token_name_to_gravity_token;
+use crate::utils::{display_denom, TIMEOUT};
+use clarity::constants::zero_address;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::batch_preview::{get_batch_preview, OUTGOING_TX_BATCH_SIZE};
+use cosmos_gravity::query::get_denom_to_erc20;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::num_conversion::{print_eth, print_gwei};
+use gravity_utils::prices::get_weth_price_with_retries;
+use relayer::batch_relaying::get_cost_with_margin;
+use relayer::request_batches::BATCH_GAS;
+use std::process::exit;
+
+/// Displays the batch the chain would create if one were requested for the token right now and
+/// whether relaying it would currently be profitable
+pub async fn query_batch_preview(args: BatchPreviewQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let denom = match user_token_name_to_gravity_token(args.token.clone(), &mut grpc).await {
+        Some(d) => d,
+        None => {
+            error!("Failed to decode your intended token name {}", args.token);
+            exit(1);
+        }
+    };
+    let erc20: EthAddress = match get_denom_to_erc20(&mut grpc, denom.clone()).await {
+        Ok(v) => v.erc20.parse().unwrap(),
+        Err(e) => {
+            error!(
+                "Could not find the ERC20 for {}, is it bridged? {:?}",
+                denom, e
+            );
+            exit(1);
+        }
+    };
+    let token_name = display_denom(&contact, &denom).await;
+
+    let preview = match get_batch_preview(&mut grpc, erc20).await {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to preview a batch for {} {:?}", token_name, e);
+            exit(1);
+        }
+    };
+
+    info!(
+        "A batch of {} requested now would contain {} of at most {} transactions paying {} in fees",
+        token_name,
+        preview.transactions.len(),
+        OUTGOING_TX_BATCH_SIZE,
+        preview.total_fees
+    );
+    for tx in preview.transactions.iter() {
+        info!(
+            "    id {} fee {} amount {} from {} to {}",
+            tx.id, tx.erc20_fee.amount, tx.erc20_token.amount, tx.sender, tx.destination
+        );
+    }
+    if preview.left_over > 0 {
+        info!(
+            "{} lower fee transactions would be left for a later batch",
+            preview.left_over
+        );
+    }
+    for tx in preview.blacklisted.iter() {
+        info!(
+            "Transaction {} to {} will never be batched, its destination is blacklisted",
+            tx.id, tx.destination
+        );
+    }
+    if let Some(last) = preview.last_batch_fees {
+        info!(
+            "The newest unexecuted batch of {} pays {} in fees",
+            token_name, last
+        );
+    }
+    match &preview.rejection {
+        Some(reason) => {
+            info!("The chain would reject a batch request now, {}", reason);
+            return;
+        }
+        None => info!("The chain would accept a batch request now"),
+    }
+
+    let gas_price = match web3.eth_gas_price().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the Ethereum gas price {:?}", e);
+            exit(1);
+        }
+    };
+    let batch_cost: Uint256 = gas_price * BATCH_GAS.into();
+    let cost_with_margin = get_cost_with_margin(batch_cost, args.margin);
+    info!(
+        "A batch is estimated to cost {} Gas @ {} gwei / {} ETH, with a {} margin relayers want {} ETH",
+        BATCH_GAS,
+        print_gwei(gas_price),
+        print_eth(batch_cost),
+        args.margin,
+        print_eth(cost_with_margin)
+    );
+    match get_weth_price_with_retries(zero_address(), erc20, preview.total_fees, &web3).await {
+        Ok(value) => {
+            info!("Fees are worth {} ETH", print_eth(value));
+            if value > cost_with_margin {
+                info!("This batch would be profitable to relay");
+            } else {
+                info!("This batch would not be profitable to relay");
+            }
+        }
+        Err(e) => info!(
+            "Unable to price this token on Uniswap, relayers may not relay it {:?}",
+            e
+        ),
+    }
+}
@@ -0,0 +1,222 @@
+use crate::args::DelegateKeysQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::{
+    get_all_valset_confirms, get_last_event_nonce_for_validator, get_latest_transaction_batches,
+    get_latest_valsets, get_transaction_batch_signatures,
+};
+use deep_space::Address as CosmosAddress;
+use gravity_proto::gravity::QueryDelegateKeysByValidatorAddress;
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::collections::{HashMap, HashSet};
+use std::process::exit;
+
+/// The delegate keys registered by an active validator, None if missing or unparsable
+struct DelegateKeys {
+    validator: String,
+    moniker: String,
+    eth_address: Option<EthAddress>,
+    orchestrator: Option<CosmosAddress>,
+}
+
+/// Checks the delegate keys of every active validator against recent activity, flagging keys that
+/// are missing, shared between validators, never sign or can not pay for relaying
+pub async fn query_delegate_keys(args: DelegateKeysQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let validators = match contact.get_active_validators().await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to get the active validator set {:?}", e);
+            exit(1);
+        }
+    };
+    let mut keys = Vec::new();
+    for v in validators {
+        let moniker = v.description.map(|d| d.moniker).unwrap_or_default();
+        let res = grpc
+            .get_delegate_key_by_validator(QueryDelegateKeysByValidatorAddress {
+                validator_address: v.operator_address.clone(),
+            })
+            .await;
+        let (eth_address, orchestrator) = match res {
+            Ok(r) => {
+                let r = r.into_inner();
+                (
+                    r.eth_address.parse().ok(),
+                    r.orchestrator_address.parse().ok(),
+                )
+            }
+            Err(e) => {
+                debug!("No delegate keys for {} {:?}", v.operator_address, e);
+                (None, None)
+            }
+        };
+        keys.push(DelegateKeys {
+            validator: v.operator_address,
+            moniker,
+            eth_address,
+            orchestrator,
+        });
+    }
+
+    // every Ethereum key that has signed one of the valsets or batches still stored on chain
+    let mut signers: HashSet<EthAddress> = HashSet::new();
+    match get_latest_valsets(&mut grpc).await {
+        Ok(valsets) => {
+            for valset in valsets {
+                match get_all_valset_confirms(&mut grpc, valset.nonce).await {
+                    Ok(confirms) => signers.extend(confirms.iter().map(|c| c.eth_address)),
+                    Err(e) => warn!("Failed to get confirms for valset {} {:?}", valset.nonce, e),
+                }
+            }
+        }
+        Err(e) => warn!("Failed to get the latest valsets {:?}", e),
+    }
+    match get_latest_transaction_batches(&mut grpc).await {
+        Ok(batches) => {
+            for batch in batches {
+                match get_transaction_batch_signatures(&mut grpc, batch.nonce, batch.token_contract)
+                    .await
+                {
+                    Ok(confirms) => signers.extend(confirms.iter().map(|c| c.ethereum_signer)),
+                    Err(e) => warn!("Failed to get confirms for batch {} {:?}", batch.nonce, e),
+                }
+            }
+        }
+        Err(e) => warn!("Failed to get the latest batches {:?}", e),
+    }
+
+    // the orchestrator key submits Ethereum event claims, the last nonce it claimed shows if it
+    // is keeping up with everyone else
+    let mut event_nonces = HashMap::new();
+    for k in keys.iter() {
+        if let Some(orchestrator) = k.orchestrator {
+            match get_last_event_nonce_for_validator(
+                &mut grpc,
+                orchestrator,
+                address_prefix.clone(),
+            )
+            .await
+            {
+                Ok(n) => {
+                    event_nonces.insert(k.validator.clone(), n);
+                }
+                Err(e) => warn!(
+                    "Failed to get the last event nonce of {} {:?}",
+                    k.validator, e
+                ),
+            }
+        }
+    }
+    let latest_event_nonce = event_nonces.values().max().cloned().unwrap_or(0);
+
+    let mut eth_owners: HashMap<EthAddress, Vec<&str>> = HashMap::new();
+    let mut orchestrator_owners: HashMap<String, Vec<&str>> = HashMap::new();
+    for k in keys.iter() {
+        if let Some(e) = k.eth_address {
+            eth_owners.entry(e).or_default().push(&k.moniker);
+        }
+        if let Some(o) = k.orchestrator {
+            orchestrator_owners
+                .entry(o.to_string())
+                .or_default()
+                .push(&k.moniker);
+        }
+    }
+
+    let mut flagged = 0;
+    for k in keys.iter() {
+        let mut problems = Vec::new();
+        match k.eth_address {
+            None => problems.push("no Ethereum key registered".to_string()),
+            Some(eth_address) => {
+                if !signers.contains(&eth_address) {
+                    problems.push(format!(
+                        "Ethereum key {} has not signed any recent valset or batch",
+                        eth_address
+                    ));
+                }
+                let owners = &eth_owners[&eth_address];
+                if owners.len() > 1 {
+                    problems.push(format!(
+                        "Ethereum key {} is shared with {}",
+                        eth_address,
+                        others(owners, &k.moniker)
+                    ));
+                }
+                match web3.eth_get_balance(eth_address).await {
+                    Ok(b) if b == 0u8.into() => problems.push(format!(
+                        "Ethereum key {} has no ETH and can not relay",
+                        eth_address
+                    )),
+                    Ok(_) => {}
+                    Err(e) => warn!("Failed to get the balance of {} {:?}", eth_address, e),
+                }
+            }
+        }
+        match k.orchestrator {
+            None => problems.push("no orchestrator key registered".to_string()),
+            Some(orchestrator) => {
+                let nonce = event_nonces.get(&k.validator).cloned().unwrap_or(0);
+                if nonce == 0 {
+                    problems.push(format!(
+                        "orchestrator key {} has never submitted a claim",
+                        orchestrator
+                    ));
+                } else if nonce < latest_event_nonce {
+                    problems.push(format!(
+                        "orchestrator key {} is {} events behind",
+                        orchestrator,
+                        latest_event_nonce - nonce
+                    ));
+                }
+                let owners = &orchestrator_owners[&orchestrator.to_string()];
+                if owners.len() > 1 {
+                    problems.push(format!(
+                        "orchestrator key {} is shared with {}",
+                        orchestrator,
+                        others(owners, &k.moniker)
+                    ));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            if args.all {
+                info!("{} {} OK", k.moniker, k.validator);
+            }
+            continue;
+        }
+        flagged += 1;
+        info!("{} {}", k.moniker, k.validator);
+        for p in problems {
+            info!("    {}", p);
+        }
+    }
+
+    info!(
+        "{} of {} active validators have delegate key problems",
+        flagged,
+        keys.len()
+    );
+}
+
+/// The other validators in a list of key owners
+fn others(owners: &[&str], moniker: &str) -> String {
+    owners
+        .iter()
+        .filter(|o| **o != moniker)
+        .cloned()
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
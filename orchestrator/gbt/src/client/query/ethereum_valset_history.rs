@@ -0,0 +1,143 @@
+use crate::args::EthereumValsetHistoryQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::get_gravity_params;
+use gravity_utils::connection_prep::create_rpc_connections;
+use relayer::valset_history::{
+    compare_with_cosmos, out_of_order_nonces, reconstruct_valset_history, ValsetComparison,
+};
+use serde_json::json;
+use std::fs;
+use std::process::exit;
+
+/// Rebuilds the valset history from the Gravity contract's logs and checks every update against
+/// the Cosmos chain's record of the same nonce
+pub async fn query_ethereum_valset_history(
+    args: EthereumValsetHistoryQueryOpts,
+    address_prefix: String,
+) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let end_block = match args.end_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    info!(
+        "Rebuilding the valset history of {} from blocks {} to {}",
+        gravity_contract_address, args.start_block, end_block
+    );
+    let history = match reconstruct_valset_history(
+        &web3,
+        gravity_contract_address,
+        args.start_block.into(),
+        end_block,
+    )
+    .await
+    {
+        Ok(h) => h,
+        Err(e) => {
+            error!(
+                "Failed to search for valset updates, is your Ethereum node working? {:?}",
+                e
+            );
+            exit(1);
+        }
+    };
+    if history.is_empty() {
+        info!("No valset updates found, is the start block before the contract was deployed?");
+        return;
+    }
+    for nonce in out_of_order_nonces(&history) {
+        error!(
+            "Valset {} was emitted after a higher nonce, these logs are not from a Gravity contract",
+            nonce
+        );
+    }
+
+    let comparisons = if args.skip_cosmos {
+        None
+    } else {
+        match compare_with_cosmos(&mut grpc, &history).await {
+            Ok(c) => Some(c),
+            Err(e) => {
+                warn!(
+                    "Failed to compare with the Cosmos chain, showing the Ethereum history only {:?}",
+                    e
+                );
+                None
+            }
+        }
+    };
+    let mut differs = 0;
+    for (i, h) in history.iter().enumerate() {
+        let comparison = comparisons.as_ref().map(|c| c[i].1);
+        let status = match comparison {
+            Some(ValsetComparison::Matches) => "matches Cosmos",
+            Some(ValsetComparison::SortingDiffers) => "matches Cosmos in a different order",
+            Some(ValsetComparison::Differs) => {
+                differs += 1;
+                "DIFFERS FROM COSMOS, possible bridge highjacking!"
+            }
+            Some(ValsetComparison::MissingOnCosmos) => "not stored on Cosmos, it may be pruned",
+            None => "not compared",
+        };
+        info!(
+            "Valset {} in block {} event nonce {} with {} members and {} total power, {}",
+            h.valset.nonce,
+            h.block_height,
+            h.event_nonce,
+            h.valset.members.len(),
+            h.valset.get_total_power(),
+            status
+        );
+    }
+    if differs > 0 {
+        error!(
+            "{} of {} valsets on Ethereum differ from the Cosmos chain's record",
+            differs,
+            history.len()
+        );
+    }
+
+    if let Some(path) = args.output {
+        let entries: Vec<_> = history
+            .iter()
+            .map(|h| {
+                json!({
+                    "valset": h.valset,
+                    "block_height": h.block_height.to_string(),
+                    "event_nonce": h.event_nonce,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&entries).unwrap();
+        match fs::write(&path, output + "\n") {
+            Ok(()) => info!("Wrote {} valsets to {}", history.len(), path.display()),
+            Err(e) => {
+                error!("Failed to write the history to {} {:?}", path.display(), e);
+                exit(1);
+            }
+        }
+    }
+}
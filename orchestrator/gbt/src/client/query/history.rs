@@ -0,0 +1,461 @@
+use crate::address_book::{resolve_cosmos_address, resolve_eth_address};
+use crate::args::HistoryQueryOpts;
+use crate::client::query::accidental_transfers::transfer_topics;
+use crate::utils::TIMEOUT;
+use chrono::{DateTime, NaiveDateTime};
+use clarity::constants::zero_address;
+use clarity::utils::bytes_to_hex_str;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_gravity_params, get_pending_send_to_eth};
+use cosmos_gravity::send::{MSG_CANCEL_SEND_TO_ETH_TYPE_URL, MSG_SEND_TO_ETH_TYPE_URL};
+use deep_space::error::CosmosGrpcError;
+use deep_space::{Address as CosmosAddress, Contact};
+use gravity_proto::cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::service_client::ServiceClient as TxServiceClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{GetTxsEventRequest, OrderBy, Tx};
+use gravity_proto::gravity::{MsgCancelSendToEth, MsgSendToEth, QueryLastObservedEthNonceRequest};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::downcast_uint256;
+use gravity_utils::types::event_signatures::{
+    SENT_TO_COSMOS_EVENT_SIG, TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+};
+use gravity_utils::types::{EthereumEvent, SendToCosmosEvent, TransactionBatchExecutedEvent};
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use prost::Message;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::process::exit;
+use web30::client::Web3;
+use web30::types::Log;
+
+/// How many transactions to request per page when searching Cosmos transactions
+const TX_SEARCH_PAGE_SIZE: u64 = 100;
+/// The typed event the Gravity module emits with the id of every new outgoing transfer
+const OUTGOING_TX_ID_EVENT: &str = "gravity.v1.EventOutgoingTxId";
+
+/// The address whose history is being assembled, what can be found depends on the chain
+#[derive(Debug, Clone, Copy)]
+enum HistoryAddress {
+    Cosmos(CosmosAddress),
+    Ethereum(EthAddress),
+}
+
+/// A single transfer in or out of the address, from either chain
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    /// Unix timestamp of the block the transfer was made in
+    time: u64,
+    /// Either "out" or "in"
+    direction: &'static str,
+    kind: &'static str,
+    amount: String,
+    /// The other side of the transfer
+    counterparty: String,
+    status: String,
+    /// The Cosmos or Ethereum transaction hash
+    tx_hash: String,
+}
+
+/// Prints every bridge transfer in or out of an address in chronological order. For a Cosmos
+/// address these are its sends to Ethereum and the deposits it received, for an Ethereum address
+/// its deposits and the batch payouts it received. Cosmos transactions are found by searching the
+/// chain and Ethereum events by scanning the Gravity contract from --start-block
+pub async fn query_history(
+    args: HistoryQueryOpts,
+    address_prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
+    let address = match resolve_eth_address(&args.address, address_book, true) {
+        Ok(a) => HistoryAddress::Ethereum(a),
+        Err(_) => match resolve_cosmos_address(&args.address, address_book) {
+            Ok(a) => HistoryAddress::Cosmos(a),
+            Err(_) => {
+                error!(
+                    "{} is not an Ethereum address, a Cosmos address or an address book entry",
+                    args.address
+                );
+                exit(1);
+            }
+        },
+    };
+
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let last_observed_nonce = match grpc
+        .get_last_observed_eth_nonce(QueryLastObservedEthNonceRequest { use_v1_key: false })
+        .await
+    {
+        Ok(n) => n.into_inner().nonce,
+        Err(e) => {
+            error!("Failed to get the last observed event nonce {:?}", e);
+            exit(1);
+        }
+    };
+
+    let mut history = Vec::new();
+    if let HistoryAddress::Cosmos(sender) = address {
+        let pending = match get_pending_send_to_eth(&mut grpc, sender).await {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to get pending sends to Ethereum {:?}", e);
+                exit(1);
+            }
+        };
+        let in_batches: HashSet<u64> = pending.transfers_in_batches.iter().map(|t| t.id).collect();
+        let unbatched: HashSet<u64> = pending.unbatched_transfers.iter().map(|t| t.id).collect();
+        match get_sends_to_eth(&contact, sender, &in_batches, &unbatched).await {
+            Ok(sends) => history.extend(sends),
+            Err(e) => {
+                error!(
+                    "Failed to search for sends to Ethereum, does your node index transactions? {:?}",
+                    e
+                );
+                exit(1);
+            }
+        }
+    }
+
+    let end_block = match args.end_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    let start_block: Uint256 = args.start_block.into();
+    let mut block_times = HashMap::new();
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block > BLOCKS_TO_SEARCH.into() {
+            current_block + BLOCKS_TO_SEARCH.into()
+        } else {
+            end_block
+        };
+        info!("Searching blocks {} to {}", current_block, end_search);
+        match scan_range(
+            &web3,
+            gravity_contract_address,
+            address,
+            last_observed_nonce,
+            current_block,
+            end_search,
+            &mut block_times,
+        )
+        .await
+        {
+            Ok(entries) => history.extend(entries),
+            Err(e) => {
+                error!(
+                    "Failed to search blocks {} to {}, is your Ethereum node working? {:?}",
+                    current_block, end_search, e
+                );
+                exit(1);
+            }
+        }
+        current_block = end_search + 1u8.into();
+    }
+
+    if history.is_empty() {
+        info!("No bridge transfers found for {}", args.address);
+        return;
+    }
+    history.sort_by_key(|h| h.time);
+    for h in history.iter() {
+        let time = NaiveDateTime::from_timestamp_opt(h.time as i64, 0)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_default();
+        let preposition = if h.direction == "out" { "to" } else { "from" };
+        info!(
+            "{} {} {} {} {} {}: {} tx {}",
+            time, h.direction, h.kind, h.amount, preposition, h.counterparty, h.status, h.tx_hash
+        );
+    }
+    info!("Found {} bridge transfers", history.len());
+}
+
+/// Finds every MsgSendToEth sent by `sender` and works out where each transfer is now using its
+/// id: waiting in the pool, in a batch waiting to be relayed, cancelled or sent
+async fn get_sends_to_eth(
+    contact: &Contact,
+    sender: CosmosAddress,
+    in_batches: &HashSet<u64>,
+    unbatched: &HashSet<u64>,
+) -> Result<Vec<HistoryEntry>, CosmosGrpcError> {
+    let mut cancelled = HashSet::new();
+    for (tx, response) in search_txs(contact, sender, MSG_CANCEL_SEND_TO_ETH_TYPE_URL).await? {
+        if response.code != 0 {
+            continue;
+        }
+        for msg in tx.body.map(|b| b.messages).unwrap_or_default() {
+            if msg.type_url == MSG_CANCEL_SEND_TO_ETH_TYPE_URL {
+                if let Ok(cancel) = MsgCancelSendToEth::decode(msg.value.as_slice()) {
+                    cancelled.insert(cancel.transaction_id);
+                }
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (tx, response) in search_txs(contact, sender, MSG_SEND_TO_ETH_TYPE_URL).await? {
+        let time = DateTime::parse_from_rfc3339(&response.timestamp)
+            .map(|t| t.timestamp().max(0) as u64)
+            .unwrap_or_default();
+        let messages = tx.body.map(|b| b.messages).unwrap_or_default();
+        for (index, msg) in messages.iter().enumerate() {
+            if msg.type_url != MSG_SEND_TO_ETH_TYPE_URL {
+                continue;
+            }
+            let send = match MsgSendToEth::decode(msg.value.as_slice()) {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let status = if response.code != 0 {
+                format!("failed with code {}", response.code)
+            } else {
+                match outgoing_tx_id(&response, index) {
+                    Some(id) if cancelled.contains(&id) => format!("cancelled (id {})", id),
+                    Some(id) if unbatched.contains(&id) => {
+                        format!("waiting to be batched (id {})", id)
+                    }
+                    Some(id) if in_batches.contains(&id) => {
+                        format!("in a batch waiting to be relayed (id {})", id)
+                    }
+                    Some(id) => format!("sent to Ethereum (id {})", id),
+                    None => "unknown".to_string(),
+                }
+            };
+            let amount = send
+                .amount
+                .map(|c| format!("{}{}", c.amount, c.denom))
+                .unwrap_or_default();
+            entries.push(HistoryEntry {
+                time,
+                direction: "out",
+                kind: "send to Ethereum",
+                amount,
+                counterparty: send.eth_dest,
+                status,
+                tx_hash: response.txhash.clone(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Searches the Cosmos chain for every transaction from `sender` containing a message of the
+/// given type, oldest first
+async fn search_txs(
+    contact: &Contact,
+    sender: CosmosAddress,
+    type_url: &str,
+) -> Result<Vec<(Tx, TxResponse)>, CosmosGrpcError> {
+    let mut tx_client = TxServiceClient::connect(contact.get_url()).await?;
+    let events = vec![
+        format!("message.sender='{}'", sender),
+        format!("message.action='{}'", type_url),
+    ];
+    let mut found = Vec::new();
+    loop {
+        let res = tx_client
+            .get_txs_event(GetTxsEventRequest {
+                events: events.clone(),
+                order_by: OrderBy::Asc as i32,
+                pagination: Some(PageRequest {
+                    offset: found.len() as u64,
+                    limit: TX_SEARCH_PAGE_SIZE,
+                    count_total: true,
+                    ..Default::default()
+                }),
+            })
+            .await?
+            .into_inner();
+        let count = res.tx_responses.len() as u64;
+        let total = res.pagination.map(|p| p.total).unwrap_or_default();
+        found.extend(res.txs.into_iter().zip(res.tx_responses));
+        if count < TX_SEARCH_PAGE_SIZE || found.len() as u64 >= total {
+            return Ok(found);
+        }
+    }
+}
+
+/// Reads the id the Gravity module assigned to the transfer created by the message at `msg_index`
+fn outgoing_tx_id(response: &TxResponse, msg_index: usize) -> Option<u64> {
+    let log = response
+        .logs
+        .iter()
+        .find(|l| l.msg_index as usize == msg_index)?;
+    let event = log
+        .events
+        .iter()
+        .find(|e| e.r#type == OUTGOING_TX_ID_EVENT)?;
+    let id = event.attributes.iter().find(|a| a.key == "tx_id")?;
+    // typed event attributes are JSON encoded, so the id is quoted
+    id.value.trim_matches('"').parse().ok()
+}
+
+/// Finds the Ethereum side of the history in a single block range: deposits to or from the
+/// address and, for Ethereum addresses, tokens paid out to it by batches
+async fn scan_range(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    address: HistoryAddress,
+    last_observed_nonce: u64,
+    start: Uint256,
+    end: Uint256,
+    block_times: &mut HashMap<Uint256, u64>,
+) -> Result<Vec<HistoryEntry>, GravityError> {
+    let mut entries = Vec::new();
+    let deposit_logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![SENT_TO_COSMOS_EVENT_SIG],
+        )
+        .await?;
+    for log in deposit_logs.iter() {
+        let deposit = SendToCosmosEvent::from_log(log)?;
+        let (direction, counterparty) = match address {
+            HistoryAddress::Ethereum(a) if deposit.sender == a => {
+                ("out", deposit.destination.clone())
+            }
+            HistoryAddress::Cosmos(a) if deposit.validated_destination == Some(a) => {
+                ("in", deposit.sender.to_string())
+            }
+            _ => continue,
+        };
+        let status = if deposit.event_nonce > last_observed_nonce {
+            format!(
+                "waiting for validators to observe it (event nonce {})",
+                deposit.event_nonce
+            )
+        } else if deposit.validated_destination.is_none() {
+            format!(
+                "invalid destination, sent to the community pool (event nonce {})",
+                deposit.event_nonce
+            )
+        } else {
+            format!("received on Cosmos (event nonce {})", deposit.event_nonce)
+        };
+        entries.push(HistoryEntry {
+            time: block_time(web3, deposit.block_height, block_times).await?,
+            direction,
+            kind: "deposit to Cosmos",
+            amount: format!("{} {}", deposit.amount, deposit.erc20),
+            counterparty,
+            status,
+            tx_hash: log_tx_hash(log),
+        });
+    }
+
+    let recipient = match address {
+        HistoryAddress::Ethereum(a) => a,
+        HistoryAddress::Cosmos(_) => return Ok(entries),
+    };
+    let executed_logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![TRANSACTION_BATCH_EXECUTED_EVENT_SIG],
+        )
+        .await?;
+    let executed = TransactionBatchExecutedEvent::from_logs(&executed_logs)?;
+    let tokens: HashSet<EthAddress> = executed.iter().map(|e| e.erc20).collect();
+    if tokens.is_empty() {
+        return Ok(entries);
+    }
+    // batch nonces by transaction, so that payouts can be matched to the batch that made them
+    let batches: HashMap<String, u64> = executed_logs
+        .iter()
+        .zip(executed.iter())
+        .map(|(l, e)| (log_tx_hash(l), e.batch_nonce))
+        .collect();
+
+    let transfer_logs = web3
+        .check_for_arbitrary_events(
+            start,
+            Some(end),
+            tokens.into_iter().collect(),
+            transfer_topics(Some(gravity_contract_address), Some(recipient)),
+        )
+        .await?;
+    for log in transfer_logs.iter() {
+        let (from, to, amount) = match parse_transfer(log) {
+            Some(t) => t,
+            None => continue,
+        };
+        if from != gravity_contract_address || to != recipient {
+            continue;
+        }
+        let tx_hash = log_tx_hash(log);
+        let status = match batches.get(&tx_hash) {
+            Some(nonce) => format!("paid out by batch {}", nonce),
+            None => "paid out".to_string(),
+        };
+        entries.push(HistoryEntry {
+            time: block_time(web3, log.block_number.unwrap_or_default(), block_times).await?,
+            direction: "in",
+            kind: "send to Ethereum",
+            amount: format!("{} {}", amount, log.address),
+            counterparty: gravity_contract_address.to_string(),
+            status,
+            tx_hash,
+        });
+    }
+    Ok(entries)
+}
+
+/// The timestamp of an Ethereum block, blocks are cached since a block often holds several events
+async fn block_time(
+    web3: &Web3,
+    block: Uint256,
+    block_times: &mut HashMap<Uint256, u64>,
+) -> Result<u64, GravityError> {
+    if let Some(t) = block_times.get(&block) {
+        return Ok(*t);
+    }
+    let time =
+        downcast_uint256(web3.eth_get_block_by_number(block).await?.timestamp).unwrap_or_default();
+    block_times.insert(block, time);
+    Ok(time)
+}
+
+fn log_tx_hash(log: &Log) -> String {
+    log.transaction_hash
+        .as_ref()
+        .map(|h| format!("0x{}", bytes_to_hex_str(h)))
+        .unwrap_or_default()
+}
+
+/// Parses an ERC20 Transfer log into its sender, recipient and amount
+fn parse_transfer(log: &Log) -> Option<(EthAddress, EthAddress, Uint256)> {
+    let (from_data, to_data) = (log.topics.get(1)?, log.topics.get(2)?);
+    if from_data.len() < 32 || to_data.len() < 32 || log.data.len() < 32 {
+        return None;
+    }
+    Some((
+        EthAddress::from_slice(&from_data[12..32]).ok()?,
+        EthAddress::from_slice(&to_data[12..32]).ok()?,
+        Uint256::from_be_bytes(&log.data[0..32]),
+    ))
+}
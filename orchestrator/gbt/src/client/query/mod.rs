@@ -0,0 +1,12 @@
+pub mod accidental_transfers;
+pub mod attestation;
+pub mod batch;
+pub mod batch_fees;
+pub mod batch_preview;
+pub mod delegate_keys;
+pub mod ethereum_valset_history;
+pub mod history;
+pub mod relayer_payouts;
+pub mod signing_info;
+pub mod token_mappings;
+pub mod valset_history;
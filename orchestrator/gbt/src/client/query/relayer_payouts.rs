@@ -0,0 +1,269 @@
+use crate::args::RelayerPayoutsQueryOpts;
+use crate::client::spot_relay::user_token_name_to_gravity_token;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_denom_to_erc20, get_gravity_params};
+use deep_space::Address as CosmosAddress;
+use ethereum_gravity::calldata::{decode_gravity_call, GravityCall};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::{
+    ERC20_TRANSFER_EVENT_SIG, TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+};
+use gravity_utils::types::{EthereumEvent, TransactionBatchExecutedEvent};
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use serde_json::Value;
+use std::process::exit;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+/// What a relayer was promised and what it received for executing a single batch
+struct Payout {
+    nonce: u64,
+    tx_hash: String,
+    relayer: EthAddress,
+    promised: Uint256,
+    received: Uint256,
+}
+
+/// Matches the fees of every batch of a token executed in the nonce range against the ERC20
+/// transfers the relayer received in the same transaction
+pub async fn query_relayer_payouts(args: RelayerPayoutsQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc.clone()),
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+    let eth_client = HttpClient::new(&args.ethereum_rpc);
+
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let denom = match user_token_name_to_gravity_token(args.token.clone(), &mut grpc).await {
+        Some(d) => d,
+        None => {
+            error!("Failed to decode your intended token name {}", args.token);
+            exit(1);
+        }
+    };
+    let erc20: EthAddress = match get_denom_to_erc20(&mut grpc, denom.clone()).await {
+        Ok(v) => v.erc20.parse().unwrap(),
+        Err(e) => {
+            error!(
+                "Could not find the ERC20 for {}, is it bridged? {:?}",
+                denom, e
+            );
+            exit(1);
+        }
+    };
+    if args.start_nonce > args.end_nonce {
+        error!("--start-nonce must not be greater than --end-nonce");
+        exit(1);
+    }
+
+    let end_block = match args.end_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    let start_block: Uint256 = args.start_block.into();
+    let mut payouts = Vec::new();
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block > BLOCKS_TO_SEARCH.into() {
+            current_block + BLOCKS_TO_SEARCH.into()
+        } else {
+            end_block
+        };
+        info!("Searching blocks {} to {}", current_block, end_search);
+        let executions = match find_executions(
+            &web3,
+            gravity_contract_address,
+            erc20,
+            current_block,
+            end_search,
+        )
+        .await
+        {
+            Ok(e) => e,
+            Err(e) => {
+                error!(
+                    "Failed to search blocks {} to {}, is your Ethereum node working? {:?}",
+                    current_block, end_search, e
+                );
+                exit(1);
+            }
+        };
+        for (event, tx_hash) in executions {
+            if event.batch_nonce < args.start_nonce || event.batch_nonce > args.end_nonce {
+                continue;
+            }
+            match get_payout(&web3, &eth_client, erc20, &event, &tx_hash).await {
+                Ok(p) => payouts.push(p),
+                Err(e) => warn!(
+                    "Could not reconcile batch {} in tx 0x{} {:?}",
+                    event.batch_nonce,
+                    bytes_to_hex_str(&tx_hash),
+                    e
+                ),
+            }
+        }
+        current_block = end_search + 1u8.into();
+    }
+
+    if payouts.is_empty() {
+        info!(
+            "No batches of {} with nonces {} to {} were executed between blocks {} and {}",
+            denom, args.start_nonce, args.end_nonce, start_block, end_block
+        );
+        return;
+    }
+    let mut discrepancies = 0;
+    for p in payouts.iter() {
+        if p.promised == p.received {
+            info!(
+                "Batch {} relayed by {} in tx {}, received the promised {}",
+                p.nonce, p.relayer, p.tx_hash, p.promised
+            );
+        } else {
+            discrepancies += 1;
+            warn!(
+                "Batch {} relayed by {} in tx {}, promised {} but received {}",
+                p.nonce, p.relayer, p.tx_hash, p.promised, p.received
+            );
+        }
+    }
+    let found: Vec<u64> = payouts.iter().map(|p| p.nonce).collect();
+    let missing = (args.start_nonce..=args.end_nonce)
+        .filter(|n| !found.contains(n))
+        .count();
+    info!(
+        "Reconciled {} batches, {} with discrepancies, {} nonces in the range were not executed in the searched blocks",
+        payouts.len(),
+        discrepancies,
+        missing
+    );
+}
+
+/// Every execution of a batch of `erc20` in the range, with the hash of the executing transaction
+async fn find_executions(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    erc20: EthAddress,
+    start: Uint256,
+    end: Uint256,
+) -> Result<Vec<(TransactionBatchExecutedEvent, Vec<u8>)>, GravityError> {
+    let logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![TRANSACTION_BATCH_EXECUTED_EVENT_SIG],
+        )
+        .await?;
+    let mut out = Vec::new();
+    for log in logs {
+        let event = TransactionBatchExecutedEvent::from_log(&log)?;
+        if event.erc20 != erc20 {
+            continue;
+        }
+        if let Some(hash) = log.transaction_hash {
+            out.push((event, hash.to_vec()));
+        }
+    }
+    Ok(out)
+}
+
+/// Decodes the promised fees from the submitBatch calldata and sums the transfers of the token to
+/// the relayer within the same transaction. For fee on transfer tokens the Transfer event carries
+/// the amount actually credited, which is what the relayer ends up with
+async fn get_payout(
+    web3: &Web3,
+    eth_client: &HttpClient,
+    erc20: EthAddress,
+    event: &TransactionBatchExecutedEvent,
+    tx_hash: &[u8],
+) -> Result<Payout, GravityError> {
+    let tx_hash_str = format!("0x{}", bytes_to_hex_str(tx_hash));
+    let tx: Value = eth_client
+        .request_method(
+            "eth_getTransactionByHash",
+            vec![tx_hash_str.clone()],
+            TIMEOUT,
+        )
+        .await?;
+    let relayer: EthAddress = tx["from"]
+        .as_str()
+        .and_then(|f| f.parse().ok())
+        .ok_or_else(|| GravityError::EthereumContractError("Transaction has no sender".into()))?;
+    let input = tx["input"]
+        .as_str()
+        .and_then(|i| hex_str_to_bytes(i).ok())
+        .ok_or_else(|| GravityError::EthereumContractError("Transaction has no input".into()))?;
+
+    // batch senders are not part of the calldata, they are irrelevant here
+    let placeholder_sender = CosmosAddress::from_slice(&[0u8; 20], "gravity").unwrap();
+    let batch = match decode_gravity_call(&input, placeholder_sender)? {
+        Some(GravityCall::SubmitBatch { batch, .. }) => batch,
+        _ => {
+            return Err(GravityError::EthereumContractError(
+                "Batch was not executed by a direct submitBatch call".into(),
+            ))
+        }
+    };
+    // the decoded total fee is always zero, it is not part of the calldata. A relayer sending to
+    // itself receives those amounts in the same transaction, so they are expected as well
+    let mut promised: Uint256 = 0u8.into();
+    for tx in batch.transactions.iter() {
+        promised += tx.erc20_fee.amount;
+        if tx.destination == relayer {
+            promised += tx.erc20_token.amount;
+        }
+    }
+
+    let logs = web3
+        .check_for_events(
+            event.block_height,
+            Some(event.block_height),
+            vec![erc20],
+            vec![ERC20_TRANSFER_EVENT_SIG],
+        )
+        .await?;
+    let mut received: Uint256 = 0u8.into();
+    for log in logs {
+        if log.transaction_hash.as_deref().map(|h| h.as_slice()) != Some(tx_hash) {
+            continue;
+        }
+        let to = match log.topics.get(2) {
+            Some(t) if t.len() >= 32 && log.data.len() >= 32 => {
+                EthAddress::from_slice(&t[12..32]).ok()
+            }
+            _ => None,
+        };
+        if to == Some(relayer) {
+            received += Uint256::from_be_bytes(&log.data[0..32]);
+        }
+    }
+
+    Ok(Payout {
+        nonce: event.batch_nonce,
+        tx_hash: tx_hash_str,
+        relayer,
+        promised,
+        received,
+    })
+}
@@ -0,0 +1,44 @@
+use crate::address_book::cosmos_address_or_exit;
+use crate::args::SigningInfoQueryOpts;
+use crate::utils::TIMEOUT;
+use deep_space::Fee;
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::collections::BTreeMap;
+use std::process::exit;
+
+/// Prints what `gbt client sign` needs to know about an account to sign for it offline
+pub async fn query_signing_info(
+    args: SigningInfoQueryOpts,
+    address_prefix: String,
+    address_book: &BTreeMap<String, String>,
+) {
+    let address = cosmos_address_or_exit(&args.address, address_book);
+    let connections =
+        create_rpc_connections(address_prefix, Some(args.cosmos_grpc), None, TIMEOUT).await;
+    let contact = connections.contact.unwrap();
+
+    let fee = Fee {
+        amount: vec![],
+        gas_limit: 0,
+        granter: None,
+        payer: None,
+    };
+    match contact.get_message_args(address, fee).await {
+        Ok(a) => {
+            info!("Chain id {}", a.chain_id);
+            info!("Account number {}", a.account_number);
+            info!("Next sequence {}", a.sequence);
+            info!(
+                "Sign with `gbt client sign --chain-id {} --account-number {} --sequence {}`, each further transaction signed before these are broadcast takes the next sequence",
+                a.chain_id, a.account_number, a.sequence
+            );
+        }
+        Err(e) => {
+            error!(
+                "Failed to get the account of {}, it must have received tokens to exist {:?}",
+                address, e
+            );
+            exit(1);
+        }
+    }
+}
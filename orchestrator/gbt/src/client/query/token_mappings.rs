@@ -0,0 +1,141 @@
+use crate::args::TokenMappingsQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::get_denom_to_erc20;
+use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::QueryTotalSupplyRequest;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::query::v1beta1::PageRequest;
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::fs;
+use std::process::exit;
+use web30::client::Web3;
+
+/// A single Cosmos denom to ERC20 mapping
+#[derive(Serialize)]
+struct TokenMapping {
+    denom: String,
+    erc20: EthAddress,
+    /// true if the asset originated on Cosmos and the ERC20 was deployed by Gravity to represent it
+    cosmos_originated: bool,
+    /// the display denom from the Cosmos bank metadata, if set
+    cosmos_display: Option<String>,
+    /// None if the ERC20 does not implement the optional metadata functions
+    name: Option<String>,
+    symbol: Option<String>,
+    decimals: Option<u8>,
+}
+
+/// Dumps every Cosmos denom with an ERC20 representation as JSON. Ethereum originated tokens are
+/// found through the bank supply, so a token with nothing currently bridged to Cosmos is not listed
+pub async fn query_token_mappings(args: TokenMappingsQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc.clone()),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let denoms = match get_all_denoms(args.cosmos_grpc).await {
+        Ok(d) => d,
+        Err(e) => {
+            error!("Failed to get the bank supply {:?}", e);
+            exit(1);
+        }
+    };
+    let metadata = contact.get_all_denoms_metadata().await.unwrap_or_default();
+
+    let mut mappings = Vec::new();
+    for denom in denoms {
+        // denoms without an ERC20 representation return an error
+        let res = match get_denom_to_erc20(&mut grpc, denom.clone()).await {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        let erc20: EthAddress = match res.erc20.parse() {
+            Ok(e) => e,
+            Err(e) => {
+                warn!("Invalid ERC20 {} for {} {:?}", res.erc20, denom, e);
+                continue;
+            }
+        };
+        let cosmos_display = metadata
+            .iter()
+            .find(|m| m.base == denom)
+            .map(|m| m.display.clone());
+        let (name, symbol, decimals) = get_erc20_metadata(&web3, erc20).await;
+        mappings.push(TokenMapping {
+            denom,
+            erc20,
+            cosmos_originated: res.cosmos_originated,
+            cosmos_display,
+            name,
+            symbol,
+            decimals,
+        });
+    }
+    mappings.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let json = serde_json::to_string_pretty(&mappings).unwrap();
+    match args.output {
+        Some(output) => match fs::write(&output, json) {
+            Ok(()) => info!(
+                "Wrote {} token mappings to {}",
+                mappings.len(),
+                output.display()
+            ),
+            Err(e) => {
+                error!("Failed to write to {} {:?}", output.display(), e);
+                exit(1);
+            }
+        },
+        None => println!("{}", json),
+    }
+}
+
+/// Every denom with a non zero supply, the gravity module has no query listing its mappings
+async fn get_all_denoms(cosmos_grpc: String) -> Result<Vec<String>, tonic::Status> {
+    let mut bank = match BankQueryClient::connect(cosmos_grpc).await {
+        Ok(b) => b,
+        Err(e) => return Err(tonic::Status::unavailable(e.to_string())),
+    };
+    let mut denoms = Vec::new();
+    let mut key = Vec::new();
+    loop {
+        let res = bank
+            .total_supply(QueryTotalSupplyRequest {
+                pagination: Some(PageRequest {
+                    key,
+                    offset: 0,
+                    limit: 1000,
+                    count_total: false,
+                    reverse: false,
+                }),
+            })
+            .await?
+            .into_inner();
+        denoms.extend(res.supply.into_iter().map(|c| c.denom));
+        match res.pagination {
+            Some(p) if !p.next_key.is_empty() => key = p.next_key,
+            _ => return Ok(denoms),
+        }
+    }
+}
+
+async fn get_erc20_metadata(
+    web3: &Web3,
+    erc20: EthAddress,
+) -> (Option<String>, Option<String>, Option<u8>) {
+    let name = web3.get_erc20_name(erc20, zero_address()).await.ok();
+    let symbol = web3.get_erc20_symbol(erc20, zero_address()).await.ok();
+    let decimals = web3
+        .get_erc20_decimals(erc20, zero_address())
+        .await
+        .ok()
+        .and_then(|d| d.to_string().parse().ok());
+    (name, symbol, decimals)
+}
@@ -0,0 +1,220 @@
+use crate::args::ValsetHistoryQueryOpts;
+use crate::utils::TIMEOUT;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_latest_valsets, get_valset};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::num_conversion::print_eth;
+use gravity_utils::types::{Valset, ValsetMember, TOTAL_GRAVITY_POWER};
+use std::process::exit;
+
+/// The chain creates a new valset whenever the power difference to the last one exceeds this,
+/// mirrors the check in x/gravity/abci.go
+const SIGNIFICANT_POWER_DIFF: f32 = 0.05;
+/// Rough gas model for updateValset, the fixed cost of the call, the cost of checking each
+/// signature from the old set and the cost of each member of the new set in calldata and hashing
+const VALSET_UPDATE_BASE_GAS: u64 = 100_000;
+const VALSET_UPDATE_GAS_PER_SIGNER: u64 = 8_000;
+const VALSET_UPDATE_GAS_PER_MEMBER: u64 = 2_000;
+
+/// The membership and power changes from one validator set to the next
+#[derive(Debug, Default, PartialEq)]
+struct ValsetDiff {
+    joined: Vec<ValsetMember>,
+    left: Vec<ValsetMember>,
+    /// (address, old power, new power) for members in both sets whose power changed
+    changed: Vec<(EthAddress, u64, u64)>,
+}
+
+fn diff_valsets(old: &Valset, new: &Valset) -> ValsetDiff {
+    let old_powers = old.to_hashmap();
+    let new_powers = new.to_hashmap();
+    let mut diff = ValsetDiff::default();
+    for member in new.members.iter() {
+        match old_powers.get(&member.eth_address) {
+            None => diff.joined.push(member.clone()),
+            Some(old_power) if *old_power != member.power => {
+                diff.changed
+                    .push((member.eth_address, *old_power, member.power))
+            }
+            Some(_) => {}
+        }
+    }
+    for member in old.members.iter() {
+        if !new_powers.contains_key(&member.eth_address) {
+            diff.left.push(member.clone());
+        }
+    }
+    diff
+}
+
+/// Estimated gas to relay `new` to a contract currently holding `old`, signatures from the old
+/// set are checked in order until the power threshold is reached, so all of them are counted
+fn estimate_update_gas(old: &Valset, new: &Valset) -> u64 {
+    VALSET_UPDATE_BASE_GAS
+        + old.members.len() as u64 * VALSET_UPDATE_GAS_PER_SIGNER
+        + new.members.len() as u64 * VALSET_UPDATE_GAS_PER_MEMBER
+}
+
+fn to_percent(power: u64) -> f32 {
+    power as f32 / TOTAL_GRAVITY_POWER as f32 * 100.0
+}
+
+/// Lists recent validator sets, oldest first, with what changed since the set before each one
+pub async fn query_valset_history(args: ValsetHistoryQueryOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let latest_nonce = match get_latest_valsets(&mut grpc).await {
+        Ok(v) => v.iter().map(|v| v.nonce).max(),
+        Err(e) => {
+            error!("Failed to get the latest validator sets {:?}", e);
+            exit(1);
+        }
+    };
+    let latest_nonce = match latest_nonce {
+        Some(n) => n,
+        None => {
+            info!("There are no validator sets on chain");
+            return;
+        }
+    };
+
+    // one extra set so the oldest shown can be compared against its predecessor
+    let first_nonce = latest_nonce.saturating_sub(args.last);
+    let mut valsets = Vec::new();
+    for nonce in first_nonce..=latest_nonce {
+        match get_valset(&mut grpc, nonce).await {
+            Ok(Some(v)) => valsets.push(v),
+            Ok(None) => debug!("Valset {} is not stored on chain", nonce),
+            Err(e) => {
+                error!("Failed to get valset {} {:?}", nonce, e);
+                exit(1);
+            }
+        }
+    }
+    let gas_price = match web3.eth_gas_price().await {
+        Ok(v) => Some(v),
+        Err(e) => {
+            warn!(
+                "Failed to get the Ethereum gas price, costs will only be shown in gas {:?}",
+                e
+            );
+            None
+        }
+    };
+
+    let mut previous: Option<&Valset> = None;
+    for valset in valsets.iter() {
+        let reward = match valset.reward_token {
+            Some(token) => format!("{} of {}", valset.reward_amount, token),
+            None => "none".to_string(),
+        };
+        let previous_set = match previous {
+            Some(p) => p,
+            None => {
+                // only shown as a baseline, unless it is the only set we have
+                previous = Some(valset);
+                if valsets.len() == 1 {
+                    info!(
+                        "Valset {} with {} members, relayer reward {}",
+                        valset.nonce,
+                        valset.members.len(),
+                        reward
+                    );
+                }
+                continue;
+            }
+        };
+        previous = Some(valset);
+
+        let power_diff = previous_set.power_diff(valset);
+        let trigger = if power_diff > SIGNIFICANT_POWER_DIFF {
+            "triggered by a significant power change"
+        } else {
+            "most likely triggered by a validator starting to unbond"
+        };
+        info!(
+            "Valset {} with {} members, relayer reward {}, {:.2}% power change since valset {}, {}",
+            valset.nonce,
+            valset.members.len(),
+            reward,
+            power_diff * 100.0,
+            previous_set.nonce,
+            trigger
+        );
+
+        let diff = diff_valsets(previous_set, valset);
+        for m in diff.joined.iter() {
+            info!(
+                "    + {} joined with {:.2}%",
+                m.eth_address,
+                to_percent(m.power)
+            );
+        }
+        for m in diff.left.iter() {
+            info!(
+                "    - {} left with {:.2}%",
+                m.eth_address,
+                to_percent(m.power)
+            );
+        }
+        for (address, old, new) in diff.changed.iter() {
+            info!(
+                "    ~ {} {:.2}% -> {:.2}%",
+                address,
+                to_percent(*old),
+                to_percent(*new)
+            );
+        }
+
+        let gas = estimate_update_gas(previous_set, valset);
+        match gas_price {
+            Some(price) => {
+                let cost: Uint256 = price * gas.into();
+                info!(
+                    "    Relaying over valset {} costs roughly {} gas / {} ETH at current gas prices",
+                    previous_set.nonce,
+                    gas,
+                    print_eth(cost)
+                );
+            }
+            None => info!(
+                "    Relaying over valset {} costs roughly {} gas",
+                previous_set.nonce, gas
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_diff_valsets() {
+    let address = |i: u8| EthAddress::from_slice(&[i; 20]).unwrap();
+    let member = |i: u8, power: u64| ValsetMember {
+        power,
+        eth_address: address(i),
+    };
+    let valset = |members: Vec<ValsetMember>| Valset {
+        nonce: 1,
+        members,
+        reward_amount: 0u8.into(),
+        reward_token: None,
+    };
+    let old = valset(vec![member(1, 100), member(2, 200), member(3, 300)]);
+    let new = valset(vec![member(1, 100), member(2, 250), member(4, 50)]);
+    assert_eq!(
+        diff_valsets(&old, &new),
+        ValsetDiff {
+            joined: vec![member(4, 50)],
+            left: vec![member(3, 300)],
+            changed: vec![(address(2), 200, 250)],
+        }
+    );
+    assert_eq!(diff_valsets(&old, &old), ValsetDiff::default());
+}
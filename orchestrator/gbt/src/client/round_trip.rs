@@ -0,0 +1,84 @@
+use crate::args::RoundTripOpts;
+use crate::utils::TIMEOUT;
+use cosmos_gravity::query::{get_denom_to_erc20, get_gravity_params};
+use cosmos_gravity::round_trip::round_trip;
+use deep_space::PrivateKey;
+use gravity_utils::connection_prep::{check_for_eth, check_for_fee, create_rpc_connections};
+use std::process::exit;
+use std::time::Duration;
+
+pub async fn round_trip_cmd(args: RoundTripOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let cosmos_address = args.cosmos_phrase.to_address(&address_prefix).unwrap();
+    check_for_eth(args.ethereum_key.to_address(), &web3).await;
+    check_for_fee(&args.fee, cosmos_address, &contact).await;
+
+    let gravity_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => match get_gravity_params(&mut grpc)
+            .await
+            .unwrap()
+            .bridge_ethereum_address
+            .parse()
+        {
+            Ok(c) => c,
+            Err(_) => {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+        },
+    };
+    let erc20 = match get_denom_to_erc20(&mut grpc, args.amount.denom.clone()).await {
+        Ok(v) if v.cosmos_originated => v.erc20.parse().unwrap(),
+        Ok(_) => {
+            error!(
+                "{} is an Ethereum originated token, a round trip must start from a Cosmos originated token",
+                args.amount.denom
+            );
+            exit(1);
+        }
+        Err(e) => {
+            error!(
+                "Could not find the ERC20 representation of {}, has it been deployed? {:?}",
+                args.amount.denom, e
+            );
+            exit(1);
+        }
+    };
+
+    let res = round_trip(
+        &contact,
+        &web3,
+        args.cosmos_phrase,
+        args.ethereum_key,
+        gravity_address,
+        erc20,
+        args.amount,
+        args.bridge_fee,
+        args.chain_fee,
+        args.fee,
+        args.request_batch,
+        Duration::from_secs(args.timeout * 60),
+    )
+    .await;
+    match res {
+        Ok(report) => info!(
+            "Round trip succeeded in {} seconds",
+            (report.to_eth + report.to_cosmos).as_secs()
+        ),
+        Err(e) => {
+            error!("Round trip failed {}", e);
+            exit(1);
+        }
+    }
+}
@@ -1,17 +1,20 @@
 use crate::args::SpotRelayOpts;
-use crate::utils::TIMEOUT;
+use crate::simulate::{print_eth_cost, simulate_cosmos_tx};
+use crate::utils::{display_denom, TIMEOUT};
 use clarity::Address as EthAddress;
 use cosmos_gravity::query::{
     get_gravity_params, get_latest_transaction_batches, get_pending_batch_fees,
     get_transaction_batch_signatures,
 };
-use cosmos_gravity::send::send_request_batch;
+use cosmos_gravity::send::{request_batch_msg, send_request_batch};
+use deep_space::PrivateKey;
 use ethereum_gravity::message_signatures::encode_tx_batch_confirm_hashed;
-use ethereum_gravity::submit_batch::send_eth_transaction_batch;
+use ethereum_gravity::submit_batch::{estimate_tx_batch_cost, send_eth_transaction_batch};
+use ethereum_gravity::utils::RelayOutcome;
 use gravity_proto::gravity::query_client::QueryClient;
 use gravity_proto::gravity::{QueryDenomToErc20Request, QueryErc20ToDenomRequest};
 use gravity_utils::connection_prep::{check_for_eth, create_rpc_connections};
-use gravity_utils::types::TransactionBatch;
+use gravity_utils::types::{PrivateRelayConfig, TransactionBatch};
 use relayer::find_latest_valset::find_latest_valset;
 use std::process::exit;
 use tonic::transport::Channel;
@@ -54,6 +57,7 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
             return;
         }
     };
+    let token_name = display_denom(&contact, &args.token).await;
     let ethereum_erc20: EthAddress = grpc
         .denom_to_erc20(QueryDenomToErc20Request {
             denom: gravity_denom.clone(),
@@ -90,13 +94,13 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
         if latest_eth_height > batch.batch_timeout.into() {
             warn!(
                 "Found pending batch {} for {}, but it has timed out",
-                batch.nonce, args.token
+                batch.nonce, token_name
             );
             warn!("These tokens will return to the pool and be rebatched when the next deposit / batch to the bridge goes through");
             warn!("The best way to speed this along would be to make a small deposit via the normal frontend. Registering that deposit will free this batch");
             return;
         }
-        info!("Found pending batch {} for {}", batch.nonce, args.token);
+        info!("Found pending batch {} for {}", batch.nonce, token_name);
         let sigs = get_transaction_batch_signatures(&mut grpc, batch.nonce, ethereum_erc20)
             .await
             .expect("Failed to get sigs for batch!");
@@ -120,9 +124,39 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
             return;
         }
 
+        if args.simulate {
+            info!(
+                "Batch {} for {} has a reward of {} base units",
+                batch.nonce, token_name, batch.total_fee.amount
+            );
+            match estimate_tx_batch_cost(
+                current_valset,
+                batch,
+                &sigs,
+                &web3,
+                gravity_contract_address,
+                gravity_id,
+                ethereum_public_key,
+                None,
+                true,
+            )
+            .await
+            {
+                Ok(cost) => print_eth_cost(&web3, cost.gas, "Relaying the batch").await,
+                Err(e) => {
+                    error!(
+                        "Simulation failed, relaying this batch would revert {:?}",
+                        e
+                    );
+                    exit(1);
+                }
+            }
+            return;
+        }
+
         info!(
             "Attempting to relay batch {} for {} with reward of {} base units",
-            batch.nonce, args.token, batch.total_fee.amount
+            batch.nonce, token_name, batch.total_fee.amount
         );
 
         let res = send_eth_transaction_batch(
@@ -134,10 +168,18 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
             gravity_contract_address,
             gravity_id.clone(),
             ethereum_key,
+            None,
+            true,
+            &PrivateRelayConfig::default(),
         )
         .await;
         match res {
-            Ok(_) => info!("Batch submission was successful! Check Etherscan and your wallet"),
+            Ok(RelayOutcome::Relayed) => {
+                info!("Batch submission was successful! Check Etherscan and your wallet")
+            }
+            Ok(RelayOutcome::Skipped) => {
+                info!("Batch was not submitted, it has already been relayed or has timed out")
+            }
             Err(e) => error!("Batch submission has failed {:?}", e),
         }
         return;
@@ -158,7 +200,13 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
         Some(btr) => {
 
     if let Some(cosmos_key) = args.cosmos_phrase {
-        info!("{} transactions for token type {} where found in the queue requesting a batch", args.token, btr.tx_count);
+        if args.simulate {
+            let sender = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+            let msg = request_batch_msg(sender, gravity_denom);
+            simulate_cosmos_tx(&contact, &[msg], &[], cosmos_key).await;
+            return;
+        }
+        info!("{} transactions for token type {} where found in the queue requesting a batch", token_name, btr.tx_count);
         let res = send_request_batch(
             cosmos_key,
             gravity_denom,
@@ -180,17 +228,17 @@ pub async fn spot_relay(args: SpotRelayOpts, address_prefix: String) {
         }
 
     } else {
-        warn!("No pending batches for token type {} where found. A potential batch with {} transactions could be requested.", args.token, btr.tx_count);
+        warn!("No pending batches for token type {} where found. A potential batch with {} transactions could be requested.", token_name, btr.tx_count);
         warn!("You have not provided a Gravity private key to send the batch request (this account only needs the smallest amount of dust for fees)");
         warn!("Please re-run this command with the --cosmos-private-key argument filled out");
     }
         },
-        None => info!("There is no pending batch or pending transactions to create a batch for {} please check the token name you are using", args.token),
+        None => info!("There is no pending batch or pending transactions to create a batch for {} please check the token name you are using", token_name),
     }
 }
 
 /// This takes a huamn input of a token name and translates it to the gravity denom that we need to operate
-async fn user_token_name_to_gravity_token(
+pub async fn user_token_name_to_gravity_token(
     user_provided_token_name: String,
     grpc: &mut QueryClient<Channel>,
 ) -> Option<String> {
@@ -1,13 +1,18 @@
 //! Handles configuration structs + saving and loading for Gravity bridge tools
 
 use crate::args::InitOpts;
+use clap::Command;
 use clarity::PrivateKey as EthPrivateKey;
-use gravity_utils::types::{GravityBridgeToolsConfig, TomlGravityBridgeToolsConfig};
+use gravity_utils::types::{
+    EndpointsConfig, GravityBridgeToolsConfig, TomlGravityBridgeToolsConfig,
+};
 use std::{
-    fs::{self, create_dir},
+    env,
+    fs::{self, create_dir_all},
     path::{Path, PathBuf},
     process::exit,
 };
+use toml_edit::{value, Document};
 
 /// The name of the config file, this file is copied
 /// from default-config.toml when generated so that we
@@ -16,11 +21,20 @@ pub const CONFIG_NAME: &str = "config.toml";
 /// The name of the keys file, this file is not expected
 /// to be hand edited.
 pub const KEYS_NAME: &str = "keys.json";
+/// The name of the signature audit log, one JSON record is appended
+/// for every transaction signed by the orchestrator keys
+pub const SIGNATURE_AUDIT_LOG_NAME: &str = "signature-audit.jsonl";
+/// The name of the watch only export, one JSON record is appended for
+/// every confirm or claim the watched orchestrator is expected to submit
+pub const WATCH_ONLY_EXPORT_NAME: &str = "watch-only.jsonl";
 /// The folder name for the config
 pub const CONFIG_FOLDER: &str = ".gbt";
+/// The folder inside the home directory holding one home directory per named profile
+pub const PROFILES_FOLDER: &str = "profiles";
 
 /// The keys storage struct, including encrypted and un-encrypted local keys
 /// un-encrypted keys provide for orchestrator start and relayer start functions
+/// in the keys file either field may instead be an env: or file: reference, see `load_keys`
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
 pub struct KeyStorage {
     pub orchestrator_phrase: Option<String>,
@@ -44,7 +58,7 @@ pub fn init_config(_init_ops: InitOpts, home_dir: PathBuf) {
         );
         warn!("You can delete this folder and run init again, you will lose any keys or other config data!");
     } else {
-        create_dir(home_dir.clone()).expect("Failed to create config directory!");
+        create_dir_all(home_dir.clone()).expect("Failed to create config directory!");
 
         fs::write(home_dir.join(CONFIG_NAME), get_default_config())
             .expect("Unable to write config file");
@@ -53,6 +67,7 @@ pub fn init_config(_init_ops: InitOpts, home_dir: PathBuf) {
             toml::to_string(&KeyStorage::default()).unwrap(),
         )
         .expect("Unable to write config file");
+        set_owner_only_permissions(&home_dir.join(KEYS_NAME));
     }
 }
 
@@ -64,27 +79,175 @@ fn get_default_config() -> String {
     include_str!("default-config.toml").to_string()
 }
 
-pub fn get_home_dir(home_arg: Option<PathBuf>) -> PathBuf {
-    match (dirs::home_dir(), home_arg) {
+/// The home directory to use, a named profile is a complete home directory of its own
+/// inside the profiles folder, so each profile has separate config and keys
+pub fn get_home_dir(home_arg: Option<PathBuf>, profile: Option<&str>) -> PathBuf {
+    let home_dir = match (dirs::home_dir(), home_arg) {
         (_, Some(user_home)) => PathBuf::from(&user_home),
         (Some(default_home_dir), None) => default_home_dir.join(CONFIG_FOLDER),
         (None, None) => {
             error!("Failed to automatically determine your home directory, please provide a path to the --home argument!");
             exit(1);
         }
+    };
+    match profile {
+        Some(name) => {
+            if !is_valid_profile_name(name) {
+                error!(
+                    "Invalid profile name {}, use only letters, numbers, - and _",
+                    name
+                );
+                exit(1);
+            }
+            home_dir.join(PROFILES_FOLDER).join(name)
+        }
+        None => home_dir,
+    }
+}
+
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Makes the endpoints in the config, usually a profile's, the defaults of the matching arguments
+/// of every subcommand. A value on the command line or in the argument's GBT_ environment variable
+/// still takes precedence
+pub fn with_endpoint_defaults(
+    cmd: Command<'static>,
+    endpoints: &EndpointsConfig,
+) -> Command<'static> {
+    let defaults = [
+        ("cosmos-grpc", &endpoints.cosmos_grpc),
+        ("ethereum-rpc", &endpoints.ethereum_rpc),
+        ("address-prefix", &endpoints.address_prefix),
+        (
+            "gravity-contract-address",
+            &endpoints.gravity_contract_address,
+        ),
+    ];
+    let defaults: Vec<(&'static str, &'static str)> = defaults
+        .iter()
+        .filter_map(|(id, value)| {
+            // clap keeps defaults for the life of the command, which is the life of gbt
+            value
+                .as_ref()
+                .map(|v| (*id, &*Box::leak(v.clone().into_boxed_str())))
+        })
+        .collect();
+    set_arg_defaults(cmd, &defaults)
+}
+
+fn set_arg_defaults(
+    mut cmd: Command<'static>,
+    defaults: &[(&'static str, &'static str)],
+) -> Command<'static> {
+    // the endpoints of the jsonrpc server are optional and enable bulk balance queries, a default
+    // would always enable them
+    if cmd.get_name() == "jsonrpc-server" {
+        return cmd;
+    }
+    for (id, value) in defaults {
+        if cmd.get_arguments().any(|a| a.get_id() == *id) {
+            cmd = cmd.mut_arg(*id, |a| a.default_value(value).required(false));
+        }
+    }
+    for sub in cmd.get_subcommands_mut() {
+        *sub = set_arg_defaults(std::mem::take(sub), defaults);
     }
+    cmd
 }
 
-/// Load the config file, this operates at runtime
-pub fn load_config(home_dir: &Path) -> GravityBridgeToolsConfig {
+/// The current config schema version, bump this and add an entry to CONFIG_MIGRATIONS whenever
+/// a release renames or moves config settings
+pub const CONFIG_VERSION: i64 = 1;
+/// Upgrades a config file from the schema version at its index to the next version, returning
+/// true if it changed any settings
+type ConfigMigration = fn(&mut Document) -> bool;
+const CONFIG_MIGRATIONS: [ConfigMigration; CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+/// The prefix for environment variables that override config file settings
+pub const CONFIG_ENV_PREFIX: &str = "GBT_CONFIG__";
+
+/// Load the config file, this operates at runtime. Settings are layered, from lowest to highest
+/// precedence: the defaults, the config file, GBT_CONFIG__ environment variables and finally
+/// `--set` flags. Environment variables name the setting path with double underscores
+/// so GBT_CONFIG__RELAYER__RELAYER_LOOP_SPEED=30 is equivalent to --set relayer.relayer_loop_speed=30
+pub fn load_config(home_dir: &Path, cli_overrides: &[String]) -> GravityBridgeToolsConfig {
     let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
-    if !config_file.exists() {
-        return GravityBridgeToolsConfig::default();
+    let mut config = if config_file.exists() {
+        let contents =
+            fs::read_to_string(&config_file).expect("Could not find config file! Run `gbt init`");
+        let mut document = match contents.parse::<Document>() {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Invalid config! {:?}", e);
+                exit(1);
+            }
+        };
+        match migrate_config(&mut document) {
+            Ok(Some(old_version)) => {
+                // the config may be on a read only mount, it is upgraded in memory either way
+                let backup = config_file.with_extension(format!("toml.v{}.bak", old_version));
+                match fs::write(&backup, &contents)
+                    .and_then(|_| fs::write(&config_file, document.to_string()))
+                {
+                    Ok(()) => info!(
+                        "Upgraded config from version {} to {}, the original was saved to {}",
+                        old_version,
+                        CONFIG_VERSION,
+                        backup.display()
+                    ),
+                    Err(e) => warn!(
+                        "Upgraded config from version {} to {} but could not save it to {}: {}",
+                        old_version,
+                        CONFIG_VERSION,
+                        config_file.display(),
+                        e
+                    ),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!("{}", e);
+                exit(1);
+            }
+        }
+        match document.to_string().parse::<toml::Value>() {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Invalid config! {:?}", e);
+                exit(1);
+            }
+        }
+    } else {
+        toml::Value::Table(toml::value::Table::new())
+    };
+
+    let env_overrides = env::vars().filter_map(|(key, value)| {
+        key.strip_prefix(CONFIG_ENV_PREFIX)
+            .map(|path| (path.to_lowercase().replace("__", "."), value))
+    });
+    for (path, value) in env_overrides {
+        if let Err(e) = set_config_value(&mut config, &path, &value) {
+            error!("Invalid config environment variable for {}: {}", path, e);
+            exit(1);
+        }
+    }
+    for o in cli_overrides {
+        let res = match o.split_once('=') {
+            Some((path, value)) => set_config_value(&mut config, path.trim(), value.trim()),
+            None => Err("expected the format setting.path=value".to_string()),
+        };
+        if let Err(e) = res {
+            error!("Invalid --set {}: {}", o, e);
+            exit(1);
+        }
     }
 
-    let config =
-        fs::read_to_string(config_file).expect("Could not find config file! Run `gbt init`");
-    let val: Result<TomlGravityBridgeToolsConfig, _> = toml::from_str(&config);
+    let val: Result<TomlGravityBridgeToolsConfig, _> = config.try_into();
     match val {
         Ok(v) => v.into(),
         Err(e) => {
@@ -94,7 +257,95 @@ pub fn load_config(home_dir: &Path) -> GravityBridgeToolsConfig {
     }
 }
 
-/// Load the keys file, this operates at runtime
+/// Brings a parsed config up to CONFIG_VERSION in place, returning the version it started at if
+/// any migration changed a setting and the file should be saved. Configs without a version predate
+/// versioning and are version 0
+fn migrate_config(config: &mut Document) -> Result<Option<i64>, String> {
+    let version = match config.get("config_version") {
+        None => 0,
+        Some(v) => match v.as_integer() {
+            Some(v) if v >= 0 => v,
+            _ => return Err(format!("Invalid config_version {}", v)),
+        },
+    };
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "Config version {} is newer than this version of gbt supports ({}), please upgrade gbt",
+            version, CONFIG_VERSION
+        ));
+    } else if version == CONFIG_VERSION {
+        return Ok(None);
+    }
+    let mut changed = false;
+    for migration in CONFIG_MIGRATIONS.iter().skip(version as usize) {
+        changed |= migration(config);
+    }
+    config["config_version"] = value(CONFIG_VERSION);
+    Ok(if changed { Some(version) } else { None })
+}
+
+/// The example config once documented altruistic_acceptable_gas_percentage, which was silently
+/// ignored in favor of the default
+fn migrate_v0_to_v1(config: &mut Document) -> bool {
+    let relayer = match config
+        .get_mut("relayer")
+        .and_then(|r| r.as_table_like_mut())
+    {
+        Some(r) => r,
+        None => return false,
+    };
+    match relayer.remove("altruistic_acceptable_gas_percentage") {
+        Some(v) => {
+            if relayer
+                .get("altruistic_acceptable_gas_price_percentage")
+                .is_none()
+            {
+                relayer.insert("altruistic_acceptable_gas_price_percentage", v);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sets a value in the parsed config given a dot separated path such as relayer.relayer_loop_speed,
+/// creating any missing tables along the way. The value is parsed as a toml literal if possible
+/// so that numbers and booleans keep their types, otherwise it is treated as a string
+fn set_config_value(config: &mut toml::Value, path: &str, value: &str) -> Result<(), String> {
+    let value = match format!("v = {}", value).parse::<toml::Value>() {
+        Ok(toml::Value::Table(mut t)) => t.remove("v").unwrap(),
+        _ => toml::Value::String(value.to_string()),
+    };
+    let mut keys: Vec<&str> = path.split('.').collect();
+    let last = keys
+        .pop()
+        .filter(|k| !k.is_empty())
+        .ok_or("empty setting name")?;
+    let mut table = config;
+    for key in keys {
+        let t = table
+            .as_table_mut()
+            .ok_or(format!("{} is not a section", key))?;
+        table = t
+            .entry(key.to_string())
+            .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+    }
+    table
+        .as_table_mut()
+        .ok_or(format!("{} is not inside a section", last))?
+        .insert(last.to_string(), value);
+    Ok(())
+}
+
+/// Key values starting with this prefix are read from the named environment variable
+pub const KEY_ENV_PREFIX: &str = "env:";
+/// Key values starting with this prefix are read from the given file, which must only be
+/// readable by its owner
+pub const KEY_FILE_PREFIX: &str = "file:";
+
+/// Load the keys file, this operates at runtime. Each key may be stored directly or as a reference
+/// such as `ethereum_key = "env:ORCH_ETH_KEY"` or `orchestrator_phrase = "file:/run/secrets/phrase"`
+/// so that key material can be kept out of the gbt home directory
 pub fn load_keys(home_dir: &Path) -> KeyStorage {
     let keys_file = home_dir.join(CONFIG_FOLDER).with_file_name(KEYS_NAME);
     if !keys_file.exists() {
@@ -105,8 +356,44 @@ pub fn load_keys(home_dir: &Path) -> KeyStorage {
         exit(1);
     }
 
+    let mut keys = load_raw_keys(&keys_file);
+    let mut stores_raw_keys = false;
+    if let Some(table) = keys.as_table_mut() {
+        for (name, value) in table.iter_mut() {
+            let raw = match value.as_str() {
+                Some(v) => v,
+                None => continue,
+            };
+            match resolve_key_reference(raw) {
+                Ok(Some(resolved)) => *value = toml::Value::String(resolved),
+                Ok(None) => stores_raw_keys = true,
+                Err(e) => {
+                    error!("Failed to load {}: {}", name, e);
+                    exit(1);
+                }
+            }
+        }
+    }
+    if stores_raw_keys && !has_owner_only_permissions(&keys_file) {
+        warn!(
+            "Your keys file {} is readable by other users, run `chmod 600 {}` or store your keys with env: or file: references",
+            keys_file.display(),
+            keys_file.display()
+        );
+    }
+
+    match keys.try_into() {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Invalid keys! {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+fn load_raw_keys(keys_file: &Path) -> toml::Value {
     let keys = fs::read_to_string(keys_file).unwrap();
-    match toml::from_str(&keys) {
+    match keys.parse() {
         Ok(v) => v,
         Err(e) => {
             error!("Invalid keys! {:?}", e);
@@ -115,21 +402,95 @@ pub fn load_keys(home_dir: &Path) -> KeyStorage {
     }
 }
 
-/// Saves the keys file, overwriting the existing one
+/// Returns the key a reference points to, or None if the value is not a reference
+fn resolve_key_reference(value: &str) -> Result<Option<String>, String> {
+    if let Some(var) = value.strip_prefix(KEY_ENV_PREFIX) {
+        env::var(var)
+            .map(|v| Some(v.trim().to_string()))
+            .map_err(|_| format!("environment variable {} is not set", var))
+    } else if let Some(path) = value.strip_prefix(KEY_FILE_PREFIX) {
+        let path = Path::new(path);
+        if !has_owner_only_permissions(path) {
+            return Err(format!(
+                "key file {} must only be accessible by its owner, run `chmod 600 {}`",
+                path.display(),
+                path.display()
+            ));
+        }
+        fs::read_to_string(path)
+            .map(|v| Some(v.trim().to_string()))
+            .map_err(|e| format!("could not read key file {}: {}", path.display(), e))
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(unix)]
+fn has_owner_only_permissions(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match fs::metadata(path) {
+        Ok(m) => m.permissions().mode() & 0o077 == 0,
+        // missing files are reported when they are read
+        Err(_) => true,
+    }
+}
+
+#[cfg(not(unix))]
+fn has_owner_only_permissions(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn set_owner_only_permissions(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(0o600)) {
+        warn!(
+            "Failed to restrict permissions on {} {:?}",
+            path.display(),
+            e
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn set_owner_only_permissions(_path: &Path) {}
+
+/// Saves the keys file, overwriting the existing one. Keys stored as env: or file: references are
+/// kept as references unless the key itself has changed
 pub fn save_keys(home_dir: &Path, updated_keys: KeyStorage) {
     let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(KEYS_NAME);
+    let mut updated = toml::Value::try_from(&updated_keys).unwrap();
     if !config_file.exists() {
         info!(
             "Config file at {} not detected, using defaults, use `gbt init` to generate a config.",
             config_file.to_str().unwrap()
         );
+    } else {
+        let existing = load_keys(home_dir);
+        let raw = load_raw_keys(&config_file);
+        let unchanged = |name: &str| match name {
+            "orchestrator_phrase" => {
+                existing.orchestrator_phrase == updated_keys.orchestrator_phrase
+            }
+            "ethereum_key" => existing.ethereum_key == updated_keys.ethereum_key,
+            _ => false,
+        };
+        if let (Some(raw), Some(updated)) = (raw.as_table(), updated.as_table_mut()) {
+            for (name, value) in raw {
+                let is_reference = value
+                    .as_str()
+                    .map(|v| v.starts_with(KEY_ENV_PREFIX) || v.starts_with(KEY_FILE_PREFIX))
+                    .unwrap_or(false);
+                if is_reference && unchanged(name) {
+                    updated.insert(name.clone(), value.clone());
+                }
+            }
+        }
     }
 
-    fs::write(
-        home_dir.join(KEYS_NAME),
-        toml::to_string(&updated_keys).unwrap(),
-    )
-    .expect("Unable to write config file");
+    let keys_file = home_dir.join(KEYS_NAME);
+    fs::write(&keys_file, toml::to_string(&updated).unwrap()).expect("Unable to write config file");
+    set_owner_only_permissions(&keys_file);
 }
 
 #[cfg(test)]
@@ -145,4 +506,97 @@ mod tests {
         let res: GravityBridgeToolsConfig = res.into();
         assert_eq!(res, GravityBridgeToolsConfig::default());
     }
+
+    #[test]
+    fn test_migrate_config() {
+        let mut config: Document = r#"
+            # tuned for our relayer
+            [relayer]
+            # keep this low
+            altruistic_acceptable_gas_percentage = 0.1
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(migrate_config(&mut config), Ok(Some(0)));
+        assert_eq!(config["config_version"].as_integer(), Some(CONFIG_VERSION));
+        assert!(config.to_string().contains("# tuned for our relayer"));
+        let res: TomlGravityBridgeToolsConfig = toml::from_str(&config.to_string()).unwrap();
+        assert_eq!(res.relayer.altruistic_acceptable_gas_price_percentage, 0.1);
+        // already migrated configs are left alone
+        assert_eq!(migrate_config(&mut config), Ok(None));
+
+        // unversioned configs that no migration changes are not rewritten
+        let mut config: Document = "[relayer]\nrelayer_loop_speed = 30\n".parse().unwrap();
+        assert_eq!(migrate_config(&mut config), Ok(None));
+
+        let mut config: Document = "config_version = 999".parse().unwrap();
+        assert!(migrate_config(&mut config).is_err());
+        let mut config: Document = get_default_config().parse().unwrap();
+        assert_eq!(migrate_config(&mut config), Ok(None));
+    }
+
+    #[test]
+    fn test_resolve_key_reference() {
+        assert_eq!(resolve_key_reference("0xabcd"), Ok(None));
+
+        env::set_var("GBT_TEST_KEY_REFERENCE", " 0xabcd\n");
+        assert_eq!(
+            resolve_key_reference("env:GBT_TEST_KEY_REFERENCE"),
+            Ok(Some("0xabcd".to_string()))
+        );
+        assert!(resolve_key_reference("env:GBT_TEST_KEY_REFERENCE_UNSET").is_err());
+
+        let key_file = env::temp_dir().join("gbt_test_key_reference");
+        fs::write(&key_file, "word word word\n").unwrap();
+        set_owner_only_permissions(&key_file);
+        assert_eq!(
+            resolve_key_reference(&format!("file:{}", key_file.display())),
+            Ok(Some("word word word".to_string()))
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&key_file, fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(resolve_key_reference(&format!("file:{}", key_file.display())).is_err());
+        }
+        fs::remove_file(key_file).unwrap();
+    }
+
+    #[test]
+    fn test_set_config_value() {
+        let mut config: toml::Value = get_default_config().parse().unwrap();
+        set_config_value(&mut config, "relayer.relayer_loop_speed", "30").unwrap();
+        set_config_value(&mut config, "orchestrator.relayer_enabled", "true").unwrap();
+        set_config_value(
+            &mut config,
+            "relayer.batch_relaying_mode.mode",
+            "EveryBatch",
+        )
+        .unwrap();
+        set_config_value(&mut config, "metrics.metrics_bind", "0.0.0.0:6631").unwrap();
+        assert!(set_config_value(&mut config, "orchestrator.relayer_enabled.x", "1").is_err());
+
+        let res: TomlGravityBridgeToolsConfig = config.try_into().unwrap();
+        let res: GravityBridgeToolsConfig = res.into();
+        assert_eq!(res.relayer.relayer_loop_speed, 30);
+        assert!(res.orchestrator.relayer_enabled);
+        assert_eq!(
+            res.relayer.batch_relaying_mode,
+            gravity_utils::types::BatchRelayingMode::EveryBatch
+        );
+        assert_eq!(res.metrics.metrics_bind, "0.0.0.0:6631");
+    }
+
+    #[test]
+    fn test_profile_home_dir() {
+        let home = get_home_dir(Some(PathBuf::from("/tmp/gbt")), Some("testnet"));
+        assert_eq!(home, PathBuf::from("/tmp/gbt/profiles/testnet"));
+        assert_eq!(
+            get_home_dir(Some(PathBuf::from("/tmp/gbt")), None),
+            PathBuf::from("/tmp/gbt")
+        );
+        assert!(is_valid_profile_name("local_net-2"));
+        assert!(!is_valid_profile_name("../mainnet"));
+        assert!(!is_valid_profile_name(""));
+    }
 }
@@ -0,0 +1,362 @@
+//! Tracks sendToCosmos deposits to a set of destination addresses for exchanges and custodians.
+//! Each deposit moves from seen, to confirmed once it has the configured number of Ethereum
+//! confirmations for its token, to attested once the Cosmos chain has observed a claim matching the
+//! deposit log and credited the destination. A deposit whose log disappears in a reorg, or whose
+//! event nonce is attested as a different deposit, is reported as removed and the blocks from it
+//! onward are searched again. Every transition is printed as one JSON line so it can be piped into
+//! whatever credits user accounts
+
+use crate::args::DepositWatchOpts;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_deposit_attestation, get_gravity_params, DepositAttestation};
+use deep_space::Address as CosmosAddress;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::SENT_TO_COSMOS_EVENT_SIG;
+use gravity_utils::types::{DepositWatchConfig, EthereumEvent, SendToCosmosEvent};
+use serde_json::json;
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap};
+use std::process::exit;
+use std::time::Duration;
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+
+/// The most blocks searched for deposits in a single request, nodes limit the range of eth_getLogs
+const BLOCKS_PER_SEARCH: u64 = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum DepositState {
+    Seen,
+    Confirmed,
+    Attested,
+    /// the deposit log was reorged out, it is no longer tracked
+    Removed,
+}
+
+impl DepositState {
+    fn name(self) -> &'static str {
+        match self {
+            DepositState::Seen => "seen",
+            DepositState::Confirmed => "confirmed",
+            DepositState::Attested => "attested",
+            DepositState::Removed => "removed",
+        }
+    }
+}
+
+pub async fn deposit_watch(
+    args: DepositWatchOpts,
+    address_prefix: String,
+    config: DepositWatchConfig,
+) {
+    let destinations: Vec<CosmosAddress> = config
+        .destinations
+        .iter()
+        .map(|d| match d.parse() {
+            Ok(a) => a,
+            Err(e) => {
+                error!("Invalid deposit_watch destination {} {:?}", d, e);
+                exit(1);
+            }
+        })
+        .collect();
+    if destinations.is_empty() {
+        error!("No destinations to watch, add them to the [deposit_watch] section of the config");
+        exit(1);
+    }
+    let mut confirmations: HashMap<EthAddress, u64> = HashMap::new();
+    for (token, depth) in config.confirmations.iter() {
+        match token.parse() {
+            Ok(t) => {
+                confirmations.insert(t, *depth);
+            }
+            Err(e) => {
+                error!(
+                    "Invalid deposit_watch confirmations token {} {:?}",
+                    token, e
+                );
+                exit(1);
+            }
+        }
+    }
+    let required = |erc20: EthAddress| {
+        confirmations
+            .get(&erc20)
+            .copied()
+            .unwrap_or(config.default_confirmations)
+    };
+
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+
+    let mut next_block: Uint256 = match args.start_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    info!(
+        "Watching deposits to {} destinations from Ethereum block {}",
+        destinations.len(),
+        next_block
+    );
+    // deposits not yet attested, by event nonce
+    let mut tracked: BTreeMap<u64, (SendToCosmosEvent, DepositState)> = BTreeMap::new();
+    loop {
+        let latest_block = match web3.eth_block_number().await {
+            Ok(b) => b,
+            Err(e) => {
+                warn!("Could not get the latest Ethereum block {:?}", e);
+                delay_for(Duration::from_secs(args.interval)).await;
+                continue;
+            }
+        };
+
+        while next_block <= latest_block {
+            let end = if latest_block - next_block >= BLOCKS_PER_SEARCH.into() {
+                next_block + (BLOCKS_PER_SEARCH - 1).into()
+            } else {
+                latest_block
+            };
+            match search_deposits(&web3, gravity_contract_address, next_block, end).await {
+                Ok(deposits) => {
+                    for deposit in deposits {
+                        let ours = deposit
+                            .validated_destination
+                            .as_ref()
+                            .is_some_and(|d| destinations.contains(d));
+                        if ours && !tracked.contains_key(&deposit.event_nonce) {
+                            let depth = required(deposit.erc20);
+                            report(&deposit, DepositState::Seen, latest_block, depth);
+                            tracked.insert(deposit.event_nonce, (deposit, DepositState::Seen));
+                        }
+                    }
+                    next_block = end + 1u8.into();
+                }
+                Err(e) => {
+                    warn!(
+                        "Could not search blocks {} to {} for deposits {:?}",
+                        next_block, end, e
+                    );
+                    break;
+                }
+            }
+        }
+
+        for (deposit, state) in tracked.values_mut() {
+            let depth = required(deposit.erc20);
+            let attested = match get_deposit_attestation(&mut grpc, deposit).await {
+                Ok(DepositAttestation::Attested) => true,
+                Ok(DepositAttestation::Pending) => false,
+                Ok(DepositAttestation::Mismatched(claim)) => {
+                    warn!(
+                        "Event nonce {} was attested as a different deposit {:?}",
+                        deposit.event_nonce, claim
+                    );
+                    report(deposit, DepositState::Removed, latest_block, depth);
+                    next_block = min(next_block, deposit.block_height);
+                    *state = DepositState::Removed;
+                    continue;
+                }
+                Err(e) => {
+                    // confirmations can still be reported without it
+                    warn!(
+                        "Could not check the attestation for event nonce {} {:?}",
+                        deposit.event_nonce, e
+                    );
+                    false
+                }
+            };
+            let new_state = current_state(deposit, latest_block, attested, depth);
+            // confirmations only count while the deposit is still in the block it was seen in
+            if new_state == DepositState::Confirmed && *state == DepositState::Seen {
+                match still_included(&web3, gravity_contract_address, deposit).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        report(deposit, DepositState::Removed, latest_block, depth);
+                        next_block = min(next_block, deposit.block_height);
+                        *state = DepositState::Removed;
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Could not check deposit {} is still included {:?}",
+                            deposit.event_nonce, e
+                        );
+                        continue;
+                    }
+                }
+            }
+            for transition in transitions(*state, new_state) {
+                report(deposit, transition, latest_block, depth);
+            }
+            *state = new_state.max(*state);
+        }
+        tracked.retain(|_, (_, state)| {
+            *state != DepositState::Attested && *state != DepositState::Removed
+        });
+
+        delay_for(Duration::from_secs(args.interval)).await;
+    }
+}
+
+async fn search_deposits(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    start: Uint256,
+    end: Uint256,
+) -> Result<Vec<SendToCosmosEvent>, GravityError> {
+    let logs = web3
+        .check_for_events(
+            start,
+            Some(end),
+            vec![gravity_contract_address],
+            vec![SENT_TO_COSMOS_EVENT_SIG],
+        )
+        .await?;
+    SendToCosmosEvent::from_logs(&logs)
+}
+
+/// True if the deposit log is still in the block it was seen in, false if it was reorged out
+async fn still_included(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    deposit: &SendToCosmosEvent,
+) -> Result<bool, GravityError> {
+    let deposits = search_deposits(
+        web3,
+        gravity_contract_address,
+        deposit.block_height,
+        deposit.block_height,
+    )
+    .await?;
+    Ok(deposits.contains(deposit))
+}
+
+/// The blocks on top of the deposit's block, counting the block itself
+fn confirmations(deposit: &SendToCosmosEvent, latest_block: Uint256) -> Uint256 {
+    if latest_block >= deposit.block_height {
+        latest_block - deposit.block_height + 1u8.into()
+    } else {
+        0u8.into()
+    }
+}
+
+fn current_state(
+    deposit: &SendToCosmosEvent,
+    latest_block: Uint256,
+    attested: bool,
+    required_confirmations: u64,
+) -> DepositState {
+    if attested {
+        DepositState::Attested
+    } else if confirmations(deposit, latest_block) >= required_confirmations.into() {
+        DepositState::Confirmed
+    } else {
+        DepositState::Seen
+    }
+}
+
+/// The states passed through moving from `from` to `to`, so that a deposit attested before it was
+/// seen to be confirmed still reports both
+fn transitions(from: DepositState, to: DepositState) -> Vec<DepositState> {
+    [DepositState::Confirmed, DepositState::Attested]
+        .iter()
+        .copied()
+        .filter(|s| *s > from && *s <= to)
+        .collect()
+}
+
+fn report(
+    deposit: &SendToCosmosEvent,
+    state: DepositState,
+    latest_block: Uint256,
+    required_confirmations: u64,
+) {
+    info!(
+        "Deposit {} of {} {} to {} is {}",
+        deposit.event_nonce,
+        deposit.amount,
+        deposit.erc20,
+        deposit.destination,
+        state.name()
+    );
+    println!(
+        "{}",
+        json!({
+            "state": state.name(),
+            "event_nonce": deposit.event_nonce,
+            "erc20": deposit.erc20.to_string(),
+            "amount": deposit.amount.to_string(),
+            "sender": deposit.sender.to_string(),
+            "destination": deposit.destination,
+            "block_height": deposit.block_height.to_string(),
+            "confirmations": confirmations(deposit, latest_block).to_string(),
+            "required_confirmations": required_confirmations,
+        })
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deposit_states() {
+        let deposit = SendToCosmosEvent {
+            erc20: zero_address(),
+            sender: zero_address(),
+            destination: String::new(),
+            validated_destination: None,
+            amount: 1u8.into(),
+            event_nonce: 10,
+            block_height: 100u8.into(),
+        };
+        assert_eq!(
+            current_state(&deposit, 110u8.into(), false, 12),
+            DepositState::Seen
+        );
+        assert_eq!(
+            current_state(&deposit, 111u8.into(), false, 12),
+            DepositState::Confirmed
+        );
+        assert_eq!(
+            current_state(&deposit, 101u8.into(), true, 12),
+            DepositState::Attested
+        );
+        assert_eq!(confirmations(&deposit, 99u8.into()), 0u8.into());
+
+        assert_eq!(
+            transitions(DepositState::Seen, DepositState::Attested),
+            vec![DepositState::Confirmed, DepositState::Attested]
+        );
+        assert_eq!(
+            transitions(DepositState::Confirmed, DepositState::Confirmed),
+            vec![]
+        );
+        assert_eq!(
+            transitions(DepositState::Confirmed, DepositState::Seen),
+            vec![]
+        );
+    }
+}
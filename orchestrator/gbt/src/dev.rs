@@ -0,0 +1,18 @@
+use crate::args::GenFixturesOpts;
+use ethereum_gravity::fixtures::generate_fixtures;
+use std::fs;
+use std::process::exit;
+
+pub fn gen_fixtures(args: GenFixturesOpts) {
+    let fixtures = serde_json::to_string_pretty(&generate_fixtures()).unwrap();
+    match args.output {
+        Some(path) => match fs::write(&path, fixtures + "\n") {
+            Ok(()) => info!("Wrote fixtures to {}", path.display()),
+            Err(e) => {
+                error!("Failed to write fixtures to {} {:?}", path.display(), e);
+                exit(1);
+            }
+        },
+        None => println!("{}", fixtures),
+    }
+}
@@ -0,0 +1,106 @@
+//! Lets a user ask a running orchestrator or relayer for a JSON dump of its in memory state.
+//! The two processes communicate through a request file in the shared home directory, the
+//! running process watches for it, writes the dump to the path it contains and removes it.
+
+use crate::args::DumpStateOpts;
+use gravity_utils::debug_state::get_debug_state;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep as delay_for;
+
+/// The name of the request file, it contains the path the dump should be written to
+pub const DUMP_REQUEST_NAME: &str = "state-dump-request";
+/// How often running processes check for a dump request
+const DUMP_REQUEST_CHECK_SPEED: Duration = Duration::from_secs(2);
+
+/// Runs alongside the orchestrator or relayer main loops, writing out the current state whenever
+/// a dump is requested with `gbt dump-state`
+pub async fn state_dump_listener(home_dir: PathBuf) {
+    let request_file = home_dir.join(DUMP_REQUEST_NAME);
+    loop {
+        if request_file.exists() {
+            match fs::read_to_string(&request_file) {
+                Ok(output) => {
+                    let output = output.trim();
+                    match write_state(Path::new(output)) {
+                        Ok(()) => info!("Dumped orchestrator state to {}", output),
+                        Err(e) => error!("Failed to dump orchestrator state to {} {:?}", output, e),
+                    }
+                }
+                Err(e) => error!("Failed to read state dump request {:?}", e),
+            }
+            if let Err(e) = fs::remove_file(&request_file) {
+                error!("Failed to remove state dump request {:?}", e);
+            }
+        }
+        delay_for(DUMP_REQUEST_CHECK_SPEED).await;
+    }
+}
+
+fn write_state(output: &Path) -> std::io::Result<()> {
+    let state = serde_json::to_string_pretty(&get_debug_state())?;
+    fs::write(output, state)
+}
+
+pub async fn dump_state(args: DumpStateOpts, home_dir: PathBuf) {
+    let request_file = home_dir.join(DUMP_REQUEST_NAME);
+    if !home_dir.exists() {
+        error!(
+            "Home directory {} does not exist, use the same --home as the running orchestrator",
+            home_dir.display()
+        );
+        exit(1);
+    }
+    let output = match args.output {
+        Some(o) => o,
+        None => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            home_dir.join(format!("state-dump-{}.json", now))
+        }
+    };
+    // the running process may have a different working directory
+    let output = if output.is_relative() {
+        std::env::current_dir().unwrap().join(output)
+    } else {
+        output
+    };
+    if output.exists() {
+        error!(
+            "{} already exists, refusing to overwrite it",
+            output.display()
+        );
+        exit(1);
+    }
+
+    if let Err(e) = fs::write(&request_file, output.to_string_lossy().as_bytes()) {
+        error!("Failed to write state dump request {:?}", e);
+        exit(1);
+    }
+
+    let timeout = Duration::from_secs(args.timeout);
+    let start = Instant::now();
+    while request_file.exists() {
+        if Instant::now() - start > timeout {
+            let _ = fs::remove_file(&request_file);
+            error!(
+                "No orchestrator or relayer using {} responded within {}s, is it running?",
+                home_dir.display(),
+                args.timeout
+            );
+            exit(1);
+        }
+        delay_for(Duration::from_millis(500)).await;
+    }
+
+    if output.exists() {
+        info!("State written to {}", output.display());
+    } else {
+        error!("The running process failed to write its state, check its logs for details");
+        exit(1);
+    }
+}
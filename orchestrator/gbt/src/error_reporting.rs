@@ -0,0 +1,107 @@
+//! Optional reporting of panics and errors to a Sentry compatible server, so that maintainers
+//! get actionable crash data from the field. Reports carry the gbt version, the running command
+//! and a fingerprint of the config, never keys.
+
+use crate::log_format::LogFormat;
+use crate::log_levels::LevelFilteredLogger;
+use crate::log_throttle::ThrottledLogger;
+use gravity_utils::audit_log::sync_audit_log;
+use gravity_utils::types::{ErrorReportingConfig, GravityBridgeToolsConfig};
+use gravity_utils::watch_only::sync_watch_only_export;
+use sentry::ClientInitGuard;
+use std::backtrace::Backtrace;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::panic;
+use std::time::Duration;
+
+/// Log target used for panic messages, Sentry already captures panics directly so these are only
+/// recorded as breadcrumbs to avoid reporting each panic twice
+const PANIC_LOG_TARGET: &str = "gbt::panic";
+/// How long to wait for queued reports to be sent before a panic unwinds the process
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Installs the global logger, a thin wrapper around env_logger that forwards errors to Sentry as
+/// events and lower levels as breadcrumbs. Until `start_error_reporting` is called this has no
+/// effect beyond env_logger itself. Module levels can be changed later, see `log_levels`, records
+/// are written as text or JSON lines, see `log_format`
+pub fn init_logging(default_level: &str, format: LogFormat) {
+    let logger = LevelFilteredLogger::new(default_level, format);
+    let logger = sentry_log::SentryLogger::with_dest(logger).filter(|md| {
+        if md.target() == PANIC_LOG_TARGET {
+            sentry_log::LogFilter::Breadcrumb
+        } else {
+            sentry_log::default_filter(md)
+        }
+    });
+    let logger = ThrottledLogger::new(logger, PANIC_LOG_TARGET);
+    // the max level was set when the filter was built
+    log::set_boxed_logger(Box::new(logger)).expect("Failed to set logger");
+}
+
+/// Starts reporting if a DSN is configured, the returned guard must be held until exit so that
+/// queued reports are flushed
+pub fn start_error_reporting(
+    config: &GravityBridgeToolsConfig,
+    command: &str,
+) -> Option<ClientInitGuard> {
+    let ErrorReportingConfig { dsn, environment } = &config.error_reporting;
+    let dsn = dsn.as_ref()?;
+    let guard = sentry::init((
+        dsn.as_str(),
+        sentry::ClientOptions {
+            release: Some(env!("CARGO_PKG_VERSION").into()),
+            environment: environment.clone().map(|e| e.into()),
+            send_default_pii: false,
+            ..Default::default()
+        },
+    ));
+    if !guard.is_enabled() {
+        warn!("Invalid error reporting DSN, error reporting is disabled");
+        return None;
+    }
+    sentry::configure_scope(|scope| {
+        scope.set_tag("command", command);
+        scope.set_tag("config_fingerprint", config_fingerprint(config));
+        scope.set_tag("relayer_enabled", config.orchestrator.relayer_enabled);
+        scope.set_tag(
+            "batch_request_mode",
+            format!("{:?}", config.relayer.batch_request_mode),
+        );
+    });
+    info!("Error reporting enabled");
+    Some(guard)
+}
+
+/// A short stable identifier for the config in use, lets reports from the same setup be grouped
+/// without uploading the config itself. The config file never contains keys
+fn config_fingerprint(config: &GravityBridgeToolsConfig) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(config).unwrap().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Replaces silent process death on panic with a logged message and backtrace, an alert through
+/// error reporting (if enabled) and a flush of any pending reports before the process unwinds.
+/// The signature audit log and the watch only export are synced to disk as well. gbt keeps no
+/// slashing-protection store, signing state lives on chain, so there is no such store to flush.
+/// Must be called after `start_error_reporting` so that the Sentry hook is chained
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let backtrace = Backtrace::force_capture();
+        error!(target: PANIC_LOG_TARGET, "gbt has crashed! {}", info);
+        error!(target: PANIC_LOG_TARGET, "Backtrace:\n{}", backtrace);
+        error!(
+            target: PANIC_LOG_TARGET,
+            "Please report this at https://github.com/Gravity-Bridge/Gravity-Bridge/issues"
+        );
+        // the previous hook is Sentry's, which reports the panic, or the default hook
+        previous(info);
+        sync_audit_log();
+        sync_watch_only_export();
+        if let Some(client) = sentry::Hub::current().client() {
+            client.flush(Some(SHUTDOWN_FLUSH_TIMEOUT));
+        }
+    }));
+}
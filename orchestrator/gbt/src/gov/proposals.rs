@@ -1,14 +1,23 @@
 use crate::args::AirdropProposalOpts;
 use crate::args::EmergencyBridgeHaltProposalOpts;
 use crate::args::IbcMetadataProposalOpts;
+use crate::client::multisig::amino_coin;
+use crate::ledger::{send_with_ledger, Ledger};
+use crate::simulate::print_cosmos_simulation;
 use crate::{args::OracleUnhaltProposalOpts, utils::TIMEOUT};
 use cosmos_gravity::proposals::AirdropProposalJsonUnparsed;
 use cosmos_gravity::proposals::{
-    submit_airdrop_proposal, submit_ibc_metadata_proposal, submit_pause_bridge_proposal,
-    submit_unhalt_bridge_proposal, IbcMetadataProposalJson, PauseBridgeProposalJson,
-    UnhaltBridgeProposalJson,
+    airdrop_proposal_content, ibc_metadata_proposal_content, pause_bridge_proposal_content,
+    simulate_gov_proposal, submit_airdrop_proposal, submit_ibc_metadata_proposal,
+    submit_pause_bridge_proposal, submit_unhalt_bridge_proposal, unhalt_bridge_proposal_content,
+    IbcMetadataProposalJson, PauseBridgeProposalJson, UnhaltBridgeProposalJson,
 };
+use deep_space::client::type_urls::MSG_SUBMIT_PROPOSAL_TYPE_URL;
+use deep_space::utils::encode_any;
+use deep_space::{Address as CosmosAddress, Coin};
+use gravity_proto::cosmos_sdk_proto::cosmos::gov::v1beta1::MsgSubmitProposal;
 use gravity_utils::connection_prep::create_rpc_connections;
+use serde_json::json;
 use std::convert::TryInto;
 use std::{fs, process::exit};
 
@@ -21,6 +30,18 @@ pub async fn submit_ibc_metadata(opts: IbcMetadataProposalOpts, prefix: String)
             let proposal: Result<IbcMetadataProposalJson, _> = serde_json::from_str(&file_contents);
             match proposal {
                 Ok(proposal_json) => {
+                    if opts.simulate {
+                        let res = simulate_gov_proposal(
+                            ibc_metadata_proposal_content(proposal_json.into()),
+                            opts.deposit,
+                            opts.fees.clone(),
+                            &contact,
+                            opts.cosmos_phrase,
+                        )
+                        .await;
+                        print_cosmos_simulation(res, &[opts.fees]);
+                        return;
+                    }
                     let res = submit_ibc_metadata_proposal(
                         proposal_json.into(),
                         opts.deposit,
@@ -67,6 +88,22 @@ pub async fn submit_airdrop(opts: AirdropProposalOpts, prefix: String) {
                 serde_json::from_str(&file_contents);
             match proposal {
                 Ok(proposal_json) => {
+                    if opts.simulate {
+                        let res = simulate_gov_proposal(
+                            airdrop_proposal_content(
+                                proposal_json
+                                    .try_into()
+                                    .expect("Invalid address in proposal.json"),
+                            ),
+                            opts.deposit,
+                            opts.fees.clone(),
+                            &contact,
+                            opts.cosmos_phrase,
+                        )
+                        .await;
+                        print_cosmos_simulation(res, &[opts.fees]);
+                        return;
+                    }
                     let res = submit_airdrop_proposal(
                         proposal_json
                             .try_into()
@@ -106,6 +143,17 @@ pub async fn submit_airdrop(opts: AirdropProposalOpts, prefix: String) {
 }
 
 pub async fn submit_emergency_bridge_halt(opts: EmergencyBridgeHaltProposalOpts, prefix: String) {
+    let ledger = if opts.ledger {
+        match Ledger::connect(opts.ledger_account, &prefix) {
+            Ok(l) => Some(l),
+            Err(e) => {
+                error!("Failed to connect to the Ledger {}", e);
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
     let connections = create_rpc_connections(prefix, Some(opts.cosmos_grpc), None, TIMEOUT).await;
     let contact = connections.contact.unwrap();
 
@@ -114,15 +162,50 @@ pub async fn submit_emergency_bridge_halt(opts: EmergencyBridgeHaltProposalOpts,
             let proposal: Result<PauseBridgeProposalJson, _> = serde_json::from_str(&file_contents);
             match proposal {
                 Ok(proposal_json) => {
-                    let res = submit_pause_bridge_proposal(
-                        proposal_json,
-                        opts.deposit,
-                        opts.fees,
-                        &contact,
-                        opts.cosmos_phrase,
-                        Some(TIMEOUT),
-                    )
-                    .await;
+                    let res = match (&ledger, opts.cosmos_phrase) {
+                        (Some(ledger), _) => {
+                            let amino = pause_bridge_proposal_amino(
+                                &proposal_json,
+                                &opts.deposit,
+                                ledger.address(),
+                            );
+                            let msg = MsgSubmitProposal {
+                                content: Some(pause_bridge_proposal_content(proposal_json)),
+                                initial_deposit: vec![opts.deposit.into()],
+                                proposer: ledger.address().to_string(),
+                            };
+                            let msg = encode_any(msg, MSG_SUBMIT_PROPOSAL_TYPE_URL.to_string());
+                            send_with_ledger(&contact, ledger, msg, amino, opts.fees, String::new())
+                                .await
+                        }
+                        (None, Some(key)) => {
+                            if opts.simulate {
+                                let res = simulate_gov_proposal(
+                                    pause_bridge_proposal_content(proposal_json),
+                                    opts.deposit,
+                                    opts.fees.clone(),
+                                    &contact,
+                                    key,
+                                )
+                                .await;
+                                print_cosmos_simulation(res, &[opts.fees]);
+                                return;
+                            }
+                            submit_pause_bridge_proposal(
+                                proposal_json,
+                                opts.deposit,
+                                opts.fees,
+                                &contact,
+                                key,
+                                Some(TIMEOUT),
+                            )
+                            .await
+                        }
+                        (None, None) => {
+                            error!("Provide either --cosmos-phrase or --ledger");
+                            exit(1);
+                        }
+                    };
                     match res {
                         Ok(r) => info!("Successfully submitted proposal with txid {}", r.txhash),
                         Err(e) => {
@@ -150,6 +233,35 @@ pub async fn submit_emergency_bridge_halt(opts: EmergencyBridgeHaltProposalOpts,
     }
 }
 
+/// The amino JSON of the MsgSubmitProposal built from `pause_bridge_proposal_content`, which is
+/// what a Ledger displays and signs. Keys are in sorted order, matching the sorted JSON the Ledger
+/// signs over
+fn pause_bridge_proposal_amino(
+    proposal: &PauseBridgeProposalJson,
+    deposit: &Coin,
+    proposer: CosmosAddress,
+) -> serde_json::Value {
+    json!({
+        "type": "cosmos-sdk/MsgSubmitProposal",
+        "value": {
+            "content": {
+                "type": "cosmos-sdk/ParameterChangeProposal",
+                "value": {
+                    "changes": [{
+                        "key": "BridgeActive",
+                        "subspace": "gravity",
+                        "value": format!("{}", proposal.paused),
+                    }],
+                    "description": proposal.description,
+                    "title": proposal.title,
+                }
+            },
+            "initial_deposit": [amino_coin(deposit)],
+            "proposer": proposer.to_string(),
+        }
+    })
+}
+
 pub async fn submit_oracle_unhalt(opts: OracleUnhaltProposalOpts, prefix: String) {
     let connections = create_rpc_connections(prefix, Some(opts.cosmos_grpc), None, TIMEOUT).await;
     let contact = connections.contact.unwrap();
@@ -160,6 +272,18 @@ pub async fn submit_oracle_unhalt(opts: OracleUnhaltProposalOpts, prefix: String
                 serde_json::from_str(&file_contents);
             match proposal {
                 Ok(proposal_json) => {
+                    if opts.simulate {
+                        let res = simulate_gov_proposal(
+                            unhalt_bridge_proposal_content(proposal_json.into()),
+                            opts.deposit,
+                            opts.fees.clone(),
+                            &contact,
+                            opts.cosmos_phrase,
+                        )
+                        .await;
+                        print_cosmos_simulation(res, &[opts.fees]);
+                        return;
+                    }
                     let res = submit_unhalt_bridge_proposal(
                         proposal_json.into(),
                         opts.deposit,
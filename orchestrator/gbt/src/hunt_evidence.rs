@@ -0,0 +1,353 @@
+//! Searches Ethereum for Gravity contract calls approved by signatures over checkpoints that never
+//! existed on the Cosmos chain. Every updateValset, submitBatch and submitLogicCall executed in the
+//! search range is decoded, and the validator signatures it carries are checked against the chain's
+//! archive of past checkpoints by simulating a MsgSubmitBadSignatureEvidence. Confirms submitted
+//! to Cosmos are verified by the Gravity module as they arrive, so they can never carry bad
+//! signatures, Ethereum is the only place such signatures can be found.
+
+use crate::args::HuntEvidenceOpts;
+use crate::config::{config_exists, load_keys};
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::send::{simulate_bad_signature_evidence, submit_bad_signature_evidence};
+use cosmos_gravity::utils::BadSignatureEvidence;
+use deep_space::{Contact, CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::calldata::{decode_gravity_call, GravityCall};
+use ethereum_gravity::message_signatures::{
+    encode_logic_call_confirm_hashed, encode_tx_batch_confirm_hashed, encode_valset_confirm_hashed,
+};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::{
+    LOGIC_CALL_EVENT_SIG, TRANSACTION_BATCH_EXECUTED_EVENT_SIG, VALSET_UPDATED_EVENT_SIG,
+};
+use gravity_utils::types::SigWithAddress;
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+/// A single slashable signature, ready to be submitted as a MsgSubmitBadSignatureEvidence
+#[derive(Serialize)]
+struct EvidenceRecord {
+    /// valset, batch or logic_call
+    kind: String,
+    nonce: u64,
+    /// The Ethereum transaction the signature was submitted in
+    ethereum_tx: String,
+    /// The Ethereum signed message hash that was signed
+    checkpoint: String,
+    eth_signer: EthAddress,
+    signature: String,
+    /// The protobuf encoded subject of the evidence message
+    subject_type_url: String,
+    subject_value: String,
+    #[serde(skip)]
+    subject: Option<BadSignatureEvidence>,
+    #[serde(skip)]
+    sig: Option<SigWithAddress>,
+}
+
+pub async fn hunt_evidence(args: HuntEvidenceOpts, address_prefix: String, home_dir: &Path) {
+    let cosmos_key = match args.cosmos_phrase {
+        Some(k) => k,
+        None => {
+            let stored = if config_exists(home_dir) {
+                load_keys(home_dir).orchestrator_phrase
+            } else {
+                None
+            };
+            match stored {
+                Some(phrase) => CosmosPrivateKey::from_phrase(&phrase, "").unwrap(),
+                None => {
+                    error!("Evidence is checked against the chain by simulating its submission, this requires a Cosmos key");
+                    error!("Use --cosmos-phrase or store an orchestrator key with `gbt keys set-orchestrator-key`");
+                    exit(1);
+                }
+            }
+        }
+    };
+
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc.clone()),
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let eth_client = HttpClient::new(&args.ethereum_rpc);
+
+    let params = match get_gravity_params(&mut grpc).await {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to get Gravity parameters {:?}", e);
+            exit(1);
+        }
+    };
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+    let end_block = match args.end_block {
+        Some(b) => b.into(),
+        None => web3.eth_block_number().await.unwrap(),
+    };
+    let start_block: Uint256 = args.start_block.into();
+
+    let mut evidence = Vec::new();
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block > BLOCKS_TO_SEARCH.into() {
+            current_block + BLOCKS_TO_SEARCH.into()
+        } else {
+            end_block
+        };
+        info!("Searching blocks {} to {}", current_block, end_search);
+        let tx_hashes =
+            match find_gravity_calls(&web3, gravity_contract_address, current_block, end_search)
+                .await
+            {
+                Ok(h) => h,
+                Err(e) => {
+                    error!(
+                        "Failed to search blocks {} to {}, is your Ethereum node working? {:?}",
+                        current_block, end_search, e
+                    );
+                    exit(1);
+                }
+            };
+        for tx_hash in tx_hashes {
+            match check_transaction(
+                &eth_client,
+                &contact,
+                cosmos_key,
+                &params.gravity_id,
+                &tx_hash,
+            )
+            .await
+            {
+                Ok(e) => evidence.extend(e),
+                Err(e) => warn!(
+                    "Could not check Ethereum transaction 0x{} {:?}",
+                    bytes_to_hex_str(&tx_hash),
+                    e
+                ),
+            }
+        }
+        current_block = end_search + 1u8.into();
+    }
+
+    if evidence.is_empty() {
+        info!(
+            "No bad signatures found between blocks {} and {}",
+            start_block, end_block
+        );
+        return;
+    }
+    for e in evidence.iter() {
+        warn!(
+            "Bad signature by {} over {} {} in Ethereum tx {}",
+            e.eth_signer, e.kind, e.nonce, e.ethereum_tx
+        );
+    }
+    warn!("Found {} slashable signatures", evidence.len());
+
+    if let Some(output) = args.output {
+        match fs::write(&output, serde_json::to_string_pretty(&evidence).unwrap()) {
+            Ok(()) => info!("Evidence written to {}", output.display()),
+            Err(e) => error!("Failed to write evidence to {} {:?}", output.display(), e),
+        }
+    }
+
+    if args.submit {
+        // clap requires fees when submitting
+        let fee = args.fees.unwrap();
+        for e in evidence {
+            let (subject, sig) = (e.subject.unwrap(), e.sig.unwrap());
+            match submit_bad_signature_evidence(
+                cosmos_key,
+                fee.clone(),
+                &contact,
+                subject,
+                sig.eth_signature,
+            )
+            .await
+            {
+                Ok(res) => info!(
+                    "Submitted evidence against {} in tx {}",
+                    e.eth_signer, res.txhash
+                ),
+                Err(err) => error!(
+                    "Failed to submit evidence against {} {:?}",
+                    e.eth_signer, err
+                ),
+            }
+        }
+    } else {
+        info!("Run again with --submit to submit this evidence to the chain");
+    }
+}
+
+/// Returns the hashes of all transactions in the range that updated the valset, executed a batch
+/// or executed a logic call, in the order they were executed
+pub(crate) async fn find_gravity_calls(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    start: Uint256,
+    end: Uint256,
+) -> Result<Vec<Vec<u8>>, GravityError> {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for sig in [
+        VALSET_UPDATED_EVENT_SIG,
+        TRANSACTION_BATCH_EXECUTED_EVENT_SIG,
+        LOGIC_CALL_EVENT_SIG,
+    ] {
+        let logs = web3
+            .check_for_events(start, Some(end), vec![gravity_contract_address], vec![sig])
+            .await?;
+        for log in logs {
+            if let Some(hash) = log.transaction_hash {
+                let hash = hash.to_vec();
+                if seen.insert(hash.clone()) {
+                    out.push(hash);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+async fn get_transaction_input(
+    eth_client: &HttpClient,
+    tx_hash: &[u8],
+) -> Result<Vec<u8>, GravityError> {
+    let tx: Value = eth_client
+        .request_method(
+            "eth_getTransactionByHash",
+            vec![format!("0x{}", bytes_to_hex_str(tx_hash))],
+            TIMEOUT,
+        )
+        .await?;
+    let input = tx["input"].as_str().ok_or_else(|| {
+        GravityError::EthereumContractError("Transaction has no input".to_string())
+    })?;
+    hex_str_to_bytes(input)
+        .map_err(|e| GravityError::EthereumContractError(format!("Invalid input {:?}", e)))
+}
+
+/// Decodes a single Gravity contract call and returns evidence for every valid signature it
+/// carries if the chain confirms the signed checkpoint never existed
+async fn check_transaction(
+    eth_client: &HttpClient,
+    contact: &Contact,
+    cosmos_key: CosmosPrivateKey,
+    gravity_id: &str,
+    tx_hash: &[u8],
+) -> Result<Vec<EvidenceRecord>, GravityError> {
+    let input = get_transaction_input(eth_client, tx_hash).await?;
+    let our_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    let gravity_id = gravity_id.to_string();
+    let (kind, nonce, checkpoint, subject, signatures) = match decode_gravity_call(
+        &input,
+        our_address,
+    )? {
+        Some(GravityCall::ValsetUpdate {
+            new_valset,
+            signatures,
+            ..
+        }) => (
+            "valset",
+            new_valset.nonce,
+            encode_valset_confirm_hashed(gravity_id, new_valset.clone()),
+            BadSignatureEvidence::Valset(new_valset),
+            signatures,
+        ),
+        Some(GravityCall::SubmitBatch {
+            batch, signatures, ..
+        }) => (
+            "batch",
+            batch.nonce,
+            encode_tx_batch_confirm_hashed(gravity_id, batch.clone()),
+            BadSignatureEvidence::Batch(batch),
+            signatures,
+        ),
+        Some(GravityCall::SubmitLogicCall {
+            call, signatures, ..
+        }) => (
+            "logic_call",
+            call.invalidation_nonce,
+            encode_logic_call_confirm_hashed(gravity_id, call.clone()),
+            BadSignatureEvidence::LogicCall(call),
+            signatures,
+        ),
+        None => {
+            // the call was made through another contract, the signatures are still in the
+            // calldata but not at a location we can decode
+            warn!(
+                "Ethereum transaction 0x{} did not call the Gravity contract directly, skipping",
+                bytes_to_hex_str(tx_hash)
+            );
+            return Ok(Vec::new());
+        }
+    };
+
+    let valid: Vec<SigWithAddress> = signatures
+        .into_iter()
+        .filter(|s| s.eth_signature.recover(&checkpoint).ok() == Some(s.eth_address))
+        .collect();
+    if valid.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // the chain rejects evidence over any checkpoint it has ever created, one simulation is
+    // enough to tell whether every signature over this checkpoint is slashable
+    if let Err(e) =
+        simulate_bad_signature_evidence(cosmos_key, contact, &subject, &valid[0].eth_signature)
+            .await
+    {
+        if e.to_string().contains("Checkpoint exists") {
+            trace!("{} {} checkpoint exists on chain", kind, nonce);
+        } else {
+            warn!(
+                "Could not check {} {} against the chain, it may be evidence {:?}",
+                kind, nonce, e
+            );
+        }
+        return Ok(Vec::new());
+    }
+
+    let any = subject.to_any();
+    Ok(valid
+        .into_iter()
+        .map(|sig| EvidenceRecord {
+            kind: kind.to_string(),
+            nonce,
+            ethereum_tx: format!("0x{}", bytes_to_hex_str(tx_hash)),
+            checkpoint: format!("0x{}", bytes_to_hex_str(&checkpoint)),
+            eth_signer: sig.eth_address,
+            signature: format!("0x{}", bytes_to_hex_str(&sig.eth_signature.to_bytes())),
+            subject_type_url: any.type_url.clone(),
+            subject_value: format!("0x{}", bytes_to_hex_str(&any.value)),
+            subject: Some(subject.clone()),
+            sig: Some(sig),
+        })
+        .collect())
+}
@@ -1,14 +1,33 @@
+use gravity_utils::connection_prep::create_rpc_connections;
+use jsonrpc_server::balances::BalanceApi;
 use jsonrpc_server::server::run as run_server;
 
 use crate::args::JsonrpcServerOpts;
+use crate::utils::TIMEOUT;
+
+pub async fn jsonrpc_server(args: JsonrpcServerOpts, prefix: String) {
+    let balance_api = match (args.cosmos_grpc, args.ethereum_rpc) {
+        (Some(cosmos_grpc), Some(ethereum_rpc)) => {
+            let connections =
+                create_rpc_connections(prefix, Some(cosmos_grpc), Some(ethereum_rpc), TIMEOUT)
+                    .await;
+            Some(BalanceApi {
+                contact: connections.contact.unwrap(),
+                grpc_client: connections.grpc.unwrap(),
+                ethereum_rpc: connections.web3.unwrap().get_url(),
+                timeout: TIMEOUT,
+            })
+        }
+        _ => None,
+    };
 
-pub async fn jsonrpc_server(args: JsonrpcServerOpts) {
     let res = run_server(
         args.domain,
         args.port,
         args.use_ssl,
         args.cert_chain_path,
         args.cert_key_path,
+        balance_api,
     )
     .await;
 
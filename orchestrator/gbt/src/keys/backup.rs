@@ -0,0 +1,282 @@
+//! Passphrase encrypted backup and restore of the keys and config stored by gbt, so that moving
+//! an orchestrator to a new machine only requires copying a single file
+
+use crate::args::{InitOpts, KeysBackupOpts, KeysRestoreOpts};
+use crate::config::{
+    config_exists, init_config, load_keys, save_keys, KeyStorage, CONFIG_FOLDER, CONFIG_NAME,
+};
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::Address as EthAddress;
+use deep_space::{CosmosPrivateKey, PrivateKey};
+use rand::RngCore;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The current backup file format version
+const BACKUP_VERSION: u32 = 1;
+/// scrypt parameters for new backups, the parameters are stored in the backup so these may be
+/// raised in the future without breaking old backups
+const SCRYPT_LOG_N: u8 = 17;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// The encrypted backup file as written to disk
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct KeyBackup {
+    version: u32,
+    scrypt_log_n: u8,
+    scrypt_r: u32,
+    scrypt_p: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The decrypted contents of a backup
+#[derive(Serialize, Deserialize, Debug)]
+struct KeyBackupContents {
+    /// Unix timestamp the backup was created at
+    created: u64,
+    gbt_version: String,
+    keys: KeyStorage,
+    /// The addresses registered for these keys, included so the backup can be checked against
+    /// the chain without decrypting the keys themselves
+    orchestrator_address: Option<String>,
+    ethereum_address: Option<EthAddress>,
+    config: Option<String>,
+}
+
+pub fn backup_keys(home_dir: &Path, opts: KeysBackupOpts, prefix: &str) {
+    if !config_exists(home_dir) {
+        error!("Please run `gbt init` before running this command!");
+        exit(1);
+    }
+    if opts.output.exists() {
+        error!(
+            "{} already exists, refusing to overwrite it",
+            opts.output.display()
+        );
+        exit(1);
+    }
+    let keys = load_keys(home_dir);
+    if keys.orchestrator_phrase.is_none() && keys.ethereum_key.is_none() {
+        error!("There are no keys stored to back up!");
+        exit(1);
+    }
+    let orchestrator_address = keys.orchestrator_phrase.as_ref().map(|p| {
+        CosmosPrivateKey::from_phrase(p, "")
+            .expect("Failed to decode key in keyfile. Did you edit it manually?")
+            .to_address(prefix)
+            .unwrap()
+            .to_string()
+    });
+    let ethereum_address = keys.ethereum_key.map(|k| k.to_address());
+    let config = fs::read_to_string(home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME)).ok();
+    let contents = KeyBackupContents {
+        created: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        gbt_version: env!("CARGO_PKG_VERSION").to_string(),
+        keys,
+        orchestrator_address: orchestrator_address.clone(),
+        ethereum_address,
+        config,
+    };
+
+    let passphrase = match opts.passphrase {
+        Some(p) => p,
+        None => {
+            let p = prompt_passphrase("Enter a passphrase to encrypt the backup: ");
+            if p != prompt_passphrase("Repeat the passphrase: ") {
+                error!("Passphrases do not match!");
+                exit(1);
+            }
+            p
+        }
+    };
+    if passphrase.is_empty() {
+        error!("The backup passphrase can not be empty!");
+        exit(1);
+    }
+
+    let backup = encrypt_backup(&contents, &passphrase);
+    if let Err(e) = fs::write(&opts.output, serde_json::to_string_pretty(&backup).unwrap()) {
+        error!("Failed to write backup {:?}", e);
+        exit(1);
+    }
+    info!("Backed up keys to {}", opts.output.display());
+    if let Some(a) = orchestrator_address {
+        info!("Orchestrator address {}", a);
+    }
+    if let Some(a) = ethereum_address {
+        info!("Ethereum address {}", a);
+    }
+    info!("Keep this file and its passphrase safe, anyone with both controls your keys");
+}
+
+pub fn restore_keys(home_dir: PathBuf, opts: KeysRestoreOpts) {
+    let backup = match fs::read_to_string(&opts.input) {
+        Ok(b) => b,
+        Err(e) => {
+            error!("Failed to read backup {} {:?}", opts.input.display(), e);
+            exit(1);
+        }
+    };
+    let backup: KeyBackup = match serde_json::from_str(&backup) {
+        Ok(b) => b,
+        Err(e) => {
+            error!("{} is not a gbt key backup {:?}", opts.input.display(), e);
+            exit(1);
+        }
+    };
+    if backup.version > BACKUP_VERSION {
+        error!(
+            "This backup was made by a newer version of gbt (format {}), please upgrade gbt",
+            backup.version
+        );
+        exit(1);
+    }
+    let passphrase = match opts.passphrase {
+        Some(p) => p,
+        None => prompt_passphrase("Enter the backup passphrase: "),
+    };
+    let contents = match decrypt_backup(&backup, &passphrase) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("{}", e);
+            exit(1);
+        }
+    };
+
+    if !config_exists(&home_dir) {
+        init_config(InitOpts {}, home_dir.clone());
+    } else if !opts.overwrite {
+        let existing = load_keys(&home_dir);
+        if existing.orchestrator_phrase.is_some() || existing.ethereum_key.is_some() {
+            error!(
+                "{} already has keys stored, use --overwrite to replace them",
+                home_dir.display()
+            );
+            exit(1);
+        }
+    }
+
+    save_keys(&home_dir, contents.keys);
+    if let Some(config) = contents.config {
+        let config_file = home_dir.join(CONFIG_FOLDER).with_file_name(CONFIG_NAME);
+        fs::write(config_file, config).expect("Unable to write config file");
+    }
+    info!(
+        "Restored keys backed up by gbt {} at unix time {}",
+        contents.gbt_version, contents.created
+    );
+    if let Some(a) = contents.orchestrator_address {
+        info!("Orchestrator address {}", a);
+    }
+    if let Some(a) = contents.ethereum_address {
+        info!("Ethereum address {}", a);
+    }
+    info!("Verify these are the addresses registered for your validator with `gbt keys show --on-chain`");
+}
+
+fn prompt_passphrase(prompt: &str) -> String {
+    match rpassword::prompt_password(prompt) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to read passphrase {:?}", e);
+            exit(1);
+        }
+    }
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<[u8; 32], String> {
+    let params = scrypt::Params::new(log_n, r, p, 32)
+        .map_err(|e| format!("Invalid backup key derivation parameters {:?}", e))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| format!("Failed to derive backup key {:?}", e))?;
+    Ok(key)
+}
+
+fn encrypt_backup(contents: &KeyBackupContents, passphrase: &str) -> KeyBackup {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P).unwrap();
+    let cipher = Aes256Gcm::new_from_slice(&key).unwrap();
+    let plaintext = serde_json::to_vec(contents).unwrap();
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .expect("Failed to encrypt backup");
+    KeyBackup {
+        version: BACKUP_VERSION,
+        scrypt_log_n: SCRYPT_LOG_N,
+        scrypt_r: SCRYPT_R,
+        scrypt_p: SCRYPT_P,
+        salt: bytes_to_hex_str(&salt),
+        nonce: bytes_to_hex_str(&nonce),
+        ciphertext: bytes_to_hex_str(&ciphertext),
+    }
+}
+
+fn decrypt_backup(backup: &KeyBackup, passphrase: &str) -> Result<KeyBackupContents, String> {
+    let decode = |v: &str| hex_str_to_bytes(v).map_err(|_| "Corrupted backup file".to_string());
+    let salt = decode(&backup.salt)?;
+    let nonce = decode(&backup.nonce)?;
+    let ciphertext = decode(&backup.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err("Corrupted backup file".to_string());
+    }
+
+    let key = derive_key(
+        passphrase,
+        &salt,
+        backup.scrypt_log_n,
+        backup.scrypt_r,
+        backup.scrypt_p,
+    )?;
+    let cipher = Aes256Gcm::new_from_slice(&key).unwrap();
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Incorrect passphrase or corrupted backup file".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Corrupted backup contents {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_round_trip() {
+        let contents = KeyBackupContents {
+            created: 1,
+            gbt_version: "1.0.0".to_string(),
+            keys: KeyStorage {
+                orchestrator_phrase: Some("a phrase".to_string()),
+                ethereum_key: None,
+            },
+            orchestrator_address: None,
+            ethereum_address: None,
+            config: Some("config_version = 1".to_string()),
+        };
+        let backup = encrypt_backup(&contents, "hunter2");
+        let res = decrypt_backup(&backup, "hunter2").unwrap();
+        assert_eq!(res.keys, contents.keys);
+        assert_eq!(res.config, contents.config);
+        assert!(decrypt_backup(&backup, "hunter3").is_err());
+    }
+}
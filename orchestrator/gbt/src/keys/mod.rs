@@ -1,39 +1,64 @@
+pub mod backup;
 pub mod register_orchestrator_address;
+pub mod show;
 
-use crate::args::RecoverFundsOpts;
+use crate::args::{KeysShowOpts, RecoverFundsOpts};
 use crate::client::cosmos_to_eth::cosmos_to_eth;
-use crate::utils::TIMEOUT;
+use crate::simulate::simulate_cosmos_tx;
+use crate::utils::{display_denom, TIMEOUT};
 use crate::{
     args::{SetEthereumKeyOpts, SetOrchestratorKeyOpts},
     config::{config_exists, load_keys, save_keys},
 };
 use cosmos_gravity::utils::get_reasonable_send_to_eth_fee;
-use deep_space::{Coin, CosmosPrivateKey, PrivateKey};
+use deep_space::client::type_urls::MSG_SEND_TYPE_URL;
+use deep_space::{Coin, CosmosPrivateKey, Msg, PrivateKey};
+use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::MsgSend;
 use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::token_display::format_coin;
 use std::{path::Path, process::exit};
 
-pub fn show_keys(home_dir: &Path, prefix: &str) {
+pub async fn show_keys(home_dir: &Path, prefix: &str, opts: KeysShowOpts) {
     if !config_exists(home_dir) {
         error!("Please run `gbt init` before running this command!");
         exit(1);
     }
     let keys = load_keys(home_dir);
-    match keys.orchestrator_phrase {
+    let orchestrator = match keys.orchestrator_phrase {
         Some(v) => {
             let key = CosmosPrivateKey::from_phrase(&v, "")
                 .expect("Failed to decode key in keyfile. Did you edit it manually?");
             let address = key.to_address(prefix).unwrap();
             info!("Your Orchestrator key, {}", address);
+            Some(address)
         }
-        None => info!("You do not have an Orchestrator key set"),
-    }
-    match keys.ethereum_key {
+        None => {
+            info!("You do not have an Orchestrator key set");
+            None
+        }
+    };
+    let ethereum = match keys.ethereum_key {
         Some(v) => {
             let address = v.to_address();
             info!("Your Ethereum key, {}", address);
+            Some(address)
         }
-        None => info!("You do not have an Ethereum key set"),
+        None => {
+            info!("You do not have an Ethereum key set");
+            None
+        }
+    };
+    if !opts.on_chain || (orchestrator.is_none() && ethereum.is_none()) {
+        return;
     }
+    show::show_on_chain(
+        orchestrator,
+        ethereum,
+        opts.cosmos_grpc,
+        opts.ethereum_rpc,
+        prefix.to_string(),
+    )
+    .await
 }
 
 pub fn set_eth_key(home_dir: &Path, opts: SetEthereumKeyOpts) {
@@ -93,6 +118,21 @@ pub async fn recover_funds(args: RecoverFundsOpts, address_prefix: String) {
             exit(1);
         }
 
+        if args.simulate {
+            let sender_address = args.ethereum_key.to_address(&address_prefix).unwrap();
+            let msg = Msg::new(
+                MSG_SEND_TYPE_URL,
+                MsgSend {
+                    from_address: sender_address.to_string(),
+                    to_address: cosmos_destination.to_string(),
+                    amount: vec![args.amount.into()],
+                },
+            );
+            let fee: Vec<Coin> = args.cosmos_fee.into_iter().collect();
+            simulate_cosmos_tx(&contact, &[msg], &fee, args.ethereum_key).await;
+            return;
+        }
+
         let res = contact
             .send_coins(
                 args.amount.clone(),
@@ -104,18 +144,18 @@ pub async fn recover_funds(args: RecoverFundsOpts, address_prefix: String) {
             .await;
         if res.is_err() {
             error!(
-                "Received an error response when sending {} of {} to {}, are you sure that your account has enough funds? Error: {}",
-                args.amount.amount.to_string(),
-                args.amount.denom,
+                "Received an error response when sending {} ({}) to {}, are you sure that your account has enough funds? Error: {}",
+                format_coin(&contact, None, &args.amount).await,
+                display_denom(&contact, &args.amount.denom).await,
                 cosmos_destination,
                 res.err().unwrap(),
             );
             exit(1);
         }
         info!(
-            "Sent {} of {} to {}, Gravity Tx ID: {}",
-            args.amount.amount,
-            args.amount.denom,
+            "Sent {} ({}) to {}, Gravity Tx ID: {}",
+            format_coin(&contact, None, &args.amount).await,
+            display_denom(&contact, &args.amount.denom).await,
             cosmos_destination.to_string(),
             res.unwrap().txhash
         );
@@ -174,6 +214,8 @@ pub async fn recover_funds(args: RecoverFundsOpts, address_prefix: String) {
             chain_fee,
             args.eth_bridge_fee.unwrap(),
             args.eth_destination.unwrap(),
+            None,
+            args.simulate,
         )
         .await;
     } else {
@@ -6,16 +6,27 @@ use crate::config::config_exists;
 use crate::config::load_keys;
 use crate::config::save_keys;
 use crate::config::KeyStorage;
+use crate::ledger::{send_with_ledger, Ledger};
+use crate::simulate::simulate_cosmos_tx;
 use crate::utils::TIMEOUT;
+use clarity::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
-use cosmos_gravity::send::set_gravity_delegate_addresses;
+use cosmos_gravity::send::{
+    get_memo, set_gravity_delegate_addresses, set_gravity_delegate_addresses_msg,
+    MSG_SET_ORCHESTRATOR_ADDRESS_TYPE_URL,
+};
 use deep_space::{
     mnemonic::Mnemonic,
     private_key::{CosmosPrivateKey, PrivateKey},
+    Address as CosmosAddress, Contact,
 };
+use gravity_proto::gravity::MsgSetOrchestratorAddress;
 use gravity_utils::connection_prep::check_for_fee;
 use gravity_utils::connection_prep::{create_rpc_connections, wait_for_cosmos_node_ready};
+use prost::Message;
+use prost_types::Any;
 use rand::{thread_rng, Rng};
+use serde_json::json;
 
 pub async fn register_orchestrator_address(
     args: RegisterOrchestratorAddressOpts,
@@ -38,7 +49,25 @@ pub async fn register_orchestrator_address(
     let contact = connections.contact.unwrap();
     wait_for_cosmos_node_ready(&contact).await;
 
-    let validator_addr = validator_key.to_address(&contact.get_prefix()).unwrap();
+    let ledger = if args.ledger {
+        match Ledger::connect(args.ledger_account, &contact.get_prefix()) {
+            Ok(l) => Some(l),
+            Err(e) => {
+                error!("Failed to connect to the Ledger {}", e);
+                exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    let validator_addr = match (&ledger, &validator_key) {
+        (Some(l), _) => l.address(),
+        (None, Some(k)) => k.to_address(&contact.get_prefix()).unwrap(),
+        (None, None) => {
+            error!("Provide either --validator-phrase or --ledger");
+            exit(1);
+        }
+    };
     check_for_fee(&fee, validator_addr, &contact).await;
 
     // Set the cosmos key to either the cli value, the value in the config, or a generated
@@ -83,16 +112,41 @@ pub async fn register_orchestrator_address(
 
     let ethereum_address = ethereum_key.to_address();
     let cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
-    let res = set_gravity_delegate_addresses(
-        &contact,
-        ethereum_address,
-        cosmos_address,
-        validator_key,
-        fee.clone(),
-    )
-    .await
-    .expect("Failed to update Eth address");
-    let res = contact.wait_for_tx(res, TIMEOUT).await;
+    if args.simulate {
+        // generated keys are thrown away, nothing is registered or saved
+        let msg = set_gravity_delegate_addresses_msg(
+            &contact,
+            validator_addr,
+            ethereum_address,
+            cosmos_address,
+        );
+        simulate_cosmos_tx(&contact, &[msg], &[fee], validator_key.unwrap()).await;
+        return;
+    }
+    let res = match (&ledger, validator_key) {
+        (Some(ledger), _) => {
+            let (msg, amino) = set_orchestrator_address_amino(
+                &contact,
+                validator_addr,
+                ethereum_address,
+                cosmos_address,
+            );
+            send_with_ledger(&contact, ledger, msg, amino, fee.clone(), get_memo()).await
+        }
+        (None, Some(key)) => {
+            let res = set_gravity_delegate_addresses(
+                &contact,
+                ethereum_address,
+                cosmos_address,
+                key,
+                fee.clone(),
+            )
+            .await
+            .expect("Failed to update Eth address");
+            contact.wait_for_tx(res, TIMEOUT).await
+        }
+        (None, None) => unreachable!(),
+    };
 
     if let Err(e) = res {
         error!("Failed trying to register delegate addresses error {:?}, correct the error and try again", e);
@@ -139,3 +193,35 @@ pub async fn register_orchestrator_address(
         save_keys(&home_dir, new_keys);
     }
 }
+
+/// The MsgSetOrchestratorAddress sent by `set_gravity_delegate_addresses` and its amino JSON form,
+/// for signing on a Ledger
+fn set_orchestrator_address_amino(
+    contact: &Contact,
+    validator_address: CosmosAddress,
+    delegate_eth_address: EthAddress,
+    delegate_cosmos_address: CosmosAddress,
+) -> (Any, serde_json::Value) {
+    let valoper = validator_address
+        .to_bech32(format!("{}valoper", contact.get_prefix()))
+        .unwrap();
+    // keys are in sorted order, matching the sorted JSON the Ledger signs over
+    let amino = json!({
+        "type": "gravity/MsgSetOrchestratorAddress",
+        "value": {
+            "eth_address": delegate_eth_address.to_string(),
+            "orchestrator": delegate_cosmos_address.to_string(),
+            "validator": valoper.to_string(),
+        }
+    });
+    let msg = MsgSetOrchestratorAddress {
+        validator: valoper.to_string(),
+        orchestrator: delegate_cosmos_address.to_string(),
+        eth_address: delegate_eth_address.to_string(),
+    };
+    let msg = Any {
+        type_url: MSG_SET_ORCHESTRATOR_ADDRESS_TYPE_URL.to_string(),
+        value: msg.encode_to_vec(),
+    };
+    (msg, amino)
+}
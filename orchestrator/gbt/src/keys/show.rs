@@ -0,0 +1,203 @@
+//! The on-chain half of `gbt keys show --on-chain`, cross-references the local keys against the delegate key
+//! registrations, the active validator set, balances on both chains and pending signing work
+
+use crate::utils::TIMEOUT;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::{
+    get_oldest_unsigned_logic_calls, get_oldest_unsigned_transaction_batches,
+    get_oldest_unsigned_valsets,
+};
+use deep_space::{Address as CosmosAddress, Coin};
+use gravity_proto::gravity::{
+    QueryDelegateKeysByEthAddress, QueryDelegateKeysByOrchestratorAddress,
+};
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::num_conversion::print_eth;
+
+/// A delegate key registration as the chain records it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Registration {
+    validator: String,
+    orchestrator: String,
+    eth_address: String,
+}
+
+pub async fn show_on_chain(
+    orchestrator: Option<CosmosAddress>,
+    ethereum: Option<EthAddress>,
+    cosmos_grpc: String,
+    ethereum_rpc: String,
+    prefix: String,
+) {
+    let connections = create_rpc_connections(
+        prefix.clone(),
+        Some(cosmos_grpc),
+        Some(ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    let by_orchestrator = match orchestrator {
+        Some(o) => grpc
+            .get_delegate_key_by_orchestrator(QueryDelegateKeysByOrchestratorAddress {
+                orchestrator_address: o.to_string(),
+            })
+            .await
+            .ok()
+            .map(|r| {
+                let r = r.into_inner();
+                Registration {
+                    validator: r.validator_address,
+                    orchestrator: o.to_string(),
+                    eth_address: r.eth_address,
+                }
+            }),
+        None => None,
+    };
+    let by_eth = match ethereum {
+        Some(e) => grpc
+            .get_delegate_key_by_eth(QueryDelegateKeysByEthAddress {
+                eth_address: e.to_string(),
+            })
+            .await
+            .ok()
+            .map(|r| {
+                let r = r.into_inner();
+                Registration {
+                    validator: r.validator_address,
+                    orchestrator: r.orchestrator_address,
+                    eth_address: e.to_string(),
+                }
+            }),
+        None => None,
+    };
+    for problem in registration_problems(
+        orchestrator.is_some(),
+        ethereum.is_some(),
+        by_orchestrator.as_ref(),
+        by_eth.as_ref(),
+    ) {
+        warn!("{}", problem);
+    }
+
+    if let Some(registration) = by_orchestrator.as_ref().or(by_eth.as_ref()) {
+        info!(
+            "Registered as the delegate keys of validator {}",
+            registration.validator
+        );
+        match contact.get_active_validators().await {
+            Ok(validators) => {
+                match validators
+                    .iter()
+                    .find(|v| v.operator_address == registration.validator)
+                {
+                    Some(v) => info!(
+                        "Validator {} is bonded with {} tokens",
+                        v.description
+                            .as_ref()
+                            .map(|d| d.moniker.as_str())
+                            .unwrap_or(""),
+                        v.tokens
+                    ),
+                    None => warn!(
+                        "Validator {} is not in the active set, it does not need to sign until it is bonded",
+                        registration.validator
+                    ),
+                }
+            }
+            Err(e) => warn!("Failed to get the active validator set {:?}", e),
+        }
+    }
+
+    if let Some(o) = orchestrator {
+        match contact.get_balances(o).await {
+            Ok(b) if b.is_empty() => {
+                warn!("Your Orchestrator address holds nothing, it can not pay fees for claims")
+            }
+            Ok(b) => info!("Your Orchestrator balance, {}", Coin::display_list(&b)),
+            Err(e) => warn!("Failed to get the Orchestrator balance {:?}", e),
+        }
+        let valsets = get_oldest_unsigned_valsets(&mut grpc, o, prefix.clone()).await;
+        let batches = get_oldest_unsigned_transaction_batches(&mut grpc, o, prefix.clone()).await;
+        let calls = get_oldest_unsigned_logic_calls(&mut grpc, o, prefix.clone()).await;
+        match (valsets, batches, calls) {
+            (Ok(v), Ok(b), Ok(c)) => info!(
+                "Pending work to sign, {} valsets, {} batches and {} logic calls",
+                v.len(),
+                b.len(),
+                c.len()
+            ),
+            _ => warn!("Failed to get the pending work for {}", o),
+        }
+    }
+    if let Some(e) = ethereum {
+        match web3.eth_get_balance(e).await {
+            Ok(b) => info!("Your Ethereum balance, {} ETH", print_eth(b)),
+            Err(e) => warn!("Failed to get the Ethereum balance {:?}", e),
+        }
+    }
+}
+
+/// Explains every way the local keys disagree with the registrations found for them
+fn registration_problems(
+    has_orchestrator: bool,
+    has_ethereum: bool,
+    by_orchestrator: Option<&Registration>,
+    by_eth: Option<&Registration>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if has_orchestrator && by_orchestrator.is_none() {
+        problems.push("Your Orchestrator key is not registered to any validator".to_string());
+    }
+    if has_ethereum && by_eth.is_none() {
+        problems.push("Your Ethereum key is not registered to any validator".to_string());
+    }
+    match (by_orchestrator, by_eth) {
+        (Some(o), Some(e)) if o.validator != e.validator => problems.push(format!(
+            "Your Orchestrator key is registered to {} but your Ethereum key to {}, the keys are mixed up between validators",
+            o.validator, e.validator
+        )),
+        (Some(o), None) if has_ethereum => problems.push(format!(
+            "Your Orchestrator key is registered with Ethereum key {}, set it with `gbt keys set-ethereum-key`",
+            o.eth_address
+        )),
+        (None, Some(e)) if has_orchestrator => problems.push(format!(
+            "Your Ethereum key is registered with Orchestrator key {}, set it with `gbt keys set-orchestrator-key`",
+            e.orchestrator
+        )),
+        _ => {}
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(validator: &str) -> Registration {
+        Registration {
+            validator: validator.to_string(),
+            orchestrator: "gravity1orch".to_string(),
+            eth_address: "0xeth".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_registration_problems() {
+        let a = registration("gravityvaloper1a");
+        let b = registration("gravityvaloper1b");
+        assert!(registration_problems(true, true, Some(&a), Some(&a)).is_empty());
+        assert!(registration_problems(false, false, None, None).is_empty());
+        assert_eq!(
+            registration_problems(true, true, Some(&a), Some(&b)).len(),
+            1
+        );
+        let problems = registration_problems(true, true, Some(&a), None);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[1].contains("0xeth"));
+        assert_eq!(registration_problems(true, false, None, None).len(), 1);
+    }
+}
@@ -0,0 +1,330 @@
+//! Signing Cosmos transactions with the Cosmos app on a Ledger hardware wallet, so that a key held on
+//! a Ledger never has to be given to gbt as a phrase. The Cosmos app only signs legacy amino JSON,
+//! so transactions are built with SIGN_MODE_LEGACY_AMINO_JSON in the same way as multisig ones.
+//! Talking to the device needs hidapi, which is only built with the `ledger` feature, without it
+//! connecting fails with an explanation
+#![cfg_attr(not(feature = "ledger"), allow(dead_code))]
+
+use crate::client::multisig::{amino_sign_doc, SECP256K1_PUBKEY_TYPE_URL};
+use crate::utils::TIMEOUT;
+use deep_space::error::CosmosGrpcError;
+use deep_space::{Address as CosmosAddress, Coin, Contact, Fee};
+use gravity_proto::cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
+use gravity_proto::cosmos_sdk_proto::cosmos::base::v1beta1::Coin as ProtoCoin;
+use gravity_proto::cosmos_sdk_proto::cosmos::crypto::secp256k1::PubKey as Secp256k1PubKey;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::signing::v1beta1::SignMode;
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::mode_info::{Single, Sum};
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::{
+    AuthInfo, BroadcastMode, Fee as ProtoFee, ModeInfo, SignerInfo, TxBody, TxRaw,
+};
+#[cfg(feature = "ledger")]
+use ledger_apdu::APDUCommand;
+#[cfg(feature = "ledger")]
+use ledger_transport_hid::hidapi::HidApi;
+#[cfg(feature = "ledger")]
+use ledger_transport_hid::{LedgerHIDError, TransportNativeHID};
+use prost::Message;
+use prost_types::Any;
+use secp256k1::ecdsa::Signature;
+use std::fmt;
+
+const CLA: u8 = 0x55;
+const INS_GET_ADDR_SECP256K1: u8 = 0x04;
+const INS_SIGN_SECP256K1: u8 = 0x02;
+const PAYLOAD_INIT: u8 = 0x00;
+const PAYLOAD_ADD: u8 = 0x01;
+const PAYLOAD_LAST: u8 = 0x02;
+/// P2 of a sign request, the app also supports SIGN_MODE_TEXTUAL which the chain does not
+const P2_AMINO_JSON: u8 = 0x00;
+const CHUNK_SIZE: usize = 250;
+const APDU_OK: u16 = 0x9000;
+const HARDENED: u32 = 0x8000_0000;
+/// The coin type of the Cosmos app, addresses are derived as on any Cosmos chain
+const COSMOS_COIN_TYPE: u32 = 118;
+
+/// The gas limit of transactions signed on a Ledger, they can not be simulated first as a key in
+/// memory can be without a confirmation on the device for every attempt
+pub const LEDGER_GAS_LIMIT: u64 = 300_000;
+
+#[derive(Debug)]
+pub enum LedgerError {
+    /// gbt was built without the ledger feature
+    Unsupported,
+    Transport(String),
+    /// the status word returned by the Cosmos app
+    App(u16),
+    InvalidResponse(String),
+}
+
+impl fmt::Display for LedgerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LedgerError::Unsupported => write!(
+                f,
+                "This gbt was built without Ledger support, build it with `cargo build --release --features ledger`"
+            ),
+            LedgerError::Transport(e) => {
+                write!(f, "{}, is the Ledger connected and unlocked?", e)
+            }
+            LedgerError::App(0x6986) => write!(f, "The transaction was rejected on the Ledger"),
+            LedgerError::App(0x6e00) | LedgerError::App(0x6e01) => {
+                write!(f, "Open the Cosmos app on the Ledger")
+            }
+            LedgerError::App(0x6984) => write!(
+                f,
+                "The Cosmos app could not parse the transaction, update the app"
+            ),
+            LedgerError::App(code) => write!(f, "The Cosmos app returned error {:#06x}", code),
+            LedgerError::InvalidResponse(e) => write!(f, "Invalid response from the Ledger {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "ledger")]
+impl From<LedgerHIDError> for LedgerError {
+    fn from(error: LedgerHIDError) -> Self {
+        LedgerError::Transport(error.to_string())
+    }
+}
+
+/// A connection to the Cosmos app on a Ledger, for the key at 44'/118'/account'/0/0
+pub struct Ledger {
+    #[cfg(feature = "ledger")]
+    transport: TransportNativeHID,
+    path: [u32; 5],
+    public_key: [u8; 33],
+    address: CosmosAddress,
+}
+
+impl Ledger {
+    /// Connects to the first Ledger found and reads the public key and address of `account`
+    #[cfg(feature = "ledger")]
+    pub fn connect(account: u32, prefix: &str) -> Result<Ledger, LedgerError> {
+        let api = HidApi::new().map_err(LedgerHIDError::from)?;
+        let transport = TransportNativeHID::new(&api)?;
+        let path = [
+            44 | HARDENED,
+            COSMOS_COIN_TYPE | HARDENED,
+            account | HARDENED,
+            0,
+            0,
+        ];
+        let mut data = vec![prefix.len() as u8];
+        data.extend_from_slice(prefix.as_bytes());
+        data.extend(serialize_path(&path));
+        let response = exchange(&transport, INS_GET_ADDR_SECP256K1, PAYLOAD_INIT, 0, data)?;
+        let (public_key, address) = parse_address_response(&response)?;
+        Ok(Ledger {
+            transport,
+            path,
+            public_key,
+            address,
+        })
+    }
+
+    #[cfg(not(feature = "ledger"))]
+    pub fn connect(_account: u32, _prefix: &str) -> Result<Ledger, LedgerError> {
+        Err(LedgerError::Unsupported)
+    }
+
+    pub fn address(&self) -> CosmosAddress {
+        self.address
+    }
+
+    /// The same key with its address on a chain using `prefix`, for commands that sign on both a
+    /// counterparty chain and Gravity
+    pub fn for_prefix(self, prefix: &str) -> Ledger {
+        Ledger {
+            address: CosmosAddress::from_slice(self.address.get_bytes(), prefix).unwrap(),
+            ..self
+        }
+    }
+
+    /// Signs an amino JSON sign doc, blocking until it is approved or rejected on the device.
+    /// Returns the 64 byte signature the chain expects
+    #[cfg(feature = "ledger")]
+    pub fn sign(&self, sign_doc: &[u8]) -> Result<Vec<u8>, LedgerError> {
+        let mut chunks = vec![serialize_path(&self.path)];
+        chunks.extend(sign_doc.chunks(CHUNK_SIZE).map(|c| c.to_vec()));
+        let last = chunks.len() - 1;
+        let mut response = Vec::new();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let p1 = if i == 0 {
+                PAYLOAD_INIT
+            } else if i == last {
+                PAYLOAD_LAST
+            } else {
+                PAYLOAD_ADD
+            };
+            response = exchange(
+                &self.transport,
+                INS_SIGN_SECP256K1,
+                p1,
+                P2_AMINO_JSON,
+                chunk,
+            )?;
+        }
+        der_to_compact(&response)
+    }
+
+    #[cfg(not(feature = "ledger"))]
+    pub fn sign(&self, _sign_doc: &[u8]) -> Result<Vec<u8>, LedgerError> {
+        Err(LedgerError::Unsupported)
+    }
+}
+
+/// Signs `msg` on the Ledger and broadcasts it, `amino_msg` is the amino JSON form of `msg` which is
+/// what the user sees and approves on the device
+pub async fn send_with_ledger(
+    contact: &Contact,
+    ledger: &Ledger,
+    msg: Any,
+    amino_msg: serde_json::Value,
+    fee: Coin,
+    memo: String,
+) -> Result<TxResponse, CosmosGrpcError> {
+    let args = contact
+        .get_message_args(
+            ledger.address(),
+            Fee {
+                amount: vec![fee.clone()],
+                gas_limit: LEDGER_GAS_LIMIT,
+                granter: None,
+                payer: None,
+            },
+        )
+        .await?;
+    let body = TxBody {
+        messages: vec![msg],
+        memo: memo.clone(),
+        ..Default::default()
+    };
+    let sign_doc = amino_sign_doc(
+        &args.chain_id,
+        args.account_number,
+        args.sequence,
+        &fee,
+        LEDGER_GAS_LIMIT,
+        &memo,
+        amino_msg,
+    );
+    info!("Review and approve the transaction on your Ledger");
+    let signature = ledger
+        .sign(sign_doc.as_bytes())
+        .map_err(|e| CosmosGrpcError::BadInput(e.to_string()))?;
+
+    let auth_info = AuthInfo {
+        signer_infos: vec![SignerInfo {
+            public_key: Some(Any {
+                type_url: SECP256K1_PUBKEY_TYPE_URL.to_string(),
+                value: Secp256k1PubKey {
+                    key: ledger.public_key.to_vec(),
+                }
+                .encode_to_vec(),
+            }),
+            mode_info: Some(ModeInfo {
+                sum: Some(Sum::Single(Single {
+                    mode: SignMode::LegacyAminoJson.into(),
+                })),
+            }),
+            sequence: args.sequence,
+        }],
+        fee: Some(ProtoFee {
+            amount: vec![ProtoCoin {
+                denom: fee.denom.clone(),
+                amount: fee.amount.to_string(),
+            }],
+            gas_limit: LEDGER_GAS_LIMIT,
+            payer: String::new(),
+            granter: String::new(),
+        }),
+    };
+    let tx_raw = TxRaw {
+        body_bytes: body.encode_to_vec(),
+        auth_info_bytes: auth_info.encode_to_vec(),
+        signatures: vec![signature],
+    };
+    let res = contact
+        .send_transaction(tx_raw.encode_to_vec(), BroadcastMode::Sync)
+        .await?;
+    contact.wait_for_tx(res, TIMEOUT).await
+}
+
+#[cfg(feature = "ledger")]
+fn exchange(
+    transport: &TransportNativeHID,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: Vec<u8>,
+) -> Result<Vec<u8>, LedgerError> {
+    let answer = transport.exchange(&APDUCommand {
+        cla: CLA,
+        ins,
+        p1,
+        p2,
+        data,
+    })?;
+    if answer.retcode() != APDU_OK {
+        return Err(LedgerError::App(answer.retcode()));
+    }
+    Ok(answer.data().to_vec())
+}
+
+/// BIP32 path components as the Cosmos app expects them, little endian
+fn serialize_path(path: &[u32; 5]) -> Vec<u8> {
+    path.iter().flat_map(|p| p.to_le_bytes()).collect()
+}
+
+/// The response to INS_GET_ADDR_SECP256K1 is the compressed public key followed by the bech32
+/// address
+fn parse_address_response(response: &[u8]) -> Result<([u8; 33], CosmosAddress), LedgerError> {
+    if response.len() <= 33 {
+        return Err(LedgerError::InvalidResponse(format!(
+            "address response of {} bytes",
+            response.len()
+        )));
+    }
+    let mut public_key = [0u8; 33];
+    public_key.copy_from_slice(&response[..33]);
+    let address = String::from_utf8(response[33..].to_vec())
+        .map_err(|e| LedgerError::InvalidResponse(e.to_string()))?;
+    let address = address
+        .parse()
+        .map_err(|e| LedgerError::InvalidResponse(format!("address {} {:?}", address, e)))?;
+    Ok((public_key, address))
+}
+
+/// The Cosmos app returns DER signatures, the chain only accepts low-s 64 byte ones
+fn der_to_compact(der: &[u8]) -> Result<Vec<u8>, LedgerError> {
+    let mut signature =
+        Signature::from_der(der).map_err(|e| LedgerError::InvalidResponse(e.to_string()))?;
+    signature.normalize_s();
+    Ok(signature.serialize_compact().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Message as Secp256k1Message, Secp256k1, SecretKey};
+
+    #[test]
+    fn test_ledger_encoding() {
+        let path = [44 | HARDENED, 118 | HARDENED, HARDENED, 0, 0];
+        assert_eq!(
+            serialize_path(&path),
+            vec![44, 0, 0, 0x80, 118, 0, 0, 0x80, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        let secp = Secp256k1::new();
+        let key = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let message = Secp256k1Message::from_slice(&[1u8; 32]).unwrap();
+        let signature = secp.sign_ecdsa(&message, &key);
+        assert_eq!(
+            der_to_compact(&signature.serialize_der()).unwrap(),
+            signature.serialize_compact().to_vec()
+        );
+        assert!(der_to_compact(&[0u8; 10]).is_err());
+        assert!(parse_address_response(&[0u8; 33]).is_err());
+    }
+}
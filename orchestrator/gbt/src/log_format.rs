@@ -0,0 +1,98 @@
+//! Structured JSON logging, selected with `--log-format json`. Each record is written as a single
+//! line JSON object so that logs can be ingested into Loki or Elasticsearch and queried by field
+//! rather than by matching free-form text. Besides the timestamp, level, module and message a
+//! record carries the loop it was logged from and, when it was logged about one, the event nonce
+//! and transaction hash, see gravity_utils::log_context
+
+use env_logger::fmt::Formatter;
+use gravity_utils::log_context::{current_event_nonce, current_loop, current_tx_hash};
+use log::Record;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// How log records are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// env_logger's human readable lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format {}, expected text or json", s)),
+        }
+    }
+}
+
+/// Writes `record` as a JSON line, used as the env_logger format
+pub fn format_json(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let timestamp = buf.timestamp_millis().to_string();
+    writeln!(buf, "{}", json_record(&timestamp, record))
+}
+
+fn json_record(timestamp: &str, record: &Record) -> Value {
+    let message = record.args().to_string();
+    let mut entry = json!({
+        "timestamp": timestamp,
+        "level": record.level().as_str(),
+        "target": record.target(),
+    });
+    if let Some(name) = current_loop() {
+        entry["loop"] = name.into();
+    }
+    if let Some(nonce) = current_event_nonce() {
+        entry["event_nonce"] = nonce.into();
+    }
+    if let Some(hash) = current_tx_hash() {
+        entry["tx_hash"] = hash.into();
+    }
+    entry["message"] = message.into();
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity_utils::log_context::{with_event_nonce, with_tx_hash};
+
+    #[test]
+    fn test_json_record() {
+        let hash = "ab".repeat(32);
+        let message = format!(
+            "Claims processed for event nonce 42 with txid 0x{} at block 1000",
+            hash
+        );
+        let record = |message: &str| {
+            json_record(
+                "2024-01-01T00:00:00.000Z",
+                &Record::builder()
+                    .args(format_args!("{}", message))
+                    .level(log::Level::Info)
+                    .target("orchestrator::ethereum_event_watcher")
+                    .build(),
+            )
+        };
+        let entry = with_event_nonce(42, || {
+            with_tx_hash(format!("0x{}", hash), || record(&message))
+        });
+        assert_eq!(entry["level"], "INFO");
+        assert_eq!(entry["event_nonce"], 42);
+        assert_eq!(entry["tx_hash"], format!("0x{}", hash));
+        assert_eq!(entry["message"], message);
+        assert!(entry.get("loop").is_none());
+
+        // numbers in the message are not taken for fields
+        let entry = record(&message);
+        assert!(entry.get("event_nonce").is_none());
+        assert!(entry.get("tx_hash").is_none());
+    }
+}
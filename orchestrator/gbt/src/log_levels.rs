@@ -0,0 +1,254 @@
+//! Per module log levels. Levels are read from the [log_levels] section of the config at startup
+//! and can be changed on a running orchestrator or relayer with `gbt log-level`, which uses a
+//! request file in the shared home directory in the same way as `gbt dump-state`
+
+use crate::args::LogLevelOpts;
+use crate::log_format::{format_json, LogFormat};
+use env_logger::filter::{Builder as FilterBuilder, Filter};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::exit;
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+
+/// The name of the request file, it contains the new log levels
+pub const LOG_LEVEL_REQUEST_NAME: &str = "log-level-request";
+/// Request contents that restore the levels from the config
+const RESET_REQUEST: &str = "reset";
+/// How often running processes check for a log level request
+const LOG_LEVEL_REQUEST_CHECK_SPEED: Duration = Duration::from_secs(2);
+
+/// Short names for parts of the orchestrator that don't have a module of their own
+const ALIASES: [(&str, &[&str]); 1] = [(
+    "oracle",
+    &[
+        "orchestrator::ethereum_event_watcher",
+        "orchestrator::oracle_resync",
+    ],
+)];
+
+/// The global level and RUST_LOG, module levels are applied on top of it
+static BASE_FILTER: RwLock<String> = RwLock::new(String::new());
+/// The levels from the config, restored by `gbt log-level --reset`
+static CONFIGURED_LEVELS: RwLock<BTreeMap<String, LevelFilter>> = RwLock::new(BTreeMap::new());
+/// The module levels currently in effect
+static LEVELS: RwLock<BTreeMap<String, LevelFilter>> = RwLock::new(BTreeMap::new());
+static FILTER: RwLock<Option<Filter>> = RwLock::new(None);
+
+/// Wraps env_logger so that its filter can be replaced while running, env_logger itself is built
+/// to accept everything and only handles formatting
+pub struct LevelFilteredLogger {
+    inner: env_logger::Logger,
+}
+
+impl LevelFilteredLogger {
+    pub fn new(default_level: &str, format: LogFormat) -> Self {
+        *BASE_FILTER.write().unwrap() =
+            env::var("RUST_LOG").unwrap_or_else(|_| default_level.to_string());
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(LevelFilter::Trace);
+        if let Ok(style) = env::var("RUST_LOG_STYLE") {
+            builder.parse_write_style(&style);
+        }
+        if format == LogFormat::Json {
+            builder.format(format_json);
+        }
+        rebuild_filter();
+        LevelFilteredLogger {
+            inner: builder.build(),
+        }
+    }
+}
+
+impl Log for LevelFilteredLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match &*FILTER.read().unwrap() {
+            Some(f) => f.enabled(metadata),
+            None => true,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let matches = match &*FILTER.read().unwrap() {
+            Some(f) => f.matches(record),
+            None => true,
+        };
+        if matches {
+            self.inner.log(record)
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Parses module levels such as relayer = "debug", expanding aliases
+pub fn parse_log_levels<'a>(
+    levels: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<BTreeMap<String, LevelFilter>, String> {
+    let mut parsed = BTreeMap::new();
+    for (module, level) in levels {
+        let module = module.trim();
+        if module.is_empty() || module.contains(['=', ',', '/']) {
+            return Err(format!("Invalid module name {:?}", module));
+        }
+        let level = LevelFilter::from_str(level.trim())
+            .map_err(|_| format!("Invalid log level {:?} for {}", level, module))?;
+        match ALIASES.iter().find(|(alias, _)| *alias == module) {
+            Some((_, modules)) => {
+                for m in modules.iter() {
+                    parsed.insert(m.to_string(), level);
+                }
+            }
+            None => {
+                parsed.insert(module.to_string(), level);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Applies the module levels from the config, exits if any are invalid
+pub fn set_configured_log_levels(levels: &BTreeMap<String, String>) {
+    let levels = match parse_log_levels(levels.iter().map(|(k, v)| (k.as_str(), v.as_str()))) {
+        Ok(l) => l,
+        Err(e) => {
+            error!("Invalid [log_levels] in config: {}", e);
+            exit(1);
+        }
+    };
+    *CONFIGURED_LEVELS.write().unwrap() = levels.clone();
+    *LEVELS.write().unwrap() = levels;
+    rebuild_filter();
+}
+
+fn rebuild_filter() {
+    let mut spec = BASE_FILTER.read().unwrap().clone();
+    for (module, level) in LEVELS.read().unwrap().iter() {
+        spec.push_str(&format!(",{}={}", module, level));
+    }
+    let filter = FilterBuilder::new().parse(&spec).build();
+    log::set_max_level(filter.filter());
+    *FILTER.write().unwrap() = Some(filter);
+}
+
+/// Applies a request written by `gbt log-level`, either new module levels or a reset to the config
+fn apply_request(request: &str) -> Result<(), String> {
+    let request = request.trim();
+    let levels = if request == RESET_REQUEST {
+        CONFIGURED_LEVELS.read().unwrap().clone()
+    } else {
+        let mut levels = LEVELS.read().unwrap().clone();
+        levels.extend(parse_log_levels(split_levels(request)?)?);
+        levels
+    };
+    *LEVELS.write().unwrap() = levels;
+    rebuild_filter();
+    Ok(())
+}
+
+/// Splits module=level pairs separated by commas
+fn split_levels(input: &str) -> Result<Vec<(&str, &str)>, String> {
+    input
+        .split(',')
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            l.split_once('=')
+                .ok_or_else(|| format!("Expected module=level, got {:?}", l))
+        })
+        .collect()
+}
+
+/// Runs alongside the orchestrator or relayer main loops, applying new log levels whenever they
+/// are changed with `gbt log-level`
+pub async fn log_level_listener(home_dir: PathBuf) {
+    let request_file = home_dir.join(LOG_LEVEL_REQUEST_NAME);
+    loop {
+        if request_file.exists() {
+            match fs::read_to_string(&request_file) {
+                Ok(request) => match apply_request(&request) {
+                    Ok(()) => info!("Log levels changed to {:?}", LEVELS.read().unwrap()),
+                    Err(e) => error!("Invalid log level request {}", e),
+                },
+                Err(e) => error!("Failed to read log level request {:?}", e),
+            }
+            if let Err(e) = fs::remove_file(&request_file) {
+                error!("Failed to remove log level request {:?}", e);
+            }
+        }
+        delay_for(LOG_LEVEL_REQUEST_CHECK_SPEED).await;
+    }
+}
+
+pub async fn log_level(args: LogLevelOpts, home_dir: PathBuf) {
+    let request = if args.reset {
+        RESET_REQUEST.to_string()
+    } else if args.levels.is_empty() {
+        error!("Provide module levels such as relayer=debug, or --reset");
+        exit(1);
+    } else {
+        let request = args.levels.join(",");
+        if let Err(e) = split_levels(&request).and_then(parse_log_levels) {
+            error!("{}", e);
+            exit(1);
+        }
+        request
+    };
+    if !home_dir.exists() {
+        error!(
+            "Home directory {} does not exist, use the same --home as the running orchestrator",
+            home_dir.display()
+        );
+        exit(1);
+    }
+    let request_file = home_dir.join(LOG_LEVEL_REQUEST_NAME);
+    if let Err(e) = fs::write(&request_file, request) {
+        error!("Failed to write log level request {:?}", e);
+        exit(1);
+    }
+
+    let timeout = Duration::from_secs(args.timeout);
+    let start = Instant::now();
+    while request_file.exists() {
+        if Instant::now() - start > timeout {
+            let _ = fs::remove_file(&request_file);
+            error!(
+                "No orchestrator or relayer using {} responded within {}s, is it running?",
+                home_dir.display(),
+                args.timeout
+            );
+            exit(1);
+        }
+        delay_for(Duration::from_millis(500)).await;
+    }
+    info!("Log levels updated, the running process logs the levels now in effect");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_levels() {
+        let levels =
+            parse_log_levels(split_levels("relayer=debug, oracle=info,web30=warn").unwrap())
+                .unwrap();
+        assert_eq!(levels.get("relayer"), Some(&LevelFilter::Debug));
+        assert_eq!(levels.get("web30"), Some(&LevelFilter::Warn));
+        assert_eq!(
+            levels.get("orchestrator::ethereum_event_watcher"),
+            Some(&LevelFilter::Info)
+        );
+        assert!(!levels.contains_key("oracle"));
+
+        assert!(split_levels("relayer").is_err());
+        assert!(parse_log_levels(vec![("relayer", "loud")]).is_err());
+        assert!(parse_log_levels(vec![("", "info")]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Throttling of repeated warnings and errors. A condition that persists, such as an unreachable
+//! RPC, is otherwise logged on every loop iteration and buries the first actionable error. The
+//! first occurrence of a message is logged as usual, identical messages within the interval are
+//! counted instead and summarized once the interval is over
+
+use log::{Level, Log, Metadata, Record};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long identical messages are suppressed for, zero disables throttling
+static INTERVAL: RwLock<Duration> = RwLock::new(Duration::from_secs(60));
+/// How often expired messages are checked for a summary
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn set_log_throttle_interval(interval: Duration) {
+    *INTERVAL.write().unwrap() = interval;
+}
+
+/// A message that was logged and any identical messages suppressed since
+struct Seen {
+    level: Level,
+    since: Instant,
+    suppressed: u64,
+}
+
+/// A summary of suppressed messages to log
+#[derive(Debug, PartialEq, Eq)]
+struct Summary {
+    level: Level,
+    target: String,
+    message: String,
+    count: u64,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    seen: HashMap<(String, String), Seen>,
+    last_sweep: Option<Instant>,
+}
+
+impl ThrottleState {
+    /// Returns true if the message should be logged now
+    fn check(
+        &mut self,
+        level: Level,
+        target: &str,
+        message: &str,
+        now: Instant,
+        interval: Duration,
+    ) -> bool {
+        let key = (target.to_string(), message.to_string());
+        match self.seen.get_mut(&key) {
+            Some(seen) if now.saturating_duration_since(seen.since) < interval => {
+                seen.suppressed += 1;
+                false
+            }
+            _ => {
+                self.seen.insert(
+                    key,
+                    Seen {
+                        level,
+                        since: now,
+                        suppressed: 0,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Forgets messages whose interval is over, returning summaries of those that were suppressed
+    fn sweep(&mut self, now: Instant, interval: Duration) -> Vec<Summary> {
+        match self.last_sweep {
+            Some(last) if now.saturating_duration_since(last) < SWEEP_INTERVAL => {
+                return Vec::new()
+            }
+            _ => self.last_sweep = Some(now),
+        }
+        let mut summaries = Vec::new();
+        self.seen.retain(|(target, message), seen| {
+            if now.saturating_duration_since(seen.since) < interval {
+                return true;
+            }
+            if seen.suppressed > 0 {
+                summaries.push(Summary {
+                    level: seen.level,
+                    target: target.clone(),
+                    message: message.clone(),
+                    count: seen.suppressed,
+                });
+            }
+            false
+        });
+        summaries
+    }
+}
+
+/// Wraps a logger, throttling its warnings and errors. Records the inner logger would drop and
+/// records with `unthrottled_target` are passed straight through
+pub struct ThrottledLogger<L: Log> {
+    inner: L,
+    unthrottled_target: &'static str,
+    state: Mutex<ThrottleState>,
+}
+
+impl<L: Log> ThrottledLogger<L> {
+    pub fn new(inner: L, unthrottled_target: &'static str) -> Self {
+        ThrottledLogger {
+            inner,
+            unthrottled_target,
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+}
+
+impl<L: Log> Log for ThrottledLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let interval = *INTERVAL.read().unwrap();
+        if interval == Duration::ZERO
+            || record.level() > Level::Warn
+            || record.target() == self.unthrottled_target
+            || !self.inner.enabled(record.metadata())
+        {
+            self.inner.log(record);
+            return;
+        }
+        let now = Instant::now();
+        let message = record.args().to_string();
+        // the lock is released before logging
+        let (log_now, summaries) = {
+            let mut state = self.state.lock().unwrap();
+            let log_now = state.check(record.level(), record.target(), &message, now, interval);
+            (log_now, state.sweep(now, interval))
+        };
+        for s in summaries {
+            self.inner.log(
+                &Record::builder()
+                    .level(s.level)
+                    .target(&s.target)
+                    .args(format_args!(
+                        "{} (repeated {} more times in the last {}s)",
+                        s.message,
+                        s.count,
+                        interval.as_secs()
+                    ))
+                    .build(),
+            );
+        }
+        if log_now {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_state() {
+        let interval = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut state = ThrottleState::default();
+        assert!(state.check(Level::Warn, "relayer", "RPC down", start, interval));
+        for i in 1..=5 {
+            let now = start + Duration::from_secs(i);
+            assert!(!state.check(Level::Warn, "relayer", "RPC down", now, interval));
+        }
+        // other messages and targets are independent
+        assert!(state.check(Level::Warn, "relayer", "RPC up", start, interval));
+        assert!(state.check(Level::Warn, "oracle", "RPC down", start, interval));
+        assert!(state
+            .sweep(start + Duration::from_secs(10), interval)
+            .is_empty());
+
+        let summaries = state.sweep(start + interval, interval);
+        assert_eq!(
+            summaries,
+            vec![Summary {
+                level: Level::Warn,
+                target: "relayer".to_string(),
+                message: "RPC down".to_string(),
+                count: 5,
+            }]
+        );
+        assert!(state.seen.is_empty());
+        assert!(state.check(
+            Level::Warn,
+            "relayer",
+            "RPC down",
+            start + interval,
+            interval
+        ));
+    }
+}
@@ -3,62 +3,171 @@ extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
-use crate::args::{ClientSubcommand, KeysSubcommand, SubCommand};
+use crate::address_book::{add_address, list_addresses, remove_address};
+use crate::args::{
+    AddressBookSubcommand, ClientQuerySubcommand, ClientSubcommand, DevSubcommand, KeysSubcommand,
+    LogicCallSubcommand, MultisigSubcommand, OrchestratorSubcommand, RelayerSubcommand, SubCommand,
+    TestSubcommand,
+};
 use crate::config::init_config;
 use crate::keys::{recover_funds, show_keys};
 use crate::{jsonrpc_server::jsonrpc_server, orchestrator::orchestrator, relayer::relayer};
 use args::{GovQuerySubcommand, GovSubcommand, GovSubmitSubcommand, Opts};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, ValueSource};
 use client::cosmos_to_eth::cosmos_to_eth_cmd;
 use client::deploy_erc20_representation::deploy_erc20_representation;
+use client::erc20_allowance::{approve_erc20, revoke_erc20};
 use client::eth_to_cosmos::eth_to_cosmos;
+use client::ibc_to_eth::ibc_to_eth;
+use client::logic_call::{build_logic_call, relay_logic_call};
+use client::multisig::{multisig_combine, multisig_create, multisig_pubkey, multisig_sign};
+use client::offline::{broadcast_tx, sign_tx};
+use client::query::accidental_transfers::query_accidental_transfers;
+use client::query::attestation::query_attestation;
+use client::query::batch::query_batch;
+use client::query::batch_fees::query_batch_fees;
+use client::query::batch_preview::query_batch_preview;
+use client::query::delegate_keys::query_delegate_keys;
+use client::query::ethereum_valset_history::query_ethereum_valset_history;
+use client::query::history::query_history;
+use client::query::relayer_payouts::query_relayer_payouts;
+use client::query::signing_info::query_signing_info;
+use client::query::token_mappings::query_token_mappings;
+use client::query::valset_history::query_valset_history;
+use client::round_trip::round_trip_cmd;
 use client::spot_relay::spot_relay;
-use config::{get_home_dir, load_config};
-use env_logger::Env;
+use config::{get_home_dir, load_config, with_endpoint_defaults};
+use cosmos_gravity::send::{set_memo, set_memo_version_tag};
+use deposit_watch::deposit_watch;
+use dev::gen_fixtures;
+use dump_state::dump_state;
+use error_reporting::{init_logging, install_panic_hook, start_error_reporting};
 use gov::proposals::{
     submit_airdrop, submit_emergency_bridge_halt, submit_ibc_metadata, submit_oracle_unhalt,
 };
 use gov::queries::query_airdrops;
+use hunt_evidence::hunt_evidence;
+use keys::backup::{backup_keys, restore_keys};
 use keys::register_orchestrator_address::register_orchestrator_address;
 use keys::set_eth_key;
 use keys::set_orchestrator_key;
+use log_levels::set_configured_log_levels;
+use log_throttle::set_log_throttle_interval;
+use pending_work::pending_work;
+use relayer_runway::relayer_runway;
+use retry_batch::retry_batch;
+use sign_logic_call::sign_logic_call;
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::Duration;
+use telemetry::start_tracing;
+use test::load::load_test;
+use watch::watch;
 
+mod address_book;
 mod args;
 mod client;
 mod config;
+mod deposit_watch;
+mod dev;
+mod dump_state;
+mod error_reporting;
 mod gov;
+mod hunt_evidence;
 mod jsonrpc_server;
 mod keys;
+mod ledger;
+mod log_format;
+mod log_levels;
+mod log_throttle;
 mod orchestrator;
+mod pending_work;
 mod relayer;
+mod relayer_runway;
+mod retry_batch;
+mod sign_logic_call;
+mod simulate;
+mod telemetry;
+mod test;
 mod utils;
+mod watch;
 
 #[actix_rt::main]
 async fn main() {
-    let opts: Opts = Opts::parse();
-    let log_level = match opts.verbose {
+    // the home directory and profile decide which config, and so which endpoint defaults, apply.
+    // They are read before the full parse, which would fail on arguments the config provides
+    let globals = Opts::command().ignore_errors(true).get_matches();
+    let log_level = match globals.is_present("verbose") {
         true => "debug",
         false => "info",
     };
-    env_logger::Builder::from_env(Env::default().default_filter_or(log_level)).init();
+    init_logging(
+        log_level,
+        globals.value_of_t("log-format").unwrap_or_default(),
+    );
     // On Linux static builds we need to probe ssl certs path to be able to
     // do TLS stuff.
     openssl_probe::init_ssl_cert_env_vars();
-    // parse the arguments
 
     // handle global config here
+    let home_dir = get_home_dir(
+        globals.value_of("home").map(PathBuf::from),
+        globals.value_of("profile"),
+    );
+    let config_overrides: Vec<String> = globals
+        .values_of("config-overrides")
+        .map(|v| v.map(String::from).collect())
+        .unwrap_or_default();
+    let config = load_config(&home_dir, &config_overrides);
+    // parse the arguments
+    let matches = with_endpoint_defaults(Opts::command(), &config.endpoints).get_matches();
+    let opts = Opts::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     let address_prefix = opts.address_prefix;
-    let home_dir = get_home_dir(opts.home);
-    let config = load_config(&home_dir);
+    // the orchestrator and relayer use the chain's prefix unless one was given explicitly
+    let explicit_prefix = matches.value_source("address-prefix") != Some(ValueSource::DefaultValue);
+    set_configured_log_levels(&config.log_levels);
+    set_log_throttle_interval(Duration::from_secs(config.log_throttle_seconds));
+    if let Err(e) = set_memo(config.memo.clone()) {
+        error!("Invalid memo in the config {:?}", e);
+        exit(1);
+    }
+    if config.memo_version_tag {
+        set_memo_version_tag(Some(format!(
+            "gbt/{}+{}",
+            env!("CARGO_PKG_VERSION"),
+            env!("GBT_GIT_HASH")
+        )));
+    }
+    start_tracing(&config.tracing);
+    let command = match &opts.subcmd {
+        SubCommand::Orchestrator(_) => "orchestrator",
+        SubCommand::Relayer(_) => "relayer",
+        SubCommand::JsonrpcServer(_) => "jsonrpc-server",
+        SubCommand::Client(_) => "client",
+        SubCommand::Gov(_) => "gov",
+        SubCommand::Keys(_) => "keys",
+        SubCommand::AddressBook(_) => "address-book",
+        SubCommand::Init(_) => "init",
+        SubCommand::DumpState(_) => "dump-state",
+        SubCommand::LogLevel(_) => "log-level",
+        SubCommand::Watch(_) => "watch",
+        SubCommand::DepositWatch(_) => "deposit-watch",
+        SubCommand::Test(_) => "test",
+        SubCommand::Dev(_) => "dev",
+    };
+    let _error_reporting = start_error_reporting(&config, command);
+    install_panic_hook();
 
     // control flow for the command structure
     match opts.subcmd {
         SubCommand::Client(client_opts) => match client_opts.subcmd {
             ClientSubcommand::EthToCosmos(eth_to_cosmos_opts) => {
-                eth_to_cosmos(eth_to_cosmos_opts, address_prefix).await
+                eth_to_cosmos(eth_to_cosmos_opts, address_prefix, &config.address_book).await
             }
+            ClientSubcommand::ApproveErc20(opts) => approve_erc20(opts, address_prefix).await,
+            ClientSubcommand::RevokeErc20(opts) => revoke_erc20(opts, address_prefix).await,
             ClientSubcommand::CosmosToEth(cosmos_to_eth_opts) => {
-                cosmos_to_eth_cmd(cosmos_to_eth_opts, address_prefix).await
+                cosmos_to_eth_cmd(cosmos_to_eth_opts, address_prefix, &config.address_book).await
             }
             ClientSubcommand::DeployErc20Representation(deploy_erc20_opts) => {
                 deploy_erc20_representation(deploy_erc20_opts, address_prefix).await
@@ -66,6 +175,64 @@ async fn main() {
             ClientSubcommand::SpotRelay(spot_relay_opts) => {
                 spot_relay(spot_relay_opts, address_prefix).await
             }
+            ClientSubcommand::RoundTrip(opts) => round_trip_cmd(opts, address_prefix).await,
+            ClientSubcommand::IbcToEth(ibc_to_eth_opts) => {
+                ibc_to_eth(ibc_to_eth_opts, address_prefix, &config.address_book).await
+            }
+            ClientSubcommand::Sign(sign_opts) => {
+                sign_tx(sign_opts, address_prefix, &config.address_book)
+            }
+            ClientSubcommand::Broadcast(broadcast_opts) => {
+                broadcast_tx(broadcast_opts, address_prefix).await
+            }
+            ClientSubcommand::Multisig(multisig_opts) => match multisig_opts {
+                MultisigSubcommand::Pubkey(opts) => multisig_pubkey(opts),
+                MultisigSubcommand::Create(opts) => multisig_create(opts, address_prefix),
+                MultisigSubcommand::Sign(opts) => {
+                    multisig_sign(opts, address_prefix, &config.address_book)
+                }
+                MultisigSubcommand::Combine(opts) => multisig_combine(opts),
+            },
+            ClientSubcommand::LogicCall(logic_call_opts) => match logic_call_opts {
+                LogicCallSubcommand::Build(opts) => build_logic_call(opts).await,
+                LogicCallSubcommand::Relay(opts) => relay_logic_call(opts, address_prefix).await,
+            },
+            ClientSubcommand::Query(query_opts) => match query_opts {
+                ClientQuerySubcommand::Attestation(opts) => {
+                    query_attestation(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::Batch(opts) => query_batch(opts, address_prefix).await,
+                ClientQuerySubcommand::BatchFees(opts) => {
+                    query_batch_fees(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::BatchPreview(opts) => {
+                    query_batch_preview(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::DelegateKeys(opts) => {
+                    query_delegate_keys(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::RelayerPayouts(opts) => {
+                    query_relayer_payouts(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::TokenMappings(opts) => {
+                    query_token_mappings(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::AccidentalTransfers(opts) => {
+                    query_accidental_transfers(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::ValsetHistory(opts) => {
+                    query_valset_history(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::EthereumValsetHistory(opts) => {
+                    query_ethereum_valset_history(opts, address_prefix).await
+                }
+                ClientQuerySubcommand::SigningInfo(opts) => {
+                    query_signing_info(opts, address_prefix, &config.address_book).await
+                }
+                ClientQuerySubcommand::History(opts) => {
+                    query_history(opts, address_prefix, &config.address_book).await
+                }
+            },
         },
         SubCommand::Keys(key_opts) => match key_opts.subcmd {
             KeysSubcommand::RegisterOrchestratorAddress(set_orchestrator_address_opts) => {
@@ -76,7 +243,7 @@ async fn main() {
                 )
                 .await
             }
-            KeysSubcommand::Show => show_keys(&home_dir, &address_prefix),
+            KeysSubcommand::Show(opts) => show_keys(&home_dir, &address_prefix, opts).await,
             KeysSubcommand::SetEthereumKey(set_eth_key_opts) => {
                 set_eth_key(&home_dir, set_eth_key_opts)
             }
@@ -86,15 +253,69 @@ async fn main() {
             KeysSubcommand::RecoverFunds(recover_funds_opts) => {
                 recover_funds(recover_funds_opts, address_prefix).await
             }
+            KeysSubcommand::Backup(backup_opts) => {
+                backup_keys(&home_dir, backup_opts, &address_prefix)
+            }
+            KeysSubcommand::Restore(restore_opts) => restore_keys(home_dir, restore_opts),
+        },
+        SubCommand::Orchestrator(mut orchestrator_opts) => match orchestrator_opts.subcmd.take() {
+            Some(OrchestratorSubcommand::HuntEvidence(opts)) => {
+                hunt_evidence(opts, address_prefix, &home_dir).await
+            }
+            Some(OrchestratorSubcommand::PendingWork(opts)) => {
+                pending_work(opts, address_prefix, &home_dir).await
+            }
+            Some(OrchestratorSubcommand::SignLogicCall(opts)) => {
+                sign_logic_call(opts, address_prefix, &home_dir, config).await
+            }
+            None => {
+                orchestrator(
+                    orchestrator_opts,
+                    address_prefix,
+                    explicit_prefix,
+                    &home_dir,
+                    config,
+                )
+                .await
+            }
         },
-        SubCommand::Orchestrator(orchestrator_opts) => {
-            orchestrator(orchestrator_opts, address_prefix, &home_dir, config).await
+        SubCommand::Relayer(mut relayer_opts) => match relayer_opts.subcmd.take() {
+            Some(RelayerSubcommand::Runway(opts)) => {
+                relayer_runway(opts, address_prefix, &home_dir).await
+            }
+            Some(RelayerSubcommand::RetryBatch(opts)) => retry_batch(opts, home_dir).await,
+            None => {
+                relayer(
+                    relayer_opts,
+                    address_prefix,
+                    explicit_prefix,
+                    &home_dir,
+                    config.relayer,
+                )
+                .await
+            }
+        },
+        SubCommand::JsonrpcServer(server_opts) => jsonrpc_server(server_opts, address_prefix).await,
+        SubCommand::AddressBook(address_book_opts) => match address_book_opts.subcmd {
+            AddressBookSubcommand::Add(opts) => add_address(&home_dir, opts),
+            AddressBookSubcommand::List => list_addresses(&config.address_book),
+            AddressBookSubcommand::Remove(opts) => remove_address(&home_dir, opts),
+        },
+        SubCommand::Init(init_opts) => init_config(init_opts, home_dir),
+        SubCommand::DumpState(dump_state_opts) => dump_state(dump_state_opts, home_dir).await,
+        SubCommand::LogLevel(log_level_opts) => {
+            log_levels::log_level(log_level_opts, home_dir).await
         }
-        SubCommand::Relayer(relayer_opts) => {
-            relayer(relayer_opts, address_prefix, &home_dir, config.relayer).await
+        SubCommand::Watch(watch_opts) => watch(watch_opts, address_prefix, &home_dir).await,
+        SubCommand::DepositWatch(opts) => {
+            deposit_watch(opts, address_prefix, config.deposit_watch.clone()).await
         }
-        SubCommand::JsonrpcServer(server_opts) => jsonrpc_server(server_opts).await,
-        SubCommand::Init(init_opts) => init_config(init_opts, home_dir),
+        SubCommand::Test(test_opts) => match test_opts.subcmd {
+            TestSubcommand::Load(opts) => load_test(opts, address_prefix).await,
+        },
+        SubCommand::Dev(dev_opts) => match dev_opts.subcmd {
+            DevSubcommand::GenFixtures(opts) => gen_fixtures(opts),
+        },
         SubCommand::Gov(gov_opts) => match gov_opts.subcmd {
             GovSubcommand::Submit(submit_opts) => match submit_opts {
                 GovSubmitSubcommand::IbcMetadata(opts) => {
@@ -1,35 +1,67 @@
 use crate::args::OrchestratorOpts;
 use crate::config::config_exists;
 use crate::config::load_keys;
+use crate::config::{SIGNATURE_AUDIT_LOG_NAME, WATCH_ONLY_EXPORT_NAME};
+use crate::dump_state::state_dump_listener;
+use crate::log_levels::log_level_listener;
+use crate::retry_batch::retry_batch_listener;
+use crate::telemetry::telemetry_report_loop;
 use crate::utils::print_relaying_explanation;
 use clarity::constants::zero_address;
+use clarity::PrivateKey as EthPrivateKey;
+use cosmos_gravity::module_version::{
+    check_gravity_module_version, ModuleVersionCheck, SUPPORTED_GRAVITY_MODULE_VERSIONS,
+};
 use cosmos_gravity::query::get_gravity_params;
-use deep_space::{CosmosPrivateKey, PrivateKey};
+use deep_space::{Contact, CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::signer::{build_ethereum_signer, EthereumSigner};
+use futures::future::join5;
+use gravity_utils::audit_log::set_audit_log_path;
 use gravity_utils::connection_prep::{
-    check_delegate_addresses, check_for_eth, wait_for_cosmos_node_ready,
+    check_address_prefix, check_delegate_addresses, check_for_eth, wait_for_cosmos_node_ready,
 };
 use gravity_utils::connection_prep::{check_for_fee, create_rpc_connections};
 use gravity_utils::types::BatchRequestMode;
 use gravity_utils::types::GravityBridgeToolsConfig;
+use gravity_utils::watch_only::set_watch_only;
 use metrics_exporter::metrics_server;
+use orchestrator::fee_grant::setup_fee_grant;
 use orchestrator::main_loop::orchestrator_main_loop;
 use orchestrator::main_loop::{ETH_ORACLE_LOOP_SPEED, ETH_SIGNER_LOOP_SPEED};
+use rand::{thread_rng, Rng};
 use std::cmp::min;
 use std::path::Path;
 use std::process::exit;
+use std::sync::Arc;
 use std::time::Duration;
 
 pub async fn orchestrator(
     args: OrchestratorOpts,
     address_prefix: String,
+    explicit_prefix: bool,
     home_dir: &Path,
-    config: GravityBridgeToolsConfig,
+    mut config: GravityBridgeToolsConfig,
 ) {
-    let fee = args.fees;
+    // clap requires fees whenever no subcommand is given
+    let fee = args.fees.unwrap();
     let cosmos_grpc = args.cosmos_grpc;
     let ethereum_rpc = args.ethereum_rpc;
     let ethereum_key = args.ethereum_key;
     let cosmos_key = args.cosmos_phrase;
+    let watch_only = args.watch_only;
+
+    // watch only mode never signs with real keys, throwaway keys stand in for them
+    let (cosmos_key, ethereum_key) = if watch_only.is_some() {
+        let mut rng = thread_rng();
+        let cosmos_secret: [u8; 32] = rng.gen();
+        let eth_secret: [u8; 32] = rng.gen();
+        (
+            Some(CosmosPrivateKey::from_secret(&cosmos_secret)),
+            Some(EthPrivateKey::from_bytes(eth_secret).unwrap()),
+        )
+    } else {
+        (cosmos_key, ethereum_key)
+    };
 
     let cosmos_key = if let Some(k) = cosmos_key {
         k
@@ -85,35 +117,106 @@ pub async fn orchestrator(
     )
     .await;
 
-    let mut grpc = connections.grpc.clone().unwrap();
-    let contact = connections.contact.clone().unwrap();
-    let web3 = connections.web3.clone().unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+
+    // check if the cosmos node is syncing, if so wait for it
+    // we can't move any steps above this because they may fail on an incorrect
+    // historic chain state while syncing occurs
+    wait_for_cosmos_node_ready(&contact).await;
+    // every address below is encoded with the chain's own prefix
+    let address_prefix = check_address_prefix(&contact, explicit_prefix).await;
+    let contact = Contact::new(&contact.get_url(), contact.get_timeout(), &address_prefix).unwrap();
+
+    // confirms may be signed with a key held in a KMS or HSM, the local key still pays for relaying
+    let eth_signer: Arc<dyn EthereumSigner> = if watch_only.is_some() {
+        Arc::new(ethereum_key)
+    } else {
+        match build_ethereum_signer(&config.orchestrator.ethereum_signer, ethereum_key).await {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to set up the Ethereum signer: {}", e);
+                exit(1);
+            }
+        }
+    };
 
     let public_eth_key = ethereum_key.to_address();
+    let signer_eth_key = eth_signer.address();
     let public_cosmos_key = cosmos_key.to_address(&contact.get_prefix()).unwrap();
     info!("Starting Gravity Validator companion binary Relayer + Oracle + Eth Signer");
     info!(
         "Ethereum Address: {} Cosmos Address {}",
         public_eth_key, public_cosmos_key
     );
+    if signer_eth_key != public_eth_key {
+        info!(
+            "Signing confirms with {:?} key {}",
+            config.orchestrator.ethereum_signer.backend, signer_eth_key
+        );
+    }
 
-    // check if the cosmos node is syncing, if so wait for it
-    // we can't move any steps above this because they may fail on an incorrect
-    // historic chain state while syncing occurs
-    wait_for_cosmos_node_ready(&contact).await;
+    // messages a newly upgraded chain rejects would silently miss confirms and claims
+    let watch_only = match check_gravity_module_version(&contact).await {
+        ModuleVersionCheck::Supported(v) => {
+            info!("Gravity module version {} is supported", v);
+            watch_only
+        }
+        ModuleVersionCheck::Unknown(e) => {
+            warn!(
+                "Could not check the Gravity module version, continuing {}",
+                e
+            );
+            watch_only
+        }
+        ModuleVersionCheck::Unsupported(v) => {
+            error!(
+                "The chain's Gravity module is version {} but this orchestrator supports versions {} to {}, upgrade gbt",
+                v,
+                SUPPORTED_GRAVITY_MODULE_VERSIONS.start(),
+                SUPPORTED_GRAVITY_MODULE_VERSIONS.end()
+            );
+            if !config.orchestrator.read_only_on_version_mismatch {
+                exit(1);
+            }
+            warn!(
+                "read_only_on_version_mismatch is set, nothing will be sent until gbt is upgraded"
+            );
+            Some(watch_only.unwrap_or(public_cosmos_key))
+        }
+    };
 
-    // check if the delegate addresses are correctly configured
-    check_delegate_addresses(
-        &mut grpc,
-        public_eth_key,
-        public_cosmos_key,
-        &contact.get_prefix(),
-    )
-    .await;
+    let mut fee_granter = None;
+    if let Some(watched) = watch_only {
+        // nothing is ever sent, so none of the keys or balances matter
+        let export = home_dir.join(WATCH_ONLY_EXPORT_NAME);
+        info!(
+            "Watch only mode, following orchestrator {} and exporting what it should submit to {}",
+            watched,
+            export.display()
+        );
+        set_watch_only(watched, export);
+        config.orchestrator.relayer_enabled = false;
+    } else {
+        // check if the delegate addresses are correctly configured
+        check_delegate_addresses(
+            &mut grpc,
+            signer_eth_key,
+            public_cosmos_key,
+            &contact.get_prefix(),
+        )
+        .await;
 
-    // check if we actually have the promised balance of tokens to pay fees
-    check_for_fee(&fee, public_cosmos_key, &contact).await;
-    check_for_eth(public_eth_key, &web3).await;
+        // fees are paid out of a fee grant if there is one, otherwise check if we actually have
+        // the promised balance of tokens to pay fees
+        fee_granter =
+            setup_fee_grant(&contact, public_cosmos_key, &fee, &config.orchestrator).await;
+        if fee_granter.is_none() {
+            check_for_fee(&fee, public_cosmos_key, &contact).await;
+        }
+        check_for_eth(public_eth_key, &web3).await;
+    }
 
     // get the gravity parameters
     let params = get_gravity_params(&mut grpc)
@@ -143,29 +246,48 @@ pub async fn orchestrator(
     if config.orchestrator.relayer_enabled {
         // setup and explain relayer settings
         if config.relayer.batch_request_mode != BatchRequestMode::None {
-            let public_cosmos_key = cosmos_key.to_address(&contact.get_prefix()).unwrap();
-            check_for_fee(&fee, public_cosmos_key, &contact).await;
+            if fee_granter.is_none() {
+                check_for_fee(&fee, public_cosmos_key, &contact).await;
+            }
             print_relaying_explanation(&config.relayer, true)
         } else {
             print_relaying_explanation(&config.relayer, false)
         }
     }
 
-    // Start monitiring if enabled on config.toml
+    if config.orchestrator.signature_audit_log {
+        let audit_log = home_dir.join(SIGNATURE_AUDIT_LOG_NAME);
+        info!("Recording signatures to {}", audit_log.display());
+        set_audit_log_path(audit_log);
+    }
+
+    // Start monitiring if enabled on config.toml or the command line
+    if let Some(listen) = args.metrics_listen {
+        config.metrics.metrics_enabled = true;
+        config.metrics.metrics_bind = listen;
+    }
     if config.metrics.metrics_enabled {
         metrics_server(&config.metrics);
     };
 
-    orchestrator_main_loop(
-        cosmos_key,
-        ethereum_key,
-        connections.web3.unwrap(),
-        connections.contact.unwrap(),
-        connections.grpc.unwrap(),
-        contract_address,
-        params.gravity_id,
-        fee,
-        config,
+    let telemetry_report = config.orchestrator.telemetry_report.clone();
+    join5(
+        orchestrator_main_loop(
+            cosmos_key,
+            ethereum_key,
+            eth_signer,
+            web3,
+            contact,
+            grpc,
+            contract_address,
+            params.gravity_id,
+            fee,
+            config,
+        ),
+        state_dump_listener(home_dir.to_path_buf()),
+        log_level_listener(home_dir.to_path_buf()),
+        telemetry_report_loop(telemetry_report),
+        retry_batch_listener(home_dir.to_path_buf()),
     )
     .await;
 }
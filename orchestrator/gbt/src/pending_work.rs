@@ -0,0 +1,89 @@
+//! Lists the work on the chain awaiting an orchestrator's signature
+
+use crate::args::PendingWorkOpts;
+use crate::config::{config_exists, load_keys};
+use crate::utils::TIMEOUT;
+use clarity::utils::bytes_to_hex_str;
+use cosmos_gravity::pending_work::{get_pending_work, Pagination};
+use deep_space::{CosmosPrivateKey, PrivateKey};
+use gravity_utils::connection_prep::create_rpc_connections;
+use std::path::Path;
+use std::process::exit;
+
+pub async fn pending_work(args: PendingWorkOpts, address_prefix: String, home_dir: &Path) {
+    let orchestrator = match args.orchestrator_address {
+        Some(a) => a,
+        None => {
+            let stored = if config_exists(home_dir) {
+                load_keys(home_dir).orchestrator_phrase
+            } else {
+                None
+            };
+            match stored {
+                Some(phrase) => CosmosPrivateKey::from_phrase(&phrase, "")
+                    .unwrap()
+                    .to_address(&address_prefix)
+                    .unwrap(),
+                None => {
+                    error!("No orchestrator key is stored, use --orchestrator-address or `gbt keys set-orchestrator-key`");
+                    exit(1);
+                }
+            }
+        }
+    };
+
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        None,
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+
+    let page = Pagination {
+        offset: args.offset,
+        limit: args.limit,
+    };
+    let work = match get_pending_work(&mut grpc, orchestrator, address_prefix, page).await {
+        Ok(w) => w,
+        Err(e) => {
+            error!(
+                "Failed to get the pending work for {} {:?}",
+                orchestrator, e
+            );
+            exit(1);
+        }
+    };
+
+    if work.is_empty() {
+        info!("{} has nothing left to sign", orchestrator);
+        return;
+    }
+    info!("Work awaiting a signature from {}", orchestrator);
+    for valset in work.valsets.iter() {
+        info!(
+            "Valset {} with {} members",
+            valset.nonce,
+            valset.members.len()
+        );
+    }
+    for batch in work.batches.iter() {
+        info!(
+            "Batch {} of {} with {} transactions, times out at Ethereum block {}",
+            batch.nonce,
+            batch.token_contract,
+            batch.transactions.len(),
+            batch.batch_timeout
+        );
+    }
+    for call in work.logic_calls.iter() {
+        info!(
+            "Logic call 0x{} nonce {} to {}, times out at Ethereum block {}",
+            bytes_to_hex_str(&call.invalidation_id),
+            call.invalidation_nonce,
+            call.logic_contract_address,
+            call.timeout
+        );
+    }
+}
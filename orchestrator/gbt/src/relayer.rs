@@ -1,16 +1,22 @@
 use crate::args::RelayerOpts;
 use crate::config::config_exists;
 use crate::config::load_keys;
+use crate::dump_state::state_dump_listener;
+use crate::log_levels::log_level_listener;
+use crate::retry_batch::retry_batch_listener;
 use crate::utils::print_relaying_explanation;
 use clarity::constants::zero_address;
 use cosmos_gravity::query::get_gravity_params;
-use deep_space::{CosmosPrivateKey, PrivateKey};
+use deep_space::{Contact, CosmosPrivateKey, PrivateKey};
+use futures::future::join4;
 use gravity_utils::connection_prep::check_for_fee;
 use gravity_utils::connection_prep::{
-    check_for_eth, create_rpc_connections, wait_for_cosmos_node_ready,
+    check_address_prefix, check_for_eth, create_rpc_connections, wait_for_cosmos_node_ready,
 };
 use gravity_utils::types::BatchRequestMode;
+use gravity_utils::types::MetricsConfig;
 use gravity_utils::types::RelayerConfig;
+use metrics_exporter::metrics_server;
 use relayer::main_loop::all_relayer_loops;
 use relayer::main_loop::TIMEOUT;
 use std::path::Path;
@@ -19,8 +25,9 @@ use std::process::exit;
 pub async fn relayer(
     args: RelayerOpts,
     address_prefix: String,
+    explicit_prefix: bool,
     home_dir: &Path,
-    config: RelayerConfig,
+    mut config: RelayerConfig,
 ) {
     let cosmos_grpc = args.cosmos_grpc;
     let ethereum_rpc = args.ethereum_rpc;
@@ -63,6 +70,14 @@ pub async fn relayer(
         None
     };
 
+    // a dry run sends nothing, including batch requests and IBC auto forwards on Cosmos
+    let cosmos_key = if args.dry_run {
+        config.dry_run = true;
+        None
+    } else {
+        cosmos_key
+    };
+
     let public_eth_key = ethereum_key.to_address();
     info!("Starting Gravity Relayer");
     info!("Ethereum Address: {}", public_eth_key);
@@ -75,6 +90,9 @@ pub async fn relayer(
     // we can't move any steps above this because they may fail on an incorrect
     // historic chain state while syncing occurs
     wait_for_cosmos_node_ready(&contact).await;
+    // the Cosmos key's address is encoded with the chain's own prefix
+    let address_prefix = check_address_prefix(&contact, explicit_prefix).await;
+    let contact = Contact::new(&contact.get_url(), contact.get_timeout(), &address_prefix).unwrap();
     check_for_eth(public_eth_key, &web3).await;
 
     // get the gravity parameters
@@ -118,16 +136,32 @@ pub async fn relayer(
         print_relaying_explanation(&config, false)
     }
 
-    all_relayer_loops(
-        cosmos_key,
-        ethereum_key,
-        web3,
-        contact,
-        grpc,
-        contract_address,
-        params.gravity_id,
-        args.fees,
-        config,
+    if config.dry_run {
+        warn!("Dry run, what would be relayed is only logged and nothing is sent to Ethereum");
+    }
+
+    if let Some(listen) = args.metrics_listen {
+        metrics_server(&MetricsConfig {
+            metrics_enabled: true,
+            metrics_bind: listen,
+        });
+    }
+
+    join4(
+        all_relayer_loops(
+            cosmos_key,
+            ethereum_key,
+            web3,
+            contact,
+            grpc,
+            contract_address,
+            params.gravity_id,
+            args.fees,
+            config,
+        ),
+        state_dump_listener(home_dir.to_path_buf()),
+        log_level_listener(home_dir.to_path_buf()),
+        retry_batch_listener(home_dir.to_path_buf()),
     )
     .await;
 }
@@ -0,0 +1,331 @@
+//! Estimates how long a relayer's ETH will last. The relayer's own valset updates, batches and
+//! logic calls over the lookback period give the gas it uses per day, which is priced at the
+//! current gas price. If a funding key is given and the runway is below the minimum the relayer
+//! is topped up to the target runway
+
+use crate::args::RelayerRunwayOpts;
+use crate::config::{config_exists, load_keys};
+use crate::hunt_evidence::find_gravity_calls;
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::utils::bytes_to_hex_str;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey, Uint256};
+use cosmos_gravity::query::get_gravity_params;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::error::GravityError;
+use orchestrator::oracle_resync::BLOCKS_TO_SEARCH;
+use serde_json::Value;
+use std::path::Path;
+use std::process::exit;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+use web30::types::SendTxOption;
+
+/// Blocks sampled to find the average block time
+const BLOCK_TIME_SAMPLE: u64 = 10_000;
+const SECONDS_PER_DAY: u64 = 86_400;
+
+pub async fn relayer_runway(args: RelayerRunwayOpts, address_prefix: String, home_dir: &Path) {
+    let relayer = match args.relayer_address {
+        Some(a) => a,
+        None => {
+            let stored = if config_exists(home_dir) {
+                load_keys(home_dir).ethereum_key
+            } else {
+                None
+            };
+            match stored {
+                Some(k) => k.to_address(),
+                None => {
+                    error!("No Ethereum key is stored, use --relayer-address or `gbt keys set-ethereum-key`");
+                    exit(1);
+                }
+            }
+        }
+    };
+    if args.lookback_days == 0 {
+        error!("--lookback-days must be at least 1");
+        exit(1);
+    }
+
+    let connections = create_rpc_connections(
+        address_prefix,
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc.clone()),
+        TIMEOUT,
+    )
+    .await;
+    let web3 = connections.web3.unwrap();
+    let eth_client = HttpClient::new(&args.ethereum_rpc);
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let mut grpc = connections.grpc.unwrap();
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+
+    let end_block = web3.eth_block_number().await.unwrap();
+    let block_time = match average_block_time(&web3, end_block).await {
+        Ok(t) => t,
+        Err(e) => {
+            error!(
+                "Failed to get the block time, is your Ethereum node working? {:?}",
+                e
+            );
+            exit(1);
+        }
+    };
+    let lookback_blocks: Uint256 = (args.lookback_days * SECONDS_PER_DAY / block_time).into();
+    let start_block = if end_block > lookback_blocks {
+        end_block - lookback_blocks
+    } else {
+        0u8.into()
+    };
+
+    let mut relays = 0u64;
+    let mut gas_used = 0u64;
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block > BLOCKS_TO_SEARCH.into() {
+            current_block + BLOCKS_TO_SEARCH.into()
+        } else {
+            end_block
+        };
+        info!("Searching blocks {} to {}", current_block, end_search);
+        let tx_hashes =
+            match find_gravity_calls(&web3, gravity_contract_address, current_block, end_search)
+                .await
+            {
+                Ok(h) => h,
+                Err(e) => {
+                    error!(
+                        "Failed to search blocks {} to {}, is your Ethereum node working? {:?}",
+                        current_block, end_search, e
+                    );
+                    exit(1);
+                }
+            };
+        for tx_hash in tx_hashes {
+            match get_gas_used_by(&eth_client, &tx_hash, relayer).await {
+                Ok(Some(gas)) => {
+                    relays += 1;
+                    gas_used += gas;
+                }
+                Ok(None) => {}
+                Err(e) => warn!(
+                    "Could not check tx 0x{} {:?}",
+                    bytes_to_hex_str(&tx_hash),
+                    e
+                ),
+            }
+        }
+        current_block = end_search + 1u8.into();
+    }
+
+    let balance = web3.eth_get_balance(relayer).await.unwrap();
+    let gas_price = web3.eth_gas_price().await.unwrap();
+    let daily_cost = daily_cost(gas_used, args.lookback_days, gas_price);
+    info!(
+        "{} relayed {} times in the last {} days using {} gas, {:.1} relays per day",
+        relayer,
+        relays,
+        args.lookback_days,
+        gas_used,
+        relays as f64 / args.lookback_days as f64
+    );
+    info!(
+        "At the current gas price of {} gwei relaying costs {} ETH per day, the balance is {} ETH",
+        gas_price / 1_000_000_000u64.into(),
+        format_eth(daily_cost),
+        format_eth(balance)
+    );
+    match runway_days(balance, daily_cost) {
+        Some(days) => info!("The balance will last about {:.1} days", days),
+        None => info!(
+            "{} has not relayed recently, no runway estimate is possible",
+            relayer
+        ),
+    }
+
+    let top_up = match top_up_amount(
+        balance,
+        daily_cost,
+        args.min_runway_days,
+        args.target_runway_days,
+    ) {
+        Some(a) => a,
+        None => return,
+    };
+    warn!(
+        "The runway is below {} days, {} ETH is needed to last {} days",
+        args.min_runway_days,
+        format_eth(top_up),
+        args.target_runway_days
+    );
+    let funding_key = match args.funding_key {
+        Some(k) => k,
+        None => return,
+    };
+    let max_top_up = (args.max_top_up * 1e18) as u128;
+    let top_up = if top_up > max_top_up.into() {
+        warn!(
+            "Limiting the top up to --max-top-up {} ETH",
+            args.max_top_up
+        );
+        max_top_up.into()
+    } else {
+        top_up
+    };
+    if let Err(e) = fund(&web3, funding_key, relayer, top_up).await {
+        error!(
+            "Failed to send {} ETH from {} to {} {:?}",
+            format_eth(top_up),
+            funding_key.to_address(),
+            relayer,
+            e
+        );
+        exit(1);
+    }
+    info!(
+        "Sent {} ETH from {} to {}",
+        format_eth(top_up),
+        funding_key.to_address(),
+        relayer
+    );
+}
+
+/// The average number of seconds per block over the last BLOCK_TIME_SAMPLE blocks
+async fn average_block_time(web3: &Web3, latest: Uint256) -> Result<u64, GravityError> {
+    let sample: Uint256 = BLOCK_TIME_SAMPLE.into();
+    let earlier = if latest > sample {
+        latest - sample
+    } else {
+        0u8.into()
+    };
+    let end = web3.eth_get_block_by_number(latest).await?;
+    let start = web3.eth_get_block_by_number(earlier).await?;
+    let seconds = end.timestamp - start.timestamp;
+    let blocks = end.number - start.number;
+    if blocks == 0u8.into() || seconds == 0u8.into() {
+        return Err(GravityError::InvalidBridgeStateError(
+            "Not enough blocks to find the block time".to_string(),
+        ));
+    }
+    Ok((seconds / blocks).to_string().parse().unwrap_or(1).max(1))
+}
+
+/// The gas used by `tx_hash` if it was sent by `sender`
+async fn get_gas_used_by(
+    eth_client: &HttpClient,
+    tx_hash: &[u8],
+    sender: EthAddress,
+) -> Result<Option<u64>, GravityError> {
+    let receipt: Value = eth_client
+        .request_method(
+            "eth_getTransactionReceipt",
+            vec![format!("0x{}", bytes_to_hex_str(tx_hash))],
+            TIMEOUT,
+        )
+        .await?;
+    let from: Option<EthAddress> = receipt["from"].as_str().and_then(|f| f.parse().ok());
+    if from != Some(sender) {
+        return Ok(None);
+    }
+    receipt["gasUsed"]
+        .as_str()
+        .and_then(|g| u64::from_str_radix(g.trim_start_matches("0x"), 16).ok())
+        .map(Some)
+        .ok_or_else(|| GravityError::EthereumContractError("Receipt has no gas used".to_string()))
+}
+
+async fn fund(
+    web3: &Web3,
+    funding_key: EthPrivateKey,
+    destination: EthAddress,
+    amount: Uint256,
+) -> Result<(), GravityError> {
+    let txid = web3
+        .send_prepared_transaction(
+            web3.prepare_transaction(
+                destination,
+                Vec::new(),
+                amount,
+                funding_key,
+                Vec::<SendTxOption>::new(),
+            )
+            .await?,
+        )
+        .await?;
+    web3.wait_for_transaction(txid, TIMEOUT, None).await?;
+    Ok(())
+}
+
+/// The wei spent per day if `gas_used` gas over `days` days is paid for at `gas_price`
+fn daily_cost(gas_used: u64, days: u64, gas_price: Uint256) -> Uint256 {
+    let gas: Uint256 = gas_used.into();
+    gas * gas_price / days.into()
+}
+
+/// How many days `balance` lasts at `daily_cost`, None if nothing is being spent
+fn runway_days(balance: Uint256, daily_cost: Uint256) -> Option<f64> {
+    if daily_cost == 0u8.into() {
+        return None;
+    }
+    let balance: f64 = balance.to_string().parse().ok()?;
+    let daily_cost: f64 = daily_cost.to_string().parse().ok()?;
+    Some(balance / daily_cost)
+}
+
+/// The wei needed to last `target_days` if `balance` lasts less than `min_days`
+fn top_up_amount(
+    balance: Uint256,
+    daily_cost: Uint256,
+    min_days: u64,
+    target_days: u64,
+) -> Option<Uint256> {
+    if daily_cost == 0u8.into() || balance >= daily_cost * min_days.into() {
+        return None;
+    }
+    let target = daily_cost * target_days.into();
+    if target > balance {
+        Some(target - balance)
+    } else {
+        None
+    }
+}
+
+fn format_eth(wei: Uint256) -> String {
+    let wei: f64 = wei.to_string().parse().unwrap_or(0.0);
+    format!("{:.4}", wei / 1e18)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runway() {
+        // 1M gas a day at 10 gwei is 0.01 ETH a day
+        let gas_price: Uint256 = 10_000_000_000u64.into();
+        let daily = daily_cost(7_000_000, 7, gas_price);
+        assert_eq!(daily, 10_000_000_000_000_000u64.into());
+        let balance: Uint256 = 50_000_000_000_000_000u64.into();
+        assert_eq!(runway_days(balance, daily), Some(5.0));
+        assert_eq!(runway_days(balance, 0u8.into()), None);
+
+        // 5 days is below the 7 day minimum, topping up to 30 days needs 0.25 ETH
+        assert_eq!(
+            top_up_amount(balance, daily, 7, 30),
+            Some(250_000_000_000_000_000u64.into())
+        );
+        assert_eq!(top_up_amount(balance, daily, 3, 30), None);
+        assert_eq!(top_up_amount(balance, 0u8.into(), 7, 30), None);
+    }
+}
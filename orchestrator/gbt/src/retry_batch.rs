@@ -0,0 +1,120 @@
+//! Early retry of batches the relayer skips because their last relay reverted. The skipped batches
+//! live in the running relayer or orchestrator, so `gbt relayer retry-batch` uses a request file in
+//! the shared home directory in the same way as `gbt log-level`
+
+use crate::args::RelayerRetryBatchOpts;
+use clarity::Address as EthAddress;
+use relayer::revert_blacklist::clear_blacklist;
+use std::fs;
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+
+/// The name of the request file, it contains the token and nonce to retry
+pub const RETRY_BATCH_REQUEST_NAME: &str = "retry-batch-request";
+/// How often running processes check for a retry request
+const RETRY_BATCH_REQUEST_CHECK_SPEED: Duration = Duration::from_secs(2);
+
+/// Encodes which batches to retry as `token:nonce`, `token` or `all`
+fn encode_request(token: Option<EthAddress>, nonce: Option<u64>) -> String {
+    match (token, nonce) {
+        (Some(token), Some(nonce)) => format!("{}:{}", token, nonce),
+        (Some(token), None) => token.to_string(),
+        (None, _) => "all".to_string(),
+    }
+}
+
+fn parse_request(request: &str) -> Result<(Option<EthAddress>, Option<u64>), String> {
+    let request = request.trim();
+    if request == "all" {
+        return Ok((None, None));
+    }
+    let (token, nonce) = match request.split_once(':') {
+        Some((token, nonce)) => (
+            token,
+            Some(
+                nonce
+                    .parse()
+                    .map_err(|_| format!("Invalid batch nonce {:?}", nonce))?,
+            ),
+        ),
+        None => (request, None),
+    };
+    let token = token
+        .parse()
+        .map_err(|_| format!("Invalid token address {:?}", token))?;
+    Ok((Some(token), nonce))
+}
+
+/// Runs alongside the relayer loops, clearing skipped batches whenever `gbt relayer retry-batch`
+/// asks for it
+pub async fn retry_batch_listener(home_dir: PathBuf) {
+    let request_file = home_dir.join(RETRY_BATCH_REQUEST_NAME);
+    loop {
+        if request_file.exists() {
+            match fs::read_to_string(&request_file) {
+                Ok(request) => match parse_request(&request) {
+                    Ok((token, nonce)) => info!(
+                        "Retrying {} skipped batches on the next relayer loop",
+                        clear_blacklist(token, nonce)
+                    ),
+                    Err(e) => error!("Invalid retry batch request {}", e),
+                },
+                Err(e) => error!("Failed to read retry batch request {:?}", e),
+            }
+            if let Err(e) = fs::remove_file(&request_file) {
+                error!("Failed to remove retry batch request {:?}", e);
+            }
+        }
+        delay_for(RETRY_BATCH_REQUEST_CHECK_SPEED).await;
+    }
+}
+
+pub async fn retry_batch(args: RelayerRetryBatchOpts, home_dir: PathBuf) {
+    if !home_dir.exists() {
+        error!(
+            "Home directory {} does not exist, use the same --home as the running relayer",
+            home_dir.display()
+        );
+        exit(1);
+    }
+    let request_file = home_dir.join(RETRY_BATCH_REQUEST_NAME);
+    if let Err(e) = fs::write(&request_file, encode_request(args.token, args.nonce)) {
+        error!("Failed to write retry batch request {:?}", e);
+        exit(1);
+    }
+
+    let timeout = Duration::from_secs(args.timeout);
+    let start = Instant::now();
+    while request_file.exists() {
+        if Instant::now() - start > timeout {
+            let _ = fs::remove_file(&request_file);
+            error!(
+                "No orchestrator or relayer using {} responded within {}s, is it running?",
+                home_dir.display(),
+                args.timeout
+            );
+            exit(1);
+        }
+        delay_for(Duration::from_millis(500)).await;
+    }
+    info!("Request accepted, the running process logs how many skipped batches will be retried");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_batch_request() {
+        let token: EthAddress = "0x0412C7c846bb6b7DC462CF6B453f76D8440b2609"
+            .parse()
+            .unwrap();
+        for (t, n) in [(None, None), (Some(token), None), (Some(token), Some(7))] {
+            assert_eq!(parse_request(&encode_request(t, n)), Ok((t, n)));
+        }
+        assert!(parse_request("0x1234:7").is_err());
+        assert!(parse_request(&format!("{}:seven", token)).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Signs a single logic call by hand. The orchestrator refuses to sign logic calls violating the
+//! operator's logic_call_policy and alerts instead, this is how a call is signed once it has been
+//! reviewed and found legitimate
+
+use crate::args::SignLogicCallOpts;
+use crate::config::{config_exists, load_keys};
+use crate::utils::TIMEOUT;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use cosmos_gravity::query::{get_gravity_params, get_oldest_unsigned_logic_calls};
+use cosmos_gravity::send::send_logic_call_confirm;
+use deep_space::{CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::signer::build_ethereum_signer;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::types::GravityBridgeToolsConfig;
+use orchestrator::logic_call_policy::LogicCallInspection;
+use std::path::Path;
+use std::process::exit;
+
+pub async fn sign_logic_call(
+    args: SignLogicCallOpts,
+    address_prefix: String,
+    home_dir: &Path,
+    config: GravityBridgeToolsConfig,
+) {
+    let invalidation_id = match hex_str_to_bytes(&args.invalidation_id) {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Invalid invalidation id {:?}", e);
+            exit(1);
+        }
+    };
+    let keys = if config_exists(home_dir) {
+        Some(load_keys(home_dir))
+    } else {
+        None
+    };
+    let cosmos_key = match args.cosmos_phrase.or_else(|| {
+        keys.as_ref()
+            .and_then(|k| k.orchestrator_phrase.as_ref())
+            .map(|phrase| CosmosPrivateKey::from_phrase(phrase, "").unwrap())
+    }) {
+        Some(k) => k,
+        None => {
+            error!("No orchestrator key is stored, use --cosmos-phrase or `gbt keys set-orchestrator-key`");
+            exit(1);
+        }
+    };
+    let ethereum_key = match args
+        .ethereum_key
+        .or_else(|| keys.as_ref().and_then(|k| k.ethereum_key))
+    {
+        Some(k) => k,
+        None => {
+            error!("No Ethereum key is stored, use --ethereum-key or `gbt keys set-ethereum-key`");
+            exit(1);
+        }
+    };
+
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        None,
+        TIMEOUT,
+    )
+    .await;
+    let mut grpc = connections.grpc.unwrap();
+    let contact = connections.contact.unwrap();
+    let our_address = cosmos_key.to_address(&address_prefix).unwrap();
+
+    let unsigned =
+        match get_oldest_unsigned_logic_calls(&mut grpc, our_address, address_prefix).await {
+            Ok(calls) => calls,
+            Err(e) => {
+                error!("Failed to get the logic calls awaiting a signature {:?}", e);
+                exit(1);
+            }
+        };
+    let invalidation_nonce = args.invalidation_nonce;
+    let call = match unsigned.into_iter().find(|c| {
+        c.invalidation_id == invalidation_id && c.invalidation_nonce == invalidation_nonce
+    }) {
+        Some(c) => c,
+        None => {
+            error!(
+                "Logic call 0x{}/{} is not awaiting a signature from {}, it may already be signed or timed out",
+                bytes_to_hex_str(&invalidation_id),
+                invalidation_nonce,
+                our_address
+            );
+            exit(1);
+        }
+    };
+    info!(
+        "Signing logic call\n{}\n    {}",
+        call.checkpoint_summary(),
+        LogicCallInspection::new(&call)
+    );
+
+    let gravity_id = match get_gravity_params(&mut grpc).await {
+        Ok(p) => p.gravity_id,
+        Err(e) => {
+            error!("Failed to get the Gravity params {:?}", e);
+            exit(1);
+        }
+    };
+    let eth_signer =
+        match build_ethereum_signer(&config.orchestrator.ethereum_signer, ethereum_key).await {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to set up the Ethereum signer: {}", e);
+                exit(1);
+            }
+        };
+    match send_logic_call_confirm(
+        &contact,
+        eth_signer.as_ref(),
+        args.fees,
+        vec![call],
+        cosmos_key,
+        gravity_id,
+    )
+    .await
+    {
+        Ok(r) => info!("Logic call signed with txid {}", r.txhash),
+        Err(e) => {
+            error!("Failed to sign the logic call {:?}", e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Output for the --simulate flag of transaction commands. The transaction is run against the current
+//! chain state and what it would cost and do is printed, nothing is broadcast
+
+use clarity::{Address as EthAddress, Uint256};
+use deep_space::error::CosmosGrpcError;
+use deep_space::{Coin, Contact, Msg, PrivateKey};
+use gravity_proto::cosmos_sdk_proto::cosmos::tx::v1beta1::SimulateResponse;
+use std::process::exit;
+use web30::client::Web3;
+use web30::types::TransactionRequest;
+
+/// Simulates a Cosmos transaction with the given messages and prints the result
+pub async fn simulate_cosmos_tx(
+    contact: &Contact,
+    msgs: &[Msg],
+    fee: &[Coin],
+    private_key: impl PrivateKey,
+) {
+    let res = contact.simulate_tx(msgs, Some(fee), private_key).await;
+    print_cosmos_simulation(res, fee);
+}
+
+/// Prints the gas, fees and events of a Cosmos simulation, exits if the transaction would fail
+pub fn print_cosmos_simulation(res: Result<SimulateResponse, CosmosGrpcError>, fee: &[Coin]) {
+    let res = match res {
+        Ok(r) => r,
+        Err(e) => {
+            error!(
+                "Simulation failed, this transaction would be rejected {:?}",
+                e
+            );
+            exit(1);
+        }
+    };
+    info!("Simulation succeeded, nothing has been broadcast");
+    if let Some(gas) = res.gas_info {
+        info!("Gas used {} of {} wanted", gas.gas_used, gas.gas_wanted);
+    }
+    for f in fee {
+        info!("Fee {}", f);
+    }
+    if let Some(result) = res.result {
+        for event in result.events {
+            let attributes: Vec<String> = event
+                .attributes
+                .iter()
+                .map(|a| {
+                    format!(
+                        "{}={}",
+                        String::from_utf8_lossy(&a.key),
+                        String::from_utf8_lossy(&a.value)
+                    )
+                })
+                .collect();
+            info!("Event {} {}", event.r#type, attributes.join(" "));
+        }
+    }
+}
+
+/// Estimates the gas of an Ethereum transaction and prints its expected cost at the current gas
+/// price, exits if the transaction would revert
+pub async fn simulate_eth_tx(
+    web3: &Web3,
+    from: EthAddress,
+    to: EthAddress,
+    payload: Vec<u8>,
+    description: &str,
+) {
+    let gas = match web3
+        .eth_estimate_gas(TransactionRequest::quick_tx(from, to, payload))
+        .await
+    {
+        Ok(g) => g,
+        Err(e) => {
+            error!(
+                "Simulation of {} failed, this transaction would revert {:?}",
+                description, e
+            );
+            exit(1);
+        }
+    };
+    print_eth_cost(web3, gas, description).await;
+}
+
+/// Prints the cost of spending `gas` at the current gas price
+pub async fn print_eth_cost(web3: &Web3, gas: Uint256, description: &str) {
+    match web3.eth_gas_price().await {
+        Ok(gas_price) => info!(
+            "{} would use {} gas, costing {} wei at the current gas price of {} wei",
+            description,
+            gas,
+            gas * gas_price,
+            gas_price
+        ),
+        Err(e) => {
+            info!("{} would use {} gas", description, gas);
+            warn!("Failed to get the gas price {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Exports tracing spans from the oracle, signer and relayer to an OpenTelemetry collector, and
+//! sends the opt-in periodic reports configured in `[orchestrator.telemetry_report]`
+
+use clarity::utils::bytes_to_hex_str;
+use gravity_utils::types::{TelemetryReportConfig, TracingConfig};
+use metrics_exporter::{error_counts, latest_values};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tracing_subscriber::layer::SubscriberExt;
+
+const REPORT_TIMEOUT: Duration = Duration::from_secs(10);
+/// The information gauges included in reports, the rest can identify the orchestrator or its nodes
+const REPORTED_GAUGES: &[&str] = &[
+    "event_nonces_behind",
+    "signer_blocks_to_sign",
+    "signer_oldest_unsigned_blocks",
+    "blocks_until_slashing",
+    "ethereum_node_healthy",
+    "cosmos_node_healthy",
+];
+
+/// Installs an OTLP exporter as the global tracing subscriber if tracing is enabled in the config.
+/// Logging is unaffected, it continues to go through env_logger
+pub fn start_tracing(config: &TracingConfig) {
+    if !config.tracing_enabled {
+        return;
+    }
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(&config.otlp_endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                config.service_name.clone(),
+            )])),
+        )
+        .install_batch(runtime::Tokio);
+    let tracer = match tracer {
+        Ok(t) => t,
+        Err(e) => {
+            error!("Failed to start OpenTelemetry exporter {:?}", e);
+            return;
+        }
+    };
+    let subscriber =
+        tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+    match tracing::subscriber::set_global_default(subscriber) {
+        Ok(()) => info!("Exporting traces to {}", config.otlp_endpoint),
+        Err(e) => error!("Failed to install tracing subscriber {:?}", e),
+    }
+}
+
+/// Periodically POSTs a report to the configured endpoint, returns immediately if none is set.
+/// Failed reports are logged and dropped, the next one carries the same counters
+pub async fn telemetry_report_loop(config: TelemetryReportConfig) {
+    let endpoint = match config.endpoint {
+        Some(e) => e,
+        None => return,
+    };
+    let instance = match config.instance_name {
+        Some(n) => n,
+        None => bytes_to_hex_str(&thread_rng().gen::<[u8; 8]>()),
+    };
+    info!(
+        "Sending telemetry reports to {} every {} seconds as {}",
+        endpoint, config.interval_seconds, instance
+    );
+    let client = reqwest::Client::new();
+    let started = Instant::now();
+    loop {
+        delay_for(Duration::from_secs(config.interval_seconds)).await;
+        let report = build_report(
+            &instance,
+            started.elapsed(),
+            &latest_values(),
+            &error_counts(),
+        );
+        let res = client
+            .post(&endpoint)
+            .timeout(REPORT_TIMEOUT)
+            .json(&report)
+            .send()
+            .await;
+        match res {
+            Ok(r) if r.status().is_success() => trace!("Sent telemetry report"),
+            Ok(r) => warn!("Telemetry endpoint rejected report with {}", r.status()),
+            Err(e) => warn!("Failed to send telemetry report {:?}", e),
+        }
+    }
+}
+
+fn build_report(
+    instance: &str,
+    uptime: Duration,
+    gauges: &BTreeMap<String, i64>,
+    errors: &BTreeMap<&'static str, BTreeMap<String, u64>>,
+) -> Value {
+    let lag: BTreeMap<&str, i64> = REPORTED_GAUGES
+        .iter()
+        .filter_map(|g| gauges.get(*g).map(|v| (*g, *v)))
+        .collect();
+    json!({
+        "instance": instance,
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_hash": env!("GBT_GIT_HASH"),
+        "uptime_seconds": uptime.as_secs(),
+        "lag": lag,
+        "errors": errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report() {
+        let mut gauges = BTreeMap::new();
+        gauges.insert("event_nonces_behind".to_string(), 3);
+        gauges.insert("fee_grant_transactions_remaining".to_string(), 500);
+        let mut errors = BTreeMap::new();
+        errors.insert(
+            "eth",
+            BTreeMap::from([("Failed to get events".to_string(), 2)]),
+        );
+        let report = build_report("abc", Duration::from_secs(60), &gauges, &errors);
+        assert_eq!(report["instance"], "abc");
+        assert_eq!(report["uptime_seconds"], 60);
+        assert_eq!(report["lag"], json!({ "event_nonces_behind": 3 }));
+        assert_eq!(report["errors"]["eth"]["Failed to get events"], 2);
+    }
+}
@@ -0,0 +1,427 @@
+//! Bridge load generation. Deposits and SendToEth transfers are submitted at a fixed rate while a
+//! tracker follows every one of them to the other side of the bridge, the resulting latencies and
+//! throughput are printed once all transfers complete or the settle timeout runs out.
+
+use crate::args::LoadTestOpts;
+use crate::utils::TIMEOUT;
+use clarity::{Address as EthAddress, PrivateKey as EthPrivateKey};
+use cosmos_gravity::query::{get_erc20_to_denom, get_gravity_params, get_pending_send_to_eth};
+use cosmos_gravity::send::send_to_eth;
+use deep_space::{Address as CosmosAddress, Coin, Contact, CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::connection_prep::{check_for_eth, check_for_fee, create_rpc_connections};
+use num256::Uint256;
+use std::collections::{HashMap, HashSet};
+use std::process::exit;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// How often the tracker checks for completed transfers
+const TRACKING_INTERVAL: Duration = Duration::from_secs(5);
+/// How often progress is printed while the test runs
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shared between the generators and the tracker, never locked across an await
+#[derive(Default)]
+struct LoadState {
+    /// Submission time of every successful deposit, deposits to the same destination are
+    /// credited in event nonce order so these complete first in first out
+    deposits_sent: Vec<Instant>,
+    deposit_latencies: Vec<Duration>,
+    deposit_failures: usize,
+    /// Submission time of every SendToEth still pending on Gravity, by transfer id
+    sends_pending: HashMap<u64, Instant>,
+    send_latencies: Vec<Duration>,
+    send_failures: usize,
+    /// Set once both generators have stopped submitting
+    generation_done: Option<Instant>,
+}
+
+impl LoadState {
+    fn outstanding(&self) -> usize {
+        self.deposits_sent.len() - self.deposit_latencies.len() + self.sends_pending.len()
+    }
+}
+
+pub async fn load_test(args: LoadTestOpts, address_prefix: String) {
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc.clone()),
+        Some(args.ethereum_rpc.clone()),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+
+    let cosmos_address = args.cosmos_phrase.to_address(&address_prefix).unwrap();
+    let eth_address = args.ethereum_key.to_address();
+    check_for_eth(eth_address, &web3).await;
+    check_for_fee(&args.fee, cosmos_address, &contact).await;
+
+    let params = get_gravity_params(&mut grpc).await.unwrap();
+    let gravity_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => match params.bridge_ethereum_address.parse() {
+            Ok(c) => c,
+            Err(_) => {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+        },
+    };
+    let denom = match get_erc20_to_denom(&mut grpc, args.token_contract_address).await {
+        Ok(v) => v.denom,
+        Err(e) => {
+            error!(
+                "Could not find the Gravity denom for {}, is it bridged? {:?}",
+                args.token_contract_address, e
+            );
+            exit(1);
+        }
+    };
+
+    // deposits go to a fresh address so its balance only ever reflects this run
+    let secret: [u8; 32] = rand::random();
+    let deposit_destination = CosmosPrivateKey::from_secret(&secret)
+        .to_address(&address_prefix)
+        .unwrap();
+    let existing_sends = get_pending_send_ids(&mut grpc, cosmos_address).await;
+
+    info!(
+        "Generating load for {} minutes, {} deposits and {} SendToEth per minute of {} {}",
+        args.duration, args.deposits_per_minute, args.sends_per_minute, args.amount, denom
+    );
+    info!(
+        "Deposits from {} to {}, SendToEth from {} to {}",
+        eth_address, deposit_destination, cosmos_address, eth_address
+    );
+
+    let state = Mutex::new(LoadState::default());
+    let start = Instant::now();
+    let generation_end = start + Duration::from_secs(args.duration * 60);
+    let amount: Uint256 = args.amount.into();
+
+    let deposits = generate_deposits(
+        &state,
+        &web3,
+        args.ethereum_key,
+        gravity_address,
+        args.token_contract_address,
+        amount,
+        deposit_destination,
+        args.deposits_per_minute,
+        generation_end,
+    );
+    let sends = generate_sends(
+        &state,
+        &contact,
+        grpc.clone(),
+        args.cosmos_phrase,
+        cosmos_address,
+        eth_address,
+        Coin {
+            denom: denom.clone(),
+            amount,
+        },
+        Coin {
+            denom: denom.clone(),
+            amount: args.bridge_fee.into(),
+        },
+        args.fee.clone(),
+        existing_sends,
+        args.sends_per_minute,
+        generation_end,
+    );
+    let generators = async {
+        futures::join!(deposits, sends);
+        state.lock().unwrap().generation_done = Some(Instant::now());
+        info!("Load generation complete, waiting for in flight transfers");
+    };
+    let tracker = track_transfers(
+        &state,
+        &contact,
+        grpc.clone(),
+        deposit_destination,
+        cosmos_address,
+        denom,
+        amount,
+        Duration::from_secs(args.settle_timeout * 60),
+    );
+    futures::join!(generators, tracker);
+
+    print_report(&state.lock().unwrap(), Instant::now() - start);
+}
+
+/// The time to wait between submissions for the given rate, None if the rate disables submission
+fn submission_interval(per_minute: f64) -> Option<Duration> {
+    if per_minute <= 0.0 {
+        None
+    } else {
+        Some(Duration::from_secs_f64(60.0 / per_minute))
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_deposits(
+    state: &Mutex<LoadState>,
+    web3: &Web3,
+    ethereum_key: EthPrivateKey,
+    gravity_address: EthAddress,
+    erc20: EthAddress,
+    amount: Uint256,
+    destination: CosmosAddress,
+    per_minute: f64,
+    generation_end: Instant,
+) {
+    let interval = match submission_interval(per_minute) {
+        Some(i) => i,
+        None => return,
+    };
+    let mut next = Instant::now();
+    while next < generation_end {
+        let submitted = Instant::now();
+        let res = send_to_cosmos(
+            erc20,
+            gravity_address,
+            amount,
+            destination,
+            ethereum_key,
+            Some(TIMEOUT),
+            web3,
+            vec![],
+        )
+        .await;
+        match res {
+            Ok(_) => state.lock().unwrap().deposits_sent.push(submitted),
+            Err(e) => {
+                warn!("Deposit failed {:?}", e);
+                state.lock().unwrap().deposit_failures += 1;
+            }
+        }
+        // if submission took longer than the interval we fall behind rather than bursting
+        next = (next + interval).max(Instant::now());
+        delay_for(next.saturating_duration_since(Instant::now())).await;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn generate_sends(
+    state: &Mutex<LoadState>,
+    contact: &Contact,
+    mut grpc: GravityQueryClient<Channel>,
+    cosmos_key: CosmosPrivateKey,
+    sender: CosmosAddress,
+    destination: EthAddress,
+    amount: Coin,
+    bridge_fee: Coin,
+    fee: Coin,
+    existing_sends: HashSet<u64>,
+    per_minute: f64,
+    generation_end: Instant,
+) {
+    let interval = match submission_interval(per_minute) {
+        Some(i) => i,
+        None => return,
+    };
+    // every id we have ever attributed to a submission, so that completed sends are not re-added
+    let mut known = existing_sends;
+    let mut next = Instant::now();
+    while next < generation_end {
+        let submitted = Instant::now();
+        let res = send_to_eth(
+            cosmos_key,
+            destination,
+            amount.clone(),
+            bridge_fee.clone(),
+            None,
+            fee.clone(),
+            contact,
+            None,
+        )
+        .await;
+        match res {
+            Ok(_) => {
+                // our sends are sequential, so exactly one new id appears for each one
+                let new_ids: Vec<u64> = get_pending_send_ids(&mut grpc, sender)
+                    .await
+                    .into_iter()
+                    .filter(|id| !known.contains(id))
+                    .collect();
+                let mut state = state.lock().unwrap();
+                for id in new_ids {
+                    known.insert(id);
+                    state.sends_pending.insert(id, submitted);
+                }
+            }
+            Err(e) => {
+                warn!("SendToEth failed {:?}", e);
+                state.lock().unwrap().send_failures += 1;
+            }
+        }
+        next = (next + interval).max(Instant::now());
+        delay_for(next.saturating_duration_since(Instant::now())).await;
+    }
+}
+
+/// Follows submitted transfers until they complete, deposits complete once credited on Gravity and
+/// SendToEth transfers once they are no longer pending, meaning they were executed on Ethereum
+#[allow(clippy::too_many_arguments)]
+async fn track_transfers(
+    state: &Mutex<LoadState>,
+    contact: &Contact,
+    mut grpc: GravityQueryClient<Channel>,
+    deposit_destination: CosmosAddress,
+    sender: CosmosAddress,
+    denom: String,
+    amount: Uint256,
+    settle_timeout: Duration,
+) {
+    let mut last_progress = Instant::now();
+    loop {
+        delay_for(TRACKING_INTERVAL).await;
+
+        let credited = match contact
+            .get_balance(deposit_destination, denom.clone())
+            .await
+        {
+            Ok(b) => b.map(|c| c.amount).unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to get deposit balance {:?}", e);
+                continue;
+            }
+        };
+        let pending = match get_pending_send_to_eth(&mut grpc, sender).await {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to get pending SendToEth transfers {:?}", e);
+                continue;
+            }
+        };
+        let pending: HashSet<u64> = pending
+            .transfers_in_batches
+            .iter()
+            .chain(pending.unbatched_transfers.iter())
+            .map(|tx| tx.id)
+            .collect();
+
+        let now = Instant::now();
+        let mut state = state.lock().unwrap();
+        let mut completed = state.deposit_latencies.len();
+        while completed < state.deposits_sent.len()
+            && credited >= amount * Uint256::from(completed as u64 + 1)
+        {
+            let latency = now - state.deposits_sent[completed];
+            state.deposit_latencies.push(latency);
+            completed += 1;
+        }
+        let executed: Vec<u64> = state
+            .sends_pending
+            .keys()
+            .filter(|id| !pending.contains(id))
+            .cloned()
+            .collect();
+        for id in executed {
+            let submitted = state.sends_pending.remove(&id).unwrap();
+            state.send_latencies.push(now - submitted);
+        }
+
+        if now - last_progress > PROGRESS_INTERVAL {
+            last_progress = now;
+            info!(
+                "{} of {} deposits credited, {} SendToEth executed, {} transfers in flight",
+                state.deposit_latencies.len(),
+                state.deposits_sent.len(),
+                state.send_latencies.len(),
+                state.outstanding()
+            );
+        }
+        if let Some(done) = state.generation_done {
+            if state.outstanding() == 0 {
+                return;
+            }
+            if now - done > settle_timeout {
+                warn!(
+                    "{} transfers did not complete within the settle timeout",
+                    state.outstanding()
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// Returns the ids of all pending SendToEth transfers for this sender, batched or not
+async fn get_pending_send_ids(
+    grpc: &mut GravityQueryClient<Channel>,
+    sender: CosmosAddress,
+) -> HashSet<u64> {
+    match get_pending_send_to_eth(grpc, sender).await {
+        Ok(pending) => pending
+            .transfers_in_batches
+            .iter()
+            .chain(pending.unbatched_transfers.iter())
+            .map(|tx| tx.id)
+            .collect(),
+        Err(e) => {
+            warn!("Failed to get pending SendToEth transfers {:?}", e);
+            HashSet::new()
+        }
+    }
+}
+
+fn print_report(state: &LoadState, elapsed: Duration) {
+    let minutes = elapsed.as_secs_f64() / 60.0;
+    info!("Load test finished after {:.1} minutes", minutes);
+    print_direction(
+        "sendToCosmos deposits",
+        state.deposits_sent.len(),
+        state.deposit_failures,
+        &state.deposit_latencies,
+        minutes,
+    );
+    print_direction(
+        "SendToEth transfers",
+        state.sends_pending.len() + state.send_latencies.len(),
+        state.send_failures,
+        &state.send_latencies,
+        minutes,
+    );
+}
+
+fn print_direction(
+    name: &str,
+    submitted: usize,
+    failed: usize,
+    latencies: &[Duration],
+    minutes: f64,
+) {
+    info!(
+        "{}: {} submitted, {} failed to submit, {} completed, {} never completed",
+        name,
+        submitted,
+        failed,
+        latencies.len(),
+        submitted - latencies.len()
+    );
+    if latencies.is_empty() {
+        return;
+    }
+    let mut sorted = latencies.to_vec();
+    sorted.sort();
+    let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100].as_secs();
+    info!(
+        "{}: {:.2} completed per minute, latency min {}s p50 {}s p90 {}s max {}s",
+        name,
+        latencies.len() as f64 / minutes,
+        sorted[0].as_secs(),
+        percentile(50),
+        percentile(90),
+        sorted[sorted.len() - 1].as_secs()
+    );
+}
@@ -1,8 +1,53 @@
-use gravity_utils::types::{BatchRequestMode, RelayerConfig, ValsetRelayingMode};
+use cosmos_gravity::query::get_denom_trace;
+use deep_space::Contact;
+use gravity_utils::types::{BatchRequestMode, GasOracleSource, RelayerConfig, ValsetRelayingMode};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
 use std::time::Duration;
 
 pub const TIMEOUT: Duration = Duration::from_secs(60);
 
+lazy_static! {
+    // denom traces never change once created, so we only ever look up each ibc/ denom once
+    static ref DENOM_TRACE_CACHE: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+}
+
+/// Formats a denom for display to the user, ibc/HASH denoms are resolved into their base denom and
+/// the path they took to reach Gravity. Any other denom is returned as is, as is any ibc denom that
+/// can not be resolved
+pub async fn display_denom(contact: &Contact, denom: &str) -> String {
+    if !denom.starts_with("ibc/") {
+        return denom.to_string();
+    }
+    if let Some(v) = DENOM_TRACE_CACHE.read().unwrap().get(denom) {
+        return v.clone();
+    }
+
+    match get_denom_trace(contact, denom).await {
+        Ok(Some(trace)) => {
+            let display = if trace.path.is_empty() {
+                format!("{} ({})", denom, trace.base_denom)
+            } else {
+                format!("{} ({} via {})", denom, trace.base_denom, trace.path)
+            };
+            DENOM_TRACE_CACHE
+                .write()
+                .unwrap()
+                .insert(denom.to_string(), display.clone());
+            display
+        }
+        Ok(None) => {
+            debug!("No denom trace found for {}", denom);
+            denom.to_string()
+        }
+        Err(e) => {
+            debug!("Failed to get denom trace for {} with {:?}", denom, e);
+            denom.to_string()
+        }
+    }
+}
+
 /// Explains the relaying config to users
 pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
     info!("Relaying from Cosmos => Ethereum is enabled, this will cost ETH");
@@ -35,6 +80,13 @@ pub fn print_relaying_explanation(input: &RelayerConfig, batch_requests: bool) {
         ),
 
     }
+    match input.gas_oracle.source {
+        GasOracleSource::Node => {}
+        source => info!(
+            "This relayer will get relaying gas prices from {:?}",
+            source
+        ),
+    }
     match &input.batch_relaying_mode {
         gravity_utils::types::BatchRelayingMode::EveryBatch => info!("This relayer will relay every batch. This will cost a lot of ETH!"),
         gravity_utils::types::BatchRelayingMode::Altruistic => info!("This relayer will relay batches during the lowest {}% of gas prices over {} samples", input.altruistic_acceptable_gas_price_percentage * 100.0, input.altruistic_gas_price_samples),
@@ -0,0 +1,330 @@
+//! A live status view for operators without Prometheus. Every refresh queries both chains directly
+//! and prints one line per component, so it works whether or not the orchestrator is running and
+//! fits in a tmux pane. Errors are shown in place of a component's status and remembered, so an
+//! intermittent failure stays visible after the component recovers
+
+use crate::args::WatchOpts;
+use crate::config::{config_exists, load_keys};
+use crate::utils::TIMEOUT;
+use clarity::constants::zero_address;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::{
+    get_gravity_params, get_last_event_nonce_for_validator, get_oldest_unsigned_logic_calls,
+    get_oldest_unsigned_transaction_batches, get_oldest_unsigned_valsets,
+};
+use deep_space::client::ChainStatus;
+use deep_space::{Address as CosmosAddress, Coin, Contact, CosmosPrivateKey, PrivateKey};
+use ethereum_gravity::utils::{get_event_nonce, get_valset_nonce};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::QueryLastObservedEthNonceRequest;
+use gravity_utils::connection_prep::create_rpc_connections;
+use gravity_utils::num_conversion::print_eth;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::exit;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// Moves the cursor home and clears the screen
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+/// Errors longer than this are cut short to keep each component on one line
+const MAX_ERROR_LENGTH: usize = 80;
+
+/// The latest status of one component
+struct Row {
+    name: &'static str,
+    status: Result<String, String>,
+}
+
+pub async fn watch(args: WatchOpts, address_prefix: String, home_dir: &Path) {
+    let keys = if config_exists(home_dir) {
+        Some(load_keys(home_dir))
+    } else {
+        None
+    };
+    let orchestrator = args.orchestrator_address.or_else(|| {
+        keys.as_ref()
+            .and_then(|k| k.orchestrator_phrase.as_ref())
+            .map(|phrase| {
+                CosmosPrivateKey::from_phrase(phrase, "")
+                    .unwrap()
+                    .to_address(&address_prefix)
+                    .unwrap()
+            })
+    });
+    let ethereum = args.ethereum_address.or_else(|| {
+        keys.as_ref()
+            .and_then(|k| k.ethereum_key)
+            .map(|k| k.to_address())
+    });
+    if args.interval == 0 {
+        error!("--interval must be at least 1");
+        exit(1);
+    }
+
+    let connections = create_rpc_connections(
+        address_prefix.clone(),
+        Some(args.cosmos_grpc),
+        Some(args.ethereum_rpc),
+        TIMEOUT,
+    )
+    .await;
+    let contact = connections.contact.unwrap();
+    let web3 = connections.web3.unwrap();
+    let mut grpc = connections.grpc.unwrap();
+    let gravity_contract_address = match args.gravity_contract_address {
+        Some(c) => c,
+        None => {
+            let params = get_gravity_params(&mut grpc).await.unwrap();
+            let c: EthAddress = params.bridge_ethereum_address.parse().unwrap();
+            if c == zero_address() {
+                error!("The Gravity address is not yet set as a chain parameter! You must specify --gravity-contract-address");
+                exit(1);
+            }
+            c
+        }
+    };
+
+    let mut last_errors: HashMap<&'static str, (Instant, String)> = HashMap::new();
+    loop {
+        let rows = vec![
+            Row {
+                name: "cosmos",
+                status: cosmos_status(&contact, &mut grpc).await,
+            },
+            Row {
+                name: "ethereum",
+                status: ethereum_status(&web3, gravity_contract_address).await,
+            },
+            Row {
+                name: "oracle",
+                status: oracle_status(
+                    &web3,
+                    &mut grpc,
+                    gravity_contract_address,
+                    orchestrator,
+                    &address_prefix,
+                )
+                .await,
+            },
+            Row {
+                name: "signer",
+                status: signer_status(&mut grpc, orchestrator, &address_prefix).await,
+            },
+            Row {
+                name: "balances",
+                status: balances_status(&contact, &web3, orchestrator, ethereum).await,
+            },
+        ];
+
+        let now = Instant::now();
+        let mut out = String::new();
+        if !args.no_clear {
+            out.push_str(CLEAR_SCREEN);
+        }
+        out.push_str(&format!("gbt watch, refreshing every {}s\n", args.interval));
+        for row in rows {
+            if let Err(e) = &row.status {
+                last_errors.insert(row.name, (now, e.clone()));
+            }
+            let last_error = last_errors
+                .get(row.name)
+                .map(|(seen, e)| (now - *seen, e.as_str()));
+            out.push_str(&render_row(row.name, &row.status, last_error));
+            out.push('\n');
+        }
+        print!("{}", out);
+
+        delay_for(Duration::from_secs(args.interval)).await;
+    }
+}
+
+/// Formats a component's status on one line, with the last error seen if it has recovered since
+fn render_row(
+    name: &str,
+    status: &Result<String, String>,
+    last_error: Option<(Duration, &str)>,
+) -> String {
+    match (status, last_error) {
+        (Err(e), _) => format!("{:<9}ERROR {}", name, truncate(e)),
+        (Ok(s), Some((ago, e))) => format!(
+            "{:<9}{} (last error {} ago: {})",
+            name,
+            s,
+            format_age(ago),
+            truncate(e)
+        ),
+        (Ok(s), None) => format!("{:<9}{}", name, s),
+    }
+}
+
+fn truncate(e: &str) -> String {
+    let e = e.replace('\n', " ");
+    if e.chars().count() > MAX_ERROR_LENGTH {
+        format!(
+            "{}...",
+            e.chars().take(MAX_ERROR_LENGTH).collect::<String>()
+        )
+    } else {
+        e
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 60 * 60 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / (60 * 60))
+    }
+}
+
+async fn cosmos_status(
+    contact: &Contact,
+    grpc: &mut GravityQueryClient<Channel>,
+) -> Result<String, String> {
+    let height = match contact.get_chain_status().await {
+        Ok(ChainStatus::Moving { block_height }) => block_height,
+        Ok(ChainStatus::Syncing) => return Err("node is syncing".to_string()),
+        Ok(ChainStatus::WaitingToStart) => return Err("chain has not started".to_string()),
+        Err(e) => return Err(format!("{:?}", e)),
+    };
+    let observed = grpc
+        .get_last_observed_eth_nonce(QueryLastObservedEthNonceRequest { use_v1_key: false })
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .into_inner()
+        .nonce;
+    Ok(format!(
+        "height {}  last observed event nonce {}",
+        height, observed
+    ))
+}
+
+async fn ethereum_status(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+) -> Result<String, String> {
+    let block = web3
+        .eth_block_number()
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let event_nonce = get_event_nonce(gravity_contract_address, zero_address(), web3)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let valset_nonce = get_valset_nonce(gravity_contract_address, zero_address(), web3)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(format!(
+        "block {}  contract event nonce {}  valset nonce {}",
+        block, event_nonce, valset_nonce
+    ))
+}
+
+/// Our last claimed event nonce and how far it trails the Gravity contract
+async fn oracle_status(
+    web3: &Web3,
+    grpc: &mut GravityQueryClient<Channel>,
+    gravity_contract_address: EthAddress,
+    orchestrator: Option<CosmosAddress>,
+    prefix: &str,
+) -> Result<String, String> {
+    let orchestrator = match orchestrator {
+        Some(o) => o,
+        None => return Ok("no orchestrator address, use --orchestrator-address".to_string()),
+    };
+    let ours = get_last_event_nonce_for_validator(grpc, orchestrator, prefix.to_string())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let latest = get_event_nonce(gravity_contract_address, zero_address(), web3)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(format!(
+        "event nonce {}  {} behind the contract",
+        ours,
+        latest.saturating_sub(ours)
+    ))
+}
+
+async fn signer_status(
+    grpc: &mut GravityQueryClient<Channel>,
+    orchestrator: Option<CosmosAddress>,
+    prefix: &str,
+) -> Result<String, String> {
+    let orchestrator = match orchestrator {
+        Some(o) => o,
+        None => return Ok("no orchestrator address, use --orchestrator-address".to_string()),
+    };
+    let valsets = get_oldest_unsigned_valsets(grpc, orchestrator, prefix.to_string())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let batches = get_oldest_unsigned_transaction_batches(grpc, orchestrator, prefix.to_string())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let calls = get_oldest_unsigned_logic_calls(grpc, orchestrator, prefix.to_string())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(format!(
+        "unsigned valsets {}  batches {}  logic calls {}",
+        valsets.len(),
+        batches.len(),
+        calls.len()
+    ))
+}
+
+async fn balances_status(
+    contact: &Contact,
+    web3: &Web3,
+    orchestrator: Option<CosmosAddress>,
+    ethereum: Option<EthAddress>,
+) -> Result<String, String> {
+    let mut parts = Vec::new();
+    if let Some(o) = orchestrator {
+        let balances = contact
+            .get_balances(o)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let balances = if balances.is_empty() {
+            "nothing".to_string()
+        } else {
+            Coin::display_list(&balances)
+        };
+        parts.push(format!("{} {}", o, balances));
+    }
+    if let Some(e) = ethereum {
+        let balance = web3
+            .eth_get_balance(e)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        parts.push(format!("{} {} ETH", e, print_eth(balance)));
+    }
+    if parts.is_empty() {
+        return Ok("no addresses, use --orchestrator-address or --ethereum-address".to_string());
+    }
+    Ok(parts.join("  "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_row() {
+        let ok: Result<String, String> = Ok("block 100".to_string());
+        assert_eq!(render_row("ethereum", &ok, None), "ethereum block 100");
+        assert_eq!(
+            render_row("ethereum", &ok, Some((Duration::from_secs(125), "timeout"))),
+            "ethereum block 100 (last error 2m ago: timeout)"
+        );
+        let err: Result<String, String> = Err("connection\nrefused".to_string());
+        assert_eq!(
+            render_row("cosmos", &err, Some((Duration::ZERO, "connection refused"))),
+            "cosmos   ERROR connection refused"
+        );
+        assert_eq!(truncate(&"x".repeat(100)).len(), MAX_ERROR_LENGTH + 3);
+    }
+}
@@ -0,0 +1,108 @@
+//! An append only record of everything the orchestrator keys sign, one JSON object per line, so
+//! operators can review exactly what their keys were used for after the fact. Nothing is recorded
+//! until a path is set with `set_audit_log_path`
+
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref AUDIT_LOG_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// A single signed Cosmos transaction and the Ethereum checkpoints signed for it, if any
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Unix timestamp at which the transaction was signed
+    pub timestamp: u64,
+    /// The type of message signed, for example valset_confirm or ethereum_claims
+    pub kind: String,
+    /// The Cosmos address that signed the transaction
+    pub signer: String,
+    /// The nonces of the items signed or attested to
+    pub nonces: Vec<u64>,
+    /// The Ethereum checkpoint hashes signed, in the same order as nonces
+    pub checkpoints: Vec<String>,
+    /// The hash of the resulting Cosmos transaction if it was accepted
+    pub tx_hash: Option<String>,
+    /// The error returned while broadcasting if it was not
+    pub error: Option<String>,
+}
+
+/// Sets the audit log file and enables recording, the file is created if it does not exist
+pub fn set_audit_log_path(path: PathBuf) {
+    *AUDIT_LOG_PATH.write().unwrap() = Some(path);
+}
+
+/// Appends a record to the audit log if one is configured, failing to write is logged but never
+/// interrupts signing
+pub fn record_signature(
+    kind: &str,
+    signer: String,
+    nonces: Vec<u64>,
+    checkpoints: Vec<String>,
+    tx_hash: Option<String>,
+    error: Option<String>,
+) {
+    let path = match AUDIT_LOG_PATH.read().unwrap().clone() {
+        Some(p) => p,
+        None => return,
+    };
+    let record = AuditRecord {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        kind: kind.to_string(),
+        signer,
+        nonces,
+        checkpoints,
+        tx_hash,
+        error,
+    };
+    let line = serde_json::to_string(&record).unwrap();
+    let res = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = res {
+        warn!(
+            "Failed to write signature audit log {} {:?}",
+            path.display(),
+            e
+        );
+    }
+}
+
+/// Syncs the audit log to disk, records are written as they are signed so this only ensures the
+/// last of them survive if the host goes down right after a crash. Safe to call from a panic hook
+pub fn sync_audit_log() {
+    // never block or panic here, the lock may be held or poisoned by the panicking thread
+    let path = match AUDIT_LOG_PATH.try_read() {
+        Ok(path) => path.clone(),
+        Err(_) => return,
+    };
+    if let Some(path) = path {
+        match OpenOptions::new().append(true).open(&path) {
+            Ok(f) => {
+                if let Err(e) = f.sync_all() {
+                    warn!(
+                        "Failed to sync signature audit log {} {:?}",
+                        path.display(),
+                        e
+                    );
+                }
+            }
+            // nothing has been signed yet
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => warn!(
+                "Failed to sync signature audit log {} {:?}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
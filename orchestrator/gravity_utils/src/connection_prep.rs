@@ -7,6 +7,7 @@ use deep_space::error::CosmosGrpcError;
 use deep_space::Address as CosmosAddress;
 use deep_space::Contact;
 use deep_space::{client::ChainStatus, Coin};
+use gravity_proto::cosmos_sdk_proto::cosmos::staking::v1beta1::QueryValidatorsRequest;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_proto::gravity::QueryDelegateKeysByEthAddress;
 use gravity_proto::gravity::QueryDelegateKeysByOrchestratorAddress;
@@ -229,6 +230,73 @@ pub async fn wait_for_cosmos_node_ready(contact: &Contact) {
     }
 }
 
+/// The chain's bech32 account prefix. The SDK serves it from the v2alpha1 reflection service's
+/// GetConfigurationDescriptor, but cosmos-sdk-proto does not export that service, so we read it
+/// off a validator operator address instead, which is always `<account prefix>valoper1...`.
+/// Returns None if the chain has no validators to inspect.
+pub async fn get_chain_address_prefix(
+    contact: &Contact,
+) -> Result<Option<String>, CosmosGrpcError> {
+    let validators = contact
+        .get_validators_list(QueryValidatorsRequest::default())
+        .await?;
+    Ok(validators
+        .iter()
+        .find_map(|v| account_prefix_from_valoper(&v.operator_address)))
+}
+
+/// Extracts the account prefix from a bech32 validator operator address
+fn account_prefix_from_valoper(operator_address: &str) -> Option<String> {
+    let (hrp, _) = operator_address.rsplit_once('1')?;
+    let prefix = hrp.strip_suffix("valoper")?;
+    if prefix.is_empty() {
+        None
+    } else {
+        Some(prefix.to_string())
+    }
+}
+
+/// Returns the address prefix to encode addresses with, the one in use on the chain when it can be
+/// detected and the prefix `contact` was created with otherwise. A prefix the user set explicitly
+/// (`explicit`) is cross checked against the chain's instead, a mismatch produces addresses (and
+/// valoper addresses derived from them) that the chain will never match so we refuse to start
+/// rather than fail in confusing ways later
+pub async fn check_address_prefix(contact: &Contact, explicit: bool) -> String {
+    let configured = contact.get_prefix();
+    match get_chain_address_prefix(contact).await {
+        Ok(Some(chain_prefix)) => {
+            if chain_prefix != configured && explicit {
+                error!(
+                    "Your configured address prefix '{}' does not match the chain's prefix '{}'!",
+                    configured, chain_prefix
+                );
+                error!(
+                    "Please run with `gbt --address-prefix {}` or check that you are connected to the correct chain",
+                    chain_prefix
+                );
+                exit(1);
+            } else if chain_prefix != configured {
+                info!("Using the chain's address prefix '{}'", chain_prefix);
+            }
+            chain_prefix
+        }
+        Ok(None) => {
+            warn!(
+                "Could not detect the chain address prefix, no validators found, using '{}'",
+                configured
+            );
+            configured
+        }
+        Err(e) => {
+            warn!(
+                "Could not detect the chain address prefix, using '{}' {:?}",
+                configured, e
+            );
+            configured
+        }
+    }
+}
+
 /// This function checks the orchestrator delegate addresses
 /// for consistency what this means is that it takes the Ethereum
 /// address and Orchestrator address from the Orchestrator and checks
@@ -368,3 +436,26 @@ pub async fn check_for_eth(address: EthAddress, web3: &Web3) {
         exit(1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::account_prefix_from_valoper;
+
+    #[test]
+    fn test_account_prefix_from_valoper() {
+        assert_eq!(
+            account_prefix_from_valoper("gravityvaloper1jpz0ahls2chajf78nkqczdwwuqcu97w6j77vg6"),
+            Some("gravity".to_string())
+        );
+        assert_eq!(
+            account_prefix_from_valoper("cosmosvaloper1jpz0ahls2chajf78nkqczdwwuqcu97w6j77vg6"),
+            Some("cosmos".to_string())
+        );
+        assert_eq!(
+            account_prefix_from_valoper("gravity1jpz0ahls2chajf78nkqczdwwuqcu97w6j77vg6"),
+            None
+        );
+        assert_eq!(account_prefix_from_valoper("valoper1abc"), None);
+        assert_eq!(account_prefix_from_valoper("garbage"), None);
+    }
+}
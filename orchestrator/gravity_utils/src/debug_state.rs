@@ -0,0 +1,108 @@
+//! A snapshot of what the orchestrator and relayer loops are currently working on. The loops
+//! update this as they run so that the full picture can be dumped to a file on request and
+//! attached to bug reports, instead of trying to reconstruct it from log excerpts.
+
+use crate::types::{LogicCall, TransactionBatch, Valset};
+use clarity::Uint256;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref DEBUG_STATE: RwLock<DebugState> = RwLock::new(DebugState::default());
+}
+
+/// Progress of the Ethereum oracle
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct OracleState {
+    /// The last Ethereum block the oracle has searched for events
+    pub last_checked_block: Option<Uint256>,
+    /// The last event nonce the oracle has submitted a claim for
+    pub last_checked_event_nonce: Option<Uint256>,
+}
+
+/// Items the Ethereum signer found waiting for our signature on its last iteration
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SignerState {
+    pub unsigned_valsets: Vec<Valset>,
+    pub unsigned_batches: Vec<TransactionBatch>,
+    pub unsigned_logic_calls: Vec<LogicCall>,
+}
+
+/// Items the relayer considered on its last iteration
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RelayerState {
+    /// The validator set currently in the Gravity contract on Ethereum
+    pub ethereum_valset: Option<Valset>,
+    /// Batches with enough signatures to be submitted to Ethereum
+    pub submittable_batches: Vec<TransactionBatch>,
+    /// The oldest logic call with enough signatures to be submitted to Ethereum
+    pub submittable_logic_call: Option<LogicCall>,
+}
+
+/// Whether a node is serving current blocks, as of the last health check
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NodeHealth {
+    /// The node's latest block
+    pub height: Option<u64>,
+    /// Why the node is considered stale or unreachable, None if it is healthy
+    pub problem: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DebugState {
+    /// Unix timestamp at which this snapshot was taken
+    pub timestamp: u64,
+    pub oracle: OracleState,
+    pub signer: SignerState,
+    pub relayer: RelayerState,
+    pub ethereum_node: NodeHealth,
+    pub cosmos_node: NodeHealth,
+}
+
+/// Returns a copy of the current debug state
+pub fn get_debug_state() -> DebugState {
+    let mut state = DEBUG_STATE.read().unwrap().clone();
+    state.timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    state
+}
+
+pub fn set_oracle_state(last_checked_block: Uint256, last_checked_event_nonce: Uint256) {
+    let mut state = DEBUG_STATE.write().unwrap();
+    state.oracle.last_checked_block = Some(last_checked_block);
+    state.oracle.last_checked_event_nonce = Some(last_checked_event_nonce);
+}
+
+pub fn set_ethereum_node_health(health: NodeHealth) {
+    DEBUG_STATE.write().unwrap().ethereum_node = health;
+}
+
+pub fn set_cosmos_node_health(health: NodeHealth) {
+    DEBUG_STATE.write().unwrap().cosmos_node = health;
+}
+
+pub fn set_unsigned_valsets(valsets: &[Valset]) {
+    DEBUG_STATE.write().unwrap().signer.unsigned_valsets = valsets.to_vec();
+}
+
+pub fn set_unsigned_batches(batches: &[TransactionBatch]) {
+    DEBUG_STATE.write().unwrap().signer.unsigned_batches = batches.to_vec();
+}
+
+pub fn set_unsigned_logic_calls(calls: &[LogicCall]) {
+    DEBUG_STATE.write().unwrap().signer.unsigned_logic_calls = calls.to_vec();
+}
+
+pub fn set_ethereum_valset(valset: &Valset) {
+    DEBUG_STATE.write().unwrap().relayer.ethereum_valset = Some(valset.clone());
+}
+
+pub fn set_submittable_batches(batches: Vec<TransactionBatch>) {
+    DEBUG_STATE.write().unwrap().relayer.submittable_batches = batches;
+}
+
+pub fn set_submittable_logic_call(call: Option<LogicCall>) {
+    DEBUG_STATE.write().unwrap().relayer.submittable_logic_call = call;
+}
@@ -28,6 +28,8 @@ pub enum GravityError {
     InsufficientVotingPowerToPass(String),
     ParseBigIntError(ParseError),
     ValsetUpToDate,
+    /// a relay transaction was mined but reverted for a reason retrying will not fix
+    RelayReverted(String),
 }
 
 impl fmt::Display for GravityError {
@@ -63,6 +65,7 @@ impl fmt::Display for GravityError {
                     "latest validator set is synced between Ethereum and Cosmos"
                 )
             }
+            GravityError::RelayReverted(val) => write!(f, "Relay reverted: {}", val),
         }
     }
 }
@@ -0,0 +1,104 @@
+//! Strict parsing of user provided Ethereum addresses. Funds sent to a mistyped address are lost for
+//! good, EIP-55 mixed case checksums catch almost every typo so they are enforced wherever a user
+//! types in a destination
+
+use clarity::Address as EthAddress;
+use sha3::{Digest, Keccak256};
+
+/// Returns the EIP-55 checksummed form of a 40 character hex address, without the 0x prefix
+fn to_checksum(hex: &str) -> String {
+    let lower = hex.to_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+    lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 {
+                hash[i / 2] >> 4
+            } else {
+                hash[i / 2] & 0x0f
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Parses an Ethereum address, rejecting mixed case input that does not match its EIP-55 checksum.
+/// Addresses with no checksum, all lowercase or all uppercase, are only accepted if `allow_unchecksummed`
+/// is set since there is nothing to catch a typo in them
+pub fn parse_checksummed_address(
+    input: &str,
+    allow_unchecksummed: bool,
+) -> Result<EthAddress, String> {
+    let hex = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{} is not a valid Ethereum address", input));
+    }
+    let address: EthAddress = match format!("0x{}", hex).parse() {
+        Ok(a) => a,
+        Err(_) => return Err(format!("{} is not a valid Ethereum address", input)),
+    };
+
+    let has_lower = hex.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = hex.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        let expected = to_checksum(hex);
+        if hex != expected {
+            return Err(format!(
+                "{} does not match its EIP-55 checksum, it has most likely been mistyped",
+                input
+            ));
+        }
+    } else if !allow_unchecksummed && hex.chars().any(|c| c.is_ascii_alphabetic()) {
+        return Err(format!(
+            "{} has no EIP-55 checksum, use the mixed case form 0x{} or explicitly allow unchecksummed addresses",
+            input,
+            to_checksum(hex)
+        ));
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_checksummed_address() {
+        // test vectors from EIP-55
+        for a in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert!(parse_checksummed_address(a, false).is_ok(), "{}", a);
+        }
+        // one character with the wrong case
+        assert!(
+            parse_checksummed_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD", true).is_err()
+        );
+        let lower = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+        assert!(parse_checksummed_address(lower, false).is_err());
+        assert_eq!(
+            parse_checksummed_address(lower, true),
+            parse_checksummed_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", false)
+        );
+        assert!(
+            parse_checksummed_address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED", true).is_ok()
+        );
+        // nothing to checksum
+        assert!(
+            parse_checksummed_address("0x0000000000000000000000000000000000000000", false).is_ok()
+        );
+        assert!(parse_checksummed_address("0x5aAeb6053F3E94C9b9A09f3366", true).is_err());
+        assert!(parse_checksummed_address("treasury", true).is_err());
+    }
+}
@@ -4,10 +4,18 @@
 extern crate serde_derive;
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
 
+pub mod audit_log;
 pub mod connection_prep;
+pub mod debug_state;
 pub mod error;
+pub mod eth_address;
 pub mod get_with_retry;
+pub mod log_context;
 pub mod num_conversion;
 pub mod prices;
+pub mod token_display;
 pub mod types;
+pub mod watch_only;
@@ -0,0 +1,85 @@
+//! The name of the loop a log record comes from. The orchestrator and relayer run many loops
+//! concurrently on the same task and several of them log from shared modules, so the module a
+//! record was logged in does not say which loop it belongs to. Each loop future is wrapped with
+//! `in_loop` and the logger reads the name back while the loop is being polled. In the same way
+//! records about a specific event or transaction are logged inside `with_event_nonce` or
+//! `with_tx_hash` so the logger can attach those as fields instead of finding them in the message
+
+use std::future::Future;
+
+tokio::task_local! {
+    static LOOP_NAME: &'static str;
+    static EVENT_NONCE: u64;
+    static TX_HASH: String;
+}
+
+/// Runs `future` with every record it logs attributed to the loop `name`, nested loops take the
+/// innermost name
+pub async fn in_loop<F: Future>(name: &'static str, future: F) -> F::Output {
+    LOOP_NAME.scope(name, future).await
+}
+
+/// The loop currently being polled, if any
+pub fn current_loop() -> Option<&'static str> {
+    LOOP_NAME.try_with(|name| *name).ok()
+}
+
+/// Calls `f` with every record it logs attributed to the event nonce `nonce`
+pub fn with_event_nonce<R>(nonce: u64, f: impl FnOnce() -> R) -> R {
+    EVENT_NONCE.sync_scope(nonce, f)
+}
+
+/// The event nonce records are currently attributed to, if any
+pub fn current_event_nonce() -> Option<u64> {
+    EVENT_NONCE.try_with(|nonce| *nonce).ok()
+}
+
+/// Calls `f` with every record it logs attributed to the transaction `hash`, Ethereum hashes are
+/// 0x prefixed and Cosmos hashes are not, as each chain displays them
+pub fn with_tx_hash<R>(hash: String, f: impl FnOnce() -> R) -> R {
+    TX_HASH.sync_scope(hash, f)
+}
+
+/// Runs `future` with every record it logs attributed to the transaction `hash`
+pub async fn for_tx_hash<F: Future>(hash: String, future: F) -> F::Output {
+    TX_HASH.scope(hash, future).await
+}
+
+/// The transaction records are currently attributed to, if any
+pub fn current_tx_hash() -> Option<String> {
+    TX_HASH.try_with(|hash| hash.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix::test]
+    async fn test_in_loop() {
+        assert_eq!(current_loop(), None);
+        let inner = in_loop("oracle", async {
+            let outer = current_loop();
+            let nested = in_loop("relayer", async { current_loop() }).await;
+            (outer, nested, current_loop())
+        })
+        .await;
+        assert_eq!(inner, (Some("oracle"), Some("relayer"), Some("oracle")));
+        assert_eq!(current_loop(), None);
+    }
+
+    #[actix::test]
+    async fn test_event_and_tx_context() {
+        assert_eq!(current_event_nonce(), None);
+        assert_eq!(with_event_nonce(7, current_event_nonce), Some(7));
+        let hash = "0xab".to_string();
+        let in_future = for_tx_hash(hash.clone(), async {
+            with_tx_hash("0xcd".to_string(), current_tx_hash)
+                .into_iter()
+                .chain(current_tx_hash())
+                .collect::<Vec<_>>()
+        })
+        .await;
+        assert_eq!(in_future, vec!["0xcd".to_string(), hash]);
+        assert_eq!(current_tx_hash(), None);
+    }
+}
@@ -60,6 +60,37 @@ pub fn print_gwei(input: Uint256) -> String {
     format!("{:}", res)
 }
 
+/// Formats a base unit amount of a token with `decimals` decimals in whole units without losing
+/// precision, trailing zeros are dropped. 1500000 with 6 decimals is 1.5
+pub fn print_amount(input: Uint256, decimals: u8) -> String {
+    let digits = input.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+#[test]
+fn test_print_amount() {
+    assert_eq!(print_amount(1500000u32.into(), 6), "1.5");
+    assert_eq!(print_amount(one_eth(), 18), "1");
+    assert_eq!(print_amount(1u8.into(), 18), "0.000000000000000001");
+    assert_eq!(print_amount(0u8.into(), 6), "0");
+    assert_eq!(print_amount(1234u32.into(), 0), "1234");
+    assert_eq!(
+        print_amount(123456789000000000000000u128.into(), 18),
+        "123456.789"
+    );
+}
+
 #[test]
 fn even_f32_rounding() {
     let one_eth: Uint256 = 1000000000000000000u128.into();
@@ -0,0 +1,142 @@
+//! Display of token amounts in whole units with their symbol. Amounts are handled in base units
+//! everywhere, 1 USDC is 1000000 and 1 WETH is 1000000000000000000, which is easy to misread by
+//! orders of magnitude. Decimals and symbols are looked up once from the ERC20 contract or the
+//! Cosmos bank metadata and cached, amounts of tokens that can not be looked up are shown raw.
+//! Failed ERC20 lookups are cached too, so that a token which is not a standard ERC20 does not cost
+//! two failing calls every time one of its amounts is displayed
+
+use crate::num_conversion::print_amount;
+use clarity::constants::zero_address;
+use clarity::{Address as EthAddress, Uint256};
+use deep_space::{Coin, Contact};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+use web30::client::Web3;
+
+lazy_static! {
+    static ref TOKEN_DISPLAY_CACHE: RwLock<HashMap<String, TokenDisplay>> =
+        RwLock::new(HashMap::new());
+    static ref ERC20_LOOKUP_FAILURES: RwLock<HashMap<EthAddress, Instant>> =
+        RwLock::new(HashMap::new());
+}
+
+/// How long a failed ERC20 lookup is cached before trying again, the failure may have been the
+/// Ethereum node rather than the token
+const ERC20_LOOKUP_RETRY: Duration = Duration::from_secs(600);
+
+/// How amounts of a token are displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDisplay {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl TokenDisplay {
+    /// Formats a base unit `amount` of this token, such as 1.5 USDC
+    pub fn format(&self, amount: Uint256) -> String {
+        format!("{} {}", print_amount(amount, self.decimals), self.symbol)
+    }
+}
+
+fn cached(key: &str) -> Option<TokenDisplay> {
+    TOKEN_DISPLAY_CACHE.read().unwrap().get(key).cloned()
+}
+
+fn cache(key: &str, display: TokenDisplay) -> TokenDisplay {
+    TOKEN_DISPLAY_CACHE
+        .write()
+        .unwrap()
+        .insert(key.to_string(), display.clone());
+    display
+}
+
+/// The symbol and decimals of an ERC20, None if the contract can not be queried
+pub async fn erc20_display(web3: &Web3, erc20: EthAddress) -> Option<TokenDisplay> {
+    let key = erc20.to_string();
+    if let Some(d) = cached(&key) {
+        return Some(d);
+    }
+    if let Some(failed_at) = ERC20_LOOKUP_FAILURES.read().unwrap().get(&erc20) {
+        if failed_at.elapsed() < ERC20_LOOKUP_RETRY {
+            return None;
+        }
+    }
+    let symbol = web3.get_erc20_symbol(erc20, zero_address()).await;
+    let decimals = web3.get_erc20_decimals(erc20, zero_address()).await;
+    match (symbol, decimals) {
+        (Ok(symbol), Ok(decimals)) => match decimals.to_string().parse() {
+            Ok(decimals) => Some(cache(&key, TokenDisplay { symbol, decimals })),
+            Err(_) => {
+                debug!("ERC20 {} has invalid decimals {}", erc20, decimals);
+                lookup_failed(erc20)
+            }
+        },
+        (symbol, decimals) => {
+            debug!(
+                "Could not get ERC20 metadata for {} {:?} {:?}",
+                erc20,
+                symbol.err(),
+                decimals.err()
+            );
+            lookup_failed(erc20)
+        }
+    }
+}
+
+fn lookup_failed(erc20: EthAddress) -> Option<TokenDisplay> {
+    ERC20_LOOKUP_FAILURES
+        .write()
+        .unwrap()
+        .insert(erc20, Instant::now());
+    None
+}
+
+/// The symbol and decimals of a Cosmos denom from its bank metadata. Ethereum originated gravity0x
+/// denoms rarely have metadata, they are looked up on their ERC20 if `web3` is given
+pub async fn denom_display(
+    contact: &Contact,
+    web3: Option<&Web3>,
+    denom: &str,
+) -> Option<TokenDisplay> {
+    if let Some(d) = cached(denom) {
+        return Some(d);
+    }
+    if let Ok(Some(metadata)) = contact.get_denom_metadata(denom.to_string()).await {
+        let decimals = metadata
+            .denom_units
+            .iter()
+            .find(|u| u.denom == metadata.display)
+            .and_then(|u| u8::try_from(u.exponent).ok());
+        if let Some(decimals) = decimals {
+            let symbol = if metadata.symbol.is_empty() {
+                metadata.display
+            } else {
+                metadata.symbol
+            };
+            return Some(cache(denom, TokenDisplay { symbol, decimals }));
+        }
+    }
+    let erc20: EthAddress = denom.strip_prefix("gravity")?.parse().ok()?;
+    let display = erc20_display(web3?, erc20).await?;
+    Some(cache(denom, display))
+}
+
+/// Formats a base unit `amount` of `erc20`, such as 1.5 USDC, or the raw amount and address if the
+/// token can not be looked up
+pub async fn format_erc20(web3: &Web3, erc20: EthAddress, amount: Uint256) -> String {
+    match erc20_display(web3, erc20).await {
+        Some(d) => d.format(amount),
+        None => format!("{} {}", amount, erc20),
+    }
+}
+
+/// Formats `coin` in whole units, such as 1.5 GRAV, or the raw amount and denom if the denom can not
+/// be looked up
+pub async fn format_coin(contact: &Contact, web3: Option<&Web3>, coin: &Coin) -> String {
+    match denom_display(contact, web3, &coin.denom).await {
+        Some(d) => d.format(coin.amount),
+        None => format!("{} {}", coin.amount, coin.denom),
+    }
+}
@@ -46,6 +46,26 @@ pub struct TransactionBatch {
 }
 
 impl TransactionBatch {
+    /// A human readable description of everything covered by a signature over this batch's
+    /// checkpoint, so operators can sanity check what they are attesting to
+    pub fn checkpoint_summary(&self) -> String {
+        let mut out = format!(
+            "Batch {} of {} with {} transactions, total fees {}, times out at Ethereum block {}",
+            self.nonce,
+            self.token_contract,
+            self.transactions.len(),
+            self.total_fee.amount,
+            self.batch_timeout
+        );
+        for tx in self.transactions.iter() {
+            out += &format!(
+                "\n    id {}: {} to {} fee {}",
+                tx.id, tx.erc20_token.amount, tx.destination, tx.erc20_fee.amount
+            );
+        }
+        out
+    }
+
     /// extracts the amounts, destinations and fees as submitted to the Ethereum contract
     /// and used for signatures
     pub fn get_checkpoint_values(&self) -> (Token, Token, Token) {
@@ -1,13 +1,51 @@
 //! contains configuration structs that need to be accessed across crates.
 
 use clarity::{Address as EthAddress, Uint256};
+use std::collections::BTreeMap;
 
 /// Global configuration struct for Gravity bridge tools
-#[derive(Serialize, Deserialize, Debug, PartialEq, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct GravityBridgeToolsConfig {
     pub relayer: RelayerConfig,
     pub orchestrator: OrchestratorConfig,
     pub metrics: MetricsConfig,
+    pub tracing: TracingConfig,
+    pub error_reporting: ErrorReportingConfig,
+    pub endpoints: EndpointsConfig,
+    /// named Cosmos or Ethereum addresses that can be used in place of an address, see `gbt address-book`
+    pub address_book: BTreeMap<String, String>,
+    /// log levels for individual modules, such as relayer = "debug", see `gbt log-level`
+    pub log_levels: BTreeMap<String, String>,
+    /// identical warnings and errors within this many seconds are logged once and then summarized
+    /// with a count, zero logs every occurrence
+    pub log_throttle_seconds: u64,
+    /// the memo attached to transactions in place of the default, so that a deployment can be
+    /// identified on chain
+    pub memo: Option<String>,
+    /// append the gbt version and git hash to the memo, so version adoption across the validator
+    /// set can be measured on chain
+    pub memo_version_tag: bool,
+    /// deposits tracked by `gbt deposit-watch`
+    pub deposit_watch: DepositWatchConfig,
+}
+
+impl Default for GravityBridgeToolsConfig {
+    fn default() -> Self {
+        GravityBridgeToolsConfig {
+            relayer: RelayerConfig::default(),
+            orchestrator: OrchestratorConfig::default(),
+            metrics: MetricsConfig::default(),
+            tracing: TracingConfig::default(),
+            error_reporting: ErrorReportingConfig::default(),
+            endpoints: EndpointsConfig::default(),
+            address_book: BTreeMap::new(),
+            log_levels: BTreeMap::new(),
+            log_throttle_seconds: default_log_throttle_seconds(),
+            memo: None,
+            memo_version_tag: default_memo_version_tag(),
+            deposit_watch: DepositWatchConfig::default(),
+        }
+    }
 }
 
 /// Toml serializable configuration struct for Gravity bridge tools
@@ -19,6 +57,32 @@ pub struct TomlGravityBridgeToolsConfig {
     pub orchestrator: OrchestratorConfig,
     #[serde(default = "MetricsConfig::default")]
     pub metrics: MetricsConfig,
+    #[serde(default = "TracingConfig::default")]
+    pub tracing: TracingConfig,
+    #[serde(default = "ErrorReportingConfig::default")]
+    pub error_reporting: ErrorReportingConfig,
+    #[serde(default)]
+    pub endpoints: EndpointsConfig,
+    #[serde(default)]
+    pub address_book: BTreeMap<String, String>,
+    #[serde(default)]
+    pub log_levels: BTreeMap<String, String>,
+    #[serde(default = "default_log_throttle_seconds")]
+    pub log_throttle_seconds: u64,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default = "default_memo_version_tag")]
+    pub memo_version_tag: bool,
+    #[serde(default = "DepositWatchConfig::default")]
+    pub deposit_watch: DepositWatchConfig,
+}
+
+fn default_log_throttle_seconds() -> u64 {
+    60
+}
+
+fn default_memo_version_tag() -> bool {
+    true
 }
 
 impl From<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
@@ -27,6 +91,15 @@ impl From<TomlGravityBridgeToolsConfig> for GravityBridgeToolsConfig {
             relayer: input.relayer.into(),
             orchestrator: input.orchestrator,
             metrics: input.metrics,
+            tracing: input.tracing,
+            error_reporting: input.error_reporting,
+            endpoints: input.endpoints,
+            address_book: input.address_book,
+            log_levels: input.log_levels,
+            log_throttle_seconds: input.log_throttle_seconds,
+            memo: input.memo,
+            memo_version_tag: input.memo_version_tag,
+            deposit_watch: input.deposit_watch,
         }
     }
 }
@@ -66,6 +139,23 @@ pub struct RelayerConfig {
     pub ibc_auto_forward_loop_speed: u64,
     /// the number of pending ibc auto forwards to attempt to execute per loop
     pub ibc_auto_forwards_to_execute: u64,
+    /// where the gas price used to estimate and submit relaying transactions comes from
+    pub gas_oracle: GasOracleConfig,
+    /// screening of batch destinations before relaying, disabled unless configured
+    pub screening: ScreeningConfig,
+    /// only submit the highest powered signatures needed to pass with validator set updates,
+    /// batches and logic calls, reducing calldata and gas for large validator sets
+    pub trim_signatures: bool,
+    /// sweeping of batch fees to a cold address after each relay, disabled unless configured
+    pub profit_sweep: ProfitSweepConfig,
+    /// submission of batches and logic calls through a private relay, disabled unless configured
+    pub private_relay: PrivateRelayConfig,
+    /// how long a batch whose relay reverted for a reason retrying will not fix is skipped, in
+    /// minutes. Skipped batches can be retried early with `gbt relayer retry-batch`, 0 disables
+    pub revert_blacklist_minutes: u64,
+    /// only log what would be relayed and never send to Ethereum, set by `gbt relayer --dry-run`
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// Relayer configuration that's is more easily parsable with toml
@@ -95,6 +185,19 @@ pub struct TomlRelayerConfig {
     pub ibc_auto_forward_loop_speed: u64,
     #[serde(default = "default_ibc_auto_forwards_to_execute")]
     pub ibc_auto_forwards_to_execute: u64,
+    #[serde(default = "GasOracleConfig::default")]
+    pub gas_oracle: GasOracleConfig,
+    #[serde(default = "ScreeningConfig::default")]
+    pub screening: ScreeningConfig,
+    /// named trim_valset_signatures when only validator set updates were trimmed
+    #[serde(default = "default_trim_signatures", alias = "trim_valset_signatures")]
+    pub trim_signatures: bool,
+    #[serde(default)]
+    pub profit_sweep: ProfitSweepConfig,
+    #[serde(default)]
+    pub private_relay: PrivateRelayConfig,
+    #[serde(default = "default_revert_blacklist_minutes")]
+    pub revert_blacklist_minutes: u64,
 }
 
 impl From<TomlRelayerConfig> for RelayerConfig {
@@ -113,6 +216,144 @@ impl From<TomlRelayerConfig> for RelayerConfig {
                 .altruistic_acceptable_gas_price_percentage,
             ibc_auto_forward_loop_speed: input.ibc_auto_forward_loop_speed,
             ibc_auto_forwards_to_execute: input.ibc_auto_forwards_to_execute,
+            gas_oracle: input.gas_oracle,
+            screening: input.screening,
+            trim_signatures: input.trim_signatures,
+            profit_sweep: input.profit_sweep,
+            private_relay: input.private_relay,
+            revert_blacklist_minutes: input.revert_blacklist_minutes,
+            dry_run: false,
+        }
+    }
+}
+
+/// The sources a relayer can get Ethereum gas prices from
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GasOracleSource {
+    /// The connected node's eth_gasPrice
+    Node,
+    /// The connected node's eth_feeHistory, the next block's base fee plus a percentile of the
+    /// priority fees paid over recent blocks chosen by inclusion_speed
+    FeeHistory,
+    /// The Etherscan gas tracker, requires an api_key
+    Etherscan,
+    /// The Blocknative gas platform, requires an api_key
+    Blocknative,
+    /// Always use fixed_gwei
+    Fixed,
+}
+
+/// Gas price oracle configuration for relaying
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct GasOracleConfig {
+    #[serde(default = "default_gas_oracle_source")]
+    pub source: GasOracleSource,
+    /// The API key for the Etherscan and Blocknative sources
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Overrides the API endpoint of the Etherscan and Blocknative sources, for example to use
+    /// the Etherscan API of another EVM chain
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The gas price in gwei used by the Fixed source
+    #[serde(default)]
+    pub fixed_gwei: Option<f64>,
+    /// Gas prices below this many gwei are raised to it
+    #[serde(default)]
+    pub min_gwei: Option<f64>,
+    /// Gas prices above this many gwei are lowered to it, transactions may take longer to be
+    /// included but will never pay more than this
+    #[serde(default)]
+    pub max_gwei: Option<f64>,
+    /// How soon transactions priced by the FeeHistory source should be included
+    #[serde(default)]
+    pub inclusion_speed: InclusionSpeed,
+}
+
+/// How soon a relaying transaction should be included, higher speeds pay a higher priority fee
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum InclusionSpeed {
+    /// The 90th percentile of recent priority fees, almost always included in the next block
+    NextBlock,
+    /// The median of recent priority fees, usually included within five blocks
+    #[default]
+    WithinFiveBlocks,
+}
+
+fn default_gas_oracle_source() -> GasOracleSource {
+    GasOracleSource::Node
+}
+
+impl Default for GasOracleConfig {
+    fn default() -> Self {
+        GasOracleConfig {
+            source: default_gas_oracle_source(),
+            api_key: None,
+            url: None,
+            fixed_gwei: None,
+            min_gwei: None,
+            max_gwei: None,
+            inclusion_speed: InclusionSpeed::default(),
+        }
+    }
+}
+
+/// Screening of the destinations of batches before they are relayed, for relayer operators that
+/// must not pay out to certain addresses. Batches paying a screened address are skipped and logged,
+/// other relayers remain free to relay them. Disabled unless a denylist_file or api_url is set
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ScreeningConfig {
+    /// A file of Ethereum addresses, one per line, lines starting with # are ignored. The file is
+    /// read again before each batch so it can be updated without restarting the relayer
+    #[serde(default)]
+    pub denylist_file: Option<String>,
+    /// An HTTP endpoint the destinations of each batch are POSTed to as {"addresses": [...]}, it
+    /// must respond with {"blocked": [...]} listing any that must not be relayed to
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Sent as the Authorization header of requests to api_url
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Relay batches that could not be screened because the denylist or API failed, by default
+    /// they are skipped until screening works again
+    #[serde(default)]
+    pub allow_on_error: bool,
+}
+
+/// Keeps relaying proceeds off the relaying host, for relayer operators relaying on behalf of a
+/// third party or who want little value at risk in the hot key. Disabled unless cold_address is set
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ProfitSweepConfig {
+    /// Where the relayer's entire balance of a relayed batch's token is sent, not only that batch's fees
+    #[serde(default)]
+    pub cold_address: Option<EthAddress>,
+    /// If set, ETH above this many wei is also swept to cold_address, the rest is kept to pay gas
+    #[serde(default)]
+    pub eth_float: Option<Uint256>,
+}
+
+/// Submits batches and logic calls through a private relay such as Flashbots Protect instead of
+/// the public mempool, so they can not be frontrun and failing ones are dropped rather than mined.
+/// Disabled unless url is set
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct PrivateRelayConfig {
+    /// An endpoint accepting eth_sendRawTransaction, for example https://rpc.flashbots.net/fast
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Broadcast to the public mempool if the private relay rejects the transaction or is down
+    #[serde(default = "default_private_relay_public_fallback")]
+    pub public_fallback: bool,
+}
+
+fn default_private_relay_public_fallback() -> bool {
+    true
+}
+
+impl Default for PrivateRelayConfig {
+    fn default() -> Self {
+        PrivateRelayConfig {
+            url: None,
+            public_fallback: default_private_relay_public_fallback(),
         }
     }
 }
@@ -289,6 +530,14 @@ fn default_ibc_auto_forwards_to_execute() -> u64 {
     50
 }
 
+fn default_trim_signatures() -> bool {
+    true
+}
+
+fn default_revert_blacklist_minutes() -> u64 {
+    360
+}
+
 impl Default for RelayerConfig {
     fn default() -> Self {
         RelayerConfig {
@@ -306,6 +555,13 @@ impl Default for RelayerConfig {
                 default_altruistic_acceptable_gas_price_percentage(),
             ibc_auto_forward_loop_speed: default_ibc_auto_forward_loop_speed(),
             ibc_auto_forwards_to_execute: default_ibc_auto_forwards_to_execute(),
+            gas_oracle: GasOracleConfig::default(),
+            screening: ScreeningConfig::default(),
+            trim_signatures: default_trim_signatures(),
+            profit_sweep: ProfitSweepConfig::default(),
+            private_relay: PrivateRelayConfig::default(),
+            revert_blacklist_minutes: default_revert_blacklist_minutes(),
+            dry_run: false,
         }
     }
 }
@@ -327,12 +583,18 @@ impl Default for TomlRelayerConfig {
                 default_altruistic_acceptable_gas_price_percentage(),
             ibc_auto_forward_loop_speed: default_ibc_auto_forward_loop_speed(),
             ibc_auto_forwards_to_execute: default_ibc_auto_forwards_to_execute(),
+            gas_oracle: GasOracleConfig::default(),
+            screening: ScreeningConfig::default(),
+            trim_signatures: default_trim_signatures(),
+            profit_sweep: ProfitSweepConfig::default(),
+            private_relay: PrivateRelayConfig::default(),
+            revert_blacklist_minutes: default_revert_blacklist_minutes(),
         }
     }
 }
 
 /// Orchestrator configuration options
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct OrchestratorConfig {
     /// If this Orchestrator should run an integrated relayer or not
     #[serde(default = "default_relayer_enabled")]
@@ -340,6 +602,324 @@ pub struct OrchestratorConfig {
     /// Whether to check that the ethereum node supports "finalized" blocks
     #[serde(default = "default_check_eth_rpc")]
     pub check_eth_rpc: bool,
+    /// Whether to record everything the orchestrator keys sign in the signature audit log
+    #[serde(default = "default_signature_audit_log")]
+    pub signature_audit_log: bool,
+    /// Finality assumptions for EVM chains, these replace the built in profile for the same chain
+    #[serde(default)]
+    pub finality_profiles: Vec<FinalityProfile>,
+    /// An optional Ethereum consensus layer (beacon node) REST API url, if set events are only
+    /// attested once the beacon chain has also finalized the block containing them
+    #[serde(default)]
+    pub beacon_api_url: Option<String>,
+    /// An optional Ethereum light client RPC url such as Helios, if set events are only attested
+    /// once the light client has finalized the block and the Ethereum node agrees on its hash
+    #[serde(default)]
+    pub light_client_rpc: Option<String>,
+    /// Use a light client found on its default local port if light_client_rpc is not set, off by
+    /// default since a full node may be listening on the same port
+    #[serde(default = "default_detect_light_client")]
+    pub detect_light_client: bool,
+    /// Whether to pay orchestrator fees out of a fee grant when the orchestrator address has one
+    #[serde(default = "default_use_fee_grant")]
+    pub use_fee_grant: bool,
+    /// The account granting the fee allowance, usually the validator operator. If unset and
+    /// use_fee_grant is enabled any grant to the orchestrator address is detected at startup
+    #[serde(default)]
+    pub fee_granter: Option<String>,
+    /// Warn once the fee grant can pay for fewer than this many transactions
+    #[serde(default = "default_fee_grant_warning_txs")]
+    pub fee_grant_warning_txs: u64,
+    /// Notifies an HTTP endpoint of deposits to a set of Cosmos addresses
+    #[serde(default)]
+    pub deposit_webhook: DepositWebhookConfig,
+    /// The signer runs every Cosmos block, warn about valsets, batches and logic calls still
+    /// unsigned this many blocks after they were first seen
+    #[serde(default = "default_signer_target_blocks")]
+    pub signer_target_blocks: u64,
+    /// Checks applied to logic calls before they are signed
+    #[serde(default)]
+    pub logic_call_policy: LogicCallPolicy,
+    /// Alert with a diagnosis if our event nonce has not advanced for this many minutes while
+    /// the rest of the network has moved on, zero disables the watchdog
+    #[serde(default = "default_claim_watchdog_minutes")]
+    pub claim_watchdog_minutes: u64,
+    /// Alert if a valset or batch has been confirmed by less than 2/3 of the bridge power for
+    /// this many minutes, zero only exports the progress metrics
+    #[serde(default = "default_confirm_alert_minutes")]
+    pub confirm_alert_minutes: u64,
+    /// The most the oracle is expected to spend on claim fees in a day, in the fee denom. Alerts
+    /// once most of it is spent, claims are still sent. None only tracks spending
+    #[serde(default)]
+    pub daily_claim_fee_budget: Option<Uint256>,
+    /// If the chain's Gravity module version is not supported, run in watch only mode following
+    /// our own orchestrator address instead of refusing to start
+    #[serde(default)]
+    pub read_only_on_version_mismatch: bool,
+    /// Checks that the Ethereum and Cosmos nodes are serving current blocks
+    #[serde(default)]
+    pub node_health: NodeHealthConfig,
+    /// Coordination with a redundant orchestrator sharing our Cosmos key
+    #[serde(default)]
+    pub redundancy: RedundancyConfig,
+    /// Opt-in reporting of version, lag and error counts to the network's monitoring team
+    #[serde(default)]
+    pub telemetry_report: TelemetryReportConfig,
+    /// Where the Ethereum key signing valset, batch and logic call confirms is held
+    #[serde(default)]
+    pub ethereum_signer: EthereumSignerConfig,
+}
+
+/// Redundant deployments run two orchestrators with the same Cosmos key so that one can take over
+/// when the other fails. While both are running they would submit every claim and confirm twice,
+/// when enabled the chain and mempool are checked for the other instance's transactions first
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RedundancyConfig {
+    /// Skip claims and confirms the other instance has already submitted
+    #[serde(default)]
+    pub enabled: bool,
+    /// A Tendermint RPC url such as http://localhost:26657, if set the mempool is also searched
+    #[serde(default)]
+    pub tendermint_rpc: Option<String>,
+    /// How many of the orchestrator's most recent transactions are searched
+    #[serde(default = "default_redundancy_recent_txs")]
+    pub recent_txs: u64,
+}
+
+fn default_detect_light_client() -> bool {
+    false
+}
+
+fn default_redundancy_recent_txs() -> u64 {
+    20
+}
+
+impl Default for RedundancyConfig {
+    fn default() -> Self {
+        RedundancyConfig {
+            enabled: false,
+            tendermint_rpc: None,
+            recent_txs: default_redundancy_recent_txs(),
+        }
+    }
+}
+
+/// Periodic reports to a monitoring endpoint so that problems across many orchestrators can be
+/// correlated during an incident. Reports hold the gbt version, uptime, lag gauges and error
+/// counts by category, never keys, addresses or endpoints. Nothing is sent unless an endpoint is set
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TelemetryReportConfig {
+    /// The url reports are POSTed to as JSON
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Seconds between reports
+    #[serde(default = "default_telemetry_report_interval")]
+    pub interval_seconds: u64,
+    /// A name to identify this orchestrator by, if unset a random id is chosen on every start so
+    /// reports can only be grouped by process
+    #[serde(default)]
+    pub instance_name: Option<String>,
+}
+
+fn default_telemetry_report_interval() -> u64 {
+    300
+}
+
+impl Default for TelemetryReportConfig {
+    fn default() -> Self {
+        TelemetryReportConfig {
+            endpoint: None,
+            interval_seconds: default_telemetry_report_interval(),
+            instance_name: None,
+        }
+    }
+}
+
+/// Where the orchestrator's Ethereum key is held when signing confirms
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EthereumSignerBackend {
+    /// The Ethereum key given to gbt with --ethereum-key or stored by gbt keys
+    #[default]
+    Local,
+    /// An ECC_SECG_P256K1 key in AWS KMS, credentials are read from the usual AWS environment
+    AwsKms,
+    /// An EC_SIGN_SECP256K1_SHA256 key version in Google Cloud KMS
+    GcpKms,
+    /// A secp256k1 asymmetric key on a YubiHSM 2 reached through yubihsm-connector
+    YubiHsm,
+}
+
+/// A remote signer for the Ethereum key that signs confirms, so that it never has to be held in
+/// memory. The local key is still used to pay for relaying on Ethereum
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct EthereumSignerConfig {
+    #[serde(default)]
+    pub backend: EthereumSignerBackend,
+    /// The AWS KMS key id or ARN, the full Google Cloud KMS key version resource name, or the
+    /// YubiHSM object id of the key
+    #[serde(default)]
+    pub key_id: Option<String>,
+    /// host:port of yubihsm-connector
+    #[serde(default = "default_yubihsm_connector")]
+    pub yubihsm_connector: String,
+    /// The YubiHSM authentication key used to open a session
+    #[serde(default = "default_yubihsm_auth_key_id")]
+    pub yubihsm_auth_key_id: u16,
+    /// The environment variable holding the password of the authentication key
+    #[serde(default = "default_yubihsm_password_env")]
+    pub yubihsm_password_env: String,
+}
+
+fn default_yubihsm_connector() -> String {
+    "127.0.0.1:12345".to_string()
+}
+
+fn default_yubihsm_auth_key_id() -> u16 {
+    1
+}
+
+fn default_yubihsm_password_env() -> String {
+    "YUBIHSM_PASSWORD".to_string()
+}
+
+impl Default for EthereumSignerConfig {
+    fn default() -> Self {
+        EthereumSignerConfig {
+            backend: EthereumSignerBackend::Local,
+            key_id: None,
+            yubihsm_connector: default_yubihsm_connector(),
+            yubihsm_auth_key_id: default_yubihsm_auth_key_id(),
+            yubihsm_password_env: default_yubihsm_password_env(),
+        }
+    }
+}
+
+/// Stale node detection. A node that falls behind keeps answering queries, so without this the
+/// orchestrator silently attests and signs late. A node is marked stale when its latest block is
+/// too old or it is too far behind any of the reference endpoints
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct NodeHealthConfig {
+    /// Other Ethereum RPC endpoints whose latest block our node is compared against
+    #[serde(default)]
+    pub reference_ethereum_rpcs: Vec<String>,
+    /// Other Cosmos gRPC endpoints whose latest block our node is compared against
+    #[serde(default)]
+    pub reference_cosmos_grpcs: Vec<String>,
+    /// Seconds after which the Ethereum node's latest block is considered stale
+    #[serde(default = "default_max_ethereum_head_age")]
+    pub max_ethereum_head_age: u64,
+    /// Seconds without a new block after which the Cosmos node is considered stale
+    #[serde(default = "default_max_cosmos_head_age")]
+    pub max_cosmos_head_age: u64,
+    /// How many blocks a node may trail the highest reference endpoint
+    #[serde(default = "default_max_blocks_behind")]
+    pub max_blocks_behind: u64,
+}
+
+fn default_max_ethereum_head_age() -> u64 {
+    180
+}
+
+fn default_max_cosmos_head_age() -> u64 {
+    120
+}
+
+fn default_max_blocks_behind() -> u64 {
+    5
+}
+
+impl Default for NodeHealthConfig {
+    fn default() -> Self {
+        NodeHealthConfig {
+            reference_ethereum_rpcs: Vec::new(),
+            reference_cosmos_grpcs: Vec::new(),
+            max_ethereum_head_age: default_max_ethereum_head_age(),
+            max_cosmos_head_age: default_max_cosmos_head_age(),
+            max_blocks_behind: default_max_blocks_behind(),
+        }
+    }
+}
+
+/// Operator policy for logic calls. Logic calls can move any amount of bridged tokens to an
+/// arbitrary contract, so validators may want to restrict what their orchestrator signs without
+/// review. Calls violating the policy are not signed and an error is logged so the operator can
+/// inspect the call and sign it with `gbt orchestrator sign-logic-call` if it is legitimate.
+/// Everything is allowed unless configured
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct LogicCallPolicy {
+    /// The logic contracts calls may target, any contract if unset
+    #[serde(default)]
+    pub allowed_targets: Option<Vec<EthAddress>>,
+    /// The 4 byte function selectors, in hex, the payload may call, any function if unset
+    #[serde(default)]
+    pub allowed_selectors: Option<Vec<String>>,
+    /// The invalidation ids, in hex, calls may use, any invalidation scope if unset
+    #[serde(default)]
+    pub allowed_invalidation_ids: Option<Vec<String>>,
+    /// The most of each token a single call may move, transfers and fees combined
+    #[serde(default)]
+    pub max_amounts: Vec<LogicCallTokenLimit>,
+    /// Refuse calls moving any token without an entry in max_amounts
+    #[serde(default)]
+    pub deny_unlisted_tokens: bool,
+}
+
+/// The most of `token`, in base units, a single logic call may move
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct LogicCallTokenLimit {
+    pub token: EthAddress,
+    pub amount: Uint256,
+}
+
+/// Deposit notifications for exchanges and other services that credit users for deposits. When
+/// the oracle observes a sendToCosmos deposit to one of the addresses, and again once the chain
+/// attests to it, a JSON notification signed with the shared secret is POSTed to the url
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct DepositWebhookConfig {
+    /// The endpoint notifications are POSTed to, notifications are disabled if unset
+    #[serde(default)]
+    pub url: Option<String>,
+    /// The Cosmos destination addresses to notify about
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// The shared secret notifications are signed with, the hex HMAC-SHA256 of the body is sent
+    /// in the X-Gravity-Signature header
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// How the oracle decides that a block on an EVM chain can no longer be reorganized
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FinalityMode {
+    /// Trust the node's "finalized" block, for proof of stake chains and rollups whose nodes
+    /// report L1 finality
+    Finalized,
+    /// Trust the node's "safe" block, justified but not yet finalized. Lower latency than
+    /// Finalized at the cost of depending on an honest majority of validators. Falls back to
+    /// the confirmation delay if the node does not support the tag
+    Safe,
+    /// Wait for a fixed number of confirmations, for chains without protocol level finality.
+    /// If the node reports a "finalized" block it is used instead of the delay
+    Confirmations,
+    /// Blocks are final as soon as they are produced, only for single signer development chains
+    Instant,
+}
+
+/// The finality assumptions the oracle makes for a single EVM chain
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FinalityProfile {
+    /// The chain id as reported by net_version
+    pub chain_id: u64,
+    /// A human readable name for the chain, used in logs
+    #[serde(default)]
+    pub name: String,
+    pub mode: FinalityMode,
+    /// In Confirmations mode the number of blocks an event must be buried under before it is
+    /// attested to, this should exceed the deepest reorg the chain is expected to experience
+    #[serde(default)]
+    pub confirmations: u64,
+    /// The expected time between blocks in milliseconds
+    pub block_time_ms: u64,
 }
 
 fn default_relayer_enabled() -> bool {
@@ -350,11 +930,54 @@ fn default_check_eth_rpc() -> bool {
     true
 }
 
+fn default_use_fee_grant() -> bool {
+    true
+}
+
+fn default_fee_grant_warning_txs() -> u64 {
+    1000
+}
+
+fn default_signature_audit_log() -> bool {
+    true
+}
+
+fn default_signer_target_blocks() -> u64 {
+    2
+}
+
+fn default_claim_watchdog_minutes() -> u64 {
+    30
+}
+
+fn default_confirm_alert_minutes() -> u64 {
+    30
+}
+
 impl Default for OrchestratorConfig {
     fn default() -> Self {
         OrchestratorConfig {
             relayer_enabled: default_relayer_enabled(),
             check_eth_rpc: default_check_eth_rpc(),
+            signature_audit_log: default_signature_audit_log(),
+            finality_profiles: Vec::new(),
+            beacon_api_url: None,
+            light_client_rpc: None,
+            detect_light_client: default_detect_light_client(),
+            use_fee_grant: default_use_fee_grant(),
+            fee_granter: None,
+            fee_grant_warning_txs: default_fee_grant_warning_txs(),
+            deposit_webhook: DepositWebhookConfig::default(),
+            signer_target_blocks: default_signer_target_blocks(),
+            logic_call_policy: LogicCallPolicy::default(),
+            claim_watchdog_minutes: default_claim_watchdog_minutes(),
+            confirm_alert_minutes: default_confirm_alert_minutes(),
+            daily_claim_fee_budget: None,
+            read_only_on_version_mismatch: false,
+            node_health: NodeHealthConfig::default(),
+            redundancy: RedundancyConfig::default(),
+            telemetry_report: TelemetryReportConfig::default(),
+            ethereum_signer: EthereumSignerConfig::default(),
         }
     }
 }
@@ -386,3 +1009,99 @@ impl Default for MetricsConfig {
         }
     }
 }
+
+/// OpenTelemetry tracing configuration options
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct TracingConfig {
+    /// If spans for loop iterations and submitted transactions should be exported
+    #[serde(default = "default_tracing_enabled")]
+    pub tracing_enabled: bool,
+    /// The OTLP gRPC collector to export spans to
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    /// The service name spans are reported under
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+fn default_tracing_enabled() -> bool {
+    false
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_service_name() -> String {
+    "gravity-orchestrator".to_string()
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        TracingConfig {
+            tracing_enabled: default_tracing_enabled(),
+            otlp_endpoint: default_otlp_endpoint(),
+            service_name: default_service_name(),
+        }
+    }
+}
+
+/// The network this config is for, each setting is the default of the matching argument of every
+/// gbt command, so that a profile can carry its own endpoints. The argument or its GBT_ environment
+/// variable take precedence
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct EndpointsConfig {
+    /// the default of --cosmos-grpc
+    #[serde(default)]
+    pub cosmos_grpc: Option<String>,
+    /// the default of --ethereum-rpc
+    #[serde(default)]
+    pub ethereum_rpc: Option<String>,
+    /// the default of --address-prefix
+    #[serde(default)]
+    pub address_prefix: Option<String>,
+    /// the default of --gravity-contract-address
+    #[serde(default)]
+    pub gravity_contract_address: Option<String>,
+}
+
+/// Deposits to watch with `gbt deposit-watch`, for exchanges and custodians crediting users once a
+/// sendToCosmos deposit is deep enough on Ethereum and once it is attested on Cosmos
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DepositWatchConfig {
+    /// the Cosmos addresses whose incoming deposits are tracked
+    #[serde(default)]
+    pub destinations: Vec<String>,
+    /// how many Ethereum blocks must be built on top of a deposit before it is reported confirmed
+    #[serde(default = "default_deposit_confirmations")]
+    pub default_confirmations: u64,
+    /// the confirmations required for individual ERC20 addresses, in place of the default
+    #[serde(default)]
+    pub confirmations: BTreeMap<String, u64>,
+}
+
+fn default_deposit_confirmations() -> u64 {
+    12
+}
+
+impl Default for DepositWatchConfig {
+    fn default() -> Self {
+        DepositWatchConfig {
+            destinations: Vec::new(),
+            default_confirmations: default_deposit_confirmations(),
+            confirmations: BTreeMap::new(),
+        }
+    }
+}
+
+/// Crash and error reporting configuration options, reports include panics and logged errors
+/// along with a fingerprint of this config file, keys are never included
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ErrorReportingConfig {
+    /// The Sentry (or compatible) DSN to send reports to, reporting is disabled if not set
+    #[serde(default)]
+    pub dsn: Option<String>,
+    /// An optional environment name attached to reports, such as mainnet or testnet
+    #[serde(default)]
+    pub environment: Option<String>,
+}
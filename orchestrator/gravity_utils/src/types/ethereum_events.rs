@@ -460,6 +460,24 @@ struct SendToCosmosEventData {
 }
 
 impl SendToCosmosEvent {
+    /// True if `claim` describes this deposit, used to check that what the chain attested to is the
+    /// deposit log we saw rather than another event that took its nonce after a reorg
+    pub fn matches_claim(&self, claim: &MsgSendToCosmosClaim) -> bool {
+        claim.event_nonce == self.event_nonce
+            && Uint256::from(claim.eth_block_height) == self.block_height
+            && claim.token_contract.parse::<EthAddress>().ok() == Some(self.erc20)
+            && claim.ethereum_sender.parse::<EthAddress>().ok() == Some(self.sender)
+            && claim.amount == self.amount.to_string()
+            && claim.cosmos_receiver == self.destination
+    }
+
+    /// Deposits with a destination that is not valid bech32 for any prefix are still claimed
+    /// as is, so that all validators agree on the event, but the chain sends the funds to the
+    /// community pool rather than any user
+    pub fn has_invalid_destination(&self) -> bool {
+        self.validated_destination.is_none()
+    }
+
     fn decode_data_bytes(data: &[u8]) -> Result<SendToCosmosEventData, GravityError> {
         if data.len() < 4 * 32 {
             return Err(GravityError::InvalidEventLogError(
@@ -1055,6 +1073,39 @@ mod tests {
         event_bytes
     }
 
+    #[test]
+    fn test_send_to_cosmos_matches_claim() {
+        let deposit = SendToCosmosEvent {
+            erc20: "0x7580bFE88Dd3d07947908FAE12d95872a260F2D8"
+                .parse()
+                .unwrap(),
+            sender: "0xBf660843528035a5A4921534E156a27e64B231fE"
+                .parse()
+                .unwrap(),
+            destination: "gravity1g0etv93428tvxqftnmj25jn06mz6dtda5zxt8k".to_string(),
+            validated_destination: None,
+            amount: 1000u32.into(),
+            event_nonce: 7,
+            block_height: 100u8.into(),
+        };
+        let claim = MsgSendToCosmosClaim {
+            event_nonce: 7,
+            eth_block_height: 100,
+            token_contract: deposit.erc20.to_string().to_lowercase(),
+            amount: "1000".to_string(),
+            ethereum_sender: deposit.sender.to_string(),
+            cosmos_receiver: deposit.destination.clone(),
+            orchestrator: String::new(),
+        };
+        assert!(deposit.matches_claim(&claim));
+        let reorged = MsgSendToCosmosClaim {
+            eth_block_height: 101,
+            amount: "999".to_string(),
+            ..claim
+        };
+        assert!(!deposit.matches_claim(&reorged));
+    }
+
     #[test]
     fn test_valset_decode() {
         let event = "0x0000000000000000000000000000000000000000000000000000000000000001\
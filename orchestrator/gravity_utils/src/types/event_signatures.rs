@@ -11,3 +11,5 @@ pub const LOGIC_CALL_EVENT_SIG: &str = "LogicCallEvent(bytes32,uint256,bytes,uin
 
 pub const VALSET_UPDATED_EVENT_SIG: &str =
     "ValsetUpdatedEvent(uint256,uint256,uint256,address,address[],uint256[])";
+
+pub const ERC20_TRANSFER_EVENT_SIG: &str = "Transfer(address,address,uint256)";
@@ -1,7 +1,8 @@
 use super::*;
 use crate::error::GravityError;
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::Address as EthAddress;
 use clarity::Signature as EthSignature;
-use clarity::{utils::hex_str_to_bytes, Address as EthAddress};
 use deep_space::Address as CosmosAddress;
 
 /// the response we get when querying for a valset confirmation
@@ -19,6 +20,29 @@ pub struct LogicCall {
     pub invalidation_nonce: u64,
 }
 
+impl LogicCall {
+    /// A human readable description of everything covered by a signature over this logic call's
+    /// checkpoint, so operators can sanity check what they are attesting to
+    pub fn checkpoint_summary(&self) -> String {
+        let tokens = |list: &[Erc20Token]| {
+            list.iter()
+                .map(|t| format!("{} of {}", t.amount, t.token_contract_address))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        format!(
+            "LogicCall 0x{}/{} calling {} with a {} byte payload, times out at Ethereum block {}\n    transfers: [{}]\n    fees: [{}]",
+            bytes_to_hex_str(&self.invalidation_id),
+            self.invalidation_nonce,
+            self.logic_contract_address,
+            self.payload.len(),
+            self.timeout,
+            tokens(&self.transfers),
+            tokens(&self.fees)
+        )
+    }
+}
+
 /// the response we get when querying for a logic call confirmation
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LogicCallConfirmResponse {
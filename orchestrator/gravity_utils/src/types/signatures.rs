@@ -68,6 +68,49 @@ pub fn to_arrays(input: Vec<GravitySignature>) -> GravitySignatureArrays {
     }
 }
 
+/// Omits every signature not needed to pass `power_threshold`, keeping the smallest set of the
+/// highest powered signatures that exceeds it. Omitted signatures are zeroed rather than removed,
+/// the contract requires one entry per member of the current valset and skips those with a v of
+/// zero, so each one costs only zero bytes of calldata and is never verified. Order is preserved
+pub fn trim_signatures_to_threshold(
+    input: Vec<GravitySignature>,
+    power_threshold: u64,
+) -> Vec<GravitySignature> {
+    let zero: Uint256 = 0u8.into();
+    let mut signed: Vec<&GravitySignature> = input.iter().filter(|s| s.v != zero).collect();
+    signed.sort();
+    signed.reverse();
+    let mut keep = Vec::new();
+    let mut power = 0u64;
+    for sig in signed {
+        if power > power_threshold {
+            break;
+        }
+        power += sig.power;
+        keep.push(sig.eth_address);
+    }
+    // not enough power to pass anyway, leave the signatures as they are
+    if power <= power_threshold {
+        return input;
+    }
+
+    input
+        .into_iter()
+        .map(|sig| {
+            if keep.contains(&sig.eth_address) {
+                sig
+            } else {
+                GravitySignature {
+                    v: zero,
+                    r: zero,
+                    s: zero,
+                    ..sig
+                }
+            }
+        })
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Hash)]
 pub struct SigWithAddress {
     pub eth_address: EthAddress,
@@ -166,4 +209,36 @@ mod tests {
         incorrect.reverse();
         assert_eq!(incorrect, correct);
     }
+
+    #[test]
+    fn test_trim_signatures() {
+        let sig = |power: u64, address: &str, signed: bool| {
+            let v: u64 = if signed { 27 } else { 0 };
+            GravitySignature {
+                power,
+                eth_address: address.parse().unwrap(),
+                v: v.into(),
+                r: v.into(),
+                s: v.into(),
+            }
+        };
+        let input = vec![
+            sig(10, "0x0000000000000000000000000000000000000001", true),
+            sig(40, "0x0000000000000000000000000000000000000002", true),
+            sig(20, "0x0000000000000000000000000000000000000003", false),
+            sig(30, "0x0000000000000000000000000000000000000004", true),
+        ];
+        let trimmed = trim_signatures_to_threshold(input.clone(), 66);
+        // the 40 and 30 signatures are enough to pass, the 10 is dropped
+        assert_eq!(trimmed.len(), input.len());
+        assert_eq!(trimmed[0].v, 0u8.into());
+        assert_eq!(trimmed[0].r, 0u8.into());
+        assert_eq!(trimmed[0].power, 10);
+        assert_eq!(trimmed[1], input[1]);
+        assert_eq!(trimmed[2], input[2]);
+        assert_eq!(trimmed[3], input[3]);
+
+        // nothing to trim without enough power
+        assert_eq!(trim_signatures_to_threshold(input.clone(), 80), input);
+    }
 }
@@ -88,6 +88,26 @@ pub struct Valset {
 }
 
 impl Valset {
+    /// A human readable description of everything covered by a signature over this valset's
+    /// checkpoint, so operators can sanity check what they are attesting to
+    pub fn checkpoint_summary(&self) -> String {
+        let reward = match self.reward_token {
+            Some(token) => format!("{} of {}", self.reward_amount, token),
+            None => "none".to_string(),
+        };
+        let mut out = format!(
+            "Valset {} with {} members, total power {}, relayer reward {}",
+            self.nonce,
+            self.members.len(),
+            self.get_total_power(),
+            reward
+        );
+        for member in self.members.iter() {
+            out += &format!("\n    {}", member);
+        }
+        out
+    }
+
     /// If this is false the validator set is invalid, as it does not
     /// have enough validators with enough voting power to ever pass
     /// anything on the bridge
@@ -0,0 +1,151 @@
+//! Watch only mode, the orchestrator follows the orchestrator address of some validator and derives
+//! every confirm and claim that address should submit, but never signs or broadcasts anything. What
+//! would have been sent is logged and exported one JSON object per line, so that the behavior of
+//! the orchestrator can be checked against an independent implementation. Disabled until
+//! `set_watch_only` is called
+
+use deep_space::address::Address as CosmosAddress;
+use deep_space::private_key::PrivateKey;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+lazy_static! {
+    static ref WATCH_ONLY: RwLock<Option<WatchOnly>> = RwLock::new(None);
+    /// Kinds and nonces already exported, the same items are derived every loop until the watched
+    /// orchestrator submits them
+    static ref EXPORTED: RwLock<HashSet<(String, u64)>> = RwLock::new(HashSet::new());
+}
+
+#[derive(Debug, Clone)]
+struct WatchOnly {
+    address: CosmosAddress,
+    export_path: PathBuf,
+}
+
+/// Something the watched orchestrator is expected to submit
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatchRecord {
+    /// Unix timestamp at which the item was derived
+    pub timestamp: u64,
+    /// The type of message, for example valset_confirm or ethereum_claims
+    pub kind: String,
+    /// The watched orchestrator address
+    pub orchestrator: String,
+    /// The nonces of the items that would be signed or attested to
+    pub nonces: Vec<u64>,
+    /// The Ethereum checkpoint hashes that would be signed, in the same order as nonces
+    pub checkpoints: Vec<String>,
+    /// The full valsets, batches, logic calls or Ethereum events the messages were derived from
+    pub items: serde_json::Value,
+}
+
+/// Enables watch only mode, following `address` and exporting to `export_path`
+pub fn set_watch_only(address: CosmosAddress, export_path: PathBuf) {
+    *WATCH_ONLY.write().unwrap() = Some(WatchOnly {
+        address,
+        export_path,
+    });
+}
+
+/// Syncs the watch only export to disk, records are written as they are derived so this only
+/// ensures the last of them survive if the host goes down right after a crash. Safe to call from a
+/// panic hook
+pub fn sync_watch_only_export() {
+    // never block or panic here, the lock may be held or poisoned by the panicking thread
+    let watch = match WATCH_ONLY.try_read() {
+        Ok(watch) => watch.clone(),
+        Err(_) => return,
+    };
+    if let Some(watch) = watch {
+        match OpenOptions::new().append(true).open(&watch.export_path) {
+            Ok(f) => {
+                if let Err(e) = f.sync_all() {
+                    warn!(
+                        "Failed to sync watch only export {} {:?}",
+                        watch.export_path.display(),
+                        e
+                    );
+                }
+            }
+            // nothing has been exported yet
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => warn!(
+                "Failed to sync watch only export {} {:?}",
+                watch.export_path.display(),
+                e
+            ),
+        }
+    }
+}
+
+/// The orchestrator address being watched, None unless watch only mode is enabled
+pub fn get_watched_address() -> Option<CosmosAddress> {
+    WATCH_ONLY.read().unwrap().as_ref().map(|w| w.address)
+}
+
+/// The address the orchestrator acts as, the watched address in watch only mode and the address
+/// of `key` otherwise
+pub fn orchestrator_address(key: &impl PrivateKey, prefix: &str) -> CosmosAddress {
+    match get_watched_address() {
+        Some(a) => a,
+        None => key.to_address(prefix).unwrap(),
+    }
+}
+
+/// Logs and exports items the watched orchestrator is expected to submit, items exported before
+/// are skipped
+pub fn record_watched<T: Serialize>(
+    kind: &str,
+    nonces: Vec<u64>,
+    checkpoints: Vec<String>,
+    items: &T,
+) {
+    let watch = match WATCH_ONLY.read().unwrap().clone() {
+        Some(w) => w,
+        None => return,
+    };
+    {
+        let mut exported = EXPORTED.write().unwrap();
+        let mut new = false;
+        for nonce in nonces.iter() {
+            new |= exported.insert((kind.to_string(), *nonce));
+        }
+        if !new {
+            return;
+        }
+    }
+    info!(
+        "Watch only: {} would submit {} for nonces {:?} with checkpoints {:?}",
+        watch.address, kind, nonces, checkpoints
+    );
+
+    let record = WatchRecord {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+        kind: kind.to_string(),
+        orchestrator: watch.address.to_string(),
+        nonces,
+        checkpoints,
+        items: serde_json::to_value(items).unwrap_or(serde_json::Value::Null),
+    };
+    let line = serde_json::to_string(&record).unwrap();
+    let res = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&watch.export_path)
+        .and_then(|mut f| writeln!(f, "{}", line));
+    if let Err(e) = res {
+        warn!(
+            "Failed to write watch only export {} {:?}",
+            watch.export_path.display(),
+            e
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! A REST endpoint returning Cosmos, Ethereum and pending bridge balances for many addresses at
+//! once, so that portfolio frontends don't need a Cosmos and an Ethereum request per address and
+//! token. Only served when the server is given Cosmos gRPC and Ethereum RPC endpoints
+
+use actix_web::{post, web, HttpResponse};
+use cosmos_gravity::balances::{get_bulk_balances, BulkBalanceRequest};
+use deep_space::Contact;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use log::warn;
+use serde_json::json;
+use std::time::Duration;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// The most addresses, Cosmos and Ethereum combined, accepted in one request
+pub const MAX_ADDRESSES: usize = 100;
+/// The most tokens, ERC20s and denoms combined, accepted in one request
+pub const MAX_TOKENS: usize = 50;
+
+/// The connections used to serve balance requests. Web3 is not Send so it can't be shared between
+/// the server's worker threads, a client is made for each request instead
+pub struct BalanceApi {
+    pub contact: Contact,
+    pub grpc_client: GravityQueryClient<Channel>,
+    pub ethereum_rpc: String,
+    pub timeout: Duration,
+}
+
+/// Expects a JSON body of {"cosmos_addresses", "ethereum_addresses", "erc20s", "denoms"}, all
+/// optional lists, and responds with {"cosmos", "ethereum"} balances for each address in order
+#[post("/balances")]
+async fn balances(
+    api: web::Data<BalanceApi>,
+    req_body: web::Json<BulkBalanceRequest>,
+) -> HttpResponse {
+    let request = req_body.into_inner();
+    if request.cosmos_addresses.len() + request.ethereum_addresses.len() > MAX_ADDRESSES
+        || request.erc20s.len() + request.denoms.len() > MAX_TOKENS
+    {
+        return HttpResponse::BadRequest().json(json!({
+            "error": format!(
+                "At most {MAX_ADDRESSES} addresses and {MAX_TOKENS} tokens may be queried at once"
+            )
+        }));
+    }
+    let web3 = Web3::new(&api.ethereum_rpc, api.timeout);
+    match get_bulk_balances(&api.contact, &api.grpc_client, &web3, &request).await {
+        Ok(balances) => HttpResponse::Ok().json(balances),
+        Err(e) => {
+            warn!("Failed to query balances {e:?}");
+            HttpResponse::BadGateway().json(json!({ "error": e.to_string() }))
+        }
+    }
+}
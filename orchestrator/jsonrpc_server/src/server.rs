@@ -2,6 +2,7 @@ const DEFAULT_DOMAIN: &str = "localhost";
 const DEFAULT_PORT: u16 = 8545;
 const EVM_CHAIN_ID: u64 = 999999;
 
+use crate::balances::{balances, BalanceApi};
 use crate::tls::{load_certs, load_private_key};
 use actix_cors::Cors;
 use actix_web::{post, web, App, HttpResponse, HttpServer};
@@ -172,6 +173,7 @@ pub async fn run(
     use_ssl: Option<bool>,
     cert_chain_path: Option<String>,
     cert_key_path: Option<String>,
+    balance_api: Option<BalanceApi>,
 ) -> std::io::Result<()> {
     openssl_probe::init_ssl_cert_env_vars();
 
@@ -183,15 +185,23 @@ pub async fn run(
     let cert_key_path =
         cert_key_path.unwrap_or(format!("/etc/letsencrypt/live/{}/privkey.pem", domain));
 
+    let balance_api = balance_api.map(web::Data::new);
+    if balance_api.is_some() {
+        info!("Serving bulk balance queries at /balances");
+    }
     let server = HttpServer::new(move || {
-        App::new()
+        let app = App::new()
             .wrap(
                 Cors::default()
                     .allow_any_origin()
                     .allow_any_header()
                     .allow_any_method(),
             )
-            .service(request_dispatcher)
+            .service(request_dispatcher);
+        match &balance_api {
+            Some(api) => app.app_data(api.clone()).service(balances),
+            None => app,
+        }
     });
 
     let server = if use_ssl {
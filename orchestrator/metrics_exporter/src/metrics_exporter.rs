@@ -1,9 +1,11 @@
 use gravity_utils::types::MetricsConfig;
 use lazy_static::lazy_static;
+use prometheus_exporter::prometheus::core::Collector;
 use prometheus_exporter::prometheus::{
-    register_int_counter, register_int_counter_vec, register_int_gauge_vec,
+    register_int_counter, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
 };
-use prometheus_exporter::prometheus::{IntCounter, IntCounterVec, IntGaugeVec};
+use prometheus_exporter::prometheus::{IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 
 lazy_static! {
@@ -32,9 +34,41 @@ lazy_static! {
     pub static ref WARNING_UNCLASSIFIED: IntCounterVec =
         register_int_counter_vec!("orchestrator_warnings_count_unclassified", "Chech orchestrator logs for more details", &["warn_message"]).unwrap();
 
+    // Deposits
+    pub static ref INVALID_DESTINATION_DEPOSITS: IntCounter =
+        register_int_counter!("orchestrator_invalid_destination_deposits_total", "Deposits claimed with a destination that is not valid bech32").unwrap();
+
     // Information gauges
     pub static ref LATEST_INFO: IntGaugeVec =
         register_int_gauge_vec!("orchestrator_information", "Latest orchestrator information", &["gauge"]).unwrap();
+
+    // Confirm progress
+    pub static ref CONFIRM_PROGRESS: IntGaugeVec =
+        register_int_gauge_vec!("orchestrator_confirm_power_basis_points", "Basis points of the bridge power that has confirmed each outstanding valset and batch", &["kind", "id"]).unwrap();
+
+    // Oracle
+    pub static ref LAST_OBSERVED_EVENT_NONCE: IntGauge =
+        register_int_gauge!("orchestrator_last_observed_event_nonce", "Last event nonce observed by the network").unwrap();
+    pub static ref LAST_CLAIMED_EVENT_NONCE: IntGauge =
+        register_int_gauge!("orchestrator_last_claimed_event_nonce", "Last event nonce claimed by this orchestrator").unwrap();
+    pub static ref CLAIMS_SUBMITTED: IntCounter =
+        register_int_counter!("orchestrator_claims_submitted_total", "Ethereum event claims submitted to Cosmos").unwrap();
+
+    // Signer
+    pub static ref LAST_SIGNED_NONCE: IntGaugeVec =
+        register_int_gauge_vec!("orchestrator_last_signed_nonce", "Nonce of the last valset, batch or logic call confirmed by this orchestrator", &["kind"]).unwrap();
+
+    // Transaction and RPC failures
+    pub static ref COSMOS_TX_FAILURES: IntCounterVec =
+        register_int_counter_vec!("orchestrator_cosmos_tx_failures_total", "Cosmos transactions that failed to be included", &["kind"]).unwrap();
+    pub static ref ETH_RPC_ERRORS: IntCounterVec =
+        register_int_counter_vec!("orchestrator_eth_rpc_errors_total", "Failed Ethereum RPC requests", &["call"]).unwrap();
+
+    // Relayer
+    pub static ref RELAYER_GAS_USED: IntCounterVec =
+        register_int_counter_vec!("relayer_gas_used_total", "Gas used by relayed transactions", &["kind"]).unwrap();
+    pub static ref RELAYER_GAS_SPENT: IntCounterVec =
+        register_int_counter_vec!("relayer_gas_spent_gwei_total", "Gwei spent on gas by relayed transactions", &["kind"]).unwrap();
 }
 
 pub fn metrics_errors_counter(s: i32, e: &str) {
@@ -57,6 +91,10 @@ pub fn metrics_warnings_counter(s: i32, e: &str) {
     WARNINGS_TOTAL.inc()
 }
 
+pub fn metrics_invalid_destination_deposits(count: u64) {
+    INVALID_DESTINATION_DEPOSITS.inc_by(count)
+}
+
 pub fn metrics_latest(u: u64, e: &str) {
     match i64::try_from(u).is_ok() {
         true => {
@@ -66,6 +104,102 @@ pub fn metrics_latest(u: u64, e: &str) {
     }
 }
 
+/// Replaces the confirm progress of every outstanding item, given as (kind, id, basis points).
+/// Items no longer outstanding are removed
+pub fn metrics_confirm_progress(progress: &[(String, String, u64)]) {
+    CONFIRM_PROGRESS.reset();
+    for (kind, id, basis_points) in progress {
+        CONFIRM_PROGRESS
+            .with_label_values(&[kind.as_str(), id.as_str()])
+            .set(*basis_points as i64);
+    }
+}
+
+/// Sets the last event nonce observed by the network and the last one claimed by this orchestrator
+pub fn metrics_event_nonces(observed: u64, claimed: u64) {
+    if let (Ok(observed), Ok(claimed)) = (i64::try_from(observed), i64::try_from(claimed)) {
+        LAST_OBSERVED_EVENT_NONCE.set(observed);
+        LAST_CLAIMED_EVENT_NONCE.set(claimed);
+    }
+}
+
+/// Counts `count` submitted claims, the highest of which had event nonce `last_nonce`
+pub fn metrics_claims_submitted(count: u64, last_nonce: u64) {
+    CLAIMS_SUBMITTED.inc_by(count);
+    if let Ok(last_nonce) = i64::try_from(last_nonce) {
+        if last_nonce > LAST_CLAIMED_EVENT_NONCE.get() {
+            LAST_CLAIMED_EVENT_NONCE.set(last_nonce);
+        }
+    }
+}
+
+/// Sets the nonce of the last item of `kind` this orchestrator confirmed
+pub fn metrics_signed_nonce(kind: &str, nonce: u64) {
+    if let Ok(nonce) = i64::try_from(nonce) {
+        LAST_SIGNED_NONCE.with_label_values(&[kind]).set(nonce);
+    }
+}
+
+/// Counts a Cosmos transaction of `kind` that failed
+pub fn metrics_cosmos_tx_failure(kind: &str) {
+    COSMOS_TX_FAILURES.with_label_values(&[kind]).inc()
+}
+
+/// Counts a failed Ethereum RPC request made for `call`
+pub fn metrics_eth_rpc_error(call: &str) {
+    ETH_RPC_ERRORS.with_label_values(&[call]).inc()
+}
+
+/// Counts the gas used by a relayed transaction of `kind` and what it cost in wei
+pub fn metrics_relayer_gas(kind: &str, gas_used: u64, cost_wei: u128) {
+    RELAYER_GAS_USED.with_label_values(&[kind]).inc_by(gas_used);
+    RELAYER_GAS_SPENT
+        .with_label_values(&[kind])
+        .inc_by(u64::try_from(cost_wei / 1_000_000_000).unwrap_or(u64::MAX));
+}
+
+/// The error counts since startup by source, then by message, in the same categories as
+/// `metrics_errors_counter`
+pub fn error_counts() -> BTreeMap<&'static str, BTreeMap<String, u64>> {
+    let mut counts = BTreeMap::new();
+    for (source, vec) in [
+        ("cosmos_eth", &*ERROR),
+        ("eth", &*ERROR_ETH),
+        ("cosmos", &*ERROR_COSMOS),
+        ("unclassified", &*ERROR_UNCLASSIFIED),
+    ] {
+        let mut messages = BTreeMap::new();
+        for family in vec.collect() {
+            for metric in family.get_metric() {
+                if let Some(label) = metric.get_label().first() {
+                    messages.insert(
+                        label.get_value().to_string(),
+                        metric.get_counter().get_value() as u64,
+                    );
+                }
+            }
+        }
+        counts.insert(source, messages);
+    }
+    counts
+}
+
+/// The current value of each information gauge set through `metrics_latest`
+pub fn latest_values() -> BTreeMap<String, i64> {
+    let mut values = BTreeMap::new();
+    for family in LATEST_INFO.collect() {
+        for metric in family.get_metric() {
+            if let Some(label) = metric.get_label().first() {
+                values.insert(
+                    label.get_value().to_string(),
+                    metric.get_gauge().get_value() as i64,
+                );
+            }
+        }
+    }
+    values
+}
+
 pub fn metrics_server(config: &MetricsConfig) {
     // Parse address used to bind exporter to.
     let addr_raw = &config.metrics_bind;
@@ -74,6 +208,14 @@ pub fn metrics_server(config: &MetricsConfig) {
     prometheus_exporter::start(addr).expect("can not start exporter");
 }
 
+#[test]
+fn test_claims_submitted_keeps_highest_nonce() {
+    metrics_claims_submitted(2, 10);
+    metrics_claims_submitted(1, 7);
+    assert_eq!(LAST_CLAIMED_EVENT_NONCE.get(), 10);
+    assert_eq!(CLAIMS_SUBMITTED.get(), 3);
+}
+
 /// Test overflowing bigint
 #[test]
 fn test_overflow_big_integer() {
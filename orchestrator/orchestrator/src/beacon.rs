@@ -0,0 +1,131 @@
+//! Optional verification of Ethereum finality against a consensus layer client. Normally the oracle
+//! trusts its execution node to report the finalized block, validators with a very low risk
+//! tolerance may also connect a beacon node. When one is configured the oracle only attests to
+//! events in blocks the beacon chain has finalized and checks that the execution node agrees with
+//! the beacon node on the hash of that block. If the check can not be completed after a few
+//! attempts the oracle falls back to depth based safety rather than stall indefinitely.
+
+use crate::finality::depth_safe_block;
+use clarity::Uint256;
+use gravity_utils::error::GravityError;
+use lazy_static::lazy_static;
+use metrics_exporter::metrics_errors_counter;
+use serde_json::Value;
+use std::cmp::min;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+lazy_static! {
+    static ref BEACON_API_URL: RwLock<Option<String>> = RwLock::new(None);
+}
+
+const BEACON_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_TIME: Duration = Duration::from_secs(5);
+/// How many times finality is checked against the beacon node before falling back
+const BEACON_ATTEMPTS: u32 = 5;
+
+/// Sets the beacon node REST API url used to verify finality, None disables verification
+pub fn set_beacon_api_url(url: Option<String>) {
+    *BEACON_API_URL.write().unwrap() = url.map(|u| u.trim_end_matches('/').to_string());
+}
+
+/// The execution block contained in the latest finalized beacon block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconFinalizedBlock {
+    pub number: Uint256,
+    pub hash: String,
+}
+
+/// Gets the execution payload of the latest finalized beacon block from the standard beacon API
+pub async fn get_beacon_finalized_block(url: &str) -> Result<BeaconFinalizedBlock, GravityError> {
+    let res = reqwest::Client::new()
+        .get(format!("{}/eth/v2/beacon/blocks/finalized", url))
+        .header("Accept", "application/json")
+        .timeout(BEACON_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| GravityError::EthereumContractError(e.to_string()))?
+        .text()
+        .await
+        .map_err(|e| GravityError::EthereumContractError(e.to_string()))?;
+    let res: Value = serde_json::from_str(&res)
+        .map_err(|e| GravityError::EthereumContractError(e.to_string()))?;
+    let payload = &res["data"]["message"]["body"]["execution_payload"];
+    match (
+        payload["block_number"]
+            .as_str()
+            .and_then(|n| n.parse::<u64>().ok()),
+        payload["block_hash"].as_str(),
+    ) {
+        (Some(number), Some(hash)) => Ok(BeaconFinalizedBlock {
+            number: number.into(),
+            hash: hash.to_lowercase(),
+        }),
+        _ => Err(GravityError::EthereumContractError(format!(
+            "Beacon node returned a finalized block without an execution payload {}",
+            res
+        ))),
+    }
+}
+
+async fn get_execution_block_hash(web3: &Web3, number: Uint256) -> Result<String, GravityError> {
+    let client = HttpClient::new(&web3.get_url());
+    let block: Value = client
+        .request_method(
+            "eth_getBlockByNumber",
+            (format!("{:#x}", number), false),
+            BEACON_TIMEOUT,
+        )
+        .await?;
+    block["hash"]
+        .as_str()
+        .map(|h| h.to_lowercase())
+        .ok_or_else(|| {
+            GravityError::EthereumContractError(format!("Node returned no block {}", number))
+        })
+}
+
+/// Limits `safe_block` to the block finalized by the beacon chain, if a beacon node is configured.
+/// Retries while the beacon node is unreachable or disagrees with the execution node on the
+/// finalized block, after BEACON_ATTEMPTS the depth based safe block is used instead
+pub async fn limit_to_beacon_finalized(web3: &Web3, safe_block: Uint256) -> Uint256 {
+    let url = match BEACON_API_URL.read().unwrap().clone() {
+        Some(url) => url,
+        None => return safe_block,
+    };
+    for _ in 0..BEACON_ATTEMPTS {
+        let finalized = match get_beacon_finalized_block(&url).await {
+            Ok(f) => f,
+            Err(e) => {
+                error!("Failed to get finalized block from beacon node {:?}", e);
+                metrics_errors_counter(1, "Failed to get finalized block from beacon node");
+                delay_for(RETRY_TIME).await;
+                continue;
+            }
+        };
+        match get_execution_block_hash(web3, finalized.number).await {
+            Ok(hash) if hash == finalized.hash => return min(safe_block, finalized.number),
+            Ok(hash) => {
+                error!(
+                    "Beacon node finalized block {} with hash {} but the Ethereum node has hash {}",
+                    finalized.number, finalized.hash, hash
+                );
+                metrics_errors_counter(1, "Beacon and execution node disagree on finality");
+            }
+            Err(e) => error!(
+                "Failed to get beacon finalized block {} from Ethereum node {:?}",
+                finalized.number, e
+            ),
+        }
+        delay_for(RETRY_TIME).await;
+    }
+    warn!(
+        "Could not verify finality against the beacon node after {} attempts, falling back to depth based safety",
+        BEACON_ATTEMPTS
+    );
+    metrics_errors_counter(1, "Beacon finality check fell back to depth");
+    depth_safe_block(web3, safe_block).await
+}
@@ -0,0 +1,160 @@
+//! Cross checks the checkpoints the Ethereum signer is about to sign against the checkpoints the
+//! Gravity module computed for the same items. The module does not expose its checkpoints directly,
+//! but every confirm it has accepted was verified against its own checkpoint. If a signature from
+//! another validator recovers correctly against our locally computed checkpoint the two must be
+//! identical, if none of them do our encoder has drifted from the chain's and signing would produce
+//! signatures over checkpoints that do not exist on Cosmos, which are slashable.
+
+use cosmos_gravity::query::{
+    get_all_valset_confirms, get_logic_call_signatures, get_transaction_batch_signatures,
+};
+use ethereum_gravity::message_signatures::{
+    encode_logic_call_confirm_hashed, encode_tx_batch_confirm_hashed, encode_valset_confirm_hashed,
+};
+use futures::future::join_all;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::types::{Confirm, LogicCall, TransactionBatch, Valset};
+use metrics_exporter::metrics_errors_counter;
+use tonic::transport::Channel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckpointCheck {
+    /// A confirm accepted by the chain was signed over our checkpoint
+    Match,
+    /// Nobody else has signed yet, so there is nothing to compare against
+    Unverified,
+    /// The chain has accepted confirms but none of them are over our checkpoint
+    Mismatch,
+}
+
+fn cross_check<T: Confirm>(checkpoint: &[u8], confirms: &[T]) -> CheckpointCheck {
+    if confirms.is_empty() {
+        return CheckpointCheck::Unverified;
+    }
+    for confirm in confirms {
+        if let Ok(signer) = confirm.get_signature().recover(checkpoint) {
+            if signer == confirm.get_eth_address() {
+                return CheckpointCheck::Match;
+            }
+        }
+    }
+    CheckpointCheck::Mismatch
+}
+
+fn report_mismatch(item: &str, checkpoint: &[u8]) {
+    error!(
+        "Refusing to sign {}, our checkpoint 0x{} does not match the checkpoint other validators signed on chain. Your orchestrator is likely incompatible with this chain, upgrade it immediately",
+        item,
+        clarity::utils::bytes_to_hex_str(checkpoint)
+    );
+    metrics_errors_counter(2, "Locally computed checkpoint does not match the chain");
+}
+
+/// Returns the valsets that are safe to sign, dropping any whose checkpoint disagrees with the chain
+pub async fn check_valset_checkpoints(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_id: &str,
+    valsets: Vec<Valset>,
+) -> Vec<Valset> {
+    // confirms for every valset are requested at once over the shared channel
+    let all_confirms = join_all(valsets.iter().map(|valset| {
+        let mut grpc_client = grpc_client.clone();
+        let nonce = valset.nonce;
+        async move { get_all_valset_confirms(&mut grpc_client, nonce).await }
+    }))
+    .await;
+    let mut out = Vec::new();
+    for (valset, confirms) in valsets.into_iter().zip(all_confirms) {
+        let checkpoint = encode_valset_confirm_hashed(gravity_id.to_string(), valset.clone());
+        match confirms {
+            Ok(confirms) => match cross_check(&checkpoint, &confirms) {
+                CheckpointCheck::Mismatch => {
+                    report_mismatch(&format!("valset {}", valset.nonce), &checkpoint)
+                }
+                CheckpointCheck::Match | CheckpointCheck::Unverified => out.push(valset),
+            },
+            Err(e) => {
+                warn!(
+                    "Could not cross check valset {} checkpoint with the chain {:?}",
+                    valset.nonce, e
+                );
+                out.push(valset)
+            }
+        }
+    }
+    out
+}
+
+/// Returns the batches that are safe to sign, dropping any whose checkpoint disagrees with the chain
+pub async fn check_batch_checkpoints(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_id: &str,
+    batches: Vec<TransactionBatch>,
+) -> Vec<TransactionBatch> {
+    let all_confirms =
+        join_all(batches.iter().map(|batch| {
+            let mut grpc_client = grpc_client.clone();
+            let (nonce, token_contract) = (batch.nonce, batch.token_contract);
+            async move {
+                get_transaction_batch_signatures(&mut grpc_client, nonce, token_contract).await
+            }
+        }))
+        .await;
+    let mut out = Vec::new();
+    for (batch, confirms) in batches.into_iter().zip(all_confirms) {
+        let checkpoint = encode_tx_batch_confirm_hashed(gravity_id.to_string(), batch.clone());
+        match confirms {
+            Ok(confirms) => match cross_check(&checkpoint, &confirms) {
+                CheckpointCheck::Mismatch => report_mismatch(
+                    &format!("batch {} for {}", batch.nonce, batch.token_contract),
+                    &checkpoint,
+                ),
+                CheckpointCheck::Match | CheckpointCheck::Unverified => out.push(batch),
+            },
+            Err(e) => {
+                warn!(
+                    "Could not cross check batch {} checkpoint with the chain {:?}",
+                    batch.nonce, e
+                );
+                out.push(batch)
+            }
+        }
+    }
+    out
+}
+
+/// Returns the logic calls that are safe to sign, dropping any whose checkpoint disagrees with the
+/// chain
+pub async fn check_logic_call_checkpoints(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    gravity_id: &str,
+    calls: Vec<LogicCall>,
+) -> Vec<LogicCall> {
+    let all_confirms = join_all(calls.iter().map(|call| {
+        let mut grpc_client = grpc_client.clone();
+        let (id, nonce) = (call.invalidation_id.clone(), call.invalidation_nonce);
+        async move { get_logic_call_signatures(&mut grpc_client, id, nonce).await }
+    }))
+    .await;
+    let mut out = Vec::new();
+    for (call, confirms) in calls.into_iter().zip(all_confirms) {
+        let checkpoint = encode_logic_call_confirm_hashed(gravity_id.to_string(), call.clone());
+        match confirms {
+            Ok(confirms) => match cross_check(&checkpoint, &confirms) {
+                CheckpointCheck::Mismatch => report_mismatch(
+                    &format!("logic call {}", call.invalidation_nonce),
+                    &checkpoint,
+                ),
+                CheckpointCheck::Match | CheckpointCheck::Unverified => out.push(call),
+            },
+            Err(e) => {
+                warn!(
+                    "Could not cross check logic call {} checkpoint with the chain {:?}",
+                    call.invalidation_nonce, e
+                );
+                out.push(call)
+            }
+        }
+    }
+    out
+}
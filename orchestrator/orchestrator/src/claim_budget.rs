@@ -0,0 +1,130 @@
+//! Daily fee budget for the oracle's claim submissions. Every claim transaction pays the configured
+//! fee, so a burst of Ethereum events during a chain fee spike can drain the orchestrator quickly.
+//! Spending is tracked over a rolling day and exported as a metric, with alerts once most of the
+//! budget is gone. Claims are never deferred, the Gravity module has no claims window to defer
+//! toward and holding claims back would only hold back the bridge. Spending is only tracked in
+//! memory and starts from zero when the orchestrator restarts
+
+use clarity::Uint256;
+use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
+use num_traits::ToPrimitive;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The period the budget applies to
+pub const BUDGET_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+/// Once this percentage of the budget is spent claims are deferred
+pub const LOW_BUDGET_PERCENT: u8 = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetState {
+    Normal,
+    /// Most of the budget is spent
+    Low,
+    Exhausted,
+}
+
+pub struct ClaimFeeBudget {
+    daily_budget: Option<Uint256>,
+    spends: VecDeque<(Instant, Uint256)>,
+    state: BudgetState,
+}
+
+impl ClaimFeeBudget {
+    /// A budget of `daily_budget` in the fee denom, None only tracks spending
+    pub fn new(daily_budget: Option<Uint256>) -> Self {
+        ClaimFeeBudget {
+            daily_budget,
+            spends: VecDeque::new(),
+            state: BudgetState::Normal,
+        }
+    }
+
+    /// Records a claim fee and alerts as the budget runs down
+    pub fn record_spend(&mut self, amount: Uint256, now: Instant) {
+        self.spends.push_back((now, amount));
+        let spent = self.spent(now);
+        metrics_latest(spent.to_u64().unwrap_or(u64::MAX), "claim_fees_spent_24h");
+        self.update_state(now);
+    }
+
+    /// The amount spent in the last BUDGET_PERIOD
+    pub fn spent(&mut self, now: Instant) -> Uint256 {
+        while let Some((t, _)) = self.spends.front() {
+            if now.saturating_duration_since(*t) < BUDGET_PERIOD {
+                break;
+            }
+            self.spends.pop_front();
+        }
+        let mut total: Uint256 = 0u8.into();
+        for (_, amount) in self.spends.iter() {
+            total += *amount;
+        }
+        total
+    }
+
+    pub fn state(&mut self, now: Instant) -> BudgetState {
+        let budget = match self.daily_budget {
+            Some(b) => b,
+            None => return BudgetState::Normal,
+        };
+        let spent = self.spent(now);
+        if spent >= budget {
+            BudgetState::Exhausted
+        } else if spent * 100u8.into() >= budget * LOW_BUDGET_PERCENT.into() {
+            BudgetState::Low
+        } else {
+            BudgetState::Normal
+        }
+    }
+
+    /// Alerts when the budget state changes, including once spending from a day ago expires
+    pub fn update_state(&mut self, now: Instant) -> BudgetState {
+        let state = self.state(now);
+        if state != self.state {
+            match state {
+                BudgetState::Normal => info!("Claim fee budget recovered"),
+                BudgetState::Low => {
+                    warn!(
+                        "{}% of the daily claim fee budget is spent",
+                        LOW_BUDGET_PERCENT
+                    );
+                    metrics_warnings_counter(2, "Claim fee budget low");
+                }
+                BudgetState::Exhausted => {
+                    error!("The daily claim fee budget is spent! Claims are still sent, raise daily_claim_fee_budget if this is expected or check the chain's fees");
+                    metrics_errors_counter(2, "Claim fee budget exhausted");
+                }
+            }
+            self.state = state;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claim_fee_budget() {
+        let start = Instant::now();
+        let mut budget = ClaimFeeBudget::new(Some(100u8.into()));
+        assert_eq!(budget.update_state(start), BudgetState::Normal);
+
+        budget.record_spend(80u8.into(), start);
+        assert_eq!(budget.update_state(start), BudgetState::Low);
+        budget.record_spend(20u8.into(), start);
+        assert_eq!(budget.update_state(start), BudgetState::Exhausted);
+
+        // spending expires after a day
+        let later = start + BUDGET_PERIOD;
+        assert_eq!(budget.spent(later), 0u8.into());
+        assert_eq!(budget.update_state(later), BudgetState::Normal);
+
+        let mut unlimited = ClaimFeeBudget::new(None);
+        unlimited.record_spend(1000u32.into(), start);
+        assert_eq!(unlimited.update_state(start), BudgetState::Normal);
+        assert_eq!(unlimited.spent(start), 1000u32.into());
+    }
+}
@@ -0,0 +1,219 @@
+//! Watches this validator's claim submission for stalls. If the last event nonce the chain has
+//! recorded for us stops advancing while the network's last observed nonce moves past it our oracle
+//! has stopped attesting, which eventually leads to the validator being jailed for not voting on
+//! events. The individual failures are usually buried in the oracle's logs, so once a stall is
+//! detected the likely causes are checked and reported together in a single alert
+
+use cosmos_gravity::fee_grant::get_fee_granter;
+use cosmos_gravity::query::get_last_event_nonce_for_validator;
+use deep_space::address::Address as CosmosAddress;
+use deep_space::client::ChainStatus;
+use deep_space::coin::Coin;
+use deep_space::Contact;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::QueryLastObservedEthNonceRequest;
+use gravity_utils::debug_state::get_debug_state;
+use lazy_static::lazy_static;
+use metrics_exporter::{metrics_errors_counter, metrics_event_nonces, metrics_latest};
+use num_traits::ToPrimitive;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// How often claim progress is checked
+pub const CLAIM_WATCHDOG_INTERVAL: Duration = Duration::from_secs(60);
+/// An Ethereum node whose latest block is older than this is probably stalled or syncing
+pub const STALE_ETH_BLOCK_AGE: Duration = Duration::from_secs(300);
+/// The oracle's block search is considered behind when this many blocks short of the latest block
+pub const ORACLE_BEHIND_BLOCKS: u64 = 1000;
+
+lazy_static! {
+    static ref ORACLE_ERRORS: RwLock<OracleErrors> = RwLock::new(OracleErrors::default());
+}
+
+/// Failures of the oracle loop since the last time our event nonce advanced
+#[derive(Debug, Clone, Default)]
+struct OracleErrors {
+    count: u64,
+    last: Option<String>,
+}
+
+/// Records a failed oracle iteration, such as an Ethereum query or a rejected claim transaction,
+/// for the next stall report
+pub fn record_oracle_error(error: String) {
+    let mut errors = ORACLE_ERRORS.write().unwrap();
+    errors.count += 1;
+    errors.last = Some(error);
+}
+
+fn take_oracle_errors() -> OracleErrors {
+    std::mem::take(&mut *ORACLE_ERRORS.write().unwrap())
+}
+
+/// Checks every CLAIM_WATCHDOG_INTERVAL that our event nonce keeps up with the network and alerts
+/// with a diagnosis if it has not advanced for `stall_minutes` while the network moved on. A value
+/// of zero disables the alert, the event nonces are still exported as metrics
+pub async fn claim_watchdog_loop(
+    contact: Contact,
+    grpc_client: GravityQueryClient<Channel>,
+    web3: Web3,
+    our_address: CosmosAddress,
+    fee: Coin,
+    stall_minutes: u64,
+) {
+    let stall_time = Duration::from_secs(stall_minutes * 60);
+    let mut grpc_client = grpc_client;
+    let mut our_last_nonce = 0;
+    let mut last_progress = Instant::now();
+    let mut last_alert: Option<Instant> = None;
+    loop {
+        delay_for(CLAIM_WATCHDOG_INTERVAL).await;
+        let ours =
+            get_last_event_nonce_for_validator(&mut grpc_client, our_address, contact.get_prefix())
+                .await;
+        let network = grpc_client
+            .get_last_observed_eth_nonce(QueryLastObservedEthNonceRequest { use_v1_key: false })
+            .await
+            .map(|n| n.into_inner().nonce);
+        let (ours, network) = match (ours, network) {
+            (Ok(o), Ok(n)) => (o, n),
+            // the oracle and signer report gRPC failures themselves
+            _ => continue,
+        };
+        metrics_latest(network.saturating_sub(ours), "event_nonces_behind");
+        metrics_event_nonces(network, ours);
+
+        if ours > our_last_nonce || ours >= network {
+            if last_alert.is_some() {
+                info!(
+                    "Claim submission has recovered, our event nonce is {}",
+                    ours
+                );
+            }
+            our_last_nonce = ours;
+            last_progress = Instant::now();
+            last_alert = None;
+            take_oracle_errors();
+            continue;
+        }
+
+        if stall_minutes == 0 {
+            continue;
+        }
+        let stalled_for = Instant::now() - last_progress;
+        let alert_due = match last_alert {
+            Some(t) => Instant::now() - t >= stall_time,
+            None => stalled_for >= stall_time,
+        };
+        if !alert_due {
+            continue;
+        }
+        last_alert = Some(Instant::now());
+
+        let findings = diagnose(&contact, &web3, our_address, &fee, ours).await;
+        error!(
+            "Claim submission stalled! Our last event nonce {} has not advanced in {} minutes while the network has observed nonce {}. Your validator will be jailed if it does not resume attesting. Likely causes:\n{}",
+            ours,
+            stalled_for.as_secs() / 60,
+            network,
+            findings
+                .iter()
+                .map(|f| format!("    - {}", f))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+        metrics_errors_counter(2, "Claim submission stalled");
+    }
+}
+
+/// Checks the usual reasons claims stop landing, returns a finding for each problem found
+async fn diagnose(
+    contact: &Contact,
+    web3: &Web3,
+    our_address: CosmosAddress,
+    fee: &Coin,
+    our_nonce: u64,
+) -> Vec<String> {
+    let mut out = Vec::new();
+
+    let mut latest_eth_block = None;
+    match web3.eth_get_latest_block().await {
+        Ok(block) => {
+            latest_eth_block = block.number.to_u64();
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let age = now.saturating_sub(block.timestamp.to_u64().unwrap_or(now));
+            if age > STALE_ETH_BLOCK_AGE.as_secs() {
+                out.push(format!(
+                    "The Ethereum node's latest block {} is {} seconds old, it is stalled or still syncing",
+                    block.number, age
+                ));
+            }
+        }
+        Err(e) => out.push(format!("The Ethereum node is unreachable {:?}", e)),
+    }
+
+    match contact.get_chain_status().await {
+        Ok(ChainStatus::Moving { .. }) => {}
+        Ok(ChainStatus::Syncing) => out.push("The Cosmos node is syncing".to_string()),
+        Ok(ChainStatus::WaitingToStart) => {
+            out.push("The Cosmos node is waiting for the chain to start".to_string())
+        }
+        Err(e) => out.push(format!("The Cosmos node is unreachable {:?}", e)),
+    }
+
+    let oracle = get_debug_state().oracle;
+    match (oracle.last_checked_block, latest_eth_block) {
+        (Some(checked), Some(latest)) => {
+            let checked = checked.to_u64().unwrap_or(0);
+            if latest > checked + ORACLE_BEHIND_BLOCKS {
+                out.push(format!(
+                    "The oracle has only searched up to Ethereum block {} of {}, event queries are failing or too slow",
+                    checked, latest
+                ));
+            }
+        }
+        (None, _) => out.push("The oracle has not completed a search for events".to_string()),
+        _ => {}
+    }
+    if let Some(checked_nonce) = oracle.last_checked_event_nonce {
+        if checked_nonce.to_u64().unwrap_or(0) > our_nonce {
+            out.push(format!(
+                "The oracle has found events up to nonce {} but the chain has only accepted our claims up to {}, claim transactions are being rejected",
+                checked_nonce, our_nonce
+            ));
+        }
+    }
+
+    let errors = take_oracle_errors();
+    if let Some(last) = errors.last {
+        out.push(format!(
+            "The oracle has failed {} times since our last progress, most recently with {}",
+            errors.count, last
+        ));
+    }
+
+    match get_fee_granter() {
+        Some(granter) => out.push(format!(
+            "Fees are paid by a fee grant from {}, check it has not run out or expired",
+            granter
+        )),
+        None => match contact.get_balance(our_address, fee.denom.clone()).await {
+            Ok(Some(balance)) if balance.amount >= fee.amount => {}
+            Ok(_) => out.push(format!(
+                "The orchestrator address {} can not pay the {} fee for a claim",
+                our_address, fee
+            )),
+            Err(e) => out.push(format!("Could not check the fee balance {:?}", e)),
+        },
+    }
+
+    if out.is_empty() {
+        out.push("No cause found, check the oracle logs for errors".to_string());
+    }
+    out
+}
@@ -0,0 +1,223 @@
+//! Tracks how much of the bridge power has confirmed each outstanding valset and batch. Once
+//! validators with 2/3 of the power have signed, an item can be relayed to Ethereum, an item stuck
+//! below that for long means too many orchestrators are down or misconfigured and is the first sign
+//! that the bridge is about to stop moving. Progress is exported per item as basis points of the
+//! power of the valset on Ethereum, which is the one the signatures are checked against when
+//! relaying, and an alert is raised for anything below the threshold for too long. Valsets and
+//! batches that are already on Ethereum are not outstanding and are left out
+
+use crate::signer_schedule::SignerWork;
+use clarity::Address as EthAddress;
+use cosmos_gravity::query::{
+    get_all_valset_confirms, get_latest_transaction_batches, get_latest_valsets,
+    get_transaction_batch_signatures,
+};
+use ethereum_gravity::utils::get_tx_batch_nonce;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{Confirm, Valset, GRAVITY_POWER_TO_PASS};
+use metrics_exporter::{metrics_confirm_progress, metrics_errors_counter, metrics_latest};
+use relayer::find_latest_valset::find_latest_valset;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// How often confirm progress is checked
+pub const CONFIRM_PROGRESS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The power of `valset` held by the signers of `confirms`
+pub fn confirmed_power<T: Confirm>(valset: &Valset, confirms: &[T]) -> u64 {
+    let signers: Vec<EthAddress> = confirms.iter().map(|c| c.get_eth_address()).collect();
+    valset
+        .members
+        .iter()
+        .filter(|m| signers.contains(&m.eth_address))
+        .map(|m| m.power)
+        .sum()
+}
+
+/// Remembers since when each outstanding item has been below the threshold
+#[derive(Debug, Default)]
+pub struct ConfirmProgress {
+    below_since: HashMap<SignerWork, Instant>,
+    alerted: HashMap<SignerWork, Instant>,
+}
+
+impl ConfirmProgress {
+    /// Records the confirmed power of every outstanding item at `now`, items no longer outstanding
+    /// are forgotten. Returns the items that have been below the threshold for `alert_after` and
+    /// were not alerted on in the last `alert_after`, with how long they have been stuck
+    pub fn update(
+        &mut self,
+        progress: &[(SignerWork, u64)],
+        now: Instant,
+        alert_after: Duration,
+    ) -> Vec<(SignerWork, Duration)> {
+        let below: Vec<&SignerWork> = progress
+            .iter()
+            .filter(|(_, power)| *power <= GRAVITY_POWER_TO_PASS)
+            .map(|(work, _)| work)
+            .collect();
+        self.below_since.retain(|work, _| below.contains(&work));
+        self.alerted.retain(|work, _| below.contains(&work));
+
+        let mut stuck = Vec::new();
+        for work in below {
+            let since = *self.below_since.entry(work.clone()).or_insert(now);
+            let stuck_for = now.saturating_duration_since(since);
+            let alert_due = match self.alerted.get(work) {
+                Some(t) => now.saturating_duration_since(*t) >= alert_after,
+                None => stuck_for >= alert_after,
+            };
+            if alert_due {
+                self.alerted.insert(work.clone(), now);
+                stuck.push((work.clone(), stuck_for));
+            }
+        }
+        stuck
+    }
+}
+
+/// Checks every CONFIRM_PROGRESS_INTERVAL the confirmed power of the latest valsets and all
+/// outstanding batches, alerting on anything below 2/3 for `alert_minutes`. A value of zero only
+/// exports the metrics
+pub async fn confirm_progress_loop(
+    grpc_client: GravityQueryClient<Channel>,
+    web3: Web3,
+    gravity_contract_address: EthAddress,
+    alert_minutes: u64,
+) {
+    let alert_after = Duration::from_secs(alert_minutes * 60);
+    let mut grpc_client = grpc_client;
+    let mut tracker = ConfirmProgress::default();
+    loop {
+        delay_for(CONFIRM_PROGRESS_INTERVAL).await;
+        let (progress, total_power) =
+            match get_confirm_progress(&mut grpc_client, &web3, gravity_contract_address).await {
+                Ok(p) => p,
+                Err(e) => {
+                    trace!("Failed to check confirm progress {:?}", e);
+                    continue;
+                }
+            };
+        let total_power = total_power.max(1);
+        let labels: Vec<(String, String, u64)> = progress
+            .iter()
+            .map(|(work, power)| {
+                (
+                    work.kind.to_string(),
+                    work.id.clone(),
+                    power * 10_000 / total_power,
+                )
+            })
+            .collect();
+        metrics_confirm_progress(&labels);
+        metrics_latest(
+            progress
+                .iter()
+                .filter(|(_, power)| *power <= GRAVITY_POWER_TO_PASS)
+                .count() as u64,
+            "confirms_below_threshold",
+        );
+
+        let stuck = tracker.update(&progress, Instant::now(), alert_after);
+        if alert_minutes == 0 {
+            continue;
+        }
+        for (work, stuck_for) in stuck {
+            let power = progress
+                .iter()
+                .find(|(w, _)| *w == work)
+                .map(|(_, p)| *p)
+                .unwrap_or_default();
+            error!(
+                "Only {:.1}% of the bridge power has confirmed {} after {} minutes, it can not be relayed until 66.7% has. Check that validators' orchestrators are running",
+                power as f64 * 100.0 / total_power as f64,
+                work,
+                stuck_for.as_secs() / 60
+            );
+            metrics_errors_counter(2, "Valset or batch below confirm threshold");
+        }
+    }
+}
+
+/// The confirmed power of every valset and batch not yet on Ethereum, and the total power of the
+/// Ethereum valset they are measured against
+async fn get_confirm_progress(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+) -> Result<(Vec<(SignerWork, u64)>, u64), GravityError> {
+    let current = find_latest_valset(grpc_client, gravity_contract_address, web3).await?;
+    let mut progress = Vec::new();
+    for valset in get_latest_valsets(grpc_client).await? {
+        if valset.nonce <= current.nonce {
+            continue;
+        }
+        let confirms = get_all_valset_confirms(grpc_client, valset.nonce).await?;
+        progress.push(((&valset).into(), confirmed_power(&current, &confirms)));
+    }
+    let mut executed_nonces = HashMap::new();
+    for batch in get_latest_transaction_batches(grpc_client).await? {
+        let executed = match executed_nonces.get(&batch.token_contract) {
+            Some(nonce) => *nonce,
+            None => {
+                let nonce = get_tx_batch_nonce(
+                    gravity_contract_address,
+                    batch.token_contract,
+                    gravity_contract_address,
+                    web3,
+                )
+                .await?;
+                executed_nonces.insert(batch.token_contract, nonce);
+                nonce
+            }
+        };
+        if batch.nonce <= executed {
+            continue;
+        }
+        let confirms =
+            get_transaction_batch_signatures(grpc_client, batch.nonce, batch.token_contract)
+                .await?;
+        progress.push(((&batch).into(), confirmed_power(&current, &confirms)));
+    }
+    Ok((progress, current.get_total_power()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_schedule::SignerWorkKind;
+
+    #[test]
+    fn test_confirm_progress() {
+        let work = |id: &str| SignerWork {
+            kind: SignerWorkKind::Batch,
+            id: id.to_string(),
+        };
+        let alert_after = Duration::from_secs(600);
+        let start = Instant::now();
+        let mut tracker = ConfirmProgress::default();
+        let progress = vec![
+            (work("1"), GRAVITY_POWER_TO_PASS / 2),
+            (work("2"), u32::MAX as u64),
+        ];
+        assert!(tracker.update(&progress, start, alert_after).is_empty());
+
+        let later = start + Duration::from_secs(700);
+        assert_eq!(
+            tracker.update(&progress, later, alert_after),
+            vec![(work("1"), Duration::from_secs(700))]
+        );
+        // not repeated until another alert_after has passed
+        let later = later + Duration::from_secs(60);
+        assert!(tracker.update(&progress, later, alert_after).is_empty());
+
+        // once it passes the threshold it is forgotten
+        let passed = vec![(work("1"), u32::MAX as u64)];
+        assert!(tracker.update(&passed, later, alert_after).is_empty());
+        assert!(tracker.below_since.is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+//! Deposit notifications over HTTP. Deposits to the configured Cosmos addresses are queued by the
+//! oracle as it observes them, a separate loop delivers the notifications so that a slow or
+//! unreachable endpoint never holds up the oracle. Each deposit is notified twice, once when it is
+//! observed and once when the chain has attested to a claim matching it, failed deliveries are
+//! retried. If the chain attests to a different deposit at the same event nonce, because the
+//! observed one was reorged out, a removed notification is sent instead
+
+use clarity::utils::bytes_to_hex_str;
+use cosmos_gravity::query::{get_deposit_attestation, DepositAttestation};
+use deep_space::address::Address as CosmosAddress;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::log_context::with_event_nonce;
+use gravity_utils::types::{DepositWebhookConfig, SendToCosmosEvent};
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use metrics_exporter::metrics_warnings_counter;
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+
+/// How often queued notifications are delivered and attestations checked
+const WEBHOOK_LOOP_SPEED: Duration = Duration::from_secs(10);
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// The header carrying the signature of the body
+pub const SIGNATURE_HEADER: &str = "X-Gravity-Signature";
+
+lazy_static! {
+    static ref WEBHOOK: RwLock<Option<DepositWebhook>> = RwLock::new(None);
+    /// Deposits that still need an observed or attested notification, by event nonce
+    static ref PENDING: RwLock<BTreeMap<u64, PendingDeposit>> = RwLock::new(BTreeMap::new());
+    /// Event nonces already queued, the oracle sees the same deposits again until its claims land
+    static ref QUEUED: RwLock<HashSet<u64>> = RwLock::new(HashSet::new());
+}
+
+#[derive(Debug, Clone)]
+struct DepositWebhook {
+    url: String,
+    addresses: HashSet<CosmosAddress>,
+    secret: String,
+}
+
+#[derive(Debug, Clone)]
+struct PendingDeposit {
+    deposit: SendToCosmosEvent,
+    observed_sent: bool,
+}
+
+/// Enables deposit notifications as configured, does nothing if no url is set
+pub fn set_deposit_webhook(config: &DepositWebhookConfig) -> Result<(), String> {
+    let url = match &config.url {
+        Some(url) => url.clone(),
+        None => return Ok(()),
+    };
+    let secret = match &config.secret {
+        Some(s) if !s.is_empty() => s.clone(),
+        _ => return Err("deposit_webhook requires a secret to sign notifications".to_string()),
+    };
+    let mut addresses = HashSet::new();
+    for a in config.addresses.iter() {
+        match a.parse() {
+            Ok(a) => {
+                addresses.insert(a);
+            }
+            Err(e) => return Err(format!("Invalid deposit_webhook address {} {:?}", a, e)),
+        }
+    }
+    if addresses.is_empty() {
+        return Err("deposit_webhook has a url but no addresses".to_string());
+    }
+    info!(
+        "Notifying {} of deposits to {} addresses",
+        url,
+        addresses.len()
+    );
+    *WEBHOOK.write().unwrap() = Some(DepositWebhook {
+        url,
+        addresses,
+        secret,
+    });
+    Ok(())
+}
+
+/// Queues notifications for observed deposits to any of the watched addresses
+pub fn record_observed_deposits(deposits: &[SendToCosmosEvent]) {
+    let addresses = match &*WEBHOOK.read().unwrap() {
+        Some(w) => w.addresses.clone(),
+        None => return,
+    };
+    let mut queued = QUEUED.write().unwrap();
+    let mut pending = PENDING.write().unwrap();
+    for d in deposits {
+        let watched = d
+            .validated_destination
+            .map(|a| addresses.contains(&a))
+            .unwrap_or(false);
+        if watched && queued.insert(d.event_nonce) {
+            pending.insert(
+                d.event_nonce,
+                PendingDeposit {
+                    deposit: d.clone(),
+                    observed_sent: false,
+                },
+            );
+        }
+    }
+}
+
+/// Delivers queued notifications, observed ones right away and attested ones once the chain has
+/// observed a claim matching the deposit
+pub async fn deposit_webhook_loop(mut grpc_client: GravityQueryClient<Channel>) {
+    let webhook = match WEBHOOK.read().unwrap().clone() {
+        Some(w) => w,
+        None => return,
+    };
+    let client = reqwest::Client::new();
+    loop {
+        delay_for(WEBHOOK_LOOP_SPEED).await;
+        let pending: Vec<PendingDeposit> = PENDING.read().unwrap().values().cloned().collect();
+        if pending.is_empty() {
+            continue;
+        }
+        for p in pending {
+            let nonce = p.deposit.event_nonce;
+            if !p.observed_sent {
+                if !send_notification(&client, &webhook, &p.deposit, "observed").await {
+                    continue;
+                }
+                if let Some(d) = PENDING.write().unwrap().get_mut(&nonce) {
+                    d.observed_sent = true;
+                }
+            }
+            let status = match get_deposit_attestation(&mut grpc_client, &p.deposit).await {
+                Ok(DepositAttestation::Attested) => "attested",
+                Ok(DepositAttestation::Mismatched(claim)) => {
+                    with_event_nonce(nonce, || {
+                        warn!(
+                            "Event nonce {} was attested as a different deposit {:?}",
+                            nonce, claim
+                        )
+                    });
+                    "removed"
+                }
+                Ok(DepositAttestation::Pending) => continue,
+                Err(e) => {
+                    with_event_nonce(nonce, || {
+                        warn!(
+                            "Could not check the attestation for event nonce {} {:?}",
+                            nonce, e
+                        )
+                    });
+                    continue;
+                }
+            };
+            if send_notification(&client, &webhook, &p.deposit, status).await {
+                PENDING.write().unwrap().remove(&nonce);
+            }
+        }
+    }
+}
+
+/// POSTs a single notification, returns true if the endpoint accepted it
+async fn send_notification(
+    client: &reqwest::Client,
+    webhook: &DepositWebhook,
+    deposit: &SendToCosmosEvent,
+    status: &str,
+) -> bool {
+    let body = notification_body(deposit, status);
+    let res = client
+        .post(&webhook.url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, &body))
+        .body(body)
+        .send()
+        .await;
+    with_event_nonce(deposit.event_nonce, || match res {
+        Ok(r) if r.status().is_success() => {
+            info!(
+                "Sent {} notification for deposit with event nonce {}",
+                status, deposit.event_nonce
+            );
+            true
+        }
+        Ok(r) => {
+            warn!(
+                "Deposit webhook rejected the {} notification for event nonce {} with {}, retrying",
+                status,
+                deposit.event_nonce,
+                r.status()
+            );
+            metrics_warnings_counter(1, "Deposit webhook failed");
+            false
+        }
+        Err(e) => {
+            warn!(
+                "Failed to send the {} notification for event nonce {}, retrying {:?}",
+                status, deposit.event_nonce, e
+            );
+            metrics_warnings_counter(1, "Deposit webhook failed");
+            false
+        }
+    })
+}
+
+fn notification_body(deposit: &SendToCosmosEvent, status: &str) -> String {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    json!({
+        "status": status,
+        "event_nonce": deposit.event_nonce,
+        "erc20": deposit.erc20.to_string(),
+        "sender": deposit.sender.to_string(),
+        "destination": deposit.destination,
+        "amount": deposit.amount.to_string(),
+        "block_height": deposit.block_height.to_string(),
+        "timestamp": timestamp,
+    })
+    .to_string()
+}
+
+/// The hex encoded HMAC-SHA256 of `body` keyed with the shared secret
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body.as_bytes());
+    bytes_to_hex_str(&mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
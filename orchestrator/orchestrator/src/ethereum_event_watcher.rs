@@ -2,16 +2,20 @@
 //! or a transaction batch update. It then responds to these events by performing actions on the Cosmos chain if required
 
 use clarity::{utils::bytes_to_hex_str, Address as EthAddress, Uint256};
-use cosmos_gravity::{query::get_last_event_nonce_for_validator, send::send_ethereum_claims};
-use deep_space::Contact;
-use deep_space::{
-    coin::Coin,
-    private_key::{CosmosPrivateKey, PrivateKey},
+use cosmos_gravity::{
+    query::get_last_event_nonce_for_validator,
+    send::{order_claim_msgs, send_ethereum_claims},
 };
+use deep_space::Contact;
+use deep_space::{coin::Coin, private_key::CosmosPrivateKey};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::get_with_retry::get_net_version_with_retry;
 use gravity_utils::get_with_retry::{get_block_number_with_retry, get_finalized_block_with_retry};
+use gravity_utils::log_context::{with_event_nonce, with_tx_hash};
+use gravity_utils::token_display::format_erc20;
 use gravity_utils::types::event_signatures::*;
+use gravity_utils::types::FinalityMode;
+use gravity_utils::watch_only::{get_watched_address, orchestrator_address};
 use gravity_utils::{
     error::GravityError,
     types::{
@@ -19,11 +23,20 @@ use gravity_utils::{
         TransactionBatchExecutedEvent, ValsetUpdatedEvent,
     },
 };
-use metrics_exporter::metrics_errors_counter;
+use metrics_exporter::{
+    metrics_errors_counter, metrics_eth_rpc_error, metrics_invalid_destination_deposits,
+};
+use std::time::Instant;
 use tonic::transport::Channel;
+use tracing::instrument;
 use web30::client::Web3;
 use web30::jsonrpc::error::Web3Error;
 
+use crate::beacon::limit_to_beacon_finalized;
+use crate::claim_budget::ClaimFeeBudget;
+use crate::deposit_webhook::record_observed_deposits;
+use crate::finality::{get_finality_profile, try_get_tagged_block_number};
+use crate::light_client::{limit_to_light_client_finalized, verify_logs_with_light_client};
 use crate::oracle_resync::BLOCKS_TO_SEARCH;
 
 pub struct CheckedNonces {
@@ -32,6 +45,7 @@ pub struct CheckedNonces {
 }
 
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(starting_block = %starting_block))]
 pub async fn check_for_events(
     web3: &Web3,
     contact: &Contact,
@@ -40,8 +54,9 @@ pub async fn check_for_events(
     our_private_key: CosmosPrivateKey,
     fee: Coin,
     starting_block: Uint256,
+    budget: &mut ClaimFeeBudget,
 ) -> Result<CheckedNonces, GravityError> {
-    let our_cosmos_address = our_private_key.to_address(&contact.get_prefix()).unwrap();
+    let our_cosmos_address = orchestrator_address(&our_private_key, &contact.get_prefix());
     let latest_block = get_latest_safe_block(web3).await;
     trace!(
         "Checking for events starting {} safe {}",
@@ -116,6 +131,8 @@ pub async fn check_for_events(
         erc20_deployed,
         logic_call_executed,
     ) {
+        verify_logs_with_light_client(&[&valsets, &batches, &deposits, &deploys, &logic_calls])
+            .await?;
         let valsets = ValsetUpdatedEvent::from_logs(&valsets)?;
         trace!("parsed valsets {:?}", valsets);
         let withdraws = TransactionBatchExecutedEvent::from_logs(&batches)?;
@@ -148,44 +165,64 @@ pub async fn check_for_events(
             LogicCallExecutedEvent::filter_by_event_nonce(last_event_nonce, &logic_calls);
 
         if !valsets.is_empty() {
-            info!(
-                "Oracle observed Valset update with nonce {} and event nonce {}",
-                valsets[0].valset_nonce, valsets[0].event_nonce
-            )
+            with_event_nonce(valsets[0].event_nonce, || {
+                info!(
+                    "Oracle observed Valset update with nonce {} and event nonce {}",
+                    valsets[0].valset_nonce, valsets[0].event_nonce
+                )
+            })
         }
         if !deposits.is_empty() {
-            info!(
-                "Oracle observed deposit with sender {}, destination {:?}, amount {}, and event nonce {}",
-                deposits[0].sender, deposits[0].validated_destination, deposits[0].amount, deposits[0].event_nonce
-            )
+            let amount = format_erc20(web3, deposits[0].erc20, deposits[0].amount).await;
+            with_event_nonce(deposits[0].event_nonce, || {
+                info!(
+                    "Oracle observed deposit with sender {}, destination {:?}, amount {}, and event nonce {}",
+                    deposits[0].sender, deposits[0].validated_destination, amount, deposits[0].event_nonce
+                )
+            })
+        }
+        record_observed_deposits(&deposits);
+        for deposit in deposits.iter().filter(|d| d.has_invalid_destination()) {
+            with_event_nonce(deposit.event_nonce, || {
+                warn!(
+                    "Oracle observed deposit with an invalid destination, sender {}, erc20 {}, amount {}, and event nonce {}. The claim is submitted unchanged and the chain will send these funds to the community pool",
+                    deposit.sender, deposit.erc20, deposit.amount, deposit.event_nonce
+                )
+            })
         }
         if !withdraws.is_empty() {
-            info!(
-                "Oracle observed batch with nonce {}, contract {}, and event nonce {}",
-                withdraws[0].batch_nonce, withdraws[0].erc20, withdraws[0].event_nonce
-            )
+            with_event_nonce(withdraws[0].event_nonce, || {
+                info!(
+                    "Oracle observed batch with nonce {}, contract {}, and event nonce {}",
+                    withdraws[0].batch_nonce, withdraws[0].erc20, withdraws[0].event_nonce
+                )
+            })
         }
         if !erc20_deploys.is_empty() {
             let v = erc20_deploys[0].clone();
-            if v.cosmos_denom.len() < 1000 && v.name.len() < 1000 && v.symbol.len() < 1000 {
-                info!(
-                "Oracle observed ERC20 deployment with denom {} erc20 name {} and symbol {} and event nonce {}",
-                erc20_deploys[0].cosmos_denom, erc20_deploys[0].name, erc20_deploys[0].symbol, erc20_deploys[0].event_nonce,
-                );
-            } else {
-                info!(
-                    "Oracle observed ERC20 deployment with  event nonce {}",
-                    erc20_deploys[0].event_nonce,
-                );
-            }
+            with_event_nonce(v.event_nonce, || {
+                if v.cosmos_denom.len() < 1000 && v.name.len() < 1000 && v.symbol.len() < 1000 {
+                    info!(
+                    "Oracle observed ERC20 deployment with denom {} erc20 name {} and symbol {} and event nonce {}",
+                    v.cosmos_denom, v.name, v.symbol, v.event_nonce,
+                    );
+                } else {
+                    info!(
+                        "Oracle observed ERC20 deployment with  event nonce {}",
+                        v.event_nonce,
+                    );
+                }
+            })
         }
         if !logic_calls.is_empty() {
-            info!(
-                "Oracle observed logic call execution with ID {} Nonce {} and event nonce {}",
-                bytes_to_hex_str(&logic_calls[0].invalidation_id),
-                logic_calls[0].invalidation_nonce,
-                logic_calls[0].event_nonce
-            )
+            with_event_nonce(logic_calls[0].event_nonce, || {
+                info!(
+                    "Oracle observed logic call execution with ID {} Nonce {} and event nonce {}",
+                    bytes_to_hex_str(&logic_calls[0].invalidation_id),
+                    logic_calls[0].invalidation_nonce,
+                    logic_calls[0].event_nonce
+                )
+            })
         }
 
         if !deposits.is_empty()
@@ -202,17 +239,41 @@ pub async fn check_for_events(
                 erc20_deploys.clone(),
                 logic_calls.clone(),
                 valsets.clone(),
-                fee,
-            )
-            .await?;
-            let new_event_nonce = get_last_event_nonce_for_validator(
-                grpc_client,
-                our_cosmos_address,
-                contact.get_prefix(),
+                fee.clone(),
+                None,
             )
             .await?;
+            // nothing is spent when every claim was left to a redundant orchestrator
+            if res.is_some() {
+                budget.record_spend(fee.amount, Instant::now());
+            }
+            let new_event_nonce = match get_watched_address() {
+                // the watched orchestrator submits these claims in its own time, carry on as if
+                // they had been accepted so the same events are not derived again
+                Some(_) => {
+                    let (nonces, _) = order_claim_msgs(
+                        our_cosmos_address,
+                        deposits.clone(),
+                        withdraws.clone(),
+                        erc20_deploys.clone(),
+                        logic_calls.clone(),
+                        valsets.clone(),
+                    );
+                    nonces.into_iter().max().unwrap_or(last_event_nonce)
+                }
+                None => {
+                    get_last_event_nonce_for_validator(
+                        grpc_client,
+                        our_cosmos_address,
+                        contact.get_prefix(),
+                    )
+                    .await?
+                }
+            };
 
-            info!("Current event nonce is {}", new_event_nonce);
+            with_event_nonce(new_event_nonce, || {
+                info!("Current event nonce is {}", new_event_nonce)
+            });
 
             // since we can't actually trust that the above txresponse is correct we have to check here
             // we may be able to trust the tx response post grpc
@@ -221,7 +282,19 @@ pub async fn check_for_events(
                     format!("Claims did not process, trying to update but still on {}, trying again in a moment, check txhash {:?} for errors", last_event_nonce, res),
                 ));
             } else {
-                info!("Claims processed, new nonce {}", new_event_nonce);
+                with_event_nonce(new_event_nonce, || match &res {
+                    Some(res) => with_tx_hash(res.txhash.clone(), || {
+                        info!("Claims processed, new nonce {}", new_event_nonce)
+                    }),
+                    None => info!("Claims processed, new nonce {}", new_event_nonce),
+                });
+            }
+            let invalid_destination_deposits = deposits
+                .iter()
+                .filter(|d| d.has_invalid_destination() && d.event_nonce <= new_event_nonce)
+                .count();
+            if invalid_destination_deposits > 0 {
+                metrics_invalid_destination_deposits(invalid_destination_deposits as u64);
             }
 
             // find the eth block for our newest event nonce
@@ -257,6 +330,7 @@ pub async fn check_for_events(
     } else {
         error!("Failed to get events");
         metrics_errors_counter(1, "Failed to get events");
+        metrics_eth_rpc_error("get_logs");
         Err(GravityError::EthereumRestError(Web3Error::BadResponse(
             "Failed to get logs!".to_string(),
         )))
@@ -271,7 +345,8 @@ pub async fn check_for_events(
 /// ways. Finality can be delayed many hundreds of blocks and hours of wall time in the worst case
 /// scenario. This function simply asks the full node what the latest finalized block is.
 ///
-/// This function makes an attempt at being safe across all chain-ids using 96 blocks as a conservative
+/// The method used depends on the chain's FinalityProfile, see the finality module. This function
+/// makes an attempt at being safe across all chain-ids using 96 blocks as a conservative
 /// finality value in the case that we are unable to determine the consensus method of the chain.
 ///
 /// As a quick summary of 'why 96?' we summarize epoch and slot timing of Ethereum proof of
@@ -290,20 +365,39 @@ pub async fn check_for_events(
 ///
 pub async fn get_latest_safe_block(web3: &Web3) -> Uint256 {
     let net_version = get_net_version_with_retry(web3).await;
-    let block_number = get_block_number_with_retry(web3).await;
+    let profile = get_finality_profile(net_version);
 
-    match net_version {
-        // Mainline Ethereum, Ethereum classic, or the Ropsten, Kotti, Mordor testnets
-        // all Ethereum proof of stake Chains
-        1 | 3 | 6 | 7 => get_finalized_block_with_retry(web3).await,
-        // Dev, our own Gravity Ethereum testnet, and Hardhat respectively
-        // all single signer chains with no chance of any reorgs
-        2018 | 15 | 31337 => block_number,
-        // Rinkeby and Goerli use Clique (POA) Consensus, finality takes
-        // up to num validators blocks. Number is higher than Ethereum based
-        // on experience with operational issues
-        4 | 5 => block_number - 10u8.into(),
-        // assume the safe option where we don't know
-        _ => block_number - 96u8.into(),
+    let safe_block = match profile.mode {
+        FinalityMode::Finalized => get_finalized_block_with_retry(web3).await,
+        FinalityMode::Instant => get_block_number_with_retry(web3).await,
+        FinalityMode::Safe => {
+            let block_number = get_block_number_with_retry(web3).await;
+            match try_get_tagged_block_number(web3, "safe").await {
+                Some(safe) if safe <= block_number => safe,
+                _ => confirmations_behind(block_number, profile.confirmations),
+            }
+        }
+        // prefer the node's view of finality where it has one, it is both safer and usually
+        // faster than a fixed delay, pre-merge and many sidechain nodes do not support the tag
+        FinalityMode::Confirmations => {
+            let block_number = get_block_number_with_retry(web3).await;
+            match try_get_tagged_block_number(web3, "finalized").await {
+                Some(finalized) if finalized <= block_number && finalized > 0u8.into() => finalized,
+                _ => confirmations_behind(block_number, profile.confirmations),
+            }
+        }
+    };
+    // both are no-ops unless a beacon node or light client is in use
+    let safe_block = limit_to_beacon_finalized(web3, safe_block).await;
+    limit_to_light_client_finalized(web3, safe_block).await
+}
+
+fn confirmations_behind(block_number: Uint256, confirmations: u64) -> Uint256 {
+    let confirmations: Uint256 = confirmations.into();
+    // comparison only to prevent panic on underflow on very young chains
+    if block_number > confirmations {
+        block_number - confirmations
+    } else {
+        0u8.into()
     }
 }
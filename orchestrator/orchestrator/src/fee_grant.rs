@@ -0,0 +1,234 @@
+//! Detection and monitoring of the fee grant the orchestrator pays its fees out of. The grant is
+//! found at startup, after which it is checked periodically so operators are warned well before it
+//! is exhausted or expires, at which point the chain deletes it and orchestrator transactions fail
+
+use clarity::Uint256;
+use cosmos_gravity::fee_grant::{
+    decode_allowance, get_fee_allowance, get_fee_allowances, get_fee_granter, set_fee_granter,
+};
+use cosmos_gravity::send::{
+    MSG_CONFIRM_BATCH_TYPE_URL, MSG_CONFIRM_LOGIC_CALL_TYPE_URL, MSG_REQUEST_BATCH_TYPE_URL,
+    MSG_VALSET_CONFIRM_TYPE_URL,
+};
+use deep_space::address::Address as CosmosAddress;
+use deep_space::coin::Coin;
+use deep_space::Contact;
+use gravity_proto::cosmos_sdk_proto::cosmos::feegrant::v1beta1::Grant;
+use gravity_utils::types::{
+    OrchestratorConfig, MSG_BATCH_SEND_TO_ETH_TYPE_URL, MSG_ERC20_DEPLOYED_CLAIM_TYPE_URL,
+    MSG_LOGIC_CALL_EXECUTED_CLAIM_TYPE_URL, MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL,
+    MSG_VALSET_UPDATED_CLAIM_TYPE_URL,
+};
+use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
+use num_traits::ToPrimitive;
+use std::process::exit;
+use std::time::{Duration, SystemTime};
+use tokio::time::sleep as delay_for;
+
+/// How often the remaining fee allowance is checked
+pub const FEE_GRANT_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+/// Warn when the fee grant expires sooner than this
+pub const FEE_GRANT_EXPIRY_WARNING: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// Every message the orchestrator sends, a grant restricted to certain messages must allow all of
+/// them or some of the orchestrator's duties will go unpaid
+pub const ORCHESTRATOR_MSG_TYPES: [&str; 9] = [
+    MSG_VALSET_CONFIRM_TYPE_URL,
+    MSG_CONFIRM_BATCH_TYPE_URL,
+    MSG_CONFIRM_LOGIC_CALL_TYPE_URL,
+    MSG_SEND_TO_COSMOS_CLAIM_TYPE_URL,
+    MSG_BATCH_SEND_TO_ETH_TYPE_URL,
+    MSG_ERC20_DEPLOYED_CLAIM_TYPE_URL,
+    MSG_LOGIC_CALL_EXECUTED_CLAIM_TYPE_URL,
+    MSG_VALSET_UPDATED_CLAIM_TYPE_URL,
+    MSG_REQUEST_BATCH_TYPE_URL,
+];
+
+/// Finds the fee grant the orchestrator should pay its fees with and sets it as the fee granter
+/// for all orchestrator transactions. Returns the granter, or None if fees are paid directly. Exits
+/// if a granter is configured but has not granted an allowance the orchestrator can use
+pub async fn setup_fee_grant(
+    contact: &Contact,
+    grantee: CosmosAddress,
+    fee: &Coin,
+    config: &OrchestratorConfig,
+) -> Option<CosmosAddress> {
+    if !config.use_fee_grant {
+        return None;
+    }
+    let granter = match &config.fee_granter {
+        Some(granter) => {
+            let granter: CosmosAddress = match granter.parse() {
+                Ok(g) => g,
+                Err(e) => {
+                    error!("Invalid fee_granter {} in config {:?}", granter, e);
+                    exit(1);
+                }
+            };
+            match get_fee_allowance(contact, granter, grantee).await {
+                Ok(Some(grant)) => match usable_allowance(&grant, granter) {
+                    Ok(_) => granter,
+                    Err(e) => {
+                        error!("{}", e);
+                        exit(1);
+                    }
+                },
+                Ok(None) => {
+                    error!(
+                        "{} has not granted a fee allowance to the orchestrator address {}",
+                        granter, grantee
+                    );
+                    error!(
+                        "Grant one with `gravity tx feegrant grant {} {}` or remove fee_granter from your config",
+                        granter, grantee
+                    );
+                    exit(1);
+                }
+                Err(e) => {
+                    error!("Failed to query the fee grant from {} {:?}", granter, e);
+                    exit(1);
+                }
+            }
+        }
+        None => {
+            let grants = match get_fee_allowances(contact, grantee).await {
+                Ok(g) => g,
+                Err(e) => {
+                    warn!(
+                        "Failed to query fee grants for {}, paying fees directly {:?}",
+                        grantee, e
+                    );
+                    return None;
+                }
+            };
+            let mut found = None;
+            for grant in grants {
+                let granter: CosmosAddress = match grant.granter.parse() {
+                    Ok(g) => g,
+                    Err(_) => continue,
+                };
+                match usable_allowance(&grant, granter) {
+                    Ok(_) => {
+                        found = Some(granter);
+                        break;
+                    }
+                    Err(e) => warn!("Not using fee grant: {}", e),
+                }
+            }
+            found?
+        }
+    };
+
+    info!(
+        "Paying orchestrator fees out of the fee grant from {}",
+        granter
+    );
+    set_fee_granter(Some(granter));
+    check_fee_grant(contact, grantee, fee, config.fee_grant_warning_txs).await;
+    Some(granter)
+}
+
+/// Checks that a grant can pay for every orchestrator message
+fn usable_allowance(grant: &Grant, granter: CosmosAddress) -> Result<(), String> {
+    let allowance = decode_allowance(grant).map_err(|e| {
+        format!(
+            "Failed to decode the fee allowance from {} {:?}",
+            granter, e
+        )
+    })?;
+    for msg in ORCHESTRATOR_MSG_TYPES {
+        if !allowance.allows(msg) {
+            return Err(format!(
+                "The fee allowance from {} does not allow {}, it must allow every orchestrator message",
+                granter, msg
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks what remains of the fee grant currently in use and warns if it is running low or
+/// about to expire
+pub async fn check_fee_grant(
+    contact: &Contact,
+    grantee: CosmosAddress,
+    fee: &Coin,
+    warning_txs: u64,
+) {
+    let granter = match get_fee_granter() {
+        Some(g) => g,
+        None => return,
+    };
+    let allowance = match get_fee_allowance(contact, granter, grantee).await {
+        Ok(Some(grant)) => match decode_allowance(&grant) {
+            Ok(a) => a,
+            Err(e) => {
+                warn!(
+                    "Failed to decode the fee allowance from {} {:?}",
+                    granter, e
+                );
+                return;
+            }
+        },
+        Ok(None) => {
+            error!(
+                "The fee grant from {} to {} has run out, expired or been revoked! Orchestrator transactions will fail until it is renewed",
+                granter, grantee
+            );
+            metrics_errors_counter(2, "Fee grant missing");
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to query the fee grant from {} {:?}", granter, e);
+            metrics_warnings_counter(1, "Could not query fee grant");
+            return;
+        }
+    };
+
+    if let Some(remaining) = allowance.remaining(&fee.denom) {
+        if fee.amount > 0u8.into() {
+            let txs_left = remaining.amount / fee.amount;
+            metrics_latest(
+                txs_left.to_u64().unwrap_or(u64::MAX),
+                "fee_grant_transactions_remaining",
+            );
+            if txs_left < Uint256::from(warning_txs) {
+                let reset = match allowance.period_reset {
+                    Some(_) => " in this period",
+                    None => "",
+                };
+                warn!(
+                    "The fee grant from {} has {} left{}, enough for {} more orchestrator transactions. Top it up soon",
+                    granter, remaining, reset, txs_left
+                );
+                metrics_warnings_counter(1, "Fee grant running low");
+            }
+        }
+    }
+    if let Some(expiration) = allowance.expiration {
+        let left = expiration
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+        if left < FEE_GRANT_EXPIRY_WARNING {
+            warn!(
+                "The fee grant from {} expires in {} hours, renew it soon",
+                granter,
+                left.as_secs() / 3600
+            );
+            metrics_warnings_counter(1, "Fee grant expiring");
+        }
+    }
+}
+
+/// Periodically checks the fee grant in use, if any
+pub async fn fee_grant_monitor_loop(
+    contact: Contact,
+    grantee: CosmosAddress,
+    fee: Coin,
+    warning_txs: u64,
+) {
+    loop {
+        delay_for(FEE_GRANT_CHECK_INTERVAL).await;
+        check_fee_grant(&contact, grantee, &fee, warning_txs).await;
+    }
+}
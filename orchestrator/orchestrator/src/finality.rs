@@ -0,0 +1,188 @@
+//! Finality assumptions for the EVM chains Gravity may be deployed against. The oracle only attests
+//! to events once the block containing them is considered safe from reorgs, what 'safe' means
+//! differs a great deal between Ethereum, rollups and sidechains. Each chain is described by a
+//! FinalityProfile, built in profiles cover well known chains and operators may add or replace
+//! profiles with [[orchestrator.finality_profiles]] entries in config.toml.
+
+use clarity::Uint256;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{FinalityMode, FinalityProfile};
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::cmp::min;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+lazy_static! {
+    static ref FINALITY_PROFILES: RwLock<Vec<FinalityProfile>> = RwLock::new(Vec::new());
+}
+
+/// The profile used for chains we know nothing about, see get_latest_safe_block for the reasoning
+/// behind 96 blocks
+const UNKNOWN_CHAIN_CONFIRMATIONS: u64 = 96;
+const ETHEREUM_BLOCK_TIME_MS: u64 = 12_000;
+const BLOCK_TAG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Set once we have warned that the node does not support a block tag, so the oracle loop does
+/// not repeat the warning every iteration
+static BLOCK_TAG_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the operator supplied finality profiles, these take precedence over the built in profile
+/// for the same chain id
+pub fn set_finality_profiles(profiles: Vec<FinalityProfile>) {
+    *FINALITY_PROFILES.write().unwrap() = profiles;
+}
+
+/// Returns the finality profile for the chain with the given net_version, operator supplied
+/// profiles first then the built in profiles, chains matching neither wait for 96 confirmations
+pub fn get_finality_profile(chain_id: u64) -> FinalityProfile {
+    if let Some(p) = FINALITY_PROFILES
+        .read()
+        .unwrap()
+        .iter()
+        .find(|p| p.chain_id == chain_id)
+    {
+        return p.clone();
+    }
+    builtin_finality_profile(chain_id).unwrap_or_else(|| {
+        profile(
+            chain_id,
+            "Unknown",
+            FinalityMode::Confirmations,
+            UNKNOWN_CHAIN_CONFIRMATIONS,
+            ETHEREUM_BLOCK_TIME_MS,
+        )
+    })
+}
+
+/// Roughly how long after an event is included in a block it will be considered final
+pub fn expected_finality_delay(profile: &FinalityProfile) -> Duration {
+    let blocks = match profile.mode {
+        // Ethereum proof of stake finalizes after two epochs of 32 slots, rollups finalize once
+        // the L1 block their batch was posted in does, so their own block time is irrelevant
+        FinalityMode::Finalized => return Duration::from_millis(64 * ETHEREUM_BLOCK_TIME_MS),
+        // the safe block trails head by roughly one epoch
+        FinalityMode::Safe => return Duration::from_millis(32 * ETHEREUM_BLOCK_TIME_MS),
+        FinalityMode::Confirmations => profile.confirmations,
+        FinalityMode::Instant => 0,
+    };
+    Duration::from_millis(blocks * profile.block_time_ms)
+}
+
+/// Gets the number of the block the node reports for `tag`, either "finalized" or "safe". Nodes
+/// from before the merge and many sidechain clients do not know these tags and return an error or
+/// a null block
+pub async fn get_tagged_block_number(web3: &Web3, tag: &str) -> Result<Uint256, GravityError> {
+    let client = HttpClient::new(&web3.get_url());
+    let block: Value = client
+        .request_method(
+            "eth_getBlockByNumber",
+            (tag.to_string(), false),
+            BLOCK_TAG_TIMEOUT,
+        )
+        .await?;
+    block["number"]
+        .as_str()
+        .and_then(|n| u64::from_str_radix(n.trim_start_matches("0x"), 16).ok())
+        .map(|n| n.into())
+        .ok_or_else(|| {
+            GravityError::EthereumContractError(format!("Node returned no {} block", tag))
+        })
+}
+
+/// Gets the block for `tag` if the node supports it, logging once when it does not so the operator
+/// knows the slower confirmation delay is being used instead
+pub async fn try_get_tagged_block_number(web3: &Web3, tag: &str) -> Option<Uint256> {
+    match get_tagged_block_number(web3, tag).await {
+        Ok(n) => Some(n),
+        Err(e) => {
+            if !BLOCK_TAG_WARNED.swap(true, Ordering::Relaxed) {
+                warn!(
+                    "Ethereum node does not support the '{}' block tag, falling back to a confirmation delay {:?}",
+                    tag, e
+                );
+            }
+            None
+        }
+    }
+}
+
+/// The conservative fallback when an external finality check (beacon node or light client) can not
+/// be completed, `safe_block` limited to 96 blocks behind the latest block, the same depth used for
+/// chains we know nothing about
+pub async fn depth_safe_block(web3: &Web3, safe_block: Uint256) -> Uint256 {
+    let confirmations: Uint256 = UNKNOWN_CHAIN_CONFIRMATIONS.into();
+    // safe_block is never past the latest block, so it is a conservative stand in if the node
+    // can not be reached
+    let latest = web3.eth_block_number().await.unwrap_or(safe_block);
+    // comparison only to prevent panic on underflow on very young chains
+    let depth_safe = if latest > confirmations {
+        latest - confirmations
+    } else {
+        0u8.into()
+    };
+    min(safe_block, depth_safe)
+}
+
+fn builtin_finality_profile(chain_id: u64) -> Option<FinalityProfile> {
+    use FinalityMode::*;
+    Some(match chain_id {
+        // Mainline Ethereum, Ethereum classic, or the Ropsten, Kotti, Mordor testnets
+        // all Ethereum proof of stake Chains
+        1 => profile(1, "Ethereum", Finalized, 0, ETHEREUM_BLOCK_TIME_MS),
+        3 | 6 | 7 => profile(
+            chain_id,
+            "Ethereum testnet",
+            Finalized,
+            0,
+            ETHEREUM_BLOCK_TIME_MS,
+        ),
+        11155111 => profile(chain_id, "Sepolia", Finalized, 0, ETHEREUM_BLOCK_TIME_MS),
+        17000 => profile(chain_id, "Holesky", Finalized, 0, ETHEREUM_BLOCK_TIME_MS),
+        // Dev, our own Gravity Ethereum testnet, and Hardhat respectively
+        // all single signer chains with no chance of any reorgs
+        2018 | 15 | 31337 => profile(chain_id, "Development", Instant, 0, 1_000),
+        // Rinkeby and Goerli use Clique (POA) Consensus, finality takes
+        // up to num validators blocks. Number is higher than Ethereum based
+        // on experience with operational issues
+        4 => profile(chain_id, "Rinkeby", Confirmations, 10, 15_000),
+        5 => profile(
+            chain_id,
+            "Goerli",
+            Confirmations,
+            10,
+            ETHEREUM_BLOCK_TIME_MS,
+        ),
+        // Optimistic rollups, their nodes report an L2 block as finalized once the batch containing
+        // it is finalized on Ethereum
+        10 => profile(chain_id, "Optimism", Finalized, 0, 2_000),
+        8453 => profile(chain_id, "Base", Finalized, 0, 2_000),
+        42161 => profile(chain_id, "Arbitrum One", Finalized, 0, 250),
+        // BSC fast finality (BEP-126) gives a finalized block within a few blocks
+        56 => profile(chain_id, "BNB Smart Chain", Finalized, 0, 3_000),
+        // Gnosis chain runs the Ethereum beacon chain consensus
+        100 => profile(chain_id, "Gnosis", Finalized, 0, 5_000),
+        // Polygon PoS has seen reorgs well over 100 blocks deep, stay conservative
+        137 => profile(chain_id, "Polygon", Confirmations, 256, 2_000),
+        _ => return None,
+    })
+}
+
+fn profile(
+    chain_id: u64,
+    name: &str,
+    mode: FinalityMode,
+    confirmations: u64,
+    block_time_ms: u64,
+) -> FinalityProfile {
+    FinalityProfile {
+        chain_id,
+        name: name.to_string(),
+        mode,
+        confirmations,
+        block_time_ms,
+    }
+}
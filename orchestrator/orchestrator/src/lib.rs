@@ -1,6 +1,20 @@
 #[macro_use]
 extern crate log;
 
+pub mod beacon;
+pub mod checkpoint_check;
+pub mod claim_budget;
+pub mod claim_watchdog;
+pub mod confirm_progress;
+pub mod deposit_webhook;
 pub mod ethereum_event_watcher;
+pub mod fee_grant;
+pub mod finality;
+pub mod light_client;
+pub mod logic_call_policy;
 pub mod main_loop;
+pub mod node_health;
 pub mod oracle_resync;
+pub mod params_watch;
+pub mod signer_schedule;
+pub mod work_priority;
@@ -0,0 +1,217 @@
+//! Optional cross-checking of the Ethereum node against a local light client such as Helios. A
+//! light client verifies block headers against the beacon chain's sync committee rather than
+//! trusting any one RPC provider, but can't serve logs or historic state. The full node is still
+//! used for everything else. The oracle checks that the full node agrees with the light client on
+//! the hash of the light client's finalized block and never attests to events past it, and that
+//! every log it attests to is in a block the light client has the same hash for.
+
+use crate::finality::depth_safe_block;
+use clarity::utils::bytes_to_hex_str;
+use clarity::Uint256;
+use gravity_utils::error::GravityError;
+use lazy_static::lazy_static;
+use metrics_exporter::metrics_errors_counter;
+use serde_json::Value;
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+use web30::types::Log;
+
+lazy_static! {
+    static ref LIGHT_CLIENT_RPC: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// Where Helios serves its RPC by default
+pub const DEFAULT_LIGHT_CLIENT_RPC: &str = "http://127.0.0.1:8545";
+/// Substrings of web3_clientVersion identifying a light client
+const LIGHT_CLIENT_VERSIONS: &[&str] = &["helios"];
+const LIGHT_CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+const RETRY_TIME: Duration = Duration::from_secs(5);
+/// How many times finality is checked against the light client before falling back
+const LIGHT_CLIENT_ATTEMPTS: u32 = 5;
+
+/// Sets the light client RPC url used to verify blocks, None disables verification
+pub fn set_light_client_rpc(url: Option<String>) {
+    *LIGHT_CLIENT_RPC.write().unwrap() = url;
+}
+
+/// True if a web3_clientVersion response is from a light client
+pub fn is_light_client_version(version: &str) -> bool {
+    let version = version.to_lowercase();
+    LIGHT_CLIENT_VERSIONS.iter().any(|v| version.contains(v))
+}
+
+/// Checks for a light client at `url`, returning it if one answers. Full nodes listen on the same
+/// default port, so anything not identifying itself as a light client is ignored
+pub async fn detect_light_client(url: &str) -> Option<String> {
+    let client = HttpClient::new(url);
+    let version: Result<String, _> = client
+        .request_method(
+            "web3_clientVersion",
+            Vec::<String>::new(),
+            LIGHT_CLIENT_TIMEOUT,
+        )
+        .await;
+    match version {
+        Ok(v) if is_light_client_version(&v) => {
+            info!("Found light client {} at {}", v, url);
+            Some(url.to_string())
+        }
+        Ok(v) => {
+            debug!("{} at {} is not a light client", v, url);
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// The number and hash of a block by number or tag
+async fn get_block(url: &str, block: &str) -> Result<(Uint256, String), GravityError> {
+    let client = HttpClient::new(url);
+    let res: Value = client
+        .request_method(
+            "eth_getBlockByNumber",
+            (block.to_string(), false),
+            LIGHT_CLIENT_TIMEOUT,
+        )
+        .await?;
+    let number = res["number"]
+        .as_str()
+        .and_then(|n| u64::from_str_radix(n.trim_start_matches("0x"), 16).ok());
+    match (number, res["hash"].as_str()) {
+        (Some(number), Some(hash)) => Ok((number.into(), hash.to_lowercase())),
+        _ => Err(GravityError::EthereumContractError(format!(
+            "{} returned no block {}",
+            url, block
+        ))),
+    }
+}
+
+/// Limits `safe_block` to the light client's finalized block, if a light client is in use.
+/// Retries while the light client is unreachable or the Ethereum node disagrees with it on the hash
+/// of that block, after LIGHT_CLIENT_ATTEMPTS the depth based safe block is used instead
+pub async fn limit_to_light_client_finalized(web3: &Web3, safe_block: Uint256) -> Uint256 {
+    let url = match LIGHT_CLIENT_RPC.read().unwrap().clone() {
+        Some(url) => url,
+        None => return safe_block,
+    };
+    for _ in 0..LIGHT_CLIENT_ATTEMPTS {
+        let (number, hash) = match get_block(&url, "finalized").await {
+            Ok(b) => b,
+            Err(e) => {
+                error!("Failed to get finalized block from light client {:?}", e);
+                metrics_errors_counter(1, "Failed to get finalized block from light client");
+                delay_for(RETRY_TIME).await;
+                continue;
+            }
+        };
+        match get_block(&web3.get_url(), &format!("{:#x}", number)).await {
+            Ok((_, node_hash)) if node_hash == hash => return min(safe_block, number),
+            Ok((_, node_hash)) => {
+                error!(
+                    "Light client finalized block {} with hash {} but the Ethereum node has hash {}",
+                    number, hash, node_hash
+                );
+                metrics_errors_counter(1, "Light client and Ethereum node disagree on block hash");
+            }
+            Err(e) => error!(
+                "Failed to get light client finalized block {} from Ethereum node {:?}",
+                number, e
+            ),
+        }
+        delay_for(RETRY_TIME).await;
+    }
+    warn!(
+        "Could not verify finality against the light client after {} attempts, falling back to depth based safety",
+        LIGHT_CLIENT_ATTEMPTS
+    );
+    metrics_errors_counter(1, "Light client finality check fell back to depth");
+    depth_safe_block(web3, safe_block).await
+}
+
+/// The distinct blocks `logs` were emitted in by number, errors if a log has no block or two logs
+/// claim different hashes for the same block number
+fn log_blocks(logs: &[&[Log]]) -> Result<BTreeMap<Uint256, String>, GravityError> {
+    let mut blocks = BTreeMap::new();
+    for log in logs.iter().flat_map(|l| l.iter()) {
+        let (number, hash) = match (log.block_number, log.block_hash.as_deref()) {
+            (Some(number), Some(hash)) => (number, format!("0x{}", bytes_to_hex_str(hash))),
+            _ => {
+                return Err(GravityError::InvalidEventLogError(format!(
+                    "Log without a block {:?}",
+                    log
+                )))
+            }
+        };
+        if let Some(existing) = blocks.insert(number, hash.clone()) {
+            if existing != hash {
+                return Err(GravityError::InvalidEventLogError(format!(
+                    "Logs in block {} have different block hashes {} and {}",
+                    number, existing, hash
+                )));
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// Checks that the light client has the same hash for every block `logs` were emitted in, if a
+/// light client is in use. Errors if it can not be reached or disagrees, the oracle then retries
+/// the same blocks on its next loop instead of attesting to events the light client can't confirm
+pub async fn verify_logs_with_light_client(logs: &[&[Log]]) -> Result<(), GravityError> {
+    let url = match LIGHT_CLIENT_RPC.read().unwrap().clone() {
+        Some(url) => url,
+        None => return Ok(()),
+    };
+    for (number, hash) in log_blocks(logs)? {
+        let (_, light_client_hash) = get_block(&url, &format!("{:#x}", number)).await?;
+        if light_client_hash != hash {
+            metrics_errors_counter(1, "Light client and Ethereum node disagree on block hash");
+            return Err(GravityError::InvalidEventLogError(format!(
+                "Ethereum node returned logs in block {} with hash {} but the light client has hash {}",
+                number, hash, light_client_hash
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web30::types::Data;
+
+    #[test]
+    fn test_is_light_client_version() {
+        assert!(is_light_client_version("helios-0.5.5"));
+        assert!(is_light_client_version("Helios/v0.7.0"));
+        assert!(!is_light_client_version(
+            "Geth/v1.13.14-stable/linux-amd64/go1.21.7"
+        ));
+        assert!(!is_light_client_version(
+            "erigon/2.58.1/linux-amd64/go1.21.5"
+        ));
+    }
+
+    #[test]
+    fn test_log_blocks() {
+        let log = |number: u8, hash: u8| Log {
+            block_number: Some(number.into()),
+            block_hash: Some(Data(vec![hash; 32])),
+            ..Default::default()
+        };
+        let deposits = vec![log(1, 0xaa), log(2, 0xbb)];
+        let batches = vec![log(2, 0xbb)];
+        let blocks = log_blocks(&[&deposits, &batches]).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[&2u8.into()], format!("0x{}", "bb".repeat(32)));
+
+        let reorged = vec![log(2, 0xcc)];
+        assert!(log_blocks(&[&deposits, &reorged]).is_err());
+        assert!(log_blocks(&[&[Log::default()]]).is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! Inspection of logic calls against the operator's LogicCallPolicy before the signer confirms
+//! them. A logic call hands tokens to an arbitrary contract and executes an arbitrary payload
+//! there, so unlike valsets and batches a valid checkpoint says nothing about whether signing is
+//! safe. Calls violating the policy are not signed, an error is logged once per call with the
+//! decoded call so the operator can review it and sign it with `gbt orchestrator sign-logic-call`
+//! if it is legitimate
+
+use clarity::utils::{bytes_to_hex_str, hex_str_to_bytes};
+use clarity::{Address as EthAddress, Uint256};
+use gravity_utils::types::{LogicCall, LogicCallPolicy};
+use metrics_exporter::metrics_errors_counter;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// A logic call decoded into the parts the policy is evaluated against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogicCallInspection {
+    pub target: EthAddress,
+    /// The function selector of the payload, None if the payload is too short to have one
+    pub selector: Option<[u8; 4]>,
+    /// The 32 byte words of the abi encoded arguments following the selector, the last word is
+    /// zero padded if the payload does not end on a word boundary
+    pub arguments: Vec<Vec<u8>>,
+    /// The total of each token moved, transfers and fees combined
+    pub totals: BTreeMap<EthAddress, Uint256>,
+}
+
+impl LogicCallInspection {
+    pub fn new(call: &LogicCall) -> Self {
+        let selector = if call.payload.len() >= 4 {
+            let mut selector = [0u8; 4];
+            selector.copy_from_slice(&call.payload[0..4]);
+            Some(selector)
+        } else {
+            None
+        };
+        let arguments = if call.payload.len() > 4 {
+            call.payload[4..]
+                .chunks(32)
+                .map(|word| {
+                    let mut word = word.to_vec();
+                    word.resize(32, 0);
+                    word
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut totals: BTreeMap<EthAddress, Uint256> = BTreeMap::new();
+        for token in call.transfers.iter().chain(call.fees.iter()) {
+            *totals
+                .entry(token.token_contract_address)
+                .or_insert_with(|| 0u8.into()) += token.amount;
+        }
+        LogicCallInspection {
+            target: call.logic_contract_address,
+            selector,
+            arguments,
+            totals,
+        }
+    }
+}
+
+impl fmt::Display for LogicCallInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.selector {
+            Some(selector) => write!(
+                f,
+                "calls 0x{} on {}",
+                bytes_to_hex_str(&selector),
+                self.target
+            )?,
+            None => write!(f, "calls {} without a function selector", self.target)?,
+        }
+        for (i, word) in self.arguments.iter().enumerate() {
+            write!(f, "\n    argument word {}: 0x{}", i, bytes_to_hex_str(word))?;
+        }
+        for (token, amount) in self.totals.iter() {
+            write!(f, "\n    moves {} of {}", amount, token)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    TargetNotAllowed(EthAddress),
+    SelectorNotAllowed(Option<[u8; 4]>),
+    InvalidationIdNotAllowed(Vec<u8>),
+    AmountExceeded {
+        token: EthAddress,
+        amount: Uint256,
+        max: Uint256,
+    },
+    TokenNotListed(EthAddress),
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::TargetNotAllowed(target) => {
+                write!(f, "target {} is not an allowed target", target)
+            }
+            PolicyViolation::SelectorNotAllowed(Some(selector)) => write!(
+                f,
+                "function 0x{} is not an allowed selector",
+                bytes_to_hex_str(selector)
+            ),
+            PolicyViolation::SelectorNotAllowed(None) => {
+                write!(f, "payload has no function selector")
+            }
+            PolicyViolation::InvalidationIdNotAllowed(id) => write!(
+                f,
+                "invalidation id 0x{} is not allowed",
+                bytes_to_hex_str(id)
+            ),
+            PolicyViolation::AmountExceeded { token, amount, max } => write!(
+                f,
+                "moves {} of {} which is more than the maximum of {}",
+                amount, token, max
+            ),
+            PolicyViolation::TokenNotListed(token) => {
+                write!(f, "moves {} which has no maximum amount", token)
+            }
+        }
+    }
+}
+
+/// A LogicCallPolicy parsed for evaluation, along with the calls already alerted on
+#[derive(Debug, Clone, Default)]
+pub struct LogicCallPolicyChecker {
+    allowed_targets: Option<HashSet<EthAddress>>,
+    allowed_selectors: Option<HashSet<[u8; 4]>>,
+    allowed_invalidation_ids: Option<HashSet<Vec<u8>>>,
+    max_amounts: HashMap<EthAddress, Uint256>,
+    deny_unlisted_tokens: bool,
+    alerted: HashSet<(Vec<u8>, u64)>,
+}
+
+impl LogicCallPolicyChecker {
+    pub fn new(policy: &LogicCallPolicy) -> Result<Self, String> {
+        let allowed_selectors = match &policy.allowed_selectors {
+            Some(list) => {
+                let mut selectors = HashSet::new();
+                for s in list {
+                    match hex_str_to_bytes(s) {
+                        Ok(b) if b.len() == 4 => {
+                            let mut selector = [0u8; 4];
+                            selector.copy_from_slice(&b);
+                            selectors.insert(selector);
+                        }
+                        _ => return Err(format!("Invalid logic call policy selector {}", s)),
+                    }
+                }
+                Some(selectors)
+            }
+            None => None,
+        };
+        let allowed_invalidation_ids = match &policy.allowed_invalidation_ids {
+            Some(list) => {
+                let mut ids = HashSet::new();
+                for id in list {
+                    match hex_str_to_bytes(id) {
+                        Ok(b) => {
+                            ids.insert(b);
+                        }
+                        Err(e) => {
+                            return Err(format!(
+                                "Invalid logic call policy invalidation id {} {:?}",
+                                id, e
+                            ))
+                        }
+                    }
+                }
+                Some(ids)
+            }
+            None => None,
+        };
+        let mut max_amounts = HashMap::new();
+        for limit in policy.max_amounts.iter() {
+            if max_amounts.insert(limit.token, limit.amount).is_some() {
+                return Err(format!(
+                    "Logic call policy lists {} more than once",
+                    limit.token
+                ));
+            }
+        }
+        Ok(LogicCallPolicyChecker {
+            allowed_targets: policy
+                .allowed_targets
+                .as_ref()
+                .map(|t| t.iter().cloned().collect()),
+            allowed_selectors,
+            allowed_invalidation_ids,
+            max_amounts,
+            deny_unlisted_tokens: policy.deny_unlisted_tokens,
+            alerted: HashSet::new(),
+        })
+    }
+
+    /// Every way `call` violates the policy, empty if it may be signed
+    pub fn check(&self, call: &LogicCall) -> Vec<PolicyViolation> {
+        let inspection = LogicCallInspection::new(call);
+        let mut out = Vec::new();
+        if let Some(targets) = &self.allowed_targets {
+            if !targets.contains(&inspection.target) {
+                out.push(PolicyViolation::TargetNotAllowed(inspection.target));
+            }
+        }
+        if let Some(selectors) = &self.allowed_selectors {
+            let allowed = inspection
+                .selector
+                .map(|s| selectors.contains(&s))
+                .unwrap_or(false);
+            if !allowed {
+                out.push(PolicyViolation::SelectorNotAllowed(inspection.selector));
+            }
+        }
+        if let Some(ids) = &self.allowed_invalidation_ids {
+            if !ids.contains(&call.invalidation_id) {
+                out.push(PolicyViolation::InvalidationIdNotAllowed(
+                    call.invalidation_id.clone(),
+                ));
+            }
+        }
+        for (token, amount) in inspection.totals {
+            match self.max_amounts.get(&token) {
+                Some(max) if amount > *max => out.push(PolicyViolation::AmountExceeded {
+                    token,
+                    amount,
+                    max: *max,
+                }),
+                Some(_) => {}
+                None if self.deny_unlisted_tokens => {
+                    out.push(PolicyViolation::TokenNotListed(token))
+                }
+                None => {}
+            }
+        }
+        out
+    }
+
+    /// Returns the calls that may be signed, alerting the operator the first time each violating
+    /// call is seen
+    pub fn filter_logic_calls(&mut self, calls: Vec<LogicCall>) -> Vec<LogicCall> {
+        let mut out = Vec::new();
+        for call in calls {
+            let violations = self.check(&call);
+            if violations.is_empty() {
+                out.push(call);
+                continue;
+            }
+            let key = (call.invalidation_id.clone(), call.invalidation_nonce);
+            if self.alerted.insert(key) {
+                error!(
+                    "Refusing to sign logic call, it violates your logic_call_policy: {}\n{}\n    {}",
+                    violations
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    call.checkpoint_summary(),
+                    LogicCallInspection::new(&call)
+                );
+                error!(
+                    "Review this call and if it is legitimate sign it with `gbt orchestrator sign-logic-call --invalidation-id 0x{} --invalidation-nonce {} --fees <fee>`, you will be slashed if it is not signed before the signed_logic_calls_window",
+                    bytes_to_hex_str(&call.invalidation_id),
+                    call.invalidation_nonce
+                );
+                metrics_errors_counter(2, "Logic call violates logic_call_policy");
+            } else {
+                trace!(
+                    "Still refusing to sign logic call 0x{}/{}",
+                    bytes_to_hex_str(&call.invalidation_id),
+                    call.invalidation_nonce
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity_utils::types::{Erc20Token, LogicCallTokenLimit};
+
+    fn address(i: u8) -> EthAddress {
+        EthAddress::from_slice(&[i; 20]).unwrap()
+    }
+
+    fn call() -> LogicCall {
+        let mut payload = vec![0xa9, 0x05, 0x9c, 0xbb];
+        payload.extend_from_slice(&[0u8; 12]);
+        payload.extend_from_slice(&[7u8; 20]);
+        payload.extend_from_slice(&[1u8; 8]);
+        LogicCall {
+            transfers: vec![Erc20Token {
+                amount: 100u32.into(),
+                token_contract_address: address(1),
+            }],
+            fees: vec![
+                Erc20Token {
+                    amount: 5u32.into(),
+                    token_contract_address: address(1),
+                },
+                Erc20Token {
+                    amount: 3u32.into(),
+                    token_contract_address: address(2),
+                },
+            ],
+            logic_contract_address: address(9),
+            payload,
+            timeout: 1000,
+            invalidation_id: vec![0xab; 32],
+            invalidation_nonce: 1,
+        }
+    }
+
+    #[test]
+    fn test_inspect_logic_call() {
+        let inspection = LogicCallInspection::new(&call());
+        assert_eq!(inspection.target, address(9));
+        assert_eq!(inspection.selector, Some([0xa9, 0x05, 0x9c, 0xbb]));
+        assert_eq!(inspection.arguments.len(), 2);
+        assert_eq!(inspection.arguments[0][12..], [7u8; 20]);
+        assert_eq!(inspection.arguments[1][0..8], [1u8; 8]);
+        assert_eq!(inspection.arguments[1][8..], [0u8; 24]);
+        assert_eq!(inspection.totals[&address(1)], 105u32.into());
+        assert_eq!(inspection.totals[&address(2)], 3u32.into());
+    }
+
+    #[test]
+    fn test_logic_call_policy() {
+        let call = call();
+        let mut policy = LogicCallPolicy::default();
+        let checker = LogicCallPolicyChecker::new(&policy).unwrap();
+        assert!(checker.check(&call).is_empty());
+
+        policy.allowed_targets = Some(vec![address(9)]);
+        policy.allowed_selectors = Some(vec!["0xa9059cbb".to_string()]);
+        policy.allowed_invalidation_ids = Some(vec![format!("0x{}", "ab".repeat(32))]);
+        policy.max_amounts = vec![LogicCallTokenLimit {
+            token: address(1),
+            amount: 105u32.into(),
+        }];
+        let checker = LogicCallPolicyChecker::new(&policy).unwrap();
+        assert!(checker.check(&call).is_empty());
+
+        policy.deny_unlisted_tokens = true;
+        policy.max_amounts[0].amount = 104u32.into();
+        policy.allowed_targets = Some(vec![address(8)]);
+        policy.allowed_selectors = Some(vec!["0x095ea7b3".to_string()]);
+        policy.allowed_invalidation_ids = Some(vec!["0x01".to_string()]);
+        let mut checker = LogicCallPolicyChecker::new(&policy).unwrap();
+        assert_eq!(
+            checker.check(&call),
+            vec![
+                PolicyViolation::TargetNotAllowed(address(9)),
+                PolicyViolation::SelectorNotAllowed(Some([0xa9, 0x05, 0x9c, 0xbb])),
+                PolicyViolation::InvalidationIdNotAllowed(vec![0xab; 32]),
+                PolicyViolation::AmountExceeded {
+                    token: address(1),
+                    amount: 105u32.into(),
+                    max: 104u32.into(),
+                },
+                PolicyViolation::TokenNotListed(address(2)),
+            ]
+        );
+        assert!(checker.filter_logic_calls(vec![call]).is_empty());
+
+        policy.allowed_selectors = Some(vec!["0xa9059c".to_string()]);
+        assert!(LogicCallPolicyChecker::new(&policy).is_err());
+    }
+}
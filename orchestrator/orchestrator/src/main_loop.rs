@@ -2,14 +2,32 @@
 //! that can only be run by a validator. This single binary the 'Orchestrator' runs not only these two rules but also the untrusted role of a relayer, that does not need any permissions and has it's
 //! own crate and binary so that anyone may run it.
 
+use crate::beacon::set_beacon_api_url;
+use crate::checkpoint_check::{
+    check_batch_checkpoints, check_logic_call_checkpoints, check_valset_checkpoints,
+};
+use crate::claim_budget::ClaimFeeBudget;
+use crate::claim_watchdog::{claim_watchdog_loop, record_oracle_error};
+use crate::confirm_progress::confirm_progress_loop;
+use crate::deposit_webhook::{deposit_webhook_loop, set_deposit_webhook};
+use crate::fee_grant::fee_grant_monitor_loop;
+use crate::finality::{expected_finality_delay, get_finality_profile, set_finality_profiles};
+use crate::light_client::{detect_light_client, set_light_client_rpc, DEFAULT_LIGHT_CLIENT_RPC};
+use crate::logic_call_policy::LogicCallPolicyChecker;
+use crate::node_health::node_health_loop;
+use crate::params_watch::params_watch_loop;
+use crate::signer_schedule::{wait_for_new_block, SignerSchedule, SignerWork, SignerWorkKind};
+use crate::work_priority::{prioritize, PrioritizedWork, MAX_CONFIRMS_PER_BLOCK};
 use crate::{ethereum_event_watcher::check_for_events, oracle_resync::get_last_checked_block};
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::{address::Address as EthAddress, Uint256};
-use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::query_cache::get_gravity_params_cached;
+use cosmos_gravity::redundancy::set_redundancy;
 use cosmos_gravity::{
     query::{
-        get_oldest_unsigned_logic_calls, get_oldest_unsigned_transaction_batches,
-        get_oldest_unsigned_valsets,
+        get_oldest_unsigned_logic_calls_with_height,
+        get_oldest_unsigned_transaction_batches_with_height,
+        get_oldest_unsigned_valsets_with_height,
     },
     send::{send_batch_confirm, send_logic_call_confirm, send_valset_confirms},
     utils::get_last_event_nonce_with_retry,
@@ -21,15 +39,25 @@ use deep_space::{
     coin::Coin,
     private_key::{CosmosPrivateKey, PrivateKey},
 };
-use futures::future::{join, join3};
+use ethereum_gravity::signer::EthereumSigner;
+use futures::future::{join, join3, join4, join5};
 use gravity_proto::cosmos_sdk_proto::cosmos::base::abci::v1beta1::TxResponse;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
-use gravity_utils::types::GravityBridgeToolsConfig;
-use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
+use gravity_utils::debug_state::{
+    set_oracle_state, set_unsigned_batches, set_unsigned_logic_calls, set_unsigned_valsets,
+};
+use gravity_utils::get_with_retry::get_net_version_with_retry;
+use gravity_utils::log_context::in_loop;
+use gravity_utils::types::{FinalityMode, GravityBridgeToolsConfig};
+use gravity_utils::watch_only::{get_watched_address, orchestrator_address};
+use metrics_exporter::{
+    metrics_errors_counter, metrics_eth_rpc_error, metrics_latest, metrics_warnings_counter,
+};
 use num_traits::ToPrimitive;
 use relayer::main_loop::all_relayer_loops;
 use std::cmp::min;
 use std::process::exit;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use tokio::time::sleep as delay_for;
@@ -38,7 +66,8 @@ use web30::client::Web3;
 
 /// The execution speed governing all loops in this file
 /// which is to say all loops started by Orchestrator main
-/// loop except the relayer loop
+/// loop except the relayer loop. The signer runs every new
+/// Cosmos block and falls back to this speed if the chain stalls
 pub const ETH_SIGNER_LOOP_SPEED: Duration = Duration::from_secs(11);
 pub const ETH_ORACLE_LOOP_SPEED: Duration = Duration::from_secs(13);
 /// Run the oracle loop slower while waiting for the merge
@@ -53,6 +82,7 @@ pub const ETH_ORACLE_WAITING_SPEED: Duration = Duration::from_secs(90);
 pub async fn orchestrator_main_loop(
     cosmos_key: CosmosPrivateKey,
     ethereum_key: EthPrivateKey,
+    eth_signer: Arc<dyn EthereumSigner>,
     web3: Web3,
     contact: Contact,
     grpc_client: GravityQueryClient<Channel>,
@@ -63,24 +93,79 @@ pub async fn orchestrator_main_loop(
 ) {
     let fee = user_fee_amount;
 
-    if config.orchestrator.check_eth_rpc {
+    set_finality_profiles(config.orchestrator.finality_profiles.clone());
+    if let Some(url) = &config.orchestrator.beacon_api_url {
+        info!("Verifying Ethereum finality with beacon node {}", url);
+    }
+    set_beacon_api_url(config.orchestrator.beacon_api_url.clone());
+    let light_client_rpc = match &config.orchestrator.light_client_rpc {
+        Some(url) => Some(url.clone()),
+        None if config.orchestrator.detect_light_client
+            && web3.get_url().trim_end_matches('/') != DEFAULT_LIGHT_CLIENT_RPC =>
+        {
+            detect_light_client(DEFAULT_LIGHT_CLIENT_RPC).await
+        }
+        None => None,
+    };
+    if let Some(url) = &light_client_rpc {
+        info!("Verifying Ethereum blocks with light client {}", url);
+    }
+    set_light_client_rpc(light_client_rpc);
+    if config.orchestrator.redundancy.enabled {
+        info!("Skipping claims and confirms already submitted by a redundant orchestrator");
+    }
+    set_redundancy(&config.orchestrator.redundancy);
+    if let Err(e) = set_deposit_webhook(&config.orchestrator.deposit_webhook) {
+        error!("Invalid deposit_webhook config: {}", e);
+        exit(1);
+    }
+    let logic_call_policy =
+        match LogicCallPolicyChecker::new(&config.orchestrator.logic_call_policy) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Invalid logic_call_policy config: {}", e);
+                exit(1);
+            }
+        };
+    let finality = get_finality_profile(get_net_version_with_retry(&web3).await);
+    info!(
+        "Using {} finality profile for chain {}: {:?} with {} confirmations, events will be attested roughly {} seconds after inclusion",
+        finality.name,
+        finality.chain_id,
+        finality.mode,
+        finality.confirmations,
+        expected_finality_delay(&finality).as_secs()
+    );
+
+    // only chains which rely on the node's finalized block need it to be sane
+    if config.orchestrator.check_eth_rpc && finality.mode == FinalityMode::Finalized {
         test_eth_connection(web3.clone()).await;
     }
 
-    let a = eth_oracle_main_loop(
-        cosmos_key,
-        web3.clone(),
-        contact.clone(),
-        grpc_client.clone(),
-        gravity_contract_address,
-        fee.clone(),
+    let a = in_loop(
+        "oracle",
+        eth_oracle_main_loop(
+            cosmos_key,
+            web3.clone(),
+            contact.clone(),
+            grpc_client.clone(),
+            gravity_contract_address,
+            fee.clone(),
+            config.orchestrator.daily_claim_fee_budget,
+        ),
     );
-    let b = eth_signer_main_loop(
-        cosmos_key,
-        ethereum_key,
-        contact.clone(),
-        grpc_client.clone(),
-        fee.clone(),
+    let b = in_loop(
+        "signer",
+        eth_signer_main_loop(
+            cosmos_key,
+            eth_signer,
+            contact.clone(),
+            grpc_client.clone(),
+            web3.clone(),
+            fee.clone(),
+            config.orchestrator.signer_target_blocks,
+            logic_call_policy,
+        ),
     );
     let c = all_relayer_loops(
         Some(cosmos_key),
@@ -94,11 +179,56 @@ pub async fn orchestrator_main_loop(
         config.relayer,
     );
 
+    let d = in_loop(
+        "fee_grant_monitor",
+        fee_grant_monitor_loop(
+            contact.clone(),
+            cosmos_key.to_address(&contact.get_prefix()).unwrap(),
+            fee.clone(),
+            config.orchestrator.fee_grant_warning_txs,
+        ),
+    );
+
+    let e = in_loop("deposit_webhook", deposit_webhook_loop(grpc_client.clone()));
+
+    let g = in_loop(
+        "node_health",
+        node_health_loop(
+            contact.clone(),
+            web3.clone(),
+            config.orchestrator.node_health.clone(),
+        ),
+    );
+
+    let f = in_loop(
+        "claim_watchdog",
+        claim_watchdog_loop(
+            contact.clone(),
+            grpc_client.clone(),
+            web3.clone(),
+            orchestrator_address(&cosmos_key, &contact.get_prefix()),
+            fee.clone(),
+            config.orchestrator.claim_watchdog_minutes,
+        ),
+    );
+
+    let h = in_loop("params_watch", params_watch_loop(grpc_client.clone()));
+
+    let i = in_loop(
+        "confirm_progress",
+        confirm_progress_loop(
+            grpc_client.clone(),
+            web3.clone(),
+            gravity_contract_address,
+            config.orchestrator.confirm_alert_minutes,
+        ),
+    );
+
     // if the relayer is not enabled we just don't start the relayer_main_loop or ibc_auto_forward_loop futures
     if config.orchestrator.relayer_enabled {
-        join3(a, b, c).await;
+        join5(join5(a, b, c, d, e), f, g, h, i).await;
     } else {
-        join(a, b).await;
+        join5(join4(a, b, d, e), f, g, h, i).await;
     }
 }
 
@@ -148,8 +278,10 @@ pub async fn eth_oracle_main_loop(
     grpc_client: GravityQueryClient<Channel>,
     gravity_contract_address: EthAddress,
     fee: Coin,
+    daily_claim_fee_budget: Option<Uint256>,
 ) {
-    let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    let our_cosmos_address = orchestrator_address(&cosmos_key, &contact.get_prefix());
+    let mut budget = ClaimFeeBudget::new(daily_claim_fee_budget);
     let long_timeout_web30 = Web3::new(&web3.get_url(), Duration::from_secs(120));
     let mut last_checked_block: Uint256 = get_last_checked_block(
         grpc_client.clone(),
@@ -205,6 +337,7 @@ pub async fn eth_oracle_main_loop(
             (Err(_), Ok(_)) => {
                 warn!("Could not contact Eth node, trying again");
                 metrics_warnings_counter(1, "Could not contact Eth node");
+                metrics_eth_rpc_error("latest_block");
                 delay_for(DELAY).await;
                 continue;
             }
@@ -212,6 +345,7 @@ pub async fn eth_oracle_main_loop(
                 error!("Could not reach Ethereum or Cosmos rpc!");
 
                 metrics_errors_counter(0, "Could not reach Ethereum or Cosmos rpc");
+                metrics_eth_rpc_error("latest_block");
 
                 delay_for(DELAY).await;
                 continue;
@@ -229,7 +363,9 @@ pub async fn eth_oracle_main_loop(
         .await
         .into();
 
-        if last_event_nonce < last_checked_event {
+        // in watch only mode the watched orchestrator normally lags behind us, so this can't be
+        // detected
+        if last_event_nonce < last_checked_event && get_watched_address().is_none() {
             // validator went back in history
             info!("Governance unhalt vote must have happened, resetting the block to check!");
             last_checked_event = last_event_nonce;
@@ -252,6 +388,7 @@ pub async fn eth_oracle_main_loop(
             cosmos_key,
             fee.clone(),
             last_checked_block,
+            &mut budget,
         )
         .await
         {
@@ -264,6 +401,7 @@ pub async fn eth_oracle_main_loop(
                 if nonces.event_nonce > last_checked_event {
                     last_checked_event = nonces.event_nonce;
                 }
+                set_oracle_state(last_checked_block, last_checked_event);
                 metrics_latest(
                     last_checked_event.to_string().parse().unwrap(),
                     "last_checked_event",
@@ -272,6 +410,7 @@ pub async fn eth_oracle_main_loop(
             Err(e) => {
                 error!("Failed to get events for block range, Check your Eth node and Cosmos gRPC {:?}", e);
                 metrics_errors_counter(0, "Failed to get events for block range");
+                record_oracle_error(format!("{:?}", e));
             }
         }
 
@@ -287,24 +426,29 @@ pub async fn eth_oracle_main_loop(
 
 /// The eth_signer simply signs off on any batches or validator sets provided by the validator
 /// since these are provided directly by a trusted Cosmsos node they can simply be assumed to be
-/// valid and signed off on.
+/// valid and signed off on. It runs once per Cosmos block so new work is signed in the block after
+/// it is created, warning about anything still unsigned after `target_blocks`. Logic calls are
+/// only signed if they pass `logic_call_policy`
+#[allow(clippy::too_many_arguments)]
 pub async fn eth_signer_main_loop(
     cosmos_key: CosmosPrivateKey,
-    ethereum_key: EthPrivateKey,
+    eth_signer: Arc<dyn EthereumSigner>,
     contact: Contact,
     grpc_client: GravityQueryClient<Channel>,
+    web3: Web3,
     fee: Coin,
+    target_blocks: u64,
+    mut logic_call_policy: LogicCallPolicyChecker,
 ) {
-    let our_cosmos_address = cosmos_key.to_address(&contact.get_prefix()).unwrap();
+    let our_cosmos_address = orchestrator_address(&cosmos_key, &contact.get_prefix());
     let mut grpc_client = grpc_client;
+    let mut schedule = SignerSchedule::new(target_blocks);
 
     loop {
-        let loop_start = Instant::now();
-
         // repeatedly refreshing the parameters here maintains loop correctness
         // if the gravity_id is changed or slashing windows are changed. Neither of these
-        // is very probable
-        let params = match get_gravity_params(&mut grpc_client).await {
+        // is very probable, so they are only queried again once the cached copy expires
+        let params = match get_gravity_params_cached(&mut grpc_client).await {
             Ok(p) => p,
             Err(e) => {
                 error!("Failed to get Gravity parameters with {} correct your Cosmos gRPC connection immediately, you are risking slashing",e);
@@ -316,12 +460,18 @@ pub async fn eth_signer_main_loop(
             min(params.signed_valsets_window, params.signed_batches_window),
             params.signed_logic_calls_window,
         );
+        let windows = (
+            params.signed_valsets_window,
+            params.signed_batches_window,
+            params.signed_logic_calls_window,
+        );
         let gravity_id = params.gravity_id;
 
         let latest_cosmos_block = contact.get_chain_status().await;
-        match latest_cosmos_block {
+        let height = match latest_cosmos_block {
             Ok(ChainStatus::Moving { block_height }) => {
                 trace!("Latest Cosmos block {}", block_height,);
+                block_height
             }
             Ok(ChainStatus::Syncing) => {
                 warn!("Cosmos node syncing, Eth signer paused");
@@ -350,17 +500,77 @@ pub async fn eth_signer_main_loop(
                 );
                 continue;
             }
+        };
+
+        // the pending valsets, batches and logic calls are independent, so they are requested
+        // at once over the shared channel rather than one round trip after another
+        let (mut valset_client, mut batch_client, mut call_client) = (
+            grpc_client.clone(),
+            grpc_client.clone(),
+            grpc_client.clone(),
+        );
+        let (unsigned_valsets, unsigned_batches, unsigned_calls) = join3(
+            get_oldest_unsigned_valsets_with_height(
+                &mut valset_client,
+                our_cosmos_address,
+                contact.get_prefix(),
+            ),
+            get_oldest_unsigned_transaction_batches_with_height(
+                &mut batch_client,
+                our_cosmos_address,
+                contact.get_prefix(),
+            ),
+            get_oldest_unsigned_logic_calls_with_height(
+                &mut call_client,
+                our_cosmos_address,
+                contact.get_prefix(),
+            ),
+        )
+        .await;
+
+        // after downtime there may be more work than fits in a block, sign what is closest to
+        // a slashing deadline or relaying timeout first
+        let mut backlog = Vec::new();
+        if let Ok(valsets) = &unsigned_valsets {
+            backlog.extend(
+                valsets
+                    .iter()
+                    .map(|(v, created)| PrioritizedWork::valset(v, *created, windows.0)),
+            );
+        }
+        if let Ok(batches) = &unsigned_batches {
+            backlog.extend(
+                batches
+                    .iter()
+                    .map(|(b, created)| PrioritizedWork::batch(b, *created, windows.1)),
+            );
+        }
+        if let Ok(calls) = &unsigned_calls {
+            backlog.extend(
+                calls
+                    .iter()
+                    .map(|(c, created)| PrioritizedWork::logic_call(c, *created, windows.2)),
+            );
         }
+        let eth_height = if backlog.len() > MAX_CONFIRMS_PER_BLOCK {
+            // timeouts are checked by the Gravity contract against Ethereum's own height, the
+            // last height the chain observed can lag it by far more than a backlog's worth
+            web3.eth_block_number().await.ok().and_then(|b| b.to_u64())
+        } else {
+            None
+        };
+        let urgent = prioritize(backlog, height, eth_height, MAX_CONFIRMS_PER_BLOCK);
 
         // sign the last unsigned valsets
-        match get_oldest_unsigned_valsets(
-            &mut grpc_client,
-            our_cosmos_address,
-            contact.get_prefix(),
-        )
-        .await
-        {
+        match unsigned_valsets {
             Ok(valsets) => {
+                let valsets: Vec<_> = valsets.into_iter().map(|(v, _)| v).collect();
+                set_unsigned_valsets(&valsets);
+                let pending: Vec<SignerWork> = valsets.iter().map(|v| v.into()).collect();
+                schedule.update(height, SignerWorkKind::Valset, &pending);
+                let valsets = urgent.select(valsets);
+                let valsets =
+                    check_valset_checkpoints(&mut grpc_client, &gravity_id, valsets).await;
                 if valsets.is_empty() {
                     trace!("No validator sets to sign, node is caught up!")
                 } else {
@@ -371,7 +581,7 @@ pub async fn eth_signer_main_loop(
                     );
                     let res = send_valset_confirms(
                         &contact,
-                        ethereum_key,
+                        eth_signer.as_ref(),
                         fee.clone(),
                         valsets,
                         cosmos_key,
@@ -389,14 +599,18 @@ pub async fn eth_signer_main_loop(
         }
 
         // sign the last unsigned batch, TODO check if we already have signed this
-        match get_oldest_unsigned_transaction_batches(
-            &mut grpc_client,
-            our_cosmos_address,
-            contact.get_prefix(),
-        )
-        .await
-        {
+        match unsigned_batches {
             Ok(last_unsigned_batches) => {
+                let last_unsigned_batches: Vec<_> =
+                    last_unsigned_batches.into_iter().map(|(b, _)| b).collect();
+                set_unsigned_batches(&last_unsigned_batches);
+                let pending: Vec<SignerWork> =
+                    last_unsigned_batches.iter().map(|b| b.into()).collect();
+                schedule.update(height, SignerWorkKind::Batch, &pending);
+                let last_unsigned_batches = urgent.select(last_unsigned_batches);
+                let last_unsigned_batches =
+                    check_batch_checkpoints(&mut grpc_client, &gravity_id, last_unsigned_batches)
+                        .await;
                 if last_unsigned_batches.is_empty() {
                     trace!("No unsigned batch sets to sign, node is caught up!")
                 } else {
@@ -408,7 +622,7 @@ pub async fn eth_signer_main_loop(
 
                     let res = send_batch_confirm(
                         &contact,
-                        ethereum_key,
+                        eth_signer.as_ref(),
                         fee.clone(),
                         last_unsigned_batches,
                         cosmos_key,
@@ -425,14 +639,22 @@ pub async fn eth_signer_main_loop(
             ),
         }
 
-        match get_oldest_unsigned_logic_calls(
-            &mut grpc_client,
-            our_cosmos_address,
-            contact.get_prefix(),
-        )
-        .await
-        {
+        match unsigned_calls {
             Ok(last_unsigned_calls) => {
+                let last_unsigned_calls: Vec<_> =
+                    last_unsigned_calls.into_iter().map(|(c, _)| c).collect();
+                set_unsigned_logic_calls(&last_unsigned_calls);
+                let pending: Vec<SignerWork> =
+                    last_unsigned_calls.iter().map(|c| c.into()).collect();
+                schedule.update(height, SignerWorkKind::LogicCall, &pending);
+                let last_unsigned_calls = urgent.select(last_unsigned_calls);
+                let last_unsigned_calls = check_logic_call_checkpoints(
+                    &mut grpc_client,
+                    &gravity_id,
+                    last_unsigned_calls,
+                )
+                .await;
+                let last_unsigned_calls = logic_call_policy.filter_logic_calls(last_unsigned_calls);
                 if last_unsigned_calls.is_empty() {
                     trace!("No unsigned call sets to sign, node is caught up!")
                 } else {
@@ -443,7 +665,7 @@ pub async fn eth_signer_main_loop(
                     );
                     let res = send_logic_call_confirm(
                         &contact,
-                        ethereum_key,
+                        eth_signer.as_ref(),
                         fee.clone(),
                         last_unsigned_calls,
                         cosmos_key,
@@ -460,13 +682,9 @@ pub async fn eth_signer_main_loop(
             ),
         }
 
-        // a bit of logic that tires to keep things running every LOOP_SPEED seconds exactly
-        // this is not required for any specific reason. In fact we expect and plan for
-        // the timing being off significantly
-        let elapsed = Instant::now() - loop_start;
-        if elapsed < ETH_SIGNER_LOOP_SPEED {
-            delay_for(ETH_SIGNER_LOOP_SPEED - elapsed).await;
-        }
+        // new work only appears at the end of a block, so wait for the next one rather
+        // than a fixed interval. If the chain stalls we still run every LOOP_SPEED seconds
+        wait_for_new_block(&contact, height, ETH_SIGNER_LOOP_SPEED).await;
     }
 }
 
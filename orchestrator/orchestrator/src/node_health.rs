@@ -0,0 +1,221 @@
+//! Stale node detection. An Ethereum or Cosmos node that stops syncing keeps answering queries with
+//! old data, so the oracle and signer keep running without errors while attestations fall further
+//! and further behind. This loop compares each node's latest block against the wall clock and
+//! against any configured reference endpoints, marks stale nodes in the debug state and alerts
+
+use deep_space::client::ChainStatus;
+use deep_space::Contact;
+use gravity_utils::debug_state::{set_cosmos_node_health, set_ethereum_node_health, NodeHealth};
+use gravity_utils::types::NodeHealthConfig;
+use metrics_exporter::{metrics_errors_counter, metrics_latest};
+use num_traits::ToPrimitive;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+
+/// How often the nodes are checked
+pub const NODE_HEALTH_INTERVAL: Duration = Duration::from_secs(30);
+/// Timeout for queries to the reference endpoints
+const REFERENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Returns why a node whose latest block is `height`, produced `head_age` ago, is stale or None
+/// if it is current. Heights from unreachable reference endpoints should be left out
+pub fn find_staleness(
+    height: u64,
+    head_age: Duration,
+    max_head_age: Duration,
+    reference_heights: &[u64],
+    max_blocks_behind: u64,
+) -> Option<String> {
+    if head_age > max_head_age {
+        return Some(format!(
+            "latest block {} is {} seconds old",
+            height,
+            head_age.as_secs()
+        ));
+    }
+    match reference_heights.iter().max() {
+        Some(best) if best.saturating_sub(height) > max_blocks_behind => Some(format!(
+            "latest block {} is {} blocks behind a reference node at {}",
+            height,
+            best - height,
+            best
+        )),
+        _ => None,
+    }
+}
+
+pub async fn node_health_loop(contact: Contact, web3: Web3, config: NodeHealthConfig) {
+    let references: Vec<Web3> = config
+        .reference_ethereum_rpcs
+        .iter()
+        .map(|url| Web3::new(url, REFERENCE_TIMEOUT))
+        .collect();
+    let mut cosmos_references = Vec::new();
+    for url in config.reference_cosmos_grpcs.iter() {
+        match Contact::new(url, REFERENCE_TIMEOUT, &contact.get_prefix()) {
+            Ok(c) => cosmos_references.push(c),
+            Err(e) => warn!("Invalid reference Cosmos gRPC {} {:?}", url, e),
+        }
+    }
+    let max_ethereum_age = Duration::from_secs(config.max_ethereum_head_age);
+    let max_cosmos_age = Duration::from_secs(config.max_cosmos_head_age);
+    // Cosmos blocks are timed from when we first saw them
+    let mut cosmos_head: Option<(u64, Instant)> = None;
+    let mut ethereum_stale = false;
+    let mut cosmos_stale = false;
+
+    loop {
+        let ethereum = check_ethereum(&web3, &references, max_ethereum_age, &config).await;
+        report("Ethereum", 1, &ethereum, &mut ethereum_stale);
+        set_ethereum_node_health(ethereum);
+
+        let cosmos = check_cosmos(
+            &contact,
+            &cosmos_references,
+            &mut cosmos_head,
+            max_cosmos_age,
+            &config,
+        )
+        .await;
+        report("Cosmos", 2, &cosmos, &mut cosmos_stale);
+        set_cosmos_node_health(cosmos);
+
+        delay_for(NODE_HEALTH_INTERVAL).await;
+    }
+}
+
+async fn check_ethereum(
+    web3: &Web3,
+    references: &[Web3],
+    max_head_age: Duration,
+    config: &NodeHealthConfig,
+) -> NodeHealth {
+    let block = match web3.eth_get_latest_block().await {
+        Ok(b) => b,
+        Err(e) => {
+            return NodeHealth {
+                height: None,
+                problem: Some(format!("unreachable {:?}", e)),
+            }
+        }
+    };
+    let height = block.number.to_u64().unwrap_or(0);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let head_age = Duration::from_secs(now.saturating_sub(block.timestamp.to_u64().unwrap_or(now)));
+    let mut reference_heights = Vec::new();
+    for reference in references {
+        if let Ok(h) = reference.eth_block_number().await {
+            reference_heights.push(h.to_u64().unwrap_or(0));
+        }
+    }
+    NodeHealth {
+        height: Some(height),
+        problem: find_staleness(
+            height,
+            head_age,
+            max_head_age,
+            &reference_heights,
+            config.max_blocks_behind,
+        ),
+    }
+}
+
+async fn check_cosmos(
+    contact: &Contact,
+    references: &[Contact],
+    head: &mut Option<(u64, Instant)>,
+    max_head_age: Duration,
+    config: &NodeHealthConfig,
+) -> NodeHealth {
+    let height = match contact.get_chain_status().await {
+        Ok(ChainStatus::Moving { block_height }) => block_height,
+        Ok(ChainStatus::Syncing) => {
+            return NodeHealth {
+                height: None,
+                problem: Some("syncing".to_string()),
+            }
+        }
+        Ok(ChainStatus::WaitingToStart) => {
+            return NodeHealth {
+                height: None,
+                problem: Some("waiting for the chain to start".to_string()),
+            }
+        }
+        Err(e) => {
+            return NodeHealth {
+                height: None,
+                problem: Some(format!("unreachable {:?}", e)),
+            }
+        }
+    };
+    let seen = match *head {
+        Some((h, seen)) if h == height => seen,
+        _ => {
+            let now = Instant::now();
+            *head = Some((height, now));
+            now
+        }
+    };
+    let mut reference_heights = Vec::new();
+    for reference in references {
+        if let Ok(ChainStatus::Moving { block_height }) = reference.get_chain_status().await {
+            reference_heights.push(block_height);
+        }
+    }
+    NodeHealth {
+        height: Some(height),
+        problem: find_staleness(
+            height,
+            Instant::now() - seen,
+            max_head_age,
+            &reference_heights,
+            config.max_blocks_behind,
+        ),
+    }
+}
+
+/// Alerts when a node becomes stale and logs when it recovers, `stale` holds the previous state
+fn report(chain: &str, metrics_source: i32, health: &NodeHealth, stale: &mut bool) {
+    let metric = format!("{}_node_healthy", chain.to_lowercase());
+    match &health.problem {
+        Some(problem) => {
+            if !*stale {
+                error!(
+                    "Your {} node is stale, {}. The orchestrator will fall behind until it recovers",
+                    chain, problem
+                );
+                metrics_errors_counter(metrics_source, &format!("{} node stale", chain));
+            }
+            metrics_latest(0, &metric);
+            *stale = true;
+        }
+        None => {
+            if *stale {
+                info!("Your {} node has recovered", chain);
+            }
+            metrics_latest(1, &metric);
+            *stale = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_staleness() {
+        let max_age = Duration::from_secs(180);
+        assert!(find_staleness(100, Duration::from_secs(12), max_age, &[], 5).is_none());
+        assert!(find_staleness(100, Duration::from_secs(600), max_age, &[], 5).is_some());
+        // within the allowed lag of the best reference
+        assert!(find_staleness(100, Duration::from_secs(12), max_age, &[90, 105], 5).is_none());
+        assert!(find_staleness(100, Duration::from_secs(12), max_age, &[90, 600], 5).is_some());
+        // references behind us are fine
+        assert!(find_staleness(100, Duration::from_secs(12), max_age, &[50], 5).is_none());
+    }
+}
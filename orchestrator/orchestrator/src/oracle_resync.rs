@@ -8,7 +8,7 @@ use gravity_utils::types::{
     Erc20DeployedEvent, EthereumEvent, LogicCallExecutedEvent, SendToCosmosEvent,
     TransactionBatchExecutedEvent, ValsetUpdatedEvent,
 };
-use metrics_exporter::metrics_errors_counter;
+use metrics_exporter::{metrics_errors_counter, metrics_eth_rpc_error};
 use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
 use web30::client::Web3;
@@ -110,6 +110,7 @@ pub async fn get_last_checked_block(
             error!("Failed to get blockchain events while resyncing, is your Eth node working? If you see only one of these it's fine",);
             delay_for(RETRY_TIME).await;
             metrics_errors_counter(1, "Failed to get blockchain events while resyncing");
+            metrics_eth_rpc_error("get_logs");
             continue;
         }
         let batch_events = batch_events.unwrap();
@@ -0,0 +1,191 @@
+//! Watches the Gravity module parameters for changes made by governance. Every change is logged so
+//! operators know why the orchestrator's behavior changed, and the cached params used by the
+//! signer and oracle are replaced at once so new slashing windows and fees apply without a restart.
+//! The Gravity contract address and gravity_id are read at startup, a change to either needs one
+
+use cosmos_gravity::query::get_gravity_params;
+use cosmos_gravity::query_cache::update_gravity_params_cache;
+use cosmos_gravity::safe_mode::update_bridge_halt;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::Params;
+use metrics_exporter::{metrics_errors_counter, metrics_latest, metrics_warnings_counter};
+use std::time::Duration;
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+
+/// How often the params are checked for changes
+pub const PARAMS_WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A single parameter that differs between two queries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamChange {
+    pub name: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// Params that the orchestrator only reads at startup
+const RESTART_REQUIRED: [&str; 2] = ["gravity_id", "bridge_ethereum_address"];
+
+/// A param name and how to render its value for comparison
+type ParamField = (&'static str, fn(&Params) -> String);
+
+/// Every param that differs between `old` and `new`
+pub fn diff_params(old: &Params, new: &Params) -> Vec<ParamChange> {
+    let decimal = |d: &[u8]| String::from_utf8_lossy(d).to_string();
+    let fields: Vec<ParamField> = vec![
+        ("gravity_id", |p| p.gravity_id.clone()),
+        ("contract_source_hash", |p| p.contract_source_hash.clone()),
+        ("bridge_ethereum_address", |p| {
+            p.bridge_ethereum_address.clone()
+        }),
+        ("bridge_chain_id", |p| p.bridge_chain_id.to_string()),
+        ("signed_valsets_window", |p| {
+            p.signed_valsets_window.to_string()
+        }),
+        ("signed_batches_window", |p| {
+            p.signed_batches_window.to_string()
+        }),
+        ("signed_logic_calls_window", |p| {
+            p.signed_logic_calls_window.to_string()
+        }),
+        ("target_batch_timeout", |p| {
+            p.target_batch_timeout.to_string()
+        }),
+        ("average_block_time", |p| p.average_block_time.to_string()),
+        ("average_ethereum_block_time", |p| {
+            p.average_ethereum_block_time.to_string()
+        }),
+        ("unbond_slashing_valsets_window", |p| {
+            p.unbond_slashing_valsets_window.to_string()
+        }),
+        ("valset_reward", |p| match &p.valset_reward {
+            Some(c) => format!("{}{}", c.amount, c.denom),
+            None => "none".to_string(),
+        }),
+        ("bridge_active", |p| p.bridge_active.to_string()),
+        ("ethereum_blacklist", |p| p.ethereum_blacklist.join(",")),
+        ("min_chain_fee_basis_points", |p| {
+            p.min_chain_fee_basis_points.to_string()
+        }),
+    ];
+    let mut changes: Vec<ParamChange> = fields
+        .into_iter()
+        .filter_map(|(name, get)| {
+            let (old, new) = (get(old), get(new));
+            if old == new {
+                None
+            } else {
+                Some(ParamChange { name, old, new })
+            }
+        })
+        .collect();
+    let fractions = [
+        (
+            "slash_fraction_valset",
+            &old.slash_fraction_valset,
+            &new.slash_fraction_valset,
+        ),
+        (
+            "slash_fraction_batch",
+            &old.slash_fraction_batch,
+            &new.slash_fraction_batch,
+        ),
+        (
+            "slash_fraction_logic_call",
+            &old.slash_fraction_logic_call,
+            &new.slash_fraction_logic_call,
+        ),
+        (
+            "slash_fraction_bad_eth_signature",
+            &old.slash_fraction_bad_eth_signature,
+            &new.slash_fraction_bad_eth_signature,
+        ),
+    ];
+    for (name, old, new) in fractions {
+        if old != new {
+            changes.push(ParamChange {
+                name,
+                old: decimal(old),
+                new: decimal(new),
+            });
+        }
+    }
+    changes
+}
+
+/// Logs every change between `old` and `new`, with what it means for this orchestrator. Halting
+/// and resuming the bridge is reported by safe mode
+fn report_changes(old: &Params, new: &Params) {
+    for change in diff_params(old, new) {
+        warn!(
+            "Gravity parameter {} changed from {} to {}",
+            change.name, change.old, change.new
+        );
+        metrics_warnings_counter(1, "Gravity parameter changed");
+        if RESTART_REQUIRED.contains(&change.name) {
+            error!(
+                "The orchestrator reads {} only at startup, restart it to use the new value!",
+                change.name
+            );
+            metrics_errors_counter(2, "Gravity parameter changed, restart required");
+        }
+    }
+}
+
+/// Checks the params every PARAMS_WATCH_INTERVAL, reporting and applying any changes
+pub async fn params_watch_loop(grpc_client: GravityQueryClient<Channel>) {
+    let mut grpc_client = grpc_client;
+    let mut last: Option<Params> = None;
+    loop {
+        match get_gravity_params(&mut grpc_client).await {
+            Ok(params) => {
+                metrics_latest(params.bridge_active as u64, "gravity_bridge_active");
+                update_bridge_halt(params.bridge_active);
+                if let Some(last) = &last {
+                    if *last != params {
+                        report_changes(last, &params);
+                        update_gravity_params_cache(&params);
+                    }
+                }
+                last = Some(params);
+            }
+            Err(e) => warn!("Failed to check the Gravity parameters for changes {:?}", e),
+        }
+        delay_for(PARAMS_WATCH_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_params() {
+        let old = Params {
+            signed_batches_window: 10_000,
+            bridge_active: true,
+            slash_fraction_batch: b"0.001".to_vec(),
+            ..Default::default()
+        };
+        assert!(diff_params(&old, &old).is_empty());
+
+        let new = Params {
+            signed_batches_window: 5_000,
+            bridge_active: false,
+            slash_fraction_batch: b"0.01".to_vec(),
+            ..old.clone()
+        };
+        let changes = diff_params(&old, &new);
+        assert_eq!(
+            changes.iter().map(|c| c.name).collect::<Vec<_>>(),
+            vec![
+                "signed_batches_window",
+                "bridge_active",
+                "slash_fraction_batch"
+            ]
+        );
+        assert_eq!(changes[0].old, "10000");
+        assert_eq!(changes[2].new, "0.01");
+    }
+}
@@ -0,0 +1,218 @@
+//! Scheduling of the Ethereum signer off Cosmos block heights. Valsets, batches and logic calls are
+//! created by the Gravity module in EndBlocker, so there is never new work to sign between blocks
+//! and waiting on a wall clock interval only adds latency between creation and 2/3 confirmation.
+//! Instead the signer runs once per new block, and anything that is still unsigned more than the
+//! configured number of blocks after it was first seen is reported so operators can react before
+//! the slashing window closes
+
+use deep_space::client::ChainStatus;
+use deep_space::Contact;
+use gravity_utils::types::{LogicCall, TransactionBatch, Valset};
+use metrics_exporter::{metrics_latest, metrics_warnings_counter};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+
+/// How often the chain is polled for a new block, Cosmos blocks take several seconds so this keeps
+/// the signer within a second of each new block at the cost of one cheap query per poll
+pub const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Waits until the chain moves past `last_height` or `max_wait` has passed, returning the latest
+/// height observed. The signer still runs every `max_wait` if the chain halts or the node stops
+/// responding so that errors are reported as before
+pub async fn wait_for_new_block(contact: &Contact, last_height: u64, max_wait: Duration) -> u64 {
+    let start = Instant::now();
+    loop {
+        if let Ok(ChainStatus::Moving { block_height }) = contact.get_chain_status().await {
+            if block_height > last_height {
+                return block_height;
+            }
+        }
+        if Instant::now() - start + BLOCK_POLL_INTERVAL > max_wait {
+            return last_height;
+        }
+        delay_for(BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignerWorkKind {
+    Valset,
+    Batch,
+    LogicCall,
+}
+
+impl fmt::Display for SignerWorkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerWorkKind::Valset => write!(f, "valset"),
+            SignerWorkKind::Batch => write!(f, "batch"),
+            SignerWorkKind::LogicCall => write!(f, "logic call"),
+        }
+    }
+}
+
+/// A single valset, batch or logic call waiting for our signature
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignerWork {
+    pub kind: SignerWorkKind,
+    pub id: String,
+}
+
+impl fmt::Display for SignerWork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.id)
+    }
+}
+
+impl From<&Valset> for SignerWork {
+    fn from(input: &Valset) -> Self {
+        SignerWork {
+            kind: SignerWorkKind::Valset,
+            id: input.nonce.to_string(),
+        }
+    }
+}
+
+impl From<&TransactionBatch> for SignerWork {
+    fn from(input: &TransactionBatch) -> Self {
+        SignerWork {
+            kind: SignerWorkKind::Batch,
+            id: format!("{}:{}", input.token_contract, input.nonce),
+        }
+    }
+}
+
+impl From<&LogicCall> for SignerWork {
+    fn from(input: &LogicCall) -> Self {
+        SignerWork {
+            kind: SignerWorkKind::LogicCall,
+            id: format!(
+                "0x{}:{}",
+                clarity::utils::bytes_to_hex_str(&input.invalidation_id),
+                input.invalidation_nonce
+            ),
+        }
+    }
+}
+
+/// Tracks the height at which each piece of unsigned work was first seen. Since the signer runs
+/// every block this is the block the work was created in, or the one after
+#[derive(Debug, Clone, Default)]
+pub struct SignerSchedule {
+    target_blocks: u64,
+    first_seen: HashMap<SignerWork, u64>,
+}
+
+impl SignerSchedule {
+    pub fn new(target_blocks: u64) -> Self {
+        SignerSchedule {
+            target_blocks,
+            first_seen: HashMap::new(),
+        }
+    }
+
+    /// Records the work of `kind` still unsigned at `height`. Work of this kind no longer pending
+    /// has been signed and is forgotten, returning it with the number of blocks it took to sign
+    pub fn observe(
+        &mut self,
+        height: u64,
+        kind: SignerWorkKind,
+        pending: &[SignerWork],
+    ) -> Vec<(SignerWork, u64)> {
+        for work in pending {
+            self.first_seen.entry(work.clone()).or_insert(height);
+        }
+        let mut done = Vec::new();
+        self.first_seen.retain(|work, seen| {
+            if work.kind != kind || pending.contains(work) {
+                true
+            } else {
+                done.push((work.clone(), height.saturating_sub(*seen)));
+                false
+            }
+        });
+        done
+    }
+
+    /// Work that has waited for our signature for more than the target number of blocks, with
+    /// the number of blocks it has waited
+    pub fn overdue(&self, height: u64) -> Vec<(SignerWork, u64)> {
+        let mut out: Vec<(SignerWork, u64)> = self
+            .first_seen
+            .iter()
+            .map(|(work, seen)| (work.clone(), height.saturating_sub(*seen)))
+            .filter(|(_, waited)| *waited > self.target_blocks)
+            .collect();
+        out.sort_by_key(|(_, waited)| std::cmp::Reverse(*waited));
+        out
+    }
+
+    /// Records the work of `kind` pending at `height`, logs the latency of work signed since the
+    /// last call and warns about anything overdue
+    pub fn update(&mut self, height: u64, kind: SignerWorkKind, pending: &[SignerWork]) {
+        for (work, blocks) in self.observe(height, kind, pending) {
+            debug!("Signed {} within {} blocks", work, blocks);
+            metrics_latest(blocks, "signer_blocks_to_sign");
+        }
+        let overdue: Vec<(SignerWork, u64)> = self
+            .overdue(height)
+            .into_iter()
+            .filter(|(work, _)| work.kind == kind)
+            .collect();
+        if let Some((_, waited)) = overdue.first() {
+            metrics_latest(*waited, "signer_oldest_unsigned_blocks");
+        }
+        for (work, waited) in overdue {
+            warn!(
+                "{} has waited {} blocks for our signature, more than the target of {}",
+                work, waited, self.target_blocks
+            );
+            metrics_warnings_counter(2, "Signer work overdue");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valset(nonce: u64) -> SignerWork {
+        SignerWork {
+            kind: SignerWorkKind::Valset,
+            id: nonce.to_string(),
+        }
+    }
+
+    fn batch(nonce: u64) -> SignerWork {
+        SignerWork {
+            kind: SignerWorkKind::Batch,
+            id: nonce.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_signer_schedule() {
+        let mut schedule = SignerSchedule::new(2);
+        assert!(schedule
+            .observe(10, SignerWorkKind::Valset, &[valset(1), valset(2)])
+            .is_empty());
+        assert!(schedule
+            .observe(11, SignerWorkKind::Batch, &[batch(1)])
+            .is_empty());
+        assert!(schedule.overdue(12).is_empty());
+
+        // valset 1 is signed, batches are untouched by a valset observation
+        let done = schedule.observe(12, SignerWorkKind::Valset, &[valset(2), valset(3)]);
+        assert_eq!(done, vec![(valset(1), 2)]);
+
+        // valset 2 was first seen at 10, batch 1 at 11 and valset 3 at 12
+        assert_eq!(schedule.overdue(13), vec![(valset(2), 3)]);
+        assert_eq!(schedule.overdue(14), vec![(valset(2), 4), (batch(1), 3)]);
+
+        let done = schedule.observe(14, SignerWorkKind::Batch, &[]);
+        assert_eq!(done, vec![(batch(1), 3)]);
+        assert_eq!(schedule.overdue(14), vec![(valset(2), 4)]);
+    }
+}
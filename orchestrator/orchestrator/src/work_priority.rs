@@ -0,0 +1,179 @@
+//! Prioritization of the signer's backlog after downtime. Normally every valset, batch and logic
+//! call is signed in the block after it is created, but an orchestrator coming back from an outage
+//! may find hundreds waiting. Confirming all of them in one transaction can exceed the block gas
+//! limit, and working through them oldest first leaves work a few blocks from its slashing deadline
+//! queued behind work with plenty of time left. Instead a limited number are signed each block,
+//! those closest to a slashing deadline or relaying timeout first. Ethereum events are not
+//! prioritized, the chain only accepts claims in event nonce order
+
+use crate::signer_schedule::SignerWork;
+use gravity_utils::types::{LogicCall, TransactionBatch, Valset};
+use metrics_exporter::metrics_latest;
+use std::collections::HashSet;
+
+/// The most confirms sent in a single block while working through a backlog
+pub const MAX_CONFIRMS_PER_BLOCK: usize = 25;
+/// Cosmos blocks are about twice as fast as Ethereum blocks, used to compare relaying timeouts
+/// which are in Ethereum blocks to slashing deadlines which are in Cosmos blocks
+const COSMOS_BLOCKS_PER_ETH_BLOCK: u64 = 2;
+
+/// A valset, batch or logic call waiting for our signature and when it must be signed by
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrioritizedWork {
+    pub work: SignerWork,
+    /// the Cosmos height at which we are slashed if this is still unsigned
+    pub slashing_height: u64,
+    /// the Ethereum height after which a batch or logic call can no longer be relayed
+    pub timeout: Option<u64>,
+}
+
+impl PrioritizedWork {
+    pub fn valset(valset: &Valset, created: u64, signed_valsets_window: u64) -> Self {
+        PrioritizedWork {
+            work: valset.into(),
+            slashing_height: created + signed_valsets_window,
+            timeout: None,
+        }
+    }
+
+    pub fn batch(batch: &TransactionBatch, created: u64, signed_batches_window: u64) -> Self {
+        PrioritizedWork {
+            work: batch.into(),
+            slashing_height: created + signed_batches_window,
+            timeout: Some(batch.batch_timeout),
+        }
+    }
+
+    pub fn logic_call(call: &LogicCall, created: u64, signed_logic_calls_window: u64) -> Self {
+        PrioritizedWork {
+            work: call.into(),
+            slashing_height: created + signed_logic_calls_window,
+            timeout: Some(call.timeout),
+        }
+    }
+
+    /// Sorts by whether the work has timed out on Ethereum, then by the Cosmos blocks left before
+    /// it is slashed or can no longer be relayed. Timed out work is cancelled by the chain once it
+    /// observes the timeout, so it goes last
+    fn urgency(&self, height: u64, eth_height: Option<u64>) -> (bool, u64) {
+        let slashing = self.slashing_height.saturating_sub(height);
+        match (self.timeout, eth_height) {
+            (Some(timeout), Some(eth_height)) if timeout <= eth_height => (true, slashing),
+            (Some(timeout), Some(eth_height)) => {
+                let relaying = (timeout - eth_height).saturating_mul(COSMOS_BLOCKS_PER_ETH_BLOCK);
+                (false, slashing.min(relaying))
+            }
+            _ => (false, slashing),
+        }
+    }
+}
+
+/// The work to sign this block, None to sign everything pending
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrgentWork(Option<HashSet<SignerWork>>);
+
+impl UrgentWork {
+    /// Keeps only the pending items selected for this block
+    pub fn select<T>(&self, pending: Vec<T>) -> Vec<T>
+    where
+        for<'a> &'a T: Into<SignerWork>,
+    {
+        match &self.0 {
+            None => pending,
+            Some(selected) => pending
+                .into_iter()
+                .filter(|p| selected.contains(&p.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Picks the `max` most urgent items of the backlog, or everything if there are no more than
+/// `max`. `eth_height` is the latest Ethereum height known, without it only slashing deadlines are
+/// considered
+pub fn prioritize(
+    mut backlog: Vec<PrioritizedWork>,
+    height: u64,
+    eth_height: Option<u64>,
+    max: usize,
+) -> UrgentWork {
+    metrics_latest(backlog.len() as u64, "signer_backlog");
+    if backlog.len() <= max {
+        return UrgentWork(None);
+    }
+    backlog.sort_by_key(|w| w.urgency(height, eth_height));
+    let (_, blocks_left) = backlog[0].urgency(height, eth_height);
+    info!(
+        "Catching up on {} unsigned items, signing the {} most urgent first, {} has {} blocks left",
+        backlog.len(),
+        max,
+        backlog[0].work,
+        blocks_left
+    );
+    UrgentWork(Some(
+        backlog.into_iter().take(max).map(|w| w.work).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer_schedule::SignerWorkKind;
+
+    fn work(
+        kind: SignerWorkKind,
+        id: u64,
+        slashing_height: u64,
+        timeout: Option<u64>,
+    ) -> PrioritizedWork {
+        PrioritizedWork {
+            work: SignerWork {
+                kind,
+                id: id.to_string(),
+            },
+            slashing_height,
+            timeout,
+        }
+    }
+
+    #[test]
+    fn test_prioritize() {
+        let backlog = vec![
+            // the most time left before slashing
+            work(SignerWorkKind::Valset, 1, 1_000, None),
+            work(SignerWorkKind::Valset, 2, 200, None),
+            // plenty of slashing window but about to time out on Ethereum
+            work(SignerWorkKind::Batch, 3, 1_000, Some(10_050)),
+            // already timed out, the chain will cancel it
+            work(SignerWorkKind::Batch, 4, 150, Some(9_000)),
+            work(SignerWorkKind::LogicCall, 5, 500, Some(20_000)),
+        ];
+        assert_eq!(
+            prioritize(backlog.clone(), 100, Some(10_000), 5),
+            UrgentWork(None)
+        );
+
+        let urgent = prioritize(backlog.clone(), 100, Some(10_000), 2);
+        let ids: Vec<String> = urgent
+            .select(backlog.clone())
+            .into_iter()
+            .map(|w| w.work.id)
+            .collect();
+        assert_eq!(ids, vec!["2", "3"]);
+
+        // without an Ethereum height timeouts are unknown
+        let urgent = prioritize(backlog.clone(), 100, None, 2);
+        let ids: Vec<String> = urgent
+            .select(backlog)
+            .into_iter()
+            .map(|w| w.work.id)
+            .collect();
+        assert_eq!(ids, vec!["2", "4"]);
+    }
+
+    impl From<&PrioritizedWork> for SignerWork {
+        fn from(input: &PrioritizedWork) -> Self {
+            input.work.clone()
+        }
+    }
+}
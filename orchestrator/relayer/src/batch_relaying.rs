@@ -1,4 +1,9 @@
+use crate::duplicate_relay::{batch_relay_in_flight, MempoolSnapshot};
+use crate::gas_oracle::get_relaying_gas_price;
 use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use crate::profit_sweep::sweep_profits;
+use crate::revert_blacklist::{batch_blacklisted, blacklist_batch};
+use crate::screening::batch_passes_screening;
 use clarity::address::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
 use clarity::Uint256;
@@ -6,15 +11,20 @@ use cosmos_gravity::query::get_latest_transaction_batches;
 use cosmos_gravity::query::get_transaction_batch_signatures;
 use ethereum_gravity::message_signatures::encode_tx_batch_confirm_hashed;
 use ethereum_gravity::submit_batch::send_eth_transaction_batch;
-use ethereum_gravity::utils::get_tx_batch_nonce;
+use ethereum_gravity::utils::{get_tx_batch_nonce, RelayOutcome};
+use futures::future::join_all;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::debug_state::set_submittable_batches;
+use gravity_utils::error::GravityError;
 use gravity_utils::num_conversion::print_eth;
 use gravity_utils::num_conversion::print_gwei;
 use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::token_display::format_erc20;
 use gravity_utils::types::BatchRelayingMode;
 use gravity_utils::types::WhitelistToken;
 use gravity_utils::types::{BatchConfirmResponse, RelayerConfig, TransactionBatch, Valset};
 use std::collections::HashMap;
+use std::time::Duration;
 use tonic::transport::Channel;
 use web30::client::Web3;
 
@@ -41,11 +51,19 @@ pub async fn relay_batches(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     config: RelayerConfig,
+    mempool: &mut MempoolSnapshot,
 ) {
     let possible_batches =
         get_batches_and_signatures(current_valset.clone(), grpc_client, gravity_id.clone()).await;
 
     trace!("possible batches {:?}", possible_batches);
+    set_submittable_batches(
+        possible_batches
+            .values()
+            .flatten()
+            .map(|b| b.batch.clone())
+            .collect(),
+    );
 
     submit_batches(
         current_valset,
@@ -55,6 +73,7 @@ pub async fn relay_batches(
         gravity_id,
         possible_batches,
         config,
+        mempool,
     )
     .await;
 }
@@ -79,10 +98,19 @@ async fn get_batches_and_signatures(
     };
     trace!("Latest batches {:?}", latest_batches);
 
+    // signatures for every batch are requested at once over the shared channel
+    let all_sigs =
+        join_all(latest_batches.iter().map(|batch| {
+            let mut grpc_client = grpc_client.clone();
+            let (nonce, token_contract) = (batch.nonce, batch.token_contract);
+            async move {
+                get_transaction_batch_signatures(&mut grpc_client, nonce, token_contract).await
+            }
+        }))
+        .await;
+
     let mut possible_batches = HashMap::new();
-    for batch in latest_batches {
-        let sigs =
-            get_transaction_batch_signatures(grpc_client, batch.nonce, batch.token_contract).await;
+    for (batch, sigs) in latest_batches.into_iter().zip(all_sigs) {
         trace!("Got sigs {:?}", sigs);
         if let Ok(sigs) = sigs {
             // this checks that the signatures for the batch are actually possible to submit to the chain
@@ -186,6 +214,24 @@ async fn should_relay_batch(
     }
 }
 
+/// The total fees of `batch` in whole tokens
+async fn format_batch_fees(web3: &Web3, batch: &TransactionBatch) -> String {
+    format_erc20(
+        web3,
+        batch.total_fee.token_contract_address,
+        batch.total_fee.amount,
+    )
+    .await
+}
+
+/// The reward of relaying a batch in ETH, it is unknown when the fee token has no price
+fn format_reward(reward_in_weth: Option<Uint256>) -> String {
+    match reward_in_weth {
+        Some(reward) => format!("{} ETH", print_eth(reward)),
+        None => "unknown".to_string(),
+    }
+}
+
 /// Takes a token price whitelist, gets the amount of weth per token we have manually valued it at
 fn get_whitelist_price(erc20: EthAddress, whitelist: &[WhitelistToken]) -> Option<(Uint256, u8)> {
     for i in whitelist {
@@ -224,6 +270,7 @@ async fn submit_batches(
     gravity_id: String,
     possible_batches: HashMap<EthAddress, Vec<SubmittableBatch>>,
     config: RelayerConfig,
+    mempool: &mut MempoolSnapshot,
 ) {
     let our_ethereum_address = ethereum_key.to_address();
     let ethereum_block_height = if let Ok(bn) = web3.eth_block_number().await {
@@ -232,6 +279,10 @@ async fn submit_batches(
         warn!("Failed to get eth block height, is your eth node working?");
         return;
     };
+    let gas_price = get_relaying_gas_price(web3, &config.gas_oracle).await;
+    let blacklist_expiry = Duration::from_secs(config.revert_blacklist_minutes * 60);
+    // tokens of the batches relayed by this loop, their fees are swept once every batch is sent
+    let mut relayed_tokens = Vec::new();
 
     // requests data from Ethereum only once per token type, this is valid because we are
     // iterating from oldest to newest, so submitting a batch earlier in the loop won't
@@ -270,6 +321,20 @@ async fn submit_batches(
 
             let latest_cosmos_batch_nonce = oldest_signed_batch.clone().nonce;
             if latest_cosmos_batch_nonce > latest_ethereum_batch {
+                if batch_blacklisted(
+                    oldest_signed_batch.token_contract,
+                    oldest_signed_batch.nonce,
+                    blacklist_expiry,
+                ) {
+                    info!(
+                        "Skipping batch {}/{}, its last relay reverted and would revert again",
+                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce
+                    );
+                    continue;
+                }
+                if !batch_passes_screening(&oldest_signed_batch, &config.screening).await {
+                    continue;
+                }
                 let cost = ethereum_gravity::submit_batch::estimate_tx_batch_cost(
                     current_valset.clone(),
                     oldest_signed_batch.clone(),
@@ -278,6 +343,8 @@ async fn submit_batches(
                     gravity_contract_address,
                     gravity_id.clone(),
                     ethereum_key.to_address(),
+                    gas_price,
+                    config.trim_signatures,
                 )
                 .await;
                 if cost.is_err() {
@@ -306,34 +373,83 @@ async fn submit_batches(
                 .await;
 
                 if should_relay {
+                    if batch_relay_in_flight(
+                        web3,
+                        gravity_contract_address,
+                        our_ethereum_address,
+                        &oldest_signed_batch,
+                        cost.gas_price,
+                        mempool,
+                    )
+                    .await
+                    {
+                        continue;
+                    }
+                    if config.dry_run {
+                        info!(
+                            "Dry run, would relay batch {}/{} Expected Cost: {} ETH Fees: {} Reward: {}",
+                            oldest_signed_batch.token_contract,
+                            oldest_signed_batch.nonce,
+                            print_eth(cost.get_total()),
+                            format_batch_fees(web3, &oldest_signed_batch).await,
+                            format_reward(reward_in_weth),
+                        );
+                        continue;
+                    }
                     info!(
-                        "Attempting to relay batch {}/{} Expected Cost: {:} Reward: {:?}",
+                        "Attempting to relay batch {}/{} Expected Cost: {} ETH Fees: {} Reward: {}",
                         oldest_signed_batch.token_contract,
                         oldest_signed_batch.nonce,
                         print_eth(cost.get_total()),
-                        reward_in_weth.map(print_eth),
+                        format_batch_fees(web3, &oldest_signed_batch).await,
+                        format_reward(reward_in_weth),
                     );
                     let res = send_eth_transaction_batch(
                         current_valset.clone(),
-                        oldest_signed_batch,
+                        oldest_signed_batch.clone(),
                         &oldest_signatures,
                         web3,
                         ETH_SUBMIT_WAIT_TIME,
                         gravity_contract_address,
                         gravity_id.clone(),
                         ethereum_key,
+                        gas_price,
+                        config.trim_signatures,
+                        &config.private_relay,
                     )
                     .await;
-                    if res.is_err() {
-                        info!("Batch submission failed with {:?}", res);
+                    match res {
+                        Ok(RelayOutcome::Relayed) => {
+                            if !relayed_tokens.contains(&oldest_signed_batch.token_contract) {
+                                relayed_tokens.push(oldest_signed_batch.token_contract);
+                            }
+                        }
+                        Ok(RelayOutcome::Skipped) => info!(
+                            "Batch {}/{} was not broadcast",
+                            oldest_signed_batch.token_contract, oldest_signed_batch.nonce
+                        ),
+                        Err(GravityError::RelayReverted(e)) => {
+                            if config.revert_blacklist_minutes > 0 {
+                                blacklist_batch(
+                                    oldest_signed_batch.token_contract,
+                                    oldest_signed_batch.nonce,
+                                );
+                            }
+                            error!(
+                                "Batch {}/{} reverted and would revert again if retried: {}",
+                                oldest_signed_batch.token_contract, oldest_signed_batch.nonce, e
+                            );
+                        }
+                        Err(e) => info!("Batch submission failed with {:?}", e),
                     }
                 } else {
                     info!(
-                        "Not relaying batch {}/{} due to it not being profitable. Cost: {}, Reward: {:?}",
-                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce, print_eth(cost.get_total()), reward_in_weth.map(print_eth),
+                        "Not relaying batch {}/{} due to it not being profitable. Cost: {} ETH, Fees: {}, Reward: {}",
+                        oldest_signed_batch.token_contract, oldest_signed_batch.nonce, print_eth(cost.get_total()), format_batch_fees(web3, &oldest_signed_batch).await, format_reward(reward_in_weth),
                     );
                 }
             }
         }
     }
+    sweep_profits(web3, ethereum_key, &relayed_tokens, &config.profit_sweep).await;
 }
@@ -0,0 +1,194 @@
+//! Many relayers watch the same chain and compute the same submissions, whoever is mined second
+//! pays for a reverted transaction. Before submitting, these checks look for an identical or newer
+//! submission that is already executed at head or already waiting in the Ethereum mempool and back
+//! off instead of racing it. If the pending transaction is dropped the next relayer loop will find
+//! nothing in flight and relay as usual. The mempool is read at most once per relayer loop,
+//! however many valsets and batches are checked against it.
+//!
+//! Confirms are public so anyone can broadcast a copy of a relay, a pending copy is only deferred to
+//! if it pays at least the gas price we would pay and passes a dry run, and only for
+//! MAX_BACKOFF_LOOPS relayer loops. Otherwise an underpriced copy, or our own stuck transaction,
+//! could hold up relaying until the batch times out.
+
+use clarity::Address as EthAddress;
+use clarity::Uint256;
+use ethereum_gravity::calldata::GravityCall;
+use ethereum_gravity::mempool::{
+    get_pending_gravity_calls, pending_call_succeeds, PendingGravityCall,
+};
+use ethereum_gravity::utils::{get_tx_batch_nonce, get_valset_nonce};
+use gravity_utils::num_conversion::print_gwei;
+use gravity_utils::types::{TransactionBatch, Valset};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use web30::client::Web3;
+
+/// How many relayer loops we back off for the same pending transaction before relaying anyway
+pub const MAX_BACKOFF_LOOPS: u32 = 5;
+
+lazy_static! {
+    /// The number of relayer loops we have backed off for each pending tx hash
+    static ref BACKOFF_LOOPS: RwLock<HashMap<String, u32>> = RwLock::new(HashMap::new());
+}
+
+/// The Gravity calls waiting in the Ethereum mempool, requested the first time a relayer loop
+/// checks for a duplicate relay and reused for the rest of that loop
+#[derive(Debug, Default)]
+pub struct MempoolSnapshot {
+    calls: Option<Vec<PendingGravityCall>>,
+}
+
+impl MempoolSnapshot {
+    async fn pending_calls(
+        &mut self,
+        web3: &Web3,
+        gravity_contract_address: EthAddress,
+    ) -> &[PendingGravityCall] {
+        if self.calls.is_none() {
+            let calls = match get_pending_gravity_calls(web3, gravity_contract_address).await {
+                Ok(calls) => calls,
+                Err(e) => {
+                    debug!("Unable to inspect the Ethereum mempool {:?}", e);
+                    Vec::new()
+                }
+            };
+            // transactions that left the mempool will not be seen again
+            BACKOFF_LOOPS
+                .write()
+                .unwrap()
+                .retain(|tx_hash, _| calls.iter().any(|c| &c.tx_hash == tx_hash));
+            self.calls = Some(calls);
+        }
+        self.calls.as_deref().unwrap_or_default()
+    }
+}
+
+/// Whether to defer to `pending` rather than relay ourselves at `our_gas_price`, counting the loop
+/// towards MAX_BACKOFF_LOOPS if so
+async fn should_back_off(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    pending: &PendingGravityCall,
+    our_gas_price: Uint256,
+) -> bool {
+    if pending.gas_price < our_gas_price {
+        info!(
+            "Pending tx {} pays {} gwei, less than our {} gwei, relaying anyway",
+            pending.tx_hash,
+            print_gwei(pending.gas_price),
+            print_gwei(our_gas_price)
+        );
+        return false;
+    }
+    let loops = BACKOFF_LOOPS
+        .read()
+        .unwrap()
+        .get(&pending.tx_hash)
+        .copied()
+        .unwrap_or(0);
+    if loops >= MAX_BACKOFF_LOOPS {
+        info!(
+            "Pending tx {} has not been mined in {} relayer loops, relaying anyway",
+            pending.tx_hash, loops
+        );
+        return false;
+    }
+    if !pending_call_succeeds(web3, gravity_contract_address, pending).await {
+        info!(
+            "Pending tx {} would revert, relaying anyway",
+            pending.tx_hash
+        );
+        return false;
+    }
+    BACKOFF_LOOPS
+        .write()
+        .unwrap()
+        .insert(pending.tx_hash.clone(), loops + 1);
+    true
+}
+
+/// Returns true if `batch` or a newer batch for the same token has already been executed or is
+/// pending on Ethereum at a gas price of at least `our_gas_price`, in which case submitting it
+/// would only revert
+pub async fn batch_relay_in_flight(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    our_ethereum_address: EthAddress,
+    batch: &TransactionBatch,
+    our_gas_price: Uint256,
+    mempool: &mut MempoolSnapshot,
+) -> bool {
+    match get_tx_batch_nonce(
+        gravity_contract_address,
+        batch.token_contract,
+        our_ethereum_address,
+        web3,
+    )
+    .await
+    {
+        Ok(nonce) if nonce >= batch.nonce => {
+            info!(
+                "Batch {}/{} has already been relayed, the contract is at nonce {}",
+                batch.token_contract, batch.nonce, nonce
+            );
+            return true;
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to check latest batch nonce at head {:?}", e),
+    }
+
+    for p in mempool.pending_calls(web3, gravity_contract_address).await {
+        if let GravityCall::SubmitBatch { batch: pending, .. } = &p.call {
+            if pending.token_contract == batch.token_contract
+                && pending.nonce >= batch.nonce
+                && should_back_off(web3, gravity_contract_address, p, our_gas_price).await
+            {
+                info!(
+                    "Batch {}/{} is already being relayed by {} in pending tx {}, backing off",
+                    pending.token_contract, pending.nonce, p.from, p.tx_hash
+                );
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Returns true if `valset` or a newer validator set has already been executed or is pending on
+/// Ethereum at a gas price of at least `our_gas_price`, in which case submitting it would only
+/// revert
+pub async fn valset_relay_in_flight(
+    web3: &Web3,
+    gravity_contract_address: EthAddress,
+    our_ethereum_address: EthAddress,
+    valset: &Valset,
+    our_gas_price: Uint256,
+    mempool: &mut MempoolSnapshot,
+) -> bool {
+    match get_valset_nonce(gravity_contract_address, our_ethereum_address, web3).await {
+        Ok(nonce) if nonce >= valset.nonce => {
+            info!(
+                "Valset {} has already been relayed, the contract is at nonce {}",
+                valset.nonce, nonce
+            );
+            return true;
+        }
+        Ok(_) => {}
+        Err(e) => warn!("Failed to check latest valset nonce at head {:?}", e),
+    }
+
+    for p in mempool.pending_calls(web3, gravity_contract_address).await {
+        if let GravityCall::ValsetUpdate { new_valset, .. } = &p.call {
+            if new_valset.nonce >= valset.nonce
+                && should_back_off(web3, gravity_contract_address, p, our_gas_price).await
+            {
+                info!(
+                    "Valset {} is already being relayed by {} in pending tx {}, backing off",
+                    new_valset.nonce, p.from, p.tx_hash
+                );
+                return true;
+            }
+        }
+    }
+    false
+}
@@ -1,23 +1,176 @@
+use clarity::utils::bytes_to_hex_str;
 use clarity::{Address, Uint256};
+use ethereum_gravity::utils::{get_valset_nonce, get_valset_nonce_at_block};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::types::event_signatures::*;
 use gravity_utils::types::{EthereumEvent, ValsetUpdatedEvent};
 use gravity_utils::{error::GravityError, types::Valset};
+use serde_json::json;
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
 use tonic::transport::Channel;
 use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+use web30::types::Log;
 
-/// This function finds the latest valset on the Gravity contract by looking back through the event
-/// history and finding the most recent ValsetUpdatedEvent. Most of the time this will be very fast
-/// as the latest update will be in recent blockchain history and the search moves from the present
-/// backwards in time. In the case that the validator set has not been updated for a very long time
-/// this will take longer.
+const BLOCKS_TO_SEARCH: u128 = 5_000u128;
+const BULK_QUERY_TIMEOUT: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    // The last valset found for each Gravity contract, reused until the contract's nonce changes
+    static ref LATEST_VALSET: RwLock<HashMap<Address, Valset>> = RwLock::new(HashMap::new());
+}
+
+/// This function finds the latest valset on the Gravity contract. The contract's current valset
+/// nonce is read first, if it matches the last valset found that one is returned without any log
+/// queries. Otherwise the ValsetUpdatedEvent for that nonce is located, first with a single
+/// eth_getLogs over the whole chain filtered by the indexed nonce, then by binary searching the
+/// contract's nonce at past blocks, and if the node supports neither by looking back through the
+/// event history from the present.
 pub async fn find_latest_valset(
     grpc_client: &mut GravityQueryClient<Channel>,
     gravity_contract_address: Address,
     web3: &Web3,
 ) -> Result<Valset, GravityError> {
-    const BLOCKS_TO_SEARCH: u128 = 5_000u128;
     let latest_block = web3.eth_block_number().await?;
+    // the contract is used as the caller, any address works for a view function
+    let nonce = get_valset_nonce(gravity_contract_address, gravity_contract_address, web3).await?;
+    if let Some(cached) = LATEST_VALSET.read().unwrap().get(&gravity_contract_address) {
+        if cached.nonce == nonce {
+            return Ok(cached.clone());
+        }
+    }
+
+    let event = match bulk_query_valset(web3, gravity_contract_address, nonce, latest_block).await {
+        Ok(Some(event)) => Some(event),
+        Ok(None) => None,
+        Err(e) => {
+            debug!("Bulk query for valset {} failed {:?}", nonce, e);
+            match binary_search_valset(web3, gravity_contract_address, nonce, latest_block).await {
+                Ok(event) => event,
+                Err(e) => {
+                    debug!("Binary search for valset {} failed {:?}", nonce, e);
+                    None
+                }
+            }
+        }
+    };
+    let event = match event {
+        Some(event) => event,
+        None => scan_for_valset(web3, gravity_contract_address, nonce, latest_block).await?,
+    };
+
+    let latest_eth_valset = Valset {
+        nonce: event.valset_nonce,
+        members: event.members,
+        reward_amount: event.reward_amount,
+        reward_token: event.reward_token,
+    };
+    let cosmos_chain_valset =
+        cosmos_gravity::query::get_valset(grpc_client, latest_eth_valset.nonce).await?;
+    check_if_valsets_differ(cosmos_chain_valset, &latest_eth_valset);
+    LATEST_VALSET
+        .write()
+        .unwrap()
+        .insert(gravity_contract_address, latest_eth_valset.clone());
+    Ok(latest_eth_valset)
+}
+
+/// The ValsetUpdatedEvent with `nonce` from a list of logs, if any
+fn find_event(logs: &[Log], nonce: u64) -> Option<ValsetUpdatedEvent> {
+    logs.iter()
+        .filter_map(|log| match ValsetUpdatedEvent::from_log(log) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                error!("Got valset event that we can't parse {}", e);
+                None
+            }
+        })
+        .find(|event| event.valset_nonce == nonce)
+}
+
+/// Queries the whole chain for the event in one request, the valset nonce is an indexed topic so
+/// the response is a single log. Many providers limit the block range of eth_getLogs, those
+/// return an error here
+async fn bulk_query_valset(
+    web3: &Web3,
+    gravity_contract_address: Address,
+    nonce: u64,
+    latest_block: Uint256,
+) -> Result<Option<ValsetUpdatedEvent>, GravityError> {
+    let mut nonce_topic = [0u8; 32];
+    nonce_topic[24..].copy_from_slice(&nonce.to_be_bytes());
+    let filter = json!({
+        "fromBlock": "0x0",
+        "toBlock": format!("{:#x}", latest_block),
+        "address": gravity_contract_address.to_string(),
+        "topics": [
+            format!(
+                "0x{}",
+                bytes_to_hex_str(&Keccak256::digest(VALSET_UPDATED_EVENT_SIG.as_bytes()))
+            ),
+            format!("0x{}", bytes_to_hex_str(&nonce_topic)),
+        ],
+    });
+    let client = HttpClient::new(&web3.get_url());
+    let logs: Vec<Log> = client
+        .request_method("eth_getLogs", vec![filter], BULK_QUERY_TIMEOUT)
+        .await?;
+    Ok(find_event(&logs, nonce))
+}
+
+/// Finds the block the contract's valset nonce became `nonce` by binary searching the nonce at
+/// past blocks, this needs an archive node. The genesis valset is set in the constructor and
+/// can't be searched for this way
+async fn binary_search_valset(
+    web3: &Web3,
+    gravity_contract_address: Address,
+    nonce: u64,
+    latest_block: Uint256,
+) -> Result<Option<ValsetUpdatedEvent>, GravityError> {
+    if nonce == 0 {
+        return Ok(None);
+    }
+    let mut low: Uint256 = 0u8.into();
+    let mut high = latest_block;
+    while low < high {
+        let mid = low + (high - low) / 2u8.into();
+        let mid_nonce = get_valset_nonce_at_block(
+            gravity_contract_address,
+            gravity_contract_address,
+            mid,
+            web3,
+        )
+        .await?;
+        if mid_nonce >= nonce {
+            high = mid;
+        } else {
+            low = mid + 1u8.into();
+        }
+    }
+    trace!("Valset {} was set in block {}", nonce, low);
+    let logs = web3
+        .check_for_events(
+            low,
+            Some(low),
+            vec![gravity_contract_address],
+            vec![VALSET_UPDATED_EVENT_SIG],
+        )
+        .await?;
+    Ok(find_event(&logs, nonce))
+}
+
+/// Looks back through the event history from the present for the event, most of the time this is
+/// fast as the latest update will be in recent history, but if the validator set has not been
+/// updated for a very long time this takes many requests
+async fn scan_for_valset(
+    web3: &Web3,
+    gravity_contract_address: Address,
+    nonce: u64,
+    latest_block: Uint256,
+) -> Result<ValsetUpdatedEvent, GravityError> {
     let mut current_block: Uint256 = latest_block;
 
     while current_block > 0u8.into() {
@@ -30,7 +183,7 @@ pub async fn find_latest_valset(
         } else {
             current_block - BLOCKS_TO_SEARCH.into()
         };
-        let mut all_valset_events = web3
+        let all_valset_events = web3
             .check_for_events(
                 end_search,
                 Some(current_block),
@@ -38,30 +191,11 @@ pub async fn find_latest_valset(
                 vec![VALSET_UPDATED_EVENT_SIG],
             )
             .await?;
-        // by default the lowest found valset goes first, we want the highest.
-        all_valset_events.reverse();
 
         trace!("Found events {:?}", all_valset_events);
 
-        // we take only the first event if we find any at all.
-        if !all_valset_events.is_empty() {
-            let event = &all_valset_events[0];
-            match ValsetUpdatedEvent::from_log(event) {
-                Ok(event) => {
-                    let latest_eth_valset = Valset {
-                        nonce: event.valset_nonce,
-                        members: event.members,
-                        reward_amount: event.reward_amount,
-                        reward_token: event.reward_token,
-                    };
-                    let cosmos_chain_valset =
-                        cosmos_gravity::query::get_valset(grpc_client, latest_eth_valset.nonce)
-                            .await?;
-                    check_if_valsets_differ(cosmos_chain_valset, &latest_eth_valset);
-                    return Ok(latest_eth_valset);
-                }
-                Err(e) => error!("Got valset event that we can't parse {}", e),
-            }
+        if let Some(event) = find_event(&all_valset_events, nonce) {
+            return Ok(event);
         }
         current_block = end_search;
     }
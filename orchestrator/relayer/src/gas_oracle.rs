@@ -0,0 +1,372 @@
+//! Gas price sources for relaying. By default the connected node's eth_gasPrice is used, operators
+//! may instead configure eth_feeHistory, Etherscan, Blocknative or a fixed price. Whatever the
+//! source the result is held within the configured min_gwei and max_gwei bounds, if the configured
+//! source fails the node's price is used instead. Only eth_feeHistory sets the priority fee, the
+//! other sources leave it to web30.
+
+use async_trait::async_trait;
+use clarity::Uint256;
+use ethereum_gravity::utils::RelayingGasPrice;
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::print_gwei;
+use gravity_utils::types::{GasOracleConfig, GasOracleSource, InclusionSpeed};
+use serde_json::Value;
+use std::cmp::min;
+use std::time::Duration;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+const ORACLE_TIMEOUT: Duration = Duration::from_secs(10);
+const ETHERSCAN_URL: &str = "https://api.etherscan.io/api";
+const BLOCKNATIVE_URL: &str = "https://api.blocknative.com/gasprices/blockprices";
+/// The number of recent blocks the FeeHistory source takes priority fees from
+const FEE_HISTORY_BLOCKS: u64 = 10;
+const WEI_PER_GWEI: f64 = 1_000_000_000f64;
+
+/// A source of Ethereum gas prices, not Send as web30's actix based client is not
+#[async_trait(?Send)]
+pub trait GasOracle {
+    /// A short name for this source used in logs
+    fn name(&self) -> &'static str;
+    /// The gas price to pay for a transaction included soon, in wei
+    async fn gas_price(&self, web3: &Web3) -> Result<RelayingGasPrice, GravityError>;
+}
+
+/// The connected node's eth_gasPrice plus 20%, the same margin the relaying cost estimates use to
+/// cover the maximum eip1559 base fee increase between blocks
+pub struct NodeOracle;
+
+#[async_trait(?Send)]
+impl GasOracle for NodeOracle {
+    fn name(&self) -> &'static str {
+        "node"
+    }
+
+    async fn gas_price(&self, web3: &Web3) -> Result<RelayingGasPrice, GravityError> {
+        let gas_price = web3.eth_gas_price().await?;
+        Ok((gas_price + (gas_price / 5u8.into())).into())
+    }
+}
+
+/// A priority fee from the connected node's eth_feeHistory, the given percentile of the fees paid in
+/// each recent block with the median across blocks taken so that a single unusual block does not
+/// skew it. The max fee is the next block's base fee with the same 20% margin as the node's price
+/// plus the priority fee
+pub struct FeeHistoryOracle {
+    pub speed: InclusionSpeed,
+}
+
+#[async_trait(?Send)]
+impl GasOracle for FeeHistoryOracle {
+    fn name(&self) -> &'static str {
+        "feeHistory"
+    }
+
+    async fn gas_price(&self, web3: &Web3) -> Result<RelayingGasPrice, GravityError> {
+        let client = HttpClient::new(&web3.get_url());
+        let history: Value = client
+            .request_method(
+                "eth_feeHistory",
+                (
+                    format!("{:#x}", FEE_HISTORY_BLOCKS),
+                    "latest",
+                    [reward_percentile(self.speed)],
+                ),
+                ORACLE_TIMEOUT,
+            )
+            .await?;
+        fee_history_price(&history)
+    }
+}
+
+/// The priority fee percentile paid in recent blocks that reaches `speed`
+fn reward_percentile(speed: InclusionSpeed) -> u8 {
+    match speed {
+        InclusionSpeed::NextBlock => 90,
+        InclusionSpeed::WithinFiveBlocks => 50,
+    }
+}
+
+fn fee_history_price(history: &Value) -> Result<RelayingGasPrice, GravityError> {
+    // baseFeePerGas has one more entry than the number of blocks requested, the base fee of
+    // the next block
+    let next_base_fee = history["baseFeePerGas"]
+        .as_array()
+        .and_then(|fees| fees.last())
+        .and_then(parse_hex_quantity)
+        .ok_or_else(|| bad_response("eth_feeHistory", "no base fee"))?;
+    let mut rewards: Vec<Uint256> = history["reward"]
+        .as_array()
+        .map(|r| r.iter().filter_map(|b| parse_hex_quantity(&b[0])).collect())
+        .unwrap_or_default();
+    rewards.sort();
+    let priority_fee = rewards
+        .get(rewards.len() / 2)
+        .cloned()
+        .unwrap_or_else(|| 0u8.into());
+    Ok(RelayingGasPrice {
+        max_fee: next_base_fee + (next_base_fee / 5u8.into()) + priority_fee,
+        priority_fee: Some(priority_fee),
+    })
+}
+
+/// The Etherscan gas tracker's proposed gas price
+pub struct EtherscanOracle {
+    pub url: String,
+    pub api_key: String,
+}
+
+#[async_trait(?Send)]
+impl GasOracle for EtherscanOracle {
+    fn name(&self) -> &'static str {
+        "etherscan"
+    }
+
+    async fn gas_price(&self, _web3: &Web3) -> Result<RelayingGasPrice, GravityError> {
+        let res = get_json(reqwest::Client::new().get(&self.url).query(&[
+            ("module", "gastracker"),
+            ("action", "gasoracle"),
+            ("apikey", &self.api_key),
+        ]))
+        .await?;
+        // prices are reported in gwei as strings, possibly with a fractional part
+        res["result"]["ProposeGasPrice"]
+            .as_str()
+            .and_then(|p| p.parse::<f64>().ok())
+            .map(|p| gwei_to_wei(p).into())
+            .ok_or_else(|| bad_response("Etherscan", &res.to_string()))
+    }
+}
+
+/// Blocknative's estimate for the next block at 90% confidence
+pub struct BlocknativeOracle {
+    pub url: String,
+    pub api_key: String,
+}
+
+#[async_trait(?Send)]
+impl GasOracle for BlocknativeOracle {
+    fn name(&self) -> &'static str {
+        "blocknative"
+    }
+
+    async fn gas_price(&self, _web3: &Web3) -> Result<RelayingGasPrice, GravityError> {
+        let res = get_json(
+            reqwest::Client::new()
+                .get(&self.url)
+                .header("Authorization", &self.api_key),
+        )
+        .await?;
+        let estimates = res["blockPrices"][0]["estimatedPrices"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        estimates
+            .iter()
+            .find(|e| e["confidence"].as_u64() == Some(90))
+            .or_else(|| estimates.first())
+            .and_then(|e| e["maxFeePerGas"].as_f64().or_else(|| e["price"].as_f64()))
+            .map(|p| gwei_to_wei(p).into())
+            .ok_or_else(|| bad_response("Blocknative", &res.to_string()))
+    }
+}
+
+/// A fixed gas price set by the operator
+pub struct FixedOracle {
+    pub price: Uint256,
+}
+
+#[async_trait(?Send)]
+impl GasOracle for FixedOracle {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    async fn gas_price(&self, _web3: &Web3) -> Result<RelayingGasPrice, GravityError> {
+        Ok(self.price.into())
+    }
+}
+
+/// Builds the gas oracle described by the config, errors if a required setting is missing
+pub fn build_gas_oracle(config: &GasOracleConfig) -> Result<Box<dyn GasOracle>, GravityError> {
+    let api_key = || {
+        config.api_key.clone().ok_or_else(|| {
+            GravityError::InvalidOptionsError(format!(
+                "The {:?} gas oracle requires an api_key",
+                config.source
+            ))
+        })
+    };
+    Ok(match config.source {
+        GasOracleSource::Node => Box::new(NodeOracle),
+        GasOracleSource::FeeHistory => Box::new(FeeHistoryOracle {
+            speed: config.inclusion_speed,
+        }),
+        GasOracleSource::Etherscan => Box::new(EtherscanOracle {
+            url: config
+                .url
+                .clone()
+                .unwrap_or_else(|| ETHERSCAN_URL.to_string()),
+            api_key: api_key()?,
+        }),
+        GasOracleSource::Blocknative => Box::new(BlocknativeOracle {
+            url: config
+                .url
+                .clone()
+                .unwrap_or_else(|| BLOCKNATIVE_URL.to_string()),
+            api_key: api_key()?,
+        }),
+        GasOracleSource::Fixed => match config.fixed_gwei {
+            Some(gwei) => Box::new(FixedOracle {
+                price: gwei_to_wei(gwei),
+            }),
+            None => {
+                return Err(GravityError::InvalidOptionsError(
+                    "The Fixed gas oracle requires fixed_gwei".to_string(),
+                ))
+            }
+        },
+    })
+}
+
+/// Gets the gas price to relay with from the configured oracle and applies the configured bounds.
+/// None means the configured oracle could not be used and callers should fall back to the node's
+/// own gas price estimate
+pub async fn get_relaying_gas_price(
+    web3: &Web3,
+    config: &GasOracleConfig,
+) -> Option<RelayingGasPrice> {
+    if config.source == GasOracleSource::Node
+        && config.min_gwei.is_none()
+        && config.max_gwei.is_none()
+    {
+        return None;
+    }
+    let oracle = match build_gas_oracle(config) {
+        Ok(o) => o,
+        Err(e) => {
+            error!("Invalid gas oracle config {:?}", e);
+            return None;
+        }
+    };
+    let price = match oracle.gas_price(web3).await {
+        Ok(p) => p,
+        Err(e) => {
+            warn!(
+                "Failed to get gas price from the {} gas oracle, using the node's {:?}",
+                oracle.name(),
+                e
+            );
+            match NodeOracle.gas_price(web3).await {
+                Ok(p) => p,
+                Err(_) => return None,
+            }
+        }
+    };
+    let bounded = apply_bounds(price.max_fee, config);
+    if bounded != price.max_fee {
+        warn!(
+            "Gas price {} gwei from the {} gas oracle is outside the configured bounds, using {} gwei",
+            print_gwei(price.max_fee),
+            oracle.name(),
+            print_gwei(bounded)
+        );
+    }
+    Some(RelayingGasPrice {
+        max_fee: bounded,
+        // the tip can never exceed the max fee
+        priority_fee: price.priority_fee.map(|p| min(p, bounded)),
+    })
+}
+
+fn apply_bounds(price: Uint256, config: &GasOracleConfig) -> Uint256 {
+    if let Some(min) = config.min_gwei.map(gwei_to_wei) {
+        if price < min {
+            return min;
+        }
+    }
+    if let Some(max) = config.max_gwei.map(gwei_to_wei) {
+        if price > max {
+            return max;
+        }
+    }
+    price
+}
+
+fn gwei_to_wei(gwei: f64) -> Uint256 {
+    ((gwei * WEI_PER_GWEI) as u128).into()
+}
+
+fn parse_hex_quantity(v: &Value) -> Option<Uint256> {
+    let s = v.as_str()?.trim_start_matches("0x");
+    u128::from_str_radix(s, 16).ok().map(|v| v.into())
+}
+
+fn bad_response(source: &str, detail: &str) -> GravityError {
+    GravityError::EthereumContractError(format!(
+        "Unexpected gas price response from {}: {}",
+        source, detail
+    ))
+}
+
+async fn get_json(request: reqwest::RequestBuilder) -> Result<Value, GravityError> {
+    let res = request
+        .timeout(ORACLE_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| GravityError::EthereumContractError(e.to_string()))?;
+    let body = res
+        .text()
+        .await
+        .map_err(|e| GravityError::EthereumContractError(e.to_string()))?;
+    serde_json::from_str(&body).map_err(|e| bad_response("gas oracle", &e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_price_bounds() {
+        let config = GasOracleConfig {
+            min_gwei: Some(2.0),
+            max_gwei: Some(150.5),
+            ..Default::default()
+        };
+        assert_eq!(apply_bounds(gwei_to_wei(1.0), &config), gwei_to_wei(2.0));
+        assert_eq!(apply_bounds(gwei_to_wei(30.0), &config), gwei_to_wei(30.0));
+        assert_eq!(
+            apply_bounds(gwei_to_wei(900.0), &config),
+            gwei_to_wei(150.5)
+        );
+        assert_eq!(gwei_to_wei(1.5), 1_500_000_000u64.into());
+
+        let missing_key = GasOracleConfig {
+            source: GasOracleSource::Etherscan,
+            ..Default::default()
+        };
+        assert!(build_gas_oracle(&missing_key).is_err());
+        let missing_price = GasOracleConfig {
+            source: GasOracleSource::Fixed,
+            ..Default::default()
+        };
+        assert!(build_gas_oracle(&missing_price).is_err());
+    }
+
+    #[test]
+    fn test_fee_history_price() {
+        let history = serde_json::json!({
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            "reward": [["0x3b9aca00"], ["0x77359400"], ["0xb2d05e00"]],
+        });
+        // next base fee of 2 gwei plus 20%, the median reward of 2 gwei
+        let price = fee_history_price(&history).unwrap();
+        assert_eq!(price.priority_fee, Some(gwei_to_wei(2.0)));
+        assert_eq!(price.max_fee, 4_400_000_000u64.into());
+        assert!(fee_history_price(&serde_json::json!({})).is_err());
+        assert!(
+            reward_percentile(InclusionSpeed::NextBlock)
+                > reward_percentile(InclusionSpeed::WithinFiveBlocks)
+        );
+    }
+}
@@ -1,10 +1,16 @@
 pub mod altruistic;
 pub mod batch_relaying;
+pub mod duplicate_relay;
 pub mod find_latest_valset;
+pub mod gas_oracle;
 pub mod ibc_auto_forwarding;
 pub mod logic_call_relaying;
 pub mod main_loop;
+pub mod profit_sweep;
 pub mod request_batches;
+pub mod revert_blacklist;
+pub mod screening;
+pub mod valset_history;
 pub mod valset_relaying;
 
 #[macro_use]
@@ -1,3 +1,4 @@
+use crate::gas_oracle::get_relaying_gas_price;
 use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
 use clarity::{address::Address as EthAddress, utils::bytes_to_hex_str};
 use clarity::{PrivateKey as EthPrivateKey, Uint256};
@@ -5,8 +6,10 @@ use cosmos_gravity::query::{get_latest_logic_calls, get_logic_call_signatures};
 use ethereum_gravity::message_signatures::encode_logic_call_confirm_hashed;
 use ethereum_gravity::{logic_call::send_eth_logic_call, utils::get_logic_call_nonce};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::debug_state::set_submittable_logic_call;
 use gravity_utils::num_conversion::{print_eth, print_gwei};
 use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::token_display::format_erc20;
 use gravity_utils::types::{LogicCall, RelayerConfig};
 use gravity_utils::types::{LogicCallConfirmResponse, Valset};
 use std::collections::HashMap;
@@ -108,6 +111,7 @@ pub async fn relay_logic_calls(
             );
         }
     }
+    set_submittable_logic_call(oldest_signed_call.clone());
     if oldest_signed_call.is_none() {
         trace!("Could not find Call with signatures! exiting");
         return;
@@ -132,6 +136,7 @@ pub async fn relay_logic_calls(
     let latest_ethereum_call = latest_ethereum_call.unwrap();
     let latest_cosmos_call_nonce = oldest_signed_call.clone().invalidation_nonce;
     if latest_cosmos_call_nonce > latest_ethereum_call {
+        let gas_price = get_relaying_gas_price(web3, &config.gas_oracle).await;
         let cost = ethereum_gravity::logic_call::estimate_logic_call_cost(
             current_valset.clone(),
             oldest_signed_call.clone(),
@@ -140,6 +145,8 @@ pub async fn relay_logic_calls(
             gravity_contract_address,
             gravity_id.clone(),
             ethereum_key.to_address(),
+            gas_price,
+            config.trim_signatures,
         )
         .await;
         if cost.is_err() {
@@ -169,6 +176,20 @@ pub async fn relay_logic_calls(
         };
 
         if should_relay {
+            if config.dry_run {
+                let mut fees = Vec::new();
+                for fee in &oldest_signed_call.fees {
+                    fees.push(format_erc20(web3, fee.token_contract_address, fee.amount).await);
+                }
+                info!(
+                    "Dry run, would relay logic call {}/{} Expected Cost: {} ETH Fees: {}",
+                    bytes_to_hex_str(&oldest_signed_call.invalidation_id),
+                    oldest_signed_call.invalidation_nonce,
+                    print_eth(cost.get_total()),
+                    fees.join(", ")
+                );
+                return;
+            }
             let res = send_eth_logic_call(
                 current_valset,
                 oldest_signed_call,
@@ -178,6 +199,9 @@ pub async fn relay_logic_calls(
                 gravity_contract_address,
                 gravity_id.clone(),
                 ethereum_key,
+                gas_price,
+                config.trim_signatures,
+                &config.private_relay,
             )
             .await;
             if res.is_err() {
@@ -2,6 +2,8 @@ use crate::altruistic::{
     gas_tracker_loop, get_acceptable_gas_price, get_current_gas_price, get_num_gas_tracker_samples,
     update_gas_history_samples,
 };
+use crate::duplicate_relay::MempoolSnapshot;
+use crate::gas_oracle::build_gas_oracle;
 use crate::ibc_auto_forwarding::ibc_auto_forward_loop;
 use crate::request_batches::request_batches;
 use crate::{
@@ -10,14 +12,18 @@ use crate::{
 };
 use clarity::address::Address as EthAddress;
 use clarity::PrivateKey as EthPrivateKey;
+use cosmos_gravity::safe_mode::check_bridge_halt;
 use deep_space::{Coin, Contact, CosmosPrivateKey};
 use futures::future::join3;
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::debug_state::set_ethereum_valset;
+use gravity_utils::log_context::in_loop;
 use gravity_utils::num_conversion::print_gwei;
 use gravity_utils::types::{BatchRelayingMode, RelayerConfig, ValsetRelayingMode};
 use std::time::{Duration, Instant};
 use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
+use tracing::instrument;
 use web30::client::Web3;
 
 /// The general network request and operation timeout
@@ -44,29 +50,38 @@ pub async fn all_relayer_loops(
             config.gas_tracker_loop_speed
         )
     }
+    if let Err(e) = build_gas_oracle(&config.gas_oracle) {
+        panic!("Invalid configured gas_oracle: {}", e)
+    }
     // Update the tracker with the now-known desired number of samples
     update_gas_history_samples(config.altruistic_gas_price_samples as usize);
     debug!("Starting all relayer loops");
 
-    let a = relayer_main_loop(
-        ethereum_key,
-        cosmos_key,
-        fee.clone(),
-        web3.clone(),
-        contact.clone(),
-        grpc_client.clone(),
-        gravity_contract_address,
-        gravity_id,
-        config.clone(),
+    let a = in_loop(
+        "relayer",
+        relayer_main_loop(
+            ethereum_key,
+            cosmos_key,
+            fee.clone(),
+            web3.clone(),
+            contact.clone(),
+            grpc_client.clone(),
+            gravity_contract_address,
+            gravity_id,
+            config.clone(),
+        ),
     );
-    let b = ibc_auto_forward_loop(
-        cosmos_key,
-        &contact,
-        grpc_client.clone(),
-        fee.clone(),
-        config.clone(),
+    let b = in_loop(
+        "ibc_auto_forward",
+        ibc_auto_forward_loop(
+            cosmos_key,
+            &contact,
+            grpc_client.clone(),
+            fee.clone(),
+            config.clone(),
+        ),
     );
-    let c = gas_tracker_loop(&web3, config.clone());
+    let c = in_loop("gas_tracker", gas_tracker_loop(&web3, config.clone()));
 
     join3(a, b, c).await;
 }
@@ -129,7 +144,11 @@ pub async fn relayer_main_loop(
 /// * Valset Relaying
 /// * Batch Relaying
 /// * Logic Call Relaying
+///
+/// While the bridge is halted by governance only valsets are relayed, validators keep signing them
+/// and the contract must keep following the validator set so the bridge can be resumed
 #[allow(clippy::too_many_arguments)]
+#[instrument(skip_all)]
 pub async fn single_relayer_iteration(
     ethereum_key: EthPrivateKey,
     cosmos_key: Option<CosmosPrivateKey>,
@@ -143,7 +162,9 @@ pub async fn single_relayer_iteration(
     should_relay_altruistic: bool,
 ) {
     let mut grpc_client: GravityQueryClient<Channel> = grpc_client.clone();
-    if let (Some(cosmos_key), Some(cosmos_fee)) = (cosmos_key, cosmos_fee.clone()) {
+    // while the bridge is halted batches are neither requested nor relayed, valsets still are
+    let halted = check_bridge_halt(&mut grpc_client).await;
+    if let (Some(cosmos_key), Some(cosmos_fee), false) = (cosmos_key, cosmos_fee.clone(), halted) {
         // Batches are only requested if it is a good time to do so, no checks needed here
         request_batches(
             contact,
@@ -170,6 +191,8 @@ pub async fn single_relayer_iteration(
         return;
     }
     let current_valset = current_valset.unwrap();
+    set_ethereum_valset(&current_valset);
+    let mut mempool = MempoolSnapshot::default();
 
     if should_relay_valsets {
         relay_valsets(
@@ -180,9 +203,15 @@ pub async fn single_relayer_iteration(
             gravity_contract_address,
             gravity_id.to_string(),
             relayer_config.clone(),
+            &mut mempool,
         )
         .await;
     }
+    if halted {
+        trace!("Bridge halted, skipping batch and logic call relaying");
+        return;
+    }
+
     let current_gas_samples = get_num_gas_tracker_samples();
     let delay_altruistic_relayer = relayer_config.batch_relaying_mode
         == BatchRelayingMode::Altruistic
@@ -204,6 +233,7 @@ pub async fn single_relayer_iteration(
             gravity_contract_address,
             gravity_id.to_string(),
             relayer_config.clone(),
+            &mut mempool,
         )
         .await;
     }
@@ -0,0 +1,127 @@
+//! Sweeps batch fees out of the relayer's hot key. When a cold address is configured the relayer's
+//! whole balance of each relayed batch's token is sent there once the relayer loop has submitted
+//! its batches, along with any ETH above the configured float, so the relaying host only ever
+//! holds what it needs to pay for gas. The whole balance is sent, not only the batch's fees, so
+//! anything else the hot key held of that token is swept too. Sweeping waits on its own
+//! transactions, it runs after the loop's batches rather than between them so that it can not hold
+//! up relaying, and not alongside them since they are sent from the same key and would race for
+//! its nonces
+
+use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
+use clarity::address::Address as EthAddress;
+use clarity::PrivateKey as EthPrivateKey;
+use clarity::Uint256;
+use gravity_utils::error::GravityError;
+use gravity_utils::num_conversion::print_eth;
+use gravity_utils::types::ProfitSweepConfig;
+use web30::client::Web3;
+
+/// Gas for a plain ETH transfer
+const ETH_TRANSFER_GAS: u64 = 21_000;
+
+/// Sends the relayer's balance of each of `erc20s`, and ETH above the float, to the cold address if
+/// one is configured. Failures are logged, the proceeds stay in the hot key until the next sweep
+pub async fn sweep_profits(
+    web3: &Web3,
+    ethereum_key: EthPrivateKey,
+    erc20s: &[EthAddress],
+    config: &ProfitSweepConfig,
+) {
+    let cold_address = match config.cold_address {
+        Some(a) => a,
+        None => return,
+    };
+    if erc20s.is_empty() {
+        return;
+    }
+    for erc20 in erc20s {
+        if let Err(e) = sweep_erc20(web3, ethereum_key, *erc20, cold_address).await {
+            warn!(
+                "Failed to sweep {} fees to {}, they will be swept after the next relay {:?}",
+                erc20, cold_address, e
+            );
+        }
+    }
+    if let Some(float) = config.eth_float {
+        if let Err(e) = sweep_eth(web3, ethereum_key, cold_address, float).await {
+            warn!("Failed to sweep ETH to {} {:?}", cold_address, e);
+        }
+    }
+}
+
+async fn sweep_erc20(
+    web3: &Web3,
+    ethereum_key: EthPrivateKey,
+    erc20: EthAddress,
+    cold_address: EthAddress,
+) -> Result<(), GravityError> {
+    let balance = web3
+        .get_erc20_balance(erc20, ethereum_key.to_address())
+        .await?;
+    if balance == 0u8.into() {
+        return Ok(());
+    }
+    web3.erc20_send(
+        balance,
+        cold_address,
+        erc20,
+        ethereum_key,
+        Some(ETH_SUBMIT_WAIT_TIME),
+        vec![],
+    )
+    .await?;
+    info!("Swept {} of {} to {}", balance, erc20, cold_address);
+    Ok(())
+}
+
+async fn sweep_eth(
+    web3: &Web3,
+    ethereum_key: EthPrivateKey,
+    cold_address: EthAddress,
+    float: Uint256,
+) -> Result<(), GravityError> {
+    let balance = web3.eth_get_balance(ethereum_key.to_address()).await?;
+    let gas_price = web3.eth_gas_price().await?;
+    // twice the current price leaves room for the price to rise before inclusion
+    let gas_cost = gas_price * ETH_TRANSFER_GAS.into() * 2u8.into();
+    let amount = match sweepable_eth(balance, float, gas_cost) {
+        Some(a) => a,
+        None => return Ok(()),
+    };
+    let txid = web3
+        .send_prepared_transaction(
+            web3.prepare_transaction(cold_address, Vec::new(), amount, ethereum_key, vec![])
+                .await?,
+        )
+        .await?;
+    web3.wait_for_transaction(txid, ETH_SUBMIT_WAIT_TIME, None)
+        .await?;
+    info!("Swept {} ETH to {}", print_eth(amount), cold_address);
+    Ok(())
+}
+
+/// The ETH above the float once the transfer itself is paid for, None if there is nothing to sweep
+fn sweepable_eth(balance: Uint256, float: Uint256, gas_cost: Uint256) -> Option<Uint256> {
+    let keep = float + gas_cost;
+    if balance > keep {
+        Some(balance - keep)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweepable_eth() {
+        let float: Uint256 = 1000u32.into();
+        assert_eq!(
+            sweepable_eth(1500u32.into(), float, 100u32.into()),
+            Some(400u32.into())
+        );
+        assert_eq!(sweepable_eth(1100u32.into(), float, 100u32.into()), None);
+        assert_eq!(sweepable_eth(500u32.into(), float, 100u32.into()), None);
+    }
+}
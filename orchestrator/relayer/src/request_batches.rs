@@ -6,13 +6,16 @@
 use std::time::Duration;
 
 use crate::altruistic::get_acceptable_gas_price;
+use crate::batch_relaying::get_cost_with_margin;
+use clarity::constants::zero_address;
 use clarity::Address as EthAddress;
 use clarity::Uint256;
-use cosmos_gravity::query::get_erc20_to_denom;
 use cosmos_gravity::query::get_pending_batch_fees;
+use cosmos_gravity::query_cache::get_erc20_to_denom_cached;
 use cosmos_gravity::send::send_request_batch;
 use deep_space::{Coin, Contact, PrivateKey};
 use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::error::GravityError;
 use gravity_utils::num_conversion::print_eth;
 use gravity_utils::prices::get_weth_price_with_retries;
 use gravity_utils::types::BatchRequestMode;
@@ -21,6 +24,48 @@ use tokio::time::sleep as delay_for;
 use tonic::transport::Channel;
 use web30::client::Web3;
 
+// TODO: this is a heuristic that needs to be dialed in
+// it's not easy to really estimate the actual cost of a batch
+// before we have an eth tx to simulate it with, so we're just
+// assuming a base batch starts at 200k gas
+pub const BATCH_GAS: u128 = 200_000;
+/// The number of sends assumed to share the cost of a batch when suggesting a bridge fee
+/// to users who have not picked one themselves
+pub const ASSUMED_SENDS_PER_BATCH: u128 = 4;
+/// The margin relayers are assumed to require over the cost of a batch, this matches the
+/// default relayer config
+pub const ASSUMED_RELAYER_MARGIN: f32 = 1.1;
+
+/// Suggests a bridge_fee for a send of `amount` of the given erc20, this is the sends share
+/// of the current cost of relaying a batch converted into the token using Uniswap prices.
+/// The amount being sent is used to determine the token price, so it must be non-zero
+pub async fn get_suggested_bridge_fee(
+    web30: &Web3,
+    erc20: EthAddress,
+    amount: Uint256,
+) -> Result<Uint256, GravityError> {
+    if amount == 0u8.into() {
+        return Err(GravityError::InvalidOptionsError(
+            "Can not suggest a bridge fee for a zero amount".to_string(),
+        ));
+    }
+    let eth_gas_price = web30.eth_gas_price().await?;
+    let batch_cost = eth_gas_price * BATCH_GAS.into();
+    let our_share =
+        get_cost_with_margin(batch_cost, ASSUMED_RELAYER_MARGIN) / ASSUMED_SENDS_PER_BATCH.into();
+
+    // the address used to simulate Uniswap swaps, this does not need to hold any funds
+    let value = get_weth_price_with_retries(zero_address(), erc20, amount, web30).await?;
+    if value == 0u8.into() {
+        return Err(GravityError::InvalidOptionsError(format!(
+            "{} has no value on Uniswap, a bridge fee can not be suggested",
+            erc20
+        )));
+    }
+    // round up, relayers won't accept a fee that's a little short
+    Ok((our_share * amount + value - 1u8.into()) / value)
+}
+
 pub async fn request_batches(
     contact: &Contact,
     web30: &Web3,
@@ -37,11 +82,6 @@ pub async fn request_batches(
     } else {
         Some(request_fee)
     };
-    // TODO: this is a heuristic that needs to be dialed in
-    // it's not easy to really estimate the actual cost of a batch
-    // before we have an eth tx to simulate it with, so we're just
-    // assuming a base batch starts at 200k gas
-    const BATCH_GAS: u128 = 200_000;
     // get the gas price once
     let eth_gas_price = web30.eth_gas_price().await;
     if let Err(e) = eth_gas_price {
@@ -64,7 +104,7 @@ pub async fn request_batches(
     for fee in batch_fees.batch_fees {
         let total_fee: Uint256 = fee.total_fees.parse().unwrap();
         let token: EthAddress = fee.token.parse().unwrap();
-        let denom = get_erc20_to_denom(grpc_client, token).await;
+        let denom = get_erc20_to_denom_cached(grpc_client, token).await;
         if let Err(e) = denom {
             error!(
                 "Failed to lookup erc20 {} for batch with {:?}",
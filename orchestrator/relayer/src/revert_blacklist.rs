@@ -0,0 +1,80 @@
+//! Batches whose relay reverted for a reason retrying will not fix, such as a token transfer that
+//! always fails. Such a batch passes gas estimation against the pending state but reverts once
+//! mined, so without this it would be submitted again every relayer loop and could consume the
+//! relayer's entire gas budget. Blacklisted batches are skipped until the configured expiry, or
+//! until they are cleared with `gbt relayer retry-batch`
+
+use clarity::Address as EthAddress;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    static ref REVERT_BLACKLIST: RwLock<HashMap<(EthAddress, u64), Instant>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Records that relaying batch `nonce` of `token` reverted and should not be retried
+pub fn blacklist_batch(token: EthAddress, nonce: u64) {
+    REVERT_BLACKLIST
+        .write()
+        .unwrap()
+        .insert((token, nonce), Instant::now());
+}
+
+/// Returns true if batch `nonce` of `token` reverted less than `expiry` ago, expired entries are
+/// removed
+pub fn batch_blacklisted(token: EthAddress, nonce: u64, expiry: Duration) -> bool {
+    let mut blacklist = REVERT_BLACKLIST.write().unwrap();
+    match blacklist.get(&(token, nonce)) {
+        Some(at) if at.elapsed() < expiry => true,
+        Some(_) => {
+            blacklist.remove(&(token, nonce));
+            false
+        }
+        None => false,
+    }
+}
+
+/// Removes batches from the blacklist so they are retried on the next relayer loop, all of them if
+/// `token` is None, every nonce of `token` if `nonce` is None. Returns how many were removed
+pub fn clear_blacklist(token: Option<EthAddress>, nonce: Option<u64>) -> usize {
+    let mut blacklist = REVERT_BLACKLIST.write().unwrap();
+    let before = blacklist.len();
+    blacklist.retain(|(t, n), _| {
+        let token_matches = token.map(|token| token == *t).unwrap_or(true);
+        let nonce_matches = nonce.map(|nonce| nonce == *n).unwrap_or(true);
+        !(token_matches && nonce_matches)
+    });
+    before - blacklist.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revert_blacklist() {
+        let token: EthAddress = "0x0412C7c846bb6b7DC462CF6B453f76D8440b2609"
+            .parse()
+            .unwrap();
+        let other: EthAddress = "0xD7600ae27C99988A6CD360234062b540F88ECA43"
+            .parse()
+            .unwrap();
+        let hour = Duration::from_secs(3600);
+        blacklist_batch(token, 5);
+        blacklist_batch(token, 6);
+        blacklist_batch(other, 5);
+        assert!(batch_blacklisted(token, 5, hour));
+        assert!(!batch_blacklisted(token, 7, hour));
+
+        assert_eq!(clear_blacklist(Some(token), Some(5)), 1);
+        assert!(!batch_blacklisted(token, 5, hour));
+        assert!(batch_blacklisted(token, 6, hour));
+
+        // expired entries are dropped
+        assert!(!batch_blacklisted(token, 6, Duration::from_secs(0)));
+        assert_eq!(clear_blacklist(None, None), 1);
+        assert!(!batch_blacklisted(other, 5, hour));
+    }
+}
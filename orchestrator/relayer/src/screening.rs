@@ -0,0 +1,220 @@
+//! Screening of batch destinations before relaying. Operators may configure a local denylist file,
+//! an external screening API or both, a batch is only relayed if none of its destinations are
+//! flagged by any of them. Skipped batches remain valid and can be relayed by anyone else
+
+use async_trait::async_trait;
+use clarity::Address as EthAddress;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::{ScreeningConfig, TransactionBatch};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::time::Duration;
+
+const SCREENING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A check of Ethereum addresses the relayer must not pay out to
+#[async_trait]
+pub trait AddressScreener: Send + Sync {
+    /// A short name for this screener used in logs
+    fn name(&self) -> &'static str;
+    /// Returns the addresses out of `addresses` that must not be relayed to
+    async fn screen(&self, addresses: &[EthAddress]) -> Result<Vec<EthAddress>, GravityError>;
+}
+
+/// A local file of denied addresses, read on every check so that it can be updated in place
+pub struct DenylistScreener {
+    pub path: String,
+}
+
+#[async_trait]
+impl AddressScreener for DenylistScreener {
+    fn name(&self) -> &'static str {
+        "denylist"
+    }
+
+    async fn screen(&self, addresses: &[EthAddress]) -> Result<Vec<EthAddress>, GravityError> {
+        let contents = fs::read_to_string(&self.path).map_err(|e| {
+            GravityError::InvalidOptionsError(format!(
+                "Failed to read denylist {} {:?}",
+                self.path, e
+            ))
+        })?;
+        let denied = parse_denylist(&contents)?;
+        Ok(addresses
+            .iter()
+            .filter(|a| denied.contains(a))
+            .cloned()
+            .collect())
+    }
+}
+
+/// An external screening service, it is POSTed {"addresses": [...]} and responds with
+/// {"blocked": [...]}
+pub struct ApiScreener {
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+#[async_trait]
+impl AddressScreener for ApiScreener {
+    fn name(&self) -> &'static str {
+        "api"
+    }
+
+    async fn screen(&self, addresses: &[EthAddress]) -> Result<Vec<EthAddress>, GravityError> {
+        let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+        let mut request = reqwest::Client::new()
+            .post(&self.url)
+            .timeout(SCREENING_TIMEOUT)
+            .header("Content-Type", "application/json")
+            .body(json!({ "addresses": addresses }).to_string());
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", key);
+        }
+        let res = request.send().await.map_err(bad_response)?;
+        if !res.status().is_success() {
+            return Err(GravityError::InvalidOptionsError(format!(
+                "Screening API responded with {}",
+                res.status()
+            )));
+        }
+        let body = res.text().await.map_err(bad_response)?;
+        let body: Value = serde_json::from_str(&body).map_err(|e| {
+            GravityError::InvalidOptionsError(format!("Invalid screening API response {:?}", e))
+        })?;
+        let blocked = body["blocked"].as_array().ok_or_else(|| {
+            GravityError::InvalidOptionsError(format!("Unexpected screening API response {}", body))
+        })?;
+        let mut out = Vec::new();
+        for b in blocked {
+            match b.as_str().map(parse_address) {
+                Some(Ok(a)) => out.push(a),
+                _ => {
+                    return Err(GravityError::InvalidOptionsError(format!(
+                        "Screening API returned an invalid address {}",
+                        b
+                    )))
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Parses an address regardless of case, lists of addresses rarely preserve EIP-55 checksums
+fn parse_address(address: &str) -> Result<EthAddress, clarity::Error> {
+    address.to_lowercase().parse()
+}
+
+fn bad_response(e: reqwest::Error) -> GravityError {
+    GravityError::InvalidOptionsError(format!("Screening API request failed {:?}", e))
+}
+
+/// Parses a denylist of one address per line, blank lines and lines starting with # are skipped.
+/// An invalid line is an error rather than being ignored, so that a typo can't let an address through
+pub fn parse_denylist(contents: &str) -> Result<HashSet<EthAddress>, GravityError> {
+    let mut denied = HashSet::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_address(line) {
+            Ok(a) => {
+                denied.insert(a);
+            }
+            Err(_) => {
+                return Err(GravityError::InvalidOptionsError(format!(
+                    "Invalid address {:?} on line {} of the denylist",
+                    line,
+                    i + 1
+                )))
+            }
+        }
+    }
+    Ok(denied)
+}
+
+/// Builds the screeners described by the config, empty if screening is disabled
+pub fn build_screeners(config: &ScreeningConfig) -> Vec<Box<dyn AddressScreener>> {
+    let mut screeners: Vec<Box<dyn AddressScreener>> = Vec::new();
+    if let Some(path) = &config.denylist_file {
+        screeners.push(Box::new(DenylistScreener { path: path.clone() }));
+    }
+    if let Some(url) = &config.api_url {
+        screeners.push(Box::new(ApiScreener {
+            url: url.clone(),
+            api_key: config.api_key.clone(),
+        }));
+    }
+    screeners
+}
+
+/// Checks the destinations of a batch against every configured screener, returns false if the
+/// batch must not be relayed
+pub async fn batch_passes_screening(batch: &TransactionBatch, config: &ScreeningConfig) -> bool {
+    let screeners = build_screeners(config);
+    if screeners.is_empty() {
+        return true;
+    }
+    let destinations: Vec<EthAddress> = batch
+        .transactions
+        .iter()
+        .map(|t| t.destination)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    for screener in screeners {
+        match screener.screen(&destinations).await {
+            Ok(flagged) if flagged.is_empty() => {}
+            Ok(flagged) => {
+                warn!(
+                    "Not relaying batch {}/{}, the {} screener flagged destinations {:?}",
+                    batch.token_contract,
+                    batch.nonce,
+                    screener.name(),
+                    flagged
+                );
+                return false;
+            }
+            Err(e) if config.allow_on_error => warn!(
+                "Failed to screen batch {}/{} with the {} screener, relaying anyway {:?}",
+                batch.token_contract,
+                batch.nonce,
+                screener.name(),
+                e
+            ),
+            Err(e) => {
+                error!(
+                    "Not relaying batch {}/{}, the {} screener failed {:?}",
+                    batch.token_contract,
+                    batch.nonce,
+                    screener.name(),
+                    e
+                );
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_denylist() {
+        let contents = "# sanctioned\n0xD041D5E6A2BD9A4D5F4E3F8E0C7B2E8D9FEA9B51\n\n  0x0000000000000000000000000000000000000001  \n";
+        let denied = parse_denylist(contents).unwrap();
+        assert_eq!(denied.len(), 2);
+        assert!(denied.contains(
+            &"0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap()
+        ));
+        assert!(parse_denylist("0x1234\n").is_err());
+        assert!(build_screeners(&ScreeningConfig::default()).is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! Rebuilds the validator set history of the Gravity contract purely from its ValsetUpdatedEvent
+//! logs on Ethereum. This is an independent check of the Cosmos chain's record of which validator
+//! sets were relayed, and a way to recover that history when no Cosmos archive node is available.
+
+use clarity::{Address, Uint256};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::error::GravityError;
+use gravity_utils::types::event_signatures::VALSET_UPDATED_EVENT_SIG;
+use gravity_utils::types::{EthereumEvent, Valset, ValsetUpdatedEvent};
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// The most blocks searched for valset updates in a single request
+pub const BLOCKS_PER_SEARCH: u64 = 5_000;
+
+/// How a validator set recorded on Ethereum compares to the Cosmos chain's record of the same nonce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValsetComparison {
+    Matches,
+    /// the same members and powers in a different order
+    SortingDiffers,
+    /// the members, powers or reward differ, possibly a highjacking update
+    Differs,
+    /// the chain does not store this nonce, it may have been pruned or never existed
+    MissingOnCosmos,
+}
+
+/// A validator set update as it happened on Ethereum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoricalValset {
+    pub valset: Valset,
+    pub block_height: Uint256,
+    pub event_nonce: u64,
+}
+
+impl From<ValsetUpdatedEvent> for HistoricalValset {
+    fn from(event: ValsetUpdatedEvent) -> Self {
+        HistoricalValset {
+            valset: Valset {
+                nonce: event.valset_nonce,
+                members: event.members,
+                reward_amount: event.reward_amount,
+                reward_token: event.reward_token,
+            },
+            block_height: event.block_height,
+            event_nonce: event.event_nonce,
+        }
+    }
+}
+
+/// Every validator set update emitted by the Gravity contract between `start_block` and
+/// `end_block` inclusive, ordered by valset nonce. The first entry is the valset set in the
+/// contract constructor if the contract was deployed in the searched range
+pub async fn reconstruct_valset_history(
+    web3: &Web3,
+    gravity_contract_address: Address,
+    start_block: Uint256,
+    end_block: Uint256,
+) -> Result<Vec<HistoricalValset>, GravityError> {
+    let mut history: Vec<HistoricalValset> = Vec::new();
+    let mut current_block = start_block;
+    while current_block <= end_block {
+        let end_search = if end_block - current_block >= BLOCKS_PER_SEARCH.into() {
+            current_block + (BLOCKS_PER_SEARCH - 1).into()
+        } else {
+            end_block
+        };
+        trace!(
+            "Searching blocks {} to {} for valset updates",
+            current_block,
+            end_search
+        );
+        let logs = web3
+            .check_for_events(
+                current_block,
+                Some(end_search),
+                vec![gravity_contract_address],
+                vec![VALSET_UPDATED_EVENT_SIG],
+            )
+            .await?;
+        for event in ValsetUpdatedEvent::from_logs(&logs)? {
+            history.push(event.into());
+        }
+        current_block = end_search + 1u8.into();
+    }
+    history.sort_by_key(|h| h.valset.nonce);
+    Ok(history)
+}
+
+/// Nonces in `history` that do not increase over the previous update, the contract rejects these
+/// so any found mean the logs were read from the wrong contract or are corrupt
+pub fn out_of_order_nonces(history: &[HistoricalValset]) -> Vec<u64> {
+    let mut by_block = history.to_vec();
+    by_block.sort_by(|a, b| {
+        a.block_height
+            .cmp(&b.block_height)
+            .then(a.event_nonce.cmp(&b.event_nonce))
+    });
+    by_block
+        .windows(2)
+        .filter(|w| w[1].valset.nonce <= w[0].valset.nonce)
+        .map(|w| w[1].valset.nonce)
+        .collect()
+}
+
+/// Compares the valset Ethereum holds for a nonce to the one Cosmos stores for it. The Gravity
+/// contract checks signatures in the order given, so a different order only costs gas
+pub fn compare_valsets(
+    cosmos_valset: Option<&Valset>,
+    ethereum_valset: &Valset,
+) -> ValsetComparison {
+    let cosmos_valset = match cosmos_valset {
+        Some(v) => v,
+        None => return ValsetComparison::MissingOnCosmos,
+    };
+    if cosmos_valset == ethereum_valset {
+        return ValsetComparison::Matches;
+    }
+    let mut c_members = cosmos_valset.members.clone();
+    let mut e_members = ethereum_valset.members.clone();
+    c_members.sort();
+    e_members.sort();
+    if c_members == e_members
+        && cosmos_valset.reward_amount == ethereum_valset.reward_amount
+        && cosmos_valset.reward_token == ethereum_valset.reward_token
+    {
+        ValsetComparison::SortingDiffers
+    } else {
+        ValsetComparison::Differs
+    }
+}
+
+/// Compares every valset in `history` to the Cosmos chain's record of the same nonce
+pub async fn compare_with_cosmos(
+    grpc_client: &mut GravityQueryClient<Channel>,
+    history: &[HistoricalValset],
+) -> Result<Vec<(u64, ValsetComparison)>, GravityError> {
+    let mut results = Vec::new();
+    for h in history {
+        let cosmos_valset = cosmos_gravity::query::get_valset(grpc_client, h.valset.nonce).await?;
+        results.push((
+            h.valset.nonce,
+            compare_valsets(cosmos_valset.as_ref(), &h.valset),
+        ));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gravity_utils::types::ValsetMember;
+
+    fn historical(nonce: u64, block: u64, members: Vec<ValsetMember>) -> HistoricalValset {
+        HistoricalValset {
+            valset: Valset {
+                nonce,
+                members,
+                reward_amount: 0u8.into(),
+                reward_token: None,
+            },
+            block_height: block.into(),
+            event_nonce: block,
+        }
+    }
+
+    #[test]
+    fn test_compare_valsets() {
+        let member = |i: u8, power: u64| ValsetMember {
+            power,
+            eth_address: Address::from_slice(&[i; 20]).unwrap(),
+        };
+        let eth = historical(1, 1, vec![member(1, 100), member(2, 200)]).valset;
+        let reordered = historical(1, 1, vec![member(2, 200), member(1, 100)]).valset;
+        let changed = historical(1, 1, vec![member(1, 100), member(3, 200)]).valset;
+        assert_eq!(compare_valsets(Some(&eth), &eth), ValsetComparison::Matches);
+        assert_eq!(
+            compare_valsets(Some(&reordered), &eth),
+            ValsetComparison::SortingDiffers
+        );
+        assert_eq!(
+            compare_valsets(Some(&changed), &eth),
+            ValsetComparison::Differs
+        );
+        assert_eq!(
+            compare_valsets(None, &eth),
+            ValsetComparison::MissingOnCosmos
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_nonces() {
+        let history = vec![
+            historical(0, 10, vec![]),
+            historical(3, 20, vec![]),
+            historical(7, 30, vec![]),
+        ];
+        assert!(out_of_order_nonces(&history).is_empty());
+        let history = vec![
+            historical(0, 10, vec![]),
+            historical(7, 20, vec![]),
+            historical(3, 30, vec![]),
+        ];
+        assert_eq!(out_of_order_nonces(&history), vec![3]);
+    }
+}
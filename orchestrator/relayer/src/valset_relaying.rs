@@ -13,12 +13,15 @@ use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
 use gravity_utils::error::GravityError;
 use gravity_utils::num_conversion::{print_eth, print_gwei};
 use gravity_utils::prices::get_weth_price_with_retries;
+use gravity_utils::token_display::format_erc20;
 use gravity_utils::types::{RelayerConfig, Valset};
 use gravity_utils::types::{ValsetConfirmResponse, ValsetRelayingMode};
 use tonic::transport::Channel;
 use web30::client::Web3;
 
 use crate::batch_relaying::get_cost_with_margin;
+use crate::duplicate_relay::{valset_relay_in_flight, MempoolSnapshot};
+use crate::gas_oracle::get_relaying_gas_price;
 use crate::main_loop::ETH_SUBMIT_WAIT_TIME;
 
 #[allow(clippy::too_many_arguments)]
@@ -35,6 +38,7 @@ pub async fn relay_valsets(
     gravity_contract_address: EthAddress,
     gravity_id: String,
     config: RelayerConfig,
+    mempool: &mut MempoolSnapshot,
 ) {
     // we have to start with the current valset, we need to know what's currently
     // in the contract in order to determine if a new validator set is valid.
@@ -79,6 +83,7 @@ pub async fn relay_valsets(
         gravity_id,
         ethereum_key,
         config,
+        mempool,
     )
     .await;
 }
@@ -95,7 +100,9 @@ async fn relay_valid_valset(
     gravity_id: String,
     ethereum_key: EthPrivateKey,
     config: RelayerConfig,
+    mempool: &mut MempoolSnapshot,
 ) {
+    let gas_price = get_relaying_gas_price(web3, &config.gas_oracle).await;
     let cost = ethereum_gravity::valset_update::estimate_valset_cost(
         &valset_to_relay,
         &current_valset,
@@ -104,6 +111,8 @@ async fn relay_valid_valset(
         gravity_contract_address,
         gravity_id.clone(),
         ethereum_key.to_address(),
+        gas_price,
+        config.trim_signatures,
     )
     .await;
     if cost.is_err() {
@@ -128,6 +137,8 @@ async fn relay_valid_valset(
         print_eth(cost.get_total())
     );
 
+    let cost_total = cost.get_total();
+    let cost_gas_price = cost.gas_price;
     let should_relay = should_relay_valset(
         latest_cosmos_valset_nonce,
         &valset_to_relay,
@@ -138,7 +149,30 @@ async fn relay_valid_valset(
     )
     .await;
 
-    if should_relay {
+    if should_relay
+        && !valset_relay_in_flight(
+            web3,
+            gravity_contract_address,
+            ethereum_key.to_address(),
+            &valset_to_relay,
+            cost_gas_price,
+            mempool,
+        )
+        .await
+    {
+        if config.dry_run {
+            let reward = match valset_to_relay.reward_token {
+                Some(token) => format_erc20(web3, token, valset_to_relay.reward_amount).await,
+                None => "none".to_string(),
+            };
+            info!(
+                "Dry run, would relay valset {} Expected Cost: {} ETH Reward: {}",
+                valset_to_relay.nonce,
+                print_eth(cost_total),
+                reward
+            );
+            return;
+        }
         let res = send_eth_valset_update(
             valset_to_relay,
             current_valset,
@@ -148,6 +182,8 @@ async fn relay_valid_valset(
             gravity_contract_address,
             gravity_id,
             ethereum_key,
+            gas_price,
+            config.trim_signatures,
         )
         .await;
         if let Err(e) = res {
@@ -212,6 +212,8 @@ pub struct BootstrapContractAddresses {
     pub gravity_erc721_contract: EthAddress,
     pub erc20_addresses: Vec<EthAddress>,
     pub erc721_addresses: Vec<EthAddress>,
+    /// the unusual and hostile ERC20s from TestHostileERC20.sol, keyed by label
+    pub hostile_erc20_addresses: Vec<(String, EthAddress)>,
     pub uniswap_liquidity_address: Option<EthAddress>,
 }
 
@@ -226,6 +228,7 @@ pub fn parse_contract_addresses() -> BootstrapContractAddresses {
     let mut maybe_gravity_erc721_address = None;
     let mut erc20_addresses = Vec::new();
     let mut erc721_addresses = Vec::new();
+    let mut hostile_erc20_addresses = Vec::new();
     let mut uniswap_liquidity = None;
     for line in output.lines() {
         if line.contains("Gravity deployed at Address -") {
@@ -242,6 +245,10 @@ pub fn parse_contract_addresses() -> BootstrapContractAddresses {
             let address_string = line.split('-').last().unwrap();
             erc721_addresses.push(address_string.trim().parse().unwrap());
             info!("found erc721 address it is {}", address_string);
+        } else if line.contains("Hostile token ") && line.contains(" at Address -") {
+            let label = line.split(' ').nth(2).unwrap().to_string();
+            let address_string = line.split('-').next_back().unwrap();
+            hostile_erc20_addresses.push((label, address_string.trim().parse().unwrap()));
         } else if line.contains("Uniswap Liquidity test deployed at Address - ") {
             let address_string = line.split('-').last().unwrap();
             uniswap_liquidity = Some(address_string.trim().parse().unwrap());
@@ -254,6 +261,7 @@ pub fn parse_contract_addresses() -> BootstrapContractAddresses {
         gravity_erc721_contract: gravity_erc721_address,
         erc20_addresses,
         erc721_addresses,
+        hostile_erc20_addresses,
         uniswap_liquidity_address: uniswap_liquidity,
     }
 }
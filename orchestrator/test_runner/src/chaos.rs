@@ -0,0 +1,395 @@
+//! Chaos testing, runs an ordinary deposit workload while randomly restarting orchestrators,
+//! cutting and slowing their Ethereum and Cosmos RPC connections and reorging the Ethereum chain.
+//! Once the chaos stops every deposit that survived on Ethereum must arrive on Gravity exactly once
+//! and every validator must have attested to every event, anything else means a retry or recovery
+//! path lost or duplicated work.
+
+use crate::utils::*;
+use crate::{
+    get_fee, one_eth, ADDRESS_PREFIX, COSMOS_NODE_GRPC, ETH_NODE, MINER_ADDRESS, MINER_PRIVATE_KEY,
+    OPERATION_TIMEOUT, TOTAL_TIMEOUT,
+};
+use actix::System;
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::query::{get_gravity_params, get_last_event_nonce_for_validator};
+use deep_space::{Contact, PrivateKey};
+use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use ethereum_gravity::utils::get_event_nonce;
+use futures::future::{join, AbortHandle, Abortable};
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::types::{FinalityMode, FinalityProfile, GravityBridgeToolsConfig};
+use orchestrator::main_loop::orchestrator_main_loop;
+use rand::Rng;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+use web30::jsonrpc::client::HttpClient;
+
+/// The number of deposits made, each followed by one random fault
+const CHAOS_ROUNDS: usize = 20;
+/// The deepest reorg introduced, must stay below CHAOS_CONFIRMATIONS or the oracle would rightly
+/// attest to events that are later reorganized away
+const MAX_REORG_DEPTH: u64 = 3;
+/// The confirmations the orchestrators wait for on the test chain during chaos testing, normally
+/// the test chain is treated as instantly final
+const CHAOS_CONFIRMATIONS: u64 = 10;
+/// The net_version of the test Ethereum chain
+const TEST_CHAIN_ID: u64 = 15;
+const MAX_OUTAGE: Duration = Duration::from_secs(30);
+const MAX_LATENCY_MS: u64 = 2000;
+
+pub async fn chaos_test(
+    web30: &Web3,
+    grpc_client: GravityQueryClient<Channel>,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+    erc20_address: EthAddress,
+) {
+    let eth_proxy = ChaosProxy::start(ETH_NODE.as_str()).await;
+    let grpc_proxy = ChaosProxy::start(COSMOS_NODE_GRPC.as_str()).await;
+
+    let mut config = create_default_test_config();
+    config.orchestrator.finality_profiles = vec![FinalityProfile {
+        chain_id: TEST_CHAIN_ID,
+        name: "Chaos test chain".to_string(),
+        mode: FinalityMode::Confirmations,
+        confirmations: CHAOS_CONFIRMATIONS,
+        block_time_ms: 1000,
+    }];
+    let gravity_id = get_gravity_params(&mut grpc_client.clone())
+        .await
+        .expect("Failed to get Gravity Bridge module parameters!")
+        .gravity_id;
+    let spawn = |k: &ValidatorKeys| {
+        spawn_orchestrator(
+            k.clone(),
+            gravity_address,
+            gravity_id.clone(),
+            config.clone(),
+            eth_proxy.url.clone(),
+            grpc_proxy.url.clone(),
+        )
+    };
+    let mut orchestrators: Vec<AbortHandle> = keys.iter().map(&spawn).collect();
+
+    let user = get_user_key(None);
+    let mut deposits: Vec<(Uint256, Uint256)> = Vec::new();
+    let mut rng = rand::thread_rng();
+    for round in 0..CHAOS_ROUNDS {
+        let multiplier: Uint256 = rng.gen_range(1u64..100).into();
+        let amount = one_eth() * multiplier;
+        let tx_hash = send_to_cosmos(
+            erc20_address,
+            gravity_address,
+            amount,
+            user.cosmos_address,
+            *MINER_PRIVATE_KEY,
+            Some(OPERATION_TIMEOUT),
+            web30,
+            vec![],
+        )
+        .await
+        .expect("Failed to send tokens to Cosmos");
+        deposits.push((tx_hash, amount));
+
+        match rng.gen_range(0..5) {
+            0 => {
+                let i = rng.gen_range(0..keys.len());
+                info!("Chaos round {}: restarting orchestrator {}", round, i);
+                orchestrators[i].abort();
+                orchestrators[i] = spawn(&keys[i]);
+            }
+            1 => {
+                let outage = MAX_OUTAGE.mul_f64(rng.gen());
+                info!("Chaos round {}: Ethereum RPC down for {:?}", round, outage);
+                eth_proxy.outage(outage);
+            }
+            2 => {
+                let outage = MAX_OUTAGE.mul_f64(rng.gen());
+                info!("Chaos round {}: Cosmos gRPC down for {:?}", round, outage);
+                grpc_proxy.outage(outage);
+            }
+            3 => {
+                let latency = rng.gen_range(0..MAX_LATENCY_MS);
+                info!("Chaos round {}: RPC latency set to {}ms", round, latency);
+                eth_proxy.set_latency(latency);
+                grpc_proxy.set_latency(latency);
+            }
+            _ => {
+                let depth = rng.gen_range(1..=MAX_REORG_DEPTH);
+                info!("Chaos round {}: reorging {} Ethereum blocks", round, depth);
+                reorg_ethereum(web30, depth).await;
+            }
+        }
+        delay_for(Duration::from_secs(rng.gen_range(5..20))).await;
+    }
+
+    info!("Chaos over, waiting for the bridge to recover");
+    eth_proxy.set_latency(0);
+    grpc_proxy.set_latency(0);
+    eth_proxy.outage(Duration::ZERO);
+    grpc_proxy.outage(Duration::ZERO);
+
+    let denom = format!("gravity{}", erc20_address);
+    let start = Instant::now();
+    loop {
+        let expected = surviving_deposit_total(web30, &deposits).await;
+        let balance: Uint256 = contact
+            .get_balance(user.cosmos_address, denom.clone())
+            .await
+            .unwrap()
+            .map(|c| c.amount)
+            .unwrap_or_else(|| 0u8.into());
+        let eth_nonce = get_event_nonce(gravity_address, *MINER_ADDRESS, web30)
+            .await
+            .unwrap();
+        let attested = all_validators_attested(grpc_client.clone(), &keys, eth_nonce).await;
+
+        if balance == expected && attested {
+            info!(
+                "Successfully recovered from chaos, {} {} arrived and all {} events were attested",
+                expected, denom, eth_nonce
+            );
+            break;
+        }
+        if balance > expected {
+            panic!(
+                "Deposits were duplicated! Gravity balance {} but only {} was deposited",
+                balance, expected
+            );
+        }
+        if Instant::now() - start > TOTAL_TIMEOUT {
+            panic!(
+                "Bridge did not recover from chaos, Gravity balance {} expected {}, all events attested {}",
+                balance, expected, attested
+            );
+        }
+        delay_for(Duration::from_secs(10)).await;
+    }
+}
+
+/// Spawns an orchestrator on its own thread talking to the chains through the chaos proxies, the
+/// returned handle stops it
+fn spawn_orchestrator(
+    k: ValidatorKeys,
+    gravity_address: EthAddress,
+    gravity_id: String,
+    config: GravityBridgeToolsConfig,
+    eth_url: String,
+    grpc_url: String,
+) -> AbortHandle {
+    let (handle, registration) = AbortHandle::new_pair();
+    thread::spawn(move || {
+        let system = System::new();
+        system.block_on(async move {
+            let web30 = Web3::new(&eth_url, OPERATION_TIMEOUT);
+            let contact =
+                Contact::new(&grpc_url, OPERATION_TIMEOUT, ADDRESS_PREFIX.as_str()).unwrap();
+            // the proxy may be down when we are restarted
+            let grpc_client = loop {
+                match GravityQueryClient::connect(grpc_url.clone()).await {
+                    Ok(c) => break c,
+                    Err(_) => delay_for(Duration::from_secs(1)).await,
+                }
+            };
+            let fut = orchestrator_main_loop(
+                k.orch_key,
+                k.eth_key,
+                Arc::new(k.eth_key),
+                web30,
+                contact,
+                grpc_client,
+                gravity_address,
+                gravity_id,
+                get_fee(None),
+                config,
+            );
+            let _ = Abortable::new(fut, registration).await;
+        });
+    });
+    handle
+}
+
+/// Rewinds the Ethereum chain `depth` blocks, the miner then builds a competing chain from there.
+/// Requires the debug namespace to be enabled on the test Geth node
+async fn reorg_ethereum(web30: &Web3, depth: u64) {
+    let head = web30.eth_block_number().await.unwrap();
+    let depth: Uint256 = depth.into();
+    let target = head - depth;
+    let client = HttpClient::new(ETH_NODE.as_str());
+    let res: Result<Value, _> = client
+        .request_method(
+            "debug_setHead",
+            vec![format!("{:#x}", target)],
+            OPERATION_TIMEOUT,
+        )
+        .await;
+    if let Err(e) = res {
+        panic!(
+            "Failed to reorg Ethereum, is the debug api enabled? {:?}",
+            e
+        );
+    }
+}
+
+/// Sums the deposits whose transactions are still part of the canonical chain and succeeded,
+/// deposits mined in reorganized blocks are dropped by Geth and never reach Gravity
+async fn surviving_deposit_total(web30: &Web3, deposits: &[(Uint256, Uint256)]) -> Uint256 {
+    let client = HttpClient::new(&web30.get_url());
+    let mut total: Uint256 = 0u8.into();
+    for (tx_hash, amount) in deposits {
+        let receipt: Result<Value, _> = client
+            .request_method(
+                "eth_getTransactionReceipt",
+                vec![format!("{:#066x}", tx_hash)],
+                OPERATION_TIMEOUT,
+            )
+            .await;
+        if let Ok(receipt) = receipt {
+            if receipt["status"].as_str() == Some("0x1") {
+                total += *amount;
+            }
+        }
+    }
+    total
+}
+
+async fn all_validators_attested(
+    grpc_client: GravityQueryClient<Channel>,
+    keys: &[ValidatorKeys],
+    eth_nonce: u64,
+) -> bool {
+    let mut grpc_client = grpc_client;
+    for k in keys {
+        let address = k.orch_key.to_address(ADDRESS_PREFIX.as_str()).unwrap();
+        match get_last_event_nonce_for_validator(
+            &mut grpc_client,
+            address,
+            ADDRESS_PREFIX.to_string(),
+        )
+        .await
+        {
+            Ok(nonce) if nonce == eth_nonce => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// A TCP proxy between the orchestrators and a chain's RPC endpoint which can be taken down,
+/// cutting every open connection and refusing new ones, or made to add latency to every read
+struct ChaosProxy {
+    url: String,
+    down: Arc<watch::Sender<bool>>,
+    latency_ms: Arc<AtomicU64>,
+}
+
+impl ChaosProxy {
+    async fn start(upstream_url: &str) -> ChaosProxy {
+        let (scheme, upstream) = upstream_url
+            .split_once("://")
+            .expect("RPC urls must include a scheme");
+        let upstream = upstream.trim_end_matches('/').to_string();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("{}://{}", scheme, listener.local_addr().unwrap());
+        info!("Chaos proxy {} -> {}", url, upstream);
+
+        let (down, _) = watch::channel(false);
+        let down = Arc::new(down);
+        let latency_ms = Arc::new(AtomicU64::new(0));
+        let (accept_down, accept_latency) = (down.clone(), latency_ms.clone());
+        actix_rt::spawn(async move {
+            while let Ok((inbound, _)) = listener.accept().await {
+                if *accept_down.borrow() {
+                    continue;
+                }
+                actix_rt::spawn(proxy_connection(
+                    inbound,
+                    upstream.clone(),
+                    accept_down.subscribe(),
+                    accept_latency.clone(),
+                ));
+            }
+        });
+        ChaosProxy {
+            url,
+            down,
+            latency_ms,
+        }
+    }
+
+    /// Takes the proxy down for `duration`, a zero duration brings it back up immediately
+    fn outage(&self, duration: Duration) {
+        if duration.is_zero() {
+            let _ = self.down.send(false);
+            return;
+        }
+        let _ = self.down.send(true);
+        let down = self.down.clone();
+        actix_rt::spawn(async move {
+            delay_for(duration).await;
+            let _ = down.send(false);
+        });
+    }
+
+    fn set_latency(&self, latency_ms: u64) {
+        self.latency_ms.store(latency_ms, Ordering::Relaxed);
+    }
+}
+
+async fn proxy_connection(
+    inbound: TcpStream,
+    upstream: String,
+    mut down: watch::Receiver<bool>,
+    latency_ms: Arc<AtomicU64>,
+) {
+    let outbound = match TcpStream::connect(&upstream).await {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Chaos proxy failed to connect to {} {:?}", upstream, e);
+            return;
+        }
+    };
+    let (in_read, in_write) = inbound.into_split();
+    let (out_read, out_write) = outbound.into_split();
+    tokio::select! {
+        _ = join(
+            pump(in_read, out_write, latency_ms.clone()),
+            pump(out_read, in_write, latency_ms),
+        ) => {}
+        _ = async {
+            while !*down.borrow() {
+                if down.changed().await.is_err() {
+                    futures::future::pending::<()>().await;
+                }
+            }
+        } => {}
+    }
+}
+
+async fn pump(mut from: OwnedReadHalf, mut to: OwnedWriteHalf, latency_ms: Arc<AtomicU64>) {
+    let mut buf = vec![0u8; 16 * 1024];
+    loop {
+        let n = match from.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => n,
+        };
+        let latency = latency_ms.load(Ordering::Relaxed);
+        if latency > 0 {
+            delay_for(Duration::from_millis(latency)).await;
+        }
+        if to.write_all(&buf[..n]).await.is_err() {
+            return;
+        }
+    }
+}
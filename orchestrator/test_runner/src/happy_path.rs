@@ -545,6 +545,7 @@ async fn test_batch(
         None,
         bridge_denom_fee.clone(),
         contact,
+        None,
     )
     .await
     .unwrap();
@@ -639,6 +640,7 @@ async fn submit_duplicate_erc20_send(
             vec![],
             vec![],
             get_fee(None),
+            None,
         )
         .await;
         info!("Submitted duplicate sendToCosmos event: {:?}", res);
@@ -231,6 +231,7 @@ pub async fn send_to_eth_and_confirm(
         cosmos_chain_fee_coin,
         cosmos_tx_fee_coin,
         contact,
+        None,
     )
     .await
     .unwrap();
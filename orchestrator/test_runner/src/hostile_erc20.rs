@@ -0,0 +1,251 @@
+//! Tests the bridge against unusual and hostile ERC20s deployed by the contract deployer from
+//! TestHostileERC20.sol. Tokens with extreme decimals must round trip exactly, tokens that charge a
+//! fee on transfer must be credited with what Gravity actually received, and tokens that can not be
+//! withdrawn or deposited must not stall the oracle or relayer for everyone else.
+
+use crate::utils::*;
+use crate::{one_eth, MINER_PRIVATE_KEY, OPERATION_TIMEOUT, TOTAL_TIMEOUT};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::send::send_to_eth;
+use deep_space::{Coin, Contact};
+use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use web30::client::Web3;
+
+pub async fn hostile_erc20_test(
+    web30: &Web3,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+    erc20_address: EthAddress,
+    hostile_erc20s: Vec<(String, EthAddress)>,
+) {
+    start_orchestrators(
+        keys.clone(),
+        gravity_address,
+        false,
+        create_default_test_config(),
+    )
+    .await;
+    let token = |label: &str| {
+        hostile_erc20s
+            .iter()
+            .find(|(l, _)| l == label)
+            .unwrap_or_else(|| panic!("Hostile token {} was not deployed", label))
+            .1
+    };
+    let round_trip_of = |token: EthAddress,
+                         deposit: Uint256,
+                         withdraw: Uint256,
+                         expected_in: Uint256,
+                         expected_out: Uint256| {
+        round_trip(
+            web30,
+            contact,
+            gravity_address,
+            token,
+            deposit,
+            withdraw,
+            expected_in,
+            expected_out,
+        )
+    };
+
+    info!("Testing a token with 0 decimals");
+    round_trip_of(
+        token("ZeroDecimals"),
+        1000u32.into(),
+        500u32.into(),
+        1000u32.into(),
+        500u32.into(),
+    )
+    .await;
+
+    info!("Testing a token with 30 decimals");
+    let thirty_decimals = Uint256::from_str("1000000000000000000000000000000").unwrap();
+    round_trip_of(
+        token("ThirtyDecimals"),
+        thirty_decimals * 5u8.into(),
+        thirty_decimals,
+        thirty_decimals * 5u8.into(),
+        thirty_decimals,
+    )
+    .await;
+
+    // 1% is burned on the way into Gravity and again on the way out
+    info!("Testing a token with a fee on transfer");
+    let deposit = one_eth() * 100u8.into();
+    let withdraw = one_eth() * 10u8.into();
+    round_trip_of(
+        token("TransferFee"),
+        deposit,
+        withdraw,
+        deposit - deposit / 100u8.into(),
+        withdraw - withdraw / 100u8.into(),
+    )
+    .await;
+
+    // deposits work but the batch can never be executed, the relayer must keep relaying other
+    // tokens and the user must not receive anything on Ethereum
+    info!("Testing a token which reverts on transfer");
+    let reverting = token("RevertOnTransfer");
+    let stuck_user =
+        deposit_and_wait(web30, contact, gravity_address, reverting, deposit, deposit).await;
+    send_withdrawal(contact, &stuck_user, reverting, withdraw).await;
+    round_trip_of(
+        erc20_address,
+        100u8.into(),
+        50u8.into(),
+        100u8.into(),
+        50u8.into(),
+    )
+    .await;
+    let stuck_balance = web30
+        .get_erc20_balance(reverting, stuck_user.eth_dest_address)
+        .await
+        .unwrap();
+    assert_eq!(stuck_balance, 0u8.into());
+
+    // Gravity uses safeTransferFrom so the deposit reverts on Ethereum, there is no event and
+    // nothing must be credited, other deposits must keep flowing
+    info!("Testing a token which returns false instead of reverting");
+    let returns_false = token("ReturnFalse");
+    let user = get_user_key(None);
+    let res = send_to_cosmos(
+        returns_false,
+        gravity_address,
+        deposit,
+        user.cosmos_address,
+        *MINER_PRIVATE_KEY,
+        Some(OPERATION_TIMEOUT),
+        web30,
+        vec![],
+    )
+    .await;
+    info!("Deposit of a token returning false resulted in {:?}", res);
+    round_trip_of(
+        erc20_address,
+        100u8.into(),
+        50u8.into(),
+        100u8.into(),
+        50u8.into(),
+    )
+    .await;
+    let credited = contact
+        .get_balance(user.cosmos_address, format!("gravity{}", returns_false))
+        .await
+        .unwrap();
+    assert!(
+        credited.is_none() || credited.unwrap().amount == 0u8.into(),
+        "Deposit of a token returning false was credited"
+    );
+
+    info!("Successfully tested hostile ERC20s");
+}
+
+/// Deposits `deposit` of `token` to a new user, then withdraws `withdraw` back to Ethereum,
+/// asserting the user is credited exactly `expected_in` on Gravity and `expected_out` on Ethereum
+#[allow(clippy::too_many_arguments)]
+async fn round_trip(
+    web30: &Web3,
+    contact: &Contact,
+    gravity_address: EthAddress,
+    token: EthAddress,
+    deposit: Uint256,
+    withdraw: Uint256,
+    expected_in: Uint256,
+    expected_out: Uint256,
+) {
+    let user = deposit_and_wait(web30, contact, gravity_address, token, deposit, expected_in).await;
+    send_withdrawal(contact, &user, token, withdraw).await;
+
+    let start = Instant::now();
+    loop {
+        let balance = web30
+            .get_erc20_balance(token, user.eth_dest_address)
+            .await
+            .unwrap_or_else(|_| 0u8.into());
+        if balance == expected_out {
+            info!("{} of {} arrived on Ethereum", balance, token);
+            return;
+        }
+        if balance > expected_out || Instant::now() - start > TOTAL_TIMEOUT {
+            panic!(
+                "Withdrawal of {} resulted in {} on Ethereum, expected {}",
+                token, balance, expected_out
+            );
+        }
+        delay_for(Duration::from_secs(5)).await;
+    }
+}
+
+async fn deposit_and_wait(
+    web30: &Web3,
+    contact: &Contact,
+    gravity_address: EthAddress,
+    token: EthAddress,
+    deposit: Uint256,
+    expected: Uint256,
+) -> BridgeUserKey {
+    let user = get_user_key(None);
+    send_to_cosmos(
+        token,
+        gravity_address,
+        deposit,
+        user.cosmos_address,
+        *MINER_PRIVATE_KEY,
+        Some(OPERATION_TIMEOUT),
+        web30,
+        vec![],
+    )
+    .await
+    .expect("Failed to send tokens to Cosmos");
+
+    let denom = format!("gravity{}", token);
+    let start = Instant::now();
+    loop {
+        let balance = contact
+            .get_balance(user.cosmos_address, denom.clone())
+            .await
+            .unwrap()
+            .map(|c| c.amount)
+            .unwrap_or_else(|| 0u8.into());
+        if balance == expected {
+            return user;
+        }
+        if balance > expected || Instant::now() - start > TOTAL_TIMEOUT {
+            panic!(
+                "Deposit of {} credited {} on Gravity, expected {}",
+                token, balance, expected
+            );
+        }
+        delay_for(Duration::from_secs(5)).await;
+    }
+}
+
+async fn send_withdrawal(
+    contact: &Contact,
+    user: &BridgeUserKey,
+    token: EthAddress,
+    amount: Uint256,
+) {
+    let denom = format!("gravity{}", token);
+    let fee = Coin {
+        denom: denom.clone(),
+        amount: 1u8.into(),
+    };
+    send_to_eth(
+        user.cosmos_key,
+        user.eth_dest_address,
+        Coin { denom, amount },
+        fee.clone(),
+        None,
+        fee,
+        contact,
+        None,
+    )
+    .await
+    .expect("Failed to send tokens to Ethereum");
+}
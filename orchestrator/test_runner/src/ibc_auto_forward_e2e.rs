@@ -0,0 +1,136 @@
+use crate::happy_path::send_erc20_deposit;
+use crate::ibc_auto_forward::{
+    get_channel_id, get_ibc_balance, setup_gravity_auto_forwards,
+    wait_for_pending_ibc_auto_forwards,
+};
+use crate::utils::*;
+use crate::{
+    get_ibc_chain_id, one_eth, COSMOS_NODE_GRPC, IBC_ADDRESS_PREFIX, IBC_NODE_GRPC, STAKING_TOKEN,
+};
+use clarity::Address as EthAddress;
+use cosmos_gravity::send::execute_pending_ibc_auto_forwards;
+use deep_space::private_key::CosmosPrivateKey;
+use deep_space::{Coin as DSCoin, Contact};
+use gravity_proto::cosmos_sdk_proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use gravity_proto::cosmos_sdk_proto::ibc::applications::transfer::v1::query_client::QueryClient as IbcTransferQueryClient;
+use gravity_proto::cosmos_sdk_proto::ibc::core::channel::v1::query_client::QueryClient as IbcChannelQueryClient;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_proto::gravity::QueryPendingIbcAutoForwards;
+use num256::Uint256;
+use std::str::FromStr;
+use std::time::Duration;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+// Runs the full IBC auto-forward flow without leaning on the relayer's auto-forward loop: an ERC20
+// deposit to a foreign chain address, the resulting pending forward on Gravity, an explicit
+// execute_pending_ibc_auto_forwards and the tokens arriving on ibc-test-1
+pub async fn ibc_auto_forward_e2e_test(
+    web30: &Web3,
+    gravity_client: GravityQueryClient<Channel>,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+    erc20_address: EthAddress,
+) {
+    // the relayer would otherwise race us to execute the forward
+    let mut config = create_default_test_config();
+    config.orchestrator.relayer_enabled = false;
+    start_orchestrators(keys.clone(), gravity_address, false, config).await;
+
+    let gravity_channel_qc = IbcChannelQueryClient::connect(COSMOS_NODE_GRPC.as_str())
+        .await
+        .expect("Could not connect channel query client");
+    let ibc_bank_qc = BankQueryClient::connect(IBC_NODE_GRPC.as_str())
+        .await
+        .expect("Could not connect bank query client");
+    let ibc_transfer_qc = IbcTransferQueryClient::connect(IBC_NODE_GRPC.as_str())
+        .await
+        .expect("Could not connect ibc-transfer query client");
+
+    let gravity_channel_id = get_channel_id(
+        gravity_channel_qc,
+        get_ibc_chain_id(),
+        Some(Duration::from_secs(60 * 5)),
+    )
+    .await
+    .expect("Could not find gravity-test-1 channel");
+
+    let executor = keys[0].validator_key;
+    setup_gravity_auto_forwards(
+        contact,
+        (*IBC_ADDRESS_PREFIX).clone(),
+        gravity_channel_id,
+        executor,
+        &keys,
+    )
+    .await;
+
+    let dest = get_user_key(Some(IBC_ADDRESS_PREFIX.as_str())).cosmos_address;
+    let amount = one_eth();
+    let bridged_erc20 = format!("gravity{}", erc20_address);
+
+    send_erc20_deposit(
+        web30,
+        &mut gravity_client.clone(),
+        dest,
+        gravity_address,
+        erc20_address,
+        amount,
+    )
+    .await
+    .expect("Failed to send ERC20 deposit");
+
+    let pending = wait_for_pending_ibc_auto_forwards(gravity_client.clone(), Some(1), None)
+        .await
+        .expect("Deposit did not produce a pending IBC auto-forward");
+    assert!(pending
+        .iter()
+        .any(|p| p.foreign_receiver == dest.to_string()));
+    info!("Found pending IBC auto-forward to {}, executing it", dest);
+
+    execute_forwards(contact, executor, pending.len() as u64).await;
+    assert_no_pending_forwards(gravity_client).await;
+
+    let received = get_ibc_balance(
+        dest,
+        bridged_erc20,
+        Some(amount),
+        ibc_bank_qc,
+        ibc_transfer_qc,
+        Some(Duration::from_secs(60 * 5)),
+    )
+    .await
+    .expect("Auto-forwarded tokens never arrived on ibc-test-1");
+    assert_eq!(Uint256::from_str(&received.amount).unwrap(), amount);
+    info!(
+        "Successful IBC auto-forward end to end test, {} arrived at {} on ibc-test-1",
+        received.amount, dest
+    );
+}
+
+async fn execute_forwards(contact: &Contact, executor: CosmosPrivateKey, forwards_to_clear: u64) {
+    let fee = DSCoin {
+        denom: (*STAKING_TOKEN).clone(),
+        amount: 0u8.into(),
+    };
+    execute_pending_ibc_auto_forwards(contact, executor, fee, forwards_to_clear)
+        .await
+        .expect("Failed to execute pending IBC auto-forwards");
+}
+
+async fn assert_no_pending_forwards(gravity_client: GravityQueryClient<Channel>) {
+    let mut gravity_client = gravity_client;
+    let remaining = gravity_client
+        .get_pending_ibc_auto_forwards(QueryPendingIbcAutoForwards { limit: 0 })
+        .await
+        .expect("Failed to query pending IBC auto-forwards")
+        .into_inner()
+        .pending_ibc_auto_forwards;
+    if !remaining.is_empty() {
+        panic!(
+            "IBC auto-forwards still pending after execution {:?}",
+            remaining
+        );
+    }
+}
@@ -12,11 +12,14 @@ use crate::auction::{
 };
 use crate::batch_timeout::batch_timeout_test;
 use crate::bootstrapping::*;
+use crate::chaos::chaos_test;
 use crate::deposit_overflow::deposit_overflow_test;
 use crate::eip_712::eip_712_test;
 use crate::ethereum_blacklist_test::ethereum_blacklist_test;
 use crate::ethereum_keys::ethereum_keys_test;
+use crate::hostile_erc20::hostile_erc20_test;
 use crate::ibc_auto_forward::ibc_auto_forward_test;
+use crate::ibc_auto_forward_e2e::ibc_auto_forward_e2e_test;
 use crate::ibc_metadata::ibc_metadata_proposal_test;
 use crate::ica_host::ica_host_happy_path;
 use crate::inflation_knockdown::inflation_knockdown_test;
@@ -27,6 +30,7 @@ use crate::signature_slashing::signature_slashing_test;
 use crate::slashing_delegation::slashing_delegation_test;
 use crate::tx_cancel::send_to_eth_and_cancel;
 use crate::upgrade::{run_upgrade, upgrade_part_1, upgrade_part_2, UPGRADE_NAME};
+use crate::upgrade_in_flight::upgrade_in_flight_test;
 use crate::utils::*;
 use crate::valset_rewards::valset_rewards_test;
 use crate::vesting::vesting_test;
@@ -46,6 +50,7 @@ use lazy_static::lazy_static;
 use orch_keys::orch_keys;
 use orch_only::orch_only_test;
 use relay_market::relay_market_test;
+use round_trip::round_trip_test;
 use std::{env, time::Duration};
 use tokio::time::sleep;
 use transaction_stress_test::transaction_stress_test;
@@ -56,6 +61,7 @@ mod airdrop_proposal;
 mod auction;
 mod batch_timeout;
 mod bootstrapping;
+mod chaos;
 mod deposit_overflow;
 mod eip_712;
 mod erc_721_happy_path;
@@ -64,7 +70,9 @@ mod ethereum_keys;
 mod evidence_based_slashing;
 mod happy_path;
 mod happy_path_v2;
+mod hostile_erc20;
 mod ibc_auto_forward;
+mod ibc_auto_forward_e2e;
 mod ibc_metadata;
 mod ica_host;
 mod inflation_knockdown;
@@ -73,6 +81,7 @@ mod orch_keys;
 mod orch_only;
 mod pause_bridge;
 mod relay_market;
+mod round_trip;
 mod send_to_eth_fees;
 mod signature_slashing;
 mod slashing_delegation;
@@ -80,6 +89,7 @@ mod transaction_stress_test;
 mod tx_cancel;
 mod unhalt_bridge;
 mod upgrade;
+mod upgrade_in_flight;
 mod utils;
 mod valset_rewards;
 mod valset_stress;
@@ -283,6 +293,7 @@ pub async fn main() {
     // INVALID_EVENTS tests the creation of hostile events on Ethereum, such as tokens with bad unicode for names
     // UNHALT_BRIDGE tests halting of the bridge when an Ethereum oracle disagreement occurs and unhalting the bridge via gov vote
     // PAUSE_BRIDGE tests a governance vote to pause and unpause bridge functionality
+    // HOSTILE_ERC20 tests tokens with extreme decimals, transfer fees, reverting transfers and false returns
     // DEPOSIT_OVERFLOW tests attacks of gravity.sol where a hostle erc20 imitates a supply above uint256 max
     // ETHEREUM_BLACKLIST tests the blacklist functionality of Ethereum addresses not allowed to interact with the bridge
     // AIRDROP_PROPOSAL tests the airdrop proposal by creating and executing an airdrop
@@ -293,7 +304,9 @@ pub async fn main() {
     // UPGRADE_PART_1 handles creating a chain upgrade proposal and passing it
     // UPGRADE_PART_2 upgrades the chain binaries and starts the upgraded chain after being halted in part 1
     // UPGRADE_ONLY performs an upgrade without making any testing assertions
+    // UPGRADE_IN_FLIGHT upgrades the chain while sends to Ethereum and deposits are in flight, run with tests/run-upgrade-test.sh <version> IN_FLIGHT
     // IBC_AUTO_FORWARD tests ibc auto forwarding functionality.
+    // IBC_AUTO_FORWARD_E2E deposits to a foreign chain address, executes the pending forward and checks arrival on ibc-test-1
     // ETHERMINT_KEYS runs a gamut of transactions using a Ethermint key to test no loss of functionality
     // BATCH_TIMEOUT is a stress test for batch timeouts, setting an extremely agressive timeout value
     // VESTING checks that the vesting module delivers partially and fully vested accounts
@@ -301,6 +314,8 @@ pub async fn main() {
     // ICA_HOST_HAPPY_PATH tests that the interchain accounts host module is correctly configured on Gravity
     // RUN_ORCH_ONLY runs only the orchestrators, for local testing where you want the chain to just run.
     // INFLATION_KNOCKDOWN tests a governance proposal to reduce inflation
+    // CHAOS runs a deposit workload while restarting orchestrators, cutting their RPC connections and reorging Ethereum
+    // ROUND_TRIP sends the native staking token to Ethereum and back, checking that only fees are lost
     let test_type = env::var("TEST_TYPE");
     info!("Starting tests with {:?}", test_type);
     if let Ok(test_type) = test_type {
@@ -369,6 +384,10 @@ pub async fn main() {
             )
             .await;
             return;
+        } else if test_type == "ROUND_TRIP" {
+            info!("Starting round trip test!");
+            round_trip_test(&web30, grpc_client, &gravity_contact, keys, gravity_address).await;
+            return;
         } else if test_type == "RELAY_MARKET" {
             info!("Starting relay market tests!");
             relay_market_test(&web30, grpc_client, &gravity_contact, keys, gravity_address).await;
@@ -531,6 +550,20 @@ pub async fn main() {
             info!("Running upgrade named {plan_name}");
             run_upgrade(&contact, keys, plan_name, true).await;
             return;
+        } else if test_type == "UPGRADE_IN_FLIGHT" {
+            info!("Starting upgrade with in flight bridge activity test");
+            let plan_name = env::var("UPGRADE_NAME").unwrap_or_else(|_| UPGRADE_NAME.to_string());
+            upgrade_in_flight_test(
+                &web30,
+                &gravity_contact,
+                grpc_client,
+                keys,
+                gravity_address,
+                erc20_addresses[0],
+                plan_name,
+            )
+            .await;
+            return;
         } else if test_type == "IBC_AUTO_FORWARD" {
             info!("Starting IBC Auto-Forward test");
             start_ibc_relayer(&gravity_contact, &ibc_contact, &keys, &ibc_keys).await;
@@ -545,6 +578,43 @@ pub async fn main() {
             )
             .await;
             return;
+        } else if test_type == "HOSTILE_ERC20" {
+            info!("Starting hostile ERC20 test");
+            hostile_erc20_test(
+                &web30,
+                &gravity_contact,
+                keys,
+                gravity_address,
+                erc20_addresses[0],
+                contracts.hostile_erc20_addresses.clone(),
+            )
+            .await;
+            return;
+        } else if test_type == "CHAOS" {
+            info!("Starting chaos test");
+            chaos_test(
+                &web30,
+                grpc_client,
+                &gravity_contact,
+                keys,
+                gravity_address,
+                erc20_addresses[0],
+            )
+            .await;
+            return;
+        } else if test_type == "IBC_AUTO_FORWARD_E2E" {
+            info!("Starting IBC Auto-Forward end to end test");
+            start_ibc_relayer(&gravity_contact, &ibc_contact, &keys, &ibc_keys).await;
+            ibc_auto_forward_e2e_test(
+                &web30,
+                grpc_client,
+                &gravity_contact,
+                keys,
+                gravity_address,
+                erc20_addresses[0],
+            )
+            .await;
+            return;
         } else if test_type == "ETHEREUM_KEYS" || test_type == "ETHERMINT_KEYS" {
             info!("Starting Ethereum Keys test");
             start_ibc_relayer(&gravity_contact, &ibc_contact, &keys, &ibc_keys).await;
@@ -140,6 +140,7 @@ pub async fn pause_bridge_test(
         Some(chain_fee_coin),
         bridge_denom_fee.clone(),
         contact,
+        None,
     )
     .await
     .unwrap();
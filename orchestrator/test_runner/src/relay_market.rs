@@ -203,6 +203,7 @@ async fn setup_batch_test(
         None,
         bridge_denom_fee.clone(),
         contact,
+        None,
     )
     .await
     .unwrap();
@@ -0,0 +1,79 @@
+//! Sends the native staking token to Ethereum and back using the same round trip helper as
+//! `gbt client round-trip`, checking that nothing but fees is lost on the way
+
+use crate::happy_path_v2::deploy_cosmos_representing_erc20_and_check_adoption;
+use crate::utils::{get_user_key, send_one_eth, ugraviton_metadata, ValidatorKeys};
+use crate::{get_fee, STAKING_TOKEN, TOTAL_TIMEOUT};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::round_trip::round_trip;
+use deep_space::coin::Coin;
+use deep_space::Contact;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+pub async fn round_trip_test(
+    web30: &Web3,
+    grpc_client: GravityQueryClient<Channel>,
+    contact: &Contact,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+) {
+    let mut grpc_client = grpc_client;
+    let erc20 = deploy_cosmos_representing_erc20_and_check_adoption(
+        gravity_address,
+        web30,
+        Some(keys.clone()),
+        &mut grpc_client,
+        false,
+        ugraviton_metadata(contact).await,
+    )
+    .await;
+
+    let amount: Uint256 = 1_000_000u64.into();
+    let user = get_user_key(None);
+    contact
+        .send_coins(
+            Coin {
+                denom: STAKING_TOKEN.clone(),
+                amount: amount * 2u8.into(),
+            },
+            Some(get_fee(None)),
+            user.cosmos_address,
+            Some(TOTAL_TIMEOUT),
+            keys[0].validator_key,
+        )
+        .await
+        .unwrap();
+    // pays for the sendToCosmos deposit and its approval
+    send_one_eth(user.eth_address, web30).await;
+
+    let report = round_trip(
+        contact,
+        web30,
+        user.cosmos_key,
+        user.eth_key,
+        gravity_address,
+        erc20,
+        Coin {
+            denom: STAKING_TOKEN.clone(),
+            amount,
+        },
+        Coin {
+            denom: STAKING_TOKEN.clone(),
+            amount: 100u8.into(),
+        },
+        None,
+        // paying the Cosmos fees in the bridged token checks they are accounted for
+        get_fee(Some(STAKING_TOKEN.clone())),
+        true,
+        TOTAL_TIMEOUT,
+    )
+    .await
+    .expect("Round trip failed");
+    info!(
+        "Successfully completed a round trip, {} seconds to Ethereum and {} seconds back",
+        report.to_eth.as_secs(),
+        report.to_cosmos.as_secs()
+    );
+}
@@ -420,6 +420,7 @@ pub async fn lock_funds_in_pool(
                 Some(chain_fee),
                 send_fee,
                 contact,
+                None,
             );
             futs.push(res);
 
@@ -64,6 +64,7 @@ pub async fn send_to_eth_and_cancel(
         None,
         bridge_denom_fee.clone(),
         contact,
+        None,
     )
     .await
     .unwrap();
@@ -0,0 +1,185 @@
+use crate::happy_path::send_erc20_deposit;
+use crate::upgrade::run_upgrade;
+use crate::utils::*;
+use crate::{one_eth, MINER_PRIVATE_KEY, OPERATION_TIMEOUT, TOTAL_TIMEOUT};
+use clarity::{Address as EthAddress, Uint256};
+use cosmos_gravity::send::send_to_eth;
+use deep_space::client::ChainStatus;
+use deep_space::{Coin, Contact};
+use ethereum_gravity::send_to_cosmos::send_to_cosmos;
+use gravity_proto::gravity::query_client::QueryClient as GravityQueryClient;
+use gravity_utils::debug_state::get_debug_state;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as delay_for;
+use tonic::transport::Channel;
+use web30::client::Web3;
+
+/// Written once the chain has halted for the upgrade, upgrade-in-flight-test-internal.sh waits for
+/// this file before swapping in the new binary
+pub const UPGRADE_HALTED_MARKER: &str = "/tmp/upgrade-halted";
+/// How many blocks before the upgrade height the in flight work is created, late enough that the
+/// batch can not be relayed before the halt
+const IN_FLIGHT_LEAD_BLOCKS: u64 = 3;
+const SENDS_TO_ETH: u64 = 3;
+const DEPOSITS_DURING_HALT: u64 = 2;
+/// How long the oracle is watched while the chain is halted
+const HALT_OBSERVATION: Duration = Duration::from_secs(60);
+/// How long to wait for the new binary to be started and the chain to resume
+const RESUME_TIMEOUT: Duration = Duration::from_secs(60 * 15);
+
+/// Performs a chain upgrade while the bridge is busy, with sends to Ethereum waiting to be batched
+/// and relayed and deposits on Ethereum waiting to be attested. The orchestrators started here run
+/// straight through the halt and must complete all of that work once the upgraded chain resumes.
+/// NOTE: this test needs the chain binary swapped while it waits, run it with
+/// tests/run-upgrade-test.sh <old version> IN_FLIGHT
+pub async fn upgrade_in_flight_test(
+    web30: &Web3,
+    contact: &Contact,
+    grpc_client: GravityQueryClient<Channel>,
+    keys: Vec<ValidatorKeys>,
+    gravity_address: EthAddress,
+    erc20_address: EthAddress,
+    plan_name: String,
+) {
+    let _ = std::fs::remove_file(UPGRADE_HALTED_MARKER);
+    start_orchestrators(
+        keys.clone(),
+        gravity_address,
+        false,
+        create_default_test_config(),
+    )
+    .await;
+
+    let user = get_user_key(None);
+    let deposit_receiver = get_user_key(None);
+    let denom = format!("gravity{}", erc20_address);
+    send_erc20_deposit(
+        web30,
+        &mut grpc_client.clone(),
+        user.cosmos_address,
+        gravity_address,
+        erc20_address,
+        one_eth() * 100u8.into(),
+    )
+    .await
+    .expect("Failed to fund the test user");
+
+    let upgrade_height = run_upgrade(contact, keys.clone(), plan_name, false).await as u64;
+    wait_for_block(contact, upgrade_height - IN_FLIGHT_LEAD_BLOCKS)
+        .await
+        .expect("Chain stopped before the upgrade height");
+
+    // sends to Ethereum right before the halt, these will be batched and relayed after the upgrade
+    let fee = Coin {
+        denom: denom.clone(),
+        amount: 1u8.into(),
+    };
+    let mut sent: Uint256 = 0u8.into();
+    for _ in 0..SENDS_TO_ETH {
+        let res = send_to_eth(
+            user.cosmos_key,
+            user.eth_dest_address,
+            Coin {
+                denom: denom.clone(),
+                amount: one_eth(),
+            },
+            fee.clone(),
+            None,
+            fee.clone(),
+            contact,
+            None,
+        )
+        .await;
+        match res {
+            Ok(_) => sent += one_eth(),
+            // the chain may halt under us, that is fine, we only expect what was accepted
+            Err(e) => warn!("Send to Ethereum before the upgrade failed {:?}", e),
+        }
+    }
+    assert!(
+        sent > 0u8.into(),
+        "No sends to Ethereum made it in before the halt"
+    );
+
+    wait_for_halt(contact, upgrade_height).await;
+    std::fs::write(UPGRADE_HALTED_MARKER, upgrade_height.to_string()).unwrap();
+
+    // deposits made while the chain is halted, these can only be attested after the upgrade
+    let oracle_nonce_at_halt = get_debug_state().oracle.last_checked_event_nonce;
+    for _ in 0..DEPOSITS_DURING_HALT {
+        send_to_cosmos(
+            erc20_address,
+            gravity_address,
+            one_eth(),
+            deposit_receiver.cosmos_address,
+            *MINER_PRIVATE_KEY,
+            Some(OPERATION_TIMEOUT),
+            web30,
+            vec![],
+        )
+        .await
+        .expect("Failed to deposit while the chain is halted");
+    }
+    delay_for(HALT_OBSERVATION).await;
+    assert_eq!(
+        get_debug_state().oracle.last_checked_event_nonce,
+        oracle_nonce_at_halt,
+        "The oracle recorded progress while the chain was halted"
+    );
+    info!("Orchestrators paused while the chain is halted, waiting for the upgraded chain");
+
+    wait_for_resume(contact, upgrade_height).await;
+    info!("Chain resumed, waiting for in flight work to complete");
+
+    let expected_deposits: Uint256 = one_eth() * DEPOSITS_DURING_HALT.into();
+    let start = Instant::now();
+    loop {
+        let deposited = contact
+            .get_balance(deposit_receiver.cosmos_address, denom.clone())
+            .await
+            .ok()
+            .flatten()
+            .map(|c| c.amount)
+            .unwrap_or_else(|| 0u8.into());
+        let withdrawn = get_erc20_balance_safe(erc20_address, web30, user.eth_dest_address)
+            .await
+            .unwrap();
+        if deposited == expected_deposits && withdrawn == sent {
+            break;
+        }
+        if Instant::now() - start > TOTAL_TIMEOUT {
+            panic!(
+                "In flight work did not complete after the upgrade, deposited {} of {}, withdrawn {} of {}",
+                deposited, expected_deposits, withdrawn, sent
+            );
+        }
+        delay_for(Duration::from_secs(10)).await;
+    }
+    info!("Successfully completed in flight bridge work across a chain upgrade");
+}
+
+async fn wait_for_halt(contact: &Contact, upgrade_height: u64) {
+    wait_for_block(contact, upgrade_height - 1)
+        .await
+        .expect("Unable to wait for the upgrade height");
+    // wait for the new block to halt the chain
+    delay_for(Duration::from_secs(10)).await;
+    if let Ok(ChainStatus::Moving { block_height }) = contact.get_chain_status().await {
+        if block_height >= upgrade_height {
+            panic!("Chain did not halt at upgrade height {}", upgrade_height);
+        }
+    }
+}
+
+async fn wait_for_resume(contact: &Contact, upgrade_height: u64) {
+    let start = Instant::now();
+    while Instant::now() - start < RESUME_TIMEOUT {
+        if let Ok(ChainStatus::Moving { block_height }) = contact.get_chain_status().await {
+            if block_height > upgrade_height {
+                return;
+            }
+        }
+        delay_for(Duration::from_secs(10)).await;
+    }
+    panic!("Chain did not resume after the upgrade");
+}
@@ -38,6 +38,7 @@ use gravity_utils::types::GravityBridgeToolsConfig;
 use gravity_utils::types::ValsetRelayingMode;
 use orchestrator::main_loop::orchestrator_main_loop;
 use rand::Rng;
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 use tokio::time::sleep;
@@ -368,6 +369,7 @@ pub async fn start_orchestrators(
             let fut = orchestrator_main_loop(
                 k.orch_key,
                 k.eth_key,
+                Arc::new(k.eth_key),
                 web30,
                 contact,
                 grpc_client,
@@ -188,6 +188,32 @@ async function deploy() {
     await testERC721.deployed();
     const erc721TestAddress = testERC721.address;
     console.log("ERC721 deployed at Address - ", erc721TestAddress);
+
+    // hostile and unusual ERC20s, see TestHostileERC20.sol for the modes
+    const hostile_path = [
+      "/gravity/solidity/artifacts/contracts/TestHostileERC20.sol/TestHostileERC20.json",
+      "/solidity/TestHostileERC20.json",
+      "TestHostileERC20.json",
+    ].find((p) => fs.existsSync(p));
+    if (hostile_path === undefined) {
+      console.log("Test mode was enabled but the hostile ERC20 contract can't be found!")
+      exit(1)
+    }
+    const hostileTokens: [string, number, number][] = [
+      ["ZeroDecimals", 0, 0],
+      ["ThirtyDecimals", 30, 0],
+      ["RevertOnTransfer", 18, 1],
+      ["TransferFee", 18, 2],
+      ["ReturnFalse", 18, 3],
+    ];
+    const { abi: abi4, bytecode: bytecode4 } = getContractArtifacts(hostile_path);
+    const hostileFactory = new ethers.ContractFactory(abi4, bytecode4, wallet);
+    for (const [label, decimals, mode] of hostileTokens) {
+      const hostile = await hostileFactory.deploy(label, label.toUpperCase(), decimals, mode, overrides);
+      await hostile.deployed();
+      // deliberately not matching the "ERC20 deployed at" lines, these are not general test tokens
+      console.log("Hostile token " + label + " at Address - ", hostile.address);
+    }
   }
   const gravityIdString = await getGravityId();
   const gravityId = ethers.utils.formatBytes32String(gravityIdString);
@@ -0,0 +1,71 @@
+//SPDX-License-Identifier: Apache-2.0
+pragma solidity 0.8.10;
+import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+
+// A testing coin with configurable unusual or hostile behavior, used to make sure the bridge
+// neither stalls nor misaccounts when it meets one of these in the wild
+contract TestHostileERC20 is ERC20 {
+	// Behaves like any other ERC20
+	uint8 constant MODE_NORMAL = 0;
+	// transfer() always reverts, deposits work but withdrawals can never be executed
+	uint8 constant MODE_REVERT_ON_TRANSFER = 1;
+	// 1% of every transfer is burned, the receiver gets less than was sent
+	uint8 constant MODE_TRANSFER_FEE = 2;
+	// transfer() and transferFrom() return false and move nothing instead of reverting
+	uint8 constant MODE_RETURN_FALSE = 3;
+
+	uint8 public immutable state_decimals;
+	uint8 public immutable state_mode;
+
+	constructor(
+		string memory _name,
+		string memory _symbol,
+		uint8 _decimals,
+		uint8 _mode
+	) ERC20(_name, _symbol) {
+		state_decimals = _decimals;
+		state_mode = _mode;
+		// this is the EtherBase address for our testnet miner in
+		// tests/assets/ETHGenesis.json, 100 million whole tokens whatever the decimals
+		_mint(0xBf660843528035a5A4921534E156a27e64B231fE, 100000000 * 10**_decimals);
+	}
+
+	function decimals() public view override returns (uint8) {
+		return state_decimals;
+	}
+
+	function transfer(address recipient, uint256 amount) public override returns (bool) {
+		if (state_mode == MODE_REVERT_ON_TRANSFER) {
+			revert("TestHostileERC20: transfers are disabled");
+		}
+		if (state_mode == MODE_RETURN_FALSE) {
+			return false;
+		}
+		return super.transfer(recipient, amount);
+	}
+
+	function transferFrom(
+		address sender,
+		address recipient,
+		uint256 amount
+	) public override returns (bool) {
+		if (state_mode == MODE_RETURN_FALSE) {
+			return false;
+		}
+		return super.transferFrom(sender, recipient, amount);
+	}
+
+	function _transfer(
+		address sender,
+		address recipient,
+		uint256 amount
+	) internal override {
+		if (state_mode == MODE_TRANSFER_FEE) {
+			uint256 fee = amount / 100;
+			_burn(sender, fee);
+			super._transfer(sender, recipient, amount - fee);
+			return;
+		}
+		super._transfer(sender, recipient, amount);
+	}
+}
@@ -0,0 +1,99 @@
+import chai from "chai";
+import { ethers } from "hardhat";
+import { solidity } from "ethereum-waffle";
+
+import { makeCheckpoint } from "../test-utils/pure";
+import vectors from "../../spec/checkpoint-test-vectors.json";
+
+chai.use(solidity);
+const { expect } = chai;
+
+// The same golden vectors are checked by the Rust orchestrator in
+// ethereum_gravity/src/message_signatures.rs, if either side changes how a
+// checkpoint is encoded one of the two test suites will fail
+describe("Checkpoint test vectors", function () {
+  it("Valset checkpoints match", function () {
+    for (const v of vectors.valsets) {
+      const checkpoint = makeCheckpoint(
+        v.members.map((m) => m.eth_address),
+        v.members.map((m) => m.power),
+        v.nonce,
+        v.reward_amount,
+        v.reward_token,
+        ethers.utils.formatBytes32String(v.gravity_id)
+      );
+      expect(checkpoint, v.description).to.equal(v.checkpoint);
+    }
+  });
+
+  it("Batch checkpoints match", function () {
+    for (const v of vectors.batches) {
+      const transactions: { destination: string; amount: string; fee: string }[] =
+        v.transactions;
+      const checkpoint = ethers.utils.keccak256(
+        ethers.utils.defaultAbiCoder.encode(
+          [
+            "bytes32", // gravityId
+            "bytes32", // methodName
+            "uint256[]", // amounts
+            "address[]", // destinations
+            "uint256[]", // fees
+            "uint256", // batchNonce
+            "address", // tokenContract
+            "uint256", // batchTimeout
+          ],
+          [
+            ethers.utils.formatBytes32String(v.gravity_id),
+            ethers.utils.formatBytes32String("transactionBatch"),
+            transactions.map((tx) => tx.amount),
+            transactions.map((tx) => tx.destination),
+            transactions.map((tx) => tx.fee),
+            v.nonce,
+            v.token_contract,
+            v.batch_timeout,
+          ]
+        )
+      );
+      expect(checkpoint, v.description).to.equal(v.checkpoint);
+    }
+  });
+
+  it("Logic call checkpoints match", function () {
+    for (const v of vectors.logic_calls) {
+      const transfers: { amount: string; token_contract_address: string }[] =
+        v.transfers;
+      const fees: { amount: string; token_contract_address: string }[] = v.fees;
+      const checkpoint = ethers.utils.keccak256(
+        ethers.utils.defaultAbiCoder.encode(
+          [
+            "bytes32", // gravityId
+            "bytes32", // methodName
+            "uint256[]", // transferAmounts
+            "address[]", // transferTokenContracts
+            "uint256[]", // feeAmounts
+            "address[]", // feeTokenContracts
+            "address", // logicContractAddress
+            "bytes", // payload
+            "uint256", // timeOut
+            "bytes32", // invalidationId
+            "uint256", // invalidationNonce
+          ],
+          [
+            ethers.utils.formatBytes32String(v.gravity_id),
+            ethers.utils.formatBytes32String("logicCall"),
+            transfers.map((t) => t.amount),
+            transfers.map((t) => t.token_contract_address),
+            fees.map((t) => t.amount),
+            fees.map((t) => t.token_contract_address),
+            v.logic_contract_address,
+            v.payload,
+            v.timeout,
+            v.invalidation_id,
+            v.invalidation_nonce,
+          ]
+        )
+      );
+      expect(checkpoint, v.description).to.equal(v.checkpoint);
+    }
+  });
+});
@@ -0,0 +1,65 @@
+import chai from "chai";
+import { ethers } from "hardhat";
+import { solidity } from "ethereum-waffle";
+import fs from "fs";
+import path from "path";
+
+import { makeCheckpoint } from "../test-utils/pure";
+
+chai.use(solidity);
+const { expect } = chai;
+
+// Generated by `gbt dev gen-fixtures --output ../spec/test-fixtures.json` and
+// committed, the Rust side checks the same file in ethereum_gravity/src/fixtures.rs
+const FIXTURES = path.join(__dirname, "../../spec/test-fixtures.json");
+
+type Signed = {
+  description: string;
+  checkpoint: string;
+  signatures: { signer: string; signature: string }[];
+};
+
+function checkSignatures(fixtures: Signed[]) {
+  for (const f of fixtures) {
+    for (const s of f.signatures) {
+      const signer = ethers.utils.verifyMessage(
+        ethers.utils.arrayify(f.checkpoint),
+        s.signature
+      );
+      expect(signer, f.description).to.equal(ethers.utils.getAddress(s.signer));
+    }
+  }
+}
+
+describe("Generated fixtures", function () {
+  let fixtures: any;
+
+  before(function () {
+    if (!fs.existsSync(FIXTURES)) {
+      throw new Error(
+        `${FIXTURES} not found, run gbt dev gen-fixtures to generate it`
+      );
+    }
+    fixtures = JSON.parse(fs.readFileSync(FIXTURES, "utf8"));
+  });
+
+  it("Valset checkpoints match", function () {
+    for (const v of fixtures.valsets) {
+      const checkpoint = makeCheckpoint(
+        v.members.map((m: any) => m.eth_address),
+        v.members.map((m: any) => m.power),
+        v.nonce,
+        v.reward_amount,
+        v.reward_token,
+        ethers.utils.formatBytes32String(v.gravity_id)
+      );
+      expect(checkpoint, v.description).to.equal(v.checkpoint);
+    }
+  });
+
+  it("Signatures recover to the test keys", function () {
+    checkSignatures(fixtures.valsets);
+    checkSignatures(fixtures.batches);
+    checkSignatures(fixtures.logic_calls);
+  });
+});
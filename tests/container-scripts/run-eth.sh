@@ -15,6 +15,7 @@ geth --identity "GravityTestnet" --nodiscover \
 --http.addr="0.0.0.0" \
 --http.vhosts="*" \
 --http.corsdomain="*" \
+--http.api="eth,net,web3,debug,txpool" \
 --miner.threads=1 \
 --nousb \
 --verbosity=5 \
@@ -0,0 +1,57 @@
+#!/bin/bash
+set -eux
+# Number of validators to start
+NODES=$1
+# old binary version to run
+OLD_VERSION=$2
+
+echo "Downloading old gravity version at https://github.com/Gravity-Bridge/Gravity-Bridge/releases/download/${OLD_VERSION}/gravity-linux-amd64"
+wget https://github.com/Gravity-Bridge/Gravity-Bridge/releases/download/${OLD_VERSION}/gravity-linux-amd64
+mv gravity-linux-amd64 oldgravity
+# Make old gravity executable
+chmod +x oldgravity
+
+export OLD_BINARY_LOCATION=/oldgravity
+
+# Prepare the contracts for later deployment
+pushd /gravity/solidity/
+HUSKY_SKIP_INSTALL=1 npm install
+npm run typechain
+
+cd /gravity/module/
+export PATH=$PATH:/usr/local/go/bin
+make
+make install
+cd /gravity/
+tests/container-scripts/setup-validators.sh $NODES
+tests/container-scripts/setup-ibc-validators.sh $NODES
+
+# Run the old binary
+tests/container-scripts/run-testnet.sh $NODES
+
+# deploy the ethereum contracts
+pushd /gravity/orchestrator/test_runner
+DEPLOY_CONTRACTS=1 RUST_BACKTRACE=full NO_GAS_OPT=1 RUST_LOG="INFO,relayer=DEBUG,orchestrator=DEBUG" PATH=$PATH:$HOME/.cargo/bin cargo run --release --bin test-runner
+popd
+
+# Run the in flight upgrade test in the background, it creates bridge activity,
+# passes the upgrade proposal and writes the marker file once the chain halts
+rm -f /tmp/upgrade-halted
+tests/container-scripts/integration-tests.sh $NODES UPGRADE_IN_FLIGHT &
+TEST_PID=$!
+
+while [[ ! -f /tmp/upgrade-halted ]]; do
+    if ! kill -0 $TEST_PID 2>/dev/null; then
+        echo "Test runner exited before the chain halted"
+        wait $TEST_PID
+        exit 1
+    fi
+    sleep 5
+done
+
+unset OLD_BINARY_LOCATION
+# Run the new binary, the test runner and its orchestrators keep running
+pkill gravity || true # allowed to fail
+tests/container-scripts/run-testnet.sh $NODES
+
+wait $TEST_PID
@@ -18,6 +18,7 @@ bash all-up-test.sh INVALID_EVENTS
 bash all-up-test.sh UNHALT_BRIDGE
 bash all-up-test.sh PAUSE_BRIDGE
 bash all-up-test.sh DEPOSIT_OVERFLOW
+bash all-up-test.sh HOSTILE_ERC20
 bash all-up-test.sh ETHEREUM_BLACKLIST
 bash all-up-test.sh AIRDROP_PROPOSAL
 bash all-up-test.sh SIGNATURE_SLASHING
@@ -25,10 +26,13 @@ bash all-up-test.sh SLASHING_DELEGATION
 bash all-up-test.sh IBC_METADATA
 bash all-up-test.sh ERC721_HAPPY_PATH
 bash all-up-test.sh IBC_AUTO_FORWARD
+bash all-up-test.sh IBC_AUTO_FORWARD_E2E
 bash all-up-test.sh ETHEREUM_KEYS
 bash all-up-test.sh BATCH_TIMEOUT
 bash all-up-test.sh VESTING
 bash all-up-test.sh SEND_TO_ETH_FEES
+bash all-up-test.sh CHAOS
+bash all-up-test.sh ROUND_TRIP
 if [ ! -z "$ALCHEMY_ID" ]; then
     bash all-up-test.sh RELAY_MARKET $ALCHEMY_ID
     bash all-up-test.sh ARBITRARY_LOGIC $ALCHEMY_ID
@@ -1,5 +1,7 @@
 #!/bin/bash
 OLD_VERSION=$1
+# optionally IN_FLIGHT, to upgrade while bridge activity is in flight
+SCENARIO=${2:-}
 set -eux
 
 if [[ -z "${OLD_VERSION}" ]]; then
@@ -32,6 +34,11 @@ if [[ "$OSTYPE" == "darwin"* ]]; then
        PLATFORM_CMD="--platform=linux/amd64"; fi
 fi
 
+INTERNAL_SCRIPT=upgrade-test-internal.sh
+if [[ "$SCENARIO" == "IN_FLIGHT" ]]; then
+    INTERNAL_SCRIPT=upgrade-in-flight-test-internal.sh
+fi
+
 # Run new test container instance
 PORTS="-p 9090:9090 -p 26657:26657 -p 1317:1317 -p 8545:8545"
-docker run --name gravity_all_up_test_instance $PLATFORM_CMD --cap-add=NET_ADMIN $PORTS gravity-base /bin/bash /gravity/tests/container-scripts/upgrade-test-internal.sh $NODES $OLD_VERSION
+docker run --name gravity_all_up_test_instance $PLATFORM_CMD --cap-add=NET_ADMIN $PORTS gravity-base /bin/bash /gravity/tests/container-scripts/$INTERNAL_SCRIPT $NODES $OLD_VERSION